@@ -1,10 +1,20 @@
+use std::{env, fmt::Write, fs, path::PathBuf};
+
+use prost::Message;
+use prost_types::{field_descriptor_proto::Type, FileDescriptorSet};
+
 const PROTO_DIR: &str = "../../proto";
+const EVENTS_PACKAGE: &str = "scuffle.events";
 
 fn main() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let descriptor_path = out_dir.join("descriptors.bin");
+
     let mut config = prost_build::Config::new();
 
     config.protoc_arg("--experimental_allow_proto3_optional");
     config.bytes(["."]);
+    config.file_descriptor_set_path(&descriptor_path);
 
     tonic_build::configure()
         .compile_with_config(
@@ -13,9 +23,166 @@ fn main() {
                 format!("{}/scuffle/events/ingest.proto", PROTO_DIR),
                 format!("{}/scuffle/events/api.proto", PROTO_DIR),
                 format!("{}/scuffle/backend/api.proto", PROTO_DIR),
+                format!("{}/scuffle/backend/admin.proto", PROTO_DIR),
+                format!("{}/scuffle/video/ingest.proto", PROTO_DIR),
                 format!("{}/scuffle/utils/health.proto", PROTO_DIR),
             ],
             &[PROTO_DIR],
         )
         .unwrap();
+
+    let descriptors =
+        FileDescriptorSet::decode(fs::read(&descriptor_path).unwrap().as_slice()).unwrap();
+
+    let (subjects, gql) = generate_events(&descriptors);
+    fs::write(out_dir.join("scuffle.events.subjects.rs"), subjects).unwrap();
+    fs::write(out_dir.join("scuffle.events.gql.rs"), gql).unwrap();
+}
+
+/// The comment lines of a message or field, and the values of its `@name value` annotations.
+struct Comment {
+    doc: Vec<String>,
+    annotations: Vec<(String, String)>,
+}
+
+impl Comment {
+    fn parse(comment: &str) -> Self {
+        let mut doc = Vec::new();
+        let mut annotations = Vec::new();
+
+        for line in comment.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            match line.strip_prefix('@') {
+                Some(annotation) => {
+                    let (name, value) = annotation.split_once(' ').unwrap_or((annotation, ""));
+                    annotations.push((name.to_string(), value.trim().to_string()));
+                }
+                None => doc.push(line.to_string()),
+            }
+        }
+
+        Self { doc, annotations }
+    }
+
+    fn annotation(&self, name: &str) -> Option<&str> {
+        self.annotations
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Generates the subjects of the events marked with `@subject`, and a GQL object for the events marked with `@gql`.
+/// The GQL object has the fields of the event in the same order, with the field comments as descriptions
+/// and `id` or `*_id` strings parsed as UUIDs, so the two can't drift apart.
+fn generate_events(descriptors: &FileDescriptorSet) -> (String, String) {
+    let mut subjects = String::new();
+    let mut gql = String::new();
+
+    for file in descriptors
+        .file
+        .iter()
+        .filter(|f| f.package() == EVENTS_PACKAGE)
+    {
+        let comments = file
+            .source_code_info
+            .as_ref()
+            .map(|info| info.location.as_slice())
+            .unwrap_or_default();
+
+        let comment = |path: &[i32]| {
+            Comment::parse(
+                comments
+                    .iter()
+                    .find(|l| l.path == path)
+                    .and_then(|l| l.leading_comments.as_deref())
+                    .unwrap_or_default(),
+            )
+        };
+
+        for (i, message) in file.message_type.iter().enumerate() {
+            // 4 is the number of `message_type` in FileDescriptorProto, 2 the one of `field` in DescriptorProto.
+            let message_comment = comment(&[4, i as i32]);
+            let name = message.name();
+
+            if let Some(subject) = message_comment.annotation("subject") {
+                assert!(
+                    subject.matches("{}").count() == 1,
+                    "the subject of {} must have exactly one {{}}",
+                    name
+                );
+
+                writeln!(
+                    subjects,
+                    "impl crate::pb::Event for {} {{\n    const SUBJECT: &'static str = {:?};\n}}\n",
+                    name, subject
+                )
+                .unwrap();
+            }
+
+            let Some(gql_name) = message_comment.annotation("gql") else {
+                continue;
+            };
+
+            let mut fields = String::new();
+            let mut conversions = String::new();
+
+            for (j, field) in message.field.iter().enumerate() {
+                let field_name = field.name();
+                let is_id = field_name == "id" || field_name.ends_with("_id");
+
+                let ty = match field.r#type() {
+                    Type::String if is_id => "uuid::Uuid",
+                    Type::String => "String",
+                    Type::Bool => "bool",
+                    Type::Int64 => "i64",
+                    Type::Int32 => "i32",
+                    Type::Uint32 => "u32",
+                    ty => panic!(
+                        "{}.{} has type {:?}, which can't be generated as a GQL field",
+                        name, field_name, ty
+                    ),
+                };
+
+                for line in comment(&[4, i as i32, 2, j as i32]).doc {
+                    writeln!(fields, "    #[doc = {:?}]", line).unwrap();
+                }
+                writeln!(fields, "    pub {}: {},", field_name, ty).unwrap();
+
+                if is_id {
+                    writeln!(
+                        conversions,
+                        "            {0}: event.{0}.parse()?,",
+                        field_name
+                    )
+                    .unwrap();
+                } else {
+                    writeln!(conversions, "            {0}: event.{0},", field_name).unwrap();
+                }
+            }
+
+            for line in &message_comment.doc {
+                writeln!(gql, "#[doc = {:?}]", line).unwrap();
+            }
+
+            writeln!(
+                gql,
+                "#[derive(async_graphql::SimpleObject, Clone, Debug, PartialEq)]
+pub struct {gql_name} {{
+{fields}}}
+
+impl TryFrom<crate::pb::scuffle::events::{name}> for {gql_name} {{
+    type Error = uuid::Error;
+
+    fn try_from(event: crate::pb::scuffle::events::{name}) -> Result<Self, Self::Error> {{
+        Ok(Self {{
+{conversions}        }})
+    }}
+}}
+"
+            )
+            .unwrap();
+        }
+    }
+
+    (subjects, gql)
 }
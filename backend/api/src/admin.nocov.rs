@@ -0,0 +1,266 @@
+#![allow(dead_code)]
+
+mod pb;
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{TimeZone, Utc};
+use clap::{Arg, ArgMatches, Command};
+use common::grpc::{make_channel, TlsSettings};
+use tonic::transport::{Certificate, Identity};
+
+use pb::scuffle::backend::{
+    admin_client::AdminClient, CreateUserRequest, DeadLetterConsumer, EndLiveStreamsRequest,
+    GrantRoleRequest, ListLiveStreamsRequest, ReconcileStreamSessionsRequest,
+    ResetStreamKeyRequest, RetryDeadLettersRequest,
+};
+
+fn command() -> Command {
+    let username = || Arg::new("username").required(true);
+
+    Command::new("scuffle-admin")
+        .about("Runs operator tasks against the admin gRPC API of the Scuffle API")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("address")
+                .long("address")
+                .global(true)
+                .default_value("localhost:50051")
+                .help("The address of the gRPC server of the API"),
+        )
+        .arg(
+            Arg::new("tls-cert")
+                .long("tls-cert")
+                .global(true)
+                .requires_all(["tls-key", "tls-ca-cert"])
+                .help("The client certificate, if the gRPC server uses TLS"),
+        )
+        .arg(Arg::new("tls-key").long("tls-key").global(true))
+        .arg(Arg::new("tls-ca-cert").long("tls-ca-cert").global(true))
+        .arg(
+            Arg::new("tls-domain")
+                .long("tls-domain")
+                .global(true)
+                .default_value("localhost")
+                .help("The domain on the certificate of the gRPC server"),
+        )
+        .subcommand(
+            Command::new("create-user")
+                .about("Create a user with a verified email")
+                .arg(username())
+                .arg(Arg::new("email").required(true))
+                .arg(Arg::new("password").long("password").required(true)),
+        )
+        .subcommand(
+            Command::new("grant-role")
+                .about("Grant a global role to a user")
+                .arg(username())
+                .arg(Arg::new("role").required(true)),
+        )
+        .subcommand(
+            Command::new("reset-stream-key")
+                .about("Give a channel a new stream key")
+                .arg(username()),
+        )
+        .subcommand(
+            Command::new("end-streams")
+                .about("End the live streams of a channel")
+                .arg(username()),
+        )
+        .subcommand(
+            Command::new("streams")
+                .about("List the live streams and their pipeline state")
+                .arg(Arg::new("username").long("channel")),
+        )
+        .subcommand(
+            Command::new("retry-dead-letters")
+                .about("Publish the pending dead letters again")
+                .arg(Arg::new("consumer").long("consumer").value_parser([
+                    "notifications",
+                    "moderation",
+                    "import",
+                    "image-processor-results",
+                    "data-export",
+                    "vod-download-results",
+                    "vod-caption-results",
+                ])),
+        )
+        .subcommand(
+            Command::new("reconcile")
+                .about("Backfill and repair the summaries of ended streams")
+                .arg(
+                    Arg::new("days")
+                        .long("days")
+                        .value_parser(clap::value_parser!(u32))
+                        .help("How many days back to check, the configured lookback by default"),
+                ),
+        )
+}
+
+fn arg(matches: &ArgMatches, name: &str) -> String {
+    matches.get_one::<String>(name).cloned().unwrap_or_default()
+}
+
+fn tls(matches: &ArgMatches) -> Result<Option<TlsSettings>> {
+    let Some(cert) = matches.get_one::<String>("tls-cert") else {
+        return Ok(None);
+    };
+
+    let cert = std::fs::read(cert).context("failed to read tls cert")?;
+    let key = std::fs::read(arg(matches, "tls-key")).context("failed to read tls key")?;
+    let ca_cert =
+        std::fs::read(arg(matches, "tls-ca-cert")).context("failed to read tls ca cert")?;
+
+    Ok(Some(TlsSettings {
+        domain: arg(matches, "tls-domain"),
+        identity: Identity::from_pem(cert, key),
+        ca_cert: Certificate::from_pem(ca_cert),
+    }))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let matches = command().get_matches();
+
+    let channel = make_channel(
+        vec![arg(&matches, "address")],
+        Duration::from_secs(30),
+        tls(&matches)?,
+    )?;
+    let mut client = AdminClient::new(channel);
+
+    match matches.subcommand() {
+        Some(("create-user", m)) => {
+            let resp = client
+                .create_user(CreateUserRequest {
+                    username: arg(m, "username"),
+                    email: arg(m, "email"),
+                    password: arg(m, "password"),
+                })
+                .await?
+                .into_inner();
+
+            println!("created user {}", resp.user_id);
+            println!("stream key: {}", resp.stream_key);
+        }
+        Some(("grant-role", m)) => {
+            let resp = client
+                .grant_role(GrantRoleRequest {
+                    username: arg(m, "username"),
+                    role: arg(m, "role"),
+                })
+                .await?
+                .into_inner();
+
+            if resp.granted {
+                println!("granted the role");
+            } else {
+                println!("the user already has the role");
+            }
+        }
+        Some(("reset-stream-key", m)) => {
+            let resp = client
+                .reset_stream_key(ResetStreamKeyRequest {
+                    username: arg(m, "username"),
+                })
+                .await?
+                .into_inner();
+
+            println!("stream key: {}", resp.stream_key);
+        }
+        Some(("end-streams", m)) => {
+            let resp = client
+                .end_live_streams(EndLiveStreamsRequest {
+                    username: arg(m, "username"),
+                })
+                .await?
+                .into_inner();
+
+            println!("ending {} live streams", resp.streams);
+        }
+        Some(("streams", m)) => {
+            let resp = client
+                .list_live_streams(ListLiveStreamsRequest {
+                    username: m.get_one::<String>("username").cloned(),
+                })
+                .await?
+                .into_inner();
+
+            if resp.streams.is_empty() {
+                println!("no live streams");
+            }
+
+            for stream in resp.streams {
+                let format_time = |t: i64| {
+                    Utc.timestamp_opt(t, 0)
+                        .single()
+                        .map(|t| t.to_rfc3339())
+                        .unwrap_or_default()
+                };
+
+                println!("{} ({})", stream.stream_id, stream.username);
+                println!("  title:       {}", stream.title);
+                println!("  state:       {}", stream.ready_state);
+                println!(
+                    "  ingest:      {} ({})",
+                    stream.ingest_address, stream.connection_id
+                );
+                println!(
+                    "  transcoded:  {}, recorded: {}",
+                    stream.transcoded, stream.recorded
+                );
+                println!("  started:     {}", format_time(stream.created_at));
+                if let Some(updated_at) = stream.updated_at {
+                    println!("  last update: {}", format_time(updated_at));
+                }
+                match stream.state {
+                    Some(state) => println!("  variants:    {}", state.variants.len()),
+                    None => println!("  variants:    none published yet"),
+                }
+            }
+        }
+        Some(("retry-dead-letters", m)) => {
+            let consumer =
+                m.get_one::<String>("consumer")
+                    .map(|consumer| match consumer.as_str() {
+                        "moderation" => DeadLetterConsumer::Moderation,
+                        "import" => DeadLetterConsumer::Import,
+                        "image-processor-results" => DeadLetterConsumer::ImageProcessorResults,
+                        "data-export" => DeadLetterConsumer::DataExport,
+                        "vod-download-results" => DeadLetterConsumer::VodDownloadResults,
+                        "vod-caption-results" => DeadLetterConsumer::VodCaptionResults,
+                        _ => DeadLetterConsumer::Notifications,
+                    });
+
+            let resp = client
+                .retry_dead_letters(RetryDeadLettersRequest {
+                    consumer: consumer.map(|c| c as i32),
+                })
+                .await?
+                .into_inner();
+
+            println!("retried {} dead letters", resp.retried);
+            if resp.failed > 0 {
+                println!(
+                    "{} could not be published and are still pending",
+                    resp.failed
+                );
+            }
+        }
+        Some(("reconcile", m)) => {
+            let resp = client
+                .reconcile_stream_sessions(ReconcileStreamSessionsRequest {
+                    lookback_days: m.get_one::<u32>("days").copied(),
+                })
+                .await?
+                .into_inner();
+
+            println!("backfilled {} stream sessions", resp.backfilled_sessions);
+            println!("corrected {} stream sessions", resp.corrected_sessions);
+        }
+        _ => unreachable!("a subcommand is required"),
+    }
+
+    Ok(())
+}
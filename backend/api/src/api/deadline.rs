@@ -0,0 +1,50 @@
+use std::{future::Future, time::Duration};
+
+use hyper::HeaderMap;
+use tokio::time::{error::Elapsed, Instant};
+
+use crate::config::ApiConfig;
+
+/// The header clients can shorten the deadline of a request with, in milliseconds.
+pub const X_REQUEST_TIMEOUT: &str = "x-request-timeout";
+
+/// How long calls to other services may take when there is no request deadline which is sooner.
+pub const DOWNSTREAM_TIMEOUT: Duration = Duration::from_secs(10);
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// The time a request may take. Clients can ask for less than the configured timeout, but not for more.
+pub fn budget(config: &ApiConfig, headers: &HeaderMap) -> Duration {
+    let max = Duration::from_secs(config.request_timeout as u64);
+
+    headers
+        .get(X_REQUEST_TIMEOUT)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(|ms| Duration::from_millis(ms).min(max))
+        .unwrap_or(max)
+}
+
+/// Runs a request until its deadline. Once it runs out the future is dropped, which cancels the queries
+/// and calls it is waiting on and returns their connections to the pool.
+pub async fn run<F: Future>(budget: Duration, fut: F) -> Result<F::Output, Elapsed> {
+    let deadline = Instant::now() + budget;
+
+    DEADLINE
+        .scope(deadline, tokio::time::timeout_at(deadline, fut))
+        .await
+}
+
+/// The time left until the deadline of the request being handled, if there is one.
+pub fn remaining() -> Option<Duration> {
+    DEADLINE
+        .try_with(|deadline| deadline.saturating_duration_since(Instant::now()))
+        .ok()
+}
+
+/// The timeout of a call to another service, shortened to the deadline of the request it is made for.
+pub fn timeout(default: Duration) -> Duration {
+    remaining().map_or(default, |remaining| remaining.min(default))
+}
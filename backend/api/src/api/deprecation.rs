@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use hyper::Method;
+use uuid::Uuid;
+
+use crate::{
+    config::{DeprecatedSurfaceConfig, DeprecationConfig},
+    database::deprecated_usage,
+    global::GlobalState,
+};
+
+/// A deprecated GraphQL field or REST endpoint, as announced in the changelog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deprecation {
+    pub surface: String,
+    pub deprecated_at: DateTime<Utc>,
+    pub sunset: Option<DateTime<Utc>>,
+    pub replacement: Option<String>,
+}
+
+fn parse_day(day: &str) -> Option<DateTime<Utc>> {
+    let day = NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?;
+    Some(Utc.from_utc_datetime(&day.and_hms_opt(0, 0, 0)?))
+}
+
+impl Deprecation {
+    /// None if one of the days is not written as YYYY-MM-DD.
+    pub fn from_config(config: &DeprecatedSurfaceConfig) -> Option<Self> {
+        let sunset = match &config.sunset {
+            Some(sunset) => Some(parse_day(sunset)?),
+            None => None,
+        };
+
+        Some(Self {
+            surface: config.surface.clone(),
+            deprecated_at: parse_day(&config.deprecated_at)?,
+            sunset,
+            replacement: config.replacement.clone(),
+        })
+    }
+}
+
+/// A misconfigured deprecation is logged and ignored, it should not break the field it describes.
+fn find(config: &DeprecationConfig, matches: impl Fn(&str) -> bool) -> Option<Deprecation> {
+    let surface = config.surfaces.iter().find(|s| matches(&s.surface))?;
+
+    let deprecation = Deprecation::from_config(surface);
+    if deprecation.is_none() {
+        tracing::warn!(surface = %surface.surface, "invalid deprecation dates");
+    }
+
+    deprecation
+}
+
+/// Finds the deprecation of a GraphQL field, configured as `Type.field`.
+pub fn find_field(
+    config: &DeprecationConfig,
+    parent_type: &str,
+    field: &str,
+) -> Option<Deprecation> {
+    find(config, |surface| {
+        surface.split_once('.') == Some((parent_type, field))
+    })
+}
+
+/// Finds the deprecation of a REST endpoint, configured as `METHOD /v1/path`.
+pub fn find_endpoint(
+    config: &DeprecationConfig,
+    method: &Method,
+    path: &str,
+) -> Option<Deprecation> {
+    find(config, |surface| {
+        surface
+            .split_once(' ')
+            .map(|(m, pattern)| m == method.as_str() && path_matches(pattern, path))
+            .unwrap_or(false)
+    })
+}
+
+/// Path parameters like `:channel_id` match any single segment, the same way the router reads them.
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_end_matches('/').split('/');
+    let path = path.trim_end_matches('/').split('/');
+
+    pattern.clone().count() == path.clone().count()
+        && pattern
+            .zip(path)
+            .all(|(p, s)| p == s || (p.starts_with(':') && !s.is_empty()))
+}
+
+/// The response headers announcing deprecations, the Deprecation header from RFC 9745 and the Sunset header from RFC 8594.
+/// When a response uses several deprecated surfaces the earliest days are announced, since the first removal is the one to prepare for.
+pub fn headers(
+    config: &DeprecationConfig,
+    deprecations: &[Deprecation],
+) -> Vec<(&'static str, String)> {
+    let Some(deprecated_at) = deprecations.iter().map(|d| d.deprecated_at).min() else {
+        return Vec::new();
+    };
+
+    let mut headers = vec![("Deprecation", format!("@{}", deprecated_at.timestamp()))];
+
+    if let Some(sunset) = deprecations.iter().filter_map(|d| d.sunset).min() {
+        headers.push((
+            "Sunset",
+            sunset.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        ));
+    }
+
+    if !config.changelog_url.is_empty() {
+        headers.push((
+            "Link",
+            format!("<{}>; rel=\"deprecation\"", config.changelog_url),
+        ));
+    }
+
+    headers
+}
+
+/// The client which used a deprecated surface, so its owner can be asked to migrate before the removal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Client {
+    AccessToken { id: Uuid, user_id: Uuid },
+    Session { id: Uuid, user_id: Uuid },
+    Anonymous,
+}
+
+impl Client {
+    pub fn key(&self) -> String {
+        match self {
+            Self::AccessToken { id, .. } => format!("token:{}", id),
+            Self::Session { id, .. } => format!("session:{}", id),
+            Self::Anonymous => "anonymous".to_string(),
+        }
+    }
+
+    pub fn user_id(&self) -> Option<Uuid> {
+        match self {
+            Self::AccessToken { user_id, .. } | Self::Session { user_id, .. } => Some(*user_id),
+            Self::Anonymous => None,
+        }
+    }
+}
+
+/// Counts the use of deprecated surfaces. Failing to count a use is logged, the request itself still succeeds.
+pub async fn record(global: &Arc<GlobalState>, deprecations: &[Deprecation], client: Client) {
+    for deprecation in deprecations {
+        if let Err(e) = deprecated_usage::record(
+            &global.db,
+            &deprecation.surface,
+            &client.key(),
+            client.user_id(),
+        )
+        .await
+        {
+            tracing::warn!(
+                surface = %deprecation.surface,
+                "failed to record deprecated usage: {}",
+                e
+            );
+        }
+    }
+}
@@ -6,6 +6,7 @@ use routerify::{prelude::RequestExt, Middleware};
 
 use crate::api::error::{ResultExt, RouteError};
 use crate::api::ext::RequestExt as _;
+use crate::api::middleware::csrf;
 use crate::api::middleware::response_headers::RequestExt as _;
 use crate::api::v1::jwt::JwtState;
 use crate::global::GlobalState;
@@ -78,21 +79,42 @@ pub fn auth_middleware(_: &Arc<GlobalState>) -> Middleware<Body, RouteError> {
             .map(AuthTokenCheck::from)
             .unwrap_or_default();
 
-        let Some(token) = req.headers().get(header::AUTHORIZATION) else {
-            fail_fast!(mode, req);
-        };
-
         let global = req.get_global()?;
-        let Ok(token) = token.to_str() else {
-            fail_fast!(mode, req);
-        };
 
-        // Token's will start with "Bearer " so we need to remove that
-        let Some(token) = token.strip_prefix("Bearer ") else {
-            fail_fast!(mode, req);
+        let token = match req.headers().get(header::AUTHORIZATION) {
+            Some(token) => {
+                let Ok(token) = token.to_str() else {
+                    fail_fast!(mode, req);
+                };
+
+                // Token's will start with "Bearer " so we need to remove that
+                let Some(token) = token.strip_prefix("Bearer ") else {
+                    fail_fast!(mode, req);
+                };
+
+                token.to_string()
+            }
+            None if global.config.cookie_auth.enabled => {
+                let Some(token) =
+                    csrf::cookie(req.headers(), &global.config.cookie_auth.session_cookie)
+                else {
+                    fail_fast!(mode, req);
+                };
+
+                // Browsers attach the cookie to cross-site requests too, so they have to prove they come from the website.
+                csrf::verify(&global.config.cookie_auth, req.method(), req.headers())
+                    .map_err(|e| RouteError::from((StatusCode::FORBIDDEN, e)))?;
+
+                req.set_context(csrf::CookieSession);
+
+                token.to_string()
+            }
+            None => {
+                fail_fast!(mode, req);
+            }
         };
 
-        let Some(jwt) = JwtState::verify(&global, token) else {
+        let Some(jwt) = JwtState::verify(&global, &token) else {
             fail_fast!(mode, req);
         };
 
@@ -116,6 +138,11 @@ pub fn auth_middleware(_: &Arc<GlobalState>) -> Middleware<Body, RouteError> {
             .map_err_route("failed to fetch user permissions")?
             .unwrap_or_default();
 
+        // Any authenticated request counts as activity for the presence shown to friends.
+        if let Err(e) = global.touch_presence(session.user_id, None).await {
+            tracing::warn!("failed to update presence: {}", e);
+        }
+
         req.set_response_header(X_AUTH_TOKEN_CHECK_STATUS, AuthTokenCheckStatus::Success);
         req.set_context((session, permissions));
 
@@ -5,21 +5,49 @@ use hyper::Body;
 use routerify::Middleware;
 
 use crate::api::error::RouteError;
+use crate::api::middleware::csrf;
 use crate::global::GlobalState;
 
-pub fn cors_middleware(_: &Arc<GlobalState>) -> Middleware<Body, RouteError> {
-    Middleware::post(|mut resp| async move {
-        resp.headers_mut()
-            .insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*".parse().unwrap());
-        resp.headers_mut().insert(
-            header::ACCESS_CONTROL_ALLOW_METHODS,
-            "GET, POST, OPTIONS".parse().unwrap(),
-        );
-        resp.headers_mut().insert(
-            header::ACCESS_CONTROL_ALLOW_HEADERS,
-            "Content-Type, Authorization".parse().unwrap(),
-        );
+pub fn cors_middleware(global: &Arc<GlobalState>) -> Middleware<Body, RouteError> {
+    let cookie_auth = global.config.cookie_auth.clone();
 
-        Ok(resp)
+    Middleware::post_with_info(move |mut resp, info| {
+        let cookie_auth = cookie_auth.clone();
+        async move {
+            // Browsers only send cookies to origins which name them explicitly, "*" is not enough.
+            let origin = csrf::request_origin(info.headers()).filter(|origin| {
+                cookie_auth.enabled && csrf::is_allowed_origin(&cookie_auth, origin)
+            });
+
+            match origin {
+                Some(origin) => {
+                    resp.headers_mut()
+                        .insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.parse().unwrap());
+                    resp.headers_mut().insert(
+                        header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                        "true".parse().unwrap(),
+                    );
+                    resp.headers_mut()
+                        .insert(header::VARY, "Origin".parse().unwrap());
+                }
+                None => {
+                    resp.headers_mut()
+                        .insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*".parse().unwrap());
+                }
+            }
+
+            resp.headers_mut().insert(
+                header::ACCESS_CONTROL_ALLOW_METHODS,
+                "GET, POST, OPTIONS".parse().unwrap(),
+            );
+            resp.headers_mut().insert(
+                header::ACCESS_CONTROL_ALLOW_HEADERS,
+                "Content-Type, Authorization, X-CSRF-Token, X-Request-Timeout"
+                    .parse()
+                    .unwrap(),
+            );
+
+            Ok(resp)
+        }
     })
 }
@@ -0,0 +1,125 @@
+use hyper::{header, HeaderMap, Method};
+use rand::Rng;
+use ring::constant_time;
+
+use crate::config::CookieAuthConfig;
+
+/// The header the website echoes the CSRF cookie in.
+pub const X_CSRF_TOKEN: &str = "X-CSRF-Token";
+
+/// Marks a request which was authenticated with the session cookie instead of the Authorization header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CookieSession;
+
+/// Returns the value of a cookie sent with the request.
+pub fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// A Set-Cookie header value. The session cookie is HttpOnly, the CSRF cookie can't be, the website has to read it.
+fn set_cookie(
+    config: &CookieAuthConfig,
+    name: &str,
+    value: &str,
+    max_age: i64,
+    http_only: bool,
+) -> String {
+    let mut cookie = format!(
+        "{}={}; Path=/; Max-Age={}; Secure; SameSite=Strict",
+        name, value, max_age
+    );
+
+    if http_only {
+        cookie.push_str("; HttpOnly");
+    }
+
+    if !config.domain.is_empty() {
+        cookie.push_str(&format!("; Domain={}", config.domain));
+    }
+
+    cookie
+}
+
+/// The cookies which log a browser in with a session token, and the CSRF token it echoes from then on.
+/// Both last as long as the session.
+pub fn login_cookies(config: &CookieAuthConfig, session_token: &str, max_age: i64) -> [String; 2] {
+    let csrf_token = rand::thread_rng()
+        .sample_iter(rand::distributions::Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect::<String>();
+
+    [
+        set_cookie(config, &config.session_cookie, session_token, max_age, true),
+        set_cookie(config, &config.csrf_cookie, &csrf_token, max_age, false),
+    ]
+}
+
+/// The cookies which log a browser out again.
+pub fn logout_cookies(config: &CookieAuthConfig) -> [String; 2] {
+    [
+        set_cookie(config, &config.session_cookie, "", 0, true),
+        set_cookie(config, &config.csrf_cookie, "", 0, false),
+    ]
+}
+
+/// The origin a request was sent from. Browsers send the Origin header with every cross-origin and
+/// every non-GET request, the Referer is only used when a proxy or an old browser stripped it.
+pub fn request_origin(headers: &HeaderMap) -> Option<String> {
+    if let Some(origin) = headers.get(header::ORIGIN).and_then(|v| v.to_str().ok()) {
+        return Some(origin.to_string());
+    }
+
+    let referer = headers.get(header::REFERER)?.to_str().ok()?;
+    let url = referer.parse::<hyper::Uri>().ok()?;
+
+    Some(format!("{}://{}", url.scheme_str()?, url.authority()?))
+}
+
+pub fn is_allowed_origin(config: &CookieAuthConfig, origin: &str) -> bool {
+    config
+        .allowed_origins
+        .iter()
+        .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+}
+
+/// Checks a cookie authenticated request. Requests from other origins are rejected, and requests which can change state
+/// also have to echo the CSRF cookie in the X-CSRF-Token header (double-submit), which a cross-site form or script cannot read.
+pub fn verify(
+    config: &CookieAuthConfig,
+    method: &Method,
+    headers: &HeaderMap,
+) -> Result<(), &'static str> {
+    let is_upgrade = headers.contains_key(header::UPGRADE);
+    let safe = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+
+    match request_origin(headers) {
+        Some(origin) if !is_allowed_origin(config, &origin) => return Err("origin not allowed"),
+        // Safe requests without an origin are top-level navigations, which can't read the response.
+        // Websockets can, and browsers always send the origin with them.
+        None if !safe || is_upgrade => return Err("missing origin"),
+        _ => {}
+    }
+
+    if safe {
+        return Ok(());
+    }
+
+    let Some(expected) = cookie(headers, &config.csrf_cookie).filter(|t| !t.is_empty()) else {
+        return Err("missing csrf cookie");
+    };
+
+    let Some(token) = headers.get(X_CSRF_TOKEN) else {
+        return Err("missing csrf token");
+    };
+
+    constant_time::verify_slices_are_equal(expected.as_bytes(), token.as_bytes())
+        .map_err(|_| "invalid csrf token")
+}
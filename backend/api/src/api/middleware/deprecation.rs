@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use hyper::{header::HeaderValue, Body, Request};
+use routerify::{prelude::RequestExt as _, Middleware};
+
+use crate::api::deprecation::{self, Client};
+use crate::api::error::RouteError;
+use crate::api::ext::RequestExt as _;
+use crate::api::middleware::response_headers::RequestExt as _;
+use crate::api::v1::control;
+use crate::database::{personal_access_token, session};
+use crate::dataloader::user_permissions::UserPermission;
+use crate::global::GlobalState;
+
+/// Logins are set by the auth middleware, access tokens are only checked by the endpoints which accept them
+/// so they are looked up here.
+async fn client(global: &Arc<GlobalState>, req: &Request<Body>) -> Client {
+    if let Some((session, _)) = req.context::<(session::Model, UserPermission)>() {
+        return Client::Session {
+            id: session.id,
+            user_id: session.user_id,
+        };
+    }
+
+    let Some(token) = control::request_token(req) else {
+        return Client::Anonymous;
+    };
+
+    let token = sqlx::query!(
+        "SELECT id, user_id FROM personal_access_tokens WHERE token_hash = $1",
+        personal_access_token::hash_token(token),
+    )
+    .fetch_optional(&*global.db)
+    .await;
+
+    match token {
+        Ok(Some(token)) => Client::AccessToken {
+            id: token.id,
+            user_id: token.user_id,
+        },
+        Ok(None) => Client::Anonymous,
+        Err(e) => {
+            tracing::warn!("failed to fetch access token: {}", e);
+            Client::Anonymous
+        }
+    }
+}
+
+/// Announces the deprecation of REST endpoints in the response headers and records who still uses them.
+pub fn deprecation_middleware(_: &Arc<GlobalState>) -> Middleware<Body, RouteError> {
+    Middleware::pre(|req| async move {
+        let global = req.get_global()?;
+
+        let Some(deprecation) =
+            deprecation::find_endpoint(&global.config.deprecations, req.method(), req.uri().path())
+        else {
+            return Ok(req);
+        };
+
+        let deprecations = [deprecation];
+        for (name, value) in deprecation::headers(&global.config.deprecations, &deprecations) {
+            match value.parse::<HeaderValue>() {
+                Ok(value) => req.set_response_header(name, value),
+                Err(_) => tracing::warn!(header = name, "invalid deprecation header value"),
+            }
+        }
+
+        let client = client(&global, &req).await;
+        deprecation::record(&global, &deprecations, client).await;
+
+        Ok(req)
+    })
+}
@@ -1,3 +1,6 @@
 pub mod auth;
 pub mod cors;
+pub mod csrf;
+pub mod deprecation;
 pub mod response_headers;
+pub mod security_headers;
@@ -0,0 +1,28 @@
+use std::sync::Arc;
+
+use hyper::Body;
+use routerify::Middleware;
+
+use crate::api::error::RouteError;
+use crate::global::GlobalState;
+
+pub fn security_headers_middleware(global: &Arc<GlobalState>) -> Middleware<Body, RouteError> {
+    let config = global.config.security_headers.clone();
+
+    Middleware::post_with_info(move |mut resp, info| {
+        let headers = config.headers(info.uri().path());
+        async move {
+            for (name, value) in headers {
+                // A policy from the config which is not a valid header would fail every request, so we skip it instead.
+                match value.parse() {
+                    Ok(value) => {
+                        resp.headers_mut().insert(name, value);
+                    }
+                    Err(_) => tracing::warn!(header = name, "invalid security header value"),
+                }
+            }
+
+            Ok(resp)
+        }
+    })
+}
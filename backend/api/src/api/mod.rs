@@ -9,11 +9,14 @@ use crate::{api::macros::make_response, global::GlobalState};
 
 use self::error::{RouteError, ShouldLog};
 
+pub mod deadline;
+pub mod deprecation;
 pub mod error;
 pub mod ext;
 pub mod macros;
 pub mod middleware;
 pub mod v1;
+pub mod webhook;
 
 async fn error_handler(
     err: Box<(dyn std::error::Error + Send + Sync + 'static)>,
@@ -56,9 +59,15 @@ pub fn routes(global: &Arc<GlobalState>) -> Router<Body, RouteError> {
         .err_handler_with_info(error_handler)
         // The CORS middleware adds the CORS headers to the response
         .middleware(middleware::cors::cors_middleware(global))
+        // Security headers like the Content-Security-Policy, the policy depends on the route
+        .middleware(middleware::security_headers::security_headers_middleware(
+            global,
+        ))
         // The auth middleware checks the Authorization header, and if it's valid, it adds the user to the request extensions
         // This way, we can access the user in the handlers, this does not fail the request if the token is invalid or not present.
         .middleware(middleware::auth::auth_middleware(global))
+        // Deprecated endpoints are announced in the response headers, the client is known once the auth middleware ran.
+        .middleware(middleware::deprecation::deprecation_middleware(global))
         .scope("/v1", v1::routes(global))
         .build()
         .expect("failed to build router")
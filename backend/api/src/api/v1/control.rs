@@ -0,0 +1,449 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use hyper::{header, Body, Request, Response, StatusCode};
+use hyper_tungstenite::{
+    tungstenite::{
+        protocol::{frame::coding::CloseCode, CloseFrame},
+        Message,
+    },
+    HyperWebsocket,
+};
+use routerify::Router;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use tokio::select;
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        error::{Result, ResultExt, RouteError},
+        ext::RequestExt as _,
+        macros::make_response,
+    },
+    database::{
+        ad_break, live_stats, personal_access_token, poll, stream_marker, user, user_suspension,
+    },
+    global::GlobalState,
+    pb,
+};
+
+const MAX_MARKER_DESCRIPTION_LENGTH: usize = 140;
+
+/// The personal access token a request is made with.
+/// Websocket clients can not always set headers, so the token can also be passed as the `token` query parameter.
+pub fn request_token(req: &Request<Body>) -> Option<&str> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .or_else(|| {
+            req.uri()
+                .query()
+                .and_then(|q| q.split('&').find_map(|p| p.strip_prefix("token=")))
+        })
+        .filter(|t| t.starts_with(personal_access_token::TOKEN_PREFIX))
+}
+
+/// Finds the personal access token the request is made with and makes sure it has the control scope.
+async fn authorize(
+    global: &Arc<GlobalState>,
+    req: &Request<Body>,
+) -> Result<personal_access_token::Model> {
+    let token = request_token(req).ok_or((StatusCode::UNAUTHORIZED, "unauthorized"))?;
+
+    let token = sqlx::query_as!(
+        personal_access_token::Model,
+        "UPDATE personal_access_tokens SET last_used_at = NOW() WHERE token_hash = $1 RETURNING *",
+        personal_access_token::hash_token(token),
+    )
+    .fetch_optional(&*global.db)
+    .await
+    .map_err_route("failed to fetch access token")?
+    .ok_or((StatusCode::UNAUTHORIZED, "unauthorized"))?;
+
+    if !token.allows(personal_access_token::Scope::Control) {
+        return Err(RouteError::from((StatusCode::FORBIDDEN, "forbidden")));
+    }
+
+    if user_suspension::active(&global.db, token.user_id)
+        .await
+        .map_err_route("failed to fetch suspension")?
+        .is_some()
+    {
+        return Err(RouteError::from((
+            StatusCode::FORBIDDEN,
+            "account suspended",
+        )));
+    }
+
+    Ok(token)
+}
+
+async fn read_json<T: DeserializeOwned>(req: Request<Body>) -> Result<T> {
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .map_err_route("failed to read body")?;
+
+    serde_json::from_slice(&body)
+        .map_err(|_| RouteError::from((StatusCode::BAD_REQUEST, "invalid request body")))
+}
+
+async fn fetch_live_stats(
+    global: &Arc<GlobalState>,
+    channel_id: Uuid,
+) -> Result<live_stats::LiveStats> {
+    live_stats::live_stats(&global.db, channel_id)
+        .await
+        .map_err_route("failed to fetch live stats")
+}
+
+fn stats_json(stats: &live_stats::LiveStats) -> serde_json::Value {
+    json!({
+        "live": stats.live(),
+        "stream_id": stats.stream_id.map(|id| id.to_string()),
+        "started_at": stats.started_at.map(|t| t.to_rfc3339()),
+        "uptime_seconds": stats.started_at.map(|t| (Utc::now() - t).num_seconds()),
+        "chat_messages": stats.chat_messages,
+        "unique_chatters": stats.unique_chatters,
+        "new_follows": stats.new_follows,
+        "new_subscriptions": stats.new_subscriptions,
+        "bits": stats.bits,
+    })
+}
+
+/// Returns the live stats of the token owner's current stream.
+async fn stats(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+    let token = authorize(&global, &req).await?;
+
+    let stats = fetch_live_stats(&global, token.user_id).await?;
+
+    Ok(make_response!(StatusCode::OK, stats_json(&stats)))
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdateStream {
+    title: Option<String>,
+    category: Option<String>,
+}
+
+/// Changes the title and / or category of the stream. A running stream gets the new title right away.
+async fn update_stream(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+    let token = authorize(&global, &req).await?;
+    let body: UpdateStream = read_json(req).await?;
+
+    if body
+        .title
+        .as_ref()
+        .map(|t| t.chars().count())
+        .unwrap_or_default()
+        > user::MAX_STREAM_TITLE_LENGTH
+    {
+        return Err(RouteError::from((
+            StatusCode::BAD_REQUEST,
+            "title must be at most 255 characters long",
+        )));
+    }
+
+    if body
+        .category
+        .as_ref()
+        .map(|c| c.chars().count())
+        .unwrap_or_default()
+        > user::MAX_STREAM_CATEGORY_LENGTH
+    {
+        return Err(RouteError::from((
+            StatusCode::BAD_REQUEST,
+            "category must be at most 64 characters long",
+        )));
+    }
+
+    let info = global
+        .update_stream_info(
+            token.user_id,
+            body.title.as_deref(),
+            body.category.as_deref(),
+            None,
+        )
+        .await
+        .map_err_route("failed to update stream")?;
+
+    Ok(make_response!(
+        StatusCode::OK,
+        json!({
+            "success": true,
+            "title": info.title,
+            "category": info.category,
+        })
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateMarker {
+    #[serde(default)]
+    description: String,
+}
+
+/// Marks the current position of the stream, so it can be found again in the VOD.
+async fn create_marker(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+    let token = authorize(&global, &req).await?;
+    let body: CreateMarker = read_json(req).await?;
+
+    if body.description.len() > MAX_MARKER_DESCRIPTION_LENGTH {
+        return Err(RouteError::from((
+            StatusCode::BAD_REQUEST,
+            "description must be at most 140 characters long",
+        )));
+    }
+
+    let stats = fetch_live_stats(&global, token.user_id).await?;
+    let (Some(stream_id), Some(started_at)) = (stats.stream_id, stats.started_at) else {
+        return Err(RouteError::from((
+            StatusCode::CONFLICT,
+            "channel is not live",
+        )));
+    };
+
+    let marker = sqlx::query_as!(
+        stream_marker::Model,
+        "INSERT INTO stream_markers (channel_id, stream_id, description, position_seconds) VALUES ($1, $2, $3, $4) RETURNING *",
+        token.user_id,
+        stream_id,
+        body.description,
+        (Utc::now() - started_at).num_seconds(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .map_err_route("failed to create marker")?;
+
+    Ok(make_response!(
+        StatusCode::OK,
+        json!({
+            "success": true,
+            "id": marker.id.to_string(),
+            "position_seconds": marker.position_seconds,
+        })
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct StartAdBreak {
+    duration: u32,
+}
+
+/// Starts an ad break on the live stream. Channels have to wait for the cooldown between ad breaks.
+async fn start_ad_break(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+    let token = authorize(&global, &req).await?;
+    let body: StartAdBreak = read_json(req).await?;
+
+    if !ad_break::DURATIONS.contains(&body.duration) {
+        return Err(RouteError::from((
+            StatusCode::BAD_REQUEST,
+            "duration must be one of 30, 60, 90, 120, 150 or 180 seconds",
+        )));
+    }
+
+    if !fetch_live_stats(&global, token.user_id).await?.live() {
+        return Err(RouteError::from((
+            StatusCode::CONFLICT,
+            "channel is not live",
+        )));
+    }
+
+    let last = ad_break::last(&global.db, token.user_id)
+        .await
+        .map_err_route("failed to fetch last ad break")?;
+    if let Some(last) = last {
+        let next_allowed_at = last.next_allowed_at(chrono::Duration::seconds(
+            global.config.control.ad_cooldown as i64,
+        ));
+        if next_allowed_at > Utc::now() {
+            return Err(RouteError::from(make_response!(
+                StatusCode::TOO_MANY_REQUESTS,
+                json!({
+                    "message": "ad break is on cooldown",
+                    "success": false,
+                    "retry_after_seconds": (next_allowed_at - Utc::now()).num_seconds(),
+                })
+            )));
+        }
+    }
+
+    let ad_break = sqlx::query_as!(
+        ad_break::Model,
+        "INSERT INTO ad_breaks (channel_id, duration_seconds) VALUES ($1, $2) RETURNING *",
+        token.user_id,
+        body.duration as i64,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .map_err_route("failed to create ad break")?;
+
+    global
+        .publish_event(
+            token.user_id,
+            &pb::scuffle::events::AdBreakStarted {
+                id: ad_break.id.to_string(),
+                duration_seconds: ad_break.duration_seconds,
+                started_at: ad_break.started_at.timestamp(),
+            },
+        )
+        .await
+        .map_err_route("failed to publish ad break")?;
+
+    Ok(make_response!(
+        StatusCode::OK,
+        json!({
+            "success": true,
+            "id": ad_break.id.to_string(),
+            "duration_seconds": ad_break.duration_seconds,
+        })
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct StartPoll {
+    title: String,
+    options: Vec<String>,
+    duration: u32,
+}
+
+/// Starts a poll in chat. A channel can only run one poll at a time.
+async fn start_poll(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+    let token = authorize(&global, &req).await?;
+    let body: StartPoll = read_json(req).await?;
+
+    if let Err(e) = poll::validate(&body.title, &body.options, body.duration) {
+        return Err(RouteError::from((StatusCode::BAD_REQUEST, e)));
+    }
+
+    let running = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM polls WHERE channel_id = $1 AND ends_at > NOW())",
+        token.user_id,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .map_err_route("failed to fetch polls")?
+    .unwrap_or(false);
+
+    if running {
+        return Err(RouteError::from((
+            StatusCode::CONFLICT,
+            "a poll is already running",
+        )));
+    }
+
+    let poll = sqlx::query_as!(
+        poll::Model,
+        "INSERT INTO polls (channel_id, title, options, ends_at) VALUES ($1, $2, $3, NOW() + $4 * INTERVAL '1 second') RETURNING *",
+        token.user_id,
+        body.title.trim(),
+        &body.options,
+        body.duration as i64,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .map_err_route("failed to create poll")?;
+
+    global
+        .publish_event(
+            token.user_id,
+            &pb::scuffle::events::PollStarted {
+                poll_id: poll.id.to_string(),
+                title: poll.title.clone(),
+                options: poll.options.clone(),
+                ends_at: poll.ends_at.timestamp(),
+            },
+        )
+        .await
+        .map_err_route("failed to publish poll")?;
+
+    Ok(make_response!(
+        StatusCode::OK,
+        json!({
+            "success": true,
+            "id": poll.id.to_string(),
+            "ends_at": poll.ends_at.to_rfc3339(),
+        })
+    ))
+}
+
+/// Upgrades to a websocket which pushes the live stats every few seconds.
+async fn websocket(mut req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+
+    if !hyper_tungstenite::is_upgrade_request(&req) {
+        return Err(RouteError::from((
+            StatusCode::BAD_REQUEST,
+            "expected a websocket upgrade request",
+        )));
+    }
+
+    let token = authorize(&global, &req).await?;
+
+    let (response, websocket) = hyper_tungstenite::upgrade(&mut req, None)
+        .map_err_route((StatusCode::BAD_REQUEST, "Failed to upgrade to websocket"))?;
+
+    tokio::spawn(stats_stream(websocket, global, token.user_id));
+
+    Ok(response)
+}
+
+async fn stats_stream(ws: HyperWebsocket, global: Arc<GlobalState>, channel_id: Uuid) {
+    let ws = match ws.await {
+        Ok(ws) => ws,
+        Err(e) => {
+            tracing::error!("Failed to upgrade websocket request: {}", e);
+            return;
+        }
+    };
+
+    let (mut tx, mut rx) = ws.split();
+    let mut interval = tokio::time::interval(Duration::from_secs(
+        global.config.control.stats_interval.max(1) as u64,
+    ));
+
+    loop {
+        select! {
+            _ = interval.tick() => {
+                let stats = match live_stats::live_stats(&global.db, channel_id).await {
+                    Ok(stats) => stats,
+                    Err(e) => {
+                        tracing::error!("failed to fetch live stats: {}", e);
+                        continue;
+                    }
+                };
+
+                if tx.send(Message::Text(stats_json(&stats).to_string())).await.is_err() {
+                    break;
+                }
+            }
+            msg = rx.next() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+            _ = global.ctx.done() => {
+                tx.send(Message::Close(Some(CloseFrame { code: CloseCode::Restart, reason: "server is restarting".into() }))).await.ok();
+                break;
+            }
+        }
+    }
+}
+
+pub fn routes(_global: &Arc<GlobalState>) -> Router<Body, RouteError> {
+    Router::builder()
+        .get("/stats", stats)
+        .post("/stream", update_stream)
+        .post("/markers", create_marker)
+        .post("/ads", start_ad_break)
+        .post("/polls", start_poll)
+        .get("/ws", websocket)
+        .build()
+        .expect("failed to build router")
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use hyper::{header, Body, Request, Response, StatusCode};
+use routerify::Router;
+use serde::Deserialize;
+
+use crate::{
+    api::error::{Result, ResultExt, RouteError},
+    global::GlobalState,
+};
+
+/// Reports larger than this are rejected, browsers send a few kilobytes at most.
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Only this many violations of a single request are logged, the Reporting API batches them.
+const MAX_REPORTS: usize = 20;
+
+/// A Content-Security-Policy violation, in the same shape for both report formats.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CspViolation {
+    pub document_uri: String,
+    pub directive: String,
+    pub blocked_uri: String,
+    pub disposition: String,
+    pub source_file: String,
+    pub line_number: u64,
+}
+
+/// The legacy `report-uri` format, sent as application/csp-report.
+#[derive(Debug, Deserialize)]
+struct LegacyReport {
+    #[serde(rename = "csp-report")]
+    report: LegacyViolation,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct LegacyViolation {
+    document_uri: String,
+    violated_directive: String,
+    effective_directive: String,
+    blocked_uri: String,
+    disposition: String,
+    source_file: String,
+    line_number: u64,
+}
+
+/// The Reporting API format, sent as application/reports+json.
+#[derive(Debug, Deserialize)]
+struct Report {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    body: ReportViolation,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ReportViolation {
+    #[serde(rename = "documentURL")]
+    document_url: String,
+    effective_directive: String,
+    #[serde(rename = "blockedURL")]
+    blocked_url: String,
+    disposition: String,
+    source_file: String,
+    line_number: u64,
+}
+
+/// Parses the violations of a report in either format. Reports of other types, like deprecations, are dropped.
+pub fn parse_reports(content_type: &str, body: &[u8]) -> serde_json::Result<Vec<CspViolation>> {
+    if content_type.starts_with("application/reports+json") {
+        let reports: Vec<Report> = serde_json::from_slice(body)?;
+
+        return Ok(reports
+            .into_iter()
+            .filter(|r| r.kind == "csp-violation")
+            .map(|r| CspViolation {
+                document_uri: r.body.document_url,
+                directive: r.body.effective_directive,
+                blocked_uri: r.body.blocked_url,
+                disposition: r.body.disposition,
+                source_file: r.body.source_file,
+                line_number: r.body.line_number,
+            })
+            .collect());
+    }
+
+    let LegacyReport { report } = serde_json::from_slice(body)?;
+
+    Ok(vec![CspViolation {
+        document_uri: report.document_uri,
+        // Older browsers only send the violated directive, which includes the sources.
+        directive: match report.effective_directive.is_empty() {
+            true => report
+                .violated_directive
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            false => report.effective_directive,
+        },
+        blocked_uri: report.blocked_uri,
+        disposition: report.disposition,
+        source_file: report.source_file,
+        line_number: report.line_number,
+    }])
+}
+
+/// Receives the violation reports browsers send for the `report-uri` of our policies, and logs them.
+async fn report(req: Request<Body>) -> Result<Response<Body>> {
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .map_err_route("failed to read body")?;
+    if body.len() > MAX_BODY_SIZE {
+        return Err(RouteError::from((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "report too large",
+        )));
+    }
+
+    let violations = parse_reports(&content_type, &body)
+        .map_err(|_| RouteError::from((StatusCode::BAD_REQUEST, "invalid report")))?;
+
+    for violation in violations.iter().take(MAX_REPORTS) {
+        tracing::warn!(
+            document_uri = violation.document_uri,
+            directive = violation.directive,
+            blocked_uri = violation.blocked_uri,
+            disposition = violation.disposition,
+            source_file = violation.source_file,
+            line_number = violation.line_number,
+            "content security policy violation"
+        );
+    }
+
+    Ok(Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .body(Body::empty())
+        .expect("failed to build response"))
+}
+
+pub fn routes(_global: &Arc<GlobalState>) -> Router<Body, RouteError> {
+    Router::builder()
+        .post("/report", report)
+        .build()
+        .expect("failed to build router")
+}
@@ -0,0 +1,148 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_elevated, authorize_user};
+use super::models::access_token::{AccessToken, AccessTokenScope, CreatedAccessToken};
+use crate::database::personal_access_token;
+
+const MAX_TOKENS_PER_USER: i64 = 25;
+const MAX_EXPIRES_IN_DAYS: u32 = 365;
+
+#[derive(Default)]
+pub struct AccessTokenQuery;
+
+#[Object]
+/// The query object for personal access tokens.
+impl AccessTokenQuery {
+    /// Get the personal access tokens of the logged in user, newest first.
+    async fn list<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<AccessToken>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let tokens = sqlx::query_as!(
+            personal_access_token::Model,
+            "SELECT * FROM personal_access_tokens WHERE user_id = $1 ORDER BY created_at DESC",
+            session.user_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch access tokens")?;
+
+        Ok(tokens.into_iter().map(AccessToken::from).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct AccessTokenMutation;
+
+#[Object]
+/// The mutation object for personal access tokens.
+impl AccessTokenMutation {
+    /// Create a personal access token for apps like the Stream Deck to use.
+    /// The secret is only returned here, it can not be shown again.
+    async fn create<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "A name to recognize the token by.")] name: String,
+        #[graphql(desc = "What the token can be used for.")] scopes: Vec<AccessTokenScope>,
+        #[graphql(
+            desc = "The number of days until the token expires, at most 365. Never expires if not given."
+        )]
+        expires_in_days: Option<u32>,
+    ) -> Result<CreatedAccessToken> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_elevated(ctx).await?;
+
+        if let Err(e) = personal_access_token::validate_name(&name) {
+            return Err(GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["name"]));
+        }
+
+        if scopes.is_empty() {
+            return Err(GqlError::InvalidInput
+                .with_message("A token needs at least one scope")
+                .with_field(vec!["scopes"]));
+        }
+
+        if let Some(days) = expires_in_days {
+            if days == 0 || days > MAX_EXPIRES_IN_DAYS {
+                return Err(GqlError::InvalidInput
+                    .with_message("Expiry must be between 1 and 365 days")
+                    .with_field(vec!["expiresInDays"]));
+            }
+        }
+
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*)::bigint AS "count!" FROM personal_access_tokens WHERE user_id = $1"#,
+            session.user_id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch access tokens")?;
+
+        if count >= MAX_TOKENS_PER_USER {
+            return Err(
+                GqlError::InvalidInput.with_message("You can not have more than 25 access tokens")
+            );
+        }
+
+        let scopes = scopes
+            .into_iter()
+            .fold(personal_access_token::Scope::none(), |acc, s| {
+                acc | personal_access_token::Scope::from(s)
+            });
+
+        let secret = personal_access_token::generate_token();
+
+        let token = sqlx::query_as!(
+            personal_access_token::Model,
+            "INSERT INTO personal_access_tokens (user_id, name, token_hash, scopes, expires_at) VALUES ($1, $2, $3, $4, NOW() + $5 * INTERVAL '1 day') RETURNING *",
+            session.user_id,
+            name.trim(),
+            personal_access_token::hash_token(&secret),
+            scopes.bits(),
+            expires_in_days.map(|d| d as i64),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to create access token")?;
+
+        Ok(CreatedAccessToken {
+            access_token: token.into(),
+            secret,
+        })
+    }
+
+    /// Revoke one of the logged in user's personal access tokens. Apps using it stop working right away.
+    async fn revoke<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the token.")] id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let result = sqlx::query!(
+            "DELETE FROM personal_access_tokens WHERE id = $1 AND user_id = $2",
+            id,
+            session.user_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to revoke access token")?;
+
+        if result.rows_affected() == 0 {
+            return Err(GqlError::NotFound
+                .with_message("Access token not found")
+                .with_field(vec!["id"]));
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,196 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_admin;
+use super::models::approval::{Approval, ApprovalEvent};
+use crate::database::{admin_approval, admin_approval_event};
+use crate::global::approval::ApprovalError;
+
+const MAX_REASON_LENGTH: usize = 500;
+
+const LIST_LIMIT: i64 = 100;
+
+fn check_reason(reason: &str, field: &'static str) -> Result<()> {
+    if reason.trim().is_empty() || reason.len() > MAX_REASON_LENGTH {
+        return Err(GqlError::InvalidInput
+            .with_message("Reason must be between 1 and 500 characters")
+            .with_field(vec![field]));
+    }
+
+    Ok(())
+}
+
+fn to_gql(result: std::result::Result<admin_approval::Model, ApprovalError>) -> Result<Approval> {
+    match result {
+        Ok(approval) => Ok(approval.into()),
+        Err(ApprovalError::TargetNotFound) => Err(GqlError::NotFound
+            .with_message("The user or payout method was not found")
+            .with_field(vec!["targetId"])),
+        Err(ApprovalError::AlreadyDone(message)) => Err(GqlError::InvalidInput
+            .with_message(message)
+            .with_field(vec!["targetId"])),
+        Err(ApprovalError::AlreadyProposed) => Err(GqlError::InvalidInput
+            .with_message("This action is already waiting for approval")
+            .with_field(vec!["targetId"])),
+        Err(ApprovalError::NotFound) => Err(GqlError::InvalidInput
+            .with_message("Approval not found or already decided")
+            .with_field(vec!["id"])),
+        Err(ApprovalError::Expired) => Err(GqlError::InvalidInput
+            .with_message("Approval expired, the action has to be proposed again")
+            .with_field(vec!["id"])),
+        Err(ApprovalError::OwnProposal) => Err(GqlError::Unauthorized
+            .with_message("A second admin has to approve this")
+            .with_field(vec!["id"])),
+        Err(ApprovalError::Database(e)) => Err(e).map_err_gql("Failed to update approval"),
+    }
+}
+
+#[derive(Default)]
+pub struct ApprovalQuery;
+
+#[Object]
+/// The query object for destructive admin actions which need a second admin. Only admins can see approvals.
+impl ApprovalQuery {
+    /// Get the actions waiting for a second admin, oldest first.
+    async fn pending<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<Approval>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let approvals = sqlx::query_as!(
+            admin_approval::Model,
+            "SELECT * FROM admin_approvals WHERE status = $1 AND expires_at > NOW() ORDER BY created_at ASC LIMIT $2",
+            i64::from(admin_approval::Status::Pending),
+            LIST_LIMIT,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch approvals")?;
+
+        Ok(approvals.into_iter().map(Approval::from).collect())
+    }
+
+    /// Get the audit trail of an approval, oldest first.
+    async fn history<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the approval.")] id: Uuid,
+    ) -> Result<Vec<ApprovalEvent>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let events = sqlx::query_as!(
+            admin_approval_event::Model,
+            "SELECT * FROM admin_approval_events WHERE approval_id = $1 ORDER BY created_at ASC",
+            id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch approval history")?;
+
+        Ok(events.into_iter().map(ApprovalEvent::from).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct ApprovalMutation;
+
+#[Object]
+/// The mutation object for destructive admin actions. One admin proposes an action, it runs once a second admin approves it.
+impl ApprovalMutation {
+    /// Propose to suspend a user permanently. Once approved this logs them out everywhere and ends their live stream.
+    async fn propose_permanent_suspension<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the user.")] target_id: Uuid,
+        #[graphql(desc = "The reason shown to the user.")] reason: String,
+    ) -> Result<Approval> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        if target_id == session.user_id {
+            return Err(GqlError::InvalidInput
+                .with_message("You can not suspend yourself")
+                .with_field(vec!["targetId"]));
+        }
+
+        check_reason(&reason, "reason")?;
+
+        to_gql(
+            global
+                .propose_approval(
+                    session.user_id,
+                    admin_approval::Kind::PermanentSuspension,
+                    target_id,
+                    &reason,
+                )
+                .await,
+        )
+    }
+
+    /// Propose to verify a payout method, so payouts are sent to the account.
+    async fn propose_payout_verification<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the payout method.")] target_id: Uuid,
+        #[graphql(desc = "A note explaining the decision, visible to the channel owner.")]
+        note: String,
+    ) -> Result<Approval> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        check_reason(&note, "note")?;
+
+        to_gql(
+            global
+                .propose_approval(
+                    session.user_id,
+                    admin_approval::Kind::PayoutVerification,
+                    target_id,
+                    &note,
+                )
+                .await,
+        )
+    }
+
+    /// Approve an action another admin proposed, which runs it.
+    async fn approve<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the approval.")] id: Uuid,
+        #[graphql(desc = "A note for the audit trail.", default)] note: String,
+    ) -> Result<Approval> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        if note.len() > MAX_REASON_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Note must be at most 500 characters")
+                .with_field(vec!["note"]));
+        }
+
+        to_gql(global.approve_approval(session.user_id, id, &note).await)
+    }
+
+    /// Reject a proposed action. Admins can also withdraw their own proposals.
+    async fn reject<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the approval.")] id: Uuid,
+        #[graphql(desc = "Why the action is rejected.")] note: String,
+    ) -> Result<Approval> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        check_reason(&note, "note")?;
+
+        to_gql(global.reject_approval(session.user_id, id, &note).await)
+    }
+}
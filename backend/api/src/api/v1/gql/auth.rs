@@ -1,10 +1,121 @@
 use super::error::{GqlError, Result, ResultExt};
 use super::ext::ContextExt;
+use super::guards::{authorize_admin, authorize_user, check_ip_reputation};
+use super::models::date::DateRFC3339;
+use super::models::external_account::ExternalProvider;
+use super::models::login_link::LoginLinkRequest;
+use super::models::passkey::PasskeyAssertionInput;
 use super::models::session::Session;
+use super::two_fa;
+use crate::api::middleware::csrf;
 use crate::api::v1::jwt::JwtState;
-use crate::database::{session, user};
+use crate::database::{
+    email_change, external_account, invite_code, login_link, password_reset, recovery_code,
+    session, user,
+};
 use async_graphql::{Context, Object};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
+use hyper::header;
+use uuid::Uuid;
+
+/// Checks the second factor of a user, either the answer to a challenge from `twoFa.startPasskeyLogin` or a recovery code.
+/// Returns whether one was given, it is only an error to give none when `required` is set.
+async fn verify_second_factor(
+    ctx: &Context<'_>,
+    user_id: Uuid,
+    required: bool,
+    passkey: Option<&PasskeyAssertionInput>,
+    recovery_code: Option<&str>,
+    action: &str,
+) -> Result<bool> {
+    let global = ctx.get_global();
+    let request_context = ctx.get_session();
+
+    match (passkey, recovery_code) {
+        (Some(passkey), _) => {
+            two_fa::to_gql(
+                global
+                    .authenticate_passkey(passkey.challenge_id, Some(user_id), &passkey.into())
+                    .await,
+            )?;
+        }
+        (None, Some(code)) => {
+            if !recovery_code::consume(
+                &*global.db,
+                user_id,
+                code,
+                request_context.client_ip(),
+                request_context.user_agent(),
+            )
+            .await
+            .map_err_gql("Failed to check recovery code")?
+            {
+                return Err(GqlError::InvalidInput
+                    .with_message("Recovery code is not valid or was already used")
+                    .with_field(vec!["recoveryCode"]));
+            }
+        }
+        (None, None) if required => {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!(
+                    "A passkey or recovery code is required to {}",
+                    action
+                ))
+                .with_field(vec!["passkey"]));
+        }
+        (None, None) => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+/// Logs a browser in with the session cookie too, when sessions are accepted from cookies.
+/// Websockets can't set cookies, they stay logged in with the connection.
+pub(super) fn set_session_cookies(ctx: &Context<'_>, token: &str, expires_at: DateTime<Utc>) {
+    let global = ctx.get_global();
+    if !global.config.cookie_auth.enabled || ctx.get_session().is_websocket() {
+        return;
+    }
+
+    let max_age = (expires_at - Utc::now()).num_seconds().max(0);
+    for cookie in csrf::login_cookies(&global.config.cookie_auth, token, max_age) {
+        ctx.append_http_header(header::SET_COOKIE, cookie);
+    }
+}
+
+/// Logs a browser out of the session cookie.
+fn clear_session_cookies(ctx: &Context<'_>) {
+    let global = ctx.get_global();
+    if !global.config.cookie_auth.enabled || ctx.get_session().is_websocket() {
+        return;
+    }
+
+    for cookie in csrf::logout_cookies(&global.config.cookie_auth) {
+        ctx.append_http_header(header::SET_COOKIE, cookie);
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct AuthQuery;
+
+#[Object]
+/// The query object for authentication
+impl AuthQuery {
+    /// The number of users whose password is still hashed with older parameters, they are upgraded when the user logs in. Only admins can use this.
+    async fn legacy_password_hashes<'ctx>(&self, ctx: &Context<'_>) -> Result<i64> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*)::bigint AS "count!" FROM users WHERE password_hash NOT LIKE $1 || '%'"#,
+            user::password_hash_prefix(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to count password hashes")
+    }
+}
 
 #[derive(Default, Clone)]
 pub struct AuthMutation;
@@ -13,12 +124,19 @@ pub struct AuthMutation;
 /// The mutation object for authentication
 impl AuthMutation {
     /// Login using a username and password. If via websocket this will authenticate the websocket connection.
+    /// Users who require a passkey also have to answer a challenge from `twoFa.startPasskeyLogin`, or use a recovery code.
     async fn login<'ctx>(
         &self,
         ctx: &Context<'_>,
         #[graphql(desc = "The username of the user.")] username: String,
         #[graphql(desc = "The password of the user.")] password: String,
         #[graphql(desc = "The captcha token from cloudflare turnstile.")] captcha_token: String,
+        #[graphql(desc = "The response of the passkey, if the user requires one.")] passkey: Option<
+            PasskeyAssertionInput,
+        >,
+        #[graphql(desc = "A recovery code to use instead of the passkey.")] recovery_code: Option<
+            String,
+        >,
         #[graphql(
             desc = "The duration of the session in seconds. If not specified it will be 7 days."
         )]
@@ -31,6 +149,9 @@ impl AuthMutation {
         let global = ctx.get_global();
         let request_context = ctx.get_session();
 
+        // A captcha is always required here, so only blocked addresses are affected.
+        check_ip_reputation(ctx).await?;
+
         if !global
             .validate_turnstile_token(&captcha_token)
             .await
@@ -58,17 +179,42 @@ impl AuthMutation {
                 .with_field(vec!["username", "password"]));
         }
 
+        let two_fa = verify_second_factor(
+            ctx,
+            user.id,
+            user.passkey_required,
+            passkey.as_ref(),
+            recovery_code.as_deref(),
+            "log in",
+        )
+        .await?;
+
+        // The plain password is only known here, so this is the only chance to upgrade its hash.
+        if user.password_needs_rehash() {
+            if let Err(e) = sqlx::query!(
+                "UPDATE users SET password_hash = $1 WHERE id = $2",
+                user::hash_password(&password),
+                user.id,
+            )
+            .execute(&*global.db)
+            .await
+            {
+                tracing::warn!(user_id = %user.id, "failed to rehash password: {}", e);
+            }
+        }
+
         let login_duration = validity.unwrap_or(60 * 60 * 24 * 7); // 7 days
         let expires_at = Utc::now() + Duration::seconds(login_duration as i64);
 
         // TODO: maybe look to batch this
-        let session = sqlx::query_as!(
-            session::Model,
-            "INSERT INTO sessions (user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        let session = session::create(
+            &*global.db,
             user.id,
             expires_at,
+            request_context.client_ip(),
+            request_context.user_agent(),
+            two_fa,
         )
-        .fetch_one(&*global.db)
         .await
         .map_err_gql("Failed to create session")?;
 
@@ -88,6 +234,7 @@ impl AuthMutation {
         // We need to update the request context with the new session
         if update_context.unwrap_or(true) {
             request_context.set_session(Some((session.clone(), permissions)));
+            set_session_cookies(ctx, &token, session.expires_at);
         }
 
         Ok(Session {
@@ -149,6 +296,7 @@ impl AuthMutation {
         // We need to update the request context with the new session
         if update_context.unwrap_or(true) {
             request_context.set_session(Some((session.clone(), permissions)));
+            set_session_cookies(ctx, &session_token, session.expires_at);
         }
 
         Ok(Session {
@@ -176,10 +324,21 @@ impl AuthMutation {
             desc = "Setting this to false will make it so logging in does not authenticate the connection."
         )]
         update_context: Option<bool>,
+        #[graphql(desc = "The invite code, required while registration is invite only.")]
+        invite_code: Option<String>,
     ) -> Result<Session> {
         let global = ctx.get_global();
         let request_context = ctx.get_session();
 
+        if global.config.invites.required && invite_code.is_none() {
+            return Err(GqlError::InvalidInput
+                .with_message("An invite code is required to register")
+                .with_field(vec!["inviteCode"]));
+        }
+
+        // A captcha is always required here, so only blocked addresses are affected.
+        check_ip_reputation(ctx).await?;
+
         if !global
             .validate_turnstile_token(&captcha_token)
             .await
@@ -222,6 +381,11 @@ impl AuthMutation {
                 .with_field(vec!["username"]));
         }
 
+        let email_hash = global.email_hash(&email);
+        let email = global
+            .encrypt_pii(&email)
+            .map_err_gql("Failed to encrypt email")?;
+
         let mut tx = global
             .db
             .begin()
@@ -231,28 +395,42 @@ impl AuthMutation {
         // TODO: maybe look to batch this
         let user =
             sqlx::query_as!(user::Model,
-            "INSERT INTO users (username, display_name, password_hash, email, stream_key) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+            "INSERT INTO users (username, display_name, password_hash, email, email_hash, stream_key) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
             username,
             display_name,
             user::hash_password(&password),
             email,
+            email_hash,
             user::generate_stream_key(),
         )
             .fetch_one(&mut *tx)
             .await
             .map_err_gql("Failed to create user")?;
 
+        // Codes are also taken when they are not required, so the invite tree stays complete after the launch.
+        if let Some(code) = &invite_code {
+            if !invite_code::redeem(&mut *tx, code, user.id)
+                .await
+                .map_err_gql("Failed to redeem invite code")?
+            {
+                return Err(GqlError::InvalidInput
+                    .with_message("Invite code is invalid or was already used")
+                    .with_field(vec!["inviteCode"]));
+            }
+        }
+
         let login_duration = validity.unwrap_or(60 * 60 * 24 * 7); // 7 days
         let expires_at = Utc::now() + Duration::seconds(login_duration as i64);
 
         // TODO: maybe look to batch this
-        let session = sqlx::query_as!(
-            session::Model,
-            "INSERT INTO sessions (user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        let session = session::create(
+            &mut *tx,
             user.id,
             expires_at,
+            request_context.client_ip(),
+            request_context.user_agent(),
+            false,
         )
-        .fetch_one(&mut *tx)
         .await
         .map_err_gql("Failed to create session")?;
 
@@ -276,6 +454,7 @@ impl AuthMutation {
         // We need to update the request context with the new session
         if update_context.unwrap_or(true) {
             request_context.set_session(Some((session.clone(), permissions)));
+            set_session_cookies(ctx, &token, session.expires_at);
         }
 
         Ok(Session {
@@ -289,6 +468,78 @@ impl AuthMutation {
         })
     }
 
+    /// Confirm the password of the logged in user. Sensitive actions, like changing payout details, are allowed for a few minutes afterwards.
+    /// Users with a passkey also have to answer a challenge from `twoFa.startPasskeyLogin`, or use a recovery code.
+    /// Returns when the elevation ends.
+    async fn reauthenticate<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The password of the user.")] password: String,
+        #[graphql(desc = "The response of the passkey, if the user has one.")] passkey: Option<
+            PasskeyAssertionInput,
+        >,
+        #[graphql(desc = "A recovery code to use instead of the passkey.")] recovery_code: Option<
+            String,
+        >,
+    ) -> Result<DateRFC3339> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        check_ip_reputation(ctx).await?;
+
+        let (session, permissions) = authorize_user(ctx).await?;
+
+        let user = global
+            .user_by_id_loader
+            .load_one(session.user_id)
+            .await
+            .map_err_gql("Failed to fetch user")?
+            .ok_or_else(|| GqlError::NotFound.with_message("User not found"))?;
+
+        if !user.verify_password(&password) {
+            return Err(GqlError::InvalidInput
+                .with_message("Invalid password")
+                .with_field(vec!["password"]));
+        }
+
+        // A stolen password alone must not unlock sensitive actions of users with a passkey.
+        let has_passkey = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM passkeys WHERE user_id = $1) AS "exists!""#,
+            user.id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch passkeys")?;
+
+        let two_fa = verify_second_factor(
+            ctx,
+            user.id,
+            has_passkey || user.passkey_required,
+            passkey.as_ref(),
+            recovery_code.as_deref(),
+            "reauthenticate",
+        )
+        .await?;
+
+        let session = sqlx::query_as!(
+            session::Model,
+            "UPDATE sessions SET elevated_until = NOW() + $2 * INTERVAL '1 second', two_fa_at = CASE WHEN $3 THEN NOW() ELSE two_fa_at END WHERE id = $1 RETURNING *",
+            session.id,
+            global.config.elevation.duration as i64,
+            two_fa,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to update session")?;
+
+        let elevated_until = session.elevated_until.unwrap_or_default();
+
+        // Later requests on the same websocket use this session.
+        request_context.set_session(Some((session, permissions)));
+
+        Ok(elevated_until.into())
+    }
+
     /// Logout the user with the given session token. This will invalidate the session token.
     async fn logout<'ctx>(
         &self,
@@ -323,18 +574,726 @@ impl AuthMutation {
         };
 
         // TODO: maybe look to batch this
-        sqlx::query!(
-            "UPDATE sessions SET invalidated_at = NOW() WHERE id = $1",
+        let user_id = sqlx::query_scalar!(
+            "UPDATE sessions SET invalidated_at = NOW() WHERE id = $1 RETURNING user_id",
             session_id
         )
-        .execute(&*global.db)
+        .fetch_optional(&*global.db)
         .await
         .map_err_gql("Failed to update session")?;
 
+        if let Some(user_id) = user_id {
+            global
+                .publish_sessions_revoked(user_id, &[session_id])
+                .await;
+        }
+
         if jwt.is_none() {
             request_context.set_session(None);
+            clear_session_cookies(ctx);
         }
 
+        Ok(true)
+    }
+    /// Request a link to log in without a password, which is sent to the email of the account. The returned device token
+    /// logs in with loginWithLink once the link was opened. This succeeds for unknown emails too, so accounts can't be discovered.
+    async fn request_login_link<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The email of the user.")] email: String,
+        #[graphql(desc = "The captcha token from cloudflare turnstile.")] captcha_token: String,
+    ) -> Result<LoginLinkRequest> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+        let config = &global.config.login_links;
+
+        check_ip_reputation(ctx).await?;
+
+        if !global
+            .validate_turnstile_token(&captcha_token)
+            .await
+            .map_err_gql("Failed to validate captcha token")?
+        {
+            return Err(GqlError::InvalidInput
+                .with_message("Captcha token is not valid")
+                .with_field(vec!["captchaToken"]));
+        }
+
+        let email = email.trim().to_lowercase();
+        let requested_ip = request_context
+            .client_ip()
+            .map(|ip| ip.to_string())
+            .unwrap_or_default();
+
+        let device_token = login_link::generate_token();
+        let expires_at = Utc::now() + Duration::seconds(config.expiry as i64);
+
+        // Plaintext emails of accounts which were not hashed yet are still found.
+        let user = sqlx::query_as!(
+            user::Model,
+            "SELECT * FROM users WHERE email_hash = $1 OR (email_hash IS NULL AND email = $2) LIMIT 1",
+            global.email_hash(&email),
+            email,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch user")?;
+
+        let requests = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM login_links WHERE (requested_ip = $1 OR user_id = $2) AND created_at > NOW() - $3 * INTERVAL '1 second'"#,
+            requested_ip,
+            user.as_ref().map(|u| u.id),
+            config.rate_limit_window as i64,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to count login links")?;
+
+        if requests >= config.max_requests as i64 {
+            return Err(GqlError::InvalidInput
+                .with_message("Too many login links were requested, try again later"));
+        }
+
+        if let Some(user) = user {
+            let token = login_link::generate_token();
+
+            sqlx::query!(
+                "INSERT INTO login_links (user_id, token_hash, device_hash, requested_ip, expires_at) VALUES ($1, $2, $3, $4, $5)",
+                user.id,
+                login_link::hash_token(&token),
+                login_link::hash_token(&device_token),
+                requested_ip,
+                expires_at,
+            )
+            .execute(&*global.db)
+            .await
+            .map_err_gql("Failed to create login link")?;
+
+            let to = global
+                .decrypt_pii(&user.email)
+                .map_err_gql("Failed to decrypt email")?;
+
+            // A failed send is only logged, an error here would tell apart emails which have an account.
+            if let Err(e) = global
+                .send_email(
+                    &to,
+                    "Your login link",
+                    &format!(
+                        "Hi {},\n\nOpen this link to log in: {}?token={}\n\nIt expires in {} minutes and only logs in the device you requested it on. If you did not request it, you can ignore this email.",
+                        user.display_name,
+                        config.url,
+                        token,
+                        config.expiry / 60,
+                    ),
+                )
+                .await
+            {
+                tracing::error!(user_id = %user.id, "failed to send login link: {:#}", e);
+            }
+        }
+
+        Ok(LoginLinkRequest {
+            device_token,
+            expires_at: expires_at.into(),
+        })
+    }
+
+    /// Confirm a login link with the token from the email. The device which requested the link can then log in.
+    async fn confirm_login_link<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The token from the login link.")] token: String,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        sqlx::query!(
+            "UPDATE login_links SET confirmed_at = NOW() WHERE token_hash = $1 AND confirmed_at IS NULL AND used_at IS NULL AND expires_at > NOW() RETURNING id",
+            login_link::hash_token(&token),
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to confirm login link")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("Login link is invalid or expired")
+                .with_field(vec!["token"])
+        })?;
+
+        Ok(true)
+    }
+
+    /// Login with the device token of a confirmed login link. Each link logs in once. If via websocket this will authenticate the websocket connection.
+    /// Users who require a passkey also have to answer a challenge from `twoFa.startPasskeyLogin`, or use a recovery code.
+    async fn login_with_link<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The device token returned by requestLoginLink.")] device_token: String,
+        #[graphql(desc = "The response of the passkey, if the user requires one.")] passkey: Option<
+            PasskeyAssertionInput,
+        >,
+        #[graphql(desc = "A recovery code to use instead of the passkey.")] recovery_code: Option<
+            String,
+        >,
+        #[graphql(
+            desc = "The duration of the session in seconds. If not specified it will be 7 days."
+        )]
+        validity: Option<u32>,
+        #[graphql(
+            desc = "Setting this to false will make it so logging in does not authenticate the connection."
+        )]
+        update_context: Option<bool>,
+    ) -> Result<Session> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        let invalid = || {
+            GqlError::InvalidInput
+                .with_message("Login link is invalid or expired")
+                .with_field(vec!["deviceToken"])
+        };
+
+        let link = sqlx::query_as!(
+            login_link::Model,
+            "SELECT * FROM login_links WHERE device_hash = $1",
+            login_link::hash_token(&device_token),
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch login link")?
+        .ok_or_else(invalid)?;
+
+        // The device polls until the link was opened.
+        if link.confirmed_at.is_none() && !link.is_expired() {
+            return Err(GqlError::InvalidInput
+                .with_message("Login link was not opened yet")
+                .with_field(vec!["deviceToken"]));
+        }
+
+        if !link.can_login() {
+            return Err(invalid());
+        }
+
+        let user = global
+            .user_by_id_loader
+            .load_one(link.user_id)
+            .await
+            .map_err_gql("Failed to fetch user")?
+            .ok_or_else(invalid)?;
+
+        // Checked before the link is used up, so a missing passkey can be retried.
+        let two_fa = verify_second_factor(
+            ctx,
+            user.id,
+            user.passkey_required,
+            passkey.as_ref(),
+            recovery_code.as_deref(),
+            "log in",
+        )
+        .await?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to create session")?;
+
+        // Only one request can use the link.
+        sqlx::query!(
+            "UPDATE login_links SET used_at = NOW() WHERE id = $1 AND used_at IS NULL RETURNING id",
+            link.id,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to use login link")?
+        .ok_or_else(invalid)?;
+
+        // Opening the link proved the user owns the email.
+        let user = sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET email_verified = TRUE WHERE id = $1 RETURNING *",
+            link.user_id,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to fetch user")?;
+
+        let login_duration = validity.unwrap_or(60 * 60 * 24 * 7); // 7 days
+        let expires_at = Utc::now() + Duration::seconds(login_duration as i64);
+
+        let session = session::create(
+            &mut *tx,
+            user.id,
+            expires_at,
+            request_context.client_ip(),
+            request_context.user_agent(),
+            two_fa,
+        )
+        .await
+        .map_err_gql("Failed to create session")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to commit transaction")?;
+
+        let token = JwtState::from(session.clone())
+            .serialize(global)
+            .ok_or((GqlError::InternalServerError, "Failed to serialize JWT"))?;
+
+        let permissions = global
+            .user_permisions_by_id_loader
+            .load_one(user.id)
+            .await
+            .map_err_gql("Failed to fetch user permissions")?
+            .unwrap_or_default();
+
+        // We need to update the request context with the new session
+        if update_context.unwrap_or(true) {
+            request_context.set_session(Some((session.clone(), permissions)));
+            set_session_cookies(ctx, &token, session.expires_at);
+        }
+
+        Ok(Session {
+            id: session.id,
+            token,
+            user_id: session.user_id,
+            expires_at: session.expires_at.into(),
+            last_used_at: session.last_used_at.into(),
+            created_at: session.created_at.into(),
+            _user: Some(user.into()),
+        })
+    }
+
+    /// Login with an account at an OAuth provider which was linked with linkExternalAccount. Pass the authorization code
+    /// the provider redirected back with. If via websocket this will authenticate the websocket connection.
+    /// Users who require a passkey also have to answer a challenge from `twoFa.startPasskeyLogin`, or use a recovery code.
+    async fn login_with_external_account<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The provider the account is at.")] provider: ExternalProvider,
+        #[graphql(desc = "The authorization code the provider redirected back with.")] code: String,
+        #[graphql(desc = "The redirect uri the authorization was requested with.")]
+        redirect_uri: String,
+        #[graphql(desc = "The response of the passkey, if the user requires one.")] passkey: Option<
+            PasskeyAssertionInput,
+        >,
+        #[graphql(desc = "A recovery code to use instead of the passkey.")] recovery_code: Option<
+            String,
+        >,
+        #[graphql(
+            desc = "The duration of the session in seconds. If not specified it will be 7 days."
+        )]
+        validity: Option<u32>,
+        #[graphql(
+            desc = "Setting this to false will make it so logging in does not authenticate the connection."
+        )]
+        update_context: Option<bool>,
+    ) -> Result<Session> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+        let provider = external_account::Provider::from(provider);
+
+        check_ip_reputation(ctx).await?;
+
+        if global.oauth_provider(provider).is_none() {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!(
+                    "Logging in with {} is not enabled",
+                    provider.display_name()
+                ))
+                .with_field(vec!["provider"]));
+        }
+
+        let identity = global
+            .fetch_external_identity(provider, &code, &redirect_uri)
+            .await
+            .map_err_gql("Failed to fetch external account")?
+            .ok_or_else(|| {
+                GqlError::InvalidInput
+                    .with_message("Authorization code is invalid or expired")
+                    .with_field(vec!["code"])
+            })?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to create session")?;
+
+        let user_id = sqlx::query_scalar!(
+            "UPDATE external_accounts SET last_login_at = NOW() WHERE provider = $1 AND provider_user_id = $2 RETURNING user_id",
+            i64::from(provider),
+            identity.id,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to fetch external account")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput.with_message(&format!(
+                "No account is linked to this {} account",
+                provider.display_name()
+            ))
+        })?;
+
+        let user = sqlx::query_as!(user::Model, "SELECT * FROM users WHERE id = $1", user_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err_gql("Failed to fetch user")?;
+
+        let two_fa = verify_second_factor(
+            ctx,
+            user.id,
+            user.passkey_required,
+            passkey.as_ref(),
+            recovery_code.as_deref(),
+            "log in",
+        )
+        .await?;
+
+        let login_duration = validity.unwrap_or(60 * 60 * 24 * 7); // 7 days
+        let expires_at = Utc::now() + Duration::seconds(login_duration as i64);
+
+        let session = session::create(
+            &mut *tx,
+            user.id,
+            expires_at,
+            request_context.client_ip(),
+            request_context.user_agent(),
+            two_fa,
+        )
+        .await
+        .map_err_gql("Failed to create session")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to commit transaction")?;
+
+        let token = JwtState::from(session.clone())
+            .serialize(global)
+            .ok_or((GqlError::InternalServerError, "Failed to serialize JWT"))?;
+
+        let permissions = global
+            .user_permisions_by_id_loader
+            .load_one(user.id)
+            .await
+            .map_err_gql("Failed to fetch user permissions")?
+            .unwrap_or_default();
+
+        // We need to update the request context with the new session
+        if update_context.unwrap_or(true) {
+            request_context.set_session(Some((session.clone(), permissions)));
+            set_session_cookies(ctx, &token, session.expires_at);
+        }
+
+        Ok(Session {
+            id: session.id,
+            token,
+            user_id: session.user_id,
+            expires_at: session.expires_at.into(),
+            last_used_at: session.last_used_at.into(),
+            created_at: session.created_at.into(),
+            _user: Some(user.into()),
+        })
+    }
+
+    /// Request an email with a token to set a new password. This succeeds for unknown emails too,
+    /// so accounts can't be discovered.
+    async fn request_password_reset<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The email of the user.")] email: String,
+        #[graphql(desc = "The captcha token from cloudflare turnstile.")] captcha_token: String,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+        let config = &global.config.password_resets;
+
+        check_ip_reputation(ctx).await?;
+
+        if !global
+            .validate_turnstile_token(&captcha_token)
+            .await
+            .map_err_gql("Failed to validate captcha token")?
+        {
+            return Err(GqlError::InvalidInput
+                .with_message("Captcha token is not valid")
+                .with_field(vec!["captchaToken"]));
+        }
+
+        let email = email.trim().to_lowercase();
+        let requested_ip = request_context
+            .client_ip()
+            .map(|ip| ip.to_string())
+            .unwrap_or_default();
+
+        // Plaintext emails of accounts which were not hashed yet are still found.
+        let user = sqlx::query_as!(
+            user::Model,
+            "SELECT * FROM users WHERE email_hash = $1 OR (email_hash IS NULL AND email = $2) LIMIT 1",
+            global.email_hash(&email),
+            email,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch user")?;
+
+        let requests = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM password_resets WHERE (requested_ip = $1 OR user_id = $2) AND created_at > NOW() - $3 * INTERVAL '1 second'"#,
+            requested_ip,
+            user.as_ref().map(|u| u.id),
+            config.rate_limit_window as i64,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to count password resets")?;
+
+        if requests >= config.max_requests as i64 {
+            return Err(GqlError::InvalidInput
+                .with_message("Too many password resets were requested, try again later"));
+        }
+
+        let Some(user) = user else {
+            return Ok(true);
+        };
+
+        let token = login_link::generate_token();
+
+        sqlx::query!(
+            "INSERT INTO password_resets (user_id, token_hash, requested_ip, expires_at) VALUES ($1, $2, $3, $4)",
+            user.id,
+            login_link::hash_token(&token),
+            requested_ip,
+            Utc::now() + Duration::seconds(config.expiry as i64),
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to create password reset")?;
+
+        let to = global
+            .decrypt_pii(&user.email)
+            .map_err_gql("Failed to decrypt email")?;
+
+        // A failed send is only logged, an error here would tell apart emails which have an account.
+        if let Err(e) = global
+            .send_email(
+                &to,
+                "Reset your password",
+                &format!(
+                    "Hi {},\n\nOpen this link to set a new password: {}?token={}\n\nIt expires in {} minutes and logs you out everywhere once used. If you did not request it, you can ignore this email.",
+                    user.display_name,
+                    config.url,
+                    token,
+                    config.expiry / 60,
+                ),
+            )
+            .await
+        {
+            tracing::error!(user_id = %user.id, "failed to send password reset: {:#}", e);
+        }
+
+        Ok(true)
+    }
+
+    /// Set a new password with the token from a password reset email. Each token works once,
+    /// and every session of the user is logged out.
+    async fn reset_password<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The token from the password reset email.")] token: String,
+        #[graphql(desc = "The new password of the user.")] new_password: String,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        user::validate_password(&new_password).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["newPassword"])
+        })?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to reset password")?;
+
+        // Only one request can use the token.
+        let reset = sqlx::query_as!(
+            password_reset::Model,
+            "UPDATE password_resets SET used_at = NOW() WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW() RETURNING *",
+            login_link::hash_token(&token),
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to use password reset")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("Password reset is invalid or expired")
+                .with_field(vec!["token"])
+        })?;
+
+        // The other emails sent for the account can't be used anymore either.
+        sqlx::query!(
+            "UPDATE password_resets SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+            reset.user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to use password reset")?;
+
+        // Using the token proved the user owns the email.
+        sqlx::query!(
+            "UPDATE users SET password_hash = $2, email_verified = TRUE WHERE id = $1",
+            reset.user_id,
+            user::hash_password(&new_password),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to update password")?;
+
+        // Revoked in the same transaction, so someone who knew the old password can't stay logged in.
+        let revoked = session::revoke_all(&mut *tx, reset.user_id, None)
+            .await
+            .map_err_gql("Failed to revoke sessions")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to commit transaction")?;
+
+        global
+            .publish_sessions_revoked(reset.user_id, &revoked)
+            .await;
+
+        Ok(true)
+    }
+
+    /// Confirm an email change with the token sent to the new address, the account uses the new email from then on.
+    async fn confirm_email_change<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The token from the confirmation email.")] token: String,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to confirm email change")?;
+
+        let change = sqlx::query_as!(
+            email_change::Model,
+            "UPDATE email_changes SET confirmed_at = NOW() WHERE confirm_token_hash = $1 AND confirmed_at IS NULL AND reverted_at IS NULL AND cancelled_at IS NULL AND expires_at > NOW() RETURNING *",
+            login_link::hash_token(&token),
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to confirm email change")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("Email change is invalid or expired")
+                .with_field(vec!["token"])
+        })?;
+
+        // Only checked now, so only whoever reads the new address learns it belongs to another account.
+        let taken = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM users WHERE email_hash = $1 AND id != $2)",
+            change.new_email_hash,
+            change.user_id,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to fetch user")?
+        .unwrap_or(false);
+
+        if taken {
+            return Err(GqlError::InvalidInput
+                .with_message("Email is already in use")
+                .with_field(vec!["token"]));
+        }
+
+        // Opening the link proved the user owns the email.
+        sqlx::query!(
+            "UPDATE users SET email = $2, email_hash = $3, email_verified = TRUE WHERE id = $1",
+            change.user_id,
+            change.new_email,
+            change.new_email_hash,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to update email")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to commit transaction")?;
+
+        Ok(true)
+    }
+
+    /// Undo an email change with the token sent to the old address, also after the new one confirmed it.
+    /// The account keeps or gets back the old email and every session is logged out.
+    async fn revert_email_change<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The token from the email sent to the old address.")] token: String,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to revert email change")?;
+
+        let change = sqlx::query_as!(
+            email_change::Model,
+            "UPDATE email_changes SET reverted_at = NOW() WHERE revert_token_hash = $1 AND reverted_at IS NULL AND cancelled_at IS NULL AND revert_expires_at > NOW() RETURNING *",
+            login_link::hash_token(&token),
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to revert email change")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("Email change is invalid or expired")
+                .with_field(vec!["token"])
+        })?;
+
+        if change.confirmed_at.is_some() {
+            let old_email = global
+                .decrypt_pii(&change.old_email)
+                .map_err_gql("Failed to decrypt email")?;
+
+            sqlx::query!(
+                "UPDATE users SET email = $2, email_hash = $3, email_verified = TRUE WHERE id = $1",
+                change.user_id,
+                change.old_email,
+                global.email_hash(&old_email),
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err_gql("Failed to update email")?;
+        }
+
+        // Whoever asked for the change could still be logged in, or change it again.
+        sqlx::query!(
+            "UPDATE email_changes SET cancelled_at = NOW() WHERE user_id = $1 AND confirmed_at IS NULL AND reverted_at IS NULL AND cancelled_at IS NULL",
+            change.user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to cancel email change")?;
+
+        let revoked = session::revoke_all(&mut *tx, change.user_id, None)
+            .await
+            .map_err_gql("Failed to revoke sessions")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to commit transaction")?;
+
+        global
+            .publish_sessions_revoked(change.user_id, &revoked)
+            .await;
+
         Ok(true)
     }
 }
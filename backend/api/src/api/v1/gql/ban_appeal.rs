@@ -0,0 +1,303 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_channel_owner, authorize_user};
+use super::models::ban_appeal::{BanAppeal, BanAppealEvent, BanAppealStatus};
+use crate::database::{chat_ban, chat_ban_appeal, chat_ban_appeal_event};
+use crate::pb;
+
+const MAX_MESSAGE_LENGTH: usize = 1000;
+const MAX_NOTE_LENGTH: usize = 500;
+
+const QUEUE_LIMIT: i64 = 50;
+
+#[derive(Default)]
+pub struct BanAppealQuery;
+
+#[Object]
+/// The query object for ban appeals.
+impl BanAppealQuery {
+    /// Get the appeal the logged in user submitted to a channel.
+    async fn mine<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Option<BanAppeal>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let appeal = sqlx::query_as!(
+            chat_ban_appeal::Model,
+            "SELECT * FROM chat_ban_appeals WHERE channel_id = $1 AND user_id = $2",
+            channel_id,
+            session.user_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch appeal")?;
+
+        Ok(appeal.map(BanAppeal::from))
+    }
+
+    /// Get the appeals of a channel, oldest first so they are reviewed in order.
+    async fn queue<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The status of the appeals. Defaults to pending.")] status: Option<
+            BanAppealStatus,
+        >,
+    ) -> Result<Vec<BanAppeal>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let status = chat_ban_appeal::Status::from(status.unwrap_or(BanAppealStatus::Pending));
+
+        let appeals = sqlx::query_as!(
+            chat_ban_appeal::Model,
+            "SELECT * FROM chat_ban_appeals WHERE channel_id = $1 AND status = $2 ORDER BY created_at ASC LIMIT $3",
+            channel_id,
+            i64::from(status),
+            QUEUE_LIMIT,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch appeals")?;
+
+        Ok(appeals.into_iter().map(BanAppeal::from).collect())
+    }
+
+    /// Get the history of an appeal, oldest first.
+    async fn history<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the appeal.")] id: Uuid,
+    ) -> Result<Vec<BanAppealEvent>> {
+        let global = ctx.get_global();
+
+        let appeal = appeal_by_id(ctx, id).await?;
+        authorize_channel_owner(ctx, appeal.channel_id).await?;
+
+        let events = sqlx::query_as!(
+            chat_ban_appeal_event::Model,
+            "SELECT * FROM chat_ban_appeal_events WHERE appeal_id = $1 ORDER BY created_at ASC",
+            appeal.id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch appeal history")?;
+
+        Ok(events.into_iter().map(BanAppealEvent::from).collect())
+    }
+}
+
+async fn appeal_by_id(ctx: &Context<'_>, id: Uuid) -> Result<chat_ban_appeal::Model> {
+    let global = ctx.get_global();
+
+    sqlx::query_as!(
+        chat_ban_appeal::Model,
+        "SELECT * FROM chat_ban_appeals WHERE id = $1",
+        id,
+    )
+    .fetch_optional(&*global.db)
+    .await
+    .map_err_gql("Failed to fetch appeal")?
+    .ok_or_else(|| {
+        GqlError::NotFound
+            .with_message("Appeal not found")
+            .with_field(vec!["id"])
+    })
+}
+
+/// Accepts or denies a pending appeal. Accepted appeals lift the ban.
+async fn review(ctx: &Context<'_>, id: Uuid, accepted: bool, note: String) -> Result<BanAppeal> {
+    let global = ctx.get_global();
+
+    let appeal = appeal_by_id(ctx, id).await?;
+    let (session, _) = authorize_channel_owner(ctx, appeal.channel_id).await?;
+
+    if note.len() > MAX_NOTE_LENGTH {
+        return Err(GqlError::InvalidInput
+            .with_message("Note must be at most 500 characters")
+            .with_field(vec!["note"]));
+    }
+
+    let (status, action) = match accepted {
+        true => (
+            chat_ban_appeal::Status::Accepted,
+            chat_ban_appeal_event::Action::Accepted,
+        ),
+        false => (
+            chat_ban_appeal::Status::Denied,
+            chat_ban_appeal_event::Action::Denied,
+        ),
+    };
+
+    let mut tx = global
+        .db
+        .begin()
+        .await
+        .map_err_gql("Failed to review appeal")?;
+
+    let appeal = sqlx::query_as!(
+        chat_ban_appeal::Model,
+        "UPDATE chat_ban_appeals SET status = $2, reviewed_by = $3, review_note = $4, reviewed_at = NOW() WHERE id = $1 AND status = $5 RETURNING *",
+        appeal.id,
+        i64::from(status),
+        session.user_id,
+        note,
+        i64::from(chat_ban_appeal::Status::Pending),
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err_gql("Failed to review appeal")?
+    .ok_or_else(|| {
+        GqlError::InvalidInput
+            .with_message("Appeal was already reviewed")
+            .with_field(vec!["id"])
+    })?;
+
+    sqlx::query!(
+        "INSERT INTO chat_ban_appeal_events (appeal_id, actor_id, action, note) VALUES ($1, $2, $3, $4)",
+        appeal.id,
+        session.user_id,
+        i64::from(action),
+        note,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err_gql("Failed to record appeal history")?;
+
+    if accepted {
+        sqlx::query!(
+            "DELETE FROM chat_bans WHERE channel_id = $1 AND user_id = $2",
+            appeal.channel_id,
+            appeal.user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to remove ban")?;
+    }
+
+    tx.commit().await.map_err_gql("Failed to review appeal")?;
+
+    if accepted {
+        global
+            .chat_ban_changed(appeal.channel_id, appeal.user_id, None)
+            .await;
+    }
+
+    // The review is already saved, so a failed notification must not fail the mutation.
+    match global
+        .publish_event(
+            appeal.user_id,
+            &pb::scuffle::events::BanAppealReviewed {
+                appeal_id: appeal.id.to_string(),
+                channel_id: appeal.channel_id.to_string(),
+                accepted,
+                note: appeal.review_note.clone(),
+            },
+        )
+        .await
+    {
+        Ok(()) => {}
+        Err(e) => tracing::error!("failed to publish ban appeal review {}: {}", appeal.id, e),
+    };
+
+    Ok(appeal.into())
+}
+
+#[derive(Default)]
+pub struct BanAppealMutation;
+
+#[Object]
+/// The mutation object for ban appeals.
+impl BanAppealMutation {
+    /// Ask to be unbanned from the chat of a channel. Each user can appeal once per channel.
+    async fn submit<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "Why the ban should be lifted.")] message: String,
+    ) -> Result<BanAppeal> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        if message.trim().is_empty() || message.len() > MAX_MESSAGE_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Message must be between 1 and 1000 characters")
+                .with_field(vec!["message"]));
+        }
+
+        if !chat_ban::is_banned(&global.db, channel_id, session.user_id)
+            .await
+            .map_err_gql("Failed to fetch ban")?
+        {
+            return Err(GqlError::InvalidInput
+                .with_message("You are not banned from this channel")
+                .with_field(vec!["channelId"]));
+        }
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to submit appeal")?;
+
+        let appeal = sqlx::query_as!(
+            chat_ban_appeal::Model,
+            "INSERT INTO chat_ban_appeals (channel_id, user_id, message) VALUES ($1, $2, $3) ON CONFLICT (channel_id, user_id) DO NOTHING RETURNING *",
+            channel_id,
+            session.user_id,
+            message,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to submit appeal")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("You already appealed your ban in this channel")
+                .with_field(vec!["channelId"])
+        })?;
+
+        sqlx::query!(
+            "INSERT INTO chat_ban_appeal_events (appeal_id, actor_id, action, note) VALUES ($1, $2, $3, $4)",
+            appeal.id,
+            session.user_id,
+            i64::from(chat_ban_appeal_event::Action::Submitted),
+            message,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to record appeal history")?;
+
+        tx.commit().await.map_err_gql("Failed to submit appeal")?;
+
+        Ok(appeal.into())
+    }
+
+    /// Accept an appeal, which unbans the user.
+    async fn accept<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the appeal.")] id: Uuid,
+        #[graphql(desc = "A note shown to the user.", default)] note: String,
+    ) -> Result<BanAppeal> {
+        review(ctx, id, true, note).await
+    }
+
+    /// Deny an appeal, the user stays banned and can not appeal again.
+    async fn deny<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the appeal.")] id: Uuid,
+        #[graphql(desc = "A note shown to the user.", default)] note: String,
+    ) -> Result<BanAppeal> {
+        review(ctx, id, false, note).await
+    }
+}
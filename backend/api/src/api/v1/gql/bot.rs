@@ -0,0 +1,289 @@
+use async_graphql::{Context, Object};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_admin, authorize_user};
+use super::models::bot::{ActiveBot, BotApplication, BotApplicationStatus};
+use crate::database::{bot_application, user_social_link};
+
+const MAX_DESCRIPTION_LENGTH: usize = 1000;
+const MAX_NOTE_LENGTH: usize = 500;
+
+const QUEUE_LIMIT: i64 = 50;
+const ACTIVE_BOTS_LIMIT: i64 = 100;
+
+/// How long a bot is listed in a channel after its last message there.
+const ACTIVE_BOT_DAYS: i64 = 30;
+
+fn check_note(note: &str) -> Result<()> {
+    if note.len() > MAX_NOTE_LENGTH {
+        return Err(GqlError::InvalidInput
+            .with_message("Note must be at most 500 characters")
+            .with_field(vec!["note"]));
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct BotQuery;
+
+#[Object]
+/// The query object for bot verification.
+impl BotQuery {
+    /// Get the last application the logged in user submitted for their bot account.
+    async fn mine<'ctx>(&self, ctx: &Context<'_>) -> Result<Option<BotApplication>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let application = sqlx::query_as!(
+            bot_application::Model,
+            "SELECT * FROM bot_applications WHERE user_id = $1 ORDER BY created_at DESC LIMIT 1",
+            session.user_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch application")?;
+
+        Ok(application.map(BotApplication::from))
+    }
+
+    /// Get the bot applications, oldest first so they are reviewed in order. Only admins can see them.
+    async fn queue<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The status of the applications. Defaults to pending.")] status: Option<
+            BotApplicationStatus,
+        >,
+    ) -> Result<Vec<BotApplication>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let status = bot_application::Status::from(status.unwrap_or(BotApplicationStatus::Pending));
+
+        let applications = sqlx::query_as!(
+            bot_application::Model,
+            "SELECT * FROM bot_applications WHERE status = $1 ORDER BY created_at ASC LIMIT $2",
+            i64::from(status),
+            QUEUE_LIMIT,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch applications")?;
+
+        Ok(applications.into_iter().map(BotApplication::from).collect())
+    }
+
+    /// Get the verified bots which chatted in a channel in the last 30 days, the last one active first.
+    async fn active_in_channel<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<ActiveBot>> {
+        let global = ctx.get_global();
+
+        let bots = sqlx::query!(
+            r#"SELECT cu.user_id, cu.bot_active_at AS "bot_active_at!" FROM channel_users cu JOIN users u ON u.id = cu.user_id WHERE cu.channel_id = $1 AND cu.bot_active_at >= $2 AND u.bot_verified_at IS NOT NULL ORDER BY cu.bot_active_at DESC LIMIT $3"#,
+            channel_id,
+            Utc::now() - Duration::days(ACTIVE_BOT_DAYS),
+            ACTIVE_BOTS_LIMIT,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch bots")?;
+
+        Ok(bots
+            .into_iter()
+            .map(|bot| ActiveBot {
+                user_id: bot.user_id,
+                last_active_at: bot.bot_active_at.into(),
+            })
+            .collect())
+    }
+}
+
+/// Approves or rejects a pending application. Approved bots are verified right away.
+async fn review(
+    ctx: &Context<'_>,
+    id: Uuid,
+    approved: bool,
+    note: String,
+) -> Result<BotApplication> {
+    let global = ctx.get_global();
+
+    let (session, _) = authorize_admin(ctx).await?;
+
+    check_note(&note)?;
+
+    let status = match approved {
+        true => bot_application::Status::Approved,
+        false => bot_application::Status::Rejected,
+    };
+
+    let mut tx = global
+        .db
+        .begin()
+        .await
+        .map_err_gql("Failed to review application")?;
+
+    let application = sqlx::query_as!(
+        bot_application::Model,
+        "UPDATE bot_applications SET status = $2, reviewed_by = $3, review_note = $4, reviewed_at = NOW() WHERE id = $1 AND status = $5 RETURNING *",
+        id,
+        i64::from(status),
+        session.user_id,
+        note,
+        i64::from(bot_application::Status::Pending),
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err_gql("Failed to review application")?
+    .ok_or_else(|| {
+        GqlError::InvalidInput
+            .with_message("Application not found or already reviewed")
+            .with_field(vec!["id"])
+    })?;
+
+    if approved {
+        sqlx::query!(
+            "UPDATE users SET bot_verified_at = NOW() WHERE id = $1",
+            application.user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to verify bot")?;
+    }
+
+    tx.commit()
+        .await
+        .map_err_gql("Failed to review application")?;
+
+    Ok(application.into())
+}
+
+#[derive(Default)]
+pub struct BotMutation;
+
+#[Object]
+/// The mutation object for bot verification. Verified bots get a badge and a higher chat rate limit.
+impl BotMutation {
+    /// Apply to verify the logged in account as a bot. Rejected bots can apply again.
+    async fn apply<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "What the bot does.")] description: String,
+        #[graphql(desc = "Where to find out more about the bot.", default)] website_url: String,
+    ) -> Result<BotApplication> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        if description.trim().is_empty() || description.len() > MAX_DESCRIPTION_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Description must be between 1 and 1000 characters")
+                .with_field(vec!["description"]));
+        }
+
+        if !website_url.is_empty() {
+            user_social_link::validate_url(&website_url).map_err(|e| {
+                GqlError::InvalidInput
+                    .with_message(e)
+                    .with_field(vec!["websiteUrl"])
+            })?;
+        }
+
+        // The unique index only allows one pending or approved application per account.
+        let application = sqlx::query_as!(
+            bot_application::Model,
+            "INSERT INTO bot_applications (user_id, description, website_url) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING RETURNING *",
+            session.user_id,
+            description.trim(),
+            website_url,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to submit application")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("Your bot is already verified or waiting for review")
+        })?;
+
+        Ok(application.into())
+    }
+
+    /// Approve an application, which verifies the bot. Only admins can do this.
+    async fn approve<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the application.")] id: Uuid,
+        #[graphql(desc = "A note shown to the bot owner.", default)] note: String,
+    ) -> Result<BotApplication> {
+        review(ctx, id, true, note).await
+    }
+
+    /// Reject an application. Only admins can do this.
+    async fn reject<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the application.")] id: Uuid,
+        #[graphql(desc = "A note shown to the bot owner.", default)] note: String,
+    ) -> Result<BotApplication> {
+        review(ctx, id, false, note).await
+    }
+
+    /// Take the verification of a bot back, it loses the badge and the higher rate limit. Only admins can do this.
+    async fn revoke<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the bot account.")] user_id: Uuid,
+        #[graphql(desc = "Why the verification was revoked, shown to the bot owner.")] note: String,
+    ) -> Result<BotApplication> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        check_note(&note)?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to revoke verification")?;
+
+        let application = sqlx::query_as!(
+            bot_application::Model,
+            "UPDATE bot_applications SET status = $2, reviewed_by = $3, review_note = $4, reviewed_at = NOW() WHERE user_id = $1 AND status = $5 RETURNING *",
+            user_id,
+            i64::from(bot_application::Status::Revoked),
+            session.user_id,
+            note,
+            i64::from(bot_application::Status::Approved),
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to revoke verification")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("This user is not a verified bot")
+                .with_field(vec!["userId"])
+        })?;
+
+        sqlx::query!(
+            "UPDATE users SET bot_verified_at = NULL WHERE id = $1",
+            user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to revoke verification")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to revoke verification")?;
+
+        Ok(application.into())
+    }
+}
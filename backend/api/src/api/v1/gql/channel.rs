@@ -0,0 +1,1161 @@
+use std::sync::Arc;
+
+use async_graphql::{Context, Object};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_channel_owner;
+use super::models::channel_appearance::{ChannelAppearance, ChannelLayout};
+use super::models::channel_audit_event::ChannelAuditEvent;
+use super::models::channel_event::{ChannelEvent, ChannelEventType};
+use super::models::channel_panel::{ChannelPanel, ScheduleSegment};
+use super::models::date::DateRFC3339;
+use super::models::events::StreamInfo;
+use super::models::promotion::Pricing;
+use super::models::stream_lifecycle::{StreamLifecycleTransition, StreamStatus};
+use super::models::stream_session::StreamSession;
+use super::models::tag::{DirectoryChannel, Tag};
+use super::pagination::{page_limit, Cursor};
+use crate::database::{
+    channel_appearance, channel_audit_event, channel_event, channel_panel,
+    channel_schedule_segment, display_color, promotion, stream, stream_lifecycle_transition,
+    stream_session, tag, user, user_social_link,
+};
+use crate::global::{image_processor::BANNER_VARIANTS, GlobalState};
+use crate::pb;
+
+const DEFAULT_RECENT_EVENTS_LIMIT: u32 = 25;
+const MAX_RECENT_EVENTS_LIMIT: u32 = 100;
+
+const DEFAULT_AUDIT_LOG_LIMIT: u32 = 25;
+const MAX_AUDIT_LOG_LIMIT: u32 = 100;
+
+const DEFAULT_STREAM_SESSIONS_LIMIT: u32 = 20;
+const MAX_STREAM_SESSIONS_LIMIT: u32 = 100;
+
+const DEFAULT_DIRECTORY_LIMIT: u32 = 25;
+const MAX_DIRECTORY_LIMIT: u32 = 100;
+
+#[derive(Default)]
+pub struct ChannelQuery;
+
+#[Object]
+/// The query object for channels
+impl ChannelQuery {
+    /// Get the most recent follows, subscriptions, raids and cheers of a channel, newest first.
+    /// Overlays use this to backfill their alert queue after reconnecting.
+    /// To fetch the next page pass the `cursor` of the last event as `after`.
+    async fn recent_events<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(
+            desc = "The types of events to return. If not specified all types are returned."
+        )]
+        types: Option<Vec<ChannelEventType>>,
+        #[graphql(desc = "Only return events after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(desc = "The maximum number of events to return. Defaults to 25, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<ChannelEvent>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let limit = page_limit(limit, DEFAULT_RECENT_EVENTS_LIMIT, MAX_RECENT_EVENTS_LIMIT)?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let kinds = types
+            .unwrap_or_else(|| {
+                vec![
+                    ChannelEventType::Follow,
+                    ChannelEventType::Subscription,
+                    ChannelEventType::Raid,
+                    ChannelEventType::Cheer,
+                ]
+            })
+            .into_iter()
+            .map(|t| i64::from(channel_event::Kind::from(t)))
+            .collect::<Vec<_>>();
+
+        let events = sqlx::query_as!(
+            channel_event::Model,
+            "SELECT * FROM channel_events WHERE channel_id = $1 AND kind = ANY($2) AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4::uuid)) ORDER BY created_at DESC, id DESC LIMIT $5",
+            channel_id,
+            &kinds,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch channel events")?;
+
+        Ok(events.into_iter().map(ChannelEvent::from).collect())
+    }
+
+    /// Get the changes to the settings of a channel which are kept in its audit log, newest first.
+    /// To fetch the next page pass the `cursor` of the last event as `after`.
+    async fn audit_log<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "Only return events after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(desc = "The maximum number of events to return. Defaults to 25, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<ChannelAuditEvent>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let limit = page_limit(limit, DEFAULT_AUDIT_LOG_LIMIT, MAX_AUDIT_LOG_LIMIT)?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let events = sqlx::query_as!(
+            channel_audit_event::Model,
+            "SELECT * FROM channel_audit_events WHERE channel_id = $1 AND ($2::timestamptz IS NULL OR (created_at, id) < ($2, $3::uuid)) ORDER BY created_at DESC, id DESC LIMIT $4",
+            channel_id,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch audit log")?;
+
+        Ok(events.into_iter().map(ChannelAuditEvent::from).collect())
+    }
+
+    /// Get the past streams of a channel with their stats, newest first.
+    /// To fetch the next page pass the `cursor` of the last session as `after`.
+    async fn stream_sessions<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "Only return sessions after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(desc = "The maximum number of sessions to return. Defaults to 20, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<StreamSession>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let limit = page_limit(
+            limit,
+            DEFAULT_STREAM_SESSIONS_LIMIT,
+            MAX_STREAM_SESSIONS_LIMIT,
+        )?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let sessions = sqlx::query_as!(
+            stream_session::Model,
+            "SELECT * FROM stream_sessions WHERE channel_id = $1 AND ($2::timestamptz IS NULL OR (ended_at, id) < ($2, $3::uuid)) ORDER BY ended_at DESC, id DESC LIMIT $4",
+            channel_id,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch stream sessions")?;
+
+        Ok(sessions.into_iter().map(StreamSession::from).collect())
+    }
+
+    /// Get the title, category and language of the stream of a channel. Returns null if the channel does not exist.
+    async fn stream_info<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Option<StreamInfo>> {
+        let global = ctx.get_global();
+
+        let channel = global
+            .user_by_id_loader
+            .load_one(channel_id)
+            .await
+            .map_err_gql("Failed to fetch channel")?;
+
+        Ok(channel.map(|channel| StreamInfo {
+            title: channel.stream_title,
+            category: channel.stream_category,
+            language: channel.stream_language,
+        }))
+    }
+
+    /// Get where the latest stream of a channel is in its life, with every move it made.
+    /// Returns null if the channel never streamed.
+    async fn stream_status<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Option<StreamStatus>> {
+        let global = ctx.get_global();
+
+        let Some(stream) = sqlx::query_as!(
+            stream::Model,
+            "SELECT * FROM streams WHERE channel_id = $1 AND deleted = FALSE ORDER BY created_at DESC LIMIT 1",
+            channel_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch stream")?
+        else {
+            return Ok(None);
+        };
+
+        let transitions = sqlx::query_as!(
+            stream_lifecycle_transition::Model,
+            "SELECT * FROM stream_lifecycle_transitions WHERE stream_id = $1 ORDER BY created_at ASC",
+            stream.id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch stream lifecycle")?;
+
+        Ok(Some(StreamStatus {
+            stream_id: stream.id,
+            state: stream.current_lifecycle(Utc::now()).into(),
+            transitions: transitions
+                .into_iter()
+                .map(StreamLifecycleTransition::from)
+                .collect(),
+        }))
+    }
+
+    /// Get the subscription prices of a channel with the best promotion applied.
+    /// When logged in, promotions for new subscribers are only applied if the user never subscribed to the channel.
+    async fn pricing<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Pricing> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        let channel = global
+            .user_by_id_loader
+            .load_one(channel_id)
+            .await
+            .map_err_gql("Failed to fetch channel")?
+            .ok_or_else(|| {
+                GqlError::InvalidInput
+                    .with_message("Channel not found")
+                    .with_field(vec!["channelId"])
+            })?;
+
+        let user_id = request_context
+            .get_session(global)
+            .await?
+            .map(|(session, _)| session.user_id);
+
+        let base_amount = global.config.revenue.subscription_price as i64;
+
+        let subscription = promotion::price(
+            &global.db,
+            channel.id,
+            user_id,
+            promotion::Target::Subscriptions,
+            base_amount,
+        )
+        .await
+        .map_err_gql("Failed to fetch promotions")?;
+
+        let gift_subscription = promotion::price(
+            &global.db,
+            channel.id,
+            user_id,
+            promotion::Target::GiftSubscriptions,
+            base_amount,
+        )
+        .await
+        .map_err_gql("Failed to fetch promotions")?;
+
+        Ok(Pricing {
+            subscription: subscription.into(),
+            gift_subscription: gift_subscription.into(),
+        })
+    }
+
+    /// Get the panels on the about page of a channel, in the order they are shown.
+    async fn panels<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<ChannelPanel>> {
+        let global = ctx.get_global();
+
+        let panels = sqlx::query_as!(
+            channel_panel::Model,
+            "SELECT * FROM channel_panels WHERE channel_id = $1 ORDER BY position",
+            channel_id
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch panels")?;
+
+        Ok(panels.into_iter().map(ChannelPanel::from).collect())
+    }
+
+    /// Get how the page of a channel looks.
+    async fn appearance<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<ChannelAppearance> {
+        let global = ctx.get_global();
+
+        let appearance = channel_appearance::for_channel(&global.db, channel_id)
+            .await
+            .map_err_gql("Failed to fetch appearance")?;
+
+        Ok(appearance.into())
+    }
+
+    /// Get the planned streams of a channel which have not ended yet, soonest first. Recurring streams are always included.
+    async fn schedule<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<ScheduleSegment>> {
+        let global = ctx.get_global();
+
+        let segments = channel_schedule_segment::upcoming(&*global.db, channel_id)
+            .await
+            .map_err_gql("Failed to fetch schedule")?;
+
+        Ok(segments.into_iter().map(ScheduleSegment::from).collect())
+    }
+
+    /// Whether changing the settings of a channel or moderating it needs two-factor authentication.
+    async fn moderation_two_fa_required<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let channel = global
+            .user_by_id_loader
+            .load_one(channel_id)
+            .await
+            .map_err_gql("Failed to fetch channel")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("Channel not found")
+                    .with_field(vec!["channelId"])
+            })?;
+
+        Ok(channel.moderation_two_fa_required)
+    }
+
+    /// Get the tags of a channel, in the order the broadcaster put them in.
+    async fn tags<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<Tag>> {
+        let global = ctx.get_global();
+
+        let tags = tag::for_channel(&*global.db, channel_id)
+            .await
+            .map_err_gql("Failed to fetch tags")?;
+
+        Ok(tags.into_iter().map(Tag::from).collect())
+    }
+
+    /// Get the channels which are live, the ones which went live last first.
+    /// To fetch the next page pass the `cursor` of the last channel as `after`.
+    async fn directory<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(
+            desc = "Only return channels which have all of these tags, matched without case.",
+            default
+        )]
+        tags: Vec<String>,
+        #[graphql(desc = "Only return channels after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(desc = "The maximum number of channels to return. Defaults to 25, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<DirectoryChannel>> {
+        let global = ctx.get_global();
+
+        let limit = page_limit(limit, DEFAULT_DIRECTORY_LIMIT, MAX_DIRECTORY_LIMIT)?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        // No channel can match more tags than it can have, so there is no point looking.
+        let tags = tag::normalize(&tags);
+        if tags.len() > tag::MAX_CHANNEL_TAGS {
+            return Ok(Vec::new());
+        }
+
+        let channels = sqlx::query!(
+            "SELECT s.id, s.created_at, u.id AS channel_id, u.stream_title, u.stream_category, u.stream_language FROM streams s JOIN users u ON u.id = s.channel_id WHERE s.lifecycle = $1 AND s.ended_at > NOW() AND s.deleted = FALSE AND (SELECT COUNT(*) FROM channel_tags ct JOIN tags t ON t.id = ct.tag_id WHERE ct.channel_id = s.channel_id AND lower(t.name) = ANY($2)) = cardinality($2::text[]) AND ($3::timestamptz IS NULL OR (s.created_at, s.id) < ($3, $4::uuid)) ORDER BY s.created_at DESC, s.id DESC LIMIT $5",
+            i64::from(stream::Lifecycle::Live),
+            &tags,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch directory")?;
+
+        Ok(channels
+            .into_iter()
+            .map(|c| {
+                DirectoryChannel::new(
+                    c.channel_id,
+                    c.id,
+                    c.stream_title,
+                    c.stream_category,
+                    c.stream_language,
+                    c.created_at,
+                )
+            })
+            .collect())
+    }
+}
+
+/// Tells open channel pages to reload the appearance. The change is already saved, so failing to publish only logs.
+async fn publish_appearance(global: &Arc<GlobalState>, channel_id: Uuid) {
+    let res = global
+        .publish_event(
+            channel_id,
+            &pb::scuffle::events::ChannelAppearanceUpdated {
+                channel_id: channel_id.to_string(),
+            },
+        )
+        .await;
+
+    if let Err(e) = res {
+        tracing::error!(
+            "failed to publish appearance of channel {}: {}",
+            channel_id,
+            e
+        );
+    }
+}
+
+/// Checks the title, text and link of a panel, and returns the sanitized text.
+fn check_panel(title: &str, description: &str, link_url: Option<&str>) -> Result<String> {
+    channel_panel::validate_title(title).map_err(|e| {
+        GqlError::InvalidInput
+            .with_message(e)
+            .with_field(vec!["title"])
+    })?;
+
+    channel_panel::validate_description(description).map_err(|e| {
+        GqlError::InvalidInput
+            .with_message(e)
+            .with_field(vec!["description"])
+    })?;
+
+    if let Some(link_url) = link_url {
+        user_social_link::validate_url(link_url).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["linkUrl"])
+        })?;
+    }
+
+    Ok(user::sanitize_bio(description))
+}
+
+/// Sends the uploaded image of a panel to the image processor, and returns the url it will be served from.
+async fn queue_panel_image(
+    global: &Arc<GlobalState>,
+    channel_id: Uuid,
+    source_url: &str,
+) -> Result<String> {
+    if reqwest::Url::parse(source_url)
+        .map(|url| url.scheme() != "https")
+        .unwrap_or(true)
+    {
+        return Err(GqlError::InvalidInput
+            .with_message("Source url must be a valid https url")
+            .with_field(vec!["imageSourceUrl"]));
+    }
+
+    global
+        .process_image(
+            source_url,
+            &channel_panel::image_prefix(channel_id, Uuid::new_v4()),
+        )
+        .await
+        .map_err_gql("Failed to queue panel image")
+}
+
+fn empty_panel() -> GqlError {
+    GqlError::InvalidInput.with_message("A panel needs a title, a text or an image")
+}
+
+#[derive(Default)]
+pub struct ChannelMutation;
+
+#[Object]
+/// The mutation object for channels
+impl ChannelMutation {
+    /// Change how the page of a channel looks. The banner is changed with `setBanner`.
+    async fn update_appearance<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(
+            desc = "A palette color like `blue` or a hex color like `#9146ff`, the site default if not set."
+        )]
+        accent_color: Option<String>,
+        #[graphql(desc = "The hex color to use on dark themes, only for hex colors.")]
+        accent_dark_color: Option<String>,
+        #[graphql(
+            desc = "How the video and chat are arranged.",
+            default_with = "ChannelLayout::Standard"
+        )]
+        layout: ChannelLayout,
+        #[graphql(desc = "Whether the about panels are shown.", default = true)] show_panels: bool,
+        #[graphql(desc = "Whether the schedule is shown.", default = true)] show_schedule: bool,
+    ) -> Result<ChannelAppearance> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let accent_color = match &accent_color {
+            Some(color) => {
+                let color = display_color::Color::parse(color, accent_dark_color.as_deref())
+                    .and_then(|c| c.check_contrast().map(|_| c))
+                    .map_err(|e| {
+                        GqlError::InvalidInput
+                            .with_message(e)
+                            .with_field(vec!["accentColor"])
+                    })?;
+
+                color.to_stored()
+            }
+            None if accent_dark_color.is_some() => {
+                return Err(GqlError::InvalidInput
+                    .with_message("An accent color is required")
+                    .with_field(vec!["accentColor"]));
+            }
+            None => String::new(),
+        };
+
+        let appearance = sqlx::query_as!(
+            channel_appearance::Model,
+            "INSERT INTO channel_appearances (channel_id, accent_color, layout, show_panels, show_schedule) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (channel_id) DO UPDATE SET accent_color = EXCLUDED.accent_color, layout = EXCLUDED.layout, show_panels = EXCLUDED.show_panels, show_schedule = EXCLUDED.show_schedule, updated_at = NOW() RETURNING *",
+            channel_id,
+            accent_color,
+            i64::from(channel_appearance::Layout::from(layout)),
+            show_panels,
+            show_schedule,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to update appearance")?;
+
+        publish_appearance(global, channel_id).await;
+
+        Ok(appearance.into())
+    }
+
+    /// Set the banner of a channel. The image is sent to the image processor, which crops it to a desktop and a mobile size.
+    /// The current banner is kept until it is done, `bannerPending` is set in the meantime.
+    async fn set_banner<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The url of the uploaded image, the banner is removed if not set.")]
+        source_url: Option<String>,
+    ) -> Result<ChannelAppearance> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let appearance = match source_url {
+            Some(source_url) => {
+                if reqwest::Url::parse(&source_url)
+                    .map(|url| url.scheme() != "https")
+                    .unwrap_or(true)
+                {
+                    return Err(GqlError::InvalidInput
+                        .with_message("Source url must be a valid https url")
+                        .with_field(vec!["sourceUrl"]));
+                }
+
+                // Every upload gets its own id, so the CDN never serves an older banner from its cache.
+                let banner_id = Uuid::new_v4();
+                let job_id = global
+                    .queue_image_variants(
+                        &source_url,
+                        &channel_appearance::banner_prefix(channel_id, banner_id),
+                        &BANNER_VARIANTS,
+                    )
+                    .await
+                    .map_err_gql("Failed to queue banner image")?;
+
+                sqlx::query_as!(
+                    channel_appearance::Model,
+                    "INSERT INTO channel_appearances (channel_id, banner_job_id, pending_banner_id) VALUES ($1, $2, $3) ON CONFLICT (channel_id) DO UPDATE SET banner_job_id = EXCLUDED.banner_job_id, pending_banner_id = EXCLUDED.pending_banner_id RETURNING *",
+                    channel_id,
+                    job_id,
+                    banner_id,
+                )
+                .fetch_one(&*global.db)
+                .await
+                .map_err_gql("Failed to update banner")?
+            }
+            // Removing the banner also drops one which is still being processed.
+            None => sqlx::query_as!(
+                channel_appearance::Model,
+                "INSERT INTO channel_appearances (channel_id) VALUES ($1) ON CONFLICT (channel_id) DO UPDATE SET banner_id = NULL, banner_job_id = NULL, pending_banner_id = NULL, updated_at = NOW() RETURNING *",
+                channel_id,
+            )
+            .fetch_one(&*global.db)
+            .await
+            .map_err_gql("Failed to update banner")?,
+        };
+
+        publish_appearance(global, channel_id).await;
+
+        Ok(appearance.into())
+    }
+
+    /// Add a panel to the bottom of the about page of a channel. A panel has a title, a markdown text, an image or any of them,
+    /// the image is sent to the image processor and shows once it is done. Only the broadcaster can do this.
+    async fn create_panel<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The title of the panel.", default)] title: String,
+        #[graphql(desc = "The markdown text of the panel.", default)] description: String,
+        #[graphql(desc = "The url of the uploaded image.")] image_source_url: Option<String>,
+        #[graphql(desc = "The url the panel links to.")] link_url: Option<String>,
+    ) -> Result<ChannelPanel> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let title = title.trim();
+        let description = check_panel(title, &description, link_url.as_deref())?;
+
+        if title.is_empty() && description.is_empty() && image_source_url.is_none() {
+            return Err(empty_panel());
+        }
+
+        let image_url = match &image_source_url {
+            Some(source_url) => Some(queue_panel_image(global, channel_id, source_url).await?),
+            None => None,
+        };
+
+        // The limit is checked by the same statement which puts the panel at the bottom.
+        let panel = sqlx::query_as!(
+            channel_panel::Model,
+            "INSERT INTO channel_panels (channel_id, position, title, description, image_url, link_url) SELECT $1, COALESCE(MAX(position) + 1, 0), $2, $3, $4, $5 FROM channel_panels WHERE channel_id = $1 HAVING COUNT(*) < $6 RETURNING *",
+            channel_id,
+            title,
+            description,
+            image_url,
+            link_url,
+            channel_panel::MAX_PANELS,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to create panel")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput.with_message("A channel can have at most 20 panels")
+        })?;
+
+        Ok(panel.into())
+    }
+
+    /// Change a panel on the about page of a channel. The title, text and link are replaced,
+    /// the image is kept unless a new one is uploaded or it is removed. Only the broadcaster can do this.
+    #[allow(clippy::too_many_arguments)]
+    async fn update_panel<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The id of the panel.")] id: Uuid,
+        #[graphql(desc = "The title of the panel.", default)] title: String,
+        #[graphql(desc = "The markdown text of the panel.", default)] description: String,
+        #[graphql(desc = "The url of a new uploaded image.")] image_source_url: Option<String>,
+        #[graphql(desc = "Whether to remove the image.", default)] remove_image: bool,
+        #[graphql(desc = "The url the panel links to.")] link_url: Option<String>,
+    ) -> Result<ChannelPanel> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        if remove_image && image_source_url.is_some() {
+            return Err(GqlError::InvalidInput
+                .with_message("An image can't be uploaded and removed at once")
+                .with_field(vec!["removeImage"]));
+        }
+
+        let title = title.trim();
+        let description = check_panel(title, &description, link_url.as_deref())?;
+
+        let panel = sqlx::query_as!(
+            channel_panel::Model,
+            "SELECT * FROM channel_panels WHERE id = $1 AND channel_id = $2",
+            id,
+            channel_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch panel")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Panel not found")
+                .with_field(vec!["id"])
+        })?;
+
+        let image_url = match &image_source_url {
+            Some(source_url) => Some(queue_panel_image(global, channel_id, source_url).await?),
+            None if remove_image => None,
+            None => panel.image_url,
+        };
+
+        if title.is_empty() && description.is_empty() && image_url.is_none() {
+            return Err(empty_panel());
+        }
+
+        let panel = sqlx::query_as!(
+            channel_panel::Model,
+            "UPDATE channel_panels SET title = $3, description = $4, image_url = $5, link_url = $6 WHERE id = $1 AND channel_id = $2 RETURNING *",
+            id,
+            channel_id,
+            title,
+            description,
+            image_url,
+            link_url,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to update panel")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Panel not found")
+                .with_field(vec!["id"])
+        })?;
+
+        Ok(panel.into())
+    }
+
+    /// Delete a panel from the about page of a channel. Returns false if it was not on the page.
+    async fn delete_panel<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The id of the panel.")] id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let deleted = sqlx::query!(
+            "DELETE FROM channel_panels WHERE id = $1 AND channel_id = $2",
+            id,
+            channel_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to delete panel")?;
+
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    /// Put the panels on the about page of a channel in a new order. Every panel of the channel has to be given once.
+    async fn reorder_panels<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The ids of the panels, in the order they are shown.")] ids: Vec<Uuid>,
+    ) -> Result<Vec<ChannelPanel>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to reorder panels")?;
+
+        // Locking the panels keeps one added in the meantime from being left out of the order.
+        let mut current = sqlx::query_scalar!(
+            "SELECT id FROM channel_panels WHERE channel_id = $1 FOR UPDATE",
+            channel_id,
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err_gql("Failed to fetch panels")?;
+
+        let mut given = ids.clone();
+        current.sort();
+        given.sort();
+        if current != given {
+            return Err(GqlError::InvalidInput
+                .with_message("Every panel of the channel has to be given once")
+                .with_field(vec!["ids"]));
+        }
+
+        sqlx::query!(
+            "UPDATE channel_panels p SET position = o.position - 1 FROM UNNEST($2::uuid[]) WITH ORDINALITY AS o(id, position) WHERE p.id = o.id AND p.channel_id = $1",
+            channel_id,
+            &ids,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to reorder panels")?;
+
+        let panels = sqlx::query_as!(
+            channel_panel::Model,
+            "SELECT * FROM channel_panels WHERE channel_id = $1 ORDER BY position",
+            channel_id,
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err_gql("Failed to fetch panels")?;
+
+        tx.commit().await.map_err_gql("Failed to reorder panels")?;
+
+        Ok(panels.into_iter().map(ChannelPanel::from).collect())
+    }
+
+    /// Plan a stream on the schedule of a channel. Planned streams of the channel can't overlap,
+    /// a stream going live while it is planned gets its title and category.
+    async fn add_schedule_segment<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The title of the planned stream.")] title: String,
+        #[graphql(desc = "The category of the planned stream.", default)] category: String,
+        #[graphql(desc = "Whether the stream repeats every week.", default)] recurring: bool,
+        #[graphql(desc = "The time the stream starts.")] starts_at: DateRFC3339,
+        #[graphql(desc = "The time the stream ends, open ended if not set.")] ends_at: Option<
+            DateRFC3339,
+        >,
+    ) -> Result<ScheduleSegment> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        if let Some(ends_at) = &ends_at {
+            if ends_at.0 <= starts_at.0 {
+                return Err(GqlError::InvalidInput
+                    .with_message("A stream must end after it starts")
+                    .with_field(vec!["endsAt"]));
+            }
+
+            if recurring && ends_at.0 - starts_at.0 > Duration::weeks(1) {
+                return Err(GqlError::InvalidInput
+                    .with_message("A recurring stream can't be longer than a week")
+                    .with_field(vec!["endsAt"]));
+            }
+        }
+
+        let segment = channel_schedule_segment::Model {
+            id: Uuid::new_v4(),
+            channel_id,
+            title,
+            category,
+            recurring,
+            starts_at: starts_at.0,
+            ends_at: ends_at.map(|ends_at| ends_at.0),
+            created_at: Utc::now(),
+        };
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to add schedule segment")?;
+
+        // Locking the channel keeps two concurrent adds from planning overlapping streams.
+        sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", channel_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err_gql("Failed to fetch channel")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("Channel not found")
+                    .with_field(vec!["channelId"])
+            })?;
+
+        let upcoming = channel_schedule_segment::upcoming(&mut *tx, channel_id)
+            .await
+            .map_err_gql("Failed to fetch schedule")?;
+
+        if let Some(conflict) = upcoming.iter().find(|other| other.overlaps(&segment)) {
+            return Err(GqlError::Conflict
+                .with_message(&format!(
+                    "This overlaps with \"{}\" on the schedule",
+                    conflict.title
+                ))
+                .with_field(vec!["startsAt"]));
+        }
+
+        let segment = sqlx::query_as!(
+            channel_schedule_segment::Model,
+            "INSERT INTO channel_schedule_segments (id, channel_id, title, category, recurring, starts_at, ends_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+            segment.id,
+            segment.channel_id,
+            segment.title,
+            segment.category,
+            segment.recurring,
+            segment.starts_at,
+            segment.ends_at,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to add schedule segment")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to add schedule segment")?;
+
+        Ok(segment.into())
+    }
+
+    /// Remove a planned stream from the schedule of a channel. Returns false if it was not on the schedule.
+    async fn remove_schedule_segment<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The id of the segment.")] id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let removed = sqlx::query!(
+            "DELETE FROM channel_schedule_segments WHERE id = $1 AND channel_id = $2",
+            id,
+            channel_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to remove schedule segment")?;
+
+        Ok(removed.rows_affected() > 0)
+    }
+
+    /// Set the title of the stream of a channel, before or during a broadcast. A running stream gets it right away.
+    async fn set_title<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The new title, at most 255 characters.")] title: String,
+    ) -> Result<StreamInfo> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let title = title.trim();
+        if title.chars().count() > user::MAX_STREAM_TITLE_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Title must be at most 255 characters")
+                .with_field(vec!["title"]));
+        }
+
+        let info = global
+            .update_stream_info(channel_id, Some(title), None, None)
+            .await
+            .map_err_gql("Failed to update title")?;
+
+        Ok(info.into())
+    }
+
+    /// Set the category of the stream of a channel, before or during a broadcast.
+    async fn set_category<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The new category, at most 64 characters. Removed if empty.")]
+        category: String,
+    ) -> Result<StreamInfo> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let category = category.trim();
+        if category.chars().count() > user::MAX_STREAM_CATEGORY_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Category must be at most 64 characters")
+                .with_field(vec!["category"]));
+        }
+
+        let info = global
+            .update_stream_info(channel_id, None, Some(category), None)
+            .await
+            .map_err_gql("Failed to update category")?;
+
+        Ok(info.into())
+    }
+
+    /// Set the language of the stream of a channel, before or during a broadcast.
+    async fn set_language<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "A BCP-47 language tag like `en`. Removed if empty.")] language: String,
+    ) -> Result<StreamInfo> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let language = language.trim();
+        user::validate_stream_language(language).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["language"])
+        })?;
+
+        let info = global
+            .update_stream_info(channel_id, None, None, Some(language))
+            .await
+            .map_err_gql("Failed to update language")?;
+
+        Ok(info.into())
+    }
+
+    /// Set the tags of a channel, at most 10 from `tag.list`, in the order they are shown.
+    /// Names are matched without case and repeats are left out. Only the broadcaster can do this.
+    async fn set_tags<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The names of the tags, an empty list removes every tag.")] tags: Vec<
+            String,
+        >,
+    ) -> Result<Vec<Tag>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let names = tag::normalize(&tags);
+        if names.len() > tag::MAX_CHANNEL_TAGS {
+            return Err(GqlError::InvalidInput
+                .with_message("A channel can have at most 10 tags")
+                .with_field(vec!["tags"]));
+        }
+
+        let found = sqlx::query_as!(
+            tag::Model,
+            "SELECT * FROM tags WHERE lower(name) = ANY($1)",
+            &names,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch tags")?;
+
+        // The tags keep the order they were given in.
+        let mut picked = Vec::with_capacity(names.len());
+        for name in &names {
+            let Some(tag) = found.iter().find(|t| &t.name.to_lowercase() == name) else {
+                return Err(GqlError::InvalidInput
+                    .with_message(&format!("There is no tag called {}", name))
+                    .with_field(vec!["tags"]));
+            };
+            picked.push(tag.clone());
+        }
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to update tags")?;
+
+        tag::replace_for_channel(
+            &mut tx,
+            channel_id,
+            &picked.iter().map(|t| t.id).collect::<Vec<_>>(),
+        )
+        .await
+        .map_err_gql("Failed to update tags")?;
+
+        tx.commit().await.map_err_gql("Failed to update tags")?;
+
+        Ok(picked.into_iter().map(Tag::from).collect())
+    }
+
+    /// Set whether changing the settings of a channel or moderating it needs two-factor authentication,
+    /// for the owner as well as for admins. To require it the logged in user has to have enabled it themselves.
+    async fn set_moderation_two_fa_required<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "Whether two-factor authentication is required.")] required: bool,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_channel_owner(ctx, channel_id).await?;
+
+        // Otherwise the user would lock themselves out of their own channel.
+        if required {
+            let enabled = global
+                .user_by_id_loader
+                .load_one(session.user_id)
+                .await
+                .map_err_gql("Failed to fetch user")?
+                .map_or(false, |user| user.passkey_required);
+
+            if !enabled {
+                return Err(GqlError::InvalidInput
+                    .with_message("Enable two-factor authentication before requiring it")
+                    .with_field(vec!["required"]));
+            }
+        }
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to update channel")?;
+
+        let previous = sqlx::query_scalar!(
+            "SELECT moderation_two_fa_required FROM users WHERE id = $1 FOR UPDATE",
+            channel_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to fetch channel")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Channel not found")
+                .with_field(vec!["channelId"])
+        })?;
+
+        if previous != required {
+            sqlx::query!(
+                "UPDATE users SET moderation_two_fa_required = $2 WHERE id = $1",
+                channel_id,
+                required,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err_gql("Failed to update channel")?;
+
+            let action = if required {
+                channel_audit_event::Action::ModerationTwoFaEnabled
+            } else {
+                channel_audit_event::Action::ModerationTwoFaDisabled
+            };
+
+            channel_audit_event::record(&mut *tx, channel_id, session.user_id, action)
+                .await
+                .map_err_gql("Failed to record audit event")?;
+        }
+
+        tx.commit().await.map_err_gql("Failed to update channel")?;
+
+        Ok(required)
+    }
+}
@@ -0,0 +1,141 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_channel_owner;
+use super::models::channel_import::{ChannelImport, ImportPlatform};
+use crate::database::channel_import;
+
+#[derive(Default)]
+pub struct ChannelImportQuery;
+
+#[Object]
+/// The query object for channel imports.
+impl ChannelImportQuery {
+    /// Get an import, used to follow its progress.
+    async fn import<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the import.")] id: Uuid,
+    ) -> Result<ChannelImport> {
+        let global = ctx.get_global();
+
+        let import = sqlx::query_as!(
+            channel_import::Model,
+            "SELECT * FROM channel_imports WHERE id = $1",
+            id
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch import")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Import not found")
+                .with_field(vec!["id"])
+        })?;
+
+        authorize_channel_owner(ctx, import.channel_id).await?;
+
+        Ok(import.into())
+    }
+
+    /// Get the imports of a channel, newest first.
+    async fn imports<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<ChannelImport>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let imports = sqlx::query_as!(
+            channel_import::Model,
+            "SELECT * FROM channel_imports WHERE channel_id = $1 ORDER BY created_at DESC LIMIT 20",
+            channel_id
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch imports")?;
+
+        Ok(imports.into_iter().map(ChannelImport::from).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct ChannelImportMutation;
+
+#[Object]
+/// The mutation object for channel imports.
+impl ChannelImportMutation {
+    /// Import the profile image, panels, schedule and emotes of a channel on another platform.
+    /// The import runs in the background, imported emotes go through the usual review.
+    async fn start<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel to import into.")] channel_id: Uuid,
+        #[graphql(desc = "The platform to import from.")] platform: ImportPlatform,
+        #[graphql(desc = "An OAuth access token for the channel on the platform.")]
+        access_token: String,
+    ) -> Result<ChannelImport> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        if access_token.is_empty() || access_token.len() > 512 {
+            return Err(GqlError::InvalidInput
+                .with_message("Access token must be between 1 and 512 characters")
+                .with_field(vec!["accessToken"]));
+        }
+
+        // Imports which stopped making progress, e.g. because the API restarted, do not block new ones.
+        let running = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM channel_imports WHERE channel_id = $1 AND status IN ($2, $3) AND updated_at > NOW() - INTERVAL '10 minutes')",
+            channel_id,
+            i64::from(channel_import::Status::Queued),
+            i64::from(channel_import::Status::Running),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch imports")?
+        .unwrap_or(false);
+
+        if running {
+            return Err(GqlError::InvalidInput
+                .with_message("An import is already running for this channel")
+                .with_field(vec!["channelId"]));
+        }
+
+        let access_token_encrypted = global
+            .encrypt_secret(&access_token)
+            .map_err_gql("Failed to encrypt access token")?;
+
+        let import = sqlx::query_as!(
+            channel_import::Model,
+            "INSERT INTO channel_imports (channel_id, platform, access_token_encrypted) VALUES ($1, $2, $3) RETURNING *",
+            channel_id,
+            i64::from(channel_import::Platform::from(platform)),
+            access_token_encrypted,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to create import")?;
+
+        if let Err(e) = global.queue_channel_import(import.id).await {
+            sqlx::query!(
+                "UPDATE channel_imports SET status = $2, error = $3, access_token_encrypted = NULL, completed_at = NOW() WHERE id = $1",
+                import.id,
+                i64::from(channel_import::Status::Failed),
+                "Failed to queue the import",
+            )
+            .execute(&*global.db)
+            .await
+            .map_err_gql("Failed to update import")?;
+
+            return Err(e).map_err_gql("Failed to queue import");
+        }
+
+        Ok(import.into())
+    }
+}
@@ -0,0 +1,279 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_channel_owner, authorize_user};
+use super::models::charity::{CharityCampaign, CharityCampaignReport, CharityDonation};
+use crate::database::{charity_campaign, charity_donation, checkout};
+
+const MAX_TITLE_LENGTH: usize = 128;
+const MAX_BENEFICIARY_LENGTH: usize = 128;
+const MAX_DONATION_MESSAGE_LENGTH: usize = 500;
+const MIN_DONATION_AMOUNT: u32 = 100;
+
+#[derive(Default)]
+pub struct CharityQuery;
+
+#[Object]
+/// The query object for charity campaigns. All amounts are in cents.
+impl CharityQuery {
+    /// Get the campaign a channel is currently running.
+    async fn active<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Option<CharityCampaign>> {
+        let global = ctx.get_global();
+
+        let campaign = sqlx::query_as!(
+            charity_campaign::Model,
+            "SELECT * FROM charity_campaigns WHERE channel_id = $1 AND ended_at IS NULL",
+            channel_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch charity campaign")?;
+
+        Ok(campaign.map(CharityCampaign::from))
+    }
+
+    /// Get the reports of all campaigns of a channel, newest first.
+    async fn reports<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<CharityCampaignReport>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let campaigns = sqlx::query_as!(
+            charity_campaign::Model,
+            "SELECT * FROM charity_campaigns WHERE channel_id = $1 ORDER BY created_at DESC",
+            channel_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch charity campaigns")?;
+
+        let mut reports = Vec::with_capacity(campaigns.len());
+        for campaign in campaigns {
+            let totals = charity_campaign::totals(&global.db, campaign.id)
+                .await
+                .map_err_gql("Failed to fetch donations")?;
+
+            reports.push(CharityCampaignReport::new(campaign, totals));
+        }
+
+        Ok(reports)
+    }
+}
+
+#[derive(Default)]
+pub struct CharityMutation;
+
+#[Object]
+/// The mutation object for charity campaigns. All amounts are in cents.
+impl CharityMutation {
+    /// Start a charity campaign. A channel can only run one campaign at a time.
+    async fn start<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The title of the campaign.")] title: String,
+        #[graphql(desc = "The charity which receives the donations.")] beneficiary: String,
+        #[graphql(desc = "The amount the channel wants to raise.")] target_amount: u32,
+    ) -> Result<CharityCampaign> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        if title.is_empty() || title.len() > MAX_TITLE_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Title must be between 1 and 128 characters")
+                .with_field(vec!["title"]));
+        }
+
+        if beneficiary.is_empty() || beneficiary.len() > MAX_BENEFICIARY_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Beneficiary must be between 1 and 128 characters")
+                .with_field(vec!["beneficiary"]));
+        }
+
+        if target_amount == 0 {
+            return Err(GqlError::InvalidInput
+                .with_message("Target amount must be greater than 0")
+                .with_field(vec!["targetAmount"]));
+        }
+
+        let campaign = sqlx::query_as!(
+            charity_campaign::Model,
+            "INSERT INTO charity_campaigns (channel_id, title, beneficiary, target_amount) VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING RETURNING *",
+            channel_id,
+            title,
+            beneficiary,
+            target_amount as i64,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to create charity campaign")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("This channel is already running a campaign")
+                .with_field(vec!["channelId"])
+        })?;
+
+        if let Err(e) = global.publish_charity_progress(&campaign).await {
+            tracing::error!(
+                "failed to publish charity progress {}: {:#}",
+                campaign.id,
+                e
+            );
+        }
+
+        Ok(campaign.into())
+    }
+
+    /// End a running charity campaign. Its report stays available afterwards.
+    async fn end<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the campaign.")] id: Uuid,
+    ) -> Result<CharityCampaign> {
+        let global = ctx.get_global();
+
+        let campaign = sqlx::query_as!(
+            charity_campaign::Model,
+            "SELECT * FROM charity_campaigns WHERE id = $1",
+            id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch charity campaign")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Campaign not found")
+                .with_field(vec!["id"])
+        })?;
+
+        authorize_channel_owner(ctx, campaign.channel_id).await?;
+
+        let campaign = sqlx::query_as!(
+            charity_campaign::Model,
+            "UPDATE charity_campaigns SET ended_at = NOW() WHERE id = $1 AND ended_at IS NULL RETURNING *",
+            campaign.id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to end charity campaign")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("Campaign has already ended")
+                .with_field(vec!["id"])
+        })?;
+
+        if let Err(e) = global.publish_charity_progress(&campaign).await {
+            tracing::error!(
+                "failed to publish charity progress {}: {:#}",
+                campaign.id,
+                e
+            );
+        }
+
+        Ok(campaign.into())
+    }
+
+    /// Start a donation to a running campaign. It counts towards the campaign once the payment completes.
+    async fn donate<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the campaign.")] campaign_id: Uuid,
+        #[graphql(desc = "The amount to donate, at least 100.")] amount: u32,
+        #[graphql(desc = "A message shown with the donation.")] message: Option<String>,
+    ) -> Result<CharityDonation> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        if amount < MIN_DONATION_AMOUNT {
+            return Err(GqlError::InvalidInput
+                .with_message("Donations must be at least 100 cents")
+                .with_field(vec!["amount"]));
+        }
+
+        let message = message.unwrap_or_default();
+        if message.len() > MAX_DONATION_MESSAGE_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Message too long")
+                .with_field(vec!["message"]));
+        }
+
+        let campaign = sqlx::query_as!(
+            charity_campaign::Model,
+            "SELECT * FROM charity_campaigns WHERE id = $1 AND ended_at IS NULL",
+            campaign_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch charity campaign")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("Campaign not found or already ended")
+                .with_field(vec!["campaignId"])
+        })?;
+
+        let donation = sqlx::query_as!(
+            charity_donation::Model,
+            "INSERT INTO charity_donations (campaign_id, user_id, amount, message) VALUES ($1, $2, $3, $4) RETURNING *",
+            campaign.id,
+            session.user_id,
+            amount as i64,
+            message,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to create donation")?;
+
+        // A donation which can't be paid must not stay pending, it is marked as failed instead.
+        let (provider_checkout_id, payment_url) = match global
+            .create_payment_checkout(
+                donation.id,
+                donation.amount,
+                &format!("Donation to {}", campaign.beneficiary),
+            )
+            .await
+        {
+            Ok(payment) => payment,
+            Err(e) => {
+                sqlx::query!(
+                    "UPDATE charity_donations SET status = $1, completed_at = NOW() WHERE id = $2 AND status = $3",
+                    i64::from(checkout::Status::Failed),
+                    donation.id,
+                    i64::from(checkout::Status::Pending),
+                )
+                .execute(&*global.db)
+                .await
+                .map_err_gql("Failed to update donation")?;
+
+                return Err(e).map_err_gql("Failed to create checkout with payment provider");
+            }
+        };
+
+        sqlx::query!(
+            "UPDATE charity_donations SET provider_checkout_id = $1 WHERE id = $2",
+            provider_checkout_id,
+            donation.id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to update donation")?;
+
+        Ok(CharityDonation {
+            id: donation.id,
+            campaign_id: donation.campaign_id,
+            amount: donation.amount,
+            payment_url,
+        })
+    }
+}
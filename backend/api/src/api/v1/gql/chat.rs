@@ -1,16 +1,300 @@
 use crate::api::v1::gql::error::ResultExt;
-use crate::database::chat_message;
+use crate::database::{
+    channel_audit_event, channel_user, chat_ban, chat_command, chat_log, chat_message, chat_timer,
+    chat_vip, display_color, emote_provider, emote_usage, global_role, stream, user, user_block,
+};
+use crate::global::ip_reputation::Action;
 use crate::pb;
-use prost::Message;
 
 use super::error::{GqlError, Result};
 use super::ext::ContextExt;
-use super::models::chat_message::ChatMessage;
+use super::guards::{
+    authorize_admin, authorize_channel_owner, authorize_user, check_ip_reputation,
+};
+use super::models::chat_command::{ChatCommand, ChatCommandPermission};
+use super::models::chat_fan_out::ChatFanOut;
+use super::models::chat_message::{ChatMessage, ChatMessageEmote};
+use super::models::chat_settings::ChatSettings;
+use super::models::chat_timer::ChatTimer;
+use super::models::chat_vip::ChatVip;
+use super::models::color::DisplayColor;
+use super::pagination::page_limit;
 use async_graphql::{Context, Object};
-use fred::prelude::PubsubInterface;
+use chrono::Utc;
+use prost::Message;
+use std::collections::HashSet;
 use uuid::Uuid;
 
 const MAX_MESSAGE_LENGTH: usize = 500;
+const DEFAULT_BACKFILL_LIMIT: u32 = 50;
+const MAX_BACKFILL_LIMIT: u32 = 200;
+const MAX_SLOW_MODE: u32 = 60 * 60;
+const MAX_COMMAND_COOLDOWN: u32 = 60 * 60;
+const MAX_TIMER_INTERVAL: u32 = 24 * 60 * 60;
+const MAX_TIMER_MIN_MESSAGES: u32 = 1000;
+const DEFAULT_EXPORT_LIMIT: u32 = 500;
+const MAX_EXPORT_LIMIT: u32 = 1000;
+const DEFAULT_FAN_OUT_LIMIT: u32 = 25;
+const MAX_FAN_OUT_LIMIT: u32 = 100;
+
+#[derive(Default)]
+pub struct ChatQuery;
+
+#[Object]
+/// The query object for chat.
+impl ChatQuery {
+    /// Get the messages of a chat after a sequence, oldest first. Used to fetch the messages
+    /// a subscription missed, for example after reconnecting. Only the last messages of a chat are kept,
+    /// if the first message returned is not the one after the given sequence the older ones are gone.
+    /// Messages of users the logged in user blocked are left out, and users banned from the chat can't read it, like in the subscription.
+    async fn messages<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to get the messages of.")] channel_id: Uuid,
+        #[graphql(desc = "The sequence of the last message the client has seen.")]
+        after_sequence: i64,
+        #[graphql(desc = "The number of messages to get. Defaults to 50, at most 200.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<ChatMessage>> {
+        let global = ctx.get_global();
+
+        let limit = page_limit(limit, DEFAULT_BACKFILL_LIMIT, MAX_BACKFILL_LIMIT)?;
+
+        let viewer_id = ctx
+            .get_session()
+            .get_session(global)
+            .await?
+            .map(|(session, _)| session.user_id);
+
+        if let Some(viewer_id) = viewer_id {
+            if chat_ban::is_banned(&global.db, channel_id, viewer_id)
+                .await
+                .map_err_gql("Failed to fetch ban")?
+            {
+                return Err(GqlError::Unauthorized.with_message("You are banned from this chat"));
+            }
+        }
+
+        let blocked = match viewer_id {
+            Some(viewer_id) => user_block::blocked_by(&*global.db, viewer_id)
+                .await
+                .map_err_gql("Failed to fetch blocked users")?
+                .into_iter()
+                .map(|id| id.to_string())
+                .collect(),
+            None => HashSet::new(),
+        };
+
+        let mut messages = Vec::new();
+        let mut after_sequence = after_sequence;
+
+        // Pages which only had messages of blocked users must not look like the end of the chat.
+        loop {
+            let events = sqlx::query_as!(
+                chat_log::Model,
+                "SELECT * FROM chat_log WHERE channel_id = $1 AND sequence > $2 ORDER BY sequence ASC LIMIT $3",
+                channel_id,
+                after_sequence,
+                limit,
+            )
+            .fetch_all(&*global.db)
+            .await
+            .map_err_gql("Failed to fetch chat messages")?;
+
+            let last_page = (events.len() as i64) < limit;
+
+            for event in events {
+                after_sequence = event.sequence;
+
+                let mut message =
+                    pb::scuffle::events::ChatMessage::decode(event.payload.as_slice())
+                        .map_err_gql("Failed to decode chat message")?;
+                if blocked.contains(&message.author_id) {
+                    continue;
+                }
+
+                message.sequence = event.sequence;
+                messages.push(ChatMessage::from_pb(message)?);
+            }
+
+            if last_page || messages.len() as i64 >= limit {
+                break;
+            }
+        }
+
+        messages.truncate(limit as usize);
+
+        Ok(messages)
+    }
+
+    /// Export the chat of a stream, oldest first. Only the broadcaster can do this, and only if the chat
+    /// was kept for the whole stream. To fetch the next page pass the id of the last message as `after`.
+    async fn export<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The stream to export the chat of.")] stream_id: Uuid,
+        #[graphql(
+            desc = "Only return messages after the message with this id, used for pagination."
+        )]
+        after: Option<Uuid>,
+        #[graphql(desc = "The number of messages to get. Defaults to 500, at most 1000.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<ChatMessage>> {
+        let global = ctx.get_global();
+
+        let limit = page_limit(limit, DEFAULT_EXPORT_LIMIT, MAX_EXPORT_LIMIT)?;
+
+        let stream = sqlx::query_as!(
+            stream::Model,
+            "SELECT * FROM streams WHERE id = $1 AND deleted = FALSE",
+            stream_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch stream")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Stream not found")
+                .with_field(vec!["streamId"])
+        })?;
+
+        authorize_channel_owner(ctx, stream.channel_id).await?;
+
+        if !stream.chat_archived {
+            return Err(GqlError::InvalidInput
+                .with_message("The chat of this stream was not kept")
+                .with_field(vec!["streamId"]));
+        }
+
+        let messages = sqlx::query_as!(
+            chat_message::Model,
+            "SELECT * FROM chat_messages WHERE channel_id = $1 AND created_at >= $2 AND created_at <= LEAST($3, NOW()) AND ($4::uuid IS NULL OR (created_at, id) > (SELECT created_at, id FROM chat_messages WHERE id = $4)) ORDER BY created_at ASC, id ASC LIMIT $5",
+            stream.channel_id,
+            stream.created_at,
+            stream.ended_at,
+            after,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch chat messages")?;
+
+        Ok(messages.into_iter().map(ChatMessage::from).collect())
+    }
+
+    /// Get the rules the chat of a channel enforces.
+    async fn settings<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to get the settings of.")] channel_id: Uuid,
+    ) -> Result<ChatSettings> {
+        let global = ctx.get_global();
+
+        let channel = global
+            .user_by_id_loader
+            .load_one(channel_id)
+            .await
+            .map_err_gql("Failed to fetch channel")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("Channel not found")
+                    .with_field(vec!["channelId"])
+            })?;
+
+        Ok(channel.into())
+    }
+
+    /// Get the VIPs of the chat of a channel, the first one made a VIP first.
+    async fn vips<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to get the VIPs of.")] channel_id: Uuid,
+    ) -> Result<Vec<ChatVip>> {
+        let global = ctx.get_global();
+
+        let vips = sqlx::query_as!(
+            chat_vip::Model,
+            "SELECT * FROM chat_vips WHERE channel_id = $1 ORDER BY created_at ASC",
+            channel_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch VIPs")?;
+
+        Ok(vips.into_iter().map(ChatVip::from).collect())
+    }
+
+    /// Get the commands of the chat of a channel, sorted by name.
+    async fn commands<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to get the commands of.")] channel_id: Uuid,
+    ) -> Result<Vec<ChatCommand>> {
+        let global = ctx.get_global();
+
+        let commands = sqlx::query_as!(
+            chat_command::Model,
+            "SELECT * FROM chat_commands WHERE channel_id = $1 ORDER BY name ASC",
+            channel_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch commands")?;
+
+        Ok(commands.into_iter().map(ChatCommand::from).collect())
+    }
+
+    /// Get the timers of the chat of a channel, ordered by name. Only the broadcaster can see them.
+    async fn timers<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to get the timers of.")] channel_id: Uuid,
+    ) -> Result<Vec<ChatTimer>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let timers = sqlx::query_as!(
+            chat_timer::Model,
+            "SELECT * FROM chat_timers WHERE channel_id = $1 ORDER BY name ASC",
+            channel_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch timers")?;
+
+        Ok(timers.into_iter().map(ChatTimer::from).collect())
+    }
+
+    /// Get how the chats with the most subscribers on this instance of the API are delivered. Only admins can see this.
+    async fn fan_out<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Only return the chat of this channel, every chat if not set.")]
+        channel_id: Option<Uuid>,
+        #[graphql(desc = "The number of chats to get. Defaults to 25, at most 100.")] limit: Option<
+            u32,
+        >,
+    ) -> Result<Vec<ChatFanOut>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let limit = page_limit(limit, DEFAULT_FAN_OUT_LIMIT, MAX_FAN_OUT_LIMIT)?;
+
+        let chats = global
+            .chat_fan_out()
+            .await
+            .map_err_gql("Failed to collect chat fan-out")?;
+
+        Ok(chats
+            .iter()
+            .filter(|(id, _)| channel_id.map_or(true, |channel_id| *id == channel_id))
+            .take(limit as usize)
+            .map(|(id, stats)| ChatFanOut::new(&global.config.subscriptions, *id, stats))
+            .collect())
+    }
+}
 
 #[derive(Default)]
 pub struct ChatMutation;
@@ -23,19 +307,34 @@ impl ChatMutation {
         ctx: &Context<'_>,
         #[graphql(desc = "ID of chat room where the message will be send.")] channel_id: Uuid,
         #[graphql(desc = "Message content that will be published.")] content: String,
+        #[graphql(
+            desc = "The captcha token from cloudflare turnstile, required when sending from a suspicious network."
+        )]
+        captcha_token: Option<String>,
     ) -> Result<ChatMessage> {
         let global = ctx.get_global();
-        let request_context = ctx.get_session();
 
         if content.len() > MAX_MESSAGE_LENGTH {
             return Err(GqlError::InvalidInput.with_message("Message too long"));
         }
 
-        // TODO: check if user is banned from chat
-        let (session, _) = request_context
-            .get_session(global)
-            .await?
-            .ok_or_else(|| GqlError::Unauthorized.with_message("You need to be logged in"))?;
+        let (session, permissions) = authorize_user(ctx).await?;
+
+        if check_ip_reputation(ctx).await? == Action::Captcha {
+            let valid = match &captcha_token {
+                Some(token) => global
+                    .validate_turnstile_token(token)
+                    .await
+                    .map_err_gql("Failed to validate captcha token")?,
+                None => false,
+            };
+
+            if !valid {
+                return Err(GqlError::InvalidInput
+                    .with_message("Captcha token is required")
+                    .with_field(vec!["captchaToken"]));
+            }
+        }
 
         // TODO: Check if the user is allowed to send messages in this chat
         let channel = global
@@ -45,36 +344,673 @@ impl ChatMutation {
             .map_err_gql("Failed to fetch channel")?
             .ok_or_else(|| GqlError::InvalidInput.with_message("Channel not found"))?;
 
-        let chat_message = sqlx::query_as!(
-            chat_message::Model,
-            "INSERT INTO chat_messages (channel_id, author_id, content) VALUES ($1, $2, $3) RETURNING *",
-            channel.id,
-            session.user_id,
-            content,
-        ).fetch_one(&*global.db).await.map_err_gql("Failed to insert chat message")?;
+        if chat_ban::is_banned(&global.db, channel.id, session.user_id)
+            .await
+            .map_err_gql("Failed to fetch ban")?
+        {
+            return Err(GqlError::Unauthorized.with_message("You are banned from this chat"));
+        }
+
+        if user_block::between(&*global.db, channel.id, session.user_id)
+            .await
+            .map_err_gql("Failed to fetch block")?
+        {
+            return Err(GqlError::Unauthorized.with_message("You can't chat in this channel"));
+        }
+
+        let author_vip = chat_vip::is_vip(&global.db, channel.id, session.user_id)
+            .await
+            .map_err_gql("Failed to fetch VIP status")?;
+
+        // The broadcaster and their VIPs are trusted to chat without the rules of the channel.
+        if !author_vip && session.user_id != channel.id {
+            if channel.chat_block_links && chat_message::contains_link(&content) {
+                return Err(GqlError::InvalidInput
+                    .with_message("Links are not allowed in this chat")
+                    .with_field(vec!["content"]));
+            }
+
+            if channel.chat_slow_mode > 0 {
+                if let Some(wait) = global
+                    .check_slow_mode(channel.id, session.user_id, channel.chat_slow_mode as i64)
+                    .await
+                    .map_err_gql("Failed to check slow mode")?
+                {
+                    return Err(GqlError::InvalidInput.with_message(&format!(
+                        "Slow mode is on, wait {} seconds before sending another message",
+                        wait
+                    )));
+                }
+            }
+        }
 
-        match global
-            .redis
-            .publish(
-                format!("user:{}:chat:messages", channel.id),
+        let author = global
+            .user_by_id_loader
+            .load_one(session.user_id)
+            .await
+            .map_err_gql("Failed to fetch user")?;
+        let author_verified_bot = author
+            .as_ref()
+            .map_or(false, |author| author.bot_verified_at.is_some());
+
+        if let Some(wait) = global
+            .check_chat_rate_limit(channel.id, session.user_id, author_verified_bot)
+            .await
+            .map_err_gql("Failed to check rate limit")?
+        {
+            return Err(GqlError::InvalidInput.with_message(&format!(
+                "You are sending messages too fast, wait {} seconds before sending another message",
+                wait
+            )));
+        }
+
+        // Channels which don't keep their chat only get the message published, it is never stored.
+        let chat_message = if channel.chat_archive {
+            sqlx::query_as!(
+                chat_message::Model,
+                "INSERT INTO chat_messages (channel_id, author_id, content) VALUES ($1, $2, $3) RETURNING *",
+                channel.id,
+                session.user_id,
+                content,
+            ).fetch_one(&*global.db).await.map_err_gql("Failed to insert chat message")?
+        } else {
+            chat_message::Model {
+                id: Uuid::new_v4(),
+                channel_id: channel.id,
+                author_id: session.user_id,
+                content,
+                created_at: Utc::now(),
+            }
+        };
+
+        if let Err(e) = emote_usage::record(&global.db, channel.id, &chat_message.content).await {
+            tracing::error!("failed to record emote usage: {}", e);
+        }
+
+        // Chat keeps working without emotes, so failing to load them only skips the tokenization.
+        let emotes = match global.chat_emotes(channel.id).await {
+            Ok(emotes) => emote_provider::tokenize(&chat_message.content, &emotes),
+            Err(e) => {
+                tracing::error!("failed to load chat emotes: {:#}", e);
+                Vec::new()
+            }
+        };
+
+        // The color is sent along with the message, so chat clients do not have to look up every author.
+        let author_color = author.as_ref().and_then(|author| {
+            display_color::DisplayColor::of_user(
+                author,
+                permissions
+                    .permissions
+                    .has_permission(global_role::Permission::DisplayNameGradient),
+            )
+        });
+
+        let sequence = match global
+            .publish_chat_message(
+                channel.id,
                 pb::scuffle::events::ChatMessage {
                     id: chat_message.id.to_string(),
                     channel_id: chat_message.channel_id.to_string(),
                     author_id: chat_message.author_id.to_string(),
                     content: chat_message.content.clone(),
                     created_at: chat_message.created_at.timestamp(),
-                }
-                .encode_to_vec()
-                .as_slice(),
+                    r#type: pb::scuffle::events::chat_message::Type::User as i32,
+                    emotes: emotes
+                        .iter()
+                        .map(|t| pb::scuffle::events::ChatEmote {
+                            name: t.emote.name.clone(),
+                            url: t.emote.url.clone(),
+                            provider: t.emote.provider.clone(),
+                            start: t.start as u32,
+                            end: t.end as u32,
+                        })
+                        .collect(),
+                    cheer: None,
+                    author_color: author_color.as_ref().map(Into::into),
+                    sequence: 0,
+                    author_vip,
+                    author_verified_bot,
+                },
             )
             .await
         {
-            Ok(()) => {}
+            Ok(sequence) => sequence,
             Err(_) => {
                 return Err(GqlError::InternalServerError.with_message("Failed to publish message"));
             }
         };
 
-        Ok(chat_message.into())
+        let mut chat_message = ChatMessage::from(chat_message);
+        chat_message.emotes = emotes.into_iter().map(ChatMessageEmote::from).collect();
+        chat_message.author_color = author_color.map(DisplayColor::from);
+        chat_message.sequence = sequence;
+        chat_message.author_vip = author_vip;
+        chat_message.author_verified_bot = author_verified_bot;
+
+        // The message is already sent, a command which fails to be answered is only logged.
+        if let Err(e) = global
+            .answer_chat_command(
+                channel.id,
+                &chat_message.content,
+                author_vip,
+                session.user_id == channel.id,
+            )
+            .await
+        {
+            tracing::error!("failed to answer chat command: {:#}", e);
+        }
+
+        if let Err(e) = global
+            .enter_giveaway_by_keyword(channel.id, session.user_id, &chat_message.content)
+            .await
+        {
+            tracing::error!("failed to enter giveaway: {:#}", e);
+        }
+
+        if let Err(e) = global
+            .viewer_queue_by_command(channel.id, session.user_id, &chat_message.content)
+            .await
+        {
+            tracing::error!("failed to update viewer queue: {:#}", e);
+        }
+
+        if let Err(e) = global
+            .media_request_by_command(channel.id, session.user_id, &chat_message.content)
+            .await
+        {
+            tracing::error!("failed to request media: {:#}", e);
+        }
+
+        // Only verified bots are listed as active in a channel, the message is already sent if this fails.
+        if author_verified_bot {
+            if let Err(e) =
+                channel_user::record_bot_activity(&*global.db, channel.id, session.user_id).await
+            {
+                tracing::error!("failed to record bot activity: {}", e);
+            }
+        }
+
+        Ok(chat_message)
+    }
+
+    /// Change the rules of the chat of a channel. Only the broadcaster can do this.
+    async fn set_settings<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to change the settings of.")] channel_id: Uuid,
+        #[graphql(
+            desc = "The seconds a user has to wait between two messages, at most an hour. 0 turns slow mode off, unchanged if not set."
+        )]
+        slow_mode: Option<u32>,
+        #[graphql(desc = "Whether messages with links are rejected, unchanged if not set.")]
+        block_links: Option<bool>,
+        #[graphql(desc = "Whether messages are kept after they were sent, unchanged if not set.")]
+        archive: Option<bool>,
+    ) -> Result<ChatSettings> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_channel_owner(ctx, channel_id).await?;
+
+        if slow_mode.map_or(false, |s| s > MAX_SLOW_MODE) {
+            return Err(GqlError::InvalidInput
+                .with_message("Slow mode can be at most an hour")
+                .with_field(vec!["slowMode"]));
+        }
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to update chat settings")?;
+
+        let archived = sqlx::query_scalar!(
+            "SELECT chat_archive FROM users WHERE id = $1 FOR UPDATE",
+            channel_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to fetch channel")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Channel not found")
+                .with_field(vec!["channelId"])
+        })?;
+
+        let channel = sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET chat_slow_mode = COALESCE($2, chat_slow_mode), chat_block_links = COALESCE($3, chat_block_links), chat_archive = COALESCE($4, chat_archive) WHERE id = $1 RETURNING *",
+            channel_id,
+            slow_mode.map(|s| s as i32),
+            block_links,
+            archive,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to update chat settings")?;
+
+        if channel.chat_archive != archived {
+            let action = if channel.chat_archive {
+                channel_audit_event::Action::ChatArchiveEnabled
+            } else {
+                channel_audit_event::Action::ChatArchiveDisabled
+            };
+
+            channel_audit_event::record(&mut *tx, channel_id, session.user_id, action)
+                .await
+                .map_err_gql("Failed to record audit event")?;
+
+            // The chat of a live stream can't be exported once some of it was not kept.
+            if !channel.chat_archive {
+                sqlx::query!(
+                    "UPDATE streams SET chat_archived = FALSE WHERE channel_id = $1 AND ended_at > NOW()",
+                    channel_id,
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err_gql("Failed to update stream")?;
+            }
+        }
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to update chat settings")?;
+
+        Ok(channel.into())
+    }
+
+    /// Make a user a VIP of the chat of a channel. Only the broadcaster can do this, and only
+    /// for as many users as they have VIP slots.
+    async fn add_vip<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to add the VIP to.")] channel_id: Uuid,
+        #[graphql(desc = "The user to make a VIP.")] user_id: Uuid,
+    ) -> Result<ChatVip> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_channel_owner(ctx, channel_id).await?;
+
+        if user_id == channel_id {
+            return Err(GqlError::InvalidInput
+                .with_message("The broadcaster can't be a VIP of their own chat")
+                .with_field(vec!["userId"]));
+        }
+
+        global
+            .user_by_id_loader
+            .load_one(user_id)
+            .await
+            .map_err_gql("Failed to fetch user")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("User not found")
+                    .with_field(vec!["userId"])
+            })?;
+
+        if user_block::between(&*global.db, channel_id, user_id)
+            .await
+            .map_err_gql("Failed to fetch block")?
+        {
+            return Err(GqlError::InvalidInput
+                .with_message("Blocked users can't be VIPs")
+                .with_field(vec!["userId"]));
+        }
+
+        // The slots are an entitlement of the broadcaster, also when an admin adds the VIP.
+        let extended = global
+            .user_permisions_by_id_loader
+            .load_one(channel_id)
+            .await
+            .map_err_gql("Failed to fetch permissions")?
+            .map_or(false, |p| {
+                p.permissions
+                    .has_permission(global_role::Permission::ExtendedVipSlots)
+            });
+        let slots = if extended {
+            global.config.chat.extended_vip_slots
+        } else {
+            global.config.chat.vip_slots
+        };
+
+        let mut tx = global.db.begin().await.map_err_gql("Failed to add VIP")?;
+
+        // Locking the channel keeps two concurrent adds from both taking the last slot.
+        sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", channel_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err_gql("Failed to fetch channel")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("Channel not found")
+                    .with_field(vec!["channelId"])
+            })?;
+
+        let existing = sqlx::query_as!(
+            chat_vip::Model,
+            "SELECT * FROM chat_vips WHERE channel_id = $1 AND user_id = $2",
+            channel_id,
+            user_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to fetch VIP")?;
+
+        if let Some(vip) = existing {
+            return Ok(vip.into());
+        }
+
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM chat_vips WHERE channel_id = $1",
+            channel_id,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to count VIPs")?
+        .unwrap_or(0);
+
+        if count >= slots as i64 {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!(
+                    "All {} VIP slots are taken, remove a VIP first",
+                    slots
+                ))
+                .with_field(vec!["userId"]));
+        }
+
+        let vip = sqlx::query_as!(
+            chat_vip::Model,
+            "INSERT INTO chat_vips (channel_id, user_id, granted_by) VALUES ($1, $2, $3) RETURNING *",
+            channel_id,
+            user_id,
+            session.user_id,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to add VIP")?;
+
+        tx.commit().await.map_err_gql("Failed to add VIP")?;
+
+        Ok(vip.into())
+    }
+
+    /// Take the VIP status of a user in the chat of a channel away. Only the broadcaster can do this.
+    /// Returns false if the user was not a VIP.
+    async fn remove_vip<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to remove the VIP from.")] channel_id: Uuid,
+        #[graphql(desc = "The user who should no longer be a VIP.")] user_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let result = sqlx::query!(
+            "DELETE FROM chat_vips WHERE channel_id = $1 AND user_id = $2",
+            channel_id,
+            user_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to remove VIP")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Add a command to the chat of a channel, or change the command with the same name. Only the broadcaster can do this.
+    /// Changing a command keeps its uses.
+    async fn set_command<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to add the command to.")] channel_id: Uuid,
+        #[graphql(desc = "The name of the command, without the leading `!`.")] name: String,
+        #[graphql(desc = "The message the chat answers with.")] response: String,
+        #[graphql(
+            desc = "The seconds between two answers, at most an hour. 0 answers every call.",
+            default
+        )]
+        cooldown: u32,
+        #[graphql(
+            desc = "Who can use the command.",
+            default_with = "ChatCommandPermission::Everyone"
+        )]
+        permission: ChatCommandPermission,
+    ) -> Result<ChatCommand> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let name = name.trim_start_matches('!').to_lowercase();
+        chat_command::validate_name(&name).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["name"])
+        })?;
+
+        if response.trim().is_empty() || response.len() > MAX_MESSAGE_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Response must be between 1 and 500 characters")
+                .with_field(vec!["response"]));
+        }
+
+        if cooldown > MAX_COMMAND_COOLDOWN {
+            return Err(GqlError::InvalidInput
+                .with_message("Cooldown must be at most an hour")
+                .with_field(vec!["cooldown"]));
+        }
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to save command")?;
+
+        // Locked like for VIPs, so two concurrent adds can't go over the limit.
+        sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", channel_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err_gql("Failed to fetch channel")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("Channel not found")
+                    .with_field(vec!["channelId"])
+            })?;
+
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM chat_commands WHERE channel_id = $1 AND name != $2",
+            channel_id,
+            name,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to count commands")?
+        .unwrap_or(0);
+
+        let max = global.config.chat.max_commands;
+        if count >= max as i64 {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!(
+                    "A chat can have at most {} commands, remove a command first",
+                    max
+                ))
+                .with_field(vec!["name"]));
+        }
+
+        let command = sqlx::query_as!(
+            chat_command::Model,
+            "INSERT INTO chat_commands (channel_id, name, response, cooldown, permission) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (channel_id, name) DO UPDATE SET response = EXCLUDED.response, cooldown = EXCLUDED.cooldown, permission = EXCLUDED.permission, updated_at = NOW() RETURNING *",
+            channel_id,
+            name,
+            response.trim(),
+            cooldown as i32,
+            i64::from(chat_command::Permission::from(permission)),
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to save command")?;
+
+        tx.commit().await.map_err_gql("Failed to save command")?;
+
+        Ok(command.into())
+    }
+
+    /// Remove a command from the chat of a channel. Only the broadcaster can do this.
+    /// Returns false if the chat has no command with the name.
+    async fn remove_command<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to remove the command from.")] channel_id: Uuid,
+        #[graphql(desc = "The name of the command, without the leading `!`.")] name: String,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let result = sqlx::query!(
+            "DELETE FROM chat_commands WHERE channel_id = $1 AND name = $2",
+            channel_id,
+            name.trim_start_matches('!').to_lowercase(),
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to remove command")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Add a timer to the chat of a channel, or change the timer with the same name. Only the broadcaster can do this.
+    /// The timer posts its message every interval while the channel is live, once enough was said in the chat since.
+    async fn set_timer<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to add the timer to.")] channel_id: Uuid,
+        #[graphql(desc = "The name of the timer.")] name: String,
+        #[graphql(desc = "The message the timer posts.")] message: String,
+        #[graphql(desc = "The seconds between two posts, at least 5 minutes and at most a day.")]
+        interval: u32,
+        #[graphql(
+            desc = "How many messages have to be sent in the chat after a post before the timer posts again.",
+            default
+        )]
+        min_messages: u32,
+        #[graphql(desc = "Whether the timer posts at all.", default = true)] enabled: bool,
+    ) -> Result<ChatTimer> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let name = name.to_lowercase();
+        chat_timer::validate_name(&name).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["name"])
+        })?;
+
+        if message.trim().is_empty() || message.len() > MAX_MESSAGE_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Message must be between 1 and 500 characters")
+                .with_field(vec!["message"]));
+        }
+
+        let min_interval = global.config.chat.min_timer_interval;
+        if interval < min_interval || interval > MAX_TIMER_INTERVAL {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!(
+                    "Interval must be between {} seconds and a day",
+                    min_interval
+                ))
+                .with_field(vec!["interval"]));
+        }
+
+        if min_messages > MAX_TIMER_MIN_MESSAGES {
+            return Err(GqlError::InvalidInput
+                .with_message("Minimum messages must be at most 1000")
+                .with_field(vec!["minMessages"]));
+        }
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to save timer")?;
+
+        sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", channel_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err_gql("Failed to fetch channel")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("Channel not found")
+                    .with_field(vec!["channelId"])
+            })?;
+
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM chat_timers WHERE channel_id = $1 AND name != $2",
+            channel_id,
+            name,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to count timers")?
+        .unwrap_or(0);
+
+        let max = global.config.chat.max_timers;
+        if count >= max as i64 {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!(
+                    "A chat can have at most {} timers, remove a timer first",
+                    max
+                ))
+                .with_field(vec!["name"]));
+        }
+
+        // A new timer counts the chat activity from now on, changing a timer keeps its count.
+        let sequence = chat_log::current_sequence(&mut *tx, channel_id)
+            .await
+            .map_err_gql("Failed to fetch chat")?;
+
+        let timer = sqlx::query_as!(
+            chat_timer::Model,
+            "INSERT INTO chat_timers (channel_id, name, message, interval, min_messages, enabled, last_sequence) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (channel_id, name) DO UPDATE SET message = EXCLUDED.message, interval = EXCLUDED.interval, min_messages = EXCLUDED.min_messages, enabled = EXCLUDED.enabled, updated_at = NOW() RETURNING *",
+            channel_id,
+            name,
+            message.trim(),
+            interval as i32,
+            min_messages as i32,
+            enabled,
+            sequence,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to save timer")?;
+
+        tx.commit().await.map_err_gql("Failed to save timer")?;
+
+        Ok(timer.into())
+    }
+
+    /// Remove a timer from the chat of a channel. Only the broadcaster can do this.
+    /// Returns false if the chat has no timer with the name.
+    async fn remove_timer<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The chat to remove the timer from.")] channel_id: Uuid,
+        #[graphql(desc = "The name of the timer.")] name: String,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let result = sqlx::query!(
+            "DELETE FROM chat_timers WHERE channel_id = $1 AND name = $2",
+            channel_id,
+            name.to_lowercase(),
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to remove timer")?;
+
+        Ok(result.rows_affected() > 0)
     }
 }
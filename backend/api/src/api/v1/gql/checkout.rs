@@ -0,0 +1,230 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_user;
+use super::models::checkout::Checkout;
+use crate::database::{checkout, payout_method, promotion};
+
+const MAX_CHEER_BITS: u32 = 100_000;
+const MAX_CHEER_MESSAGE_LENGTH: usize = 500;
+
+/// Creates the payment for a pending checkout at the provider.
+/// If the provider call fails the checkout is marked as failed, so it does not stay pending without a way to pay it.
+async fn start_payment(
+    ctx: &Context<'_>,
+    checkout: checkout::Model,
+    description: &str,
+) -> Result<Checkout> {
+    let global = ctx.get_global();
+
+    let (provider_checkout_id, payment_url) = match global
+        .create_payment_checkout(checkout.id, checkout.amount, description)
+        .await
+    {
+        Ok(payment) => payment,
+        Err(e) => {
+            sqlx::query!(
+                "UPDATE checkouts SET status = $1, completed_at = NOW() WHERE id = $2 AND status = $3",
+                i64::from(checkout::Status::Failed),
+                checkout.id,
+                i64::from(checkout::Status::Pending),
+            )
+            .execute(&*global.db)
+            .await
+            .map_err_gql("Failed to update checkout")?;
+
+            return Err(e).map_err_gql("Failed to create checkout with payment provider");
+        }
+    };
+
+    let checkout = sqlx::query_as!(
+        checkout::Model,
+        "UPDATE checkouts SET provider_checkout_id = $1 WHERE id = $2 RETURNING *",
+        provider_checkout_id,
+        checkout.id,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .map_err_gql("Failed to update checkout")?;
+
+    Ok(Checkout::from_model(checkout, payment_url))
+}
+
+#[derive(Default)]
+pub struct CheckoutMutation;
+
+#[Object]
+/// The mutation object for purchases.
+impl CheckoutMutation {
+    /// Start buying a subscription, or a gift subscription if a recipient is given.
+    /// Chat announces the purchase once the payment completes.
+    async fn create<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The user who receives the gift subscription, if it is a gift.")]
+        recipient_id: Option<Uuid>,
+    ) -> Result<Checkout> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let channel = global
+            .user_by_id_loader
+            .load_one(channel_id)
+            .await
+            .map_err_gql("Failed to fetch channel")?
+            .ok_or_else(|| {
+                GqlError::InvalidInput
+                    .with_message("Channel not found")
+                    .with_field(vec!["channelId"])
+            })?;
+
+        if !payout_method::is_onboarded(&global.db, channel.id)
+            .await
+            .map_err_gql("Failed to fetch payout method")?
+        {
+            return Err(GqlError::InvalidInput
+                .with_message("This channel can not receive payments yet")
+                .with_field(vec!["channelId"]));
+        }
+
+        let (kind, target, subscriber_id) = match recipient_id {
+            Some(recipient_id) => {
+                let recipient = global
+                    .user_by_id_loader
+                    .load_one(recipient_id)
+                    .await
+                    .map_err_gql("Failed to fetch recipient")?
+                    .ok_or_else(|| {
+                        GqlError::InvalidInput
+                            .with_message("Recipient not found")
+                            .with_field(vec!["recipientId"])
+                    })?;
+
+                (
+                    checkout::Kind::GiftSubscription,
+                    promotion::Target::GiftSubscriptions,
+                    recipient.id,
+                )
+            }
+            None => (
+                checkout::Kind::Subscription,
+                promotion::Target::Subscriptions,
+                session.user_id,
+            ),
+        };
+
+        if subscriber_id == channel.id {
+            return Err(GqlError::InvalidInput
+                .with_message("You can not subscribe to your own channel")
+                .with_field(vec!["channelId"]));
+        }
+
+        // Promotions for new subscribers depend on who receives the subscription, not who pays.
+        let price = promotion::price(
+            &global.db,
+            channel.id,
+            Some(subscriber_id),
+            target,
+            global.config.revenue.subscription_price as i64,
+        )
+        .await
+        .map_err_gql("Failed to fetch promotions")?;
+
+        let checkout = sqlx::query_as!(
+            checkout::Model,
+            "INSERT INTO checkouts (channel_id, user_id, recipient_id, kind, amount, promotion_id) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+            channel.id,
+            session.user_id,
+            recipient_id,
+            i64::from(kind),
+            price.amount,
+            price.promotion.map(|p| p.id),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to create checkout")?;
+
+        start_payment(
+            ctx,
+            checkout,
+            &format!("Subscription to {}", channel.display_name),
+        )
+        .await
+    }
+
+    /// Start buying bits to cheer in a channel's chat. One bit costs one cent.
+    /// The cheer is sent to chat with its cheermote once the payment completes.
+    async fn cheer<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The amount of bits to cheer, at most 100000.")] bits: u32,
+        #[graphql(desc = "The message sent to chat with the cheer.")] message: String,
+    ) -> Result<Checkout> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        if bits == 0 || bits > MAX_CHEER_BITS {
+            return Err(GqlError::InvalidInput
+                .with_message("Bits must be between 1 and 100000")
+                .with_field(vec!["bits"]));
+        }
+
+        if message.len() > MAX_CHEER_MESSAGE_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Message too long")
+                .with_field(vec!["message"]));
+        }
+
+        let channel = global
+            .user_by_id_loader
+            .load_one(channel_id)
+            .await
+            .map_err_gql("Failed to fetch channel")?
+            .ok_or_else(|| {
+                GqlError::InvalidInput
+                    .with_message("Channel not found")
+                    .with_field(vec!["channelId"])
+            })?;
+
+        if channel.id == session.user_id {
+            return Err(GqlError::InvalidInput
+                .with_message("You can not cheer in your own channel")
+                .with_field(vec!["channelId"]));
+        }
+
+        if !payout_method::is_onboarded(&global.db, channel.id)
+            .await
+            .map_err_gql("Failed to fetch payout method")?
+        {
+            return Err(GqlError::InvalidInput
+                .with_message("This channel can not receive payments yet")
+                .with_field(vec!["channelId"]));
+        }
+
+        let checkout = sqlx::query_as!(
+            checkout::Model,
+            "INSERT INTO checkouts (channel_id, user_id, kind, amount, message) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+            channel.id,
+            session.user_id,
+            i64::from(checkout::Kind::Cheer),
+            bits as i64,
+            message,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to create checkout")?;
+
+        start_payment(
+            ctx,
+            checkout,
+            &format!("{} bits for {}", bits, channel.display_name),
+        )
+        .await
+    }
+}
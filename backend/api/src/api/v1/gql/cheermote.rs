@@ -0,0 +1,165 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_admin, authorize_channel_owner};
+use super::models::cheermote::CheermoteTier;
+use crate::database::cheermote_tier;
+
+#[derive(Default)]
+pub struct CheermoteQuery;
+
+#[Object]
+/// The query object for cheermotes.
+impl CheermoteQuery {
+    /// Get the cheermote tiers used in a channel's chat, ordered by amount.
+    /// If the channel has no tiers of its own the default tiers are returned.
+    async fn tiers<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<CheermoteTier>> {
+        let global = ctx.get_global();
+
+        let tiers = cheermote_tier::for_channel(&global.db, channel_id)
+            .await
+            .map_err_gql("Failed to fetch cheermote tiers")?;
+
+        let has_channel_tiers = tiers.iter().any(|t| t.channel_id.is_some());
+
+        Ok(tiers
+            .into_iter()
+            .filter(|t| t.channel_id.is_some() == has_channel_tiers)
+            .map(CheermoteTier::from)
+            .collect())
+    }
+}
+
+#[derive(Default)]
+pub struct CheermoteMutation;
+
+#[Object]
+/// The mutation object for cheermotes.
+impl CheermoteMutation {
+    /// Add a cheermote tier. The image is sent to the image processor and served from the CDN once processed.
+    /// Without a channel the tier becomes one of the defaults, which only admins can change.
+    async fn create_tier<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel, or null to create a default tier.")]
+        channel_id: Option<Uuid>,
+        #[graphql(desc = "The word typed in front of the amount, e.g. `Cheer`.")] prefix: String,
+        #[graphql(desc = "The minimum amount of bits for this tier.")] min_bits: u32,
+        #[graphql(desc = "The color of the tier as hex, e.g. `#9146ff`.")] color: String,
+        #[graphql(desc = "The url of the uploaded image.")] source_url: String,
+    ) -> Result<CheermoteTier> {
+        let global = ctx.get_global();
+
+        match channel_id {
+            Some(channel_id) => authorize_channel_owner(ctx, channel_id).await?,
+            None => authorize_admin(ctx).await?,
+        };
+
+        if let Err(e) = cheermote_tier::validate_prefix(&prefix) {
+            return Err(GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["prefix"]));
+        }
+
+        if min_bits == 0 {
+            return Err(GqlError::InvalidInput
+                .with_message("Minimum bits must be at least 1")
+                .with_field(vec!["minBits"]));
+        }
+
+        if let Err(e) = cheermote_tier::validate_color(&color) {
+            return Err(GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["color"]));
+        }
+
+        if reqwest::Url::parse(&source_url)
+            .map(|url| url.scheme() != "https")
+            .unwrap_or(true)
+        {
+            return Err(GqlError::InvalidInput
+                .with_message("Source url must be a valid https url")
+                .with_field(vec!["sourceUrl"]));
+        }
+
+        // The unique constraint does not cover the default tiers, since their channel is null.
+        let exists = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM cheermote_tiers WHERE channel_id IS NOT DISTINCT FROM $1 AND min_bits = $2)",
+            channel_id,
+            min_bits as i64,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch cheermote tiers")?
+        .unwrap_or(false);
+
+        if exists {
+            return Err(GqlError::InvalidInput
+                .with_message("A tier with this amount already exists")
+                .with_field(vec!["minBits"]));
+        }
+
+        let id = Uuid::new_v4();
+        let image_url = global
+            .process_image(&source_url, &format!("cheermotes/{}", id))
+            .await
+            .map_err_gql("Failed to queue cheermote image")?;
+
+        let tier = sqlx::query_as!(
+            cheermote_tier::Model,
+            "INSERT INTO cheermote_tiers (id, channel_id, prefix, min_bits, color, image_url) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+            id,
+            channel_id,
+            prefix,
+            min_bits as i64,
+            color.to_lowercase(),
+            image_url,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to create cheermote tier")?;
+
+        Ok(tier.into())
+    }
+
+    /// Delete a cheermote tier.
+    async fn delete_tier<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the tier.")] id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let tier = sqlx::query_as!(
+            cheermote_tier::Model,
+            "SELECT * FROM cheermote_tiers WHERE id = $1",
+            id
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch cheermote tier")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Cheermote tier not found")
+                .with_field(vec!["id"])
+        })?;
+
+        match tier.channel_id {
+            Some(channel_id) => authorize_channel_owner(ctx, channel_id).await?,
+            None => authorize_admin(ctx).await?,
+        };
+
+        sqlx::query!("DELETE FROM cheermote_tiers WHERE id = $1", tier.id)
+            .execute(&*global.db)
+            .await
+            .map_err_gql("Failed to delete cheermote tier")?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,159 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, GqlErrorInterface, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_admin;
+use super::models::dead_letter::{DeadLetter, DeadLetterConsumer};
+use super::pagination::{page_limit, Cursor};
+use crate::database::dead_letter::{self, Consumer};
+use crate::global::GlobalState;
+
+const DEFAULT_DEAD_LETTERS_LIMIT: u32 = 25;
+const MAX_DEAD_LETTERS_LIMIT: u32 = 100;
+
+async fn fetch(global: &GlobalState, id: Uuid) -> Result<dead_letter::Model> {
+    sqlx::query_as!(
+        dead_letter::Model,
+        "SELECT * FROM dead_letters WHERE id = $1",
+        id
+    )
+    .fetch_optional(&*global.db)
+    .await
+    .map_err_gql("Failed to fetch dead letter")?
+    .ok_or_else(|| {
+        GqlError::NotFound
+            .with_message("Dead letter not found")
+            .with_field(vec!["id"])
+    })
+}
+
+fn not_pending() -> GqlErrorInterface {
+    GqlError::InvalidInput
+        .with_message("The dead letter was already retried or discarded")
+        .with_field(vec!["id"])
+}
+
+#[derive(Default)]
+pub struct DeadLetterQuery;
+
+#[Object]
+/// The query object for messages the event consumers failed to handle. Only admins can see them.
+impl DeadLetterQuery {
+    /// Get the dead letters which were not retried or discarded yet, the last to fail first.
+    async fn dead_letters<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Only return the dead letters of this consumer, all of them if not set.")]
+        consumer: Option<DeadLetterConsumer>,
+        #[graphql(desc = "Only return dead letters after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(desc = "The number of dead letters to get. Defaults to 25, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<DeadLetter>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let limit = page_limit(limit, DEFAULT_DEAD_LETTERS_LIMIT, MAX_DEAD_LETTERS_LIMIT)?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let letters = sqlx::query_as!(
+            dead_letter::Model,
+            "SELECT * FROM dead_letters WHERE retried_at IS NULL AND discarded_at IS NULL AND ($1::bigint IS NULL OR consumer = $1) AND ($2::timestamptz IS NULL OR (failed_at, id) < ($2, $3::uuid)) ORDER BY failed_at DESC, id DESC LIMIT $4",
+            consumer.map(|c| i64::from(Consumer::from(c))),
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch dead letters")?;
+
+        Ok(letters.into_iter().map(DeadLetter::from).collect())
+    }
+
+    /// Get a dead letter, also once it was retried or discarded.
+    async fn dead_letter<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the dead letter.")] id: Uuid,
+    ) -> Result<DeadLetter> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        Ok(fetch(global, id).await?.into())
+    }
+}
+
+#[derive(Default)]
+pub struct DeadLetterMutation;
+
+#[Object]
+/// The mutation object for messages the event consumers failed to handle.
+impl DeadLetterMutation {
+    /// Publish a dead letter to the queue of its consumer again, once the reason it failed is fixed.
+    /// If it fails again it comes back as the same dead letter with one more attempt.
+    async fn retry<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the dead letter.")] id: Uuid,
+    ) -> Result<DeadLetter> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        fetch(global, id).await?;
+
+        // Claiming the dead letter keeps two admins from publishing it twice.
+        let letter = sqlx::query_as!(
+            dead_letter::Model,
+            "UPDATE dead_letters SET retried_at = NOW() WHERE id = $1 AND retried_at IS NULL AND discarded_at IS NULL RETURNING *",
+            id
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to update dead letter")?
+        .ok_or_else(not_pending)?;
+
+        if let Err(e) = global.retry_dead_letter(&letter).await {
+            sqlx::query!(
+                "UPDATE dead_letters SET retried_at = NULL WHERE id = $1",
+                id
+            )
+            .execute(&*global.db)
+            .await
+            .map_err_gql("Failed to update dead letter")?;
+
+            return Err(e).map_err_gql("Failed to publish dead letter");
+        }
+
+        Ok(letter.into())
+    }
+
+    /// Drop a dead letter which should not be handled, like a message which can't be decoded.
+    async fn discard<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the dead letter.")] id: Uuid,
+    ) -> Result<DeadLetter> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        fetch(global, id).await?;
+
+        let letter = sqlx::query_as!(
+            dead_letter::Model,
+            "UPDATE dead_letters SET discarded_at = NOW() WHERE id = $1 AND retried_at IS NULL AND discarded_at IS NULL RETURNING *",
+            id
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to update dead letter")?
+        .ok_or_else(not_pending)?;
+
+        Ok(letter.into())
+    }
+}
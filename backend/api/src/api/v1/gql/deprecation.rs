@@ -0,0 +1,169 @@
+use std::sync::{Arc, Mutex};
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextExecute, NextResolve, ResolveInfo,
+};
+use async_graphql::{value, Context, Object, Response, ServerResult, Value};
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_admin;
+use super::models::deprecation::{DeprecatedUsage, Deprecation};
+use super::request_context::RequestContext;
+use crate::api::deprecation::{self, Client};
+use crate::database::deprecated_usage;
+use crate::global::GlobalState;
+
+const DEFAULT_USAGE_DAYS: u32 = 30;
+const MAX_USAGE_DAYS: u32 = 90;
+
+/// Tells clients which deprecated fields their query used, in the `deprecations` extension of the response and in the
+/// Deprecation and Sunset headers, and records the use. Fields are deprecated in the config,
+/// the field itself is also marked with `#[graphql(deprecation = "...")]` so it shows up in introspection.
+pub struct Deprecations;
+
+impl ExtensionFactory for Deprecations {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(DeprecationsExtension::default())
+    }
+}
+
+#[derive(Default)]
+struct DeprecationsExtension {
+    used: Mutex<Vec<deprecation::Deprecation>>,
+}
+
+fn to_value(deprecation: &deprecation::Deprecation) -> Value {
+    value!({
+        "surface": deprecation.surface.clone(),
+        "deprecatedAt": deprecation.deprecated_at.to_rfc3339(),
+        "sunset": deprecation.sunset.map(|s| s.to_rfc3339()),
+        "replacement": deprecation.replacement.clone(),
+    })
+}
+
+#[async_graphql::async_trait::async_trait]
+impl Extension for DeprecationsExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        if let Some(global) = ctx.data_opt::<Arc<GlobalState>>() {
+            let deprecation =
+                deprecation::find_field(&global.config.deprecations, info.parent_type, info.name);
+
+            if let Some(deprecation) = deprecation {
+                let mut used = self.used.lock().expect("failed to lock deprecations");
+                // A field in a list is resolved once per item, but it is one use.
+                if !used.iter().any(|u| u.surface == deprecation.surface) {
+                    used.push(deprecation);
+                }
+            }
+        }
+
+        next.run(ctx, info).await
+    }
+
+    async fn execute(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        operation_name: Option<&str>,
+        next: NextExecute<'_>,
+    ) -> Response {
+        let mut response = next.run(ctx, operation_name).await;
+
+        let used = std::mem::take(&mut *self.used.lock().expect("failed to lock deprecations"));
+        let Some(global) = ctx.data_opt::<Arc<GlobalState>>() else {
+            return response;
+        };
+
+        if used.is_empty() {
+            return response;
+        }
+
+        for (name, value) in deprecation::headers(&global.config.deprecations, &used) {
+            match value.parse() {
+                Ok(value) => {
+                    response.http_headers.insert(name, value);
+                }
+                Err(_) => tracing::warn!(header = name, "invalid deprecation header value"),
+            }
+        }
+
+        response.extensions.insert(
+            "deprecations".to_string(),
+            Value::List(used.iter().map(to_value).collect()),
+        );
+
+        let session = match ctx.data_opt::<Arc<RequestContext>>() {
+            Some(request_context) => request_context.get_session(global).await.ok().flatten(),
+            None => None,
+        };
+
+        let client = match session {
+            Some((session, _)) => Client::Session {
+                id: session.id,
+                user_id: session.user_id,
+            },
+            None => Client::Anonymous,
+        };
+
+        deprecation::record(global, &used, client).await;
+
+        response
+    }
+}
+
+#[derive(Default)]
+pub struct DeprecationQuery;
+
+#[Object]
+/// The query object for deprecations of the API.
+impl DeprecationQuery {
+    /// Get the changelog of deprecated fields and endpoints, the ones removed first come first.
+    async fn deprecations<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<Deprecation>> {
+        let global = ctx.get_global();
+
+        let mut deprecations = global
+            .config
+            .deprecations
+            .surfaces
+            .iter()
+            .filter_map(deprecation::Deprecation::from_config)
+            .collect::<Vec<_>>();
+
+        // Deprecations without a sunset are removed last.
+        deprecations.sort_by_key(|d| (d.sunset.is_none(), d.sunset, d.deprecated_at));
+
+        Ok(deprecations.into_iter().map(Deprecation::from).collect())
+    }
+
+    /// Get how often each client used a deprecated field or endpoint, most uses first. Only admins can see the usage.
+    async fn usage<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The field as `Type.field` or the endpoint as `METHOD /v1/path`.")]
+        surface: String,
+        #[graphql(desc = "The number of days to look back. Defaults to 30, at most 90.")]
+        days: Option<u32>,
+    ) -> Result<Vec<DeprecatedUsage>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let days = days.unwrap_or(DEFAULT_USAGE_DAYS);
+        if days == 0 || days > MAX_USAGE_DAYS {
+            return Err(GqlError::InvalidInput
+                .with_message("Days must be between 1 and 90")
+                .with_field(vec!["days"]));
+        }
+
+        let usage = deprecated_usage::by_client(&global.db, &surface, days as i64)
+            .await
+            .map_err_gql("Failed to fetch deprecated usage")?;
+
+        Ok(usage.into_iter().map(DeprecatedUsage::from).collect())
+    }
+}
@@ -0,0 +1,249 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_channel_owner;
+use super::models::discord::{DiscordAnnouncement, DiscordAnnouncementKind, DiscordIntegration};
+use crate::{
+    database::{discord_announcement, discord_integration},
+    global::GlobalState,
+    integrations::discord,
+};
+
+async fn load_integration(
+    global: &GlobalState,
+    channel_id: Uuid,
+) -> Result<Option<DiscordIntegration>> {
+    let Some(integration) = sqlx::query_as!(
+        discord_integration::Model,
+        "SELECT * FROM discord_integrations WHERE channel_id = $1",
+        channel_id
+    )
+    .fetch_optional(&*global.db)
+    .await
+    .map_err_gql("Failed to fetch Discord integration")?
+    else {
+        return Ok(None);
+    };
+
+    let announcements = discord_announcement::for_channel(&global.db, channel_id)
+        .await
+        .map_err_gql("Failed to fetch Discord announcements")?;
+
+    Ok(Some(DiscordIntegration::from_model(
+        integration,
+        announcements,
+    )))
+}
+
+#[derive(Default)]
+pub struct DiscordQuery;
+
+#[Object]
+/// The query object for the Discord integration.
+impl DiscordQuery {
+    /// Get the Discord integration of a channel.
+    async fn integration<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Option<DiscordIntegration>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        load_integration(global, channel_id).await
+    }
+}
+
+#[derive(Default)]
+pub struct DiscordMutation;
+
+#[Object]
+/// The mutation object for the Discord integration.
+impl DiscordMutation {
+    /// Post a channel's announcements through a Discord webhook, replacing the current setup.
+    async fn connect_webhook<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The url of the Discord webhook.")] webhook_url: String,
+    ) -> Result<DiscordIntegration> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        if let Err(e) = discord_integration::validate_webhook_url(&webhook_url) {
+            return Err(GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["webhookUrl"]));
+        }
+
+        let webhook_url_encrypted = global
+            .encrypt_secret(&webhook_url)
+            .map_err_gql("Failed to encrypt webhook url")?;
+
+        sqlx::query!(
+            "INSERT INTO discord_integrations (channel_id, webhook_url_encrypted) VALUES ($1, $2) ON CONFLICT (channel_id) DO UPDATE SET webhook_url_encrypted = $2, bot_token_encrypted = NULL, bot_channel_id = NULL, last_error = '', updated_at = NOW()",
+            channel_id,
+            webhook_url_encrypted,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to save Discord integration")?;
+
+        load_integration(global, channel_id)
+            .await?
+            .ok_or_else(|| GqlError::NotFound.with_message("Discord integration not found"))
+    }
+
+    /// Post a channel's announcements with a Discord bot, replacing the current setup.
+    /// The bot needs to be able to send messages in the Discord channel.
+    async fn connect_bot<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The token of the Discord bot.")] bot_token: String,
+        #[graphql(desc = "The id of the Discord channel the bot posts in.")] bot_channel_id: String,
+    ) -> Result<DiscordIntegration> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        if bot_token.is_empty() || bot_token.len() > 100 {
+            return Err(GqlError::InvalidInput
+                .with_message("Bot token must be between 1 and 100 characters")
+                .with_field(vec!["botToken"]));
+        }
+
+        if let Err(e) = discord_integration::validate_bot_channel_id(&bot_channel_id) {
+            return Err(GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["botChannelId"]));
+        }
+
+        let bot_token_encrypted = global
+            .encrypt_secret(&bot_token)
+            .map_err_gql("Failed to encrypt bot token")?;
+
+        sqlx::query!(
+            "INSERT INTO discord_integrations (channel_id, bot_token_encrypted, bot_channel_id) VALUES ($1, $2, $3) ON CONFLICT (channel_id) DO UPDATE SET webhook_url_encrypted = NULL, bot_token_encrypted = $2, bot_channel_id = $3, last_error = '', updated_at = NOW()",
+            channel_id,
+            bot_token_encrypted,
+            bot_channel_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to save Discord integration")?;
+
+        load_integration(global, channel_id)
+            .await?
+            .ok_or_else(|| GqlError::NotFound.with_message("Discord integration not found"))
+    }
+
+    /// Stop posting a channel's announcements on Discord. The announcement templates are kept.
+    async fn disconnect<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        sqlx::query!(
+            "DELETE FROM discord_integrations WHERE channel_id = $1",
+            channel_id
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to delete Discord integration")?;
+
+        Ok(true)
+    }
+
+    /// Change an announcement. Templates can use the variables `{channel}`, `{username}`, `{title}`, `{category}` and `{url}`.
+    async fn update_announcement<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The notification the announcement is for.")]
+        kind: DiscordAnnouncementKind,
+        #[graphql(desc = "Whether the announcement is posted.")] enabled: bool,
+        #[graphql(desc = "The message, or null for the default.")] template: Option<String>,
+    ) -> Result<DiscordAnnouncement> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let kind = discord_announcement::Kind::from(kind);
+        let template = template.unwrap_or_else(|| kind.default_template().to_string());
+
+        if let Err(e) = discord_announcement::validate_template(&template) {
+            return Err(GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["template"]));
+        }
+
+        let announcement = sqlx::query_as!(
+            discord_announcement::Model,
+            "INSERT INTO discord_announcements (channel_id, kind, enabled, template) VALUES ($1, $2, $3, $4) ON CONFLICT (channel_id, kind) DO UPDATE SET enabled = $3, template = $4 RETURNING *",
+            channel_id,
+            i64::from(kind),
+            enabled,
+            template,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to save Discord announcement")?;
+
+        Ok(announcement.into())
+    }
+
+    /// Post an announcement right away with the channel's current stream info, even if it is disabled.
+    /// Whether it worked is shown by the last error of the returned integration.
+    async fn test_announcement<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The notification to post the announcement of.")]
+        kind: DiscordAnnouncementKind,
+    ) -> Result<DiscordIntegration> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let integration = sqlx::query_as!(
+            discord_integration::Model,
+            "SELECT * FROM discord_integrations WHERE channel_id = $1",
+            channel_id
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch Discord integration")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Discord integration not found")
+                .with_field(vec!["channelId"])
+        })?;
+
+        let kind = discord_announcement::Kind::from(kind);
+        let announcement = discord_announcement::for_channel(&global.db, channel_id)
+            .await
+            .map_err_gql("Failed to fetch Discord announcements")?
+            .into_iter()
+            .find(|a| a.kind == kind)
+            .unwrap_or_else(|| discord_announcement::Model::default_for(channel_id, kind));
+
+        // The error is stored on the integration, which is returned below.
+        if let Err(e) = discord::send_announcement(global, &integration, &announcement, None).await
+        {
+            tracing::debug!("test announcement failed: {:#}", e);
+        }
+
+        load_integration(global, channel_id)
+            .await?
+            .ok_or_else(|| GqlError::NotFound.with_message("Discord integration not found"))
+    }
+}
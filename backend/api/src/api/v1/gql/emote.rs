@@ -0,0 +1,584 @@
+use std::collections::{HashMap, HashSet};
+
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_admin, authorize_channel_owner};
+use super::models::emote::{
+    ChatEmote, Emote, EmoteDailyUsage, EmoteProvider, EmoteSlots, EmoteUsage,
+};
+use crate::database::{emote, emote_provider, emote_usage};
+use crate::pb;
+
+const MAX_REVIEW_NOTE_LENGTH: usize = 1000;
+const DEFAULT_PENDING_LIMIT: u32 = 50;
+const MAX_PENDING_LIMIT: u32 = 100;
+const DEFAULT_USAGE_DAYS: u32 = 30;
+const MAX_USAGE_DAYS: u32 = 90;
+const MAX_EXTERNAL_ID_LENGTH: usize = 255;
+const MAX_RESOLVE_NAMES: usize = 100;
+
+fn validate_usage_days(days: Option<u32>) -> Result<i64> {
+    let days = days.unwrap_or(DEFAULT_USAGE_DAYS);
+    if days == 0 || days > MAX_USAGE_DAYS {
+        return Err(GqlError::InvalidInput
+            .with_message("Days must be between 1 and 90")
+            .with_field(vec!["days"]));
+    }
+
+    Ok(days as i64)
+}
+
+#[derive(Default)]
+pub struct EmoteQuery;
+
+#[Object]
+/// The query object for emotes.
+impl EmoteQuery {
+    /// Get the approved emotes of a channel.
+    async fn approved<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<Emote>> {
+        let global = ctx.get_global();
+
+        let emotes = sqlx::query_as!(
+            emote::Model,
+            "SELECT * FROM emotes WHERE channel_id = $1 AND status = $2 ORDER BY name ASC",
+            channel_id,
+            i64::from(emote::Status::Approved),
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch emotes")?;
+
+        Ok(emotes.into_iter().map(Emote::from).collect())
+    }
+
+    /// Get the emotes which can be used in a channel's chat, its own and the ones of the providers it enabled.
+    /// Pass `names` to only resolve the emotes with these names. Own emotes win over provider emotes with the same name.
+    async fn chat<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(
+            desc = "The names of the emotes to resolve, at most 100. All emotes if empty.",
+            default
+        )]
+        names: Vec<String>,
+    ) -> Result<Vec<ChatEmote>> {
+        let global = ctx.get_global();
+
+        if names.len() > MAX_RESOLVE_NAMES {
+            return Err(GqlError::InvalidInput
+                .with_message("At most 100 names can be resolved at once")
+                .with_field(vec!["names"]));
+        }
+
+        let mut emotes = global
+            .chat_emotes(channel_id)
+            .await
+            .map_err_gql("Failed to fetch emotes")?;
+
+        let mut seen = HashSet::new();
+        emotes.retain(|e| {
+            (names.is_empty() || names.contains(&e.name)) && seen.insert(e.name.clone())
+        });
+
+        Ok(emotes.into_iter().map(ChatEmote::from).collect())
+    }
+
+    /// Get all emotes a channel uploaded, including pending and rejected ones, newest first.
+    async fn uploads<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<Emote>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let emotes = sqlx::query_as!(
+            emote::Model,
+            "SELECT * FROM emotes WHERE channel_id = $1 ORDER BY created_at DESC",
+            channel_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch emotes")?;
+
+        Ok(emotes.into_iter().map(Emote::from).collect())
+    }
+
+    /// Get the number of emote slots a channel has and how many are in use.
+    async fn slots<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<EmoteSlots> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let sub_points = emote::sub_points(&global.db, channel_id)
+            .await
+            .map_err_gql("Failed to fetch sub points")?;
+        let used = emote::used_slots(&global.db, channel_id)
+            .await
+            .map_err_gql("Failed to fetch emotes")?;
+
+        Ok(EmoteSlots {
+            used,
+            total: emote::slot_count(sub_points, &global.config.emotes),
+            sub_points,
+        })
+    }
+
+    /// Get how often each approved emote of a channel was used in its chat, least used first.
+    /// Emotes nobody used are included, so they can be removed to free up slots.
+    async fn usage<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The number of days to look back. Defaults to 30, at most 90.")]
+        days: Option<u32>,
+    ) -> Result<Vec<EmoteUsage>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let days = validate_usage_days(days)?;
+
+        let mut emotes = sqlx::query_as!(
+            emote::Model,
+            "SELECT * FROM emotes WHERE channel_id = $1 AND status = $2",
+            channel_id,
+            i64::from(emote::Status::Approved),
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch emotes")?
+        .into_iter()
+        .map(|e| (e.id, e))
+        .collect::<HashMap<_, _>>();
+
+        let summary = emote_usage::summary(&global.db, channel_id, days)
+            .await
+            .map_err_gql("Failed to fetch emote usage")?;
+
+        Ok(summary
+            .into_iter()
+            .filter_map(|usage| {
+                Some(EmoteUsage {
+                    emote: emotes.remove(&usage.emote_id)?.into(),
+                    uses: usage.uses,
+                    last_used: usage.last_used.map(Into::into),
+                })
+            })
+            .collect())
+    }
+
+    /// Get how often an emote was used per day, oldest first. Days without any use are left out.
+    async fn daily_usage<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the emote.")] emote_id: Uuid,
+        #[graphql(desc = "The number of days to look back. Defaults to 30, at most 90.")]
+        days: Option<u32>,
+    ) -> Result<Vec<EmoteDailyUsage>> {
+        let global = ctx.get_global();
+
+        let emote = sqlx::query_as!(emote::Model, "SELECT * FROM emotes WHERE id = $1", emote_id)
+            .fetch_optional(&*global.db)
+            .await
+            .map_err_gql("Failed to fetch emote")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("Emote not found")
+                    .with_field(vec!["emoteId"])
+            })?;
+
+        authorize_channel_owner(ctx, emote.channel_id).await?;
+
+        let days = validate_usage_days(days)?;
+
+        let usage = emote_usage::daily(&global.db, emote.id, days)
+            .await
+            .map_err_gql("Failed to fetch emote usage")?;
+
+        Ok(usage.into_iter().map(EmoteDailyUsage::from).collect())
+    }
+
+    /// Get the names of the third-party emote providers channels can enable.
+    async fn available_providers<'ctx>(&self, ctx: &Context<'_>) -> Vec<String> {
+        let global = ctx.get_global();
+
+        let mut providers = global
+            .config
+            .emotes
+            .providers
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        providers.sort();
+
+        providers
+    }
+
+    /// Get the third-party emote providers a channel enabled for its chat.
+    async fn providers<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<EmoteProvider>> {
+        let global = ctx.get_global();
+
+        let providers = sqlx::query_as!(
+            emote_provider::Model,
+            "SELECT * FROM channel_emote_providers WHERE channel_id = $1 ORDER BY created_at ASC",
+            channel_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch emote providers")?;
+
+        Ok(providers.into_iter().map(EmoteProvider::from).collect())
+    }
+
+    /// Get the emotes which are waiting to be reviewed, oldest first. Only admins can do this.
+    async fn pending_review<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The maximum number of emotes to return. Defaults to 50, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<Emote>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let limit = limit.unwrap_or(DEFAULT_PENDING_LIMIT);
+        if limit == 0 || limit > MAX_PENDING_LIMIT {
+            return Err(GqlError::InvalidInput
+                .with_message("Limit must be between 1 and 100")
+                .with_field(vec!["limit"]));
+        }
+
+        let emotes = sqlx::query_as!(
+            emote::Model,
+            "SELECT * FROM emotes WHERE status = $1 ORDER BY created_at ASC LIMIT $2",
+            i64::from(emote::Status::PendingReview),
+            limit as i64,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch emotes")?;
+
+        Ok(emotes.into_iter().map(Emote::from).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct EmoteMutation;
+
+#[Object]
+/// The mutation object for emotes.
+impl EmoteMutation {
+    /// Submit an emote for review. It takes up one of the channel's slots unless it gets rejected.
+    /// The image is fetched to check its size, emotes which fail the automatic checks are rejected right away.
+    async fn submit<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The name used to type the emote in chat.")] name: String,
+        #[graphql(desc = "The url of the uploaded emote image, a png, gif or webp image.")]
+        image_url: String,
+    ) -> Result<Emote> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        if let Err(e) = emote::validate_name(&name) {
+            return Err(GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["name"]));
+        }
+
+        if reqwest::Url::parse(&image_url)
+            .map(|url| url.scheme() != "https")
+            .unwrap_or(true)
+        {
+            return Err(GqlError::InvalidInput
+                .with_message("Image url must be a valid https url")
+                .with_field(vec!["imageUrl"]));
+        }
+
+        let sub_points = emote::sub_points(&global.db, channel_id)
+            .await
+            .map_err_gql("Failed to fetch sub points")?;
+        let slots = emote::slot_count(sub_points, &global.config.emotes);
+        let used = emote::used_slots(&global.db, channel_id)
+            .await
+            .map_err_gql("Failed to fetch emotes")?;
+
+        // Saves fetching the image, the slots are counted again when the emote is saved.
+        if used >= slots {
+            return Err(GqlError::InvalidInput
+                .with_message("All emote slots are in use")
+                .with_field(vec!["channelId"]));
+        }
+
+        let image = global
+            .fetch_image(&image_url, global.config.emotes.max_file_size as usize)
+            .await
+            .map_err(|e| {
+                tracing::debug!("failed to fetch emote image: {:#}", e);
+                GqlError::InvalidInput
+                    .with_message("Emote image could not be fetched or is too large")
+                    .with_field(vec!["imageUrl"])
+            })?;
+
+        let (width, height) = emote::image_dimensions(&image).ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("Emote image must be a png, gif or webp image")
+                .with_field(vec!["imageUrl"])
+        })?;
+
+        if let Err(e) = emote::validate_dimensions(width, height, global.config.emotes.max_size) {
+            return Err(GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["imageUrl"]));
+        }
+
+        let nsfw_score = global
+            .nsfw_score(&image_url)
+            .await
+            .map_err_gql("Failed to check emote image")?;
+
+        // The chat serves the image the image processor made, not the upload.
+        let processed_url = global
+            .process_image(&image_url, &emote::image_prefix(channel_id, Uuid::new_v4()))
+            .await
+            .map_err_gql("Failed to queue emote image")?;
+
+        let (status, review_note) = if nsfw_score >= global.config.emotes.nsfw_threshold {
+            (emote::Status::Rejected, "Rejected by the automatic checks")
+        } else {
+            (emote::Status::PendingReview, "")
+        };
+
+        // Replace a rejected emote with the same name, so channels can fix and resubmit it.
+        // The slots are counted in the same statement, so emotes submitted at once can't take more than there are.
+        let emote = sqlx::query_as!(
+            emote::Model,
+            "INSERT INTO emotes (channel_id, name, image_url, width, height, nsfw_score, status, review_note, reviewed_at) SELECT $1, $2, $3, $4, $5, $6, $7, $8, CASE WHEN $7 = $9::int8 THEN NOW() END WHERE $7 = $9 OR (SELECT COUNT(*) FROM emotes WHERE channel_id = $1 AND status != $9) < $10 ON CONFLICT (channel_id, name) DO UPDATE SET image_url = $3, width = $4, height = $5, nsfw_score = $6, status = $7, review_note = $8, reviewed_by = NULL, created_at = NOW(), reviewed_at = CASE WHEN $7 = $9 THEN NOW() END WHERE emotes.status = $9 RETURNING *",
+            channel_id,
+            name,
+            processed_url,
+            width as i64,
+            height as i64,
+            nsfw_score as i64,
+            i64::from(status),
+            review_note,
+            i64::from(emote::Status::Rejected),
+            slots,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to save emote")?;
+
+        let Some(emote) = emote else {
+            let used = emote::used_slots(&global.db, channel_id)
+                .await
+                .map_err_gql("Failed to fetch emotes")?;
+
+            if used >= slots {
+                return Err(GqlError::InvalidInput
+                    .with_message("All emote slots are in use")
+                    .with_field(vec!["channelId"]));
+            }
+
+            return Err(GqlError::InvalidInput
+                .with_message("An emote with this name already exists")
+                .with_field(vec!["name"]));
+        };
+
+        Ok(emote.into())
+    }
+
+    /// Approve or reject an emote. Only admins can do this. The channel is notified about the decision.
+    /// Pending emotes can be approved or rejected, approved ones can only be taken down by rejecting them.
+    async fn review<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the emote.")] id: Uuid,
+        #[graphql(desc = "If the emote is approved.")] approved: bool,
+        #[graphql(desc = "A note explaining the decision, visible to the channel owner.")]
+        note: Option<String>,
+    ) -> Result<Emote> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        let note = note.unwrap_or_default();
+        if note.len() > MAX_REVIEW_NOTE_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Note too long")
+                .with_field(vec!["note"]));
+        }
+
+        let status = if approved {
+            emote::Status::Approved
+        } else {
+            emote::Status::Rejected
+        };
+
+        let emote = sqlx::query_as!(emote::Model, "SELECT * FROM emotes WHERE id = $1", id)
+            .fetch_optional(&*global.db)
+            .await
+            .map_err_gql("Failed to fetch emote")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("Emote not found")
+                    .with_field(vec!["id"])
+            })?;
+
+        if !emote.status.can_become(status) {
+            return Err(GqlError::InvalidInput
+                .with_message(match status {
+                    emote::Status::Approved => "Only pending emotes can be approved",
+                    _ => "This emote was already rejected",
+                })
+                .with_field(vec!["approved"]));
+        }
+
+        // The emote may have been reviewed by someone else in the meantime.
+        let emote = sqlx::query_as!(
+            emote::Model,
+            "UPDATE emotes SET status = $1, review_note = $2, reviewed_by = $3, reviewed_at = NOW() WHERE id = $4 AND status = $5 RETURNING *",
+            i64::from(status),
+            note,
+            session.user_id,
+            emote.id,
+            i64::from(emote.status),
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to update emote")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("The emote was reviewed in the meantime")
+                .with_field(vec!["id"])
+        })?;
+
+        // The review is already saved, so a failed notification must not fail the mutation.
+        match global
+            .publish_event(
+                emote.channel_id,
+                &pb::scuffle::events::EmoteReviewed {
+                    emote_id: emote.id.to_string(),
+                    name: emote.name.clone(),
+                    approved,
+                    note: emote.review_note.clone(),
+                },
+            )
+            .await
+        {
+            Ok(()) => {}
+            Err(e) => tracing::error!("failed to publish emote review {}: {}", emote.id, e),
+        };
+
+        Ok(emote.into())
+    }
+
+    /// Delete an emote, freeing up its slot.
+    async fn remove<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the emote.")] id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let emote = sqlx::query_as!(emote::Model, "SELECT * FROM emotes WHERE id = $1", id)
+            .fetch_optional(&*global.db)
+            .await
+            .map_err_gql("Failed to fetch emote")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("Emote not found")
+                    .with_field(vec!["id"])
+            })?;
+
+        authorize_channel_owner(ctx, emote.channel_id).await?;
+
+        sqlx::query!("DELETE FROM emotes WHERE id = $1", emote.id)
+            .execute(&*global.db)
+            .await
+            .map_err_gql("Failed to delete emote")?;
+
+        Ok(true)
+    }
+
+    /// Enable a third-party emote provider for a channel's chat, or change the emote set used.
+    async fn enable_provider<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The name of the provider.")] provider: String,
+        #[graphql(desc = "The id of the channel's emote set at the provider.")] external_id: String,
+    ) -> Result<EmoteProvider> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        if !global.config.emotes.providers.contains_key(&provider) {
+            return Err(GqlError::InvalidInput
+                .with_message("Unknown emote provider")
+                .with_field(vec!["provider"]));
+        }
+
+        if external_id.is_empty() || external_id.len() > MAX_EXTERNAL_ID_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("External id must be between 1 and 255 characters")
+                .with_field(vec!["externalId"]));
+        }
+
+        let provider = sqlx::query_as!(
+            emote_provider::Model,
+            "INSERT INTO channel_emote_providers (channel_id, provider, external_id) VALUES ($1, $2, $3) ON CONFLICT (channel_id, provider) DO UPDATE SET external_id = $3 RETURNING *",
+            channel_id,
+            provider,
+            external_id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to save emote provider")?;
+
+        Ok(provider.into())
+    }
+
+    /// Disable a third-party emote provider for a channel's chat.
+    async fn disable_provider<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The name of the provider.")] provider: String,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let res = sqlx::query!(
+            "DELETE FROM channel_emote_providers WHERE channel_id = $1 AND provider = $2",
+            channel_id,
+            provider,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to delete emote provider")?;
+
+        Ok(res.rows_affected() > 0)
+    }
+}
@@ -5,6 +5,7 @@ use std::{
 };
 
 use async_graphql::ErrorExtensions;
+use common::database::{DatabaseError, ErrorKind};
 
 pub type Result<T, E = GqlErrorInterface> = std::result::Result<T, E>;
 
@@ -65,6 +66,10 @@ pub enum GqlError {
     Unauthorized,
     /// Not Found
     NotFound,
+    /// The request conflicted with a concurrent one, sending it again can succeed.
+    Conflict,
+    /// The action needs two-factor authentication, which the user has not enabled.
+    TwoFaRequired,
 }
 
 impl Display for GqlError {
@@ -76,6 +81,8 @@ impl Display for GqlError {
             GqlError::NotImplemented => write!(f, "NotImplemented"),
             GqlError::Unauthorized => write!(f, "Unauthorized"),
             GqlError::NotFound => write!(f, "NotFound"),
+            GqlError::Conflict => write!(f, "Conflict"),
+            GqlError::TwoFaRequired => write!(f, "TwoFaRequired"),
         }
     }
 }
@@ -213,9 +220,22 @@ where
     {
         match self {
             Ok(v) => Ok(v),
-            Err(e) => Err(GqlErrorInterface::from(ctx)
-                .with_source(Some(e.into()))
-                .with_location(Location::caller())),
+            Err(e) => {
+                let e: anyhow::Error = e.into();
+                let mut err = GqlErrorInterface::from(ctx);
+
+                // Conflicts left after retrying are not a bug, the client is told it can try again.
+                if err.kind == GqlError::InternalServerError
+                    && matches!(
+                        e.database_error().map(ErrorKind::of),
+                        Some(ErrorKind::Retryable | ErrorKind::Ambiguous)
+                    )
+                {
+                    err.kind = GqlError::Conflict;
+                }
+
+                Err(err.with_source(Some(e)).with_location(Location::caller()))
+            }
         }
     }
 }
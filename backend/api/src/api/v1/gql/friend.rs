@@ -0,0 +1,311 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_user;
+use super::models::friend::{Friend, FriendRequest};
+use super::pagination::{page_limit, Cursor};
+use crate::database::{friend, user_block};
+
+const DEFAULT_FRIENDS_LIMIT: u32 = 50;
+const MAX_FRIENDS_LIMIT: u32 = 100;
+const DEFAULT_FRIEND_REQUESTS_LIMIT: u32 = 50;
+const MAX_FRIEND_REQUESTS_LIMIT: u32 = 100;
+
+#[derive(Default)]
+pub struct FriendQuery;
+
+#[Object]
+/// The query object for friends. Unlike follows, friendships are mutual and start with a request the other user accepts.
+impl FriendQuery {
+    /// Get the friends of the logged in user, the last one made first.
+    /// To fetch the next page pass the `cursor` of the last friend as `after`.
+    async fn friends<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Only return friends after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(desc = "The maximum number of friends to return. Defaults to 50, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<Friend>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let limit = page_limit(limit, DEFAULT_FRIENDS_LIMIT, MAX_FRIENDS_LIMIT)?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let friends = sqlx::query_as!(
+            friend::Model,
+            "SELECT * FROM friends WHERE user_id = $1 AND ($2::timestamptz IS NULL OR (created_at, friend_id) < ($2, $3::uuid)) ORDER BY created_at DESC, friend_id DESC LIMIT $4",
+            session.user_id,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch friends")?;
+
+        Ok(friends.into_iter().map(Friend::from).collect())
+    }
+
+    /// Get the pending friend requests sent to the logged in user, or the ones they sent, the last one sent first.
+    /// To fetch the next page pass the `cursor` of the last request as `after`.
+    async fn requests<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(
+            desc = "Whether to return the requests sent to the logged in user, or the ones they sent.",
+            default = true
+        )]
+        incoming: bool,
+        #[graphql(desc = "Only return requests after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(desc = "The maximum number of requests to return. Defaults to 50, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<FriendRequest>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let limit = page_limit(
+            limit,
+            DEFAULT_FRIEND_REQUESTS_LIMIT,
+            MAX_FRIEND_REQUESTS_LIMIT,
+        )?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let requests = if incoming {
+            sqlx::query_as!(
+                friend::Request,
+                "SELECT * FROM friend_requests WHERE friend_id = $1 AND ($2::timestamptz IS NULL OR (created_at, user_id) < ($2, $3::uuid)) ORDER BY created_at DESC, user_id DESC LIMIT $4",
+                session.user_id,
+                after_time,
+                after_id,
+                limit,
+            )
+            .fetch_all(&*global.db)
+            .await
+        } else {
+            sqlx::query_as!(
+                friend::Request,
+                "SELECT * FROM friend_requests WHERE user_id = $1 AND ($2::timestamptz IS NULL OR (created_at, friend_id) < ($2, $3::uuid)) ORDER BY created_at DESC, friend_id DESC LIMIT $4",
+                session.user_id,
+                after_time,
+                after_id,
+                limit,
+            )
+            .fetch_all(&*global.db)
+            .await
+        }
+        .map_err_gql("Failed to fetch friend requests")?;
+
+        Ok(requests
+            .into_iter()
+            .map(|r| FriendRequest::new(r, session.user_id))
+            .collect())
+    }
+}
+
+#[derive(Default)]
+pub struct FriendMutation;
+
+#[Object]
+impl FriendMutation {
+    /// Ask a user to be friends. Sending the same request again keeps the time of the first one.
+    async fn send_request<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the user to ask.")] user_id: Uuid,
+    ) -> Result<FriendRequest> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        if user_id == session.user_id {
+            return Err(GqlError::InvalidInput
+                .with_message("You can't befriend yourself")
+                .with_field(vec!["userId"]));
+        }
+
+        global
+            .user_by_id_loader
+            .load_one(user_id)
+            .await
+            .map_err_gql("Failed to fetch user")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("User not found")
+                    .with_field(vec!["userId"])
+            })?;
+
+        let blocked = user_block::between(&*global.db, session.user_id, user_id)
+            .await
+            .map_err_gql("Failed to fetch blocks")?;
+        if blocked {
+            return Err(GqlError::InvalidInput
+                .with_message("You can't befriend this user")
+                .with_field(vec!["userId"]));
+        }
+
+        let friends = friend::are_friends(&*global.db, session.user_id, user_id)
+            .await
+            .map_err_gql("Failed to fetch friends")?;
+        if friends {
+            return Err(GqlError::InvalidInput
+                .with_message("You are already friends")
+                .with_field(vec!["userId"]));
+        }
+
+        let asked = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM friend_requests WHERE user_id = $1 AND friend_id = $2) AS "asked!""#,
+            user_id,
+            session.user_id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch friend requests")?;
+        if asked {
+            return Err(GqlError::InvalidInput
+                .with_message(
+                    "This user already asked you to be friends, accept their request instead",
+                )
+                .with_field(vec!["userId"]));
+        }
+
+        let request = sqlx::query_as!(
+            friend::Request,
+            "INSERT INTO friend_requests (user_id, friend_id) VALUES ($1, $2) ON CONFLICT (user_id, friend_id) DO UPDATE SET user_id = EXCLUDED.user_id RETURNING *",
+            session.user_id,
+            user_id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to send friend request")?;
+
+        Ok(FriendRequest::new(request, session.user_id))
+    }
+
+    /// Accept a friend request the user sent to the logged in user, which makes them friends.
+    async fn accept_request<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the user who sent the request.")] user_id: Uuid,
+    ) -> Result<Friend> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to accept friend request")?;
+
+        sqlx::query!(
+            "DELETE FROM friend_requests WHERE user_id = $1 AND friend_id = $2 RETURNING user_id",
+            user_id,
+            session.user_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to accept friend request")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Friend request not found")
+                .with_field(vec!["userId"])
+        })?;
+
+        // The friendship is stored for both users, so either can list it and remove it.
+        let friends = sqlx::query_as!(
+            friend::Model,
+            "INSERT INTO friends (user_id, friend_id) VALUES ($1, $2), ($2, $1) ON CONFLICT (user_id, friend_id) DO UPDATE SET user_id = EXCLUDED.user_id RETURNING *",
+            session.user_id,
+            user_id,
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err_gql("Failed to accept friend request")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to accept friend request")?;
+
+        let friend = friends
+            .into_iter()
+            .find(|f| f.user_id == session.user_id)
+            .ok_or_else(|| {
+                GqlError::InternalServerError.with_message("Failed to accept friend request")
+            })?;
+
+        Ok(friend.into())
+    }
+
+    /// Decline a friend request the user sent to the logged in user. Returns false if there was no request.
+    async fn decline_request<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the user who sent the request.")] user_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let res = sqlx::query!(
+            "DELETE FROM friend_requests WHERE user_id = $1 AND friend_id = $2",
+            user_id,
+            session.user_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to decline friend request")?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Take back a friend request the logged in user sent. Returns false if there was no request.
+    async fn cancel_request<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the user who was asked.")] user_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let res = sqlx::query!(
+            "DELETE FROM friend_requests WHERE user_id = $1 AND friend_id = $2",
+            session.user_id,
+            user_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to cancel friend request")?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Stop being friends with a user, for both of them. Returns false if they were not friends.
+    async fn remove<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the friend.")] user_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let res = sqlx::query!(
+            "DELETE FROM friends WHERE (user_id = $1 AND friend_id = $2) OR (user_id = $2 AND friend_id = $1)",
+            session.user_id,
+            user_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to remove friend")?;
+
+        Ok(res.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,244 @@
+use async_graphql::{Context, Object};
+use common::database::ErrorKind;
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_channel_owner, authorize_user};
+use super::models::giveaway::{Giveaway, GiveawayEntry};
+use crate::database::{giveaway, giveaway_entry};
+use crate::global::{giveaway::EnterError, GlobalState};
+
+async fn open_giveaway(global: &GlobalState, channel_id: Uuid) -> Result<giveaway::Model> {
+    sqlx::query_as!(
+        giveaway::Model,
+        "SELECT * FROM giveaways WHERE channel_id = $1 AND status = $2",
+        channel_id,
+        i64::from(giveaway::Status::Open),
+    )
+    .fetch_optional(&*global.db)
+    .await
+    .map_err_gql("Failed to fetch giveaway")?
+    .ok_or_else(|| {
+        GqlError::NotFound
+            .with_message("This chat has no open giveaway")
+            .with_field(vec!["channelId"])
+    })
+}
+
+#[derive(Default)]
+pub struct GiveawayQuery;
+
+#[Object]
+/// The query object for giveaways.
+impl GiveawayQuery {
+    /// Get the last giveaway of a channel, the open one if it is running one.
+    async fn current<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Option<Giveaway>> {
+        let global = ctx.get_global();
+
+        let giveaway = sqlx::query_as!(
+            giveaway::Model,
+            "SELECT * FROM giveaways WHERE channel_id = $1 ORDER BY created_at DESC LIMIT 1",
+            channel_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch giveaway")?;
+
+        Ok(giveaway.map(Giveaway::from))
+    }
+
+    /// Get the entries of a giveaway ordered by user id, the order the draw hashes them in.
+    /// With the seed they are everything needed to check the winner.
+    async fn entries<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the giveaway.")] giveaway_id: Uuid,
+    ) -> Result<Vec<GiveawayEntry>> {
+        let global = ctx.get_global();
+
+        let entries = sqlx::query_as!(
+            giveaway_entry::Model,
+            "SELECT * FROM giveaway_entries WHERE giveaway_id = $1 ORDER BY user_id ASC",
+            giveaway_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch entries")?;
+
+        Ok(entries.into_iter().map(GiveawayEntry::from).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct GiveawayMutation;
+
+#[Object]
+/// The mutation object for giveaways. A channel runs one giveaway at a time.
+impl GiveawayMutation {
+    /// Start a giveaway in the chat of a channel and announce it there. Only the broadcaster can do this.
+    async fn start<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "What is given away.")] title: String,
+        #[graphql(
+            desc = "The chat message which enters the giveaway. Viewers can only enter with the button if not set."
+        )]
+        keyword: Option<String>,
+        #[graphql(desc = "Whether only followers can enter.", default)] followers_only: bool,
+        #[graphql(desc = "The tickets a follower gets, at most 10.", default = 1)]
+        follower_weight: u32,
+        #[graphql(desc = "The tickets a subscriber gets, at most 10.", default = 1)]
+        subscriber_weight: u32,
+    ) -> Result<Giveaway> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let keyword = keyword.map(|k| k.trim().to_lowercase());
+
+        giveaway::validate(
+            &title,
+            keyword.as_deref(),
+            follower_weight,
+            subscriber_weight,
+        )
+        .map_err(|(field, e)| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec![field])
+        })?;
+
+        let seed = giveaway::generate_seed();
+
+        let giveaway = sqlx::query_as!(
+            giveaway::Model,
+            "INSERT INTO giveaways (channel_id, title, keyword, followers_only, follower_weight, subscriber_weight, seed, commitment) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+            channel_id,
+            title.trim(),
+            keyword,
+            followers_only,
+            follower_weight as i32,
+            subscriber_weight as i32,
+            seed,
+            giveaway::commitment(&seed),
+        )
+        .fetch_one(&*global.db)
+        .await;
+
+        let giveaway = match giveaway {
+            Err(e) if ErrorKind::of(&e) == ErrorKind::UniqueViolation => {
+                return Err(GqlError::InvalidInput
+                    .with_message("A giveaway is already running in this chat")
+                    .with_field(vec!["channelId"]));
+            }
+            giveaway => giveaway.map_err_gql("Failed to start giveaway")?,
+        };
+
+        let content = match &giveaway.keyword {
+            Some(keyword) => format!(
+                "Giveaway: {}! Type {} in chat to enter.",
+                giveaway.title, keyword
+            ),
+            None => format!("Giveaway: {}! Press the button to enter.", giveaway.title),
+        };
+
+        // The giveaway is running either way, viewers still see it next to the chat.
+        if let Err(e) = global.announce_giveaway(channel_id, content).await {
+            tracing::error!("failed to announce giveaway {}: {:#}", giveaway.id, e);
+        }
+
+        Ok(giveaway.into())
+    }
+
+    /// Enter the open giveaway of a channel with the logged in user. The tickets are fixed when entering.
+    async fn enter<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<GiveawayEntry> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let giveaway = open_giveaway(global, channel_id).await?;
+
+        match global.enter_giveaway(&giveaway, session.user_id).await {
+            Ok(entry) => Ok(entry.into()),
+            Err(EnterError::NotFollowing) => {
+                Err(GqlError::Unauthorized.with_message("Only followers can enter this giveaway"))
+            }
+            Err(EnterError::AlreadyEntered) => {
+                Err(GqlError::InvalidInput.with_message("You already entered this giveaway"))
+            }
+            Err(EnterError::Database(e)) => Err(e).map_err_gql("Failed to enter giveaway"),
+        }
+    }
+
+    /// Draw the winner of the open giveaway of a channel and announce them in chat. Only the broadcaster can do this.
+    async fn draw<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Giveaway> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let giveaway = global
+            .draw_giveaway(channel_id)
+            .await
+            .map_err_gql("Failed to draw giveaway")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("This chat has no open giveaway")
+                    .with_field(vec!["channelId"])
+            })?;
+
+        Ok(giveaway.into())
+    }
+
+    /// Cancel the open giveaway of a channel without a winner. Only the broadcaster can do this.
+    async fn cancel<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Giveaway> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let giveaway = sqlx::query_as!(
+            giveaway::Model,
+            "UPDATE giveaways SET status = $2, closed_at = NOW() WHERE channel_id = $1 AND status = $3 RETURNING *",
+            channel_id,
+            i64::from(giveaway::Status::Cancelled),
+            i64::from(giveaway::Status::Open),
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to cancel giveaway")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("This chat has no open giveaway")
+                .with_field(vec!["channelId"])
+        })?;
+
+        if let Err(e) = global
+            .announce_giveaway(
+                channel_id,
+                format!("The giveaway was cancelled: {}", giveaway.title),
+            )
+            .await
+        {
+            tracing::error!("failed to announce giveaway {}: {:#}", giveaway.id, e);
+        }
+
+        Ok(giveaway.into())
+    }
+}
@@ -0,0 +1,176 @@
+use async_graphql::{parser::types::OperationType, Context};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use crate::database::{global_role, session, user_suspension};
+use crate::dataloader::user_permissions::UserPermission;
+use crate::global::{ip_reputation::Action, GlobalState};
+
+/// Two-factor authentication is only enforced for changes, privileged users can still look around without it.
+fn is_mutation(ctx: &Context<'_>) -> bool {
+    ctx.query_env.operation.node.ty == OperationType::Mutation
+}
+
+/// Rejects users who did not enable two-factor authentication, if it is required for what they are doing.
+/// Enabling it is not enough, the session also has to have checked a passkey or recovery code, when logging in or reauthenticating.
+/// The session is read again, a websocket may have been reauthenticated by another request in the meantime.
+async fn require_two_fa(
+    global: &GlobalState,
+    session: &session::Model,
+    required: bool,
+) -> Result<()> {
+    if !required {
+        return Ok(());
+    }
+
+    let enabled = global
+        .user_by_id_loader
+        .load_one(session.user_id)
+        .await
+        .map_err_gql("Failed to fetch user")?
+        .map_or(false, |user| user.passkey_required);
+
+    if !enabled {
+        return Err(GqlError::TwoFaRequired
+            .with_message("You need to enable two-factor authentication to do this"));
+    }
+
+    let two_fa_at = sqlx::query_scalar!("SELECT two_fa_at FROM sessions WHERE id = $1", session.id)
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch session")?;
+
+    if two_fa_at.is_none() {
+        return Err(GqlError::TwoFaRequired.with_message(
+            "You need to reauthenticate with your passkey or a recovery code to do this",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Makes sure the request is authenticated and returns the session, even if the user is suspended.
+/// Only use this for what a suspended user still has to be able to do, like appealing.
+pub async fn authorize_session(ctx: &Context<'_>) -> Result<(session::Model, UserPermission)> {
+    let global = ctx.get_global();
+    let request_context = ctx.get_session();
+
+    request_context
+        .get_session(global)
+        .await?
+        .ok_or_else(|| GqlError::Unauthorized.with_message("You need to be logged in"))
+}
+
+/// Makes sure the request is authenticated by a user who is not suspended and returns the session.
+pub async fn authorize_user(ctx: &Context<'_>) -> Result<(session::Model, UserPermission)> {
+    let global = ctx.get_global();
+    let (session, perms) = authorize_session(ctx).await?;
+
+    if user_suspension::active(&global.db, session.user_id)
+        .await
+        .map_err_gql("Failed to fetch suspension")?
+        .is_some()
+    {
+        return Err(GqlError::Unauthorized.with_message("Your account is suspended"));
+    }
+
+    Ok((session, perms))
+}
+
+/// Makes sure the logged in user reauthenticated recently, for sensitive actions like changing payout details.
+/// The session is read again, a websocket may have been elevated by another request in the meantime.
+pub async fn authorize_elevated(ctx: &Context<'_>) -> Result<(session::Model, UserPermission)> {
+    let global = ctx.get_global();
+    let (session, perms) = authorize_user(ctx).await?;
+
+    let elevated_until = sqlx::query_scalar!(
+        "SELECT elevated_until FROM sessions WHERE id = $1",
+        session.id,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .map_err_gql("Failed to fetch session")?;
+
+    let session = session::Model {
+        elevated_until,
+        ..session
+    };
+
+    if !session.is_elevated() {
+        return Err(GqlError::Unauthorized.with_message("You need to reauthenticate to do this"));
+    }
+
+    Ok((session, perms))
+}
+
+/// Makes sure the logged in user is the owner of the channel or an admin.
+/// Changes need two-factor authentication if the channel requires it for moderation, or if an admin makes them and admins need it.
+pub async fn authorize_channel_owner(
+    ctx: &Context<'_>,
+    channel_id: Uuid,
+) -> Result<(session::Model, UserPermission)> {
+    let global = ctx.get_global();
+    let (session, perms) = authorize_user(ctx).await?;
+
+    let as_admin = session.user_id != channel_id;
+    if as_admin
+        && !perms
+            .permissions
+            .has_permission(global_role::Permission::Admin)
+    {
+        return Err(GqlError::Unauthorized
+            .with_message("You are not allowed to access this channel")
+            .with_field(vec!["channelId"]));
+    }
+
+    if is_mutation(ctx) {
+        let required = (as_admin && global.config.passkeys.required_for_admins)
+            || global
+                .user_by_id_loader
+                .load_one(channel_id)
+                .await
+                .map_err_gql("Failed to fetch channel")?
+                .map_or(false, |channel| channel.moderation_two_fa_required);
+
+        require_two_fa(global, &session, required).await?;
+    }
+
+    Ok((session, perms))
+}
+
+/// Makes sure the logged in user is an admin. Changes need two-factor authentication if admins are configured to need it.
+pub async fn authorize_admin(ctx: &Context<'_>) -> Result<(session::Model, UserPermission)> {
+    let global = ctx.get_global();
+    let (session, perms) = authorize_user(ctx).await?;
+
+    if !perms
+        .permissions
+        .has_permission(global_role::Permission::Admin)
+    {
+        return Err(GqlError::Unauthorized.with_message("You need to be an admin"));
+    }
+
+    if is_mutation(ctx) {
+        require_two_fa(global, &session, global.config.passkeys.required_for_admins).await?;
+    }
+
+    Ok((session, perms))
+}
+
+/// Rejects requests from addresses the IP reputation provider blocks, and returns if the address has to solve a captcha.
+pub async fn check_ip_reputation(ctx: &Context<'_>) -> Result<Action> {
+    let global = ctx.get_global();
+    let request_context = ctx.get_session();
+
+    let Some(ip) = request_context.client_ip() else {
+        return Ok(Action::Allow);
+    };
+
+    match global.ip_reputation(ip).await {
+        Action::Block => Err(GqlError::Unauthorized.with_message(
+            "Requests from your network are not allowed, try again without a proxy or VPN",
+        )),
+        action => Ok(action),
+    }
+}
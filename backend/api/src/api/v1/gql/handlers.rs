@@ -1,12 +1,13 @@
-use std::{future, str::FromStr, sync::Arc};
+use std::{future, str::FromStr, sync::Arc, time::Duration};
 
 use crate::database::session;
 use async_graphql::{
     http::{WebSocketProtocols, WsMessage},
+    parser::types::OperationType,
     Data, ErrorExtensions,
 };
 use futures_util::{SinkExt, StreamExt};
-use hyper::{body::HttpBody, header, Body, Request, Response, StatusCode};
+use hyper::{body::HttpBody, header, Body, HeaderMap, Request, Response, StatusCode};
 use hyper_tungstenite::{
     tungstenite::{
         protocol::{frame::coding::CloseCode, CloseFrame},
@@ -14,27 +15,140 @@ use hyper_tungstenite::{
     },
     HyperWebsocket,
 };
+use prost::Message as _;
+use ring::constant_time;
 use routerify::prelude::RequestExt;
 use serde_json::json;
 use tokio::select;
 
 use crate::{
     api::{
+        deadline,
         error::{Result, ResultExt as _, RouteError},
         ext::RequestExt as _,
+        middleware::csrf,
         v1::jwt::JwtState,
     },
+    config::GqlConfig,
     dataloader::user_permissions::UserPermission,
-    global::GlobalState,
+    global::{ip_reputation, GlobalState},
+    pb::{scuffle::events::UserSessionsRevoked, Event},
+    subscription::RecvError,
 };
 
 use super::{
     error::{GqlError, ResultExt as _},
     ext::RequestExt as _,
     request_context::RequestContext,
-    MySchema,
+    MySchema, Schemas,
 };
 
+/// If the query contains a mutation. Queries which fail to parse are left to the executor to reject.
+fn is_mutation(query: &str) -> bool {
+    async_graphql::parser::parse_query(query)
+        .map(|document| {
+            document
+                .operations
+                .iter()
+                .any(|(_, operation)| operation.node.ty == OperationType::Mutation)
+        })
+        .unwrap_or(false)
+}
+
+/// Checks the X-Introspection-Token header against the allow-listed partner tokens.
+pub fn has_introspection_token(config: &GqlConfig, headers: &HeaderMap) -> bool {
+    let Some(token) = headers
+        .get("x-introspection-token")
+        .map(|token| token.as_bytes())
+    else {
+        return false;
+    };
+
+    config
+        .introspection_tokens
+        .iter()
+        .any(|allowed| constant_time::verify_slices_are_equal(allowed.as_bytes(), token).is_ok())
+}
+
+/// Resolves once the session the websocket is logged in with is revoked. The websocket can log in or out at any time,
+/// so this starts listening again for the new session whenever it changes.
+async fn session_revoked(global: &GlobalState, request_context: &RequestContext) {
+    loop {
+        let changed = request_context.session_changed();
+
+        let Some(session) = request_context.current_session() else {
+            changed.await;
+            continue;
+        };
+
+        let mut revoked = match global
+            .subscription_manager
+            .subscribe(UserSessionsRevoked::subject(session.user_id))
+            .await
+        {
+            Ok(revoked) => revoked,
+            Err(e) => {
+                tracing::warn!("failed to subscribe to revoked sessions: {}", e);
+                changed.await;
+                continue;
+            }
+        };
+
+        let session_id = session.id.to_string();
+
+        // Resolves to false if the subscription closed, then the websocket only finds out with its next request.
+        let listen = async {
+            loop {
+                let message = match revoked.recv().await {
+                    Ok(message) => message,
+                    // It could have missed its own revocation, the client reconnects and logs in again if it was not.
+                    Err(RecvError::Evicted) => return true,
+                    Err(RecvError::Closed) => return false,
+                };
+
+                let is_revoked = message
+                    .as_bytes()
+                    .and_then(|b| UserSessionsRevoked::decode(b).ok())
+                    .map(|event| event.session_ids.contains(&session_id))
+                    .unwrap_or(false);
+
+                if is_revoked {
+                    return true;
+                }
+            }
+        };
+
+        select! {
+            _ = changed => {}
+            true = listen => return,
+        }
+    }
+}
+
+/// Keeps the user the websocket is logged in with online for as long as it is open, the websocket is the heartbeat.
+/// Never resolves. The presence is renewed right away when the websocket logs in and then twice per ttl.
+async fn keep_presence(global: &GlobalState, request_context: &RequestContext) {
+    let mut interval = tokio::time::interval(Duration::from_secs(
+        (global.config.presence.ttl as u64 / 2).max(1),
+    ));
+    interval.tick().await;
+
+    loop {
+        let changed = request_context.session_changed();
+
+        if let Some(session) = request_context.current_session() {
+            if let Err(e) = global.touch_presence(session.user_id, None).await {
+                tracing::warn!("failed to update presence: {}", e);
+            }
+        }
+
+        select! {
+            _ = interval.tick() => {}
+            _ = changed => {}
+        }
+    }
+}
+
 async fn websocket_handler(
     ws: HyperWebsocket,
     schema: MySchema,
@@ -70,6 +184,7 @@ async fn websocket_handler(
 
     let stream = {
         let global = global.clone();
+        let request_context = request_context.clone();
 
         async_graphql::http::WebSocket::new(schema, input, protocol)
             .on_connection_init(|params| async move {
@@ -130,6 +245,10 @@ async fn websocket_handler(
     //  This is interesting since when we shutdown we interrupt the stream forward rather then waiting for the stream to finish.
     select! {
         _ = stream.forward(&mut tx) => {}
+        _ = session_revoked(&global, &request_context) => {
+            tx.send(Message::Close(Some(CloseFrame { code: CloseCode::Policy, reason: "session was revoked".into() }))).await.ok();
+        }
+        _ = keep_presence(&global, &request_context) => {}
         _ = global.ctx.done() => {
             tx.send(Message::Close(Some(CloseFrame { code: CloseCode::Restart, reason: "server is restarting".into() }))).await.ok();
         }
@@ -151,14 +270,26 @@ pub async fn graphql_handler(mut req: Request<Body>) -> Result<Response<Body>> {
             .expect("failed to build response"));
     }
 
-    let schema = req
-        .data::<MySchema>()
-        .expect("failed to get schema")
-        .clone();
-
     let global = req.get_global()?;
 
+    let schemas = req.data::<Schemas>().expect("failed to get schema");
+    let schema = if has_introspection_token(&global.config.gql, req.headers()) {
+        schemas.introspection.clone()
+    } else {
+        schemas.public.clone()
+    };
+
     let session = req.context::<(session::Model, UserPermission)>();
+    let client_ip = ip_reputation::client_ip(
+        &global.config.ip_reputation,
+        req.headers(),
+        req.remote_addr(),
+    );
+    let user_agent = req
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|val| val.to_str().ok())
+        .map(str::to_string);
 
     // We need to check if this is a websocket upgrade request.
     // If it is, we need to upgrade the request to a websocket request.
@@ -185,7 +316,11 @@ pub async fn graphql_handler(mut req: Request<Body>) -> Result<Response<Body>> {
                 .expect("failed to set websocket protocol"),
         );
 
-        let request_context = Arc::new(RequestContext::new(true));
+        let request_context = Arc::new(
+            RequestContext::new(true)
+                .with_client_ip(client_ip)
+                .with_user_agent(user_agent),
+        );
         request_context.set_session(session);
 
         tokio::spawn(websocket_handler(
@@ -199,7 +334,14 @@ pub async fn graphql_handler(mut req: Request<Body>) -> Result<Response<Body>> {
         return Ok(response);
     }
 
-    let session_state = Arc::new(RequestContext::new(false));
+    // Websockets stay open for as long as the client wants, so only single requests have a deadline.
+    let budget = deadline::budget(&global.config.api, req.headers());
+
+    let session_state = Arc::new(
+        RequestContext::new(false)
+            .with_client_ip(client_ip)
+            .with_user_agent(user_agent),
+    );
     session_state.set_session(session);
 
     // We need to parse the request body into a GraphQL request.
@@ -230,8 +372,18 @@ pub async fn graphql_handler(mut req: Request<Body>) -> Result<Response<Body>> {
         hyper::Method::GET => {
             let query = req.uri().query().unwrap_or("");
 
-            async_graphql::http::parse_query_string(query)
-                .map_err_route((StatusCode::BAD_REQUEST, "Invalid query string"))?
+            let request = async_graphql::http::parse_query_string(query)
+                .map_err_route((StatusCode::BAD_REQUEST, "Invalid query string"))?;
+
+            // GET requests don't need a CSRF token, so they must not be able to change anything.
+            if req.context::<csrf::CookieSession>().is_some() && is_mutation(&request.query) {
+                return Err(RouteError::from((
+                    StatusCode::METHOD_NOT_ALLOWED,
+                    "Mutations must be sent with POST",
+                )));
+            }
+
+            request
         }
         _ => {
             return Err(RouteError::from((
@@ -243,7 +395,10 @@ pub async fn graphql_handler(mut req: Request<Body>) -> Result<Response<Body>> {
     .provide_global(global)
     .provide_context(session_state);
 
-    let response = schema.execute(request).await;
+    // Hyper drops this future when the client disconnects, which cancels the execution just like the deadline does.
+    let response = deadline::run(budget, schema.execute(request))
+        .await
+        .map_err(|_| RouteError::from((StatusCode::GATEWAY_TIMEOUT, "Request timed out")))?;
 
     let mut resp = Response::builder()
         .status(StatusCode::OK)
@@ -269,10 +424,11 @@ pub async fn graphql_handler(mut req: Request<Body>) -> Result<Response<Body>> {
         ))
         .expect("failed to build response");
 
+    // Appended, a login sets more than one cookie.
     (&response.http_headers)
         .into_iter()
         .for_each(|(key, value)| {
-            resp.headers_mut().insert(key, value.clone());
+            resp.headers_mut().append(key, value.clone());
         });
 
     Ok(resp)
@@ -0,0 +1,292 @@
+use async_graphql::{Context, Object};
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_admin, authorize_user, check_ip_reputation};
+use super::models::invite::{InviteCode, InviteStats, InviteTreeLevel};
+use crate::database::{invite_code, user, waitlist_entry};
+use crate::global::GlobalState;
+
+/// The most waitlist entries admitted at once, each one is emailed.
+const MAX_ADMIT_COUNT: u32 = 100;
+
+/// Sends the invite code of an admitted waitlist entry.
+async fn email_invite(global: &GlobalState, email: &str, code: &str) -> anyhow::Result<()> {
+    let to = global.decrypt_pii(email)?;
+
+    global
+        .send_email(
+            &to,
+            "Your invite to Scuffle",
+            &format!(
+                "Hi,\n\nYou are off the waitlist! Register with this link: {}?code={}\n\nThe code works once, so keep it to yourself.",
+                global.config.invites.url,
+                code,
+            ),
+        )
+        .await
+}
+
+#[derive(Default)]
+pub struct InviteQuery;
+
+#[Object]
+/// The query object for invite codes and the waitlist of an invite only launch.
+impl InviteQuery {
+    /// Get the invite codes the logged in user created, the newest first.
+    async fn codes<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<InviteCode>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let codes = sqlx::query_as!(
+            invite_code::Model,
+            "SELECT * FROM invite_codes WHERE created_by = $1 ORDER BY created_at DESC",
+            session.user_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch invite codes")?;
+
+        Ok(codes.into_iter().map(InviteCode::from).collect())
+    }
+
+    /// Get how many invite codes the logged in user can still create.
+    async fn remaining_codes<'ctx>(&self, ctx: &Context<'_>) -> Result<i64> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let created = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM invite_codes WHERE created_by = $1",
+            session.user_id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to count invite codes")?
+        .unwrap_or(0);
+
+        Ok((global.config.invites.codes_per_user as i64 - created).max(0))
+    }
+
+    /// Get how the invites spread and how long the waitlist is. Only admins can see this.
+    async fn stats<'ctx>(&self, ctx: &Context<'_>) -> Result<InviteStats> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let codes = sqlx::query!(
+            "SELECT COUNT(*) FILTER (WHERE created_by IS NOT NULL) AS created, COUNT(*) FILTER (WHERE used_at IS NOT NULL) AS used FROM invite_codes"
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to count invite codes")?;
+
+        let waitlist = sqlx::query!(
+            "SELECT COUNT(*) FILTER (WHERE admitted_at IS NULL) AS pending, COUNT(*) FILTER (WHERE admitted_at IS NOT NULL) AS admitted FROM waitlist_entries"
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to count waitlist entries")?;
+
+        // A code can only be used by a user who registers after it was created, so the tree has no cycles.
+        let tree = sqlx::query_as!(
+            InviteTreeLevel,
+            r#"WITH RECURSIVE tree AS (
+                SELECT c.used_by AS user_id, 1 AS depth FROM invite_codes c WHERE c.used_by IS NOT NULL AND c.created_by IS NOT NULL AND NOT EXISTS (SELECT 1 FROM invite_codes p WHERE p.used_by = c.created_by AND p.created_by IS NOT NULL)
+                UNION ALL
+                SELECT c.used_by, t.depth + 1 FROM invite_codes c JOIN tree t ON c.created_by = t.user_id WHERE c.used_by IS NOT NULL
+            )
+            SELECT depth::bigint AS "depth!", COUNT(*) AS "users!" FROM tree GROUP BY depth ORDER BY depth"#
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch invite tree")?;
+
+        Ok(InviteStats {
+            codes_created: codes.created.unwrap_or(0),
+            codes_used: codes.used.unwrap_or(0),
+            tree,
+            waitlist_pending: waitlist.pending.unwrap_or(0),
+            waitlist_admitted: waitlist.admitted.unwrap_or(0),
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct InviteMutation;
+
+#[Object]
+/// The mutation object for invite codes and the waitlist of an invite only launch.
+impl InviteMutation {
+    /// Create an invite code to share. Every user can create a limited number of them.
+    async fn create_code<'ctx>(&self, ctx: &Context<'_>) -> Result<InviteCode> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to create invite code")?;
+
+        // Locking the user keeps two requests at the same time from both creating the last code.
+        sqlx::query!(
+            "SELECT id FROM users WHERE id = $1 FOR UPDATE",
+            session.user_id
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to fetch user")?;
+
+        let created = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM invite_codes WHERE created_by = $1",
+            session.user_id,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to count invite codes")?
+        .unwrap_or(0);
+
+        if created >= global.config.invites.codes_per_user as i64 {
+            return Err(GqlError::InvalidInput.with_message("You have no invite codes left"));
+        }
+
+        let code = sqlx::query_as!(
+            invite_code::Model,
+            "INSERT INTO invite_codes (code, created_by) VALUES ($1, $2) RETURNING *",
+            invite_code::generate_code(),
+            session.user_id,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to create invite code")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to commit transaction")?;
+
+        Ok(code.into())
+    }
+
+    /// Join the waitlist, the invite code is emailed once an admin admits the entry.
+    /// This succeeds for emails which already joined too, so the waitlist can't be used to find out who is on it.
+    async fn join_waitlist<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The email to send the invite to.")] email: String,
+        #[graphql(desc = "The captcha token from cloudflare turnstile.")] captcha_token: String,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        check_ip_reputation(ctx).await?;
+
+        if !global
+            .validate_turnstile_token(&captcha_token)
+            .await
+            .map_err_gql("Failed to validate captcha token")?
+        {
+            return Err(GqlError::InvalidInput
+                .with_message("Capcha token is invalid")
+                .with_field(vec!["captchaToken"]));
+        }
+
+        let email = email.to_lowercase();
+        user::validate_email(&email).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["email"])
+        })?;
+
+        sqlx::query!(
+            "INSERT INTO waitlist_entries (email, email_hash) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            global
+                .encrypt_pii(&email)
+                .map_err_gql("Failed to encrypt email")?,
+            global.email_hash(&email),
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to join waitlist")?;
+
+        Ok(true)
+    }
+
+    /// Admit the waitlist entries which waited the longest, each gets an invite code by email. Only admins can do this.
+    /// Entries whose email could not be sent stay on the waitlist. Returns the number of entries admitted.
+    async fn admit_waitlist<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "How many entries to admit, at most 100.")] count: u32,
+    ) -> Result<i64> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        if count == 0 || count > MAX_ADMIT_COUNT {
+            return Err(GqlError::InvalidInput
+                .with_message("Count must be between 1 and 100")
+                .with_field(vec!["count"]));
+        }
+
+        let entries = sqlx::query_as!(
+            waitlist_entry::Model,
+            "SELECT * FROM waitlist_entries WHERE admitted_at IS NULL ORDER BY created_at LIMIT $1",
+            count as i64,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch waitlist")?;
+
+        let mut admitted = 0;
+
+        for entry in entries {
+            let mut tx = global
+                .db
+                .begin()
+                .await
+                .map_err_gql("Failed to admit waitlist entry")?;
+
+            let code = sqlx::query_as!(
+                invite_code::Model,
+                "INSERT INTO invite_codes (code) VALUES ($1) RETURNING *",
+                invite_code::generate_code(),
+            )
+            .fetch_one(&mut *tx)
+            .await
+            .map_err_gql("Failed to create invite code")?;
+
+            // Another admin admitting at the same time already took the entry if nothing is updated.
+            let claimed = sqlx::query!(
+                "UPDATE waitlist_entries SET invite_code_id = $2, admitted_by = $3, admitted_at = NOW() WHERE id = $1 AND admitted_at IS NULL",
+                entry.id,
+                code.id,
+                session.user_id,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err_gql("Failed to admit waitlist entry")?
+            .rows_affected()
+                > 0;
+
+            if !claimed {
+                continue;
+            }
+
+            // The entry is only admitted once the email is out, otherwise it is rolled back and can be admitted again.
+            if let Err(e) = email_invite(global, &entry.email, &code.code).await {
+                tracing::warn!(entry_id = %entry.id, "failed to email invite: {:#}", e);
+                continue;
+            }
+
+            tx.commit()
+                .await
+                .map_err_gql("Failed to commit transaction")?;
+
+            admitted += 1;
+        }
+
+        Ok(admitted)
+    }
+}
@@ -0,0 +1,226 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_admin;
+use super::models::legal_hold::{LegalHold, LegalHoldEvent, LegalHoldSubject};
+use crate::database::{legal_hold, legal_hold_event};
+
+const MAX_REASON_LENGTH: usize = 500;
+
+const LIST_LIMIT: i64 = 100;
+
+#[derive(Default)]
+pub struct LegalHoldQuery;
+
+#[Object]
+/// The query object for legal holds. Only admins can see holds.
+impl LegalHoldQuery {
+    /// Get the holds on a subject, or the most recent holds of every subject, newest first.
+    async fn holds<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The kind of the subject.")] subject_kind: Option<LegalHoldSubject>,
+        #[graphql(desc = "The id of the subject, requires the kind.")] subject_id: Option<Uuid>,
+        #[graphql(desc = "Only return holds which are still in place.", default = true)]
+        active: bool,
+    ) -> Result<Vec<LegalHold>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        if subject_id.is_some() && subject_kind.is_none() {
+            return Err(GqlError::InvalidInput
+                .with_message("The subject kind is required to filter by subject")
+                .with_field(vec!["subjectKind"]));
+        }
+
+        let holds = sqlx::query_as!(
+            legal_hold::Model,
+            "SELECT * FROM legal_holds WHERE ($1::bigint IS NULL OR subject_kind = $1) AND ($2::uuid IS NULL OR subject_id = $2) AND (NOT $3 OR released_at IS NULL) ORDER BY created_at DESC LIMIT $4",
+            subject_kind.map(|k| i64::from(legal_hold::SubjectKind::from(k))),
+            subject_id,
+            active,
+            LIST_LIMIT,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch legal holds")?;
+
+        Ok(holds.into_iter().map(LegalHold::from).collect())
+    }
+
+    /// Get the audit trail of a hold, oldest first.
+    async fn history<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the hold.")] id: Uuid,
+    ) -> Result<Vec<LegalHoldEvent>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let events = sqlx::query_as!(
+            legal_hold_event::Model,
+            "SELECT * FROM legal_hold_events WHERE hold_id = $1 ORDER BY created_at ASC",
+            id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch legal hold history")?;
+
+        Ok(events.into_iter().map(LegalHoldEvent::from).collect())
+    }
+}
+
+fn check_reason(reason: &str) -> Result<()> {
+    if reason.trim().is_empty() || reason.len() > MAX_REASON_LENGTH {
+        return Err(GqlError::InvalidInput
+            .with_message("Reason must be between 1 and 500 characters")
+            .with_field(vec!["reason"]));
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct LegalHoldMutation;
+
+#[Object]
+/// The mutation object for legal holds.
+impl LegalHoldMutation {
+    /// Place a hold on a user, a channel or a VOD. Held data can not be deleted until the hold is released.
+    async fn place<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The kind of the subject.")] subject_kind: LegalHoldSubject,
+        #[graphql(desc = "The id of the user, channel or VOD.")] subject_id: Uuid,
+        #[graphql(desc = "Why the data is preserved, e.g. a case number.")] reason: String,
+    ) -> Result<LegalHold> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        check_reason(&reason)?;
+
+        let kind = legal_hold::SubjectKind::from(subject_kind);
+
+        // Channels are users, VODs are the recorded streams.
+        let exists = match kind {
+            legal_hold::SubjectKind::User | legal_hold::SubjectKind::Channel => global
+                .user_by_id_loader
+                .load_one(subject_id)
+                .await
+                .map_err_gql("Failed to fetch user")?
+                .is_some(),
+            legal_hold::SubjectKind::Vod => sqlx::query_scalar!(
+                "SELECT EXISTS(SELECT 1 FROM streams WHERE id = $1 AND recorded = TRUE)",
+                subject_id,
+            )
+            .fetch_one(&*global.db)
+            .await
+            .map_err_gql("Failed to fetch VOD")?
+            .unwrap_or(false),
+        };
+
+        if !exists {
+            return Err(GqlError::NotFound
+                .with_message("Subject not found")
+                .with_field(vec!["subjectId"]));
+        }
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to place legal hold")?;
+
+        let hold = sqlx::query_as!(
+            legal_hold::Model,
+            "INSERT INTO legal_holds (subject_kind, subject_id, reason, placed_by) VALUES ($1, $2, $3, $4) ON CONFLICT (subject_kind, subject_id) WHERE released_at IS NULL DO NOTHING RETURNING *",
+            i64::from(kind),
+            subject_id,
+            reason,
+            session.user_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to place legal hold")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("The subject is already under legal hold")
+                .with_field(vec!["subjectId"])
+        })?;
+
+        sqlx::query!(
+            "INSERT INTO legal_hold_events (hold_id, actor_id, action, note) VALUES ($1, $2, $3, $4)",
+            hold.id,
+            session.user_id,
+            i64::from(legal_hold_event::Action::Placed),
+            reason,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to record legal hold history")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to place legal hold")?;
+
+        Ok(hold.into())
+    }
+
+    /// Release a hold, the data can be deleted again.
+    async fn release<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the hold.")] id: Uuid,
+        #[graphql(desc = "Why the hold is released.")] reason: String,
+    ) -> Result<LegalHold> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        check_reason(&reason)?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to release legal hold")?;
+
+        let hold = sqlx::query_as!(
+            legal_hold::Model,
+            "UPDATE legal_holds SET released_by = $2, release_reason = $3, released_at = NOW() WHERE id = $1 AND released_at IS NULL RETURNING *",
+            id,
+            session.user_id,
+            reason,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err_gql("Failed to release legal hold")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("Legal hold not found or already released")
+                .with_field(vec!["id"])
+        })?;
+
+        sqlx::query!(
+            "INSERT INTO legal_hold_events (hold_id, actor_id, action, note) VALUES ($1, $2, $3, $4)",
+            hold.id,
+            session.user_id,
+            i64::from(legal_hold_event::Action::Released),
+            reason,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to record legal hold history")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to release legal hold")?;
+
+        Ok(hold.into())
+    }
+}
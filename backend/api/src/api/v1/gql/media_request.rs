@@ -0,0 +1,218 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_channel_owner, authorize_user};
+use super::models::media_request::{MediaRequest, MediaRequestQueue};
+use super::pagination::page_limit;
+use crate::database::media_request;
+use crate::global::media_request::SubmitError;
+
+const DEFAULT_PENDING_LIMIT: u32 = 50;
+const MAX_PENDING_LIMIT: u32 = 500;
+
+fn no_request() -> GqlError {
+    GqlError::NotFound
+        .with_message("Request not found or already reviewed")
+        .with_field(vec!["id"])
+}
+
+#[derive(Default)]
+pub struct MediaRequestQuery;
+
+#[Object]
+/// The query object for media requests.
+impl MediaRequestQuery {
+    /// Get the approved requests of a channel and the one playing, for overlays to show on stream.
+    async fn queue<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<MediaRequestQueue> {
+        let global = ctx.get_global();
+
+        MediaRequestQueue::load(global, channel_id).await
+    }
+
+    /// Get the requests of a channel waiting for approval, oldest first. Only the broadcaster can see them.
+    async fn pending<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The maximum number of requests to return. Defaults to 50, at most 500.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<MediaRequest>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let limit = page_limit(limit, DEFAULT_PENDING_LIMIT, MAX_PENDING_LIMIT)?;
+
+        let requests = sqlx::query_as!(
+            media_request::Model,
+            "SELECT * FROM media_requests WHERE channel_id = $1 AND status = $2 ORDER BY created_at, id LIMIT $3",
+            channel_id,
+            i64::from(media_request::Status::Pending),
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch requests")?;
+
+        Ok(requests.into_iter().map(MediaRequest::from).collect())
+    }
+
+    /// Get the requests the logged in user has waiting or queued in a channel.
+    async fn mine<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<MediaRequest>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let requests = sqlx::query_as!(
+            media_request::Model,
+            "SELECT * FROM media_requests WHERE channel_id = $1 AND user_id = $2 AND status IN ($3, $4) ORDER BY created_at, id",
+            channel_id,
+            session.user_id,
+            i64::from(media_request::Status::Pending),
+            i64::from(media_request::Status::Approved),
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch requests")?;
+
+        Ok(requests.into_iter().map(MediaRequest::from).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct MediaRequestMutation;
+
+#[Object]
+/// The mutation object for media requests. Viewers request media with the mutation or by typing `!sr <url>` in chat,
+/// the broadcaster approves them before they are queued.
+impl MediaRequestMutation {
+    /// Let viewers request media in a channel or stop them, requests already made are kept.
+    /// Only the broadcaster can do this.
+    async fn set_enabled<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "Whether viewers can request media.")] enabled: bool,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        sqlx::query!(
+            "UPDATE users SET media_requests_enabled = $2 WHERE id = $1",
+            channel_id,
+            enabled,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to update media requests")?;
+
+        global.media_requests_changed(channel_id).await;
+
+        Ok(enabled)
+    }
+
+    /// Request media in a channel with the logged in user. The url has to link to one of the sites the platform allows.
+    async fn submit<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The url of the song or video.")] url: String,
+    ) -> Result<MediaRequest> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let limit = global.config.media_requests.max_pending_per_viewer;
+
+        match global
+            .submit_media_request(channel_id, session.user_id, url.trim())
+            .await
+        {
+            Ok(request) => Ok(request.into()),
+            Err(SubmitError::Disabled) => Err(GqlError::InvalidInput
+                .with_message("This channel does not take media requests")
+                .with_field(vec!["channelId"])),
+            Err(SubmitError::InvalidUrl(e)) => Err(GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["url"])),
+            Err(SubmitError::TooMany) => Err(GqlError::InvalidInput.with_message(&format!(
+                "You can have at most {} requests waiting in this channel",
+                limit
+            ))),
+            Err(SubmitError::QueueFull) => Err(GqlError::InvalidInput
+                .with_message("This channel has too many requests waiting, try again later")
+                .with_field(vec!["channelId"])),
+            Err(SubmitError::Database(e)) => Err(e).map_err_gql("Failed to submit request"),
+        }
+    }
+
+    /// Approve a pending request, it joins the end of the queue. Only the broadcaster can do this.
+    async fn approve<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The id of the request.")] id: Uuid,
+    ) -> Result<MediaRequest> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_channel_owner(ctx, channel_id).await?;
+
+        let request = global
+            .review_media_request(channel_id, id, session.user_id, true)
+            .await
+            .map_err_gql("Failed to approve request")?
+            .ok_or_else(no_request)?;
+
+        Ok(request.into())
+    }
+
+    /// Reject a pending request or take an approved one out of the queue. Only the broadcaster can do this.
+    async fn reject<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The id of the request.")] id: Uuid,
+    ) -> Result<MediaRequest> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_channel_owner(ctx, channel_id).await?;
+
+        let request = global
+            .review_media_request(channel_id, id, session.user_id, false)
+            .await
+            .map_err_gql("Failed to reject request")?
+            .ok_or_else(no_request)?;
+
+        Ok(request.into())
+    }
+
+    /// Play the first request of the queue, it becomes `nowPlaying`. Returns null if the queue is empty.
+    /// Only the broadcaster can do this.
+    async fn next<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Option<MediaRequest>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let request = global
+            .play_next_media_request(channel_id)
+            .await
+            .map_err_gql("Failed to play request")?;
+
+        Ok(request.map(MediaRequest::from))
+    }
+}
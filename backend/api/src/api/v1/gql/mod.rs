@@ -1,38 +1,132 @@
 use std::sync::Arc;
 
-use async_graphql::{extensions, ComplexObject, Context, Schema, SimpleObject};
+use async_graphql::{extensions, ComplexObject, Context, Schema, SchemaBuilder, SimpleObject};
 use hyper::{Body, Response};
 use routerify::Router;
 use uuid::Uuid;
 
-use crate::{api::error::RouteError, global::GlobalState};
+use crate::{api::error::RouteError, config::GqlConfig, global::GlobalState};
 
 use self::{
     error::{Result, ResultExt},
     ext::ContextExt,
 };
 
+pub mod access_token;
+pub mod approval;
 pub mod auth;
+pub mod ban_appeal;
+pub mod bot;
+pub mod channel;
+pub mod channel_import;
+pub mod charity;
 pub mod chat;
+pub mod checkout;
+pub mod cheermote;
+pub mod dead_letter;
+pub mod deprecation;
+pub mod discord;
+pub mod emote;
 pub mod error;
 pub mod ext;
+pub mod friend;
+pub mod giveaway;
+pub mod guards;
 pub mod handlers;
+pub mod invite;
+pub mod legal_hold;
+pub mod media_request;
 pub mod models;
+pub mod moderation;
+pub mod obs;
+pub mod pagination;
+pub mod payout;
+pub mod presence;
+pub mod promotion;
 pub mod request_context;
+pub mod revenue;
+pub mod stream_key;
 pub mod subscription;
+pub mod suspension;
+pub mod tag;
+pub mod two_fa;
+pub mod user;
+pub mod viewer_queue;
+pub mod vod;
 
 #[derive(Default, SimpleObject)]
 #[graphql(complex)]
 /// The root query type which contains root level fields.
 pub struct Query {
+    access_token: access_token::AccessTokenQuery,
+    approval: approval::ApprovalQuery,
+    auth: auth::AuthQuery,
+    ban_appeal: ban_appeal::BanAppealQuery,
+    bot: bot::BotQuery,
+    channel: channel::ChannelQuery,
+    channel_import: channel_import::ChannelImportQuery,
+    charity: charity::CharityQuery,
+    chat: chat::ChatQuery,
+    cheermote: cheermote::CheermoteQuery,
+    dead_letter: dead_letter::DeadLetterQuery,
+    deprecation: deprecation::DeprecationQuery,
+    discord: discord::DiscordQuery,
+    emote: emote::EmoteQuery,
+    friend: friend::FriendQuery,
+    giveaway: giveaway::GiveawayQuery,
+    invite: invite::InviteQuery,
+    legal_hold: legal_hold::LegalHoldQuery,
+    media_request: media_request::MediaRequestQuery,
+    moderation: moderation::ModerationQuery,
     noop: bool,
+    obs: obs::ObsQuery,
+    payout: payout::PayoutQuery,
+    presence: presence::PresenceQuery,
+    promotion: promotion::PromotionQuery,
+    revenue: revenue::RevenueQuery,
+    stream_key: stream_key::StreamKeyQuery,
+    suspension: suspension::SuspensionQuery,
+    tag: tag::TagQuery,
+    two_fa: two_fa::TwoFaQuery,
+    user: user::UserQuery,
+    viewer_queue: viewer_queue::ViewerQueueQuery,
+    vod: vod::VodQuery,
 }
 
 #[derive(Default, SimpleObject)]
 /// The root mutation type which contains root level fields.
 pub struct Mutation {
+    access_token: access_token::AccessTokenMutation,
+    approval: approval::ApprovalMutation,
     auth: auth::AuthMutation,
+    ban_appeal: ban_appeal::BanAppealMutation,
+    bot: bot::BotMutation,
+    channel: channel::ChannelMutation,
+    channel_import: channel_import::ChannelImportMutation,
+    charity: charity::CharityMutation,
     chat: chat::ChatMutation,
+    checkout: checkout::CheckoutMutation,
+    cheermote: cheermote::CheermoteMutation,
+    dead_letter: dead_letter::DeadLetterMutation,
+    discord: discord::DiscordMutation,
+    emote: emote::EmoteMutation,
+    friend: friend::FriendMutation,
+    giveaway: giveaway::GiveawayMutation,
+    invite: invite::InviteMutation,
+    legal_hold: legal_hold::LegalHoldMutation,
+    media_request: media_request::MediaRequestMutation,
+    moderation: moderation::ModerationMutation,
+    obs: obs::ObsMutation,
+    payout: payout::PayoutMutation,
+    presence: presence::PresenceMutation,
+    promotion: promotion::PromotionMutation,
+    stream_key: stream_key::StreamKeyMutation,
+    suspension: suspension::SuspensionMutation,
+    tag: tag::TagMutation,
+    two_fa: two_fa::TwoFaMutation,
+    user: user::UserMutation,
+    viewer_queue: viewer_queue::ViewerQueueMutation,
+    vod: vod::VodMutation,
 }
 
 #[ComplexObject]
@@ -74,7 +168,7 @@ pub type MySchema = Schema<Query, Mutation, subscription::Subscription>;
 
 pub const PLAYGROUND_HTML: &str = include_str!("playground.html");
 
-pub fn schema() -> MySchema {
+fn schema_builder() -> SchemaBuilder<Query, Mutation, subscription::Subscription> {
     Schema::build(
         Query::default(),
         Mutation::default(),
@@ -83,13 +177,45 @@ pub fn schema() -> MySchema {
     .enable_federation()
     .enable_subscription_in_federation()
     .extension(extensions::Analyzer)
+    .extension(deprecation::Deprecations)
     .limit_complexity(100) // We don't want to allow too complex queries to be executed
-    .finish()
 }
 
-pub fn routes(_global: &Arc<GlobalState>) -> Router<Body, RouteError> {
+/// The full schema with introspection, used for tests and to export the schema.
+pub fn schema() -> MySchema {
+    schema_builder().finish()
+}
+
+/// The schemas requests are executed against. Requests with an introspection token use `introspection`.
+#[derive(Clone)]
+pub struct Schemas {
+    pub public: MySchema,
+    pub introspection: MySchema,
+}
+
+impl Schemas {
+    pub fn new(config: &GqlConfig) -> Self {
+        let build = |introspection: bool| {
+            let mut builder = schema_builder();
+            if !introspection {
+                builder = builder.disable_introspection();
+            }
+            if !config.suggestions {
+                builder = builder.disable_suggestions();
+            }
+            builder.finish()
+        };
+
+        Self {
+            public: build(config.introspection),
+            introspection: build(true),
+        }
+    }
+}
+
+pub fn routes(global: &Arc<GlobalState>) -> Router<Body, RouteError> {
     let router = Router::builder()
-        .data(schema())
+        .data(Schemas::new(&global.config.gql))
         .any_method("/", handlers::graphql_handler)
         .get("/playground", move |_| async move {
             Ok(Response::builder()
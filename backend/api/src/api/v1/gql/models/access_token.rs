@@ -0,0 +1,61 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::personal_access_token;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum AccessTokenScope {
+    /// Control the stream and read live stats, used by Stream Deck and companion apps
+    Control,
+}
+
+impl From<AccessTokenScope> for personal_access_token::Scope {
+    fn from(scope: AccessTokenScope) -> Self {
+        match scope {
+            AccessTokenScope::Control => Self::Control,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct AccessToken {
+    /// The token's id
+    pub id: Uuid,
+    /// The name given to the token
+    pub name: String,
+    /// What the token can be used for
+    pub scopes: Vec<AccessTokenScope>,
+    /// When the token expires, null if it never expires
+    pub expires_at: Option<DateRFC3339>,
+    /// When the token was last used
+    pub last_used_at: Option<DateRFC3339>,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl From<personal_access_token::Model> for AccessToken {
+    fn from(value: personal_access_token::Model) -> Self {
+        let scopes = [AccessTokenScope::Control]
+            .into_iter()
+            .filter(|s| value.has_scope((*s).into()))
+            .collect();
+
+        Self {
+            id: value.id,
+            name: value.name,
+            scopes,
+            expires_at: value.expires_at.map(Into::into),
+            last_used_at: value.last_used_at.map(Into::into),
+            created_at: value.created_at.into(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CreatedAccessToken {
+    /// The created token
+    pub access_token: AccessToken,
+    /// The secret used to authenticate, it is only shown once
+    pub secret: String,
+}
@@ -0,0 +1,126 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::{admin_approval, admin_approval_event};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ApprovalKind {
+    PermanentSuspension,
+    PayoutVerification,
+}
+
+impl From<admin_approval::Kind> for ApprovalKind {
+    fn from(kind: admin_approval::Kind) -> Self {
+        match kind {
+            admin_approval::Kind::PermanentSuspension => Self::PermanentSuspension,
+            admin_approval::Kind::PayoutVerification => Self::PayoutVerification,
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Expired,
+}
+
+impl From<admin_approval::Status> for ApprovalStatus {
+    fn from(status: admin_approval::Status) -> Self {
+        match status {
+            admin_approval::Status::Pending => Self::Pending,
+            admin_approval::Status::Approved => Self::Approved,
+            admin_approval::Status::Rejected => Self::Rejected,
+            admin_approval::Status::Expired => Self::Expired,
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ApprovalAction {
+    Proposed,
+    Approved,
+    Rejected,
+}
+
+impl From<admin_approval_event::Action> for ApprovalAction {
+    fn from(action: admin_approval_event::Action) -> Self {
+        match action {
+            admin_approval_event::Action::Proposed => Self::Proposed,
+            admin_approval_event::Action::Approved => Self::Approved,
+            admin_approval_event::Action::Rejected => Self::Rejected,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Approval {
+    /// The approval's id
+    pub id: Uuid,
+    /// What the action does
+    pub kind: ApprovalKind,
+    /// The id of the user to suspend or the payout method to verify
+    pub target_id: Uuid,
+    /// The reason shown to the suspended user, or the review note of the payout method
+    pub reason: String,
+    /// The status of the approval
+    pub status: ApprovalStatus,
+    /// The admin who proposed the action
+    pub proposed_by: Option<Uuid>,
+    /// The admin who approved or rejected the action
+    pub decided_by: Option<Uuid>,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// When the action can't be approved anymore
+    pub expires_at: DateRFC3339,
+    /// Decided at
+    pub decided_at: Option<DateRFC3339>,
+}
+
+impl From<admin_approval::Model> for Approval {
+    fn from(model: admin_approval::Model) -> Self {
+        Self {
+            id: model.id,
+            kind: model.kind.into(),
+            target_id: model.target_id,
+            status: model.current_status().into(),
+            reason: model.reason,
+            proposed_by: model.proposed_by,
+            decided_by: model.decided_by,
+            created_at: model.created_at.into(),
+            expires_at: model.expires_at.into(),
+            decided_at: model.decided_at.map(Into::into),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ApprovalEvent {
+    /// The event's id
+    pub id: Uuid,
+    /// The approval the event belongs to
+    pub approval_id: Uuid,
+    /// The admin who took the action, null if their account was deleted
+    pub actor_id: Option<Uuid>,
+    /// What happened to the approval
+    pub action: ApprovalAction,
+    /// The note given for the action
+    pub note: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl From<admin_approval_event::Model> for ApprovalEvent {
+    fn from(model: admin_approval_event::Model) -> Self {
+        Self {
+            id: model.id,
+            approval_id: model.approval_id,
+            actor_id: model.actor_id,
+            action: model.action.into(),
+            note: model.note,
+            created_at: model.created_at.into(),
+        }
+    }
+}
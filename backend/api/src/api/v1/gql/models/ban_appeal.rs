@@ -0,0 +1,138 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+    },
+    database::{chat_ban_appeal, chat_ban_appeal_event},
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum BanAppealStatus {
+    Pending,
+    Accepted,
+    Denied,
+}
+
+impl From<chat_ban_appeal::Status> for BanAppealStatus {
+    fn from(status: chat_ban_appeal::Status) -> Self {
+        match status {
+            chat_ban_appeal::Status::Pending => Self::Pending,
+            chat_ban_appeal::Status::Accepted => Self::Accepted,
+            chat_ban_appeal::Status::Denied => Self::Denied,
+        }
+    }
+}
+
+impl From<BanAppealStatus> for chat_ban_appeal::Status {
+    fn from(status: BanAppealStatus) -> Self {
+        match status {
+            BanAppealStatus::Pending => Self::Pending,
+            BanAppealStatus::Accepted => Self::Accepted,
+            BanAppealStatus::Denied => Self::Denied,
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum BanAppealAction {
+    Submitted,
+    Accepted,
+    Denied,
+}
+
+impl From<chat_ban_appeal_event::Action> for BanAppealAction {
+    fn from(action: chat_ban_appeal_event::Action) -> Self {
+        match action {
+            chat_ban_appeal_event::Action::Submitted => Self::Submitted,
+            chat_ban_appeal_event::Action::Accepted => Self::Accepted,
+            chat_ban_appeal_event::Action::Denied => Self::Denied,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct BanAppeal {
+    /// The appeal's id
+    pub id: Uuid,
+    /// The channel the user is banned from
+    pub channel_id: Uuid,
+    /// The banned user
+    pub user_id: Uuid,
+    /// Why the user should be unbanned
+    pub message: String,
+    /// The status of the appeal
+    pub status: BanAppealStatus,
+    /// The moderator who reviewed the appeal
+    pub reviewed_by: Option<Uuid>,
+    /// The note the moderator left for the user
+    pub review_note: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Reviewed at
+    pub reviewed_at: Option<DateRFC3339>,
+}
+
+#[ComplexObject]
+impl BanAppeal {
+    pub async fn user(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.user_id)
+            .await
+            .map_err_gql("failed to fetch user")?;
+
+        Ok(user.map(User::from))
+    }
+}
+
+impl From<chat_ban_appeal::Model> for BanAppeal {
+    fn from(model: chat_ban_appeal::Model) -> Self {
+        Self {
+            id: model.id,
+            channel_id: model.channel_id,
+            user_id: model.user_id,
+            message: model.message,
+            status: model.status.into(),
+            reviewed_by: model.reviewed_by,
+            review_note: model.review_note,
+            created_at: model.created_at.into(),
+            reviewed_at: model.reviewed_at.map(Into::into),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct BanAppealEvent {
+    /// The event's id
+    pub id: Uuid,
+    /// The appeal the event belongs to
+    pub appeal_id: Uuid,
+    /// Who took the action, null if their account was deleted
+    pub actor_id: Option<Uuid>,
+    /// What happened to the appeal
+    pub action: BanAppealAction,
+    /// The message or review note of the action
+    pub note: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl From<chat_ban_appeal_event::Model> for BanAppealEvent {
+    fn from(model: chat_ban_appeal_event::Model) -> Self {
+        Self {
+            id: model.id,
+            appeal_id: model.appeal_id,
+            actor_id: model.actor_id,
+            action: model.action.into(),
+            note: model.note,
+            created_at: model.created_at.into(),
+        }
+    }
+}
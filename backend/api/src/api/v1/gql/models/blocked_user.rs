@@ -0,0 +1,51 @@
+use async_graphql::{ComplexObject, Context, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{GqlError, Result, ResultExt},
+        ext::ContextExt,
+        pagination::Cursor,
+    },
+    database::user_block,
+};
+
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+/// A user the logged in user blocked.
+pub struct BlockedUser {
+    /// The user who was blocked.
+    pub user_id: Uuid,
+    /// The time the user was blocked.
+    pub created_at: DateRFC3339,
+    /// Pass as `after` to get the users which were blocked before this one.
+    pub cursor: Cursor,
+}
+
+#[ComplexObject]
+impl BlockedUser {
+    async fn user(&self, ctx: &Context<'_>) -> Result<User> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.user_id)
+            .await
+            .map_err_gql("failed to fetch user")?
+            .ok_or(GqlError::NotFound.with_message("user not found"))?;
+
+        Ok(User::from(user))
+    }
+}
+
+impl From<user_block::Model> for BlockedUser {
+    fn from(value: user_block::Model) -> Self {
+        Self {
+            user_id: value.blocked_id,
+            created_at: value.created_at.into(),
+            // Blocks are unique per user, so the blocked user breaks ties between blocks of the same time.
+            cursor: Cursor::new(value.created_at, value.blocked_id),
+        }
+    }
+}
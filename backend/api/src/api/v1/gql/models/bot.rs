@@ -0,0 +1,119 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+    },
+    database::bot_application,
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum BotApplicationStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Revoked,
+}
+
+impl From<bot_application::Status> for BotApplicationStatus {
+    fn from(status: bot_application::Status) -> Self {
+        match status {
+            bot_application::Status::Pending => Self::Pending,
+            bot_application::Status::Approved => Self::Approved,
+            bot_application::Status::Rejected => Self::Rejected,
+            bot_application::Status::Revoked => Self::Revoked,
+        }
+    }
+}
+
+impl From<BotApplicationStatus> for bot_application::Status {
+    fn from(status: BotApplicationStatus) -> Self {
+        match status {
+            BotApplicationStatus::Pending => Self::Pending,
+            BotApplicationStatus::Approved => Self::Approved,
+            BotApplicationStatus::Rejected => Self::Rejected,
+            BotApplicationStatus::Revoked => Self::Revoked,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct BotApplication {
+    /// The application's id
+    pub id: Uuid,
+    /// The bot account
+    pub user_id: Uuid,
+    /// What the bot does
+    pub description: String,
+    /// Where to find out more about the bot, empty if there is no website
+    pub website_url: String,
+    /// The status of the application
+    pub status: BotApplicationStatus,
+    /// The admin who reviewed the application
+    pub reviewed_by: Option<Uuid>,
+    /// The note the admin left for the bot owner
+    pub review_note: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Reviewed at, or when the verification was revoked
+    pub reviewed_at: Option<DateRFC3339>,
+}
+
+#[ComplexObject]
+impl BotApplication {
+    pub async fn user(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.user_id)
+            .await
+            .map_err_gql("failed to fetch user")?;
+
+        Ok(user.map(User::from))
+    }
+}
+
+impl From<bot_application::Model> for BotApplication {
+    fn from(model: bot_application::Model) -> Self {
+        Self {
+            id: model.id,
+            user_id: model.user_id,
+            description: model.description,
+            website_url: model.website_url,
+            status: model.status.into(),
+            reviewed_by: model.reviewed_by,
+            review_note: model.review_note,
+            created_at: model.created_at.into(),
+            reviewed_at: model.reviewed_at.map(Into::into),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct ActiveBot {
+    /// The bot account
+    pub user_id: Uuid,
+    /// When the bot last chatted in the channel
+    pub last_active_at: DateRFC3339,
+}
+
+#[ComplexObject]
+impl ActiveBot {
+    pub async fn user(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.user_id)
+            .await
+            .map_err_gql("failed to fetch user")?;
+
+        Ok(user.map(User::from))
+    }
+}
@@ -0,0 +1,99 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::color::Color;
+use crate::api::v1::gql::ext::ContextExt;
+use crate::database::channel_appearance;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ChannelLayout {
+    /// The video with chat next to it
+    Standard,
+    /// A wide video with chat below it
+    Theatre,
+}
+
+impl From<channel_appearance::Layout> for ChannelLayout {
+    fn from(layout: channel_appearance::Layout) -> Self {
+        match layout {
+            channel_appearance::Layout::Standard => Self::Standard,
+            channel_appearance::Layout::Theatre => Self::Theatre,
+        }
+    }
+}
+
+impl From<ChannelLayout> for channel_appearance::Layout {
+    fn from(layout: ChannelLayout) -> Self {
+        match layout {
+            ChannelLayout::Standard => Self::Standard,
+            ChannelLayout::Theatre => Self::Theatre,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Banner {
+    /// The banner's id, every upload gets a new one
+    pub id: Uuid,
+
+    #[graphql(skip)]
+    pub channel_id: Uuid,
+}
+
+#[ComplexObject]
+impl Banner {
+    /// The url of the banner cropped for desktop pages, 1920x480
+    async fn desktop_url(&self, ctx: &Context<'_>) -> String {
+        self.url(ctx, "desktop")
+    }
+
+    /// The url of the banner cropped for phones, 960x320
+    async fn mobile_url(&self, ctx: &Context<'_>) -> String {
+        self.url(ctx, "mobile")
+    }
+}
+
+impl Banner {
+    fn url(&self, ctx: &Context<'_>, variant: &str) -> String {
+        ctx.get_global().image_url(
+            &channel_appearance::banner_prefix(self.channel_id, self.id),
+            variant,
+        )
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ChannelAppearance {
+    /// The channel the appearance belongs to
+    pub channel_id: Uuid,
+    /// The accent color of the channel page, null for the site default
+    pub accent_color: Option<Color>,
+    /// The banner of the channel page, null if the channel has none
+    pub banner: Option<Banner>,
+    /// Whether a new banner is being processed. The old one is shown until it is done
+    pub banner_pending: bool,
+    /// How the video and chat are arranged
+    pub layout: ChannelLayout,
+    /// Whether the about panels are shown below the stream
+    pub show_panels: bool,
+    /// Whether the schedule is shown below the stream
+    pub show_schedule: bool,
+}
+
+impl From<channel_appearance::Model> for ChannelAppearance {
+    fn from(value: channel_appearance::Model) -> Self {
+        Self {
+            channel_id: value.channel_id,
+            accent_color: value.accent_color().map(Color::from),
+            banner: value.banner_id.map(|id| Banner {
+                id,
+                channel_id: value.channel_id,
+            }),
+            banner_pending: value.banner_job_id.is_some(),
+            layout: value.layout.into(),
+            show_panels: value.show_panels,
+            show_schedule: value.show_schedule,
+        }
+    }
+}
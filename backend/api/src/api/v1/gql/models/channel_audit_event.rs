@@ -0,0 +1,80 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+        pagination::Cursor,
+    },
+    database::channel_audit_event,
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ChannelAuditAction {
+    ChatArchiveEnabled,
+    ChatArchiveDisabled,
+    ModerationTwoFaEnabled,
+    ModerationTwoFaDisabled,
+}
+
+impl From<channel_audit_event::Action> for ChannelAuditAction {
+    fn from(action: channel_audit_event::Action) -> Self {
+        match action {
+            channel_audit_event::Action::ChatArchiveEnabled => Self::ChatArchiveEnabled,
+            channel_audit_event::Action::ChatArchiveDisabled => Self::ChatArchiveDisabled,
+            channel_audit_event::Action::ModerationTwoFaEnabled => Self::ModerationTwoFaEnabled,
+            channel_audit_event::Action::ModerationTwoFaDisabled => Self::ModerationTwoFaDisabled,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct ChannelAuditEvent {
+    /// The event's id
+    pub id: Uuid,
+    /// The channel the setting belongs to
+    pub channel_id: Uuid,
+    /// The user who changed the setting, null if their account was deleted
+    pub actor_id: Option<Uuid>,
+    /// What was changed
+    pub action: ChannelAuditAction,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Pass as `after` to get the events which happened before this one
+    pub cursor: Cursor,
+}
+
+#[ComplexObject]
+impl ChannelAuditEvent {
+    pub async fn actor(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let Some(actor_id) = self.actor_id else {
+            return Ok(None);
+        };
+
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(actor_id)
+            .await
+            .map_err_gql("failed to fetch user")?;
+
+        Ok(user.map(User::from))
+    }
+}
+
+impl From<channel_audit_event::Model> for ChannelAuditEvent {
+    fn from(model: channel_audit_event::Model) -> Self {
+        Self {
+            id: model.id,
+            channel_id: model.channel_id,
+            actor_id: model.actor_id,
+            action: model.action.into(),
+            created_at: model.created_at.into(),
+            cursor: Cursor::new(model.created_at, model.id),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+        pagination::Cursor,
+    },
+    database::channel_event,
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ChannelEventType {
+    Follow,
+    Subscription,
+    Raid,
+    Cheer,
+}
+
+impl From<channel_event::Kind> for ChannelEventType {
+    fn from(kind: channel_event::Kind) -> Self {
+        match kind {
+            channel_event::Kind::Follow => Self::Follow,
+            channel_event::Kind::Subscription => Self::Subscription,
+            channel_event::Kind::Raid => Self::Raid,
+            channel_event::Kind::Cheer => Self::Cheer,
+        }
+    }
+}
+
+impl From<ChannelEventType> for channel_event::Kind {
+    fn from(kind: ChannelEventType) -> Self {
+        match kind {
+            ChannelEventType::Follow => Self::Follow,
+            ChannelEventType::Subscription => Self::Subscription,
+            ChannelEventType::Raid => Self::Raid,
+            ChannelEventType::Cheer => Self::Cheer,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct ChannelEvent {
+    /// The event's id
+    pub id: Uuid,
+    /// The channel the event happened in
+    pub channel_id: Uuid,
+    /// The user who caused the event, null if anonymous
+    pub user_id: Option<Uuid>,
+    /// The type of the event
+    pub r#type: ChannelEventType,
+    /// Subscription months, raid viewers or cheered bits depending on the type
+    pub amount: i64,
+    /// The message attached to the event
+    pub message: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Pass as `after` to get the events which happened before this one
+    pub cursor: Cursor,
+}
+
+#[ComplexObject]
+impl ChannelEvent {
+    pub async fn user(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let Some(user_id) = self.user_id else {
+            return Ok(None);
+        };
+
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(user_id)
+            .await
+            .map_err_gql("failed to fetch user")?;
+
+        Ok(user.map(User::from))
+    }
+}
+
+impl From<channel_event::Model> for ChannelEvent {
+    fn from(model: channel_event::Model) -> Self {
+        Self {
+            id: model.id,
+            channel_id: model.channel_id,
+            user_id: model.user_id,
+            r#type: model.kind.into(),
+            amount: model.amount,
+            message: model.message,
+            created_at: model.created_at.into(),
+            cursor: Cursor::new(model.created_at, model.id),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::channel_import;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ImportPlatform {
+    Twitch,
+    Youtube,
+}
+
+impl From<channel_import::Platform> for ImportPlatform {
+    fn from(platform: channel_import::Platform) -> Self {
+        match platform {
+            channel_import::Platform::Twitch => Self::Twitch,
+            channel_import::Platform::Youtube => Self::Youtube,
+        }
+    }
+}
+
+impl From<ImportPlatform> for channel_import::Platform {
+    fn from(platform: ImportPlatform) -> Self {
+        match platform {
+            ImportPlatform::Twitch => Self::Twitch,
+            ImportPlatform::Youtube => Self::Youtube,
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ImportStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl From<channel_import::Status> for ImportStatus {
+    fn from(status: channel_import::Status) -> Self {
+        match status {
+            channel_import::Status::Queued => Self::Queued,
+            channel_import::Status::Running => Self::Running,
+            channel_import::Status::Completed => Self::Completed,
+            channel_import::Status::Failed => Self::Failed,
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ImportStep {
+    Fetching,
+    ProfileImage,
+    Panels,
+    Schedule,
+    Emotes,
+}
+
+impl From<channel_import::Step> for ImportStep {
+    fn from(step: channel_import::Step) -> Self {
+        match step {
+            channel_import::Step::Fetching => Self::Fetching,
+            channel_import::Step::ProfileImage => Self::ProfileImage,
+            channel_import::Step::Panels => Self::Panels,
+            channel_import::Step::Schedule => Self::Schedule,
+            channel_import::Step::Emotes => Self::Emotes,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ChannelImport {
+    /// The import's id
+    pub id: Uuid,
+    /// The channel which is imported into
+    pub channel_id: Uuid,
+    /// The platform the channel is imported from
+    pub platform: ImportPlatform,
+    /// The status of the import
+    pub status: ImportStatus,
+    /// What is currently imported
+    pub step: ImportStep,
+    /// The number of items found on the platform
+    pub total_items: i64,
+    /// The number of items imported so far
+    pub imported_items: i64,
+    /// The number of items which could not be imported, e.g. emotes with names that are not allowed here
+    pub skipped_items: i64,
+    /// The share of the items which has been handled, from 0 to 1
+    pub progress: f64,
+    /// The reason the import failed
+    pub error: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Updated at
+    pub updated_at: DateRFC3339,
+    /// Completed at
+    pub completed_at: Option<DateRFC3339>,
+}
+
+impl From<channel_import::Model> for ChannelImport {
+    fn from(value: channel_import::Model) -> Self {
+        Self {
+            progress: value.progress(),
+            id: value.id,
+            channel_id: value.channel_id,
+            platform: value.platform.into(),
+            status: value.status.into(),
+            step: value.step.into(),
+            total_items: value.total_items,
+            imported_items: value.imported_items,
+            skipped_items: value.skipped_items,
+            error: value.error,
+            created_at: value.created_at.into(),
+            updated_at: value.updated_at.into(),
+            completed_at: value.completed_at.map(Into::into),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use async_graphql::SimpleObject;
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::{channel_panel, channel_schedule_segment};
+
+#[derive(SimpleObject)]
+pub struct ChannelPanel {
+    /// The panel's id
+    pub id: Uuid,
+    /// The position of the panel on the about page, lowest first
+    pub position: i64,
+    /// The title of the panel
+    pub title: String,
+    /// The text of the panel
+    pub description: String,
+    /// The url of the panel image
+    pub image_url: Option<String>,
+    /// The url the panel links to
+    pub link_url: Option<String>,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl From<channel_panel::Model> for ChannelPanel {
+    fn from(value: channel_panel::Model) -> Self {
+        Self {
+            id: value.id,
+            position: value.position,
+            title: value.title,
+            description: value.description,
+            image_url: value.image_url,
+            link_url: value.link_url,
+            created_at: value.created_at.into(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ScheduleSegment {
+    /// The segment's id
+    pub id: Uuid,
+    /// The title of the planned stream
+    pub title: String,
+    /// The category of the planned stream
+    pub category: String,
+    /// Whether the stream repeats every week
+    pub recurring: bool,
+    /// Starts at
+    pub starts_at: DateRFC3339,
+    /// Ends at, null if open ended
+    pub ends_at: Option<DateRFC3339>,
+}
+
+impl From<channel_schedule_segment::Model> for ScheduleSegment {
+    fn from(value: channel_schedule_segment::Model) -> Self {
+        Self {
+            id: value.id,
+            title: value.title,
+            category: value.category,
+            recurring: value.recurring,
+            starts_at: value.starts_at.into(),
+            ends_at: value.ends_at.map(Into::into),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use async_graphql::SimpleObject;
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::charity_campaign;
+
+#[derive(SimpleObject)]
+pub struct CharityCampaign {
+    /// The campaign's id
+    pub id: Uuid,
+    /// The channel running the campaign
+    pub channel_id: Uuid,
+    /// The title of the campaign
+    pub title: String,
+    /// The charity which receives the donations
+    pub beneficiary: String,
+    /// The amount the channel wants to raise in cents
+    pub target_amount: i64,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Ended at
+    pub ended_at: Option<DateRFC3339>,
+}
+
+impl From<charity_campaign::Model> for CharityCampaign {
+    fn from(value: charity_campaign::Model) -> Self {
+        Self {
+            id: value.id,
+            channel_id: value.channel_id,
+            title: value.title,
+            beneficiary: value.beneficiary,
+            target_amount: value.target_amount,
+            created_at: value.created_at.into(),
+            ended_at: value.ended_at.map(Into::into),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CharityCampaignReport {
+    /// The campaign
+    pub campaign: CharityCampaign,
+    /// The amount raised in cents
+    pub raised_amount: i64,
+    /// The number of donations
+    pub donation_count: i64,
+    /// The number of distinct donors
+    pub donor_count: i64,
+    /// The largest single donation in cents
+    pub largest_donation: i64,
+}
+
+impl CharityCampaignReport {
+    pub fn new(campaign: charity_campaign::Model, totals: charity_campaign::Totals) -> Self {
+        Self {
+            campaign: campaign.into(),
+            raised_amount: totals.raised_amount,
+            donation_count: totals.donation_count,
+            donor_count: totals.donor_count,
+            largest_donation: totals.largest_donation,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CharityDonation {
+    /// The donation's id
+    pub id: Uuid,
+    /// The campaign the donation is for
+    pub campaign_id: Uuid,
+    /// The amount donated in cents
+    pub amount: i64,
+    /// The url where the user completes the payment
+    pub payment_url: String,
+}
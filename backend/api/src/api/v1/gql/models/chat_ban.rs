@@ -0,0 +1,76 @@
+use async_graphql::{ComplexObject, Context, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{GqlError, Result, ResultExt},
+        ext::ContextExt,
+        pagination::Cursor,
+    },
+    database::chat_ban,
+};
+
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+/// A user who is banned from the chat of a channel.
+pub struct ChatBan {
+    pub channel_id: Uuid,
+    pub user_id: Uuid,
+    /// The moderator who banned the user, null if their account was deleted.
+    pub banned_by_id: Option<Uuid>,
+    /// The reason the user was banned, empty if none was given.
+    pub reason: String,
+    /// The time the user was banned.
+    pub created_at: DateRFC3339,
+    /// The time the ban ends, null if it is permanent.
+    pub expires_at: Option<DateRFC3339>,
+    /// Pass as `after` to get the users banned before this one.
+    pub cursor: Cursor,
+}
+
+#[ComplexObject]
+impl ChatBan {
+    async fn user(&self, ctx: &Context<'_>) -> Result<User> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.user_id)
+            .await
+            .map_err_gql("failed to fetch user")?
+            .ok_or(GqlError::NotFound.with_message("user not found"))?;
+
+        Ok(User::from(user))
+    }
+
+    async fn banned_by(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let Some(banned_by_id) = self.banned_by_id else {
+            return Ok(None);
+        };
+
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(banned_by_id)
+            .await
+            .map_err_gql("failed to fetch user")?;
+
+        Ok(user.map(User::from))
+    }
+}
+
+impl From<chat_ban::Model> for ChatBan {
+    fn from(value: chat_ban::Model) -> Self {
+        Self {
+            channel_id: value.channel_id,
+            user_id: value.user_id,
+            banned_by_id: value.banned_by,
+            reason: value.reason,
+            created_at: value.created_at.into(),
+            expires_at: value.expires_at.map(Into::into),
+            cursor: Cursor::new(value.created_at, value.user_id),
+        }
+    }
+}
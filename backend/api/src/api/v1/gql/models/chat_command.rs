@@ -0,0 +1,70 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::chat_command;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ChatCommandPermission {
+    Everyone,
+    Vip,
+    Broadcaster,
+}
+
+impl From<chat_command::Permission> for ChatCommandPermission {
+    fn from(permission: chat_command::Permission) -> Self {
+        match permission {
+            chat_command::Permission::Everyone => Self::Everyone,
+            chat_command::Permission::Vip => Self::Vip,
+            chat_command::Permission::Broadcaster => Self::Broadcaster,
+        }
+    }
+}
+
+impl From<ChatCommandPermission> for chat_command::Permission {
+    fn from(permission: ChatCommandPermission) -> Self {
+        match permission {
+            ChatCommandPermission::Everyone => Self::Everyone,
+            ChatCommandPermission::Vip => Self::Vip,
+            ChatCommandPermission::Broadcaster => Self::Broadcaster,
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone)]
+/// A command of a chat, answered when a message starts with `!` and its name.
+pub struct ChatCommand {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    /// The name of the command, without the leading `!`.
+    pub name: String,
+    /// The message the chat answers with, sent in the name of the broadcaster.
+    pub response: String,
+    /// The seconds between two answers, 0 if every call is answered.
+    pub cooldown: u32,
+    /// Who can use the command.
+    pub permission: ChatCommandPermission,
+    /// How often the command was answered.
+    pub uses: i64,
+    /// The time the command was last answered.
+    pub last_used_at: Option<DateRFC3339>,
+    pub created_at: DateRFC3339,
+    pub updated_at: DateRFC3339,
+}
+
+impl From<chat_command::Model> for ChatCommand {
+    fn from(value: chat_command::Model) -> Self {
+        Self {
+            id: value.id,
+            channel_id: value.channel_id,
+            name: value.name,
+            response: value.response,
+            cooldown: value.cooldown as u32,
+            permission: value.permission.into(),
+            uses: value.uses,
+            last_used_at: value.last_used_at.map(Into::into),
+            created_at: value.created_at.into(),
+            updated_at: value.updated_at.into(),
+        }
+    }
+}
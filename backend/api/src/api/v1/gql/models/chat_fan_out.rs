@@ -0,0 +1,50 @@
+use async_graphql::SimpleObject;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::{config::SubscriptionConfig, subscription::TopicStats};
+
+#[derive(SimpleObject, Clone)]
+/// How the messages of a chat are delivered to its subscribers on this instance of the API.
+pub struct ChatFanOut {
+    /// The channel of the chat
+    pub channel_id: Uuid,
+    /// The subscribers of the chat
+    pub subscribers: u64,
+    /// The fan-out tasks the subscribers are spread over
+    pub shards: u64,
+    /// The most messages waiting for one fan-out task, a lag which keeps growing means the tasks can't keep up
+    pub lag: u64,
+    /// The messages delivered since the chat got its first subscriber
+    pub messages: u64,
+    /// The messages per second since the chat got its first subscriber
+    pub messages_per_second: f64,
+    /// The messages a fan-out task was too far behind to take, counted once per task
+    pub dropped: u64,
+    /// The subscribers closed because they fell behind
+    pub evicted: u64,
+    /// Whether the chat is spread over the most fan-out tasks and they are full, more subscribers make every task slower
+    pub at_capacity: bool,
+    /// When the chat got its first subscriber
+    pub active_since: DateRFC3339,
+}
+
+impl ChatFanOut {
+    pub fn new(config: &SubscriptionConfig, channel_id: Uuid, stats: &TopicStats) -> Self {
+        Self {
+            channel_id,
+            subscribers: stats.subscribers as u64,
+            shards: stats.shards as u64,
+            lag: stats.lag as u64,
+            messages: stats.published,
+            messages_per_second: stats.published as f64 / stats.age.as_secs_f64().max(1.0),
+            dropped: stats.dropped,
+            evicted: stats.evicted,
+            at_capacity: stats.at_capacity(config),
+            active_since: (Utc::now()
+                - Duration::from_std(stats.age).unwrap_or_else(|_| Duration::zero()))
+            .into(),
+        }
+    }
+}
@@ -1,13 +1,15 @@
 use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use chrono::{TimeZone, Utc};
 use uuid::Uuid;
 
-use super::{date, user::User};
+use super::{color::DisplayColor, date, user::User};
 use crate::{
     api::v1::gql::{
         error::{GqlError, Result, ResultExt},
         ext::ContextExt,
     },
-    database::chat_message,
+    database::{chat_message, emote_provider},
+    pb,
 };
 
 #[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
@@ -15,6 +17,11 @@ pub enum MessageType {
     User,
     Welcome,
     System,
+    Purchase,
+    Deleted,
+    Command,
+    Timer,
+    Giveaway,
 }
 
 #[derive(SimpleObject)]
@@ -26,6 +33,82 @@ pub struct ChatMessage {
     pub content: String,
     pub created_at: date::DateRFC3339,
     pub r#type: MessageType,
+    pub emotes: Vec<ChatMessageEmote>,
+    pub cheer: Option<ChatMessageCheer>,
+    /// The color of the author's name when the message was sent
+    pub author_color: Option<DisplayColor>,
+    /// The position of the message in the chat of the channel, counting up by one for every message.
+    /// A jump means messages were missed, they can be fetched with `chat.messages`.
+    /// The welcome message has the sequence of the last message before it
+    pub sequence: i64,
+    /// If the author is a VIP of the chat, shown as a badge
+    pub author_vip: bool,
+    /// If the author is a verified bot, shown as a badge
+    pub author_verified_bot: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct ChatMessageEmote {
+    /// The name of the emote
+    pub name: String,
+    /// The url of the emote image
+    pub url: String,
+    /// The third-party provider the emote comes from, null for the channel's own emotes
+    pub provider: Option<String>,
+    /// The character offset the emote starts at in the message
+    pub start: u32,
+    /// The character offset after the end of the emote in the message
+    pub end: u32,
+}
+
+impl From<emote_provider::EmoteToken> for ChatMessageEmote {
+    fn from(token: emote_provider::EmoteToken) -> Self {
+        Self {
+            name: token.emote.name,
+            url: token.emote.url,
+            provider: token.emote.provider,
+            start: token.start as u32,
+            end: token.end as u32,
+        }
+    }
+}
+
+impl From<pb::scuffle::events::ChatEmote> for ChatMessageEmote {
+    fn from(emote: pb::scuffle::events::ChatEmote) -> Self {
+        Self {
+            name: emote.name,
+            url: emote.url,
+            provider: emote.provider,
+            start: emote.start,
+            end: emote.end,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ChatMessageCheer {
+    /// The amount of bits cheered
+    pub bits: i64,
+    /// The prefix of the cheermote, e.g. `Cheer` in `Cheer100`
+    pub prefix: String,
+    /// The minimum amount of bits of the tier the cheer belongs to
+    pub tier_min_bits: i64,
+    /// The color of the tier as hex
+    pub color: String,
+    /// The url of the animated cheermote image
+    pub image_url: String,
+}
+
+impl From<pb::scuffle::events::ChatCheer> for ChatMessageCheer {
+    fn from(cheer: pb::scuffle::events::ChatCheer) -> Self {
+        Self {
+            bits: cheer.bits,
+            prefix: cheer.prefix,
+            tier_min_bits: cheer.tier_min_bits,
+            color: cheer.color,
+            image_url: cheer.image_url,
+        }
+    }
 }
 
 #[ComplexObject]
@@ -70,6 +153,44 @@ impl From<chat_message::Model> for ChatMessage {
             content: model.content,
             created_at: model.created_at.into(),
             r#type: MessageType::User,
+            emotes: Vec::new(),
+            cheer: None,
+            author_color: None,
+            sequence: 0,
+            author_vip: false,
+            author_verified_bot: false,
         }
     }
 }
+
+impl ChatMessage {
+    pub fn from_pb(event: pb::scuffle::events::ChatMessage) -> Result<Self> {
+        Ok(Self {
+            id: Uuid::parse_str(&event.id).map_err_gql("failed to parse chat message id")?,
+            author_id: Uuid::parse_str(&event.author_id)
+                .map_err_gql("failed to parse chat message author id")?,
+            channel_id: Uuid::parse_str(&event.channel_id)
+                .map_err_gql("failed to parse chat message channel id")?,
+            content: event.content,
+            created_at: Utc
+                .timestamp_opt(event.created_at, 0)
+                .single()
+                .map_err_gql("failed to parse chat message created at")?
+                .into(),
+            r#type: match pb::scuffle::events::chat_message::Type::from_i32(event.r#type) {
+                Some(pb::scuffle::events::chat_message::Type::Purchase) => MessageType::Purchase,
+                Some(pb::scuffle::events::chat_message::Type::Deleted) => MessageType::Deleted,
+                Some(pb::scuffle::events::chat_message::Type::Command) => MessageType::Command,
+                Some(pb::scuffle::events::chat_message::Type::Timer) => MessageType::Timer,
+                Some(pb::scuffle::events::chat_message::Type::Giveaway) => MessageType::Giveaway,
+                _ => MessageType::User,
+            },
+            emotes: event.emotes.into_iter().map(Into::into).collect(),
+            cheer: event.cheer.map(Into::into),
+            author_color: event.author_color.and_then(DisplayColor::from_pb),
+            sequence: event.sequence,
+            author_vip: event.author_vip,
+            author_verified_bot: event.author_verified_bot,
+        })
+    }
+}
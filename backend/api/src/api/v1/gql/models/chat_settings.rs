@@ -0,0 +1,25 @@
+use async_graphql::SimpleObject;
+
+use crate::database::user;
+
+#[derive(SimpleObject, Clone)]
+/// The rules the chat of a channel enforces. The broadcaster and VIPs are not held to them.
+pub struct ChatSettings {
+    /// The seconds a user has to wait between two messages, 0 if slow mode is off.
+    pub slow_mode: u32,
+    /// Whether messages with links are rejected.
+    pub block_links: bool,
+    /// Whether messages are kept after they were sent, so the chat of a stream can be exported.
+    /// If not, only the last messages are kept for clients which reconnect.
+    pub archive: bool,
+}
+
+impl From<user::Model> for ChatSettings {
+    fn from(value: user::Model) -> Self {
+        Self {
+            slow_mode: value.chat_slow_mode.max(0) as u32,
+            block_links: value.chat_block_links,
+            archive: value.chat_archive,
+        }
+    }
+}
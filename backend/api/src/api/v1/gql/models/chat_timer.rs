@@ -0,0 +1,43 @@
+use async_graphql::SimpleObject;
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::chat_timer;
+
+#[derive(SimpleObject, Clone)]
+/// A timer of a chat, posting its message every interval while the channel is live.
+pub struct ChatTimer {
+    pub id: Uuid,
+    pub channel_id: Uuid,
+    /// The name of the timer, only the broadcaster sees it.
+    pub name: String,
+    /// The message the timer posts, sent in the name of the broadcaster.
+    pub message: String,
+    /// The seconds between two posts.
+    pub interval: u32,
+    /// How many chat messages have to be sent after a post before the timer posts again.
+    pub min_messages: u32,
+    /// Whether the timer posts at all.
+    pub enabled: bool,
+    /// The time the timer last posted.
+    pub last_posted_at: Option<DateRFC3339>,
+    pub created_at: DateRFC3339,
+    pub updated_at: DateRFC3339,
+}
+
+impl From<chat_timer::Model> for ChatTimer {
+    fn from(value: chat_timer::Model) -> Self {
+        Self {
+            id: value.id,
+            channel_id: value.channel_id,
+            name: value.name,
+            message: value.message,
+            interval: value.interval as u32,
+            min_messages: value.min_messages as u32,
+            enabled: value.enabled,
+            last_posted_at: value.last_posted_at.map(Into::into),
+            created_at: value.created_at.into(),
+            updated_at: value.updated_at.into(),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use async_graphql::{ComplexObject, Context, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{GqlError, Result, ResultExt},
+        ext::ContextExt,
+    },
+    database::chat_vip,
+};
+
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+/// A VIP of a chat.
+pub struct ChatVip {
+    pub channel_id: Uuid,
+    pub user_id: Uuid,
+    /// The time the user was made a VIP.
+    pub created_at: DateRFC3339,
+}
+
+#[ComplexObject]
+impl ChatVip {
+    async fn user(&self, ctx: &Context<'_>) -> Result<User> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.user_id)
+            .await
+            .map_err_gql("failed to fetch user")?
+            .ok_or(GqlError::NotFound.with_message("user not found"))?;
+
+        Ok(User::from(user))
+    }
+}
+
+impl From<chat_vip::Model> for ChatVip {
+    fn from(value: chat_vip::Model) -> Self {
+        Self {
+            channel_id: value.channel_id,
+            user_id: value.user_id,
+            created_at: value.created_at.into(),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::checkout;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum CheckoutStatus {
+    Pending,
+    Completed,
+    Failed,
+}
+
+impl From<checkout::Status> for CheckoutStatus {
+    fn from(status: checkout::Status) -> Self {
+        match status {
+            checkout::Status::Pending => Self::Pending,
+            checkout::Status::Completed => Self::Completed,
+            checkout::Status::Failed => Self::Failed,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Checkout {
+    /// The checkout's id
+    pub id: Uuid,
+    /// The channel the purchase is for
+    pub channel_id: Uuid,
+    /// The user who receives the gift subscription
+    pub recipient_id: Option<Uuid>,
+    /// The amount to pay in cents
+    pub amount: i64,
+    /// The status of the checkout
+    pub status: CheckoutStatus,
+    /// The url where the user completes the payment
+    pub payment_url: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl Checkout {
+    pub fn from_model(value: checkout::Model, payment_url: String) -> Self {
+        Self {
+            id: value.id,
+            channel_id: value.channel_id,
+            recipient_id: value.recipient_id,
+            amount: value.amount,
+            status: value.status.into(),
+            payment_url,
+            created_at: value.created_at.into(),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use async_graphql::SimpleObject;
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::cheermote_tier;
+
+#[derive(SimpleObject)]
+pub struct CheermoteTier {
+    /// The tier's id
+    pub id: Uuid,
+    /// The channel the tier belongs to, null for the default tiers
+    pub channel_id: Option<Uuid>,
+    /// The word typed in front of the amount, e.g. `Cheer` in `Cheer100`
+    pub prefix: String,
+    /// The minimum amount of bits for this tier
+    pub min_bits: i64,
+    /// The color of the tier as hex
+    pub color: String,
+    /// The url of the animated image
+    pub image_url: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl From<cheermote_tier::Model> for CheermoteTier {
+    fn from(value: cheermote_tier::Model) -> Self {
+        Self {
+            id: value.id,
+            channel_id: value.channel_id,
+            prefix: value.prefix,
+            min_bits: value.min_bits,
+            color: value.color,
+            image_url: value.image_url,
+            created_at: value.created_at.into(),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use async_graphql::SimpleObject;
+
+use crate::database::display_color;
+use crate::pb;
+
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq)]
+pub struct Color {
+    /// The name of the palette color, null for custom colors
+    pub name: Option<String>,
+    /// The color to use on light themes as hex
+    pub light: String,
+    /// The color to use on dark themes as hex
+    pub dark: String,
+}
+
+impl From<display_color::Color> for Color {
+    fn from(color: display_color::Color) -> Self {
+        Self {
+            name: color.name,
+            light: color.light.to_hex(),
+            dark: color.dark.to_hex(),
+        }
+    }
+}
+
+impl From<pb::scuffle::events::ChatColor> for Color {
+    fn from(color: pb::scuffle::events::ChatColor) -> Self {
+        Self {
+            name: color.name,
+            light: color.light,
+            dark: color.dark,
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq)]
+pub struct DisplayColor {
+    /// The color of the name, or where the gradient starts
+    pub color: Color,
+    /// Where the gradient ends, null if the name has a solid color
+    pub gradient_end: Option<Color>,
+}
+
+impl From<display_color::DisplayColor> for DisplayColor {
+    fn from(color: display_color::DisplayColor) -> Self {
+        Self {
+            color: color.color.into(),
+            gradient_end: color.gradient_end.map(Color::from),
+        }
+    }
+}
+
+impl DisplayColor {
+    /// A color without its first stop is malformed and dropped.
+    pub fn from_pb(color: pb::scuffle::events::ChatDisplayColor) -> Option<Self> {
+        Some(Self {
+            color: color.color?.into(),
+            gradient_end: color.gradient_end.map(Color::from),
+        })
+    }
+}
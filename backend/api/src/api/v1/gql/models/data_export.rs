@@ -0,0 +1,95 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::api::v1::gql::{error::Result, error::ResultExt, ext::ContextExt, guards};
+use crate::database::data_export;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DataExportStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl From<data_export::Status> for DataExportStatus {
+    fn from(status: data_export::Status) -> Self {
+        match status {
+            data_export::Status::Queued => Self::Queued,
+            data_export::Status::Running => Self::Running,
+            data_export::Status::Completed => Self::Completed,
+            data_export::Status::Failed => Self::Failed,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct DataExport {
+    /// The export's id
+    pub id: Uuid,
+    /// The status of the export
+    pub status: DataExportStatus,
+    /// The size of the archive in bytes, 0 until the export completed
+    pub size_bytes: i64,
+    /// The reason the export failed
+    pub error: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Completed at
+    pub completed_at: Option<DateRFC3339>,
+    /// When the archive can't be downloaded anymore
+    pub expires_at: Option<DateRFC3339>,
+    /// The region the archive is kept in, empty for the default region
+    pub region: String,
+
+    // Private fields
+    #[graphql(skip)]
+    pub object_key_: String,
+    #[graphql(skip)]
+    pub downloadable_: bool,
+}
+
+#[ComplexObject]
+impl DataExport {
+    /// A signed url the archive can be downloaded with for a limited time, null until the export completed or after it expired.
+    /// The archive is a tar file with a JSON file each for the profile, follows, chat messages and sessions.
+    /// The logged in user has to have reauthenticated recently to see this.
+    async fn download_url(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let global = ctx.get_global();
+
+        guards::authorize_elevated(ctx).await?;
+
+        if !self.downloadable_ {
+            return Ok(None);
+        }
+
+        global
+            .object_url(
+                &self.region,
+                &self.object_key_,
+                global.config.data_exports.url_expiry,
+            )
+            .map(Some)
+            .map_err_gql("Failed to sign download url")
+    }
+}
+
+impl From<data_export::Model> for DataExport {
+    fn from(value: data_export::Model) -> Self {
+        Self {
+            downloadable_: value.downloadable(Utc::now()),
+            id: value.id,
+            status: value.status.into(),
+            size_bytes: value.size_bytes,
+            error: value.error,
+            created_at: value.created_at.into(),
+            completed_at: value.completed_at.map(Into::into),
+            expires_at: value.expires_at.map(Into::into),
+            region: value.region,
+            object_key_: value.object_key,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use async_graphql::{Enum, SimpleObject};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::{api::v1::gql::pagination::Cursor, database::dead_letter};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DeadLetterConsumer {
+    Notifications,
+    Moderation,
+    Import,
+    ImageProcessorResults,
+    DataExport,
+    VodDownloadResults,
+    VodCaptionResults,
+}
+
+impl From<dead_letter::Consumer> for DeadLetterConsumer {
+    fn from(consumer: dead_letter::Consumer) -> Self {
+        match consumer {
+            dead_letter::Consumer::Notifications => Self::Notifications,
+            dead_letter::Consumer::Moderation => Self::Moderation,
+            dead_letter::Consumer::Import => Self::Import,
+            dead_letter::Consumer::ImageProcessorResults => Self::ImageProcessorResults,
+            dead_letter::Consumer::DataExport => Self::DataExport,
+            dead_letter::Consumer::VodDownloadResults => Self::VodDownloadResults,
+            dead_letter::Consumer::VodCaptionResults => Self::VodCaptionResults,
+        }
+    }
+}
+
+impl From<DeadLetterConsumer> for dead_letter::Consumer {
+    fn from(consumer: DeadLetterConsumer) -> Self {
+        match consumer {
+            DeadLetterConsumer::Notifications => Self::Notifications,
+            DeadLetterConsumer::Moderation => Self::Moderation,
+            DeadLetterConsumer::Import => Self::Import,
+            DeadLetterConsumer::ImageProcessorResults => Self::ImageProcessorResults,
+            DeadLetterConsumer::DataExport => Self::DataExport,
+            DeadLetterConsumer::VodDownloadResults => Self::VodDownloadResults,
+            DeadLetterConsumer::VodCaptionResults => Self::VodCaptionResults,
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone)]
+pub struct DeadLetter {
+    /// The dead letter's id
+    pub id: Uuid,
+    /// The consumer which failed to handle the message
+    pub consumer: DeadLetterConsumer,
+    /// The message as it was delivered, base64 encoded
+    pub payload: String,
+    /// The message decoded with the protobuf message of the consumer, null if it is not a valid message
+    pub decoded: Option<String>,
+    /// Why the message could not be decoded, empty if it was decoded
+    pub decode_error: String,
+    /// The error of the last failed attempt
+    pub error: String,
+    /// The number of times handling the message failed
+    pub attempts: i64,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// When the message last failed
+    pub failed_at: DateRFC3339,
+    /// When the message was last published again
+    pub retried_at: Option<DateRFC3339>,
+    /// When the message was discarded
+    pub discarded_at: Option<DateRFC3339>,
+    /// Pass as `after` to get the dead letters which failed before this one
+    pub cursor: Cursor,
+}
+
+impl From<dead_letter::Model> for DeadLetter {
+    fn from(model: dead_letter::Model) -> Self {
+        let (decoded, decode_error) = match model.consumer.decode(&model.payload) {
+            Ok(decoded) => (Some(decoded), String::new()),
+            Err(e) => (None, e.to_string()),
+        };
+
+        Self {
+            id: model.id,
+            consumer: model.consumer.into(),
+            payload: BASE64.encode(&model.payload),
+            decoded,
+            decode_error,
+            error: model.error,
+            attempts: model.attempts,
+            created_at: model.created_at.into(),
+            failed_at: model.failed_at.into(),
+            retried_at: model.retried_at.map(Into::into),
+            discarded_at: model.discarded_at.map(Into::into),
+            cursor: Cursor::new(model.failed_at, model.id),
+        }
+    }
+}
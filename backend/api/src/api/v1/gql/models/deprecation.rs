@@ -0,0 +1,51 @@
+use async_graphql::SimpleObject;
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::{api::deprecation, database::deprecated_usage};
+
+#[derive(SimpleObject, Clone)]
+pub struct Deprecation {
+    /// The field as `Type.field` or the REST endpoint as `METHOD /v1/path`
+    pub surface: String,
+    /// When the deprecation was announced
+    pub deprecated_at: DateRFC3339,
+    /// When the field or endpoint is removed, null if no date was set yet
+    pub sunset: Option<DateRFC3339>,
+    /// What to use instead
+    pub replacement: Option<String>,
+}
+
+impl From<deprecation::Deprecation> for Deprecation {
+    fn from(deprecation: deprecation::Deprecation) -> Self {
+        Self {
+            surface: deprecation.surface,
+            deprecated_at: deprecation.deprecated_at.into(),
+            sunset: deprecation.sunset.map(Into::into),
+            replacement: deprecation.replacement,
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone)]
+pub struct DeprecatedUsage {
+    /// The client, `token:<id>` for access tokens, `session:<id>` for logins or `anonymous`
+    pub client: String,
+    /// The user the client belongs to, null for anonymous clients
+    pub user_id: Option<Uuid>,
+    /// How often the client used it
+    pub uses: i64,
+    /// When the client last used it
+    pub last_used_at: DateRFC3339,
+}
+
+impl From<deprecated_usage::ClientUsage> for DeprecatedUsage {
+    fn from(usage: deprecated_usage::ClientUsage) -> Self {
+        Self {
+            client: usage.client,
+            user_id: usage.user_id,
+            uses: usage.uses,
+            last_used_at: usage.last_used_at.into(),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::{discord_announcement, discord_integration};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DiscordTarget {
+    Webhook,
+    Bot,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DiscordAnnouncementKind {
+    GoLive,
+    Offline,
+    VodPublished,
+}
+
+impl From<discord_announcement::Kind> for DiscordAnnouncementKind {
+    fn from(kind: discord_announcement::Kind) -> Self {
+        match kind {
+            discord_announcement::Kind::GoLive => Self::GoLive,
+            discord_announcement::Kind::Offline => Self::Offline,
+            discord_announcement::Kind::VodPublished => Self::VodPublished,
+        }
+    }
+}
+
+impl From<DiscordAnnouncementKind> for discord_announcement::Kind {
+    fn from(kind: DiscordAnnouncementKind) -> Self {
+        match kind {
+            DiscordAnnouncementKind::GoLive => Self::GoLive,
+            DiscordAnnouncementKind::Offline => Self::Offline,
+            DiscordAnnouncementKind::VodPublished => Self::VodPublished,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct DiscordAnnouncement {
+    /// The notification this announces
+    pub kind: DiscordAnnouncementKind,
+    /// Whether the announcement is posted
+    pub enabled: bool,
+    /// The message, with variables like `{title}` replaced when posting
+    pub template: String,
+}
+
+impl From<discord_announcement::Model> for DiscordAnnouncement {
+    fn from(value: discord_announcement::Model) -> Self {
+        Self {
+            kind: value.kind.into(),
+            enabled: value.enabled,
+            template: value.template,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct DiscordIntegration {
+    /// The channel the integration belongs to
+    pub channel_id: Uuid,
+    /// Whether announcements are posted through a webhook or by a bot, the credentials are never returned
+    pub target: DiscordTarget,
+    /// The Discord channel the bot posts in
+    pub bot_channel_id: Option<String>,
+    /// The reason the last announcement could not be posted
+    pub last_error: String,
+    /// The announcements for every kind of notification
+    pub announcements: Vec<DiscordAnnouncement>,
+    /// Updated at
+    pub updated_at: DateRFC3339,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl DiscordIntegration {
+    pub fn from_model(
+        value: discord_integration::Model,
+        announcements: Vec<discord_announcement::Model>,
+    ) -> Self {
+        Self {
+            channel_id: value.channel_id,
+            target: match value.webhook_url_encrypted {
+                Some(_) => DiscordTarget::Webhook,
+                None => DiscordTarget::Bot,
+            },
+            bot_channel_id: value.bot_channel_id,
+            last_error: value.last_error,
+            announcements: announcements
+                .into_iter()
+                .map(DiscordAnnouncement::from)
+                .collect(),
+            updated_at: value.updated_at.into(),
+            created_at: value.created_at.into(),
+        }
+    }
+}
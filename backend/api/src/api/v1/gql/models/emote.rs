@@ -0,0 +1,140 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::{emote, emote_provider, emote_usage};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum EmoteStatus {
+    PendingReview,
+    Approved,
+    Rejected,
+}
+
+impl From<emote::Status> for EmoteStatus {
+    fn from(status: emote::Status) -> Self {
+        match status {
+            emote::Status::PendingReview => Self::PendingReview,
+            emote::Status::Approved => Self::Approved,
+            emote::Status::Rejected => Self::Rejected,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Emote {
+    /// The emote's id
+    pub id: Uuid,
+    /// The channel the emote belongs to
+    pub channel_id: Uuid,
+    /// The name used to type the emote in chat
+    pub name: String,
+    /// The url of the emote image
+    pub image_url: String,
+    /// The width of the image in pixels
+    pub width: i64,
+    /// The height of the image in pixels
+    pub height: i64,
+    /// The review status
+    pub status: EmoteStatus,
+    /// Why the emote was rejected
+    pub review_note: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Reviewed at
+    pub reviewed_at: Option<DateRFC3339>,
+}
+
+impl From<emote::Model> for Emote {
+    fn from(value: emote::Model) -> Self {
+        Self {
+            id: value.id,
+            channel_id: value.channel_id,
+            name: value.name,
+            image_url: value.image_url,
+            width: value.width,
+            height: value.height,
+            status: value.status.into(),
+            review_note: value.review_note,
+            created_at: value.created_at.into(),
+            reviewed_at: value.reviewed_at.map(Into::into),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ChatEmote {
+    /// The name used to type the emote in chat
+    pub name: String,
+    /// The url of the emote image
+    pub url: String,
+    /// The third-party provider the emote comes from, null for the channel's own emotes
+    pub provider: Option<String>,
+}
+
+impl From<emote_provider::Emote> for ChatEmote {
+    fn from(value: emote_provider::Emote) -> Self {
+        Self {
+            name: value.name,
+            url: value.url,
+            provider: value.provider,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct EmoteSlots {
+    /// The number of slots taken by pending and approved emotes
+    pub used: i64,
+    /// The number of slots the channel has
+    pub total: i64,
+    /// The sub points the slot count is based on
+    pub sub_points: i64,
+}
+
+#[derive(SimpleObject)]
+pub struct EmoteUsage {
+    /// The emote
+    pub emote: Emote,
+    /// The number of times the emote was used
+    pub uses: i64,
+    /// The last day the emote was used on
+    pub last_used: Option<DateRFC3339>,
+}
+
+#[derive(SimpleObject)]
+pub struct EmoteDailyUsage {
+    /// The start of the day
+    pub day: DateRFC3339,
+    /// The number of times the emote was used
+    pub uses: i64,
+}
+
+impl From<emote_usage::DailyUsage> for EmoteDailyUsage {
+    fn from(value: emote_usage::DailyUsage) -> Self {
+        Self {
+            day: value.day.into(),
+            uses: value.uses,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct EmoteProvider {
+    /// The name of the provider
+    pub provider: String,
+    /// The id of the channel's emote set at the provider
+    pub external_id: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl From<emote_provider::Model> for EmoteProvider {
+    fn from(value: emote_provider::Model) -> Self {
+        Self {
+            provider: value.provider,
+            external_id: value.external_id,
+            created_at: value.created_at.into(),
+        }
+    }
+}
@@ -0,0 +1,12 @@
+// The GQL objects of events which subscriptions expose as they are, generated from their `@gql` proto definitions.
+include!(concat!(env!("OUT_DIR"), "/scuffle.events.gql.rs"));
+
+impl From<crate::database::user::StreamInfo> for StreamInfo {
+    fn from(info: crate::database::user::StreamInfo) -> Self {
+        Self {
+            title: info.title,
+            category: info.category,
+            language: info.language,
+        }
+    }
+}
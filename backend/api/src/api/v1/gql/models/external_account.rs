@@ -0,0 +1,58 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::external_account;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ExternalProvider {
+    Google,
+    Discord,
+    Github,
+}
+
+impl From<external_account::Provider> for ExternalProvider {
+    fn from(provider: external_account::Provider) -> Self {
+        match provider {
+            external_account::Provider::Google => Self::Google,
+            external_account::Provider::Discord => Self::Discord,
+            external_account::Provider::Github => Self::Github,
+        }
+    }
+}
+
+impl From<ExternalProvider> for external_account::Provider {
+    fn from(provider: ExternalProvider) -> Self {
+        match provider {
+            ExternalProvider::Google => Self::Google,
+            ExternalProvider::Discord => Self::Discord,
+            ExternalProvider::Github => Self::Github,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ExternalAccount {
+    /// The link's id
+    pub id: Uuid,
+    /// The provider the account is at
+    pub provider: ExternalProvider,
+    /// The name of the account at the provider when it was linked
+    pub provider_username: String,
+    /// Linked at
+    pub created_at: DateRFC3339,
+    /// When the user last logged in with the account
+    pub last_login_at: Option<DateRFC3339>,
+}
+
+impl From<external_account::Model> for ExternalAccount {
+    fn from(value: external_account::Model) -> Self {
+        Self {
+            id: value.id,
+            provider: value.provider.into(),
+            provider_username: value.provider_username,
+            created_at: value.created_at.into(),
+            last_login_at: value.last_login_at.map(Into::into),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{GqlError, Result, ResultExt},
+        ext::ContextExt,
+        pagination::Cursor,
+    },
+    database::channel_event,
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum FollowSort {
+    /// The last follow first
+    #[default]
+    Recent,
+    /// By username
+    Alphabetical,
+    /// The live channels first, each group with the last follow first
+    LiveFirst,
+}
+
+impl From<FollowSort> for channel_event::FollowSort {
+    fn from(sort: FollowSort) -> Self {
+        match sort {
+            FollowSort::Recent => Self::Recent,
+            FollowSort::Alphabetical => Self::Alphabetical,
+            FollowSort::LiveFirst => Self::LiveFirst,
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+/// A follow in a list of followed channels or followers.
+pub struct Follow {
+    /// The followed channel, or the follower in a list of followers.
+    pub user_id: Uuid,
+    /// Whether the user is live right now.
+    pub live: bool,
+    /// The time of the follow.
+    pub followed_at: DateRFC3339,
+    /// Pass as `after` with the same sort to get the next page.
+    pub cursor: Cursor,
+}
+
+#[ComplexObject]
+impl Follow {
+    async fn user(&self, ctx: &Context<'_>) -> Result<User> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.user_id)
+            .await
+            .map_err_gql("failed to fetch user")?
+            .ok_or(GqlError::NotFound.with_message("user not found"))?;
+
+        Ok(User::from(user))
+    }
+}
+
+impl From<channel_event::Follow> for Follow {
+    fn from(value: channel_event::Follow) -> Self {
+        Self {
+            user_id: value.user_id,
+            live: value.live,
+            followed_at: value.created_at.into(),
+            cursor: Cursor::new(value.created_at, value.user_id),
+        }
+    }
+}
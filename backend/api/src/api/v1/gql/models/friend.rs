@@ -0,0 +1,95 @@
+use async_graphql::{ComplexObject, Context, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{GqlError, Result, ResultExt},
+        ext::ContextExt,
+        pagination::Cursor,
+    },
+    database::friend,
+};
+
+async fn load_user(ctx: &Context<'_>, user_id: Uuid) -> Result<User> {
+    let global = ctx.get_global();
+
+    let user = global
+        .user_by_id_loader
+        .load_one(user_id)
+        .await
+        .map_err_gql("failed to fetch user")?
+        .ok_or(GqlError::NotFound.with_message("user not found"))?;
+
+    Ok(User::from(user))
+}
+
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+/// A friend of the logged in user.
+pub struct Friend {
+    /// The friend.
+    pub user_id: Uuid,
+    /// The time the users became friends.
+    pub created_at: DateRFC3339,
+    /// Pass as `after` to get the friends made before this one.
+    pub cursor: Cursor,
+}
+
+#[ComplexObject]
+impl Friend {
+    async fn user(&self, ctx: &Context<'_>) -> Result<User> {
+        load_user(ctx, self.user_id).await
+    }
+}
+
+impl From<friend::Model> for Friend {
+    fn from(value: friend::Model) -> Self {
+        Self {
+            user_id: value.friend_id,
+            created_at: value.created_at.into(),
+            cursor: Cursor::new(value.created_at, value.friend_id),
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+/// A friend request the logged in user sent or was sent.
+pub struct FriendRequest {
+    /// The other user, who sent the request if it is incoming and was asked otherwise.
+    pub user_id: Uuid,
+    /// Whether the request was sent to the logged in user.
+    pub incoming: bool,
+    /// The time the request was sent.
+    pub created_at: DateRFC3339,
+    /// Pass as `after` to get the requests sent before this one.
+    pub cursor: Cursor,
+}
+
+#[ComplexObject]
+impl FriendRequest {
+    async fn user(&self, ctx: &Context<'_>) -> Result<User> {
+        load_user(ctx, self.user_id).await
+    }
+}
+
+impl FriendRequest {
+    /// The request as the user with the given id sees it.
+    pub fn new(value: friend::Request, viewer_id: Uuid) -> Self {
+        let incoming = value.friend_id == viewer_id;
+        let user_id = if incoming {
+            value.user_id
+        } else {
+            value.friend_id
+        };
+
+        Self {
+            user_id,
+            incoming,
+            created_at: value.created_at.into(),
+            // A user sends the viewer at most one request, so the other user breaks ties between requests of the same time.
+            cursor: Cursor::new(value.created_at, user_id),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+    },
+    database::{giveaway, giveaway_entry},
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GiveawayStatus {
+    Open,
+    Drawn,
+    Cancelled,
+}
+
+impl From<giveaway::Status> for GiveawayStatus {
+    fn from(status: giveaway::Status) -> Self {
+        match status {
+            giveaway::Status::Open => Self::Open,
+            giveaway::Status::Drawn => Self::Drawn,
+            giveaway::Status::Cancelled => Self::Cancelled,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Giveaway {
+    /// The giveaway's id
+    pub id: Uuid,
+    /// The channel running the giveaway
+    pub channel_id: Uuid,
+    /// What is given away
+    pub title: String,
+    /// The chat message which enters the giveaway, null if it is only entered with the button
+    pub keyword: Option<String>,
+    /// Whether only followers of the channel can enter
+    pub followers_only: bool,
+    /// The tickets a follower gets, everyone else gets one
+    pub follower_weight: u32,
+    /// The tickets a subscriber gets
+    pub subscriber_weight: u32,
+    /// The status of the giveaway
+    pub status: GiveawayStatus,
+    /// The sha256 of the seed as hex, published when the giveaway starts
+    pub commitment: String,
+    /// The seed the winner was drawn with, null while the giveaway is open.
+    /// The winner is the ticket the first 8 bytes of the sha256 of the seed, followed by the user id and
+    /// tickets (as a big endian i32) of every entry ordered by user id, modulo the total tickets falls on.
+    pub seed: Option<String>,
+    /// The user who won, null until drawn or if nobody entered
+    pub winner_id: Option<Uuid>,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Drawn or cancelled at
+    pub closed_at: Option<DateRFC3339>,
+}
+
+impl From<giveaway::Model> for Giveaway {
+    fn from(value: giveaway::Model) -> Self {
+        Self {
+            id: value.id,
+            channel_id: value.channel_id,
+            title: value.title,
+            keyword: value.keyword,
+            followers_only: value.followers_only,
+            follower_weight: value.follower_weight as u32,
+            subscriber_weight: value.subscriber_weight as u32,
+            status: value.status.into(),
+            commitment: value.commitment,
+            seed: Some(value.seed).filter(|_| value.status != giveaway::Status::Open),
+            winner_id: value.winner_id,
+            created_at: value.created_at.into(),
+            closed_at: value.closed_at.map(Into::into),
+        }
+    }
+}
+
+#[ComplexObject]
+impl Giveaway {
+    pub async fn winner(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let global = ctx.get_global();
+
+        let Some(winner_id) = self.winner_id else {
+            return Ok(None);
+        };
+
+        let user = global
+            .user_by_id_loader
+            .load_one(winner_id)
+            .await
+            .map_err_gql("failed to fetch user")?;
+
+        Ok(user.map(User::from))
+    }
+
+    /// The number of viewers who entered
+    pub async fn entry_count(&self, ctx: &Context<'_>) -> Result<i64> {
+        let global = ctx.get_global();
+
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM giveaway_entries WHERE giveaway_id = $1"#,
+            self.id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("failed to count entries")
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct GiveawayEntry {
+    /// The viewer who entered
+    pub user_id: Uuid,
+    /// How many tickets the viewer has in the draw
+    pub tickets: u32,
+    /// Entered at
+    pub created_at: DateRFC3339,
+}
+
+impl From<giveaway_entry::Model> for GiveawayEntry {
+    fn from(value: giveaway_entry::Model) -> Self {
+        Self {
+            user_id: value.user_id,
+            tickets: value.tickets as u32,
+            created_at: value.created_at.into(),
+        }
+    }
+}
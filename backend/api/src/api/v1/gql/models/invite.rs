@@ -0,0 +1,46 @@
+use async_graphql::SimpleObject;
+
+use super::date::DateRFC3339;
+use crate::database::invite_code;
+
+#[derive(SimpleObject)]
+pub struct InviteCode {
+    /// The code to share, like `ABCD-EFGH`
+    pub code: String,
+    pub created_at: DateRFC3339,
+    /// When someone registered with the code, null if it can still be used
+    pub used_at: Option<DateRFC3339>,
+}
+
+impl From<invite_code::Model> for InviteCode {
+    fn from(value: invite_code::Model) -> Self {
+        Self {
+            code: value.code,
+            created_at: value.created_at.into(),
+            used_at: value.used_at.map(Into::into),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct InviteTreeLevel {
+    /// How many invites away from a user who registered without one of another user, starting at 1
+    pub depth: i64,
+    /// The number of users who registered at this depth
+    pub users: i64,
+}
+
+#[derive(SimpleObject)]
+/// How the invites spread, for watching a controlled launch.
+pub struct InviteStats {
+    /// The invite codes users created, without the ones of admitted waitlist entries
+    pub codes_created: i64,
+    /// The invite codes users registered with, including the ones of admitted waitlist entries
+    pub codes_used: i64,
+    /// The users who registered with a code another user shared, by how far they are from the first users
+    pub tree: Vec<InviteTreeLevel>,
+    /// The waitlist entries which were not admitted yet
+    pub waitlist_pending: i64,
+    /// The waitlist entries which were admitted
+    pub waitlist_admitted: i64,
+}
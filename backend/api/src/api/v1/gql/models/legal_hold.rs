@@ -0,0 +1,117 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::{legal_hold, legal_hold_event};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum LegalHoldSubject {
+    User,
+    Channel,
+    Vod,
+}
+
+impl From<legal_hold::SubjectKind> for LegalHoldSubject {
+    fn from(kind: legal_hold::SubjectKind) -> Self {
+        match kind {
+            legal_hold::SubjectKind::User => Self::User,
+            legal_hold::SubjectKind::Channel => Self::Channel,
+            legal_hold::SubjectKind::Vod => Self::Vod,
+        }
+    }
+}
+
+impl From<LegalHoldSubject> for legal_hold::SubjectKind {
+    fn from(kind: LegalHoldSubject) -> Self {
+        match kind {
+            LegalHoldSubject::User => Self::User,
+            LegalHoldSubject::Channel => Self::Channel,
+            LegalHoldSubject::Vod => Self::Vod,
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum LegalHoldAction {
+    Placed,
+    Released,
+}
+
+impl From<legal_hold_event::Action> for LegalHoldAction {
+    fn from(action: legal_hold_event::Action) -> Self {
+        match action {
+            legal_hold_event::Action::Placed => Self::Placed,
+            legal_hold_event::Action::Released => Self::Released,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct LegalHold {
+    /// The hold's id
+    pub id: Uuid,
+    /// What kind of data is held
+    pub subject_kind: LegalHoldSubject,
+    /// The id of the held user, channel or VOD
+    pub subject_id: Uuid,
+    /// Why the data is preserved
+    pub reason: String,
+    /// The admin who placed the hold
+    pub placed_by: Option<Uuid>,
+    /// The admin who released the hold
+    pub released_by: Option<Uuid>,
+    /// Why the hold was released
+    pub release_reason: String,
+    /// If the hold is still in place
+    pub active: bool,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Released at
+    pub released_at: Option<DateRFC3339>,
+}
+
+impl From<legal_hold::Model> for LegalHold {
+    fn from(model: legal_hold::Model) -> Self {
+        Self {
+            id: model.id,
+            subject_kind: model.subject_kind.into(),
+            subject_id: model.subject_id,
+            reason: model.reason,
+            placed_by: model.placed_by,
+            released_by: model.released_by,
+            release_reason: model.release_reason,
+            active: model.released_at.is_none(),
+            created_at: model.created_at.into(),
+            released_at: model.released_at.map(Into::into),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct LegalHoldEvent {
+    /// The event's id
+    pub id: Uuid,
+    /// The hold the event belongs to
+    pub hold_id: Uuid,
+    /// The admin who took the action, null if their account was deleted
+    pub actor_id: Option<Uuid>,
+    /// What happened to the hold
+    pub action: LegalHoldAction,
+    /// The reason given for the action
+    pub note: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl From<legal_hold_event::Model> for LegalHoldEvent {
+    fn from(model: legal_hold_event::Model) -> Self {
+        Self {
+            id: model.id,
+            hold_id: model.hold_id,
+            actor_id: model.actor_id,
+            action: model.action.into(),
+            note: model.note,
+            created_at: model.created_at.into(),
+        }
+    }
+}
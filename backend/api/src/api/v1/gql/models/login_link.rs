@@ -0,0 +1,11 @@
+use async_graphql::SimpleObject;
+
+use super::date::DateRFC3339;
+
+#[derive(SimpleObject)]
+pub struct LoginLinkRequest {
+    /// The token this device logs in with once the link was opened, keep it secret
+    pub device_token: String,
+    /// When the link expires
+    pub expires_at: DateRFC3339,
+}
@@ -0,0 +1,136 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+    },
+    database::media_request,
+    global::GlobalState,
+};
+
+/// How many approved requests the queue of a channel shows.
+const QUEUE_LIMIT: i64 = 50;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum MediaRequestStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Played,
+}
+
+impl From<media_request::Status> for MediaRequestStatus {
+    fn from(status: media_request::Status) -> Self {
+        match status {
+            media_request::Status::Pending => Self::Pending,
+            media_request::Status::Approved => Self::Approved,
+            media_request::Status::Rejected => Self::Rejected,
+            media_request::Status::Played => Self::Played,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct MediaRequest {
+    /// The request's id
+    pub id: Uuid,
+    /// The channel the request was made in
+    pub channel_id: Uuid,
+    /// The viewer who made the request
+    pub user_id: Uuid,
+    /// The url of the media
+    pub url: String,
+    /// The status of the request
+    pub status: MediaRequestStatus,
+    /// Requested at
+    pub created_at: DateRFC3339,
+    /// Approved or rejected at
+    pub reviewed_at: Option<DateRFC3339>,
+    /// Played at
+    pub played_at: Option<DateRFC3339>,
+}
+
+impl From<media_request::Model> for MediaRequest {
+    fn from(value: media_request::Model) -> Self {
+        Self {
+            id: value.id,
+            channel_id: value.channel_id,
+            user_id: value.user_id,
+            url: value.url,
+            status: value.status.into(),
+            created_at: value.created_at.into(),
+            reviewed_at: value.reviewed_at.map(Into::into),
+            played_at: value.played_at.map(Into::into),
+        }
+    }
+}
+
+#[ComplexObject]
+impl MediaRequest {
+    pub async fn user(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.user_id)
+            .await
+            .map_err_gql("failed to fetch user")?;
+
+        Ok(user.map(User::from))
+    }
+}
+
+/// The media requests of a channel as an overlay shows them.
+#[derive(SimpleObject)]
+pub struct MediaRequestQueue {
+    /// Whether viewers can request media
+    pub enabled: bool,
+    /// The request played last, null if none was played yet
+    pub now_playing: Option<MediaRequest>,
+    /// The first 50 approved requests, in the order they are played
+    pub requests: Vec<MediaRequest>,
+}
+
+impl MediaRequestQueue {
+    pub async fn load(global: &GlobalState, channel_id: Uuid) -> Result<Self> {
+        let enabled = sqlx::query_scalar!(
+            "SELECT media_requests_enabled FROM users WHERE id = $1",
+            channel_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("failed to fetch channel")?
+        .unwrap_or_default();
+
+        let now_playing = sqlx::query_as!(
+            media_request::Model,
+            "SELECT * FROM media_requests WHERE channel_id = $1 AND status = $2 ORDER BY played_at DESC LIMIT 1",
+            channel_id,
+            i64::from(media_request::Status::Played),
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("failed to fetch media requests")?;
+
+        let requests = sqlx::query_as!(
+            media_request::Model,
+            "SELECT * FROM media_requests WHERE channel_id = $1 AND status = $2 ORDER BY created_at, id LIMIT $3",
+            channel_id,
+            i64::from(media_request::Status::Approved),
+            QUEUE_LIMIT,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("failed to fetch media requests")?;
+
+        Ok(Self {
+            enabled,
+            now_playing: now_playing.map(MediaRequest::from),
+            requests: requests.into_iter().map(MediaRequest::from).collect(),
+        })
+    }
+}
@@ -1,5 +1,61 @@
+pub mod access_token;
+pub mod approval;
+pub mod ban_appeal;
+pub mod blocked_user;
+pub mod bot;
+pub mod channel_appearance;
+pub mod channel_audit_event;
+pub mod channel_event;
+pub mod channel_import;
+pub mod channel_panel;
+pub mod charity;
+pub mod chat_ban;
+pub mod chat_command;
+pub mod chat_fan_out;
 pub mod chat_message;
+pub mod chat_settings;
+pub mod chat_timer;
+pub mod chat_vip;
+pub mod checkout;
+pub mod cheermote;
+pub mod color;
+pub mod data_export;
 pub mod date;
+pub mod dead_letter;
+pub mod deprecation;
+pub mod discord;
+pub mod emote;
+pub mod events;
+pub mod external_account;
+pub mod follow;
+pub mod friend;
+pub mod giveaway;
 pub mod global_roles;
+pub mod invite;
+pub mod legal_hold;
+pub mod login_link;
+pub mod media_request;
+pub mod moderation_job;
+pub mod muted_channel;
+pub mod notification_settings;
+pub mod obs;
+pub mod passkey;
+pub mod payout_method;
+pub mod presence;
+pub mod profile;
+pub mod promotion;
+pub mod recovery_code;
+pub mod revenue;
 pub mod session;
+pub mod social_link;
+pub mod stream_key;
+pub mod stream_lifecycle;
+pub mod stream_session;
+pub mod suspension;
+pub mod tag;
+pub mod ulid;
 pub mod user;
+pub mod viewer_queue;
+pub mod vod;
+pub mod vod_caption;
+pub mod vod_download;
@@ -0,0 +1,111 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::{channel_event, moderation_job};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ModerationJobKind {
+    BanUsers,
+    DeleteMessages,
+    RemoveFollows,
+}
+
+impl From<moderation_job::Kind> for ModerationJobKind {
+    fn from(kind: moderation_job::Kind) -> Self {
+        match kind {
+            moderation_job::Kind::BanUsers => Self::BanUsers,
+            moderation_job::Kind::DeleteMessages => Self::DeleteMessages,
+            moderation_job::Kind::RemoveFollows => Self::RemoveFollows,
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ModerationJobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl From<moderation_job::Status> for ModerationJobStatus {
+    fn from(status: moderation_job::Status) -> Self {
+        match status {
+            moderation_job::Status::Queued => Self::Queued,
+            moderation_job::Status::Running => Self::Running,
+            moderation_job::Status::Completed => Self::Completed,
+            moderation_job::Status::Failed => Self::Failed,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ModerationJob {
+    /// The job's id
+    pub id: Uuid,
+    /// The channel which is moderated
+    pub channel_id: Uuid,
+    /// The moderator who started the job
+    pub created_by: Uuid,
+    /// What the job does
+    pub kind: ModerationJobKind,
+    /// The status of the job
+    pub status: ModerationJobStatus,
+    /// The number of users, messages or follows the job handles
+    pub total_items: i64,
+    /// The number of users banned, messages deleted or follows removed so far
+    pub processed_items: i64,
+    /// The share of the items which has been handled, from 0 to 1
+    pub progress: f64,
+    /// The reason the job failed
+    pub error: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Updated at
+    pub updated_at: DateRFC3339,
+    /// Completed at
+    pub completed_at: Option<DateRFC3339>,
+}
+
+impl From<moderation_job::Model> for ModerationJob {
+    fn from(value: moderation_job::Model) -> Self {
+        Self {
+            progress: value.progress(),
+            id: value.id,
+            channel_id: value.channel_id,
+            created_by: value.created_by,
+            kind: value.kind.into(),
+            status: value.status.into(),
+            total_items: value.total_items,
+            processed_items: value.processed_items,
+            error: value.error,
+            created_at: value.created_at.into(),
+            updated_at: value.updated_at.into(),
+            completed_at: value.completed_at.map(Into::into),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct FollowSpike {
+    /// The start of the spike
+    pub starts_at: DateRFC3339,
+    /// The end of the spike
+    pub ends_at: DateRFC3339,
+    /// The number of follows during the spike
+    pub follows: i64,
+    /// The usual number of follows per minute
+    pub baseline: f64,
+}
+
+impl From<channel_event::FollowSpike> for FollowSpike {
+    fn from(value: channel_event::FollowSpike) -> Self {
+        Self {
+            starts_at: value.starts_at.into(),
+            ends_at: value.ends_at.into(),
+            follows: value.follows,
+            baseline: value.baseline,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use async_graphql::{ComplexObject, Context, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{GqlError, Result, ResultExt},
+        ext::ContextExt,
+        pagination::Cursor,
+    },
+    database::channel_user,
+};
+
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+/// A channel the logged in user muted.
+pub struct MutedChannel {
+    /// The channel which was muted.
+    pub channel_id: Uuid,
+    /// The time the channel was muted.
+    pub muted_at: DateRFC3339,
+    /// Pass as `after` to get the channels which were muted before this one.
+    pub cursor: Cursor,
+}
+
+#[ComplexObject]
+impl MutedChannel {
+    async fn channel(&self, ctx: &Context<'_>) -> Result<User> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.channel_id)
+            .await
+            .map_err_gql("failed to fetch user")?
+            .ok_or(GqlError::NotFound.with_message("user not found"))?;
+
+        Ok(User::from(user))
+    }
+}
+
+impl From<channel_user::Model> for MutedChannel {
+    fn from(value: channel_user::Model) -> Self {
+        // Only muted channels are turned into this.
+        let muted_at = value.muted_at.unwrap_or(value.created_at);
+
+        Self {
+            channel_id: value.channel_id,
+            muted_at: muted_at.into(),
+            cursor: Cursor::new(muted_at, value.channel_id),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use async_graphql::{Enum, SimpleObject};
+
+use super::date::DateRFC3339;
+use crate::database::notification_settings;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum NotificationCategory {
+    /// An email when a followed channel goes live
+    GoLive,
+    /// Someone followed your channel
+    NewFollower,
+    /// You were mentioned in chat
+    Mentions,
+    /// News and offers from Scuffle
+    MarketingEmails,
+    /// A weekly email of what the channels you follow did
+    WeeklyDigest,
+}
+
+impl From<NotificationCategory> for notification_settings::Category {
+    fn from(value: NotificationCategory) -> Self {
+        match value {
+            NotificationCategory::GoLive => Self::GoLive,
+            NotificationCategory::NewFollower => Self::NewFollower,
+            NotificationCategory::Mentions => Self::Mentions,
+            NotificationCategory::MarketingEmails => Self::MarketingEmails,
+            NotificationCategory::WeeklyDigest => Self::WeeklyDigest,
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone)]
+/// Which notifications the user gets, every category can be turned on or off with `setNotificationSetting`.
+pub struct NotificationSettings {
+    pub go_live: bool,
+    pub new_follower: bool,
+    pub mentions: bool,
+    /// Off until the user turns it on.
+    pub marketing_emails: bool,
+    pub weekly_digest: bool,
+    /// When the settings were last changed.
+    pub updated_at: DateRFC3339,
+}
+
+impl From<notification_settings::Model> for NotificationSettings {
+    fn from(value: notification_settings::Model) -> Self {
+        Self {
+            go_live: value.go_live,
+            new_follower: value.new_follower,
+            mentions: value.mentions,
+            marketing_emails: value.marketing_emails,
+            weekly_digest: value.weekly_digest,
+            updated_at: value.updated_at.into(),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::{obs_connection, obs_mapping};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ObsConnectionStatus {
+    Connecting,
+    Connected,
+    Failed,
+}
+
+impl From<obs_connection::Status> for ObsConnectionStatus {
+    fn from(status: obs_connection::Status) -> Self {
+        match status {
+            obs_connection::Status::Connecting => Self::Connecting,
+            obs_connection::Status::Connected => Self::Connected,
+            obs_connection::Status::Failed => Self::Failed,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ObsConnection {
+    /// The channel the connection belongs to
+    pub channel_id: Uuid,
+    /// The host OBS is reached at
+    pub host: String,
+    /// The port of the obs-websocket server
+    pub port: i64,
+    /// Whether a password is set, the password itself is never returned
+    pub has_password: bool,
+    /// Whether scene changes update the stream info
+    pub sync_scenes: bool,
+    /// Whether profile changes update the stream info
+    pub sync_profiles: bool,
+    /// Whether the stream info is updated when OBS starts streaming
+    pub update_on_go_live: bool,
+    /// The connection status
+    pub status: ObsConnectionStatus,
+    /// The reason the last connection attempt failed
+    pub last_error: String,
+    /// The time the current connection was established
+    pub connected_at: Option<DateRFC3339>,
+    /// Updated at
+    pub updated_at: DateRFC3339,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl From<obs_connection::Model> for ObsConnection {
+    fn from(value: obs_connection::Model) -> Self {
+        Self {
+            channel_id: value.channel_id,
+            host: value.host,
+            port: value.port,
+            has_password: value.password_encrypted.is_some(),
+            sync_scenes: value.sync_scenes,
+            sync_profiles: value.sync_profiles,
+            update_on_go_live: value.update_on_go_live,
+            status: value.status.into(),
+            last_error: value.last_error,
+            connected_at: value.connected_at.map(Into::into),
+            updated_at: value.updated_at.into(),
+            created_at: value.created_at.into(),
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ObsMappingKind {
+    Scene,
+    Profile,
+}
+
+impl From<obs_mapping::Kind> for ObsMappingKind {
+    fn from(kind: obs_mapping::Kind) -> Self {
+        match kind {
+            obs_mapping::Kind::Scene => Self::Scene,
+            obs_mapping::Kind::Profile => Self::Profile,
+        }
+    }
+}
+
+impl From<ObsMappingKind> for obs_mapping::Kind {
+    fn from(kind: ObsMappingKind) -> Self {
+        match kind {
+            ObsMappingKind::Scene => Self::Scene,
+            ObsMappingKind::Profile => Self::Profile,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ObsMapping {
+    /// The mapping's id
+    pub id: Uuid,
+    /// The channel the mapping belongs to
+    pub channel_id: Uuid,
+    /// Whether this maps a scene or a profile
+    pub kind: ObsMappingKind,
+    /// The name of the scene or profile in OBS
+    pub source_name: String,
+    /// The stream category to switch to
+    pub category: String,
+    /// The stream title to switch to, null to keep the current title
+    pub title: Option<String>,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl From<obs_mapping::Model> for ObsMapping {
+    fn from(value: obs_mapping::Model) -> Self {
+        Self {
+            id: value.id,
+            channel_id: value.channel_id,
+            kind: value.kind.into(),
+            source_name: value.source_name,
+            category: value.category,
+            title: value.title,
+            created_at: value.created_at.into(),
+        }
+    }
+}
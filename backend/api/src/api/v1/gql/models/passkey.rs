@@ -0,0 +1,109 @@
+use async_graphql::{InputObject, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::{passkey, passkey_challenge};
+use crate::global::passkey::{Assertion, Attestation};
+
+#[derive(SimpleObject)]
+pub struct Passkey {
+    /// The passkey's id
+    pub id: Uuid,
+    /// The name the user gave the passkey
+    pub name: String,
+    /// Registered at
+    pub created_at: DateRFC3339,
+    /// When the passkey was last used to log in
+    pub last_used_at: Option<DateRFC3339>,
+}
+
+impl From<passkey::Model> for Passkey {
+    fn from(value: passkey::Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            created_at: value.created_at.into(),
+            last_used_at: value.last_used_at.map(Into::into),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+/// The options to pass to `navigator.credentials`.
+pub struct PasskeyChallenge {
+    /// Pass back with the response of the authenticator
+    pub id: Uuid,
+    /// The base64url challenge the authenticator signs
+    pub challenge: String,
+    /// The relying party id
+    pub rp_id: String,
+    /// The base64url ids of the user's passkeys, to exclude when registering or allow when logging in
+    pub credential_ids: Vec<String>,
+    /// When the challenge can't be answered anymore
+    pub expires_at: DateRFC3339,
+}
+
+impl PasskeyChallenge {
+    pub fn new(model: passkey_challenge::Model, rp_id: &str, credential_ids: Vec<String>) -> Self {
+        Self {
+            id: model.id,
+            challenge: model.challenge,
+            rp_id: rp_id.to_string(),
+            credential_ids,
+            expires_at: model.expires_at.into(),
+        }
+    }
+}
+
+#[derive(InputObject)]
+/// The response of `navigator.credentials.create`, binary fields as base64url.
+pub struct PasskeyAttestationInput {
+    /// The id of the new credential.
+    pub credential_id: String,
+    /// The `clientDataJSON` of the response.
+    pub client_data_json: String,
+    /// The result of `getAuthenticatorData()`.
+    pub authenticator_data: String,
+    /// The result of `getPublicKey()`.
+    pub public_key: String,
+    /// The result of `getPublicKeyAlgorithm()`, only ES256 (-7) is supported.
+    pub public_key_algorithm: i64,
+}
+
+impl From<PasskeyAttestationInput> for Attestation {
+    fn from(value: PasskeyAttestationInput) -> Self {
+        Self {
+            credential_id: value.credential_id,
+            client_data_json: value.client_data_json,
+            authenticator_data: value.authenticator_data,
+            public_key: value.public_key,
+            public_key_algorithm: value.public_key_algorithm,
+        }
+    }
+}
+
+#[derive(InputObject)]
+/// The response of `navigator.credentials.get`, binary fields as base64url.
+pub struct PasskeyAssertionInput {
+    /// The id of the challenge which was answered.
+    pub challenge_id: Uuid,
+    /// The id of the credential which was used.
+    pub credential_id: String,
+    /// The `clientDataJSON` of the response.
+    pub client_data_json: String,
+    /// The `authenticatorData` of the response.
+    pub authenticator_data: String,
+    /// The `signature` of the response.
+    pub signature: String,
+}
+
+impl From<&PasskeyAssertionInput> for Assertion {
+    fn from(value: &PasskeyAssertionInput) -> Self {
+        Self {
+            credential_id: value.credential_id.clone(),
+            client_data_json: value.client_data_json.clone(),
+            authenticator_data: value.authenticator_data.clone(),
+            signature: value.signature.clone(),
+        }
+    }
+}
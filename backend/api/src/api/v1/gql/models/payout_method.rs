@@ -0,0 +1,84 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::api::v1::gql::{
+    error::{Result, ResultExt},
+    ext::ContextExt,
+};
+use crate::database::payout_method;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PayoutMethodStatus {
+    PendingVerification,
+    Verified,
+    Blocked,
+}
+
+impl From<payout_method::Status> for PayoutMethodStatus {
+    fn from(status: payout_method::Status) -> Self {
+        match status {
+            payout_method::Status::PendingVerification => Self::PendingVerification,
+            payout_method::Status::Verified => Self::Verified,
+            payout_method::Status::Blocked => Self::Blocked,
+        }
+    }
+}
+
+impl From<PayoutMethodStatus> for payout_method::Status {
+    fn from(status: PayoutMethodStatus) -> Self {
+        match status {
+            PayoutMethodStatus::PendingVerification => Self::PendingVerification,
+            PayoutMethodStatus::Verified => Self::Verified,
+            PayoutMethodStatus::Blocked => Self::Blocked,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct PayoutMethod {
+    /// The payout method's id
+    pub id: Uuid,
+    /// The channel which gets paid out
+    pub channel_id: Uuid,
+    /// The name of the payout provider
+    pub provider: String,
+    /// The verification status
+    pub status: PayoutMethodStatus,
+    /// A note left by the reviewing admin
+    pub review_note: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Reviewed at
+    pub reviewed_at: Option<DateRFC3339>,
+
+    // Private fields
+    #[graphql(skip)]
+    pub provider_account_id_: String,
+}
+
+#[ComplexObject]
+impl PayoutMethod {
+    /// The id of the account at the payout provider
+    async fn provider_account_id(&self, ctx: &Context<'_>) -> Result<String> {
+        ctx.get_global()
+            .decrypt_pii(&self.provider_account_id_)
+            .map_err_gql("Failed to decrypt payout account")
+    }
+}
+
+impl From<payout_method::Model> for PayoutMethod {
+    fn from(value: payout_method::Model) -> Self {
+        Self {
+            id: value.id,
+            channel_id: value.channel_id,
+            provider: value.provider,
+            status: value.status.into(),
+            review_note: value.review_note,
+            created_at: value.created_at.into(),
+            reviewed_at: value.reviewed_at.map(Into::into),
+            provider_account_id_: value.provider_account_id,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{GqlError, Result, ResultExt},
+        ext::ContextExt,
+    },
+    database::user,
+    global::presence,
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+/// Who can see whether a user is online.
+pub enum PresenceVisibility {
+    /// Nobody, the default.
+    Nobody,
+    /// Friends of the user.
+    Friends,
+    /// Everyone who follows the user, and their friends.
+    Followers,
+}
+
+impl From<user::PresenceVisibility> for PresenceVisibility {
+    fn from(value: user::PresenceVisibility) -> Self {
+        match value {
+            user::PresenceVisibility::Nobody => Self::Nobody,
+            user::PresenceVisibility::Friends => Self::Friends,
+            user::PresenceVisibility::Followers => Self::Followers,
+        }
+    }
+}
+
+impl From<PresenceVisibility> for user::PresenceVisibility {
+    fn from(value: PresenceVisibility) -> Self {
+        match value {
+            PresenceVisibility::Nobody => Self::Nobody,
+            PresenceVisibility::Friends => Self::Friends,
+            PresenceVisibility::Followers => Self::Followers,
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone)]
+/// The presence settings of the logged in user.
+pub struct PresenceSettings {
+    /// Who can see whether the user is online.
+    pub visibility: PresenceVisibility,
+    /// Whether the channel the user watches is shown to those who can see them online.
+    pub share_watching: bool,
+}
+
+impl From<user::Model> for PresenceSettings {
+    fn from(value: user::Model) -> Self {
+        Self {
+            visibility: value.presence_visibility.into(),
+            share_watching: value.presence_share_watching,
+        }
+    }
+}
+
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+/// A followed user who is online.
+pub struct Presence {
+    pub user_id: Uuid,
+    /// The time of the last request or playback heartbeat of the user.
+    pub last_active_at: DateRFC3339,
+    /// The channel the user is watching, null if they are not watching or don't share it.
+    pub watching_channel_id: Option<Uuid>,
+}
+
+#[ComplexObject]
+impl Presence {
+    async fn user(&self, ctx: &Context<'_>) -> Result<User> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.user_id)
+            .await
+            .map_err_gql("failed to fetch user")?
+            .ok_or(GqlError::NotFound.with_message("user not found"))?;
+
+        Ok(User::from(user))
+    }
+}
+
+impl From<presence::Presence> for Presence {
+    fn from(value: presence::Presence) -> Self {
+        Self {
+            user_id: value.user_id,
+            last_active_at: value.last_active_at.into(),
+            watching_channel_id: value.watching,
+        }
+    }
+}
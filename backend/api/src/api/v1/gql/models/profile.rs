@@ -0,0 +1,77 @@
+use async_graphql::{InputObject, SimpleObject};
+
+use super::{
+    color::DisplayColor,
+    social_link::{SocialLink, SocialLinkInput},
+};
+use crate::database::{display_color, user, user_social_link};
+use crate::pb;
+
+#[derive(InputObject)]
+/// A display color, checked like the arguments of `setDisplayColor`.
+pub struct DisplayColorInput {
+    /// A palette color like `blue` or a hex color like `#9146ff`. The color is removed if not set.
+    pub color: Option<String>,
+    /// The hex color to use on dark themes, only for hex colors.
+    pub dark_color: Option<String>,
+    /// The color the gradient ends in.
+    pub gradient_end: Option<String>,
+}
+
+#[derive(InputObject)]
+/// The profile fields to change, unset fields are left as they are.
+pub struct ProfileInput {
+    /// The color of the display name.
+    pub display_color: Option<DisplayColorInput>,
+    /// The bio as markdown, at most 1000 characters. An empty bio removes it.
+    pub bio: Option<String>,
+    /// The links to show on the profile in order, at most 5. An empty list removes all links.
+    pub social_links: Option<Vec<SocialLinkInput>>,
+    /// A BCP-47 language tag like `en-US`.
+    pub locale: Option<String>,
+    /// An IANA time zone like `Europe/Berlin`.
+    pub timezone: Option<String>,
+}
+
+#[derive(SimpleObject, Clone)]
+/// The public profile of a user.
+pub struct UserProfile {
+    /// The bio as sanitized markdown, empty if the user has none.
+    pub bio: String,
+    /// The color of the display name, null if the user did not pick one.
+    pub display_color: Option<DisplayColor>,
+    /// The links on the profile, in order.
+    pub social_links: Vec<SocialLink>,
+}
+
+impl UserProfile {
+    pub fn new(
+        user: &user::Model,
+        gradient_allowed: bool,
+        links: Vec<user_social_link::Model>,
+    ) -> Self {
+        Self {
+            bio: user.bio.clone(),
+            display_color: display_color::DisplayColor::of_user(user, gradient_allowed)
+                .map(DisplayColor::from),
+            social_links: links.into_iter().map(SocialLink::from).collect(),
+        }
+    }
+}
+
+impl From<pb::scuffle::events::UserProfileUpdated> for UserProfile {
+    fn from(event: pb::scuffle::events::UserProfileUpdated) -> Self {
+        Self {
+            bio: event.bio,
+            display_color: event.display_color.and_then(DisplayColor::from_pb),
+            social_links: event
+                .social_links
+                .into_iter()
+                .map(|link| SocialLink {
+                    label: link.label,
+                    url: link.url,
+                })
+                .collect(),
+        }
+    }
+}
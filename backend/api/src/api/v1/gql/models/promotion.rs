@@ -0,0 +1,92 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::promotion;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PromotionTarget {
+    Subscriptions,
+    GiftSubscriptions,
+}
+
+impl From<promotion::Target> for PromotionTarget {
+    fn from(target: promotion::Target) -> Self {
+        match target {
+            promotion::Target::Subscriptions => Self::Subscriptions,
+            promotion::Target::GiftSubscriptions => Self::GiftSubscriptions,
+        }
+    }
+}
+
+impl From<PromotionTarget> for promotion::Target {
+    fn from(target: PromotionTarget) -> Self {
+        match target {
+            PromotionTarget::Subscriptions => Self::Subscriptions,
+            PromotionTarget::GiftSubscriptions => Self::GiftSubscriptions,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Promotion {
+    /// The promotion's id
+    pub id: Uuid,
+    /// The name of the promotion
+    pub name: String,
+    /// What the discount applies to
+    pub target: PromotionTarget,
+    /// The discount in percent
+    pub percent_off: i64,
+    /// If only users who never subscribed to the channel get the discount
+    pub first_month_only: bool,
+    /// The channel the promotion is limited to, all channels if not set
+    pub channel_id: Option<Uuid>,
+    /// Starts at
+    pub starts_at: DateRFC3339,
+    /// Ends at
+    pub ends_at: DateRFC3339,
+}
+
+impl From<promotion::Model> for Promotion {
+    fn from(value: promotion::Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            target: value.target.into(),
+            percent_off: value.percent_off,
+            first_month_only: value.first_month_only,
+            channel_id: value.channel_id,
+            starts_at: value.starts_at.into(),
+            ends_at: value.ends_at.into(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Price {
+    /// The price without discount in cents
+    pub base_amount: i64,
+    /// The price to pay in cents
+    pub amount: i64,
+    /// The promotion which was applied
+    pub promotion: Option<Promotion>,
+}
+
+impl From<promotion::Price> for Price {
+    fn from(value: promotion::Price) -> Self {
+        Self {
+            base_amount: value.base_amount,
+            amount: value.amount,
+            promotion: value.promotion.map(Into::into),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Pricing {
+    /// The price of a subscription
+    pub subscription: Price,
+    /// The price of a gift subscription
+    pub gift_subscription: Price,
+}
@@ -0,0 +1,48 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::recovery_code_event;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RecoveryCodeAction {
+    /// New codes were generated, the old ones stopped working
+    Regenerated,
+    /// A code was used to log in
+    Used,
+}
+
+impl From<recovery_code_event::Action> for RecoveryCodeAction {
+    fn from(action: recovery_code_event::Action) -> Self {
+        match action {
+            recovery_code_event::Action::Regenerated => Self::Regenerated,
+            recovery_code_event::Action::Used => Self::Used,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct RecoveryCodeEvent {
+    /// The event's id
+    pub id: Uuid,
+    /// What happened
+    pub action: RecoveryCodeAction,
+    /// The address the request came from
+    pub ip_address: String,
+    /// The user agent of the request
+    pub user_agent: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl From<recovery_code_event::Model> for RecoveryCodeEvent {
+    fn from(value: recovery_code_event::Model) -> Self {
+        Self {
+            id: value.id,
+            action: value.action.into(),
+            ip_address: value.ip_address,
+            user_agent: value.user_agent,
+            created_at: value.created_at.into(),
+        }
+    }
+}
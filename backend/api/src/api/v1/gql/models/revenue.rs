@@ -0,0 +1,67 @@
+use async_graphql::SimpleObject;
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::api::v1::gql::pagination::Cursor;
+use crate::database::{payout_ledger_entry, revenue_transaction};
+
+#[derive(SimpleObject)]
+pub struct MonthlyRevenue {
+    /// The start of the month
+    pub month: DateRFC3339,
+    /// Income from subscriptions in cents
+    pub subscriptions: i64,
+    /// Income from bits in cents
+    pub bits: i64,
+    /// Income from gifts in cents
+    pub gifts: i64,
+    /// Total income before the platform fee in cents
+    pub gross: i64,
+    /// The part of the income the platform keeps in cents
+    pub platform_fee: i64,
+    /// The income the channel keeps in cents
+    pub net: i64,
+}
+
+impl From<revenue_transaction::MonthlyRevenue> for MonthlyRevenue {
+    fn from(value: revenue_transaction::MonthlyRevenue) -> Self {
+        Self {
+            net: value.net(),
+            month: value.month.into(),
+            subscriptions: value.subscriptions,
+            bits: value.bits,
+            gifts: value.gifts,
+            gross: value.gross,
+            platform_fee: value.platform_fee,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct PayoutLedgerEntry {
+    /// The entry's id
+    pub id: Uuid,
+    /// The channel the entry belongs to
+    pub channel_id: Uuid,
+    /// The amount in cents, negative amounts are payouts
+    pub amount: i64,
+    /// What the entry is for
+    pub description: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Pass as `after` to get the entries created before this one
+    pub cursor: Cursor,
+}
+
+impl From<payout_ledger_entry::Model> for PayoutLedgerEntry {
+    fn from(value: payout_ledger_entry::Model) -> Self {
+        Self {
+            id: value.id,
+            channel_id: value.channel_id,
+            amount: value.amount,
+            description: value.description,
+            created_at: value.created_at.into(),
+            cursor: Cursor::new(value.created_at, value.id),
+        }
+    }
+}
@@ -2,9 +2,12 @@ use async_graphql::{ComplexObject, Context, SimpleObject};
 use uuid::Uuid;
 
 use super::{date, user::User};
-use crate::api::v1::gql::{
-    error::{GqlError, Result, ResultExt},
-    ext::ContextExt,
+use crate::{
+    api::v1::gql::{
+        error::{GqlError, Result, ResultExt},
+        ext::ContextExt,
+    },
+    database::session,
 };
 
 #[derive(SimpleObject)]
@@ -46,3 +49,36 @@ impl Session {
         Ok(User::from(user))
     }
 }
+
+#[derive(SimpleObject)]
+/// A session of the logged in user which was not revoked and has not expired.
+pub struct ActiveSession {
+    /// The session's id
+    pub id: Uuid,
+    /// Whether this is the session the request was made with
+    pub current: bool,
+    /// The address the session was logged in from, empty if unknown
+    pub ip_address: String,
+    /// The user agent of the client which logged in, empty if unknown
+    pub user_agent: String,
+    /// Expires at
+    pub expires_at: date::DateRFC3339,
+    /// Last used at
+    pub last_used_at: date::DateRFC3339,
+    /// Created at
+    pub created_at: date::DateRFC3339,
+}
+
+impl ActiveSession {
+    pub fn new(session: session::Model, current_id: Uuid) -> Self {
+        Self {
+            id: session.id,
+            current: session.id == current_id,
+            ip_address: session.ip_address,
+            user_agent: session.user_agent,
+            expires_at: session.expires_at.into(),
+            last_used_at: session.last_used_at.into(),
+            created_at: session.created_at.into(),
+        }
+    }
+}
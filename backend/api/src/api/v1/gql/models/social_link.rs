@@ -0,0 +1,30 @@
+use async_graphql::{InputObject, SimpleObject};
+
+use crate::database::user_social_link;
+
+#[derive(SimpleObject, Clone)]
+/// A link on the profile of a user.
+pub struct SocialLink {
+    /// The text the link is shown with.
+    pub label: String,
+    /// The url the link goes to.
+    pub url: String,
+}
+
+impl From<user_social_link::Model> for SocialLink {
+    fn from(value: user_social_link::Model) -> Self {
+        Self {
+            label: value.label,
+            url: value.url,
+        }
+    }
+}
+
+#[derive(InputObject)]
+/// A link to put on the profile of the logged in user.
+pub struct SocialLinkInput {
+    /// The text the link is shown with, at most 32 characters.
+    pub label: String,
+    /// The url the link goes to, a http(s) or mailto link.
+    pub url: String,
+}
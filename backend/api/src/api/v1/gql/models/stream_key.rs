@@ -0,0 +1,52 @@
+use async_graphql::SimpleObject;
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::{stream_key, user};
+
+#[derive(SimpleObject)]
+pub struct StreamKey {
+    /// The key's id, null for the default key of the channel
+    pub id: Option<Uuid>,
+    /// The name given to the key
+    pub name: String,
+    /// The key with all but its last characters hidden
+    pub masked_key: String,
+    /// When the key was last used to go live, always null for the default key
+    pub last_used_at: Option<DateRFC3339>,
+    /// Created at, null for the default key
+    pub created_at: Option<DateRFC3339>,
+}
+
+impl StreamKey {
+    /// The key every channel has, which admins can reset too.
+    pub fn default_key(channel: &user::Model) -> Self {
+        Self {
+            id: None,
+            name: "Default".to_string(),
+            masked_key: stream_key::mask(channel.id, &channel.stream_key),
+            last_used_at: None,
+            created_at: None,
+        }
+    }
+}
+
+impl From<stream_key::Model> for StreamKey {
+    fn from(value: stream_key::Model) -> Self {
+        Self {
+            id: Some(value.id),
+            masked_key: stream_key::mask(value.channel_id, &value.key),
+            name: value.name,
+            last_used_at: value.last_used_at.map(Into::into),
+            created_at: Some(value.created_at.into()),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CreatedStreamKey {
+    /// The created or regenerated key
+    pub stream_key: StreamKey,
+    /// The full key to paste into the encoder, a named key is only shown in full here
+    pub key: String,
+}
@@ -0,0 +1,92 @@
+use async_graphql::{Enum, SimpleObject};
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::{stream::Lifecycle, stream_lifecycle_transition};
+use crate::pb::scuffle::events::{stream_lifecycle_changed::State, StreamLifecycleChanged};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum StreamLifecycle {
+    /// The ingest accepted the stream, it is not transcoded yet
+    Requested,
+    /// The variants of the stream are known, viewers can't watch yet
+    Ready,
+    /// Viewers can watch the stream
+    Live,
+    /// The connection dropped, the stream continues if the streamer reconnects in time
+    Reconnecting,
+    /// The stream is over
+    Ended,
+    /// The stream is over and its recording is being published
+    ProcessingVod,
+}
+
+impl From<Lifecycle> for StreamLifecycle {
+    fn from(state: Lifecycle) -> Self {
+        match state {
+            Lifecycle::Requested => Self::Requested,
+            Lifecycle::Ready => Self::Ready,
+            Lifecycle::Live => Self::Live,
+            Lifecycle::Reconnecting => Self::Reconnecting,
+            Lifecycle::Ended => Self::Ended,
+            Lifecycle::ProcessingVod => Self::ProcessingVod,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct StreamLifecycleTransition {
+    /// The stream which moved
+    pub stream_id: Uuid,
+    /// The state it was in
+    pub from: StreamLifecycle,
+    /// The state it moved to
+    pub to: StreamLifecycle,
+    /// Changed at
+    pub changed_at: DateRFC3339,
+}
+
+impl From<stream_lifecycle_transition::Model> for StreamLifecycleTransition {
+    fn from(model: stream_lifecycle_transition::Model) -> Self {
+        Self {
+            stream_id: model.stream_id,
+            from: model.from_state.into(),
+            to: model.to_state.into(),
+            changed_at: model.created_at.into(),
+        }
+    }
+}
+
+impl TryFrom<StreamLifecycleChanged> for StreamLifecycleTransition {
+    type Error = anyhow::Error;
+
+    fn try_from(event: StreamLifecycleChanged) -> Result<Self, Self::Error> {
+        let state = |value: i32| {
+            State::from_i32(value)
+                .map(|state| Lifecycle::from(state).into())
+                .ok_or_else(|| anyhow::anyhow!("invalid lifecycle state: {}", value))
+        };
+
+        Ok(Self {
+            stream_id: event.stream_id.parse()?,
+            from: state(event.from)?,
+            to: state(event.to)?,
+            changed_at: Utc
+                .timestamp_opt(event.changed_at, 0)
+                .single()
+                .ok_or_else(|| anyhow::anyhow!("invalid timestamp: {}", event.changed_at))?
+                .into(),
+        })
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct StreamStatus {
+    /// The stream's id
+    pub stream_id: Uuid,
+    /// Where the stream is in its life
+    pub state: StreamLifecycle,
+    /// Every move the stream made, oldest first
+    pub transitions: Vec<StreamLifecycleTransition>,
+}
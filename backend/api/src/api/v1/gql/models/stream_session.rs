@@ -0,0 +1,41 @@
+use async_graphql::SimpleObject;
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::api::v1::gql::pagination::Cursor;
+use crate::database::stream_session;
+
+#[derive(SimpleObject)]
+pub struct StreamSession {
+    /// The session's id
+    pub id: Uuid,
+    /// The channel which streamed
+    pub channel_id: Uuid,
+    /// The stream this session summarizes
+    pub stream_id: Uuid,
+    /// The number of distinct users who chatted
+    pub unique_chatters: i64,
+    /// The number of new followers
+    pub new_follows: i64,
+    /// Started at
+    pub started_at: DateRFC3339,
+    /// Ended at
+    pub ended_at: DateRFC3339,
+    /// Pass as `after` to get the sessions which ended before this one
+    pub cursor: Cursor,
+}
+
+impl From<stream_session::Model> for StreamSession {
+    fn from(model: stream_session::Model) -> Self {
+        Self {
+            id: model.id,
+            channel_id: model.channel_id,
+            stream_id: model.stream_id,
+            unique_chatters: model.unique_chatters,
+            new_follows: model.new_follows,
+            started_at: model.started_at.into(),
+            ended_at: model.ended_at.into(),
+            cursor: Cursor::new(model.ended_at, model.id),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use async_graphql::{Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::database::{user_suspension, user_suspension_appeal};
+
+#[derive(SimpleObject)]
+pub struct Suspension {
+    /// The suspension's id
+    pub id: Uuid,
+    /// The suspended user
+    pub user_id: Uuid,
+    /// The admin who suspended the user
+    pub suspended_by: Option<Uuid>,
+    /// Why the user was suspended
+    pub reason: String,
+    /// The admin who lifted the suspension, null if it expired
+    pub lifted_by: Option<Uuid>,
+    /// Why the suspension was lifted
+    pub lift_reason: String,
+    /// If the user is still suspended
+    pub active: bool,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// When the suspension ends on its own, null if it is permanent
+    pub expires_at: Option<DateRFC3339>,
+    /// Lifted at
+    pub lifted_at: Option<DateRFC3339>,
+}
+
+impl From<user_suspension::Model> for Suspension {
+    fn from(model: user_suspension::Model) -> Self {
+        Self {
+            active: model.is_active(),
+            id: model.id,
+            user_id: model.user_id,
+            suspended_by: model.suspended_by,
+            reason: model.reason,
+            lifted_by: model.lifted_by,
+            lift_reason: model.lift_reason,
+            created_at: model.created_at.into(),
+            expires_at: model.expires_at.map(Into::into),
+            lifted_at: model.lifted_at.map(Into::into),
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SuspensionAppealStatus {
+    Pending,
+    Accepted,
+    Denied,
+}
+
+impl From<user_suspension_appeal::Status> for SuspensionAppealStatus {
+    fn from(status: user_suspension_appeal::Status) -> Self {
+        match status {
+            user_suspension_appeal::Status::Pending => Self::Pending,
+            user_suspension_appeal::Status::Accepted => Self::Accepted,
+            user_suspension_appeal::Status::Denied => Self::Denied,
+        }
+    }
+}
+
+impl From<SuspensionAppealStatus> for user_suspension_appeal::Status {
+    fn from(status: SuspensionAppealStatus) -> Self {
+        match status {
+            SuspensionAppealStatus::Pending => Self::Pending,
+            SuspensionAppealStatus::Accepted => Self::Accepted,
+            SuspensionAppealStatus::Denied => Self::Denied,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct SuspensionAppeal {
+    /// The appeal's id
+    pub id: Uuid,
+    /// The appealed suspension
+    pub suspension_id: Uuid,
+    /// Why the suspension should be lifted
+    pub message: String,
+    /// The status of the appeal
+    pub status: SuspensionAppealStatus,
+    /// The admin who reviewed the appeal
+    pub reviewed_by: Option<Uuid>,
+    /// The note the admin left for the user
+    pub review_note: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Reviewed at
+    pub reviewed_at: Option<DateRFC3339>,
+}
+
+impl From<user_suspension_appeal::Model> for SuspensionAppeal {
+    fn from(model: user_suspension_appeal::Model) -> Self {
+        Self {
+            id: model.id,
+            suspension_id: model.suspension_id,
+            message: model.message,
+            status: model.status.into(),
+            reviewed_by: model.reviewed_by,
+            review_note: model.review_note,
+            created_at: model.created_at.into(),
+            reviewed_at: model.reviewed_at.map(Into::into),
+        }
+    }
+}
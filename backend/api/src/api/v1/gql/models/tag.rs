@@ -0,0 +1,103 @@
+use async_graphql::{ComplexObject, Context, SimpleObject};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+        pagination::Cursor,
+    },
+    database::tag,
+};
+
+#[derive(SimpleObject, Clone)]
+pub struct Tag {
+    /// The tag's id
+    pub id: Uuid,
+    /// The name of the tag
+    pub name: String,
+    /// What the tag is for
+    pub description: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+}
+
+impl From<tag::Model> for Tag {
+    fn from(value: tag::Model) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            description: value.description,
+            created_at: value.created_at.into(),
+        }
+    }
+}
+
+/// A live channel as the directory lists it.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct DirectoryChannel {
+    /// The channel which is live
+    pub channel_id: Uuid,
+    /// The live stream
+    pub stream_id: Uuid,
+    /// The title of the stream
+    pub title: String,
+    /// The category of the stream, empty if not set
+    pub category: String,
+    /// The BCP-47 language tag of the stream, empty if not set
+    pub language: String,
+    /// When the stream started
+    pub started_at: DateRFC3339,
+    /// Pass as `after` to get the channels which went live before this one
+    pub cursor: Cursor,
+}
+
+impl DirectoryChannel {
+    pub fn new(
+        channel_id: Uuid,
+        stream_id: Uuid,
+        title: String,
+        category: String,
+        language: String,
+        started_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            channel_id,
+            stream_id,
+            title,
+            category,
+            language,
+            started_at: started_at.into(),
+            cursor: Cursor::new(started_at, stream_id),
+        }
+    }
+}
+
+#[ComplexObject]
+impl DirectoryChannel {
+    pub async fn channel(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.channel_id)
+            .await
+            .map_err_gql("failed to fetch channel")?;
+
+        Ok(user.map(User::from))
+    }
+
+    /// The tags of the channel, in the order the broadcaster put them in
+    pub async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<Tag>> {
+        let global = ctx.get_global();
+
+        let tags = tag::for_channel(&*global.db, self.channel_id)
+            .await
+            .map_err_gql("failed to fetch tags")?;
+
+        Ok(tags.into_iter().map(Tag::from).collect())
+    }
+}
@@ -0,0 +1,91 @@
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+use chrono::{DateTime, TimeZone, Utc};
+use uuid::Uuid;
+
+/// Crockford's base32, it leaves out I, L, O and U so ids can be read out loud.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// The length of an encoded ULID, 26 characters of 5 bits hold the 128 bits of the id.
+const ENCODED_LENGTH: usize = 26;
+
+/// An id in ULID format. ULIDs and UUIDs are both 128 bits, so every UUID has exactly one ULID and the other way around.
+/// Inputs can be either format, outputs are always ULIDs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GqlUlid(pub Uuid);
+
+impl GqlUlid {
+    /// The time in the first 48 bits of the id.
+    /// Only ids which were generated as ULIDs have a meaningful time, for random UUIDs it is random as well.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        let millis = (self.0.as_u128() >> 80) as i64;
+
+        // 48 bits of milliseconds end in the year 10889, which chrono can represent.
+        Utc.timestamp_millis_opt(millis)
+            .single()
+            .expect("48 bit timestamp out of range")
+    }
+}
+
+/// Formats an id as a ULID.
+pub fn encode(id: Uuid) -> String {
+    let value = id.as_u128();
+
+    (0..ENCODED_LENGTH)
+        .rev()
+        .map(|i| ALPHABET[((value >> (i * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+/// Parses a ULID. Lowercase letters are accepted, and so are the letters Crockford's base32 reads as digits.
+pub fn decode(ulid: &str) -> Option<Uuid> {
+    if ulid.len() != ENCODED_LENGTH {
+        return None;
+    }
+
+    let mut value = 0u128;
+    for (i, c) in ulid.bytes().enumerate() {
+        let digit = match c.to_ascii_uppercase() {
+            b'O' => 0,
+            b'I' | b'L' => 1,
+            c => ALPHABET.iter().position(|a| *a == c)? as u128,
+        };
+
+        // The first character only holds 3 bits, anything larger overflows 128 bits.
+        if i == 0 && digit > 7 {
+            return None;
+        }
+
+        value = (value << 5) | digit;
+    }
+
+    Some(Uuid::from_u128(value))
+}
+
+#[Scalar(name = "ULID")]
+impl ScalarType for GqlUlid {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match value {
+            Value::String(s) => decode(&s)
+                .or_else(|| Uuid::parse_str(&s).ok())
+                .map(GqlUlid)
+                .ok_or_else(|| InputValueError::custom("Invalid ULID")),
+            _ => Err(InputValueError::custom("Invalid value")),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(encode(self.0))
+    }
+}
+
+impl From<Uuid> for GqlUlid {
+    fn from(id: Uuid) -> Self {
+        GqlUlid(id)
+    }
+}
+
+impl From<GqlUlid> for Uuid {
+    fn from(id: GqlUlid) -> Self {
+        id.0
+    }
+}
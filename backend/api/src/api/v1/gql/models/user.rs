@@ -2,12 +2,27 @@ use async_graphql::{ComplexObject, Context, SimpleObject};
 use uuid::Uuid;
 
 use crate::api::v1::gql::{
-    error::{GqlError, Result},
+    error::{GqlError, Result, ResultExt},
     ext::ContextExt,
+    pagination::{page_limit, Cursor},
+};
+use crate::database::{
+    channel_appearance, channel_event, channel_user, display_color, global_role,
+    notification_settings, user, user_social_link,
+};
+
+use super::{
+    channel_appearance::Banner,
+    color::DisplayColor,
+    date::DateRFC3339,
+    follow::{Follow, FollowSort},
+    global_roles::GlobalRole,
+    notification_settings::NotificationSettings,
+    social_link::SocialLink,
 };
-use crate::database::{global_role, user};
 
-use super::{date::DateRFC3339, global_roles::GlobalRole};
+const DEFAULT_FOLLOWS_LIMIT: u32 = 50;
+const MAX_FOLLOWS_LIMIT: u32 = 100;
 
 #[derive(SimpleObject, Clone)]
 #[graphql(complex)]
@@ -15,6 +30,13 @@ pub struct User {
     pub id: Uuid,
     pub display_name: String,
     pub username: String,
+    pub profile_image_url: Option<String>,
+    /// Whether a new profile picture is being processed. The old one is shown until it is done.
+    pub profile_image_pending: bool,
+    /// The bio shown on the profile page, as sanitized markdown.
+    pub bio: Option<String>,
+    /// Whether an admin verified the user as a bot, shown as a badge.
+    pub verified_bot: bool,
     pub created_at: DateRFC3339,
 
     // Private fields
@@ -26,13 +48,23 @@ pub struct User {
     pub last_login_at_: DateRFC3339,
     #[graphql(skip)]
     pub stream_key_: String,
+    #[graphql(skip)]
+    pub display_color_: String,
+    #[graphql(skip)]
+    pub display_gradient_end_: String,
+    #[graphql(skip)]
+    pub locale_: String,
+    #[graphql(skip)]
+    pub timezone_: String,
+    #[graphql(skip)]
+    pub data_region_: String,
 }
 
 /// TODO: find a better way to check if a user is allowed to read a field.
 
 #[ComplexObject]
 impl User {
-    async fn email(&self, ctx: &Context<'_>) -> Result<&str> {
+    async fn email(&self, ctx: &Context<'_>) -> Result<String> {
         let global = ctx.get_global();
         let request_context = ctx.get_session();
 
@@ -44,7 +76,9 @@ impl User {
                     .permissions
                     .has_permission(global_role::Permission::Admin)
             {
-                return Ok(&self.email_);
+                return global
+                    .decrypt_pii(&self.email_)
+                    .map_err_gql("Failed to decrypt email");
             }
         }
 
@@ -116,6 +150,97 @@ impl User {
             .with_field(vec!["stream_key"]))
     }
 
+    /// The BCP-47 language tag emails to the user are written in. Only the user and admins can see this.
+    async fn locale(&self, ctx: &Context<'_>) -> Result<String> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        let session = request_context.get_session(global).await?;
+
+        if let Some((session, perms)) = session {
+            if session.user_id == self.id
+                || perms
+                    .permissions
+                    .has_permission(global_role::Permission::Admin)
+            {
+                return Ok(self.locale_.clone());
+            }
+        }
+
+        Err(GqlError::Unauthorized
+            .with_message("you are not allowed to see this field")
+            .with_field(vec!["locale"]))
+    }
+
+    /// The IANA time zone times are shown to the user in. Only the user and admins can see this.
+    async fn timezone(&self, ctx: &Context<'_>) -> Result<String> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        let session = request_context.get_session(global).await?;
+
+        if let Some((session, perms)) = session {
+            if session.user_id == self.id
+                || perms
+                    .permissions
+                    .has_permission(global_role::Permission::Admin)
+            {
+                return Ok(self.timezone_.clone());
+            }
+        }
+
+        Err(GqlError::Unauthorized
+            .with_message("you are not allowed to see this field")
+            .with_field(vec!["timezone"]))
+    }
+
+    /// The region the data of the user is kept in, empty for the default region. Only the user and admins can see this.
+    async fn data_region(&self, ctx: &Context<'_>) -> Result<String> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        let session = request_context.get_session(global).await?;
+
+        if let Some((session, perms)) = session {
+            if session.user_id == self.id
+                || perms
+                    .permissions
+                    .has_permission(global_role::Permission::Admin)
+            {
+                return Ok(self.data_region_.clone());
+            }
+        }
+
+        Err(GqlError::Unauthorized
+            .with_message("you are not allowed to see this field")
+            .with_field(vec!["dataRegion"]))
+    }
+
+    /// Which notifications the user gets. Only the user and admins can see this.
+    async fn notification_settings(&self, ctx: &Context<'_>) -> Result<NotificationSettings> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        let session = request_context.get_session(global).await?;
+
+        if let Some((session, perms)) = session {
+            if session.user_id == self.id
+                || perms
+                    .permissions
+                    .has_permission(global_role::Permission::Admin)
+            {
+                return notification_settings::for_user(&global.db, self.id)
+                    .await
+                    .map(NotificationSettings::from)
+                    .map_err_gql("failed to fetch notification settings");
+            }
+        }
+
+        Err(GqlError::Unauthorized
+            .with_message("you are not allowed to see this field")
+            .with_field(vec!["notificationSettings"]))
+    }
+
     async fn permissions(&self, ctx: &Context<'_>) -> Result<i64> {
         let global = ctx.get_global();
 
@@ -136,6 +261,66 @@ impl User {
         Ok(global_roles.bits())
     }
 
+    /// The links on the profile of the user, in the order the user put them in.
+    async fn social_links(&self, ctx: &Context<'_>) -> Result<Vec<SocialLink>> {
+        let global = ctx.get_global();
+
+        let links = sqlx::query_as!(
+            user_social_link::Model,
+            "SELECT * FROM user_social_links WHERE user_id = $1 ORDER BY position",
+            self.id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("failed to fetch social links")?;
+
+        Ok(links.into_iter().map(SocialLink::from).collect())
+    }
+
+    /// The banner of the user's channel page, null if they have none.
+    async fn banner(&self, ctx: &Context<'_>) -> Result<Option<Banner>> {
+        let global = ctx.get_global();
+
+        let appearance = channel_appearance::for_channel(&global.db, self.id)
+            .await
+            .map_err_gql("failed to fetch channel appearance")?;
+
+        Ok(appearance.banner_id.map(|id| Banner {
+            id,
+            channel_id: self.id,
+        }))
+    }
+
+    /// The color of the display name, null if the user did not pick one.
+    async fn display_color(&self, ctx: &Context<'_>) -> Result<Option<DisplayColor>> {
+        let global = ctx.get_global();
+
+        if self.display_color_.is_empty() {
+            return Ok(None);
+        }
+
+        let gradient_allowed = match self.display_gradient_end_.is_empty() {
+            true => false,
+            false => global
+                .user_permisions_by_id_loader
+                .load_one(self.id)
+                .await
+                .map_err_gql("failed to fetch permissions")?
+                .map(|p| {
+                    p.permissions
+                        .has_permission(global_role::Permission::DisplayNameGradient)
+                })
+                .unwrap_or_default(),
+        };
+
+        Ok(display_color::DisplayColor::from_stored(
+            &self.display_color_,
+            &self.display_gradient_end_,
+            gradient_allowed,
+        )
+        .map(DisplayColor::from))
+    }
+
     async fn global_roles(&self, ctx: &Context<'_>) -> Result<Vec<GlobalRole>> {
         let global = ctx.get_global();
 
@@ -155,6 +340,112 @@ impl User {
 
         Ok(global_roles)
     }
+
+    /// Whether the logged in user muted the channel of the user, false if not logged in.
+    async fn muted(&self, ctx: &Context<'_>) -> Result<bool> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        let Some((session, _)) = request_context.get_session(global).await? else {
+            return Ok(false);
+        };
+
+        channel_user::is_muted(&*global.db, self.id, session.user_id)
+            .await
+            .map_err_gql("failed to fetch mute")
+    }
+
+    /// Whether the logged in user is notified when the user goes live, true if they did not turn it off or are not logged in.
+    async fn live_notifications(&self, ctx: &Context<'_>) -> Result<bool> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        let Some((session, _)) = request_context.get_session(global).await? else {
+            return Ok(true);
+        };
+
+        channel_user::live_notifications(&*global.db, self.id, session.user_id)
+            .await
+            .map_err_gql("failed to fetch notifications")
+    }
+
+    /// Whether the user is online. False if they do not let the logged in user see it, see `presence.setSettings`.
+    async fn is_online(&self, ctx: &Context<'_>) -> Result<bool> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        let Some((session, _)) = request_context.get_session(global).await? else {
+            return Ok(false);
+        };
+
+        let visible = user::presence_visible(&*global.db, session.user_id, self.id)
+            .await
+            .map_err_gql("failed to fetch presence settings")?;
+        if !visible {
+            return Ok(false);
+        }
+
+        global
+            .is_online(self.id)
+            .await
+            .map_err_gql("failed to fetch presence")
+    }
+
+    /// The channels the user follows. To fetch the next page pass the `cursor` of the last channel as `after`.
+    async fn following(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Only return channels after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(desc = "The maximum number of channels to return. Defaults to 50, at most 100.")]
+        limit: Option<u32>,
+        #[graphql(desc = "How the channels are ordered.", default)] sort: FollowSort,
+    ) -> Result<Vec<Follow>> {
+        let global = ctx.get_global();
+
+        let limit = page_limit(limit, DEFAULT_FOLLOWS_LIMIT, MAX_FOLLOWS_LIMIT)?;
+
+        let follows = channel_event::following(
+            &global.db,
+            self.id,
+            sort.into(),
+            Cursor::split(after),
+            limit,
+        )
+        .await
+        .map_err_gql("failed to fetch follows")?;
+
+        Ok(follows.into_iter().map(Follow::from).collect())
+    }
+
+    /// The followers of the user. To fetch the next page pass the `cursor` of the last follower as `after`.
+    async fn followers(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Only return followers after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(
+            desc = "The maximum number of followers to return. Defaults to 50, at most 100."
+        )]
+        limit: Option<u32>,
+        #[graphql(desc = "How the followers are ordered.", default)] sort: FollowSort,
+    ) -> Result<Vec<Follow>> {
+        let global = ctx.get_global();
+
+        let limit = page_limit(limit, DEFAULT_FOLLOWS_LIMIT, MAX_FOLLOWS_LIMIT)?;
+
+        let follows = channel_event::followers(
+            &global.db,
+            self.id,
+            sort.into(),
+            Cursor::split(after),
+            limit,
+        )
+        .await
+        .map_err_gql("failed to fetch followers")?;
+
+        Ok(follows.into_iter().map(Follow::from).collect())
+    }
 }
 
 impl From<user::Model> for User {
@@ -164,11 +455,20 @@ impl From<user::Model> for User {
             id: value.id,
             username: value.username,
             display_name: value.display_name,
+            profile_image_url: Some(value.profile_image_url).filter(|url| !url.is_empty()),
+            profile_image_pending: value.profile_image_job_id.is_some(),
+            bio: Some(value.bio).filter(|bio| !bio.is_empty()),
+            verified_bot: value.bot_verified_at.is_some(),
             email_: value.email,
             email_verified_: value.email_verified,
             created_at: value.created_at.into(),
             last_login_at_: value.last_login_at.into(),
             stream_key_: stream_key,
+            display_color_: value.display_color,
+            display_gradient_end_: value.display_gradient_end,
+            locale_: value.locale,
+            timezone_: value.timezone,
+            data_region_: value.data_region,
         }
     }
 }
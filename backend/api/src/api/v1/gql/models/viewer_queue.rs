@@ -0,0 +1,141 @@
+use async_graphql::{ComplexObject, Context, SimpleObject};
+use uuid::Uuid;
+
+use super::{date::DateRFC3339, user::User};
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+    },
+    database::{viewer_queue, viewer_queue_entry},
+    global::GlobalState,
+};
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct ViewerQueue {
+    /// The channel the queue is for
+    pub channel_id: Uuid,
+    /// What the viewers queue for
+    pub title: String,
+    /// Whether viewers can join, a closed queue keeps its viewers
+    pub open: bool,
+    /// The most viewers who can wait at once, null if unlimited
+    pub max_size: Option<u32>,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Last opened, closed or renamed at
+    pub updated_at: DateRFC3339,
+}
+
+impl From<viewer_queue::Model> for ViewerQueue {
+    fn from(value: viewer_queue::Model) -> Self {
+        Self {
+            channel_id: value.channel_id,
+            title: value.title,
+            open: value.open,
+            max_size: value.max_size.map(|size| size as u32),
+            created_at: value.created_at.into(),
+            updated_at: value.updated_at.into(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl ViewerQueue {
+    /// The number of viewers waiting
+    pub async fn size(&self, ctx: &Context<'_>) -> Result<i64> {
+        let global = ctx.get_global();
+
+        viewer_queue_entry::count(&*global.db, self.channel_id)
+            .await
+            .map_err_gql("failed to count viewers")
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct ViewerQueueEntry {
+    /// The viewer waiting
+    pub user_id: Uuid,
+    /// Where the viewer is in the queue, counting from 1
+    pub position: u32,
+    /// Joined at
+    pub created_at: DateRFC3339,
+}
+
+impl ViewerQueueEntry {
+    /// The entries in the order of the queue, the first one at `first_position`.
+    pub fn list(entries: Vec<viewer_queue_entry::Model>, first_position: u32) -> Vec<Self> {
+        entries
+            .into_iter()
+            .zip(first_position..)
+            .map(|(entry, position)| Self {
+                user_id: entry.user_id,
+                position,
+                created_at: entry.created_at.into(),
+            })
+            .collect()
+    }
+}
+
+#[ComplexObject]
+impl ViewerQueueEntry {
+    pub async fn user(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let global = ctx.get_global();
+
+        let user = global
+            .user_by_id_loader
+            .load_one(self.user_id)
+            .await
+            .map_err_gql("failed to fetch user")?;
+
+        Ok(user.map(User::from))
+    }
+}
+
+/// Where a viewer is in the queue of a channel.
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq)]
+pub struct ViewerQueuePosition {
+    /// Where the viewer is in the queue counting from 1, null if they are not in it
+    pub position: Option<u32>,
+    /// The number of viewers waiting
+    pub size: u32,
+    /// Whether viewers can join, false if the channel has no queue
+    pub open: bool,
+    /// Whether the broadcaster just called the viewer from the queue to play
+    pub called: bool,
+}
+
+impl ViewerQueuePosition {
+    pub async fn load(
+        global: &GlobalState,
+        channel_id: Uuid,
+        user_id: Uuid,
+        called: bool,
+    ) -> Result<Self> {
+        let open = sqlx::query_scalar!(
+            "SELECT open FROM viewer_queues WHERE channel_id = $1",
+            channel_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("failed to fetch queue")?
+        .unwrap_or_default();
+
+        let position = viewer_queue_entry::position(&*global.db, channel_id, user_id)
+            .await
+            .map_err_gql("failed to fetch position")?;
+
+        let size = viewer_queue_entry::count(&*global.db, channel_id)
+            .await
+            .map_err_gql("failed to count viewers")?;
+
+        Ok(Self {
+            position: position.map(|p| p as u32),
+            size: size as u32,
+            open,
+            called,
+        })
+    }
+}
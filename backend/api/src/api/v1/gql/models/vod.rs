@@ -0,0 +1,161 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use super::vod_caption::VodCaptions;
+use crate::{
+    api::v1::gql::{error::Result, error::ResultExt, ext::ContextExt, pagination::Cursor},
+    database::{stream, stream_chapter, vod_caption},
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum VodAccess {
+    /// Everyone can watch the VOD
+    Public,
+    /// Only subscribers of the channel can watch the VOD
+    Subscribers,
+    /// Subscribers can watch the VOD right away, everyone else once it becomes public
+    EarlyAccess,
+}
+
+impl From<stream::VodAccess> for VodAccess {
+    fn from(access: stream::VodAccess) -> Self {
+        match access {
+            stream::VodAccess::Public => Self::Public,
+            stream::VodAccess::Subscribers => Self::Subscribers,
+            stream::VodAccess::EarlyAccess => Self::EarlyAccess,
+        }
+    }
+}
+
+impl From<VodAccess> for stream::VodAccess {
+    fn from(access: VodAccess) -> Self {
+        match access {
+            VodAccess::Public => Self::Public,
+            VodAccess::Subscribers => Self::Subscribers,
+            VodAccess::EarlyAccess => Self::EarlyAccess,
+        }
+    }
+}
+
+/// A part of a VOD with the same title and category
+#[derive(SimpleObject)]
+pub struct VodChapter {
+    /// The title of the stream during the chapter
+    pub title: String,
+    /// The category of the stream during the chapter
+    pub category: String,
+    /// Where the chapter starts, in seconds since the start of the VOD
+    pub start_seconds: i64,
+    /// Where the chapter ends, in seconds since the start of the VOD
+    pub end_seconds: i64,
+}
+
+impl From<stream_chapter::Chapter> for VodChapter {
+    fn from(chapter: stream_chapter::Chapter) -> Self {
+        Self {
+            title: chapter.title,
+            category: chapter.category,
+            start_seconds: chapter.start_seconds,
+            end_seconds: chapter.end_seconds,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Vod {
+    /// The VOD's id, the same as the id of the recorded stream
+    pub id: Uuid,
+    /// The channel which streamed
+    pub channel_id: Uuid,
+    /// The title of the stream
+    pub title: String,
+    /// Who can watch the VOD
+    pub access: VodAccess,
+    /// When an early access VOD becomes public, null for other VODs
+    pub public_at: Option<DateRFC3339>,
+    /// Whether the viewer can't watch the VOD yet, shown as a lock on the VOD
+    pub locked: bool,
+    /// Started at
+    pub started_at: DateRFC3339,
+    /// Ended at
+    pub ended_at: DateRFC3339,
+    /// Pass as `after` to get the VODs which were streamed before this one
+    pub cursor: Cursor,
+    /// The region the recording is kept in, empty for the default region
+    pub region: String,
+
+    // Private fields
+    #[graphql(skip)]
+    pub duration_seconds_: i64,
+}
+
+#[ComplexObject]
+impl Vod {
+    /// The url of the edge to play the VOD from, empty if the default edge serves it.
+    async fn edge_url(&self, ctx: &Context<'_>) -> String {
+        ctx.get_global().region_edge_url(&self.region).to_string()
+    }
+
+    /// The captions of the VOD, null if it wasn't transcribed.
+    async fn captions(&self, ctx: &Context<'_>) -> Result<Option<VodCaptions>> {
+        let global = ctx.get_global();
+
+        let captions = sqlx::query_as!(
+            vod_caption::Model,
+            "SELECT * FROM vod_captions WHERE stream_id = $1",
+            self.id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("failed to fetch captions")?;
+
+        Ok(captions.map(|captions| VodCaptions::new(captions, !self.locked)))
+    }
+
+    /// The chapters of the VOD, a new one starts whenever the broadcaster changed the title or category.
+    async fn chapters(&self, ctx: &Context<'_>) -> Result<Vec<VodChapter>> {
+        Ok(self
+            .load_chapters(ctx)
+            .await?
+            .into_iter()
+            .map(VodChapter::from)
+            .collect())
+    }
+
+    /// The chapters as a WebVTT chapters track, for players which show them on the seek bar.
+    async fn chapters_track(&self, ctx: &Context<'_>) -> Result<String> {
+        Ok(stream_chapter::webvtt(&self.load_chapters(ctx).await?))
+    }
+}
+
+impl Vod {
+    async fn load_chapters(&self, ctx: &Context<'_>) -> Result<Vec<stream_chapter::Chapter>> {
+        let global = ctx.get_global();
+
+        let chapters = stream_chapter::for_stream(&*global.db, self.id)
+            .await
+            .map_err_gql("failed to fetch chapters")?;
+
+        Ok(stream_chapter::chapters(&chapters, self.duration_seconds_))
+    }
+
+    /// Subscribers, the broadcaster and admins can watch every VOD of a channel.
+    pub fn new(model: stream::Model, subscriber: bool) -> Self {
+        Self {
+            locked: !subscriber && !model.vod_public(Utc::now()),
+            id: model.id,
+            channel_id: model.channel_id,
+            title: model.title,
+            access: model.vod_access.into(),
+            public_at: model.vod_public_at.map(Into::into),
+            started_at: model.created_at.into(),
+            ended_at: model.ended_at.into(),
+            cursor: Cursor::new(model.created_at, model.id),
+            region: model.region,
+            duration_seconds_: (model.ended_at - model.created_at).num_seconds(),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use super::vod::Vod;
+use crate::api::v1::gql::{error::Result, error::ResultExt, ext::ContextExt};
+use crate::database::vod_caption;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum VodCaptionStatus {
+    Queued,
+    Completed,
+    Failed,
+}
+
+impl From<vod_caption::Status> for VodCaptionStatus {
+    fn from(status: vod_caption::Status) -> Self {
+        match status {
+            vod_caption::Status::Queued => Self::Queued,
+            vod_caption::Status::Completed => Self::Completed,
+            vod_caption::Status::Failed => Self::Failed,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct VodCaptions {
+    /// The captions' id
+    pub id: Uuid,
+    /// The VOD the captions are for
+    pub stream_id: Uuid,
+    /// The status of the transcription
+    pub status: VodCaptionStatus,
+    /// The language spoken in the VOD as a BCP-47 tag, empty if it isn't known
+    pub language: String,
+    /// The reason the transcription failed
+    pub error: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Completed at
+    pub completed_at: Option<DateRFC3339>,
+
+    // Private fields
+    #[graphql(skip)]
+    pub object_key_: String,
+    #[graphql(skip)]
+    pub region_: String,
+    #[graphql(skip)]
+    pub playable_: bool,
+}
+
+#[ComplexObject]
+impl VodCaptions {
+    /// A signed url of the WebVTT file to add as a captions track to the player,
+    /// null until the captions are done or if the viewer can't watch the VOD.
+    async fn url(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let global = ctx.get_global();
+
+        if !self.playable_ || self.status != VodCaptionStatus::Completed {
+            return Ok(None);
+        }
+
+        global
+            .object_url(
+                &self.region_,
+                &self.object_key_,
+                global.config.vod_captions.url_expiry,
+            )
+            .map(Some)
+            .map_err_gql("Failed to sign captions url")
+    }
+}
+
+impl VodCaptions {
+    /// Viewers who can't watch the VOD see the status of its captions, but don't get the url.
+    pub fn new(model: vod_caption::Model, playable: bool) -> Self {
+        Self {
+            id: model.id,
+            stream_id: model.stream_id,
+            status: model.status.into(),
+            language: model.language,
+            error: model.error,
+            created_at: model.created_at.into(),
+            completed_at: model.completed_at.map(Into::into),
+            object_key_: model.object_key,
+            region_: model.region,
+            playable_: playable,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct VodCaptionCue {
+    /// Where the line starts, in milliseconds since the start of the VOD
+    pub start_ms: i64,
+    /// Where the line ends, in milliseconds since the start of the VOD
+    pub end_ms: i64,
+    /// What was said
+    pub text: String,
+}
+
+impl From<vod_caption::Cue> for VodCaptionCue {
+    fn from(cue: vod_caption::Cue) -> Self {
+        Self {
+            start_ms: cue.start_ms,
+            end_ms: cue.end_ms,
+            text: cue.text,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct VodSearchResult {
+    /// The VOD which matched
+    pub vod: Vod,
+    /// The first lines of the captions which matched, empty if only the title matched
+    pub cues: Vec<VodCaptionCue>,
+}
+
+#[derive(SimpleObject)]
+pub struct VodTranscriptSnippet {
+    /// Where the matching line starts, in milliseconds since the start of the VOD. Seek the player here to jump to it
+    pub start_ms: i64,
+    /// Where the matching line ends, in milliseconds since the start of the VOD
+    pub end_ms: i64,
+    /// The line which matched
+    pub line: String,
+    /// The line with the end of the line said before and the start of the line said after it
+    pub text: String,
+}
+
+impl From<vod_caption::Match> for VodTranscriptSnippet {
+    fn from(m: vod_caption::Match) -> Self {
+        Self {
+            start_ms: m.start_ms,
+            end_ms: m.end_ms,
+            text: vod_caption::snippet(m.line_before.as_deref(), &m.text, m.line_after.as_deref()),
+            line: m.text,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct VodTranscriptMatch {
+    /// The VOD in which it was said
+    pub vod: Vod,
+    /// How many lines of the captions matched
+    pub matches: i64,
+    /// The first lines which matched, in the order they were said
+    pub snippets: Vec<VodTranscriptSnippet>,
+}
@@ -0,0 +1,103 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::date::DateRFC3339;
+use crate::api::v1::gql::{error::Result, error::ResultExt, ext::ContextExt};
+use crate::database::vod_download;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum VodDownloadStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl From<vod_download::Status> for VodDownloadStatus {
+    fn from(status: vod_download::Status) -> Self {
+        match status {
+            vod_download::Status::Queued => Self::Queued,
+            vod_download::Status::Running => Self::Running,
+            vod_download::Status::Completed => Self::Completed,
+            vod_download::Status::Failed => Self::Failed,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct VodDownload {
+    /// The download's id
+    pub id: Uuid,
+    /// The VOD which is downloaded
+    pub stream_id: Uuid,
+    /// The quality of the VOD which is put into the MP4
+    pub quality: String,
+    /// Whether the VOD is transcoded, which takes longer than copying it into the MP4 as it is
+    pub transcoded: bool,
+    /// The status of the download
+    pub status: VodDownloadStatus,
+    /// How far the download is, from 0 to 100
+    pub progress: i64,
+    /// The size of the MP4 in bytes, 0 until the download completed
+    pub size_bytes: i64,
+    /// The reason the download failed
+    pub error: String,
+    /// Created at
+    pub created_at: DateRFC3339,
+    /// Completed at
+    pub completed_at: Option<DateRFC3339>,
+    /// When the MP4 can't be downloaded anymore
+    pub expires_at: Option<DateRFC3339>,
+
+    // Private fields
+    #[graphql(skip)]
+    pub object_key_: String,
+    #[graphql(skip)]
+    pub region_: String,
+    #[graphql(skip)]
+    pub downloadable_: bool,
+}
+
+#[ComplexObject]
+impl VodDownload {
+    /// A signed url the MP4 can be downloaded with for a limited time, null until the download completed or after it expired.
+    async fn download_url(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let global = ctx.get_global();
+
+        if !self.downloadable_ {
+            return Ok(None);
+        }
+
+        global
+            .object_url(
+                &self.region_,
+                &self.object_key_,
+                global.config.vod_downloads.url_expiry,
+            )
+            .map(Some)
+            .map_err_gql("Failed to sign download url")
+    }
+}
+
+impl From<vod_download::Model> for VodDownload {
+    fn from(value: vod_download::Model) -> Self {
+        Self {
+            downloadable_: value.downloadable(Utc::now()),
+            id: value.id,
+            stream_id: value.stream_id,
+            quality: value.variant,
+            transcoded: !value.remux,
+            status: value.status.into(),
+            progress: value.progress,
+            size_bytes: value.size_bytes,
+            error: value.error,
+            created_at: value.created_at.into(),
+            completed_at: value.completed_at.map(Into::into),
+            expires_at: value.expires_at.map(Into::into),
+            object_key_: value.object_key,
+            region_: value.region,
+        }
+    }
+}
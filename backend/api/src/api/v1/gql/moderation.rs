@@ -0,0 +1,479 @@
+use async_graphql::{Context, Object};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_channel_owner;
+use super::models::chat_ban::ChatBan;
+use super::models::date::DateRFC3339;
+use super::models::moderation_job::{FollowSpike, ModerationJob};
+use super::pagination::{page_limit, Cursor};
+use crate::database::legal_hold;
+use crate::database::moderation_job::{self, Kind, Status};
+use crate::database::{channel_event, chat_ban};
+use crate::global::GlobalState;
+
+const DEFAULT_FOLLOW_SPIKE_HOURS: u32 = 24;
+const MAX_FOLLOW_SPIKE_HOURS: u32 = 7 * 24;
+
+const DEFAULT_BANNED_USERS_LIMIT: u32 = 25;
+const MAX_BANNED_USERS_LIMIT: u32 = 100;
+
+#[derive(Default)]
+pub struct ModerationQuery;
+
+#[Object]
+/// The query object for moderation jobs.
+impl ModerationQuery {
+    /// Get a moderation job, used to follow its progress.
+    async fn job<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the job.")] id: Uuid,
+    ) -> Result<ModerationJob> {
+        let global = ctx.get_global();
+
+        let job = sqlx::query_as!(
+            moderation_job::Model,
+            "SELECT * FROM moderation_jobs WHERE id = $1",
+            id
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch job")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Job not found")
+                .with_field(vec!["id"])
+        })?;
+
+        authorize_channel_owner(ctx, job.channel_id).await?;
+
+        Ok(job.into())
+    }
+
+    /// Get the moderation jobs of a channel, newest first.
+    async fn jobs<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<ModerationJob>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let jobs = sqlx::query_as!(
+            moderation_job::Model,
+            "SELECT * FROM moderation_jobs WHERE channel_id = $1 ORDER BY created_at DESC LIMIT 20",
+            channel_id
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch jobs")?;
+
+        Ok(jobs.into_iter().map(ModerationJob::from).collect())
+    }
+
+    /// Get the users who are banned from the chat of a channel, the most recently banned first. Bans which ran out are left out.
+    /// To fetch the next page pass the `cursor` of the last ban as `after`.
+    async fn banned_users<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "Only return bans after this cursor, used for pagination.")] after: Option<
+            Cursor,
+        >,
+        #[graphql(desc = "The maximum number of bans to return. Defaults to 25, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<ChatBan>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let limit = page_limit(limit, DEFAULT_BANNED_USERS_LIMIT, MAX_BANNED_USERS_LIMIT)?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let bans = sqlx::query_as!(
+            chat_ban::Model,
+            "SELECT * FROM chat_bans WHERE channel_id = $1 AND (expires_at IS NULL OR expires_at > NOW()) AND ($2::timestamptz IS NULL OR (created_at, user_id) < ($2, $3::uuid)) ORDER BY created_at DESC, user_id DESC LIMIT $4",
+            channel_id,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch bans")?;
+
+        Ok(bans.into_iter().map(ChatBan::from).collect())
+    }
+
+    /// Get the minutes in which a channel got far more follows than usual, a sign of a follow-bot attack.
+    /// The spikes can be passed to `removeFollows` to clean them up.
+    async fn follow_spikes<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "How many hours back to look. Defaults to 24, at most 168.")]
+        hours: Option<u32>,
+    ) -> Result<Vec<FollowSpike>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let hours = hours.unwrap_or(DEFAULT_FOLLOW_SPIKE_HOURS);
+        if hours == 0 || hours > MAX_FOLLOW_SPIKE_HOURS {
+            return Err(GqlError::InvalidInput
+                .with_message("Hours must be between 1 and 168")
+                .with_field(vec!["hours"]));
+        }
+
+        let since = Utc::now() - Duration::hours(hours as i64);
+
+        let buckets = sqlx::query!(
+            r#"SELECT FLOOR(EXTRACT(EPOCH FROM created_at - $3) / 60)::bigint AS "bucket!", COUNT(*) AS "count!" FROM channel_events WHERE channel_id = $1 AND kind = $2 AND created_at >= $3 GROUP BY 1"#,
+            channel_id,
+            i64::from(channel_event::Kind::Follow),
+            since,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch follows")?;
+
+        // Minutes without follows have no row, but they make up the baseline of most channels.
+        let mut counts = vec![0; hours as usize * 60];
+        for bucket in buckets {
+            if let Some(count) = counts.get_mut(bucket.bucket as usize) {
+                *count = bucket.count;
+            }
+        }
+
+        Ok(channel_event::follow_spikes(
+            since,
+            Duration::minutes(1),
+            &counts,
+            global.config.moderation.follow_spike_multiplier,
+            global.config.moderation.follow_spike_min_follows,
+        )
+        .into_iter()
+        .map(FollowSpike::from)
+        .collect())
+    }
+}
+
+/// Queues a job which was just created, marking it as failed if it can't be queued.
+async fn queue(global: &GlobalState, job: moderation_job::Model) -> Result<ModerationJob> {
+    if let Err(e) = global.queue_moderation_job(job.id).await {
+        sqlx::query!(
+            "UPDATE moderation_jobs SET status = $2, error = $3, completed_at = NOW() WHERE id = $1",
+            job.id,
+            i64::from(Status::Failed),
+            "Failed to queue the job",
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to update job")?;
+
+        return Err(e).map_err_gql("Failed to queue job");
+    }
+
+    Ok(job.into())
+}
+
+/// Deleting content of a channel under legal hold would destroy what the hold preserves.
+async fn check_not_held(global: &GlobalState, channel_id: Uuid) -> Result<()> {
+    if legal_hold::is_held(&global.db, legal_hold::SubjectKind::Channel, channel_id)
+        .await
+        .map_err_gql("Failed to fetch legal hold")?
+    {
+        return Err(GqlError::InvalidInput
+            .with_message("The content of this channel is under legal hold")
+            .with_field(vec!["channelId"]));
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct ModerationMutation;
+
+#[Object]
+/// The mutation object for moderating many users, messages or follows at once, e.g. during a raid.
+/// The actions run in the background, their progress can be followed with the returned job.
+impl ModerationMutation {
+    /// Ban users from chatting in a channel.
+    async fn ban_users<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The ids of the users to ban.")] user_ids: Vec<Uuid>,
+        #[graphql(desc = "The reason for the bans.", default)] reason: String,
+    ) -> Result<ModerationJob> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_channel_owner(ctx, channel_id).await?;
+
+        let mut user_ids = user_ids;
+        user_ids.sort();
+        user_ids.dedup();
+
+        if user_ids.is_empty() || user_ids.len() > global.config.moderation.max_users {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!(
+                    "Between 1 and {} users can be banned at once",
+                    global.config.moderation.max_users
+                ))
+                .with_field(vec!["userIds"]));
+        }
+
+        if reason.len() > 255 {
+            return Err(GqlError::InvalidInput
+                .with_message("Reason must be at most 255 characters")
+                .with_field(vec!["reason"]));
+        }
+
+        let job = sqlx::query_as!(
+            moderation_job::Model,
+            "INSERT INTO moderation_jobs (channel_id, created_by, kind, user_ids, reason, total_items) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+            channel_id,
+            session.user_id,
+            i64::from(Kind::BanUsers),
+            &user_ids,
+            reason,
+            user_ids.len() as i64,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to create job")?;
+
+        queue(global, job).await
+    }
+
+    /// Ban a user from chatting in a channel right away, their open chats end. Banning a user who is already banned
+    /// replaces the reason and when the ban ends.
+    async fn ban_user<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The id of the user to ban.")] user_id: Uuid,
+        #[graphql(desc = "The reason for the ban.", default)] reason: String,
+        #[graphql(desc = "When the ban ends, the ban is permanent if not set.")] expires_at: Option<
+            DateRFC3339,
+        >,
+    ) -> Result<ChatBan> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_channel_owner(ctx, channel_id).await?;
+
+        if user_id == channel_id {
+            return Err(GqlError::InvalidInput
+                .with_message("The broadcaster can't be banned from their own chat")
+                .with_field(vec!["userId"]));
+        }
+
+        if reason.len() > 255 {
+            return Err(GqlError::InvalidInput
+                .with_message("Reason must be at most 255 characters")
+                .with_field(vec!["reason"]));
+        }
+
+        let expires_at = expires_at.map(|e| e.0);
+        if expires_at.map_or(false, |e| e <= Utc::now()) {
+            return Err(GqlError::InvalidInput
+                .with_message("The ban must end in the future")
+                .with_field(vec!["expiresAt"]));
+        }
+
+        global
+            .user_by_id_loader
+            .load_one(user_id)
+            .await
+            .map_err_gql("Failed to fetch user")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("User not found")
+                    .with_field(vec!["userId"])
+            })?;
+
+        // A ban which ran out counts as a new one, an active ban keeps the time the user was banned.
+        let ban = sqlx::query_as!(
+            chat_ban::Model,
+            "INSERT INTO chat_bans (channel_id, user_id, banned_by, reason, expires_at) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (channel_id, user_id) DO UPDATE SET banned_by = excluded.banned_by, reason = excluded.reason, expires_at = excluded.expires_at, created_at = CASE WHEN chat_bans.expires_at <= NOW() THEN NOW() ELSE chat_bans.created_at END RETURNING *",
+            channel_id,
+            user_id,
+            session.user_id,
+            reason,
+            expires_at,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to ban user")?;
+
+        global
+            .chat_ban_changed(channel_id, user_id, Some(&ban))
+            .await;
+
+        Ok(ban.into())
+    }
+
+    /// Let a banned user chat in a channel again. Returns false if the user was not banned.
+    async fn unban_user<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The id of the user to unban.")] user_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let ban = sqlx::query_as!(
+            chat_ban::Model,
+            "DELETE FROM chat_bans WHERE channel_id = $1 AND user_id = $2 RETURNING *",
+            channel_id,
+            user_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to unban user")?;
+
+        // A ban which ran out is cleaned up, but the user was not banned anymore.
+        let was_banned = ban.map_or(false, |ban| ban.expires_at.map_or(true, |e| e > Utc::now()));
+
+        if was_banned {
+            global.chat_ban_changed(channel_id, user_id, None).await;
+        }
+
+        Ok(was_banned)
+    }
+
+    /// Delete the messages of a channel containing a text, e.g. the one a bot attack spams.
+    async fn delete_messages<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The text the messages contain, case insensitive.")] pattern: String,
+        #[graphql(desc = "Only messages sent in the last this many minutes are deleted.")]
+        minutes: u32,
+    ) -> Result<ModerationJob> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_channel_owner(ctx, channel_id).await?;
+        check_not_held(global, channel_id).await?;
+
+        if pattern.trim().is_empty() || pattern.len() > 255 {
+            return Err(GqlError::InvalidInput
+                .with_message("Pattern must be between 1 and 255 characters")
+                .with_field(vec!["pattern"]));
+        }
+
+        if minutes == 0 || minutes > global.config.moderation.max_minutes {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!(
+                    "Minutes must be between 1 and {}",
+                    global.config.moderation.max_minutes
+                ))
+                .with_field(vec!["minutes"]));
+        }
+
+        let since = Utc::now() - Duration::minutes(minutes as i64);
+
+        // NOW() is the start of the transaction, so the job deletes exactly the messages which were counted.
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to create job")?;
+
+        let total_items = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM chat_messages WHERE channel_id = $1 AND created_at >= $2 AND created_at <= NOW() AND content ILIKE $3"#,
+            channel_id,
+            since,
+            moderation_job::like_pattern(&pattern),
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to count messages")?;
+
+        let job = sqlx::query_as!(
+            moderation_job::Model,
+            "INSERT INTO moderation_jobs (channel_id, created_by, kind, pattern, since, total_items) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+            channel_id,
+            session.user_id,
+            i64::from(Kind::DeleteMessages),
+            pattern,
+            since,
+            total_items,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to create job")?;
+
+        tx.commit().await.map_err_gql("Failed to create job")?;
+
+        queue(global, job).await
+    }
+    /// Remove the follows a channel got in a time window, e.g. a follow spike, and update the follower counts of its streams.
+    /// The heuristics narrow the follows down to the ones which look like bots.
+    async fn remove_follows<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The start of the window.")] since: DateRFC3339,
+        #[graphql(desc = "The end of the window.")] until: DateRFC3339,
+        #[graphql(
+            desc = "Only remove follows of accounts which were at most this many minutes old when following."
+        )]
+        max_account_age_minutes: Option<u32>,
+        #[graphql(
+            desc = "Only remove follows of users who never chatted in the channel.",
+            default
+        )]
+        only_silent: bool,
+    ) -> Result<ModerationJob> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_channel_owner(ctx, channel_id).await?;
+        check_not_held(global, channel_id).await?;
+
+        if since.0 >= until.0 {
+            return Err(GqlError::InvalidInput
+                .with_message("The window must end after it starts")
+                .with_field(vec!["until"]));
+        }
+
+        let max_account_age = max_account_age_minutes.map(i64::from);
+
+        let total_items = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM channel_events e LEFT JOIN users u ON u.id = e.user_id WHERE e.channel_id = $1 AND e.kind = $2 AND e.created_at BETWEEN $3 AND $4 AND ($5::bigint IS NULL OR e.created_at - u.created_at <= $5 * INTERVAL '1 minute') AND (NOT $6 OR NOT EXISTS (SELECT 1 FROM chat_messages m WHERE m.channel_id = e.channel_id AND m.author_id = e.user_id))"#,
+            channel_id,
+            i64::from(channel_event::Kind::Follow),
+            since.0,
+            until.0,
+            max_account_age,
+            only_silent,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to count follows")?;
+
+        let job = sqlx::query_as!(
+            moderation_job::Model,
+            "INSERT INTO moderation_jobs (channel_id, created_by, kind, since, until, max_account_age, only_silent, total_items) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+            channel_id,
+            session.user_id,
+            i64::from(Kind::RemoveFollows),
+            since.0,
+            until.0,
+            max_account_age,
+            only_silent,
+            total_items,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to create job")?;
+
+        queue(global, job).await
+    }
+}
@@ -0,0 +1,233 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_channel_owner;
+use super::models::obs::{ObsConnection, ObsMapping, ObsMappingKind};
+use crate::database::{obs_connection, obs_mapping};
+
+#[derive(Default)]
+pub struct ObsQuery;
+
+#[Object]
+/// The query object for the OBS integration.
+impl ObsQuery {
+    /// Get the OBS connection of a channel.
+    async fn connection<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Option<ObsConnection>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let connection = sqlx::query_as!(
+            obs_connection::Model,
+            "SELECT * FROM obs_connections WHERE channel_id = $1",
+            channel_id
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch OBS connection")?;
+
+        Ok(connection.map(ObsConnection::from))
+    }
+
+    /// Get the scenes and profiles a channel mapped to stream info.
+    async fn mappings<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<ObsMapping>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let mappings = sqlx::query_as!(
+            obs_mapping::Model,
+            "SELECT * FROM obs_mappings WHERE channel_id = $1 ORDER BY kind, source_name",
+            channel_id
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch OBS mappings")?;
+
+        Ok(mappings.into_iter().map(ObsMapping::from).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct ObsMutation;
+
+#[Object]
+/// The mutation object for the OBS integration.
+impl ObsMutation {
+    /// Connect a channel to its OBS, replacing the existing connection settings.
+    /// The API connects within a few seconds, the status shows whether it succeeded.
+    #[allow(clippy::too_many_arguments)]
+    async fn connect<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The public host OBS can be reached at.")] host: String,
+        #[graphql(desc = "The port of the obs-websocket server.", default = 4455)] port: u16,
+        #[graphql(desc = "The obs-websocket password, if one is set.")] password: Option<String>,
+        #[graphql(desc = "Whether scene changes update the stream info.", default = true)]
+        sync_scenes: bool,
+        #[graphql(
+            desc = "Whether profile changes update the stream info.",
+            default = false
+        )]
+        sync_profiles: bool,
+        #[graphql(
+            desc = "Whether the stream info is updated when OBS starts streaming.",
+            default = true
+        )]
+        update_on_go_live: bool,
+    ) -> Result<ObsConnection> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let host = host.trim().to_lowercase();
+        if let Err(e) = obs_connection::validate_host(&host) {
+            return Err(GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["host"]));
+        }
+
+        if port == 0 {
+            return Err(GqlError::InvalidInput
+                .with_message("Port must be between 1 and 65535")
+                .with_field(vec!["port"]));
+        }
+
+        let password_encrypted = password
+            .filter(|p| !p.is_empty())
+            .map(|p| global.encrypt_secret(&p))
+            .transpose()
+            .map_err_gql("Failed to encrypt password")?;
+
+        let connection = sqlx::query_as!(
+            obs_connection::Model,
+            "INSERT INTO obs_connections (channel_id, host, port, password_encrypted, sync_scenes, sync_profiles, update_on_go_live) VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (channel_id) DO UPDATE SET host = $2, port = $3, password_encrypted = $4, sync_scenes = $5, sync_profiles = $6, update_on_go_live = $7, status = $8, last_error = '', connected_at = NULL, updated_at = NOW() RETURNING *",
+            channel_id,
+            host,
+            port as i64,
+            password_encrypted,
+            sync_scenes,
+            sync_profiles,
+            update_on_go_live,
+            i64::from(obs_connection::Status::Connecting),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to save OBS connection")?;
+
+        Ok(connection.into())
+    }
+
+    /// Disconnect a channel from its OBS. The mappings are kept for when it reconnects.
+    async fn disconnect<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        sqlx::query!(
+            "DELETE FROM obs_connections WHERE channel_id = $1",
+            channel_id
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to delete OBS connection")?;
+
+        Ok(true)
+    }
+
+    /// Set the stream info a scene or profile switches to.
+    async fn set_mapping<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "Whether to map a scene or a profile.")] kind: ObsMappingKind,
+        #[graphql(desc = "The name of the scene or profile in OBS.")] source_name: String,
+        #[graphql(desc = "The stream category to switch to.")] category: String,
+        #[graphql(desc = "The stream title to switch to, or null to keep the current title.")]
+        title: Option<String>,
+    ) -> Result<ObsMapping> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        if source_name.is_empty() || source_name.len() > 255 {
+            return Err(GqlError::InvalidInput
+                .with_message("Source name must be between 1 and 255 characters")
+                .with_field(vec!["sourceName"]));
+        }
+
+        if category.is_empty() || category.len() > 64 {
+            return Err(GqlError::InvalidInput
+                .with_message("Category must be between 1 and 64 characters")
+                .with_field(vec!["category"]));
+        }
+
+        if title.as_ref().map(|t| t.len() > 255).unwrap_or(false) {
+            return Err(GqlError::InvalidInput
+                .with_message("Title must be at most 255 characters")
+                .with_field(vec!["title"]));
+        }
+
+        let mapping = sqlx::query_as!(
+            obs_mapping::Model,
+            "INSERT INTO obs_mappings (channel_id, kind, source_name, category, title) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (channel_id, kind, source_name) DO UPDATE SET category = $4, title = $5 RETURNING *",
+            channel_id,
+            i64::from(obs_mapping::Kind::from(kind)),
+            source_name,
+            category,
+            title,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to save OBS mapping")?;
+
+        Ok(mapping.into())
+    }
+
+    /// Remove a scene or profile mapping.
+    async fn remove_mapping<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the mapping.")] id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let mapping = sqlx::query_as!(
+            obs_mapping::Model,
+            "SELECT * FROM obs_mappings WHERE id = $1",
+            id
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch OBS mapping")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("OBS mapping not found")
+                .with_field(vec!["id"])
+        })?;
+
+        authorize_channel_owner(ctx, mapping.channel_id).await?;
+
+        sqlx::query!("DELETE FROM obs_mappings WHERE id = $1", mapping.id)
+            .execute(&*global.db)
+            .await
+            .map_err_gql("Failed to delete OBS mapping")?;
+
+        Ok(true)
+    }
+}
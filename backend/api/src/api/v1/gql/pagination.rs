@@ -0,0 +1,75 @@
+use async_graphql::{InputValueError, InputValueResult, Scalar, ScalarType, Value};
+use chrono::{DateTime, TimeZone, Utc};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result};
+use super::models::ulid;
+
+/// Points at the last item of a page of a list ordered by time, pass it back to get the next page.
+/// Items with the same time are ordered by id, so no item is skipped or returned twice when a page ends between them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cursor {
+    pub time: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(time: DateTime<Utc>, id: Uuid) -> Self {
+        Self { time, id }
+    }
+
+    /// Splits an optional cursor into query parameters, both are null for the first page.
+    pub fn split(cursor: Option<Cursor>) -> (Option<DateTime<Utc>>, Option<Uuid>) {
+        match cursor {
+            Some(c) => (Some(c.time), Some(c.id)),
+            None => (None, None),
+        }
+    }
+
+    fn parse_str(s: &str) -> Option<Self> {
+        let (micros, id) = s.split_once('_')?;
+        let micros = micros.parse::<i64>().ok()?;
+
+        let time = Utc
+            .timestamp_opt(
+                micros.div_euclid(1_000_000),
+                (micros.rem_euclid(1_000_000) * 1000) as u32,
+            )
+            .single()?;
+
+        Some(Self::new(time, ulid::decode(id)?))
+    }
+}
+
+/// Postgres stores microseconds, so the time survives the round trip exactly.
+#[Scalar]
+impl ScalarType for Cursor {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match value {
+            Value::String(s) => {
+                Cursor::parse_str(&s).ok_or_else(|| InputValueError::custom("Invalid cursor"))
+            }
+            _ => Err(InputValueError::custom("Invalid value")),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(format!(
+            "{}_{}",
+            self.time.timestamp_micros(),
+            ulid::encode(self.id)
+        ))
+    }
+}
+
+/// Checks the page size a client asked for, using the default if it did not ask.
+pub fn page_limit(limit: Option<u32>, default: u32, max: u32) -> Result<i64> {
+    let limit = limit.unwrap_or(default);
+    if limit == 0 || limit > max {
+        return Err(GqlError::InvalidInput
+            .with_message(&format!("Limit must be between 1 and {}", max))
+            .with_field(vec!["limit"]));
+    }
+
+    Ok(limit as i64)
+}
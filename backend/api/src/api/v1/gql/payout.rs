@@ -0,0 +1,192 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_admin, authorize_channel_owner, authorize_elevated};
+use super::models::payout_method::{PayoutMethod, PayoutMethodStatus};
+use crate::database::payout_method;
+
+const MAX_REVIEW_NOTE_LENGTH: usize = 1000;
+const DEFAULT_PENDING_LIMIT: u32 = 50;
+const MAX_PENDING_LIMIT: u32 = 100;
+
+#[derive(Default)]
+pub struct PayoutQuery;
+
+#[Object]
+/// The query object for payout methods.
+impl PayoutQuery {
+    /// Get the payout method of a channel.
+    async fn method<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Option<PayoutMethod>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let method = sqlx::query_as!(
+            payout_method::Model,
+            "SELECT * FROM payout_methods WHERE channel_id = $1",
+            channel_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch payout method")?;
+
+        Ok(method.map(PayoutMethod::from))
+    }
+
+    /// Get the payout methods which are waiting to be reviewed, oldest first. Only admins can do this.
+    async fn pending_review<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(
+            desc = "The maximum number of payout methods to return. Defaults to 50, at most 100."
+        )]
+        limit: Option<u32>,
+    ) -> Result<Vec<PayoutMethod>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let limit = limit.unwrap_or(DEFAULT_PENDING_LIMIT);
+        if limit == 0 || limit > MAX_PENDING_LIMIT {
+            return Err(GqlError::InvalidInput
+                .with_message("Limit must be between 1 and 100")
+                .with_field(vec!["limit"]));
+        }
+
+        let methods = sqlx::query_as!(
+            payout_method::Model,
+            "SELECT * FROM payout_methods WHERE status = $1 ORDER BY created_at ASC LIMIT $2",
+            i64::from(payout_method::Status::PendingVerification),
+            limit as i64,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch payout methods")?;
+
+        Ok(methods.into_iter().map(PayoutMethod::from).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct PayoutMutation;
+
+#[Object]
+/// The mutation object for payout methods.
+impl PayoutMutation {
+    /// Register the account created during the payout provider's onboarding (bank details and tax forms).
+    /// Replaces any previous payout method, which then has to be verified again.
+    async fn register_method<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The token returned by the payout provider after onboarding.")]
+        onboarding_token: String,
+    ) -> Result<PayoutMethod> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_elevated(ctx).await?;
+        if session.user_id != channel_id {
+            return Err(GqlError::Unauthorized
+                .with_message("Only the owner of the channel can register a payout method")
+                .with_field(vec!["channelId"]));
+        }
+
+        let existing = sqlx::query_as!(
+            payout_method::Model,
+            "SELECT * FROM payout_methods WHERE channel_id = $1",
+            channel_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch payout method")?;
+
+        if existing.map(|m| m.status) == Some(payout_method::Status::Blocked) {
+            return Err(GqlError::InvalidInput
+                .with_message("Payouts are blocked for this channel")
+                .with_field(vec!["channelId"]));
+        }
+
+        let account_id = global
+            .register_payout_account(&onboarding_token)
+            .await
+            .map_err_gql("Failed to register account with payout provider")?;
+        let account_id = global
+            .encrypt_pii(&account_id)
+            .map_err_gql("Failed to encrypt payout account")?;
+
+        // The method could have been blocked while the account was registered, that must not be undone.
+        let method = sqlx::query_as!(
+            payout_method::Model,
+            "INSERT INTO payout_methods (channel_id, provider, provider_account_id) VALUES ($1, $2, $3) ON CONFLICT (channel_id) DO UPDATE SET provider = $2, provider_account_id = $3, status = $4, review_note = '', reviewed_by = NULL, created_at = NOW(), reviewed_at = NULL WHERE payout_methods.status <> $5 RETURNING *",
+            channel_id,
+            global.config.payout.provider,
+            account_id,
+            i64::from(payout_method::Status::PendingVerification),
+            i64::from(payout_method::Status::Blocked),
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to save payout method")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("Payouts are blocked for this channel")
+                .with_field(vec!["channelId"])
+        })?;
+
+        Ok(method.into())
+    }
+
+    /// Block a payout method or send it back to verification. Only admins can do this, verifying a method needs
+    /// a second admin, see approval.proposePayoutVerification.
+    async fn review_method<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the payout method.")] id: Uuid,
+        #[graphql(desc = "The new status of the payout method.")] status: PayoutMethodStatus,
+        #[graphql(desc = "A note explaining the decision, visible to the channel owner.")]
+        note: Option<String>,
+    ) -> Result<PayoutMethod> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        // Blocking a method is safe for one admin, sending payouts to an account is not.
+        if status == PayoutMethodStatus::Verified {
+            return Err(GqlError::InvalidInput
+                .with_message("Verifying a payout method needs a second admin, propose it with approval.proposePayoutVerification")
+                .with_field(vec!["status"]));
+        }
+
+        let note = note.unwrap_or_default();
+        if note.len() > MAX_REVIEW_NOTE_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Note too long")
+                .with_field(vec!["note"]));
+        }
+
+        let method = sqlx::query_as!(
+            payout_method::Model,
+            "UPDATE payout_methods SET status = $1, review_note = $2, reviewed_by = $3, reviewed_at = NOW() WHERE id = $4 RETURNING *",
+            i64::from(payout_method::Status::from(status)),
+            note,
+            session.user_id,
+            id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to update payout method")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Payout method not found")
+                .with_field(vec!["id"])
+        })?;
+
+        Ok(method.into())
+    }
+}
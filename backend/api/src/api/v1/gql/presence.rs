@@ -0,0 +1,105 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_user;
+use super::models::presence::{Presence, PresenceSettings, PresenceVisibility};
+use crate::database::user;
+
+#[derive(Default)]
+pub struct PresenceQuery;
+
+#[Object]
+/// The query object for the online status of followed users and friends.
+impl PresenceQuery {
+    /// Get the followed users and friends who are online and let the logged in user see it, most recently active first.
+    async fn friends_presence<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<Presence>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let presences = global
+            .friends_presence(session.user_id)
+            .await
+            .map_err_gql("Failed to fetch presence")?;
+
+        Ok(presences.into_iter().map(Presence::from).collect())
+    }
+
+    /// Get the presence settings of the logged in user.
+    async fn settings<'ctx>(&self, ctx: &Context<'_>) -> Result<PresenceSettings> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "SELECT * FROM users WHERE id = $1",
+            session.user_id
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch user")?;
+
+        Ok(user.into())
+    }
+}
+
+#[derive(Default)]
+pub struct PresenceMutation;
+
+#[Object]
+/// The mutation object for the online status of the logged in user.
+impl PresenceMutation {
+    /// Tell that the logged in user is still watching. The player sends this while playing,
+    /// the user goes offline when it stops.
+    async fn heartbeat<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(
+            desc = "The channel the user is watching, not set while the user is only browsing."
+        )]
+        watching_channel_id: Option<Uuid>,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        global
+            .touch_presence(session.user_id, watching_channel_id)
+            .await
+            .map_err_gql("Failed to update presence")?;
+
+        Ok(true)
+    }
+
+    /// Change who can see that the logged in user is online. Nobody can until the user opts in.
+    async fn set_settings<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Who can see that the user is online, unchanged if not set.")]
+        visibility: Option<PresenceVisibility>,
+        #[graphql(
+            desc = "Whether the channel the user watches is shown too, unchanged if not set."
+        )]
+        share_watching: Option<bool>,
+    ) -> Result<PresenceSettings> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET presence_visibility = COALESCE($2, presence_visibility), presence_share_watching = COALESCE($3, presence_share_watching) WHERE id = $1 RETURNING *",
+            session.user_id,
+            visibility.map(|v| i64::from(user::PresenceVisibility::from(v))),
+            share_watching,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to update presence settings")?;
+
+        Ok(user.into())
+    }
+}
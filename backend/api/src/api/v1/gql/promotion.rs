@@ -0,0 +1,124 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_admin;
+use super::models::date::DateRFC3339;
+use super::models::promotion::{Promotion, PromotionTarget};
+use crate::database::promotion;
+
+const MAX_NAME_LENGTH: usize = 64;
+
+#[derive(Default)]
+pub struct PromotionQuery;
+
+#[Object]
+/// The query object for promotions. Only admins can use these.
+impl PromotionQuery {
+    /// Get all promotions which have not ended yet, ordered by their start.
+    async fn upcoming<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<Promotion>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let promotions = sqlx::query_as!(
+            promotion::Model,
+            "SELECT * FROM promotions WHERE ends_at > NOW() ORDER BY starts_at ASC",
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch promotions")?;
+
+        Ok(promotions.into_iter().map(Promotion::from).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct PromotionMutation;
+
+#[Object]
+/// The mutation object for promotions. Only admins can use these.
+impl PromotionMutation {
+    /// Create a new promotion. It is applied automatically to checkouts while it runs.
+    #[allow(clippy::too_many_arguments)]
+    async fn create<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The name shown next to discounted prices.")] name: String,
+        #[graphql(desc = "What the discount applies to.")] target: PromotionTarget,
+        #[graphql(desc = "The discount in percent, between 1 and 100.")] percent_off: u32,
+        #[graphql(desc = "Only give the discount to users who never subscribed to the channel.")]
+        first_month_only: Option<bool>,
+        #[graphql(desc = "Limit the promotion to a single channel.")] channel_id: Option<Uuid>,
+        #[graphql(desc = "The time the promotion starts.")] starts_at: DateRFC3339,
+        #[graphql(desc = "The time the promotion ends.")] ends_at: DateRFC3339,
+    ) -> Result<Promotion> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Name must be between 1 and 64 characters")
+                .with_field(vec!["name"]));
+        }
+
+        if percent_off == 0 || percent_off > 100 {
+            return Err(GqlError::InvalidInput
+                .with_message("Discount must be between 1 and 100 percent")
+                .with_field(vec!["percentOff"]));
+        }
+
+        if ends_at.0 <= starts_at.0 {
+            return Err(GqlError::InvalidInput
+                .with_message("Promotion must end after it starts")
+                .with_field(vec!["endsAt"]));
+        }
+
+        let promotion = sqlx::query_as!(
+            promotion::Model,
+            "INSERT INTO promotions (name, target, percent_off, first_month_only, channel_id, created_by, starts_at, ends_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+            name,
+            i64::from(promotion::Target::from(target)),
+            percent_off as i64,
+            first_month_only.unwrap_or(false),
+            channel_id,
+            session.user_id,
+            starts_at.0,
+            ends_at.0,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to create promotion")?;
+
+        Ok(promotion.into())
+    }
+
+    /// End a running promotion immediately.
+    async fn end<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the promotion.")] id: Uuid,
+    ) -> Result<Promotion> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let promotion = sqlx::query_as!(
+            promotion::Model,
+            "UPDATE promotions SET ends_at = NOW() WHERE id = $1 AND starts_at < NOW() AND ends_at > NOW() RETURNING *",
+            id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to end promotion")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Promotion not found or not running")
+                .with_field(vec!["id"])
+        })?;
+
+        Ok(promotion.into())
+    }
+}
@@ -1,7 +1,8 @@
-use std::sync::Arc;
+use std::{net::IpAddr, sync::Arc};
 
 use crate::database::session;
 use arc_swap::ArcSwap;
+use tokio::sync::{futures::Notified, Notify};
 
 use crate::{
     api::v1::gql::error::Result, dataloader::user_permissions::UserPermission, global::GlobalState,
@@ -12,7 +13,10 @@ use super::error::{GqlError, ResultExt};
 #[derive(Default)]
 pub struct RequestContext {
     is_websocket: bool,
+    client_ip: Option<IpAddr>,
+    user_agent: Option<String>,
     session: ArcSwap<Option<(session::Model, UserPermission)>>,
+    session_changed: Notify,
 }
 
 impl RequestContext {
@@ -23,8 +27,49 @@ impl RequestContext {
         }
     }
 
+    pub fn with_client_ip(mut self, client_ip: IpAddr) -> Self {
+        self.client_ip = Some(client_ip);
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: Option<String>) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    /// Websockets can't set cookies, they are logged in with the session of the connection.
+    pub fn is_websocket(&self) -> bool {
+        self.is_websocket
+    }
+
+    /// The address the request came from, not set for requests made internally.
+    pub fn client_ip(&self) -> Option<IpAddr> {
+        self.client_ip
+    }
+
+    /// The user agent of the client, if it sent one.
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
     pub fn set_session(&self, session: Option<(session::Model, UserPermission)>) {
         self.session.store(Arc::new(session));
+        self.session_changed.notify_waiters();
+    }
+
+    /// The session the request is logged in with, without checking if it is still valid.
+    pub fn current_session(&self) -> Option<session::Model> {
+        self.session
+            .load()
+            .as_ref()
+            .as_ref()
+            .map(|(s, _)| s.clone())
+    }
+
+    /// Resolves the next time the session is set. Wakeups are received from the moment this is called,
+    /// so a change between calling it and reading the session is not missed.
+    pub fn session_changed(&self) -> Notified<'_> {
+        self.session_changed.notified()
     }
 
     pub async fn get_session(
@@ -0,0 +1,91 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_channel_owner;
+use super::models::revenue::{MonthlyRevenue, PayoutLedgerEntry};
+use super::pagination::{page_limit, Cursor};
+use crate::database::{payout_ledger_entry, revenue_transaction};
+
+const DEFAULT_LEDGER_LIMIT: u32 = 50;
+const MAX_LEDGER_LIMIT: u32 = 100;
+
+#[derive(Default)]
+pub struct RevenueQuery;
+
+#[Object]
+/// The query object for channel revenue. All amounts are in cents.
+impl RevenueQuery {
+    /// Get the revenue of a channel per month, newest first.
+    /// The same report can be downloaded as CSV from `/v1/revenue/{channelId}/export`.
+    async fn monthly<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<MonthlyRevenue>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let revenue = revenue_transaction::monthly_revenue(&global.db, channel_id)
+            .await
+            .map_err_gql("Failed to fetch revenue")?;
+
+        Ok(revenue.into_iter().map(MonthlyRevenue::from).collect())
+    }
+
+    /// Get the entries of a channel's payout ledger, newest first.
+    /// To fetch the next page pass the `cursor` of the last entry as `after`.
+    async fn ledger<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "Only return entries after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(desc = "The maximum number of entries to return. Defaults to 50, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<PayoutLedgerEntry>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let limit = page_limit(limit, DEFAULT_LEDGER_LIMIT, MAX_LEDGER_LIMIT)?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let entries = sqlx::query_as!(
+            payout_ledger_entry::Model,
+            "SELECT * FROM payout_ledger_entries WHERE channel_id = $1 AND ($2::timestamptz IS NULL OR (created_at, id) < ($2, $3::uuid)) ORDER BY created_at DESC, id DESC LIMIT $4",
+            channel_id,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch payout ledger")?;
+
+        Ok(entries.into_iter().map(PayoutLedgerEntry::from).collect())
+    }
+
+    /// The amount which has been credited to a channel but not paid out yet.
+    async fn balance<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<i64> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let balance = sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(amount), 0)::bigint AS "balance!" FROM payout_ledger_entries WHERE channel_id = $1"#,
+            channel_id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch balance")?;
+
+        Ok(balance)
+    }
+}
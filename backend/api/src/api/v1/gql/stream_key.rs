@@ -0,0 +1,265 @@
+use async_graphql::{Context, Object};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_channel_owner, authorize_elevated};
+use super::models::stream_key::{CreatedStreamKey, StreamKey};
+use crate::database::{stream_key, user};
+use crate::global::GlobalState;
+
+fn no_key() -> GqlError {
+    GqlError::NotFound
+        .with_message("Stream key not found")
+        .with_field(vec!["id"])
+}
+
+async fn fetch_channel(global: &GlobalState, channel_id: Uuid) -> Result<user::Model> {
+    global
+        .user_by_id_loader
+        .load_one(channel_id)
+        .await
+        .map_err_gql("Failed to fetch channel")?
+        .ok_or_else(|| {
+            GqlError::NotFound
+                .with_message("Channel not found")
+                .with_field(vec!["channelId"])
+        })
+}
+
+#[derive(Default)]
+pub struct StreamKeyQuery;
+
+#[Object]
+/// The query object for stream keys.
+impl StreamKeyQuery {
+    /// Get the stream keys of a channel, masked. The default key comes first, then the named keys oldest first.
+    /// Only the broadcaster can see them.
+    async fn list<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<StreamKey>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let channel = fetch_channel(global, channel_id).await?;
+
+        let keys = sqlx::query_as!(
+            stream_key::Model,
+            "SELECT * FROM stream_keys WHERE channel_id = $1 ORDER BY created_at, id",
+            channel_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch stream keys")?;
+
+        Ok(std::iter::once(StreamKey::default_key(&channel))
+            .chain(keys.into_iter().map(StreamKey::from))
+            .collect())
+    }
+}
+
+#[derive(Default)]
+pub struct StreamKeyMutation;
+
+#[Object]
+/// The mutation object for stream keys. A channel can go live with its default key or any of its named keys,
+/// like one per encoder, so a leaked key can be replaced without touching the others.
+impl StreamKeyMutation {
+    /// Create a named stream key. Only the broadcaster can do this, and they need to have reauthenticated recently.
+    async fn create<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "A name to recognize the key by, like the encoder it is used in.")]
+        name: String,
+    ) -> Result<CreatedStreamKey> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+        authorize_elevated(ctx).await?;
+
+        stream_key::validate_name(&name).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["name"])
+        })?;
+
+        let max_keys = global.config.stream_keys.max_keys;
+
+        // The count is checked in the insert so keys created at the same time can't get past the limit.
+        let key = sqlx::query_as!(
+            stream_key::Model,
+            "INSERT INTO stream_keys (channel_id, name, key) SELECT $1, $2, $3 WHERE (SELECT COUNT(*) FROM stream_keys WHERE channel_id = $1) < $4 ON CONFLICT DO NOTHING RETURNING *",
+            channel_id,
+            name.trim(),
+            user::generate_stream_key(),
+            max_keys as i64,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to create stream key")?;
+
+        let Some(key) = key else {
+            let count = sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM stream_keys WHERE channel_id = $1"#,
+                channel_id,
+            )
+            .fetch_one(&*global.db)
+            .await
+            .map_err_gql("Failed to create stream key")?;
+
+            if count >= max_keys as i64 {
+                return Err(GqlError::InvalidInput.with_message(&format!(
+                    "A channel can have at most {} named stream keys",
+                    max_keys
+                )));
+            }
+
+            return Err(GqlError::InvalidInput
+                .with_message("A stream key with this name already exists")
+                .with_field(vec!["name"]));
+        };
+
+        Ok(CreatedStreamKey {
+            key: stream_key::full_key(key.channel_id, &key.key),
+            stream_key: key.into(),
+        })
+    }
+
+    /// Replace a stream key with a new one. The old key keeps working for a while so an encoder reconnecting
+    /// in the meantime is not cut off, a live stream is never ended. Only the broadcaster can do this,
+    /// and they need to have reauthenticated recently.
+    async fn regenerate<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The id of the named key, the default key if not set.")] id: Option<Uuid>,
+        #[graphql(
+            desc = "Stop the old key from working right away, for a key which leaked.",
+            default
+        )]
+        revoke_old: bool,
+    ) -> Result<CreatedStreamKey> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+        authorize_elevated(ctx).await?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to regenerate stream key")?;
+
+        let new_key = user::generate_stream_key();
+
+        // The row is locked while the old key is read, so two rotations at once can't lose a key.
+        let (old_key, stream_key) = match id {
+            Some(id) => {
+                let old_key = sqlx::query_scalar!(
+                    "SELECT key FROM stream_keys WHERE id = $1 AND channel_id = $2 FOR UPDATE",
+                    id,
+                    channel_id,
+                )
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err_gql("Failed to regenerate stream key")?
+                .ok_or_else(no_key)?;
+
+                let key = sqlx::query_as!(
+                    stream_key::Model,
+                    "UPDATE stream_keys SET key = $2 WHERE id = $1 RETURNING *",
+                    id,
+                    new_key,
+                )
+                .fetch_one(&mut *tx)
+                .await
+                .map_err_gql("Failed to regenerate stream key")?;
+
+                (old_key, StreamKey::from(key))
+            }
+            None => {
+                let old_key = sqlx::query_scalar!(
+                    "SELECT stream_key FROM users WHERE id = $1 FOR UPDATE",
+                    channel_id,
+                )
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err_gql("Failed to regenerate stream key")?
+                .ok_or_else(|| {
+                    GqlError::NotFound
+                        .with_message("Channel not found")
+                        .with_field(vec!["channelId"])
+                })?;
+
+                let channel = sqlx::query_as!(
+                    user::Model,
+                    "UPDATE users SET stream_key = $2 WHERE id = $1 RETURNING *",
+                    channel_id,
+                    new_key,
+                )
+                .fetch_one(&mut *tx)
+                .await
+                .map_err_gql("Failed to regenerate stream key")?;
+
+                (old_key, StreamKey::default_key(&channel))
+            }
+        };
+
+        let grace = global.config.stream_keys.rotation_grace;
+        if !revoke_old && grace > 0 {
+            stream_key::retire(
+                &mut tx,
+                channel_id,
+                &old_key,
+                Utc::now() + Duration::seconds(grace as i64),
+            )
+            .await
+            .map_err_gql("Failed to regenerate stream key")?;
+        }
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to regenerate stream key")?;
+
+        Ok(CreatedStreamKey {
+            key: stream_key::full_key(channel_id, &new_key),
+            stream_key,
+        })
+    }
+
+    /// Delete a named stream key, it stops working right away. A live stream is not ended.
+    /// Only the broadcaster can do this, and they need to have reauthenticated recently.
+    async fn delete<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The id of the named key.")] id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+        authorize_elevated(ctx).await?;
+
+        let deleted = sqlx::query!(
+            "DELETE FROM stream_keys WHERE id = $1 AND channel_id = $2",
+            id,
+            channel_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to delete stream key")?
+        .rows_affected()
+            > 0;
+
+        if !deleted {
+            return Err(no_key());
+        }
+
+        Ok(true)
+    }
+}
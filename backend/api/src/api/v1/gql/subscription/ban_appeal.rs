@@ -0,0 +1,49 @@
+use async_graphql::{Context, Subscription};
+use futures_util::Stream;
+use prost::Message;
+
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+        guards::authorize_user,
+        models::events::BanAppealReview,
+    },
+    pb::{self, Event},
+};
+
+#[derive(Default)]
+pub struct BanAppealSubscription;
+
+#[Subscription]
+impl BanAppealSubscription {
+    /// Listen to moderators accepting or denying the ban appeals of the logged in user.
+    async fn ban_appeal_reviews<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+    ) -> Result<impl Stream<Item = Result<BanAppealReview>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let mut subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::BanAppealReviewed::subject(
+                session.user_id,
+            ))
+            .await
+            .map_err_gql("failed to subscribe to ban appeal reviews")?;
+
+        Ok(async_stream::stream!({
+            while let Ok(message) = subscription.recv().await {
+                let event = pb::scuffle::events::BanAppealReviewed::decode(
+                    message.as_bytes().map_err_gql("invalid redis value")?,
+                )
+                .map_err_gql("failed to decode ban appeal review")?;
+
+                yield BanAppealReview::try_from(event)
+                    .map_err_gql("failed to parse ban appeal review");
+            }
+        }))
+    }
+}
@@ -0,0 +1,131 @@
+use async_graphql::{Context, Subscription};
+use futures_util::Stream;
+use prost::Message;
+use uuid::Uuid;
+
+use crate::{
+    api::v1::gql::{
+        error::{GqlError, Result, ResultExt},
+        ext::ContextExt,
+        models::{
+            channel_appearance::ChannelAppearance, events::StreamInfo,
+            stream_lifecycle::StreamLifecycleTransition,
+        },
+    },
+    database::channel_appearance,
+    pb::{self, Event},
+};
+
+#[derive(Default)]
+pub struct ChannelSubscription;
+
+#[Subscription]
+impl ChannelSubscription {
+    /// Listen to changes to how the page of a channel looks. The current appearance is sent first.
+    async fn channel_appearance<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<ChannelAppearance>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let mut subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::ChannelAppearanceUpdated::subject(
+                channel_id,
+            ))
+            .await
+            .map_err_gql("failed to subscribe to appearance")?;
+
+        Ok(async_stream::stream!({
+            // The event only says something changed, so every update is read from the database.
+            // Subscribing before the first read means no change can happen unnoticed in between.
+            loop {
+                let appearance = channel_appearance::for_channel(&global.db, channel_id)
+                    .await
+                    .map_err_gql("failed to fetch appearance")?;
+
+                yield Ok(appearance.into());
+
+                if subscription.recv().await.is_err() {
+                    break;
+                }
+            }
+        }))
+    }
+
+    /// Listen to changes to the title, category and language of the stream of a channel, so the directory and the
+    /// channel page stay up to date. The current stream info is sent first.
+    async fn channel_stream_info<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<StreamInfo>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let Some(channel) = global
+            .user_by_id_loader
+            .load_one(channel_id)
+            .await
+            .map_err_gql("failed to fetch channel")?
+        else {
+            return Err(GqlError::NotFound
+                .with_message("channel not found")
+                .with_field(vec!["channelId"]));
+        };
+
+        let mut subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::ChannelStreamInfoUpdated::subject(
+                channel_id,
+            ))
+            .await
+            .map_err_gql("failed to subscribe to stream info")?;
+
+        Ok(async_stream::stream!({
+            yield Ok(StreamInfo {
+                title: channel.stream_title,
+                category: channel.stream_category,
+                language: channel.stream_language,
+            });
+
+            while let Ok(message) = subscription.recv().await {
+                let event = pb::scuffle::events::ChannelStreamInfoUpdated::decode(
+                    message.as_bytes().map_err_gql("invalid redis value")?,
+                )
+                .map_err_gql("failed to decode stream info")?;
+
+                yield StreamInfo::try_from(event).map_err_gql("failed to parse stream info");
+            }
+        }))
+    }
+
+    /// Listen to the streams of a channel moving through their lifecycle, from requested to live to ended.
+    async fn stream_lifecycle<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<StreamLifecycleTransition>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let mut subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::StreamLifecycleChanged::subject(
+                channel_id,
+            ))
+            .await
+            .map_err_gql("failed to subscribe to stream lifecycle")?;
+
+        Ok(async_stream::stream!({
+            while let Ok(message) = subscription.recv().await {
+                let event = pb::scuffle::events::StreamLifecycleChanged::decode(
+                    message.as_bytes().map_err_gql("invalid redis value")?,
+                )
+                .map_err_gql("failed to decode stream lifecycle")?;
+
+                yield StreamLifecycleTransition::try_from(event)
+                    .map_err_gql("failed to parse stream lifecycle");
+            }
+        }))
+    }
+}
@@ -0,0 +1,91 @@
+use async_graphql::{Context, Subscription};
+use futures_util::Stream;
+use prost::Message;
+use uuid::Uuid;
+
+use crate::{
+    api::v1::gql::{
+        error::{GqlError, Result, ResultExt},
+        ext::ContextExt,
+        models::events::CharityProgress,
+    },
+    database::charity_campaign,
+    pb::{self, Event},
+};
+
+#[derive(Default)]
+pub struct CharitySubscription;
+
+#[Subscription]
+impl CharitySubscription {
+    /// Listen to the progress of a channel's charity campaigns. Starts with the running campaign, if any.
+    async fn charity_progress<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<CharityProgress>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let Some(channel) = global
+            .user_by_id_loader
+            .load_one(channel_id)
+            .await
+            .map_err_gql("failed to fetch channel")?
+        else {
+            return Err(GqlError::NotFound
+                .with_message("channel not found")
+                .with_field(vec!["channelId"]));
+        };
+
+        // Subscribe before reading the current state so no update is missed in between.
+        let mut subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::CharityCampaignProgress::subject(
+                channel.id,
+            ))
+            .await
+            .map_err_gql("failed to subscribe to charity progress")?;
+
+        let campaign = sqlx::query_as!(
+            charity_campaign::Model,
+            "SELECT * FROM charity_campaigns WHERE channel_id = $1 AND ended_at IS NULL",
+            channel.id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("failed to fetch charity campaign")?;
+
+        let current = match campaign {
+            Some(campaign) => {
+                let totals = charity_campaign::totals(&global.db, campaign.id)
+                    .await
+                    .map_err_gql("failed to fetch donations")?;
+
+                Some(CharityProgress {
+                    campaign_id: campaign.id,
+                    raised_amount: totals.raised_amount,
+                    target_amount: campaign.target_amount,
+                    donation_count: totals.donation_count,
+                    ended: false,
+                })
+            }
+            None => None,
+        };
+
+        Ok(async_stream::stream!({
+            if let Some(current) = current {
+                yield Ok(current);
+            }
+
+            while let Ok(message) = subscription.recv().await {
+                let event = pb::scuffle::events::CharityCampaignProgress::decode(
+                    message.as_bytes().map_err_gql("invalid redis value")?,
+                )
+                .map_err_gql("failed to decode charity progress")?;
+
+                yield CharityProgress::try_from(event)
+                    .map_err_gql("failed to parse charity progress");
+            }
+        }))
+    }
+}
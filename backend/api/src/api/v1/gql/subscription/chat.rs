@@ -1,8 +1,14 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::Duration,
+};
+
 use async_graphql::{Context, Subscription};
 use async_stream::stream;
-use chrono::{TimeZone, Utc};
+use fred::types::RedisValue;
 use futures_util::Stream;
 use prost::Message;
+use tokio::sync::broadcast::error::TryRecvError;
 use uuid::Uuid;
 
 use crate::{
@@ -11,15 +17,91 @@ use crate::{
         ext::ContextExt,
         models::chat_message::{ChatMessage, MessageType},
     },
-    pb,
+    database::{chat_ban, chat_log, user_block},
+    pb::{self, Event},
+    subscription::{RecvError, SubscriberReceiver},
 };
 
+/// How many events a subscription holds back at most while waiting for an earlier one.
+const MAX_REORDER_EVENTS: usize = 64;
+
+/// Applies the blocks the viewer made or removed since the last message, before the next one is delivered.
+fn apply_blocks(blocks: &mut Option<SubscriberReceiver<'_>>, blocked: &mut HashSet<String>) {
+    let Some(blocks) = blocks else {
+        return;
+    };
+
+    loop {
+        let message = match blocks.try_recv() {
+            Ok(message) => message,
+            Err(TryRecvError::Lagged(_)) => continue,
+            Err(_) => return,
+        };
+
+        let Some(event) = message
+            .as_bytes()
+            .and_then(|b| pb::scuffle::events::UserBlockChanged::decode(b).ok())
+        else {
+            continue;
+        };
+
+        if event.blocked {
+            blocked.insert(event.blocked_id);
+        } else {
+            blocked.remove(&event.blocked_id);
+        }
+    }
+}
+
+/// Waits for the next chat event. While events are held back it only waits for the reorder window, None once it passed.
+async fn receive(
+    messages: &mut SubscriberReceiver<'_>,
+    window: Option<Duration>,
+) -> Option<Result<RedisValue, RecvError>> {
+    match window {
+        Some(window) => tokio::time::timeout(window, messages.recv()).await.ok(),
+        None => Some(messages.recv().await),
+    }
+}
+
+/// Resolves once the viewer is banned from the chat. Never resolves for anonymous viewers, or if the subscription closed.
+async fn banned(
+    bans: &mut Option<SubscriberReceiver<'_>>,
+    channel_id: &str,
+) -> Result<(), RecvError> {
+    if let Some(bans) = bans {
+        loop {
+            let message = match bans.recv().await {
+                Ok(message) => message,
+                Err(RecvError::Closed) => break,
+                Err(e) => return Err(e),
+            };
+
+            let banned = message
+                .as_bytes()
+                .and_then(|b| pb::scuffle::events::ChatBanChanged::decode(b).ok())
+                .map_or(false, |event| {
+                    event.banned && event.channel_id == channel_id
+                });
+
+            if banned {
+                return Ok(());
+            }
+        }
+    }
+
+    std::future::pending().await
+}
+
 #[derive(Default)]
 pub struct ChatSubscription;
 
 #[Subscription]
 impl ChatSubscription {
-    // Listen to new messages in chat.
+    // Listen to new messages in chat. Messages are delivered in the order of their sequence,
+    // a message which never arrives is skipped after a short wait so the client can fetch it.
+    // Messages of users the logged in user blocked are left out. A user banned from the chat can't subscribe,
+    // and the subscription ends if they are banned while subscribed.
     pub async fn chat_messages<'ctx>(
         &self,
         ctx: &'ctx Context<'_>,
@@ -27,15 +109,6 @@ impl ChatSubscription {
     ) -> Result<impl Stream<Item = Result<ChatMessage>> + 'ctx> {
         let global = ctx.get_global();
 
-        let welcome_message = ChatMessage {
-            id: Uuid::nil(),
-            author_id: Uuid::nil(),
-            channel_id,
-            content: "Welcome to the chat!".to_string(),
-            created_at: chrono::Utc::now().into(),
-            r#type: MessageType::Welcome,
-        };
-
         // TODO: check if user is allowed to read this chat
         let channel = global
             .user_by_id_loader
@@ -45,33 +118,151 @@ impl ChatSubscription {
             .ok_or(GqlError::NotFound.with_message("user not found"))?;
         let mut message_stream = global
             .subscription_manager
-            .subscribe(format!("user:{}:chat:messages", channel.id))
+            .subscribe(pb::scuffle::events::ChatMessage::subject(channel.id))
             .await
             .map_err_gql("failed to subscribe to chat messages")?;
 
+        // Read after subscribing, so no event after the current one can be missed.
+        let current = chat_log::current_sequence(&*global.db, channel.id)
+            .await
+            .map_err_gql("failed to fetch chat sequence")?;
+
+        let viewer_id = ctx
+            .get_session()
+            .get_session(global)
+            .await?
+            .map(|(session, _)| session.user_id);
+
+        let (mut blocks, mut blocked) = match viewer_id {
+            Some(viewer_id) => {
+                let blocks = global
+                    .subscription_manager
+                    .subscribe(pb::scuffle::events::UserBlockChanged::subject(viewer_id))
+                    .await
+                    .map_err_gql("failed to subscribe to blocks")?;
+
+                let blocked = user_block::blocked_by(&*global.db, viewer_id)
+                    .await
+                    .map_err_gql("failed to fetch blocked users")?
+                    .into_iter()
+                    .map(|id| id.to_string())
+                    .collect();
+
+                (Some(blocks), blocked)
+            }
+            None => (None, HashSet::new()),
+        };
+
+        let mut bans = match viewer_id {
+            Some(viewer_id) => {
+                // Subscribed before checking, so a ban right after the check still ends the subscription.
+                let bans = global
+                    .subscription_manager
+                    .subscribe(pb::scuffle::events::ChatBanChanged::subject(viewer_id))
+                    .await
+                    .map_err_gql("failed to subscribe to bans")?;
+
+                if chat_ban::is_banned(&global.db, channel.id, viewer_id)
+                    .await
+                    .map_err_gql("failed to fetch ban")?
+                {
+                    return Err(
+                        GqlError::Unauthorized.with_message("You are banned from this chat")
+                    );
+                }
+
+                Some(bans)
+            }
+            None => None,
+        };
+        let channel_key = channel.id.to_string();
+
+        let welcome_message = ChatMessage {
+            id: Uuid::nil(),
+            author_id: Uuid::nil(),
+            channel_id,
+            content: "Welcome to the chat!".to_string(),
+            created_at: chrono::Utc::now().into(),
+            r#type: MessageType::Welcome,
+            emotes: Vec::new(),
+            cheer: None,
+            author_color: None,
+            sequence: current,
+            author_vip: false,
+            author_verified_bot: false,
+        };
+
+        let reorder_window = Duration::from_millis(global.config.chat.reorder_window as u64);
+
         Ok(stream!({
             yield Ok(welcome_message);
-            while let Ok(message) = message_stream.recv().await {
+
+            let mut next = current + 1;
+            let mut pending = BTreeMap::new();
+
+            loop {
+                let window = (!pending.is_empty()).then_some(reorder_window);
+                let received = tokio::select! {
+                    ban = banned(&mut bans, &channel_key) => Err(ban),
+                    message = receive(&mut message_stream, window) => Ok(message),
+                };
+
+                let message = match received {
+                    Ok(Some(message)) => message,
+                    // The missing events are not coming, the client fetches them when it sees the jump.
+                    Ok(None) => {
+                        next = pending.keys().next().copied().unwrap_or(next);
+                        apply_blocks(&mut blocks, &mut blocked);
+                        while let Some(event) = pending.remove(&next) {
+                            next += 1;
+                            if !blocked.contains(&event.author_id) {
+                                yield ChatMessage::from_pb(event);
+                            }
+                        }
+                        continue;
+                    }
+                    Err(Ok(())) => {
+                        yield Err(
+                            GqlError::Unauthorized.with_message("You are banned from this chat")
+                        );
+                        break;
+                    }
+                    // It could have missed a ban of the viewer.
+                    Err(Err(_)) => Err(RecvError::Evicted),
+                };
+
+                let message = match message {
+                    Ok(message) => message,
+                    Err(RecvError::Evicted) => {
+                        yield Err(GqlError::InternalServerError
+                            .with_message("chat fell behind, subscribe again to continue"));
+                        break;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+
                 let event = pb::scuffle::events::ChatMessage::decode(
                     message.as_bytes().map_err_gql("invalid redis value type")?,
                 )
                 .map_err_gql("failed to decode chat message")?;
 
-                yield Ok(ChatMessage {
-                    id: Uuid::parse_str(&event.id)
-                        .map_err_gql("failed to parse chat message id")?,
-                    author_id: Uuid::parse_str(&event.author_id)
-                        .map_err_gql("failed to parse chat message author id")?,
-                    channel_id: Uuid::parse_str(&event.channel_id)
-                        .map_err_gql("failed to parse chat message channel id")?,
-                    content: event.content,
-                    created_at: Utc
-                        .timestamp_opt(event.created_at, 0)
-                        .single()
-                        .map_err_gql("failed to parse chat message created at")?
-                        .into(),
-                    r#type: MessageType::User,
-                });
+                // Already delivered, or published before the subscription started.
+                if event.sequence < next {
+                    continue;
+                }
+
+                pending.insert(event.sequence, event);
+                if pending.len() > MAX_REORDER_EVENTS {
+                    next = pending.keys().next().copied().unwrap_or(next);
+                }
+
+                apply_blocks(&mut blocks, &mut blocked);
+                while let Some(event) = pending.remove(&next) {
+                    next += 1;
+                    if !blocked.contains(&event.author_id) {
+                        yield ChatMessage::from_pb(event);
+                    }
+                }
             }
         }))
     }
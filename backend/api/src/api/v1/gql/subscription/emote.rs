@@ -0,0 +1,48 @@
+use async_graphql::{Context, Subscription};
+use futures_util::Stream;
+use prost::Message;
+use uuid::Uuid;
+
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+        guards::authorize_channel_owner,
+        models::events::EmoteReview,
+    },
+    pb::{self, Event},
+};
+
+#[derive(Default)]
+pub struct EmoteSubscription;
+
+#[Subscription]
+impl EmoteSubscription {
+    /// Listen to admins approving or rejecting the emotes of a channel.
+    async fn emote_reviews<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<EmoteReview>> + 'ctx> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let mut subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::EmoteReviewed::subject(channel_id))
+            .await
+            .map_err_gql("failed to subscribe to emote reviews")?;
+
+        Ok(async_stream::stream!({
+            while let Ok(message) = subscription.recv().await {
+                let event = pb::scuffle::events::EmoteReviewed::decode(
+                    message.as_bytes().map_err_gql("invalid redis value")?,
+                )
+                .map_err_gql("failed to decode emote review")?;
+
+                yield EmoteReview::try_from(event).map_err_gql("failed to parse emote review");
+            }
+        }))
+    }
+}
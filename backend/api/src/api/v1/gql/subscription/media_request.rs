@@ -0,0 +1,44 @@
+use async_graphql::{Context, Subscription};
+use futures_util::Stream;
+use uuid::Uuid;
+
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+        models::media_request::MediaRequestQueue,
+    },
+    pb::{scuffle::events::MediaRequestsChanged, Event},
+};
+
+#[derive(Default)]
+pub struct MediaRequestSubscription;
+
+#[Subscription]
+impl MediaRequestSubscription {
+    /// Listen to the media request queue of a channel, like `mediaRequest.queue`, for overlays to show on stream.
+    /// The current queue is sent first and then again whenever a request is made, reviewed or played.
+    async fn media_request_queue<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<MediaRequestQueue>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let mut subscription = global
+            .subscription_manager
+            .subscribe(MediaRequestsChanged::subject(channel_id))
+            .await
+            .map_err_gql("failed to subscribe to media requests")?;
+
+        Ok(async_stream::stream!({
+            loop {
+                yield MediaRequestQueue::load(global, channel_id).await;
+
+                if subscription.recv().await.is_err() {
+                    break;
+                }
+            }
+        }))
+    }
+}
@@ -1,13 +1,35 @@
 use async_graphql::{MergedSubscription, Subscription};
 use futures_util::Stream;
 
-use self::{chat::ChatSubscription, user::UserSubscription};
+use self::{
+    ban_appeal::BanAppealSubscription, channel::ChannelSubscription, charity::CharitySubscription,
+    chat::ChatSubscription, emote::EmoteSubscription, media_request::MediaRequestSubscription,
+    presence::PresenceSubscription, user::UserSubscription, viewer_queue::ViewerQueueSubscription,
+};
 
+pub mod ban_appeal;
+pub mod channel;
+pub mod charity;
 pub mod chat;
+pub mod emote;
+pub mod media_request;
+pub mod presence;
 pub mod user;
+pub mod viewer_queue;
 
 #[derive(MergedSubscription, Default)]
-pub struct Subscription(UserSubscription, ChatSubscription, NoopSubscription);
+pub struct Subscription(
+    UserSubscription,
+    ChatSubscription,
+    CharitySubscription,
+    EmoteSubscription,
+    BanAppealSubscription,
+    ChannelSubscription,
+    PresenceSubscription,
+    ViewerQueueSubscription,
+    MediaRequestSubscription,
+    NoopSubscription,
+);
 
 #[derive(Default)]
 struct NoopSubscription;
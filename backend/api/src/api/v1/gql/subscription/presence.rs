@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use async_graphql::{Context, Subscription};
+use futures_util::Stream;
+use tokio::select;
+use uuid::Uuid;
+
+use crate::{
+    api::v1::gql::{
+        error::{GqlError, Result, ResultExt},
+        ext::ContextExt,
+        guards::authorize_user,
+        models::presence::Presence,
+    },
+    database::user,
+    pb::{scuffle::events::UserCameOnline, Event},
+};
+
+#[derive(Default)]
+pub struct PresenceSubscription;
+
+#[Subscription]
+impl PresenceSubscription {
+    /// Listen to the followed users and friends who are online, like `presence.friendsPresence`. The list is sent
+    /// again when someone comes online, goes offline or starts watching something else.
+    async fn friends_presence<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+    ) -> Result<impl Stream<Item = Result<Vec<Presence>>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        // Users go offline by their presence expiring, which is never published, so the list is polled.
+        let mut interval = tokio::time::interval(Duration::from_secs(
+            global.config.presence.poll_interval as u64,
+        ));
+
+        Ok(async_stream::stream!({
+            let mut last = None;
+
+            loop {
+                interval.tick().await;
+
+                let presences = global
+                    .friends_presence(session.user_id)
+                    .await
+                    .map_err_gql("failed to fetch presence")?;
+
+                // Only a change of who is online or what they watch is sent, not every new activity.
+                let current = presences
+                    .iter()
+                    .map(|p| (p.user_id, p.watching))
+                    .collect::<Vec<_>>();
+                if last.as_ref() == Some(&current) {
+                    continue;
+                }
+                last = Some(current);
+
+                yield Ok(presences.into_iter().map(Presence::from).collect());
+            }
+        }))
+    }
+
+    /// Listen to whether a user is online, like `User.isOnline`. Sent once right away and then whenever it changes.
+    /// Only users who let the logged in user see when they are online can be listened to.
+    async fn user_online<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        #[graphql(desc = "The id of the user.")] user_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<bool>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let visible = user::presence_visible(&*global.db, session.user_id, user_id)
+            .await
+            .map_err_gql("failed to fetch presence settings")?;
+        if !visible {
+            return Err(GqlError::Unauthorized
+                .with_message("this user does not let you see when they are online")
+                .with_field(vec!["userId"]));
+        }
+
+        let mut subscription = global
+            .subscription_manager
+            .subscribe(UserCameOnline::subject(user_id))
+            .await
+            .map_err_gql("failed to subscribe to presence")?;
+
+        // Coming online is published, going offline is only noticed by checking again.
+        let mut interval = tokio::time::interval(Duration::from_secs(
+            global.config.presence.poll_interval as u64,
+        ));
+        interval.tick().await;
+
+        Ok(async_stream::stream!({
+            let mut online = global
+                .is_online(user_id)
+                .await
+                .map_err_gql("failed to fetch presence")?;
+            yield Ok(online);
+
+            loop {
+                let current = select! {
+                    message = subscription.recv() => {
+                        if message.is_err() {
+                            break;
+                        }
+                        true
+                    }
+                    _ = interval.tick(), if online => {
+                        global
+                            .is_online(user_id)
+                            .await
+                            .map_err_gql("failed to fetch presence")?
+                    }
+                };
+
+                if current != online {
+                    online = current;
+                    yield Ok(online);
+                }
+            }
+        }))
+    }
+}
@@ -7,8 +7,10 @@ use crate::{
     api::v1::gql::{
         error::{GqlError, Result, ResultExt},
         ext::ContextExt,
+        models::{color::DisplayColor, events::UserBio, profile::UserProfile},
     },
-    pb,
+    database::{display_color, global_role, user_social_link},
+    pb::{self, Event},
 };
 
 #[derive(Default)]
@@ -20,6 +22,22 @@ struct DisplayNameStream {
     pub display_name: String,
 }
 
+/// How a user is shown, sent whole with every change.
+#[derive(SimpleObject, Clone)]
+struct UserUpdate {
+    pub username: String,
+    pub display_name: String,
+    /// The color of the name, null if the user has none.
+    pub display_color: Option<DisplayColor>,
+}
+
+/// The subject a `UserUpdate` change came from.
+enum UserChange {
+    DisplayName,
+    DisplayColor,
+    Profile,
+}
+
 #[Subscription]
 impl UserSubscription {
     async fn user_display_name<'ctx>(
@@ -42,7 +60,7 @@ impl UserSubscription {
 
         let mut subscription = global
             .subscription_manager
-            .subscribe(format!("user:{}:display_name", user_id))
+            .subscribe(pb::scuffle::events::UserDisplayName::subject(user_id))
             .await
             .map_err_gql("failed to subscribe to user display name")?;
 
@@ -73,4 +91,231 @@ impl UserSubscription {
             }
         }))
     }
+
+    /// Listen to changes to the bio of a user, so the profile page can show them live. The current bio is sent first.
+    async fn user_bio<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        user_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<UserBio>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let Some(user) = global
+            .user_by_id_loader
+            .load_one(user_id)
+            .await
+            .map_err_gql("failed to fetch user")?
+        else {
+            return Err(GqlError::NotFound
+                .with_message("user not found")
+                .with_field(vec!["user_id"]));
+        };
+
+        let mut subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::UserBioUpdated::subject(user_id))
+            .await
+            .map_err_gql("failed to subscribe to user bio")?;
+
+        // Bios changed with updateProfile come with the rest of the profile.
+        let mut profile_subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::UserProfileUpdated::subject(user_id))
+            .await
+            .map_err_gql("failed to subscribe to user profile")?;
+
+        Ok(async_stream::stream!({
+            yield Ok(UserBio { bio: user.bio });
+
+            loop {
+                let (message, from_profile) = tokio::select! {
+                    message = subscription.recv() => (message, false),
+                    message = profile_subscription.recv() => (message, true),
+                };
+
+                let Ok(message) = message else {
+                    break;
+                };
+
+                let bytes = message.as_bytes().map_err_gql("invalid redis value")?;
+
+                if from_profile {
+                    let event = pb::scuffle::events::UserProfileUpdated::decode(bytes)
+                        .map_err_gql("failed to decode user profile")?;
+
+                    yield Ok(UserBio { bio: event.bio });
+                } else {
+                    let event = pb::scuffle::events::UserBioUpdated::decode(bytes)
+                        .map_err_gql("failed to decode user bio")?;
+
+                    yield UserBio::try_from(event).map_err_gql("failed to parse user bio");
+                }
+            }
+        }))
+    }
+
+    /// Listen to changes to the profile of a user made with `updateProfile`, each change comes as one event.
+    /// The current profile is sent first.
+    async fn user_profile<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        user_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<UserProfile>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let Some(user) = global
+            .user_by_id_loader
+            .load_one(user_id)
+            .await
+            .map_err_gql("failed to fetch user")?
+        else {
+            return Err(GqlError::NotFound
+                .with_message("user not found")
+                .with_field(vec!["user_id"]));
+        };
+
+        let gradient_allowed = global
+            .user_permisions_by_id_loader
+            .load_one(user_id)
+            .await
+            .map_err_gql("failed to fetch permissions")?
+            .map(|p| {
+                p.permissions
+                    .has_permission(global_role::Permission::DisplayNameGradient)
+            })
+            .unwrap_or_default();
+
+        let links = sqlx::query_as!(
+            user_social_link::Model,
+            "SELECT * FROM user_social_links WHERE user_id = $1 ORDER BY position",
+            user_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("failed to fetch social links")?;
+
+        let mut subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::UserProfileUpdated::subject(user_id))
+            .await
+            .map_err_gql("failed to subscribe to user profile")?;
+
+        Ok(async_stream::stream!({
+            yield Ok(UserProfile::new(&user, gradient_allowed, links));
+
+            while let Ok(message) = subscription.recv().await {
+                let event = pb::scuffle::events::UserProfileUpdated::decode(
+                    message.as_bytes().map_err_gql("invalid redis value")?,
+                )
+                .map_err_gql("failed to decode user profile")?;
+
+                yield Ok(UserProfile::from(event));
+            }
+        }))
+    }
+
+    /// Listen to changes to how a user is shown, their username, display name and display color.
+    /// The current state is sent first, then the whole state again with every change.
+    async fn user_updates<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        user_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<UserUpdate>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let Some(user) = global
+            .user_by_id_loader
+            .load_one(user_id)
+            .await
+            .map_err_gql("failed to fetch user")?
+        else {
+            return Err(GqlError::NotFound
+                .with_message("user not found")
+                .with_field(vec!["user_id"]));
+        };
+
+        let gradient_allowed = global
+            .user_permisions_by_id_loader
+            .load_one(user_id)
+            .await
+            .map_err_gql("failed to fetch permissions")?
+            .map(|p| {
+                p.permissions
+                    .has_permission(global_role::Permission::DisplayNameGradient)
+            })
+            .unwrap_or_default();
+
+        let mut display_name_subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::UserDisplayName::subject(user_id))
+            .await
+            .map_err_gql("failed to subscribe to user display name")?;
+
+        let mut display_color_subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::UserDisplayColorUpdated::subject(
+                user_id,
+            ))
+            .await
+            .map_err_gql("failed to subscribe to user display color")?;
+
+        // Colors changed with updateProfile come with the rest of the profile.
+        let mut profile_subscription = global
+            .subscription_manager
+            .subscribe(pb::scuffle::events::UserProfileUpdated::subject(user_id))
+            .await
+            .map_err_gql("failed to subscribe to user profile")?;
+
+        Ok(async_stream::stream!({
+            let mut update = UserUpdate {
+                display_color: display_color::DisplayColor::of_user(&user, gradient_allowed)
+                    .map(DisplayColor::from),
+                username: user.username,
+                display_name: user.display_name,
+            };
+
+            loop {
+                yield Ok(update.clone());
+
+                let (message, subject) = tokio::select! {
+                    message = display_name_subscription.recv() => (message, UserChange::DisplayName),
+                    message = display_color_subscription.recv() => (message, UserChange::DisplayColor),
+                    message = profile_subscription.recv() => (message, UserChange::Profile),
+                };
+
+                let Ok(message) = message else {
+                    break;
+                };
+
+                let bytes = message.as_bytes().map_err_gql("invalid redis value")?;
+
+                match subject {
+                    UserChange::DisplayName => {
+                        let event = pb::scuffle::events::UserDisplayName::decode(bytes)
+                            .map_err_gql("failed to decode user display name")?;
+
+                        if let Some(username) = event.username {
+                            update.username = username;
+                        }
+
+                        if let Some(display_name) = event.display_name {
+                            update.display_name = display_name;
+                        }
+                    }
+                    UserChange::DisplayColor => {
+                        let event = pb::scuffle::events::UserDisplayColorUpdated::decode(bytes)
+                            .map_err_gql("failed to decode user display color")?;
+
+                        update.display_color = event.display_color.and_then(DisplayColor::from_pb);
+                    }
+                    UserChange::Profile => {
+                        let event = pb::scuffle::events::UserProfileUpdated::decode(bytes)
+                            .map_err_gql("failed to decode user profile")?;
+
+                        update.display_color = event.display_color.and_then(DisplayColor::from_pb);
+                    }
+                }
+            }
+        }))
+    }
 }
@@ -0,0 +1,62 @@
+use async_graphql::{Context, Subscription};
+use futures_util::Stream;
+use prost::Message;
+use uuid::Uuid;
+
+use crate::{
+    api::v1::gql::{
+        error::{Result, ResultExt},
+        ext::ContextExt,
+        guards::authorize_user,
+        models::viewer_queue::ViewerQueuePosition,
+    },
+    pb::{scuffle::events::ViewerQueueChanged, Event},
+};
+
+#[derive(Default)]
+pub struct ViewerQueueSubscription;
+
+#[Subscription]
+impl ViewerQueueSubscription {
+    /// Listen to where the logged in user is in the queue of a channel, like `viewerQueue.position`.
+    /// Sent once right away and then whenever it changes, `called` is true once when the broadcaster calls them.
+    async fn viewer_queue_position<'ctx>(
+        &self,
+        ctx: &'ctx Context<'ctx>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<ViewerQueuePosition>> + 'ctx> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+        let user_id = session.user_id.to_string();
+
+        let mut subscription = global
+            .subscription_manager
+            .subscribe(ViewerQueueChanged::subject(channel_id))
+            .await
+            .map_err_gql("failed to subscribe to viewer queue")?;
+
+        Ok(async_stream::stream!({
+            let mut last =
+                ViewerQueuePosition::load(global, channel_id, session.user_id, false).await?;
+            yield Ok(last.clone());
+
+            while let Ok(message) = subscription.recv().await {
+                let event = ViewerQueueChanged::decode(
+                    message.as_bytes().map_err_gql("invalid redis value")?,
+                )
+                .map_err_gql("failed to decode viewer queue")?;
+
+                let called = event.called_ids.contains(&user_id);
+                let position =
+                    ViewerQueuePosition::load(global, channel_id, session.user_id, called).await?;
+
+                // Viewers further back only hear about changes which move them or the size of the queue.
+                if position != last {
+                    last = position.clone();
+                    yield Ok(position);
+                }
+            }
+        }))
+    }
+}
@@ -0,0 +1,359 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_admin, authorize_session};
+use super::models::suspension::{Suspension, SuspensionAppeal, SuspensionAppealStatus};
+use crate::database::{user_suspension, user_suspension_appeal};
+
+const MAX_REASON_LENGTH: usize = 500;
+const MAX_MESSAGE_LENGTH: usize = 1000;
+const MAX_NOTE_LENGTH: usize = 500;
+
+/// Timed suspensions can last at most a year, longer ones should be permanent.
+const MAX_DURATION_HOURS: u32 = 24 * 365;
+
+const QUEUE_LIMIT: i64 = 50;
+
+#[derive(Default)]
+pub struct SuspensionQuery;
+
+#[Object]
+/// The query object for account suspensions.
+impl SuspensionQuery {
+    /// Get the suspension the logged in user is currently under, if any.
+    async fn mine<'ctx>(&self, ctx: &Context<'_>) -> Result<Option<Suspension>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_session(ctx).await?;
+
+        let suspension = user_suspension::active(&global.db, session.user_id)
+            .await
+            .map_err_gql("Failed to fetch suspension")?;
+
+        Ok(suspension.map(Suspension::from))
+    }
+
+    /// Get the appeal of a suspension of the logged in user, if they submitted one.
+    async fn my_appeal<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the suspension.")] suspension_id: Uuid,
+    ) -> Result<Option<SuspensionAppeal>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_session(ctx).await?;
+
+        let appeal = sqlx::query_as!(
+            user_suspension_appeal::Model,
+            "SELECT a.* FROM user_suspension_appeals a JOIN user_suspensions s ON s.id = a.suspension_id WHERE a.suspension_id = $1 AND s.user_id = $2",
+            suspension_id,
+            session.user_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch appeal")?;
+
+        Ok(appeal.map(SuspensionAppeal::from))
+    }
+
+    /// Get every suspension of a user, newest first.
+    async fn history<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the user.")] user_id: Uuid,
+    ) -> Result<Vec<Suspension>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let suspensions = sqlx::query_as!(
+            user_suspension::Model,
+            "SELECT * FROM user_suspensions WHERE user_id = $1 ORDER BY created_at DESC",
+            user_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch suspensions")?;
+
+        Ok(suspensions.into_iter().map(Suspension::from).collect())
+    }
+
+    /// Get the suspension appeals, oldest first so they are reviewed in order.
+    async fn appeals<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The status of the appeals. Defaults to pending.")] status: Option<
+            SuspensionAppealStatus,
+        >,
+    ) -> Result<Vec<SuspensionAppeal>> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let status =
+            user_suspension_appeal::Status::from(status.unwrap_or(SuspensionAppealStatus::Pending));
+
+        let appeals = sqlx::query_as!(
+            user_suspension_appeal::Model,
+            "SELECT * FROM user_suspension_appeals WHERE status = $1 ORDER BY created_at ASC LIMIT $2",
+            i64::from(status),
+            QUEUE_LIMIT,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch appeals")?;
+
+        Ok(appeals.into_iter().map(SuspensionAppeal::from).collect())
+    }
+}
+
+/// Accepts or denies a pending appeal. Accepted appeals lift the suspension.
+async fn review(
+    ctx: &Context<'_>,
+    id: Uuid,
+    accepted: bool,
+    note: String,
+) -> Result<SuspensionAppeal> {
+    let global = ctx.get_global();
+
+    let (session, _) = authorize_admin(ctx).await?;
+
+    if note.len() > MAX_NOTE_LENGTH {
+        return Err(GqlError::InvalidInput
+            .with_message("Note must be at most 500 characters")
+            .with_field(vec!["note"]));
+    }
+
+    let status = match accepted {
+        true => user_suspension_appeal::Status::Accepted,
+        false => user_suspension_appeal::Status::Denied,
+    };
+
+    let mut tx = global
+        .db
+        .begin()
+        .await
+        .map_err_gql("Failed to review appeal")?;
+
+    let appeal = sqlx::query_as!(
+        user_suspension_appeal::Model,
+        "UPDATE user_suspension_appeals SET status = $2, reviewed_by = $3, review_note = $4, reviewed_at = NOW() WHERE id = $1 AND status = $5 RETURNING *",
+        id,
+        i64::from(status),
+        session.user_id,
+        note,
+        i64::from(user_suspension_appeal::Status::Pending),
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err_gql("Failed to review appeal")?
+    .ok_or_else(|| {
+        GqlError::InvalidInput
+            .with_message("Appeal not found or already reviewed")
+            .with_field(vec!["id"])
+    })?;
+
+    if accepted {
+        sqlx::query!(
+            "UPDATE user_suspensions SET lifted_by = $2, lift_reason = $3, lifted_at = NOW() WHERE id = $1 AND lifted_at IS NULL",
+            appeal.suspension_id,
+            session.user_id,
+            "Appeal accepted",
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to lift suspension")?;
+    }
+
+    tx.commit().await.map_err_gql("Failed to review appeal")?;
+
+    Ok(appeal.into())
+}
+
+#[derive(Default)]
+pub struct SuspensionMutation;
+
+#[Object]
+/// The mutation object for account suspensions.
+impl SuspensionMutation {
+    /// Suspend a user from the whole site for a while. This logs them out everywhere and ends their live stream.
+    /// Permanent suspensions need a second admin, see approval.proposePermanentSuspension.
+    async fn suspend<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the user.")] user_id: Uuid,
+        #[graphql(desc = "The reason shown to the user.")] reason: String,
+        #[graphql(desc = "How long the suspension lasts in hours, at most a year.")]
+        duration_hours: Option<u32>,
+    ) -> Result<Suspension> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        if user_id == session.user_id {
+            return Err(GqlError::InvalidInput
+                .with_message("You can not suspend yourself")
+                .with_field(vec!["userId"]));
+        }
+
+        if reason.trim().is_empty() || reason.len() > MAX_REASON_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Reason must be between 1 and 500 characters")
+                .with_field(vec!["reason"]));
+        }
+
+        let Some(hours) = duration_hours else {
+            return Err(GqlError::InvalidInput
+                .with_message("Permanent suspensions need a second admin, propose them with approval.proposePermanentSuspension")
+                .with_field(vec!["durationHours"]));
+        };
+
+        if hours == 0 || hours > MAX_DURATION_HOURS {
+            return Err(GqlError::InvalidInput
+                .with_message("Duration must be between 1 hour and 1 year")
+                .with_field(vec!["durationHours"]));
+        }
+
+        global
+            .user_by_id_loader
+            .load_one(user_id)
+            .await
+            .map_err_gql("Failed to fetch user")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("User not found")
+                    .with_field(vec!["userId"])
+            })?;
+
+        if user_suspension::active(&global.db, user_id)
+            .await
+            .map_err_gql("Failed to fetch suspension")?
+            .is_some()
+        {
+            return Err(GqlError::InvalidInput
+                .with_message("User is already suspended")
+                .with_field(vec!["userId"]));
+        }
+
+        let suspension = sqlx::query_as!(
+            user_suspension::Model,
+            "INSERT INTO user_suspensions (user_id, suspended_by, reason, expires_at) VALUES ($1, $2, $3, NOW() + $4 * INTERVAL '1 hour') RETURNING *",
+            user_id,
+            session.user_id,
+            reason,
+            hours as i64,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to suspend user")?;
+
+        global
+            .revoke_sessions(user_id)
+            .await
+            .map_err_gql("Failed to revoke sessions")?;
+
+        // The suspension is already saved and new streams are rejected, so a stream we fail to end must not fail the mutation.
+        if let Err(e) = global.end_live_streams(user_id).await {
+            tracing::error!(
+                "failed to end streams of suspended user {}: {:#}",
+                user_id,
+                e
+            );
+        }
+
+        Ok(suspension.into())
+    }
+
+    /// Lift a suspension before it expires.
+    async fn lift<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the suspension.")] id: Uuid,
+        #[graphql(desc = "Why the suspension is lifted.", default)] reason: String,
+    ) -> Result<Suspension> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_admin(ctx).await?;
+
+        if reason.len() > MAX_REASON_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Reason must be at most 500 characters")
+                .with_field(vec!["reason"]));
+        }
+
+        let suspension = sqlx::query_as!(
+            user_suspension::Model,
+            "UPDATE user_suspensions SET lifted_by = $2, lift_reason = $3, lifted_at = NOW() WHERE id = $1 AND lifted_at IS NULL AND (expires_at IS NULL OR expires_at > NOW()) RETURNING *",
+            id,
+            session.user_id,
+            reason,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to lift suspension")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("Suspension not found or no longer active")
+                .with_field(vec!["id"])
+        })?;
+
+        Ok(suspension.into())
+    }
+
+    /// Ask for the current suspension of the logged in user to be lifted. Each suspension can be appealed once.
+    async fn appeal<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Why the suspension should be lifted.")] message: String,
+    ) -> Result<SuspensionAppeal> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_session(ctx).await?;
+
+        if message.trim().is_empty() || message.len() > MAX_MESSAGE_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Message must be between 1 and 1000 characters")
+                .with_field(vec!["message"]));
+        }
+
+        let suspension = user_suspension::active(&global.db, session.user_id)
+            .await
+            .map_err_gql("Failed to fetch suspension")?
+            .ok_or_else(|| GqlError::InvalidInput.with_message("You are not suspended"))?;
+
+        let appeal = sqlx::query_as!(
+            user_suspension_appeal::Model,
+            "INSERT INTO user_suspension_appeals (suspension_id, message) VALUES ($1, $2) ON CONFLICT (suspension_id) DO NOTHING RETURNING *",
+            suspension.id,
+            message,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to submit appeal")?
+        .ok_or_else(|| GqlError::InvalidInput.with_message("You already appealed this suspension"))?;
+
+        Ok(appeal.into())
+    }
+
+    /// Accept an appeal, which lifts the suspension.
+    async fn accept_appeal<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the appeal.")] id: Uuid,
+        #[graphql(desc = "A note shown to the user.", default)] note: String,
+    ) -> Result<SuspensionAppeal> {
+        review(ctx, id, true, note).await
+    }
+
+    /// Deny an appeal, the user stays suspended and can not appeal again.
+    async fn deny_appeal<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the appeal.")] id: Uuid,
+        #[graphql(desc = "A note shown to the user.", default)] note: String,
+    ) -> Result<SuspensionAppeal> {
+        review(ctx, id, false, note).await
+    }
+}
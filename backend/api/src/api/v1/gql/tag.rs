@@ -0,0 +1,101 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_admin;
+use super::models::tag::Tag;
+use crate::database::tag;
+
+#[derive(Default)]
+pub struct TagQuery;
+
+#[Object]
+/// The query object for the tags broadcasters can pick for their channel.
+impl TagQuery {
+    /// Get every tag, by name.
+    async fn list<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<Tag>> {
+        let global = ctx.get_global();
+
+        let tags = sqlx::query_as!(tag::Model, "SELECT * FROM tags ORDER BY lower(name)")
+            .fetch_all(&*global.db)
+            .await
+            .map_err_gql("Failed to fetch tags")?;
+
+        Ok(tags.into_iter().map(Tag::from).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct TagMutation;
+
+#[Object]
+/// The mutation object for the tags broadcasters can pick for their channel.
+impl TagMutation {
+    /// Add a tag broadcasters can pick. Only admins can do this.
+    async fn create<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The name of the tag, unique without case.")] name: String,
+        #[graphql(desc = "What the tag is for.", default)] description: String,
+    ) -> Result<Tag> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        tag::validate_name(&name).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["name"])
+        })?;
+
+        if description.len() > tag::MAX_DESCRIPTION_LENGTH {
+            return Err(GqlError::InvalidInput
+                .with_message("Description must be at most 200 characters")
+                .with_field(vec!["description"]));
+        }
+
+        let tag = sqlx::query_as!(
+            tag::Model,
+            "INSERT INTO tags (name, description) VALUES ($1, $2) ON CONFLICT DO NOTHING RETURNING *",
+            name.trim(),
+            description.trim(),
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to create tag")?
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("A tag with this name already exists")
+                .with_field(vec!["name"])
+        })?;
+
+        Ok(tag.into())
+    }
+
+    /// Remove a tag, it is taken off every channel which has it. Only admins can do this.
+    async fn remove<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the tag.")] id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_admin(ctx).await?;
+
+        let removed = sqlx::query!("DELETE FROM tags WHERE id = $1", id)
+            .execute(&*global.db)
+            .await
+            .map_err_gql("Failed to remove tag")?
+            .rows_affected()
+            > 0;
+
+        if !removed {
+            return Err(GqlError::NotFound
+                .with_message("Tag not found")
+                .with_field(vec!["id"]));
+        }
+
+        Ok(true)
+    }
+}
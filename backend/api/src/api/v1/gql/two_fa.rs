@@ -0,0 +1,441 @@
+use async_graphql::{Context, Object};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use super::auth;
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_elevated, authorize_user, check_ip_reputation};
+use super::models::passkey::{
+    Passkey, PasskeyAssertionInput, PasskeyAttestationInput, PasskeyChallenge,
+};
+use super::models::recovery_code::RecoveryCodeEvent;
+use super::models::session::Session;
+use crate::api::v1::jwt::JwtState;
+use crate::database::{passkey, passkey_challenge, recovery_code, recovery_code_event, session};
+use crate::global::passkey::PasskeyError;
+
+const MAX_PASSKEYS: i64 = 10;
+
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Turns a failed passkey check into the error shown to the user.
+pub fn to_gql<T>(result: std::result::Result<T, PasskeyError>) -> Result<T> {
+    match result {
+        Ok(value) => Ok(value),
+        Err(PasskeyError::Invalid(message)) => Err(GqlError::InvalidInput
+            .with_message(message)
+            .with_field(vec!["credential"])),
+        Err(PasskeyError::ChallengeNotFound) => Err(GqlError::InvalidInput
+            .with_message("Challenge not found or expired")
+            .with_field(vec!["challengeId"])),
+        Err(PasskeyError::Database(e)) => Err(e).map_err_gql("Failed to check passkey"),
+    }
+}
+
+async fn credential_ids(ctx: &Context<'_>, user_id: Uuid) -> Result<Vec<String>> {
+    let global = ctx.get_global();
+
+    sqlx::query_scalar!(
+        "SELECT credential_id FROM passkeys WHERE user_id = $1 ORDER BY created_at",
+        user_id,
+    )
+    .fetch_all(&*global.db)
+    .await
+    .map_err_gql("Failed to fetch passkeys")
+}
+
+#[derive(Default)]
+pub struct TwoFaQuery;
+
+#[Object]
+/// The query object for passkeys.
+impl TwoFaQuery {
+    /// Get the passkeys of the logged in user, the first one registered first.
+    async fn passkeys<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<Passkey>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let passkeys = sqlx::query_as!(
+            passkey::Model,
+            "SELECT * FROM passkeys WHERE user_id = $1 ORDER BY created_at",
+            session.user_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch passkeys")?;
+
+        Ok(passkeys.into_iter().map(Passkey::from).collect())
+    }
+
+    /// Whether logging in with the password of the logged in user also needs a passkey.
+    async fn passkey_required<'ctx>(&self, ctx: &Context<'_>) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        sqlx::query_scalar!(
+            "SELECT passkey_required FROM users WHERE id = $1",
+            session.user_id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch user")
+    }
+
+    /// The number of recovery codes of the logged in user which were not used yet.
+    async fn recovery_codes_remaining<'ctx>(&self, ctx: &Context<'_>) -> Result<i64> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM recovery_codes WHERE user_id = $1 AND used_at IS NULL"#,
+            session.user_id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to count recovery codes")
+    }
+
+    /// Get when recovery codes of the logged in user were regenerated or used, the last 20 first.
+    async fn recovery_code_events<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<RecoveryCodeEvent>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let events = sqlx::query_as!(
+            recovery_code_event::Model,
+            "SELECT * FROM recovery_code_events WHERE user_id = $1 ORDER BY created_at DESC LIMIT 20",
+            session.user_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch recovery code events")?;
+
+        Ok(events.into_iter().map(RecoveryCodeEvent::from).collect())
+    }
+}
+
+#[derive(Default)]
+pub struct TwoFaMutation;
+
+#[Object]
+/// The mutation object for passkeys. They can be used as a second factor after the password, or to log in without one.
+impl TwoFaMutation {
+    /// Start registering a passkey for the logged in user, pass the challenge to `navigator.credentials.create`.
+    /// The logged in user has to have reauthenticated recently.
+    async fn start_passkey_registration<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<PasskeyChallenge> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_elevated(ctx).await?;
+
+        let credential_ids = credential_ids(ctx, session.user_id).await?;
+        if credential_ids.len() as i64 >= MAX_PASSKEYS {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!("You can have at most {} passkeys", MAX_PASSKEYS)));
+        }
+
+        let challenge = global
+            .create_passkey_challenge(Some(session.user_id), passkey_challenge::Kind::Registration)
+            .await
+            .map_err_gql("Failed to create challenge")?;
+
+        Ok(PasskeyChallenge::new(
+            challenge,
+            &global.config.passkeys.rp_id,
+            credential_ids,
+        ))
+    }
+
+    /// Finish registering a passkey with the response of the authenticator.
+    /// The logged in user has to have reauthenticated recently.
+    async fn finish_passkey_registration<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the registration challenge.")] challenge_id: Uuid,
+        #[graphql(desc = "A name to recognize the passkey by, at most 64 characters.")]
+        name: String,
+        #[graphql(desc = "The response of the authenticator.")] credential: PasskeyAttestationInput,
+    ) -> Result<Passkey> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_elevated(ctx).await?;
+
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > 64 {
+            return Err(GqlError::InvalidInput
+                .with_message("Name must be between 1 and 64 characters")
+                .with_field(vec!["name"]));
+        }
+
+        let passkey = to_gql(
+            global
+                .register_passkey(session.user_id, challenge_id, name, &credential.into())
+                .await,
+        )?;
+
+        Ok(passkey.into())
+    }
+
+    /// Remove a passkey of the logged in user. Removing the last one also stops requiring a passkey to log in.
+    /// The logged in user has to have reauthenticated recently.
+    async fn remove_passkey<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the passkey.")] id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_elevated(ctx).await?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to remove passkey")?;
+
+        let removed = sqlx::query!(
+            "DELETE FROM passkeys WHERE id = $1 AND user_id = $2",
+            id,
+            session.user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to remove passkey")?
+        .rows_affected()
+            > 0;
+
+        sqlx::query!(
+            "UPDATE users SET passkey_required = FALSE WHERE id = $1 AND NOT EXISTS (SELECT 1 FROM passkeys WHERE user_id = $1)",
+            session.user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to update user")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to commit transaction")?;
+
+        Ok(removed)
+    }
+
+    /// Set whether logging in with the password of the logged in user also needs a passkey.
+    /// The logged in user has to have reauthenticated recently.
+    async fn set_passkey_required<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Whether a passkey is required.")] required: bool,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_elevated(ctx).await?;
+
+        // Without a passkey the user could not log in anymore.
+        let updated = sqlx::query!(
+            "UPDATE users SET passkey_required = $2 WHERE id = $1 AND (NOT $2 OR EXISTS (SELECT 1 FROM passkeys WHERE user_id = $1))",
+            session.user_id,
+            required,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to update user")?
+        .rows_affected();
+
+        if updated == 0 {
+            return Err(GqlError::InvalidInput
+                .with_message("Register a passkey before requiring one")
+                .with_field(vec!["required"]));
+        }
+
+        Ok(required)
+    }
+
+    /// Generate new recovery codes for the logged in user, the old ones stop working. Each code logs in once instead of a passkey.
+    /// The codes are only shown now. The logged in user has to have reauthenticated recently.
+    async fn regenerate_recovery_codes<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        let (session, _) = authorize_elevated(ctx).await?;
+
+        let codes = (0..RECOVERY_CODE_COUNT)
+            .map(|_| recovery_code::generate_code())
+            .collect::<Vec<_>>();
+        let hashes = codes
+            .iter()
+            .map(|code| recovery_code::hash_code(code))
+            .collect::<Vec<_>>();
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to regenerate recovery codes")?;
+
+        sqlx::query!(
+            "DELETE FROM recovery_codes WHERE user_id = $1",
+            session.user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to regenerate recovery codes")?;
+
+        sqlx::query!(
+            "INSERT INTO recovery_codes (user_id, code_hash) SELECT $1, UNNEST($2::text[]) ON CONFLICT DO NOTHING",
+            session.user_id,
+            &hashes,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to regenerate recovery codes")?;
+
+        sqlx::query!(
+            "INSERT INTO recovery_code_events (user_id, action, ip_address, user_agent) VALUES ($1, $2, $3, $4)",
+            session.user_id,
+            i64::from(recovery_code_event::Action::Regenerated),
+            request_context
+                .client_ip()
+                .map(|ip| ip.to_string())
+                .unwrap_or_default(),
+            request_context
+                .user_agent()
+                .unwrap_or_default()
+                .chars()
+                .take(512)
+                .collect::<String>(),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to regenerate recovery codes")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to commit transaction")?;
+
+        Ok(codes)
+    }
+
+    /// Start logging in with a passkey, pass the challenge to `navigator.credentials.get`.
+    /// With a username only that user's passkeys are allowed, which is needed when the passkey is the second factor.
+    /// Without one any passkey stored on the device can be picked.
+    async fn start_passkey_login<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The username of the user.")] username: Option<String>,
+    ) -> Result<PasskeyChallenge> {
+        let global = ctx.get_global();
+
+        check_ip_reputation(ctx).await?;
+
+        let user_id = match username {
+            Some(username) => global
+                .user_by_username_loader
+                .load_one(username.to_lowercase())
+                .await
+                .map_err_gql("Failed to fetch user")?
+                .map(|user| user.id),
+            None => None,
+        };
+
+        // Unknown usernames get a challenge without passkeys, like users without any, so accounts can't be discovered.
+        let credential_ids = match user_id {
+            Some(user_id) => credential_ids(ctx, user_id).await?,
+            None => Vec::new(),
+        };
+
+        let challenge = global
+            .create_passkey_challenge(user_id, passkey_challenge::Kind::Login)
+            .await
+            .map_err_gql("Failed to create challenge")?;
+
+        Ok(PasskeyChallenge::new(
+            challenge,
+            &global.config.passkeys.rp_id,
+            credential_ids,
+        ))
+    }
+
+    /// Login with a passkey instead of a password. The authenticator has to verify the user, with a PIN or biometrics.
+    /// If via websocket this will authenticate the websocket connection.
+    async fn login_with_passkey<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The response of the authenticator.")] credential: PasskeyAssertionInput,
+        #[graphql(
+            desc = "The duration of the session in seconds. If not specified it will be 7 days."
+        )]
+        validity: Option<u32>,
+        #[graphql(
+            desc = "Setting this to false will make it so logging in does not authenticate the connection."
+        )]
+        update_context: Option<bool>,
+    ) -> Result<Session> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        check_ip_reputation(ctx).await?;
+
+        let passkey = to_gql(
+            global
+                .authenticate_passkey(credential.challenge_id, None, &(&credential).into())
+                .await,
+        )?;
+
+        let user = global
+            .user_by_id_loader
+            .load_one(passkey.user_id)
+            .await
+            .map_err_gql("Failed to fetch user")?
+            .ok_or_else(|| GqlError::NotFound.with_message("User not found"))?;
+
+        let login_duration = validity.unwrap_or(60 * 60 * 24 * 7); // 7 days
+        let expires_at = Utc::now() + Duration::seconds(login_duration as i64);
+
+        let session = session::create(
+            &*global.db,
+            user.id,
+            expires_at,
+            request_context.client_ip(),
+            request_context.user_agent(),
+            true,
+        )
+        .await
+        .map_err_gql("Failed to create session")?;
+
+        let token = JwtState::from(session.clone())
+            .serialize(global)
+            .ok_or((GqlError::InternalServerError, "Failed to serialize JWT"))?;
+
+        let permissions = global
+            .user_permisions_by_id_loader
+            .load_one(user.id)
+            .await
+            .map_err_gql("Failed to fetch user permissions")?
+            .unwrap_or_default();
+
+        // We need to update the request context with the new session
+        if update_context.unwrap_or(true) {
+            request_context.set_session(Some((session.clone(), permissions)));
+            auth::set_session_cookies(ctx, &token, session.expires_at);
+        }
+
+        Ok(Session {
+            id: session.id,
+            token,
+            user_id: session.user_id,
+            expires_at: session.expires_at.into(),
+            last_used_at: session.last_used_at.into(),
+            created_at: session.created_at.into(),
+            _user: Some(user.into()),
+        })
+    }
+}
@@ -0,0 +1,1294 @@
+use async_graphql::{Context, Object};
+use chrono::{Duration, Utc};
+use common::database::ErrorKind;
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_elevated, authorize_user};
+use super::models::blocked_user::BlockedUser;
+use super::models::data_export::DataExport;
+use super::models::external_account::{ExternalAccount, ExternalProvider};
+use super::models::muted_channel::MutedChannel;
+use super::models::notification_settings::{NotificationCategory, NotificationSettings};
+use super::models::profile::ProfileInput;
+use super::models::session::ActiveSession;
+use super::models::social_link::SocialLinkInput;
+use super::models::user::User;
+use super::pagination::{page_limit, Cursor};
+use crate::database::{
+    channel_event, channel_user, data_export, email_change, external_account, global_role,
+    login_link, notification_settings, session, user, user_block, user_social_link,
+    username_history,
+};
+use crate::global::{
+    display_color::{self, DisplayColorError},
+    profile::ProfileChange,
+    GlobalState,
+};
+use crate::pb;
+
+const DEFAULT_BLOCKED_USERS_LIMIT: u32 = 50;
+const MAX_BLOCKED_USERS_LIMIT: u32 = 100;
+const DEFAULT_MUTED_CHANNELS_LIMIT: u32 = 50;
+const MAX_MUTED_CHANNELS_LIMIT: u32 = 100;
+
+/// Tells the open chats of the user about the block, so they hide or show the messages right away.
+/// The block is already saved, so failing to publish only logs.
+async fn publish_block(global: &GlobalState, user_id: Uuid, blocked_id: Uuid, blocked: bool) {
+    let res = global
+        .publish_event(
+            user_id,
+            &pb::scuffle::events::UserBlockChanged {
+                blocked_id: blocked_id.to_string(),
+                blocked,
+            },
+        )
+        .await;
+
+    if let Err(e) = res {
+        tracing::error!("failed to publish block of user {}: {}", user_id, e);
+    }
+}
+
+/// Turns a refused display color change into the error shown to the user.
+fn display_color_result<T>(
+    result: std::result::Result<T, DisplayColorError>,
+    field_prefix: &[&str],
+    failed: &'static str,
+) -> Result<T> {
+    let field = |field: &'static str| {
+        let mut path = field_prefix.to_vec();
+        path.push(field);
+        path
+    };
+
+    match result {
+        Ok(value) => Ok(value),
+        Err(DisplayColorError::Invalid { field: f, message }) => Err(GqlError::InvalidInput
+            .with_message(message)
+            .with_field(field(f))),
+        Err(DisplayColorError::NotAllowed { field: f, message }) => Err(GqlError::Unauthorized
+            .with_message(message)
+            .with_field(field(f))),
+        Err(DisplayColorError::RateLimited(retry_at)) => {
+            Err(GqlError::InvalidInput.with_message(&format!(
+                "You are changing your color too often, try again in {} seconds",
+                (retry_at - Utc::now()).num_seconds().max(1)
+            )))
+        }
+        Err(DisplayColorError::Database(e)) => Err(e).map_err_gql(failed),
+    }
+}
+
+/// Checks the links for a profile, returns their labels and urls as they are stored.
+fn check_social_links(
+    links: Vec<SocialLinkInput>,
+    field: &[&str],
+) -> Result<Vec<(String, String)>> {
+    if links.len() > user_social_link::MAX_LINKS {
+        return Err(GqlError::InvalidInput
+            .with_message(&format!(
+                "You can have at most {} links",
+                user_social_link::MAX_LINKS
+            ))
+            .with_field(field.to_vec()));
+    }
+
+    for (i, link) in links.iter().enumerate() {
+        let i = i.to_string();
+        let path = |name: &'static str| {
+            let mut path = field.to_vec();
+            path.extend([i.as_str(), name]);
+            path
+        };
+
+        user_social_link::validate_label(&link.label).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(path("label"))
+        })?;
+
+        user_social_link::validate_url(&link.url).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(path("url"))
+        })?;
+    }
+
+    Ok(links
+        .into_iter()
+        .map(|l| (l.label.trim().to_string(), l.url))
+        .collect())
+}
+
+#[derive(Default)]
+pub struct UserQuery;
+
+#[Object]
+/// The query object for the logged in user.
+impl UserQuery {
+    /// Get the users the logged in user blocked, the last one blocked first.
+    /// To fetch the next page pass the `cursor` of the last user as `after`.
+    async fn blocked_users<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Only return users after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(desc = "The maximum number of users to return. Defaults to 50, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<BlockedUser>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let limit = page_limit(limit, DEFAULT_BLOCKED_USERS_LIMIT, MAX_BLOCKED_USERS_LIMIT)?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let blocks = sqlx::query_as!(
+            user_block::Model,
+            "SELECT * FROM user_blocks WHERE user_id = $1 AND ($2::timestamptz IS NULL OR (created_at, blocked_id) < ($2, $3::uuid)) ORDER BY created_at DESC, blocked_id DESC LIMIT $4",
+            session.user_id,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch blocked users")?;
+
+        Ok(blocks.into_iter().map(BlockedUser::from).collect())
+    }
+
+    /// Get the channels the logged in user muted, the last one muted first.
+    /// To fetch the next page pass the `cursor` of the last channel as `after`.
+    async fn muted_channels<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Only return channels after this cursor, used for pagination.")]
+        after: Option<Cursor>,
+        #[graphql(desc = "The maximum number of channels to return. Defaults to 50, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<MutedChannel>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let limit = page_limit(
+            limit,
+            DEFAULT_MUTED_CHANNELS_LIMIT,
+            MAX_MUTED_CHANNELS_LIMIT,
+        )?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let mutes = sqlx::query_as!(
+            channel_user::Model,
+            "SELECT * FROM channel_users WHERE user_id = $1 AND muted_at IS NOT NULL AND ($2::timestamptz IS NULL OR (muted_at, channel_id) < ($2, $3::uuid)) ORDER BY muted_at DESC, channel_id DESC LIMIT $4",
+            session.user_id,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch muted channels")?;
+
+        Ok(mutes.into_iter().map(MutedChannel::from).collect())
+    }
+
+    /// Get the sessions the logged in user is logged in with, the last one used first.
+    async fn sessions<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<ActiveSession>> {
+        let global = ctx.get_global();
+
+        let (current, _) = authorize_user(ctx).await?;
+
+        let sessions = sqlx::query_as!(
+            session::Model,
+            "SELECT * FROM sessions WHERE user_id = $1 AND invalidated_at IS NULL AND expires_at > NOW() ORDER BY last_used_at DESC, id DESC",
+            current.user_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch sessions")?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| ActiveSession::new(session, current.id))
+            .collect())
+    }
+
+    /// Get the data exports of the logged in user, the last one requested first.
+    async fn data_exports<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<DataExport>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let exports = sqlx::query_as!(
+            data_export::Model,
+            "SELECT * FROM data_exports WHERE user_id = $1 ORDER BY created_at DESC LIMIT 10",
+            session.user_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch data exports")?;
+
+        Ok(exports.into_iter().map(DataExport::from).collect())
+    }
+
+    /// Get the OAuth accounts the logged in user can log in with.
+    async fn external_accounts<'ctx>(&self, ctx: &Context<'_>) -> Result<Vec<ExternalAccount>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let accounts = sqlx::query_as!(
+            external_account::Model,
+            "SELECT * FROM external_accounts WHERE user_id = $1 ORDER BY created_at",
+            session.user_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch external accounts")?;
+
+        Ok(accounts.into_iter().map(ExternalAccount::from).collect())
+    }
+
+    /// Get the email the logged in user asked to change to, null if no change waits for confirmation.
+    async fn pending_email<'ctx>(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let change = sqlx::query_as!(
+            email_change::Model,
+            "SELECT * FROM email_changes WHERE user_id = $1 ORDER BY created_at DESC LIMIT 1",
+            session.user_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch email change")?;
+
+        change
+            .filter(|change| change.is_pending(Utc::now()))
+            .map(|change| global.decrypt_pii(&change.new_email))
+            .transpose()
+            .map_err_gql("Failed to decrypt email")
+    }
+
+    /// Get the regions users can keep their data in.
+    async fn data_regions<'ctx>(&self, ctx: &Context<'_>) -> Vec<String> {
+        ctx.get_global()
+            .data_regions()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct UserMutation;
+
+#[Object]
+/// The mutation object for the logged in user.
+impl UserMutation {
+    /// Set the color of the display name of the logged in user. Palette colors have a variant for each theme,
+    /// hex colors can be given a separate dark variant. Hex colors and gradients need their own permissions,
+    /// and colors can only be changed so often.
+    async fn set_display_color<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(
+            desc = "A palette color like `blue` or a hex color like `#9146ff`. The color is removed if not set."
+        )]
+        color: Option<String>,
+        #[graphql(desc = "The hex color to use on dark themes, only for hex colors.")]
+        dark_color: Option<String>,
+        #[graphql(desc = "The color the gradient ends in.")] gradient_end: Option<String>,
+    ) -> Result<User> {
+        let global = ctx.get_global();
+
+        let (session, permissions) = authorize_user(ctx).await?;
+
+        let result = global
+            .set_display_color(
+                session.user_id,
+                permissions.permissions,
+                color.as_deref(),
+                dark_color.as_deref(),
+                gradient_end.as_deref(),
+            )
+            .await;
+
+        let user = display_color_result(result, &[], "Failed to update display color")?;
+
+        Ok(user.into())
+    }
+
+    /// Change the username of the logged in user. The old username is kept in the history of the user,
+    /// and it can only be changed again after a cooldown. Changing only the case of the username
+    /// changes the display name and is always allowed.
+    async fn set_username<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The new username, the case is kept for the display name.")]
+        username: String,
+    ) -> Result<User> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let display_name = username.clone();
+        let username = username.to_lowercase();
+
+        user::validate_username(&username).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["username"])
+        })?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to update username")?;
+
+        // Locking the user keeps two concurrent changes from both passing the cooldown.
+        let current = sqlx::query_as!(
+            user::Model,
+            "SELECT * FROM users WHERE id = $1 FOR UPDATE",
+            session.user_id,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to fetch user")?;
+
+        if current.username == username && current.display_name == display_name {
+            return Ok(current.into());
+        }
+
+        if current.username != username {
+            let last_change = username_history::last_change(&mut *tx, current.id)
+                .await
+                .map_err_gql("Failed to fetch username history")?;
+
+            let retry_at = last_change
+                .map(|t| t + Duration::seconds(global.config.usernames.cooldown as i64))
+                .filter(|t| *t > Utc::now());
+
+            if let Some(retry_at) = retry_at {
+                let seconds = (retry_at - Utc::now()).num_seconds().max(1);
+                return Err(GqlError::InvalidInput
+                    .with_message(&format!(
+                        "You changed your username recently, try again in {} days",
+                        (seconds + 24 * 60 * 60 - 1) / (24 * 60 * 60)
+                    ))
+                    .with_field(vec!["username"]));
+            }
+
+            sqlx::query!(
+                "INSERT INTO username_history (user_id, username) VALUES ($1, $2)",
+                current.id,
+                current.username,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err_gql("Failed to update username history")?;
+        }
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET username = $2, display_name = $3 WHERE id = $1 RETURNING *",
+            current.id,
+            username,
+            display_name,
+        )
+        .fetch_one(&mut *tx)
+        .await;
+
+        let user = match user {
+            Err(e) if ErrorKind::of(&e) == ErrorKind::UniqueViolation => {
+                return Err(GqlError::InvalidInput
+                    .with_message("Username already taken")
+                    .with_field(vec!["username"]));
+            }
+            user => user.map_err_gql("Failed to update username")?,
+        };
+
+        tx.commit().await.map_err_gql("Failed to update username")?;
+
+        // Chat and the channel pages show the name from this event, the change is saved either way.
+        if let Err(e) = global
+            .publish_event(
+                user.id,
+                &pb::scuffle::events::UserDisplayName {
+                    username: Some(user.username.clone()),
+                    display_name: Some(user.display_name.clone()),
+                },
+            )
+            .await
+        {
+            tracing::error!("failed to publish username of user {}: {}", user.id, e);
+        }
+
+        Ok(user.into())
+    }
+
+    /// Set the bio shown on the profile page of the logged in user. The bio is markdown,
+    /// raw HTML and links to anything but http(s) and mailto urls are removed.
+    async fn set_bio<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The bio, at most 1000 characters. The bio is removed if not set.")]
+        bio: Option<String>,
+    ) -> Result<User> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let bio = bio.unwrap_or_default();
+
+        user::validate_bio(&bio).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["bio"])
+        })?;
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET bio = $2 WHERE id = $1 RETURNING *",
+            session.user_id,
+            user::sanitize_bio(&bio),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to update bio")?;
+
+        if let Err(e) = global
+            .publish_event(
+                user.id,
+                &pb::scuffle::events::UserBioUpdated {
+                    bio: user.bio.clone(),
+                },
+            )
+            .await
+        {
+            tracing::error!("failed to publish bio of user {}: {}", user.id, e);
+        }
+
+        Ok(user.into())
+    }
+
+    /// Set the language and time zone of the logged in user, emails and times are localized with them.
+    /// Unset arguments are left as they are.
+    async fn set_locale<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "A BCP-47 language tag like `en-US`.")] locale: Option<String>,
+        #[graphql(desc = "An IANA time zone like `Europe/Berlin`.")] timezone: Option<String>,
+    ) -> Result<User> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        if let Some(locale) = &locale {
+            user::validate_locale(locale).map_err(|e| {
+                GqlError::InvalidInput
+                    .with_message(e)
+                    .with_field(vec!["locale"])
+            })?;
+        }
+
+        if let Some(timezone) = &timezone {
+            user::validate_timezone(timezone).map_err(|e| {
+                GqlError::InvalidInput
+                    .with_message(e)
+                    .with_field(vec!["timezone"])
+            })?;
+        }
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET locale = COALESCE($2, locale), timezone = COALESCE($3, timezone) WHERE id = $1 RETURNING *",
+            session.user_id,
+            locale,
+            timezone,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to update locale")?;
+
+        Ok(user.into())
+    }
+
+    /// Set the region the data of the logged in user is kept in. Pass an empty region to use the default region.
+    /// Exports and recordings made before the change stay in the region they were made in.
+    async fn set_data_region<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "One of `dataRegions`.")] region: String,
+    ) -> Result<User> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        if !region.is_empty() && !global.config.residency.regions.contains_key(&region) {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!(
+                    "Unknown region, valid regions are: {}",
+                    global.data_regions().join(", ")
+                ))
+                .with_field(vec!["region"]));
+        }
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET data_region = $2 WHERE id = $1 RETURNING *",
+            session.user_id,
+            region,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to update data region")?;
+
+        Ok(user.into())
+    }
+
+    /// Block a user. Follows, friendships and friend requests between the two users are removed and they can't follow each other,
+    /// the user loses their VIP status in the chat of the logged in user and their chat messages are hidden from them.
+    async fn block_user<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the user to block.")] user_id: Uuid,
+    ) -> Result<BlockedUser> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        if user_id == session.user_id {
+            return Err(GqlError::InvalidInput
+                .with_message("You can't block yourself")
+                .with_field(vec!["userId"]));
+        }
+
+        global
+            .user_by_id_loader
+            .load_one(user_id)
+            .await
+            .map_err_gql("Failed to fetch user")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("User not found")
+                    .with_field(vec!["userId"])
+            })?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to block user")?;
+
+        // Blocking again keeps the time of the first block.
+        let block = sqlx::query_as!(
+            user_block::Model,
+            "INSERT INTO user_blocks (user_id, blocked_id) VALUES ($1, $2) ON CONFLICT (user_id, blocked_id) DO UPDATE SET user_id = EXCLUDED.user_id RETURNING *",
+            session.user_id,
+            user_id,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to block user")?;
+
+        sqlx::query!(
+            "DELETE FROM channel_events WHERE kind = $3 AND ((channel_id = $1 AND user_id = $2) OR (channel_id = $2 AND user_id = $1))",
+            session.user_id,
+            user_id,
+            i64::from(channel_event::Kind::Follow),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to remove follows")?;
+
+        sqlx::query!(
+            "DELETE FROM chat_vips WHERE channel_id = $1 AND user_id = $2",
+            session.user_id,
+            user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to remove VIP")?;
+
+        sqlx::query!(
+            "DELETE FROM friends WHERE (user_id = $1 AND friend_id = $2) OR (user_id = $2 AND friend_id = $1)",
+            session.user_id,
+            user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to remove friend")?;
+
+        sqlx::query!(
+            "DELETE FROM friend_requests WHERE (user_id = $1 AND friend_id = $2) OR (user_id = $2 AND friend_id = $1)",
+            session.user_id,
+            user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to remove friend requests")?;
+
+        tx.commit().await.map_err_gql("Failed to block user")?;
+
+        publish_block(global, session.user_id, user_id, true).await;
+
+        Ok(block.into())
+    }
+
+    /// Unblock a user. Returns false if the user was not blocked.
+    async fn unblock_user<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the user to unblock.")] user_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let removed = sqlx::query!(
+            "DELETE FROM user_blocks WHERE user_id = $1 AND blocked_id = $2",
+            session.user_id,
+            user_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to unblock user")?
+        .rows_affected()
+            > 0;
+
+        if removed {
+            publish_block(global, session.user_id, user_id, false).await;
+        }
+
+        Ok(removed)
+    }
+
+    /// Mute a channel. The logged in user keeps following it, but it is left out of their go-live emails and digests.
+    async fn mute_channel<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel to mute.")] channel_id: Uuid,
+    ) -> Result<MutedChannel> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        if channel_id == session.user_id {
+            return Err(GqlError::InvalidInput
+                .with_message("You can't mute your own channel")
+                .with_field(vec!["channelId"]));
+        }
+
+        global
+            .user_by_id_loader
+            .load_one(channel_id)
+            .await
+            .map_err_gql("Failed to fetch channel")?
+            .ok_or_else(|| {
+                GqlError::NotFound
+                    .with_message("Channel not found")
+                    .with_field(vec!["channelId"])
+            })?;
+
+        // Muting again keeps the time of the first mute.
+        let mute = sqlx::query_as!(
+            channel_user::Model,
+            "INSERT INTO channel_users (channel_id, user_id, muted_at) VALUES ($1, $2, NOW()) ON CONFLICT (channel_id, user_id) DO UPDATE SET muted_at = COALESCE(channel_users.muted_at, EXCLUDED.muted_at) RETURNING *",
+            channel_id,
+            session.user_id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to mute channel")?;
+
+        Ok(mute.into())
+    }
+
+    /// Unmute a channel. Returns false if the channel was not muted.
+    async fn unmute_channel<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel to unmute.")] channel_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        Ok(sqlx::query!(
+            "UPDATE channel_users SET muted_at = NULL WHERE channel_id = $1 AND user_id = $2 AND muted_at IS NOT NULL",
+            channel_id,
+            session.user_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to unmute channel")?
+        .rows_affected()
+            > 0)
+    }
+
+    /// Turn the go-live notifications of a followed channel on or off, the logged in user keeps following it.
+    /// Returns the new setting.
+    async fn set_follow_notifications<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the followed channel.")] channel_id: Uuid,
+        #[graphql(desc = "Whether to notify the user when the channel goes live.")] enabled: bool,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let follows = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM channel_events WHERE channel_id = $1 AND user_id = $2 AND kind = $3) AS "follows!""#,
+            channel_id,
+            session.user_id,
+            i64::from(channel_event::Kind::Follow),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch follow")?;
+
+        if !follows {
+            return Err(GqlError::InvalidInput
+                .with_message("You don't follow this channel")
+                .with_field(vec!["channelId"]));
+        }
+
+        let setting = sqlx::query_scalar!(
+            "INSERT INTO channel_users (channel_id, user_id, live_notifications) VALUES ($1, $2, $3) ON CONFLICT (channel_id, user_id) DO UPDATE SET live_notifications = EXCLUDED.live_notifications RETURNING live_notifications",
+            channel_id,
+            session.user_id,
+            enabled,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to update notifications")?;
+
+        Ok(setting)
+    }
+
+    /// Log out a session of the logged in user. Returns false if the session was already logged out.
+    async fn revoke_session<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the session.")] id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+        let request_context = ctx.get_session();
+
+        let (current, _) = authorize_user(ctx).await?;
+
+        let revoked = sqlx::query!(
+            "UPDATE sessions SET invalidated_at = NOW() WHERE id = $1 AND user_id = $2 AND invalidated_at IS NULL",
+            id,
+            current.user_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to revoke session")?
+        .rows_affected()
+            > 0;
+
+        if revoked {
+            global
+                .publish_sessions_revoked(current.user_id, &[id])
+                .await;
+        }
+
+        if id == current.id {
+            request_context.set_session(None);
+        }
+
+        Ok(revoked)
+    }
+
+    /// Log out every session of the logged in user except the one the request was made with.
+    /// Returns the number of sessions logged out.
+    async fn revoke_other_sessions<'ctx>(&self, ctx: &Context<'_>) -> Result<i64> {
+        let global = ctx.get_global();
+
+        let (current, _) = authorize_user(ctx).await?;
+
+        let revoked = session::revoke_all(&*global.db, current.user_id, Some(current.id))
+            .await
+            .map_err_gql("Failed to revoke sessions")?;
+
+        global
+            .publish_sessions_revoked(current.user_id, &revoked)
+            .await;
+
+        Ok(revoked.len() as i64)
+    }
+
+    /// Turn a category of notifications of the logged in user on or off.
+    async fn set_notification_setting<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The category to change.")] category: NotificationCategory,
+        #[graphql(desc = "Whether the user gets notifications of the category.")] enabled: bool,
+    ) -> Result<NotificationSettings> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let settings =
+            notification_settings::set(&global.db, session.user_id, category.into(), enabled)
+                .await
+                .map_err_gql("Failed to update notification settings")?;
+
+        Ok(settings.into())
+    }
+
+    /// Replace the links on the profile of the logged in user. The links are shown in the order given.
+    async fn set_social_links<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(
+            desc = "The links to show on the profile, at most 5. An empty list removes all links."
+        )]
+        links: Vec<SocialLinkInput>,
+    ) -> Result<User> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let links = check_social_links(links, &["links"])?;
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to update social links")?;
+
+        user_social_link::replace(&mut tx, session.user_id, &links)
+            .await
+            .map_err_gql("Failed to update social links")?;
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "SELECT * FROM users WHERE id = $1",
+            session.user_id,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err_gql("Failed to fetch user")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to update social links")?;
+
+        Ok(user.into())
+    }
+
+    /// Change several profile fields of the logged in user at once. Every field is checked like by its own mutation,
+    /// and either all of them change or none does. Unset fields are left as they are.
+    async fn update_profile<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The fields to change.")] input: ProfileInput,
+    ) -> Result<User> {
+        let global = ctx.get_global();
+
+        let (session, permissions) = authorize_user(ctx).await?;
+
+        let display_color = input
+            .display_color
+            .map(|c| {
+                display_color::stored_change(
+                    permissions.permissions,
+                    c.color.as_deref(),
+                    c.dark_color.as_deref(),
+                    c.gradient_end.as_deref(),
+                )
+            })
+            .transpose();
+        let display_color = display_color_result(
+            display_color,
+            &["input", "displayColor"],
+            "Failed to update profile",
+        )?;
+
+        let bio = match input.bio {
+            Some(bio) => {
+                user::validate_bio(&bio).map_err(|e| {
+                    GqlError::InvalidInput
+                        .with_message(e)
+                        .with_field(vec!["input", "bio"])
+                })?;
+                Some(user::sanitize_bio(&bio))
+            }
+            None => None,
+        };
+
+        let social_links = input
+            .social_links
+            .map(|links| check_social_links(links, &["input", "socialLinks"]))
+            .transpose()?;
+
+        if let Some(locale) = &input.locale {
+            user::validate_locale(locale).map_err(|e| {
+                GqlError::InvalidInput
+                    .with_message(e)
+                    .with_field(vec!["input", "locale"])
+            })?;
+        }
+
+        if let Some(timezone) = &input.timezone {
+            user::validate_timezone(timezone).map_err(|e| {
+                GqlError::InvalidInput
+                    .with_message(e)
+                    .with_field(vec!["input", "timezone"])
+            })?;
+        }
+
+        let change = ProfileChange {
+            display_color,
+            bio,
+            social_links,
+            locale: input.locale,
+            timezone: input.timezone,
+        };
+
+        let result = global
+            .update_profile(
+                session.user_id,
+                &change,
+                permissions
+                    .permissions
+                    .has_permission(global_role::Permission::DisplayNameGradient),
+            )
+            .await;
+
+        let user = display_color_result(
+            result,
+            &["input", "displayColor"],
+            "Failed to update profile",
+        )?;
+
+        Ok(user.into())
+    }
+
+    /// Set the profile picture of the logged in user. The image is sent to the image processor to be resized
+    /// and converted, the user keeps their old picture until it is done.
+    async fn set_profile_picture<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(
+            desc = "The url of the uploaded image, the profile picture is removed if not set."
+        )]
+        source_url: Option<String>,
+    ) -> Result<User> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        let Some(source_url) = source_url else {
+            let user = sqlx::query_as!(
+                user::Model,
+                "UPDATE users SET profile_image_url = '', profile_image_job_id = NULL, pending_profile_image_url = NULL WHERE id = $1 RETURNING *",
+                session.user_id,
+            )
+            .fetch_one(&*global.db)
+            .await
+            .map_err_gql("Failed to update profile picture")?;
+
+            return Ok(user.into());
+        };
+
+        if reqwest::Url::parse(&source_url)
+            .map(|url| url.scheme() != "https")
+            .unwrap_or(true)
+        {
+            return Err(GqlError::InvalidInput
+                .with_message("Source url must be a valid https url")
+                .with_field(vec!["sourceUrl"]));
+        }
+
+        // Every upload gets its own prefix, so the CDN never serves an older picture from its cache.
+        let (job_id, image_url) = global
+            .queue_image_job(
+                &source_url,
+                &format!("profile_pictures/{}/{}", session.user_id, Uuid::new_v4()),
+            )
+            .await
+            .map_err_gql("Failed to queue profile picture")?;
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET profile_image_job_id = $2, pending_profile_image_url = $3 WHERE id = $1 RETURNING *",
+            session.user_id,
+            job_id,
+            image_url,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to update profile picture")?;
+
+        Ok(user.into())
+    }
+
+    /// Request to change the email of the logged in user. The email only changes once the link sent to the new address
+    /// is opened, the old address gets a link to undo the change. Requesting another change replaces a pending one.
+    async fn request_email_change<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The new email.")] email: String,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+        let config = &global.config.email_changes;
+
+        let (session, _) = authorize_elevated(ctx).await?;
+
+        let email = email.trim().to_lowercase();
+        user::validate_email(&email).map_err(|e| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec!["email"])
+        })?;
+
+        let user = global
+            .user_by_id_loader
+            .load_one(session.user_id)
+            .await
+            .map_err_gql("Failed to fetch user")?
+            .ok_or(GqlError::NotFound.with_message("User not found"))?;
+
+        let old_email = global
+            .decrypt_pii(&user.email)
+            .map_err_gql("Failed to decrypt email")?;
+        if old_email == email {
+            return Err(GqlError::InvalidInput
+                .with_message("This is already your email")
+                .with_field(vec!["email"]));
+        }
+
+        let confirm_token = login_link::generate_token();
+        let revert_token = login_link::generate_token();
+
+        let mut tx = global
+            .db
+            .begin()
+            .await
+            .map_err_gql("Failed to request email change")?;
+
+        sqlx::query!(
+            "UPDATE email_changes SET cancelled_at = NOW() WHERE user_id = $1 AND confirmed_at IS NULL AND reverted_at IS NULL AND cancelled_at IS NULL",
+            session.user_id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to cancel email change")?;
+
+        // Encrypting the old email again keeps it with the current key, it is only read if the change is reverted.
+        sqlx::query!(
+            "INSERT INTO email_changes (user_id, old_email, new_email, new_email_hash, confirm_token_hash, revert_token_hash, expires_at, revert_expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            session.user_id,
+            global.encrypt_pii(&old_email).map_err_gql("Failed to encrypt email")?,
+            global.encrypt_pii(&email).map_err_gql("Failed to encrypt email")?,
+            global.email_hash(&email),
+            login_link::hash_token(&confirm_token),
+            login_link::hash_token(&revert_token),
+            Utc::now() + Duration::seconds(config.expiry as i64),
+            Utc::now() + Duration::seconds(config.revert_expiry as i64),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_gql("Failed to request email change")?;
+
+        tx.commit()
+            .await
+            .map_err_gql("Failed to commit transaction")?;
+
+        global
+            .send_email(
+                &email,
+                "Confirm your new email",
+                &format!(
+                    "Hi {},\n\nOpen this link to use this email for your account: {}?token={}\n\nIt expires in {} hours. Until then your account keeps using your old email.",
+                    user.display_name,
+                    config.confirm_url,
+                    confirm_token,
+                    config.expiry / 60 / 60,
+                ),
+            )
+            .await
+            .map_err_gql("Failed to send confirmation email")?;
+
+        global
+            .send_email(
+                &old_email,
+                "Your email is being changed",
+                &format!(
+                    "Hi {},\n\nSomeone asked to change the email of your account to {}. If this was not you, open this link to keep this email and log out everywhere: {}?token={}\n\nThe link works for {} days, also after the new email was confirmed.",
+                    user.display_name,
+                    email,
+                    config.revert_url,
+                    revert_token,
+                    config.revert_expiry / 60 / 60 / 24,
+                ),
+            )
+            .await
+            .map_err_gql("Failed to send email change notice")?;
+
+        Ok(true)
+    }
+
+    /// Request an archive of everything stored about the logged in user. The export runs in the background,
+    /// poll `dataExports` until it completed to get the download url. Exports can only be requested so often.
+    async fn request_data_export<'ctx>(&self, ctx: &Context<'_>) -> Result<DataExport> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_elevated(ctx).await?;
+
+        // Exports which stopped making progress, e.g. because the API restarted, only block new ones for the cooldown.
+        let blocked = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM data_exports WHERE user_id = $1 AND (created_at > NOW() - make_interval(secs => $2) OR (status IN ($3, $4) AND updated_at > NOW() - INTERVAL '10 minutes')))",
+            session.user_id,
+            global.config.data_exports.cooldown as f64,
+            i64::from(data_export::Status::Queued),
+            i64::from(data_export::Status::Running),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch data exports")?
+        .unwrap_or(false);
+
+        if blocked {
+            return Err(
+                GqlError::InvalidInput.with_message("You already requested a data export recently")
+            );
+        }
+
+        let export = sqlx::query_as!(
+            data_export::Model,
+            "INSERT INTO data_exports (user_id) VALUES ($1) RETURNING *",
+            session.user_id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to create data export")?;
+
+        if let Err(e) = global.queue_data_export(export.id).await {
+            sqlx::query!(
+                "UPDATE data_exports SET status = $2, error = $3, completed_at = NOW() WHERE id = $1",
+                export.id,
+                i64::from(data_export::Status::Failed),
+                "Failed to queue the export",
+            )
+            .execute(&*global.db)
+            .await
+            .map_err_gql("Failed to update data export")?;
+
+            return Err(e).map_err_gql("Failed to queue data export");
+        }
+
+        Ok(export.into())
+    }
+
+    /// Link an account at an OAuth provider to the logged in user, so they can log in with loginWithExternalAccount.
+    /// Pass the authorization code the provider redirected back with. Each provider can be linked once.
+    async fn link_external_account<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The provider the account is at.")] provider: ExternalProvider,
+        #[graphql(desc = "The authorization code the provider redirected back with.")] code: String,
+        #[graphql(desc = "The redirect uri the authorization was requested with.")]
+        redirect_uri: String,
+    ) -> Result<ExternalAccount> {
+        let global = ctx.get_global();
+        let provider = external_account::Provider::from(provider);
+
+        // A linked account can log in, so it is as sensitive as the password.
+        let (session, _) = authorize_elevated(ctx).await?;
+
+        if global.oauth_provider(provider).is_none() {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!(
+                    "Logging in with {} is not enabled",
+                    provider.display_name()
+                ))
+                .with_field(vec!["provider"]));
+        }
+
+        let linked = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM external_accounts WHERE user_id = $1 AND provider = $2)",
+            session.user_id,
+            i64::from(provider),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch external accounts")?
+        .unwrap_or(false);
+
+        if linked {
+            return Err(GqlError::InvalidInput
+                .with_message(&format!(
+                    "You already linked a {} account",
+                    provider.display_name()
+                ))
+                .with_field(vec!["provider"]));
+        }
+
+        let identity = global
+            .fetch_external_identity(provider, &code, &redirect_uri)
+            .await
+            .map_err_gql("Failed to fetch external account")?
+            .ok_or_else(|| {
+                GqlError::InvalidInput
+                    .with_message("Authorization code is invalid or expired")
+                    .with_field(vec!["code"])
+            })?;
+
+        let account = sqlx::query_as!(
+            external_account::Model,
+            "INSERT INTO external_accounts (user_id, provider, provider_user_id, provider_username) VALUES ($1, $2, $3, $4) RETURNING *",
+            session.user_id,
+            i64::from(provider),
+            identity.id,
+            identity.username,
+        )
+        .fetch_one(&*global.db)
+        .await;
+
+        match account {
+            Err(e) if ErrorKind::of(&e) == ErrorKind::UniqueViolation => {
+                Err(GqlError::InvalidInput.with_message(&format!(
+                    "This {} account is already linked to another user",
+                    provider.display_name()
+                )))
+            }
+            account => Ok(account
+                .map_err_gql("Failed to link external account")?
+                .into()),
+        }
+    }
+
+    /// Unlink the account at an OAuth provider from the logged in user. Returns false if none was linked.
+    async fn unlink_external_account<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The provider the account is at.")] provider: ExternalProvider,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_elevated(ctx).await?;
+
+        Ok(sqlx::query!(
+            "DELETE FROM external_accounts WHERE user_id = $1 AND provider = $2",
+            session.user_id,
+            i64::from(external_account::Provider::from(provider)),
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to unlink external account")?
+        .rows_affected()
+            > 0)
+    }
+}
@@ -0,0 +1,307 @@
+use async_graphql::{Context, Object};
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::{authorize_channel_owner, authorize_user};
+use super::models::viewer_queue::{ViewerQueue, ViewerQueueEntry, ViewerQueuePosition};
+use super::pagination::page_limit;
+use crate::database::{viewer_queue, viewer_queue_entry};
+use crate::global::{viewer_queue::JoinError, GlobalState};
+
+const DEFAULT_ENTRIES_LIMIT: u32 = 100;
+const MAX_ENTRIES_LIMIT: u32 = 1000;
+const MAX_POP_COUNT: u32 = 25;
+
+/// The first viewers of the queue of a channel, in the order they are called.
+async fn first_entries(
+    global: &GlobalState,
+    channel_id: Uuid,
+    limit: i64,
+) -> Result<Vec<ViewerQueueEntry>> {
+    let entries = sqlx::query_as!(
+        viewer_queue_entry::Model,
+        "SELECT * FROM viewer_queue_entries WHERE channel_id = $1 ORDER BY position, user_id LIMIT $2",
+        channel_id,
+        limit,
+    )
+    .fetch_all(&*global.db)
+    .await
+    .map_err_gql("Failed to fetch queue")?;
+
+    Ok(ViewerQueueEntry::list(entries, 1))
+}
+
+fn no_queue() -> GqlError {
+    GqlError::NotFound
+        .with_message("This channel has no queue")
+        .with_field(vec!["channelId"])
+}
+
+#[derive(Default)]
+pub struct ViewerQueueQuery;
+
+#[Object]
+/// The query object for viewer queues.
+impl ViewerQueueQuery {
+    /// Get the queue of a channel, null if it has none.
+    async fn current<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Option<ViewerQueue>> {
+        let global = ctx.get_global();
+
+        let queue = sqlx::query_as!(
+            viewer_queue::Model,
+            "SELECT * FROM viewer_queues WHERE channel_id = $1",
+            channel_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch queue")?;
+
+        Ok(queue.map(ViewerQueue::from))
+    }
+
+    /// Get the viewers waiting in the queue of a channel, first in line first. Only the broadcaster can see them.
+    async fn entries<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(
+            desc = "The maximum number of viewers to return. Defaults to 100, at most 1000."
+        )]
+        limit: Option<u32>,
+    ) -> Result<Vec<ViewerQueueEntry>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let limit = page_limit(limit, DEFAULT_ENTRIES_LIMIT, MAX_ENTRIES_LIMIT)?;
+
+        first_entries(global, channel_id, limit).await
+    }
+
+    /// Get where the logged in user is in the queue of a channel.
+    async fn position<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<ViewerQueuePosition> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        ViewerQueuePosition::load(global, channel_id, session.user_id, false).await
+    }
+}
+
+#[derive(Default)]
+pub struct ViewerQueueMutation;
+
+#[Object]
+/// The mutation object for viewer queues. Viewers join with the mutation or by typing `!join` in chat,
+/// and leave with `!leave`.
+impl ViewerQueueMutation {
+    /// Open the queue of a channel, creating it if it has none. Viewers already waiting keep their place.
+    /// Only the broadcaster can do this.
+    async fn open<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "What the viewers queue for.")] title: String,
+        #[graphql(
+            desc = "The most viewers who can wait at once, at most 1000. Unlimited if not set."
+        )]
+        max_size: Option<u32>,
+    ) -> Result<ViewerQueue> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        viewer_queue::validate(&title, max_size).map_err(|(field, e)| {
+            GqlError::InvalidInput
+                .with_message(e)
+                .with_field(vec![field])
+        })?;
+
+        let queue = sqlx::query_as!(
+            viewer_queue::Model,
+            "INSERT INTO viewer_queues (channel_id, title, max_size) VALUES ($1, $2, $3) ON CONFLICT (channel_id) DO UPDATE SET title = EXCLUDED.title, max_size = EXCLUDED.max_size, open = TRUE, updated_at = NOW() RETURNING *",
+            channel_id,
+            title.trim(),
+            max_size.map(|size| size as i32),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to open queue")?;
+
+        global.viewer_queue_changed(channel_id, &[]).await;
+
+        Ok(queue.into())
+    }
+
+    /// Stop viewers from joining the queue of a channel, the viewers waiting keep their place.
+    /// Only the broadcaster can do this.
+    async fn close<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<ViewerQueue> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let queue = sqlx::query_as!(
+            viewer_queue::Model,
+            "UPDATE viewer_queues SET open = FALSE, updated_at = NOW() WHERE channel_id = $1 RETURNING *",
+            channel_id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to close queue")?
+        .ok_or_else(no_queue)?;
+
+        global.viewer_queue_changed(channel_id, &[]).await;
+
+        Ok(queue.into())
+    }
+
+    /// Remove the queue of a channel and everyone waiting in it. Only the broadcaster can do this.
+    async fn end<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        let ended = sqlx::query!(
+            "DELETE FROM viewer_queues WHERE channel_id = $1",
+            channel_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err_gql("Failed to end queue")?
+        .rows_affected()
+            > 0;
+
+        if !ended {
+            return Err(no_queue());
+        }
+
+        global.viewer_queue_changed(channel_id, &[]).await;
+
+        Ok(true)
+    }
+
+    /// Join the queue of a channel with the logged in user, at the end of the line.
+    async fn join<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<ViewerQueuePosition> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        match global.join_viewer_queue(channel_id, session.user_id).await {
+            Ok(_) => {}
+            Err(JoinError::Closed) => {
+                return Err(GqlError::InvalidInput
+                    .with_message("This queue is closed")
+                    .with_field(vec!["channelId"]))
+            }
+            Err(JoinError::Full) => {
+                return Err(GqlError::InvalidInput
+                    .with_message("This queue is full")
+                    .with_field(vec!["channelId"]))
+            }
+            Err(JoinError::AlreadyJoined) => {
+                return Err(GqlError::InvalidInput.with_message("You are already in this queue"))
+            }
+            Err(JoinError::Database(e)) => return Err(e).map_err_gql("Failed to join queue"),
+        }
+
+        ViewerQueuePosition::load(global, channel_id, session.user_id, false).await
+    }
+
+    /// Leave the queue of a channel with the logged in user. Returns false if they were not in it.
+    async fn leave<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        let (session, _) = authorize_user(ctx).await?;
+
+        global
+            .leave_viewer_queue(channel_id, session.user_id)
+            .await
+            .map_err_gql("Failed to leave queue")
+    }
+
+    /// Call the next viewers from the queue of a channel, they are told over `viewerQueuePosition`.
+    /// Only the broadcaster can do this.
+    async fn pop<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "How many viewers to call, at most 25.", default = 1)] count: u32,
+    ) -> Result<Vec<ViewerQueueEntry>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        if !(1..=MAX_POP_COUNT).contains(&count) {
+            return Err(GqlError::InvalidInput
+                .with_message("Count must be between 1 and 25")
+                .with_field(vec!["count"]));
+        }
+
+        let entries = global
+            .pop_viewer_queue(channel_id, count as i64)
+            .await
+            .map_err_gql("Failed to call viewers")?;
+
+        Ok(ViewerQueueEntry::list(entries, 1))
+    }
+
+    /// Put the viewers in the queue of a channel in a random order. Only the broadcaster can do this.
+    async fn shuffle<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+    ) -> Result<Vec<ViewerQueueEntry>> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        global
+            .shuffle_viewer_queue(channel_id)
+            .await
+            .map_err_gql("Failed to shuffle queue")?;
+
+        first_entries(global, channel_id, DEFAULT_ENTRIES_LIMIT as i64).await
+    }
+
+    /// Take a viewer out of the queue of a channel without calling them. Only the broadcaster can do this.
+    async fn remove<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The id of the viewer.")] user_id: Uuid,
+    ) -> Result<bool> {
+        let global = ctx.get_global();
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        global
+            .leave_viewer_queue(channel_id, user_id)
+            .await
+            .map_err_gql("Failed to remove viewer")
+    }
+}
@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+
+use async_graphql::{Context, Object};
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::error::{GqlError, Result, ResultExt};
+use super::ext::ContextExt;
+use super::guards::authorize_channel_owner;
+use super::models::date::DateRFC3339;
+use super::models::vod::{Vod, VodAccess};
+use super::models::vod_caption::{
+    VodCaptionCue, VodCaptions, VodSearchResult, VodTranscriptMatch, VodTranscriptSnippet,
+};
+use super::models::vod_download::VodDownload;
+use super::pagination::{page_limit, Cursor};
+use crate::api::v1::jwt::PlaybackToken;
+use crate::database::protobuf::ProtobufValue;
+use crate::database::{
+    channel_event, global_role, moderation_job, stream, vod_caption, vod_download,
+};
+
+const DEFAULT_VODS_LIMIT: u32 = 20;
+const MAX_VODS_LIMIT: u32 = 100;
+
+const MIN_SEARCH_LENGTH: usize = 2;
+const MAX_SEARCH_LENGTH: usize = 100;
+
+/// Returns the logged in user, and whether they can watch every VOD of a channel because they subscribed to it,
+/// are the broadcaster or an admin.
+async fn viewer_access(ctx: &Context<'_>, channel_id: Uuid) -> Result<(Option<Uuid>, bool)> {
+    let global = ctx.get_global();
+
+    let Some((session, perms)) = ctx.get_session().get_session(global).await? else {
+        return Ok((None, false));
+    };
+
+    if session.user_id == channel_id
+        || perms
+            .permissions
+            .has_permission(global_role::Permission::Admin)
+    {
+        return Ok((Some(session.user_id), true));
+    }
+
+    let subscriber = channel_event::is_subscriber(&*global.db, channel_id, session.user_id)
+        .await
+        .map_err_gql("Failed to fetch subscription")?;
+
+    Ok((Some(session.user_id), subscriber))
+}
+
+/// Checks the text a client searches for, and turns it into a pattern for ILIKE.
+fn search_pattern(query: &str) -> Result<String> {
+    let query = query.trim();
+    if !(MIN_SEARCH_LENGTH..=MAX_SEARCH_LENGTH).contains(&query.chars().count()) {
+        return Err(GqlError::InvalidInput
+            .with_message("Search text must be between 2 and 100 characters")
+            .with_field(vec!["query"]));
+    }
+
+    Ok(moderation_job::like_pattern(query))
+}
+
+async fn fetch(ctx: &Context<'_>, stream_id: Uuid) -> Result<stream::Model> {
+    let global = ctx.get_global();
+
+    sqlx::query_as!(
+        stream::Model,
+        "SELECT * FROM streams WHERE id = $1 AND recorded = TRUE AND deleted = FALSE AND ended_at <= NOW()",
+        stream_id,
+    )
+    .fetch_optional(&*global.db)
+    .await
+    .map_err_gql("Failed to fetch VOD")?
+    .ok_or_else(|| {
+        GqlError::NotFound
+            .with_message("VOD not found")
+            .with_field(vec!["streamId"])
+    })
+}
+
+#[derive(Default)]
+pub struct VodQuery;
+
+#[Object]
+/// The query object for the recordings of past streams.
+impl VodQuery {
+    /// Get the VODs of a channel, newest first. VODs the logged in user can't watch are included with `locked` set.
+    /// To fetch the next page pass the `cursor` of the last VOD as `after`.
+    async fn vods<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "Only return VODs after this cursor, used for pagination.")] after: Option<
+            Cursor,
+        >,
+        #[graphql(desc = "The maximum number of VODs to return. Defaults to 20, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<Vod>> {
+        let global = ctx.get_global();
+
+        let limit = page_limit(limit, DEFAULT_VODS_LIMIT, MAX_VODS_LIMIT)?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let (_, subscriber) = viewer_access(ctx, channel_id).await?;
+
+        let vods = sqlx::query_as!(
+            stream::Model,
+            "SELECT * FROM streams WHERE channel_id = $1 AND recorded = TRUE AND deleted = FALSE AND ended_at <= NOW() AND ($2::timestamptz IS NULL OR (created_at, id) < ($2, $3::uuid)) ORDER BY created_at DESC, id DESC LIMIT $4",
+            channel_id,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch VODs")?;
+
+        Ok(vods
+            .into_iter()
+            .map(|vod| Vod::new(vod, subscriber))
+            .collect())
+    }
+
+    /// Search the VODs of a channel by their title and by what was said in them, newest first.
+    /// The captions are only searched in VODs the logged in user can watch.
+    /// To fetch the next page pass the `cursor` of the last VOD as `after`.
+    async fn search<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The text to look for, between 2 and 100 characters.")] query: String,
+        #[graphql(desc = "Only return VODs after this cursor, used for pagination.")] after: Option<
+            Cursor,
+        >,
+        #[graphql(desc = "The maximum number of VODs to return. Defaults to 20, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<VodSearchResult>> {
+        let global = ctx.get_global();
+
+        let pattern = search_pattern(&query)?;
+        let limit = page_limit(limit, DEFAULT_VODS_LIMIT, MAX_VODS_LIMIT)?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let (_, subscriber) = viewer_access(ctx, channel_id).await?;
+
+        let vods = sqlx::query_as!(
+            stream::Model,
+            "SELECT * FROM streams s WHERE s.channel_id = $1 AND s.recorded = TRUE AND s.deleted = FALSE AND s.ended_at <= NOW() AND ($2::timestamptz IS NULL OR (s.created_at, s.id) < ($2, $3::uuid)) AND (s.title ILIKE $4 OR (($5 OR s.vod_access = $6 OR (s.vod_access = $7 AND (s.vod_public_at IS NULL OR s.vod_public_at <= NOW()))) AND EXISTS (SELECT 1 FROM vod_caption_cues c WHERE c.stream_id = s.id AND c.text ILIKE $4))) ORDER BY s.created_at DESC, s.id DESC LIMIT $8",
+            channel_id,
+            after_time,
+            after_id,
+            pattern,
+            subscriber,
+            i64::from(stream::VodAccess::Public),
+            i64::from(stream::VodAccess::EarlyAccess),
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to search VODs")?;
+
+        let now = Utc::now();
+        let watchable = vods
+            .iter()
+            .filter(|vod| subscriber || vod.vod_public(now))
+            .map(|vod| vod.id)
+            .collect::<Vec<_>>();
+
+        let mut cues = HashMap::<Uuid, Vec<VodCaptionCue>>::new();
+        for cue in sqlx::query_as!(
+            vod_caption::Cue,
+            r#"SELECT stream_id AS "stream_id!", start_ms AS "start_ms!", end_ms AS "end_ms!", text AS "text!" FROM (SELECT *, ROW_NUMBER() OVER (PARTITION BY stream_id ORDER BY start_ms) AS n FROM vod_caption_cues WHERE stream_id = ANY($1) AND text ILIKE $2) c WHERE n <= $3 ORDER BY start_ms"#,
+            &watchable,
+            pattern,
+            vod_caption::MAX_SEARCH_CUES as i64,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to search captions")?
+        {
+            cues.entry(cue.stream_id).or_default().push(cue.into());
+        }
+
+        Ok(vods
+            .into_iter()
+            .map(|vod| VodSearchResult {
+                cues: cues.remove(&vod.id).unwrap_or_default(),
+                vod: Vod::new(vod, subscriber),
+            })
+            .collect())
+    }
+
+    /// Search what was said in the VODs of a channel the logged in user can watch, newest first.
+    /// Each VOD comes with the lines which matched and when they were said, so the player can jump to them.
+    /// To fetch the next page pass the `cursor` of the last VOD as `after`.
+    async fn search_transcripts<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the channel.")] channel_id: Uuid,
+        #[graphql(desc = "The text to look for, between 2 and 100 characters.")] query: String,
+        #[graphql(desc = "Only return VODs after this cursor, used for pagination.")] after: Option<
+            Cursor,
+        >,
+        #[graphql(desc = "The maximum number of VODs to return. Defaults to 20, at most 100.")]
+        limit: Option<u32>,
+    ) -> Result<Vec<VodTranscriptMatch>> {
+        let global = ctx.get_global();
+
+        let pattern = search_pattern(&query)?;
+        let limit = page_limit(limit, DEFAULT_VODS_LIMIT, MAX_VODS_LIMIT)?;
+        let (after_time, after_id) = Cursor::split(after);
+
+        let (_, subscriber) = viewer_access(ctx, channel_id).await?;
+
+        let vods = sqlx::query_as!(
+            stream::Model,
+            "SELECT * FROM streams s WHERE s.channel_id = $1 AND s.recorded = TRUE AND s.deleted = FALSE AND s.ended_at <= NOW() AND ($2::timestamptz IS NULL OR (s.created_at, s.id) < ($2, $3::uuid)) AND ($5 OR s.vod_access = $6 OR (s.vod_access = $7 AND (s.vod_public_at IS NULL OR s.vod_public_at <= NOW()))) AND EXISTS (SELECT 1 FROM vod_caption_cues c WHERE c.stream_id = s.id AND c.text ILIKE $4) ORDER BY s.created_at DESC, s.id DESC LIMIT $8",
+            channel_id,
+            after_time,
+            after_id,
+            pattern,
+            subscriber,
+            i64::from(stream::VodAccess::Public),
+            i64::from(stream::VodAccess::EarlyAccess),
+            limit,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to search transcripts")?;
+
+        let ids = vods.iter().map(|vod| vod.id).collect::<Vec<_>>();
+
+        // The lines around a match are taken before filtering, so they are the lines said right before and after it.
+        let mut snippets = HashMap::<Uuid, (i64, Vec<VodTranscriptSnippet>)>::new();
+        for m in sqlx::query_as!(
+            vod_caption::Match,
+            r#"SELECT stream_id AS "stream_id!", start_ms AS "start_ms!", end_ms AS "end_ms!", text AS "text!", line_before, line_after, matches AS "matches!" FROM (SELECT *, ROW_NUMBER() OVER (PARTITION BY stream_id ORDER BY start_ms) AS n, COUNT(*) OVER (PARTITION BY stream_id) AS matches FROM (SELECT stream_id, start_ms, end_ms, text, LAG(text) OVER w AS line_before, LEAD(text) OVER w AS line_after FROM vod_caption_cues WHERE stream_id = ANY($1) WINDOW w AS (PARTITION BY stream_id ORDER BY start_ms)) c WHERE text ILIKE $2) c WHERE n <= $3 ORDER BY start_ms"#,
+            &ids,
+            pattern,
+            vod_caption::MAX_SEARCH_CUES as i64,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err_gql("Failed to search transcripts")?
+        {
+            let entry = snippets.entry(m.stream_id).or_default();
+            entry.0 = m.matches;
+            entry.1.push(m.into());
+        }
+
+        Ok(vods
+            .into_iter()
+            .map(|vod| {
+                let (matches, snippets) = snippets.remove(&vod.id).unwrap_or_default();
+                VodTranscriptMatch {
+                    vod: Vod::new(vod, subscriber),
+                    matches,
+                    snippets,
+                }
+            })
+            .collect())
+    }
+
+    /// Get a download of a VOD, poll this to follow its progress. Only the broadcaster can see it.
+    async fn download<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the download.")] id: Uuid,
+    ) -> Result<Option<VodDownload>> {
+        let global = ctx.get_global();
+
+        let Some(download) = sqlx::query_as!(
+            vod_download::Model,
+            "SELECT * FROM vod_downloads WHERE id = $1",
+            id,
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch download")?
+        else {
+            return Ok(None);
+        };
+
+        let channel_id = sqlx::query_scalar!(
+            "SELECT channel_id FROM streams WHERE id = $1",
+            download.stream_id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch VOD")?;
+
+        authorize_channel_owner(ctx, channel_id).await?;
+
+        Ok(Some(download.into()))
+    }
+}
+
+#[derive(Default)]
+pub struct VodMutation;
+
+#[Object]
+/// The mutation object for the recordings of past streams.
+impl VodMutation {
+    /// Get a token the edge accepts to play a VOD. Only subscribers get one for VODs which are not public.
+    async fn playback_token<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the VOD.")] stream_id: Uuid,
+    ) -> Result<String> {
+        let global = ctx.get_global();
+
+        let vod = fetch(ctx, stream_id).await?;
+        let (user_id, subscriber) = viewer_access(ctx, vod.channel_id).await?;
+
+        if !subscriber && !vod.vod_public(Utc::now()) {
+            let message = match vod.vod_public_at {
+                Some(public_at) => format!(
+                    "This VOD is only available to subscribers until {}",
+                    public_at.to_rfc3339()
+                ),
+                None => "This VOD is only available to subscribers".to_string(),
+            };
+
+            return Err(GqlError::Unauthorized
+                .with_message(&message)
+                .with_field(vec!["streamId"]));
+        }
+
+        PlaybackToken {
+            stream_id: vod.id,
+            user_id,
+            expiration: Utc::now()
+                + chrono::Duration::seconds(global.config.vods.playback_token_ttl as i64),
+        }
+        .serialize(global)
+        .ok_or_else(|| {
+            GqlError::InternalServerError.with_message("Failed to serialize playback token")
+        })
+    }
+
+    /// Change who can watch a VOD. Only the broadcaster can do this.
+    async fn set_access<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the VOD.")] stream_id: Uuid,
+        #[graphql(desc = "Who can watch the VOD.")] access: VodAccess,
+        #[graphql(desc = "When an early access VOD becomes public, required for early access.")]
+        public_at: Option<DateRFC3339>,
+    ) -> Result<Vod> {
+        let global = ctx.get_global();
+
+        let vod = fetch(ctx, stream_id).await?;
+        authorize_channel_owner(ctx, vod.channel_id).await?;
+
+        let public_at = match access {
+            VodAccess::EarlyAccess => match public_at {
+                Some(public_at) if public_at.0 > Utc::now() => Some(public_at.0),
+                _ => {
+                    return Err(GqlError::InvalidInput
+                        .with_message(
+                            "Early access needs a time in the future the VOD becomes public",
+                        )
+                        .with_field(vec!["publicAt"]))
+                }
+            },
+            _ => None,
+        };
+
+        let vod = sqlx::query_as!(
+            stream::Model,
+            "UPDATE streams SET vod_access = $2, vod_public_at = $3 WHERE id = $1 RETURNING *",
+            stream_id,
+            i64::from(stream::VodAccess::from(access)),
+            public_at,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to update VOD")?;
+
+        Ok(Vod::new(vod, true))
+    }
+
+    /// Transcribe a VOD into captions, for VODs from before captions were enabled or whose transcription failed.
+    /// Poll `captions` of the VOD for the status. Only the broadcaster can do this.
+    async fn generate_captions<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the VOD.")] stream_id: Uuid,
+    ) -> Result<VodCaptions> {
+        let global = ctx.get_global();
+
+        if !global.config.vod_captions.enabled {
+            return Err(GqlError::InvalidInput.with_message("Captions are not enabled"));
+        }
+
+        let vod = fetch(ctx, stream_id).await?;
+        authorize_channel_owner(ctx, vod.channel_id).await?;
+
+        let captions = global
+            .queue_vod_captions(&vod)
+            .await
+            .map_err_gql("Failed to queue captions")?
+            .ok_or_else(|| {
+                GqlError::InvalidInput
+                    .with_message("This VOD already has captions or is being transcribed")
+                    .with_field(vec!["streamId"])
+            })?;
+
+        Ok(VodCaptions::new(captions, true))
+    }
+
+    /// Ask for an MP4 of a VOD. The recording is copied into it, or transcoded if its codecs can't go into an MP4,
+    /// poll `vod.download` for the progress and the url. A download of the same quality which is still running
+    /// or can still be downloaded is returned instead of making another one. Only the broadcaster can do this.
+    async fn request_download<'ctx>(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The id of the VOD.")] stream_id: Uuid,
+        #[graphql(desc = "The name of a variant of the recording, like `source`.")] quality: String,
+    ) -> Result<VodDownload> {
+        let global = ctx.get_global();
+
+        let vod = fetch(ctx, stream_id).await?;
+        let (session, _) = authorize_channel_owner(ctx, vod.channel_id).await?;
+
+        let remux = match &vod.state {
+            ProtobufValue::Some(state) => vod_download::remux(state, &quality),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            GqlError::InvalidInput
+                .with_message("This VOD has no such quality")
+                .with_field(vec!["quality"])
+        })?;
+
+        // Downloads which stopped making progress, e.g. because the worker restarted, are not reused.
+        let existing = sqlx::query_as!(
+            vod_download::Model,
+            "SELECT * FROM vod_downloads WHERE stream_id = $1 AND variant = $2 AND ((status IN ($3, $4) AND updated_at > NOW() - INTERVAL '10 minutes') OR (status = $5 AND expires_at > NOW())) ORDER BY created_at DESC LIMIT 1",
+            vod.id,
+            quality,
+            i64::from(vod_download::Status::Queued),
+            i64::from(vod_download::Status::Running),
+            i64::from(vod_download::Status::Completed),
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err_gql("Failed to fetch downloads")?;
+
+        if let Some(existing) = existing {
+            return Ok(existing.into());
+        }
+
+        let id = Uuid::new_v4();
+        let download = sqlx::query_as!(
+            vod_download::Model,
+            "INSERT INTO vod_downloads (id, stream_id, requested_by, variant, remux, object_key, region) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+            id,
+            vod.id,
+            session.user_id,
+            quality,
+            remux,
+            vod_download::object_key(vod.channel_id, vod.id, id),
+            vod.region,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err_gql("Failed to create download")?;
+
+        if let Err(e) = global.queue_vod_download(&download).await {
+            sqlx::query!(
+                "UPDATE vod_downloads SET status = $2, error = $3, completed_at = NOW() WHERE id = $1",
+                download.id,
+                i64::from(vod_download::Status::Failed),
+                "Failed to queue the download",
+            )
+            .execute(&*global.db)
+            .await
+            .map_err_gql("Failed to update download")?;
+
+            return Err(e).map_err_gql("Failed to queue download");
+        }
+
+        Ok(download.into())
+    }
+}
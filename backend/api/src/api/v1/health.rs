@@ -1,15 +1,16 @@
 use std::sync::Arc;
 
-use hyper::{Body, Request, Response, StatusCode};
+use hyper::{header, Body, Request, Response, StatusCode};
 use routerify::Router;
 use serde_json::json;
 
 use crate::{
     api::{
-        error::{Result, RouteError},
+        error::{Result, ResultExt, RouteError},
+        ext::RequestExt as _,
         macros::make_response,
     },
-    global::GlobalState,
+    global::{chat_fan_out, GlobalState},
 };
 
 async fn health(_: Request<Body>) -> Result<Response<Body>> {
@@ -21,9 +22,29 @@ async fn health(_: Request<Body>) -> Result<Response<Body>> {
     ))
 }
 
+/// The fan-out of the chats for Prometheus to scrape, so it is visible when big events need more shards.
+async fn metrics(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+
+    let chats = global
+        .chat_fan_out()
+        .await
+        .map_err_route("failed to collect chat fan-out")?;
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+        .body(Body::from(chat_fan_out::render_metrics(
+            &global.config.subscriptions,
+            &chats,
+        )))
+        .expect("failed to build response"))
+}
+
 pub fn routes(_global: &Arc<GlobalState>) -> Router<Body, RouteError> {
     Router::builder()
         .get("/", health)
+        .get("/metrics", metrics)
         .build()
         .expect("failed to build router")
 }
@@ -9,6 +9,9 @@ use uuid::Uuid;
 
 use crate::global::GlobalState;
 
+/// The audience of playback tokens, so they can't be used as session tokens.
+const PLAYBACK_AUDIENCE: &str = "playback";
+
 pub struct JwtState {
     pub user_id: Uuid,
     pub session_id: Uuid,
@@ -80,6 +83,9 @@ impl JwtState {
             .parse::<Uuid>()
             .ok()?;
         let audience = claims.registered.audience.clone();
+        if audience.as_deref() == Some(PLAYBACK_AUDIENCE) {
+            return None;
+        }
 
         Some(JwtState {
             user_id,
@@ -104,3 +110,66 @@ impl From<session::Model> for JwtState {
         }
     }
 }
+
+/// Allows a viewer to watch the recording of a stream, the edge checks it before serving the recording.
+pub struct PlaybackToken {
+    pub stream_id: Uuid,
+    /// The viewer, None if they are not logged in.
+    pub user_id: Option<Uuid>,
+    pub expiration: DateTime<Utc>,
+}
+
+impl PlaybackToken {
+    pub fn serialize(&self, global: &Arc<GlobalState>) -> Option<String> {
+        let key = Hmac::<Sha256>::new_from_slice(global.config.jwt.secret.as_bytes()).ok()?;
+        let claims = Claims::new(RegisteredClaims {
+            issued_at: Some(Utc::now().timestamp() as u64),
+            expiration: Some(self.expiration.timestamp() as u64),
+            issuer: Some(global.config.jwt.issuer.to_string()),
+            json_web_token_id: Some(self.stream_id.to_string()),
+            subject: self.user_id.map(|id| id.to_string()),
+            not_before: None,
+            audience: Some(PLAYBACK_AUDIENCE.to_string()),
+        });
+
+        claims.sign_with_key(&key).ok()
+    }
+
+    pub fn verify(global: &Arc<GlobalState>, token: &str) -> Option<Self> {
+        let key = Hmac::<Sha256>::new_from_slice(global.config.jwt.secret.as_bytes()).ok()?;
+        let token: Token<Header, Claims, _> = token.verify_with_key(&key).ok()?;
+
+        let claims = token.claims();
+
+        if claims.registered.issuer.clone()? != global.config.jwt.issuer
+            || claims.registered.audience.as_deref() != Some(PLAYBACK_AUDIENCE)
+        {
+            return None;
+        }
+
+        let expiration = Utc
+            .timestamp_opt(claims.registered.expiration? as i64, 0)
+            .single()?;
+        if expiration < Utc::now() {
+            return None;
+        }
+
+        let stream_id = claims
+            .registered
+            .json_web_token_id
+            .clone()?
+            .parse::<Uuid>()
+            .ok()?;
+
+        let user_id = match &claims.registered.subject {
+            Some(subject) => Some(subject.parse::<Uuid>().ok()?),
+            None => None,
+        };
+
+        Some(PlaybackToken {
+            stream_id,
+            user_id,
+            expiration,
+        })
+    }
+}
@@ -7,14 +7,24 @@ use crate::global::GlobalState;
 
 use super::error::RouteError;
 
+pub mod control;
+pub mod csp;
 pub mod gql;
 pub mod health;
 pub mod jwt;
+pub mod notifications;
+pub mod payments;
+pub mod revenue;
 
 pub fn routes(global: &Arc<GlobalState>) -> Router<Body, RouteError> {
     Router::builder()
         .scope("/health", health::routes(global))
+        .scope("/control", control::routes(global))
+        .scope("/csp", csp::routes(global))
         .scope("/gql", gql::routes(global))
+        .scope("/notifications", notifications::routes(global))
+        .scope("/payments", payments::routes(global))
+        .scope("/revenue", revenue::routes(global))
         .build()
         .expect("failed to build router")
 }
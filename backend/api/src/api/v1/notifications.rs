@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use hyper::{header, Body, Request, Response, StatusCode};
+use routerify::{prelude::RequestExt as _, Router};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        error::{Result, ResultExt, RouteError},
+        ext::RequestExt as _,
+    },
+    database::notification_settings,
+    global::GlobalState,
+};
+
+/// Turns a category of emails off from the link in an email, without logging in. The link is signed for the user
+/// and category, so it can't be changed to unsubscribe someone else.
+async fn unsubscribe(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+
+    let user_id = req
+        .param("user_id")
+        .and_then(|id| id.parse::<Uuid>().ok())
+        .ok_or((StatusCode::BAD_REQUEST, "invalid user id"))?;
+
+    let category = req
+        .param("category")
+        .and_then(|c| notification_settings::Category::from_name(c))
+        .ok_or((StatusCode::BAD_REQUEST, "invalid category"))?;
+
+    let token = req.param("token").map(String::as_str).unwrap_or_default();
+    if !global.verify_unsubscribe_token(user_id, category, token) {
+        return Err(RouteError::from((StatusCode::FORBIDDEN, "invalid token")));
+    }
+
+    notification_settings::set(&global.db, user_id, category, false)
+        .await
+        .map_err_route("failed to update notification settings")?;
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(Body::from(
+            "You are unsubscribed. You can turn these emails on again in your notification settings.",
+        ))
+        .expect("failed to build response"))
+}
+
+pub fn routes(_global: &Arc<GlobalState>) -> Router<Body, RouteError> {
+    // Mail clients with one-click unsubscribe send a POST to the same link.
+    Router::builder()
+        .get("/unsubscribe/:user_id/:category/:token", unsubscribe)
+        .post("/unsubscribe/:user_id/:category/:token", unsubscribe)
+        .build()
+        .expect("failed to build router")
+}
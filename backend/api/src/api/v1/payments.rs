@@ -0,0 +1,324 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use hyper::{Body, Request, Response, StatusCode};
+use routerify::Router;
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        error::{Result, ResultExt, RouteError},
+        ext::RequestExt as _,
+        macros::make_response,
+        webhook,
+    },
+    database::{
+        channel_event, charity_campaign, charity_donation, checkout, cheermote_tier, display_color,
+        global_role, revenue_transaction, webhook_event,
+    },
+    global::GlobalState,
+    pb,
+};
+
+/// The source payment events are recorded under in the webhook event ledger.
+const WEBHOOK_SOURCE: &str = "payments";
+const MAX_EVENT_ID_LENGTH: usize = 255;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PaymentStatus {
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Deserialize)]
+struct PaymentEvent {
+    /// The provider's id of the event, the same for every delivery of it.
+    id: String,
+    /// The id of our checkout or donation, passed to the provider when the payment was created.
+    reference: Uuid,
+    status: PaymentStatus,
+}
+
+/// Called by the payment provider when a checkout or donation was paid or the payment failed.
+/// Every event is applied once, events which were already processed and events for payments which are no longer
+/// pending are acknowledged and ignored, so the provider can safely retry.
+async fn webhook(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+    let config = &global.config.payment;
+
+    let headers = req.headers().clone();
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .map_err_route("failed to read body")?;
+
+    webhook::verify(
+        &config.webhook_secret,
+        config.webhook_tolerance,
+        &headers,
+        &body,
+        Utc::now(),
+    )
+    .map_err(|e| RouteError::from((StatusCode::UNAUTHORIZED, e)))?;
+
+    let event: PaymentEvent = serde_json::from_slice(&body)
+        .ok()
+        .filter(|e: &PaymentEvent| !e.id.is_empty() && e.id.len() <= MAX_EVENT_ID_LENGTH)
+        .ok_or((StatusCode::BAD_REQUEST, "invalid payment event"))?;
+
+    let status = match event.status {
+        PaymentStatus::Completed => checkout::Status::Completed,
+        PaymentStatus::Failed => checkout::Status::Failed,
+    };
+
+    let mut tx = global
+        .db
+        .begin()
+        .await
+        .map_err_route("failed to begin transaction")?;
+
+    if !webhook_event::claim(&mut *tx, WEBHOOK_SOURCE, &event.id)
+        .await
+        .map_err_route("failed to claim webhook event")?
+    {
+        return Ok(make_response!(
+            StatusCode::OK,
+            json!({ "success": true, "duplicate": true })
+        ));
+    }
+
+    // References are unique across checkouts and donations, so whichever table has the row owns the event.
+    let checkout = complete_checkout(&global, &mut *tx, event.reference, status).await?;
+    let donation = match checkout {
+        Some(_) => None,
+        None => complete_donation(&mut *tx, event.reference, status).await?,
+    };
+
+    tx.commit()
+        .await
+        .map_err_route("failed to commit transaction")?;
+
+    // The payment is already recorded at this point, so failed announcements must not fail the webhook.
+    if status == checkout::Status::Completed {
+        if let Some(checkout) = checkout {
+            if let Err(e) = announce_purchase(&global, &checkout).await {
+                tracing::error!("failed to announce purchase {}: {:#}", checkout.id, e);
+            }
+        }
+
+        if let Some(donation) = donation {
+            if let Err(e) = publish_donation(&global, &donation).await {
+                tracing::error!(
+                    "failed to publish charity progress {}: {:#}",
+                    donation.campaign_id,
+                    e
+                );
+            }
+        }
+    }
+
+    Ok(make_response!(StatusCode::OK, json!({ "success": true })))
+}
+
+/// Records the outcome of a subscription or cheer checkout. Returns None if there is no pending checkout with this id.
+async fn complete_checkout(
+    global: &Arc<GlobalState>,
+    tx: &mut sqlx::PgConnection,
+    reference: Uuid,
+    status: checkout::Status,
+) -> Result<Option<checkout::Model>> {
+    let Some(checkout) = sqlx::query_as!(
+        checkout::Model,
+        "UPDATE checkouts SET status = $1, completed_at = NOW() WHERE id = $2 AND status = $3 RETURNING *",
+        i64::from(status),
+        reference,
+        i64::from(checkout::Status::Pending),
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err_route("failed to update checkout")? else {
+        return Ok(None);
+    };
+    if status == checkout::Status::Completed {
+        let platform_fee = revenue_transaction::platform_fee(
+            checkout.amount,
+            global.config.revenue.platform_fee_percent,
+        );
+
+        sqlx::query!(
+            "INSERT INTO revenue_transactions (channel_id, user_id, kind, gross_amount, platform_fee) VALUES ($1, $2, $3, $4, $5)",
+            checkout.channel_id,
+            checkout.user_id,
+            i64::from(revenue_transaction::Kind::from(checkout.kind)),
+            checkout.amount,
+            platform_fee,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_route("failed to insert revenue transaction")?;
+
+        sqlx::query!(
+            "INSERT INTO payout_ledger_entries (channel_id, amount, description) VALUES ($1, $2, $3)",
+            checkout.channel_id,
+            checkout.amount - platform_fee,
+            match checkout.kind {
+                checkout::Kind::Subscription => "Subscription",
+                checkout::Kind::GiftSubscription => "Gift subscription",
+                checkout::Kind::Cheer => "Cheer",
+            },
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_route("failed to insert payout ledger entry")?;
+
+        let (event_kind, event_amount) = match checkout.kind {
+            checkout::Kind::Cheer => (channel_event::Kind::Cheer, checkout.amount),
+            _ => (channel_event::Kind::Subscription, 1),
+        };
+
+        sqlx::query!(
+            "INSERT INTO channel_events (channel_id, user_id, kind, amount, message) VALUES ($1, $2, $3, $4, $5)",
+            checkout.channel_id,
+            checkout.recipient_id.unwrap_or(checkout.user_id),
+            i64::from(event_kind),
+            event_amount,
+            checkout.message,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err_route("failed to insert channel event")?;
+    }
+
+    Ok(Some(checkout))
+}
+
+/// Records the outcome of a charity donation. Donations go to the beneficiary, so they are not added to the channel's revenue.
+async fn complete_donation(
+    tx: &mut sqlx::PgConnection,
+    reference: Uuid,
+    status: checkout::Status,
+) -> Result<Option<charity_donation::Model>> {
+    sqlx::query_as!(
+        charity_donation::Model,
+        "UPDATE charity_donations SET status = $1, completed_at = NOW() WHERE id = $2 AND status = $3 RETURNING *",
+        i64::from(status),
+        reference,
+        i64::from(checkout::Status::Pending),
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err_route("failed to update donation")
+}
+
+/// Publishes the new totals of the campaign a donation went to.
+async fn publish_donation(
+    global: &Arc<GlobalState>,
+    donation: &charity_donation::Model,
+) -> anyhow::Result<()> {
+    let campaign = sqlx::query_as!(
+        charity_campaign::Model,
+        "SELECT * FROM charity_campaigns WHERE id = $1",
+        donation.campaign_id,
+    )
+    .fetch_one(&*global.db)
+    .await?;
+
+    global.publish_charity_progress(&campaign).await
+}
+
+/// Publishes a chat message celebrating the purchase, attributed to the user who paid.
+async fn announce_purchase(
+    global: &Arc<GlobalState>,
+    checkout: &checkout::Model,
+) -> anyhow::Result<()> {
+    let buyer = global
+        .user_by_id_loader
+        .load_one(checkout.user_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to fetch buyer: {:?}", e))?
+        .ok_or_else(|| anyhow::anyhow!("buyer not found"))?;
+
+    // Cheers are sent with the buyer's message, the other purchases get a generated announcement.
+    let (content, cheer) = match (checkout.kind, checkout.recipient_id) {
+        (checkout::Kind::Cheer, _) => {
+            let tiers = cheermote_tier::for_channel(&global.db, checkout.channel_id).await?;
+            // Without any tiers the cheer is still sent, just without a cheermote.
+            let tier = cheermote_tier::resolve(&tiers, checkout.amount)
+                .cloned()
+                .unwrap_or_default();
+
+            let cheer = pb::scuffle::events::ChatCheer {
+                bits: checkout.amount,
+                prefix: tier.prefix,
+                tier_min_bits: tier.min_bits,
+                color: tier.color,
+                image_url: tier.image_url,
+            };
+
+            (checkout.message.clone(), Some(cheer))
+        }
+        (_, Some(recipient_id)) => {
+            let recipient = global
+                .user_by_id_loader
+                .load_one(recipient_id)
+                .await
+                .map_err(|e| anyhow::anyhow!("failed to fetch recipient: {:?}", e))?
+                .ok_or_else(|| anyhow::anyhow!("recipient not found"))?;
+
+            (
+                format!(
+                    "{} gifted a subscription to {}!",
+                    buyer.display_name, recipient.display_name
+                ),
+                None,
+            )
+        }
+        (_, None) => (format!("{} subscribed!", buyer.display_name), None),
+    };
+
+    let gradient_allowed = global
+        .user_permisions_by_id_loader
+        .load_one(buyer.id)
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to fetch buyer permissions: {:?}", e))?
+        .map(|p| {
+            p.permissions
+                .has_permission(global_role::Permission::DisplayNameGradient)
+        })
+        .unwrap_or_default();
+    let author_color = display_color::DisplayColor::of_user(&buyer, gradient_allowed);
+
+    global
+        .publish_chat_message(
+            checkout.channel_id,
+            pb::scuffle::events::ChatMessage {
+                id: checkout.id.to_string(),
+                channel_id: checkout.channel_id.to_string(),
+                author_id: checkout.user_id.to_string(),
+                content,
+                created_at: checkout
+                    .completed_at
+                    .unwrap_or(checkout.created_at)
+                    .timestamp(),
+                r#type: pb::scuffle::events::chat_message::Type::Purchase as i32,
+                emotes: vec![],
+                cheer,
+                author_color: author_color.as_ref().map(Into::into),
+                sequence: 0,
+                author_vip: false,
+                author_verified_bot: false,
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn routes(_global: &Arc<GlobalState>) -> Router<Body, RouteError> {
+    Router::builder()
+        .post("/webhook", webhook)
+        .build()
+        .expect("failed to build router")
+}
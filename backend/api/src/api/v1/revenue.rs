@@ -0,0 +1,74 @@
+use std::{fmt::Write, sync::Arc};
+
+use hyper::{header, Body, Request, Response, StatusCode};
+use routerify::{prelude::RequestExt as _, Router};
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        error::{Result, ResultExt, RouteError},
+        ext::RequestExt as _,
+    },
+    database::{global_role, revenue_transaction, session},
+    dataloader::user_permissions::UserPermission,
+    global::GlobalState,
+};
+
+/// Exports the monthly revenue report of a channel as CSV. All amounts are in cents.
+async fn export(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+
+    let channel_id = req
+        .param("channel_id")
+        .and_then(|id| id.parse::<Uuid>().ok())
+        .ok_or((StatusCode::BAD_REQUEST, "invalid channel id"))?;
+
+    let Some((session, permissions)) = req.context::<(session::Model, UserPermission)>() else {
+        return Err(RouteError::from((StatusCode::UNAUTHORIZED, "unauthorized")));
+    };
+
+    if session.user_id != channel_id
+        && !permissions
+            .permissions
+            .has_permission(global_role::Permission::Admin)
+    {
+        return Err(RouteError::from((StatusCode::FORBIDDEN, "forbidden")));
+    }
+
+    let revenue = revenue_transaction::monthly_revenue(&global.db, channel_id)
+        .await
+        .map_err_route("failed to fetch revenue")?;
+
+    let mut csv = String::from("month,subscriptions,bits,gifts,gross,platform_fee,net\n");
+    for month in revenue {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{}",
+            month.month.format("%Y-%m"),
+            month.subscriptions,
+            month.bits,
+            month.gifts,
+            month.gross,
+            month.platform_fee,
+            month.net(),
+        )
+        .map_err_route("failed to write csv")?;
+    }
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/csv")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"revenue-{}.csv\"", channel_id),
+        )
+        .body(Body::from(csv))
+        .expect("failed to build response"))
+}
+
+pub fn routes(_global: &Arc<GlobalState>) -> Router<Body, RouteError> {
+    Router::builder()
+        .get("/:channel_id/export", export)
+        .build()
+        .expect("failed to build router")
+}
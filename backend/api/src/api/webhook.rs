@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use hyper::HeaderMap;
+use ring::constant_time;
+use sha2::Sha256;
+
+pub const X_WEBHOOK_TIMESTAMP: &str = "X-Webhook-Timestamp";
+pub const X_WEBHOOK_SIGNATURE: &str = "X-Webhook-Signature";
+
+/// The signature of a webhook call as `sha256=<hex>`. The timestamp is signed along with the body,
+/// so a captured call can't be replayed later with a fresh timestamp.
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+/// Checks that a webhook call is signed with the secret and was sent at most `tolerance` seconds from now.
+/// Calls within the tolerance can still be delivered twice, the receiver has to skip events it already processed.
+pub fn verify(
+    secret: &str,
+    tolerance: u32,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> Result<(), &'static str> {
+    let header = |name: &str| headers.get(name).and_then(|h| h.to_str().ok());
+
+    let timestamp = header(X_WEBHOOK_TIMESTAMP)
+        .and_then(|t| t.parse::<i64>().ok())
+        .ok_or("missing timestamp")?;
+    let signature = header(X_WEBHOOK_SIGNATURE).ok_or("missing signature")?;
+
+    let expected = sign(secret, timestamp, body);
+    if constant_time::verify_slices_are_equal(expected.as_bytes(), signature.as_bytes()).is_err() {
+        return Err("invalid signature");
+    }
+
+    if (now.timestamp() - timestamp).abs() > tolerance as i64 {
+        return Err("timestamp is too old");
+    }
+
+    Ok(())
+}
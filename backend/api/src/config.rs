@@ -1,7 +1,9 @@
-use std::net::SocketAddr;
+use std::{collections::HashMap, net::SocketAddr};
 
 use anyhow::Result;
-use common::config::{LoggingConfig, RedisConfig, RmqConfig, TlsConfig};
+use common::config::{
+    DatabaseConfig, LoggingConfig, RedisConfig, RmqConfig, SecurityHeadersConfig, TlsConfig,
+};
 
 #[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
 #[serde(default)]
@@ -19,12 +21,24 @@ pub struct AppConfig {
     /// API Config
     pub api: ApiConfig,
 
+    /// GraphQL Config
+    pub gql: GqlConfig,
+
+    /// Cookie Auth Config
+    pub cookie_auth: CookieAuthConfig,
+
+    /// Security Headers Config
+    pub security_headers: SecurityHeadersConfig,
+
     /// Database Config
     pub database: DatabaseConfig,
 
     /// Turnstile Config
     pub turnstile: TurnstileConfig,
 
+    /// IP Reputation Config
+    pub ip_reputation: IpReputationConfig,
+
     /// JWT Config
     pub jwt: JwtConfig,
 
@@ -36,6 +50,126 @@ pub struct AppConfig {
 
     /// Redis configuration
     pub redis: RedisConfig,
+
+    /// Revenue Config
+    pub revenue: RevenueConfig,
+
+    /// Payout Config
+    pub payout: PayoutConfig,
+
+    /// Payment Config
+    pub payment: PaymentConfig,
+
+    /// Emote Config
+    pub emotes: EmoteConfig,
+
+    /// Image Processor Config
+    pub image_processor: ImageProcessorConfig,
+
+    /// Control API Config
+    pub control: ControlConfig,
+
+    /// Encryption Config
+    pub encryption: EncryptionConfig,
+
+    /// OBS Config
+    pub obs: ObsConfig,
+
+    /// Notifications Config
+    pub notifications: NotificationsConfig,
+
+    /// Discord Config
+    pub discord: DiscordConfig,
+
+    /// Channel Import Config
+    pub import: ImportConfig,
+
+    /// Moderation Config
+    pub moderation: ModerationConfig,
+
+    /// Suspension Config
+    pub suspensions: SuspensionConfig,
+
+    /// Mail Config
+    pub mail: MailConfig,
+
+    /// Login Link Config
+    pub login_links: LoginLinkConfig,
+
+    /// Password Reset Config
+    pub password_resets: PasswordResetConfig,
+
+    /// Email Change Config
+    pub email_changes: EmailChangeConfig,
+
+    /// Invite Config
+    pub invites: InviteConfig,
+
+    /// Session Elevation Config
+    pub elevation: ElevationConfig,
+
+    /// Display Color Config
+    pub display_colors: DisplayColorConfig,
+
+    /// Username Config
+    pub usernames: UsernameConfig,
+
+    /// Chat Config
+    pub chat: ChatConfig,
+
+    /// Presence Config
+    pub presence: PresenceConfig,
+
+    /// Subscription Config
+    pub subscriptions: SubscriptionConfig,
+
+    /// Deprecation Config
+    pub deprecations: DeprecationConfig,
+
+    /// VOD Config
+    pub vods: VodConfig,
+
+    /// VOD Download Config
+    pub vod_downloads: VodDownloadConfig,
+
+    /// VOD Caption Config
+    pub vod_captions: VodCaptionConfig,
+
+    /// Reconciliation Config
+    pub reconciliation: ReconciliationConfig,
+
+    /// Digest Config
+    pub digests: DigestConfig,
+
+    /// Data Export Config
+    pub data_exports: DataExportConfig,
+
+    /// Object Storage Config
+    pub storage: StorageConfig,
+
+    /// OAuth Config
+    pub oauth: OAuthConfig,
+
+    /// Admin Approval Config
+    pub approvals: ApprovalConfig,
+
+    /// Data Residency Config
+    pub residency: ResidencyConfig,
+
+    /// Passkey Config
+    pub passkeys: PasskeyConfig,
+
+    /// Media Request Config
+    pub media_requests: MediaRequestConfig,
+
+    /// Stream Key Config
+    pub stream_keys: StreamKeyConfig,
+
+    /// Seed fake users, channels and follows and keep a test stream live, for local development only
+    pub sandbox: bool,
+
+    /// Sandbox Config
+    pub sandbox_stream: SandboxStreamConfig,
 }
 
 #[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
@@ -46,6 +180,9 @@ pub struct ApiConfig {
 
     /// If we should use TLS for the API server
     pub tls: Option<TlsConfig>,
+
+    /// The longest a request may take in seconds, clients can shorten it with the X-Request-Timeout header (in milliseconds)
+    pub request_timeout: u32,
 }
 
 impl Default for ApiConfig {
@@ -53,21 +190,61 @@ impl Default for ApiConfig {
         Self {
             bind_address: "[::]:4000".parse().expect("failed to parse bind address"),
             tls: None,
+            request_timeout: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct GqlConfig {
+    /// If anyone can introspect the schema, this should be disabled in production
+    pub introspection: bool,
+
+    /// If validation errors suggest similarly named fields and types, this leaks the schema when introspection is disabled
+    pub suggestions: bool,
+
+    /// Tokens which allow partners to introspect the schema through the X-Introspection-Token header when introspection is disabled
+    pub introspection_tokens: Vec<String>,
+}
+
+impl Default for GqlConfig {
+    fn default() -> Self {
+        Self {
+            introspection: true,
+            suggestions: true,
+            introspection_tokens: Vec::new(),
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
 #[serde(default)]
-pub struct DatabaseConfig {
-    /// The database URL to use
-    pub uri: String,
+pub struct CookieAuthConfig {
+    /// If sessions are accepted from the session cookie when there is no Authorization header
+    pub enabled: bool,
+
+    /// The name of the cookie holding the session token
+    pub session_cookie: String,
+
+    /// The name of the cookie holding the CSRF token, which has to be echoed in the X-CSRF-Token header
+    pub csrf_cookie: String,
+
+    /// The origins allowed to send cookie authenticated requests, like https://scuffle.tv
+    pub allowed_origins: Vec<String>,
+
+    /// The domain the cookies are set for, so the website can read the CSRF cookie when the API is on a subdomain. Empty for the API host only
+    pub domain: String,
 }
 
-impl Default for DatabaseConfig {
+impl Default for CookieAuthConfig {
     fn default() -> Self {
         Self {
-            uri: "postgres://root@localhost:5432/scuffle_dev".to_string(),
+            enabled: false,
+            session_cookie: "scuffle_session".to_string(),
+            csrf_cookie: "scuffle_csrf".to_string(),
+            allowed_origins: vec!["http://localhost:4000".to_string()],
+            domain: String::new(),
         }
     }
 }
@@ -91,6 +268,53 @@ impl Default for TurnstileConfig {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct IpReputationConfig {
+    /// The IP reputation provider, either "none" to allow every address or "http"
+    pub provider: String,
+
+    /// The base url of the provider API, addresses are looked up with a GET to {url}/{ip}
+    pub url: String,
+
+    /// The secret key for the provider API
+    pub secret_key: String,
+
+    /// Requests from addresses with a risk score (0 - 100) at or above this have to solve a captcha
+    pub captcha_score: u32,
+
+    /// Requests from addresses with a risk score (0 - 100) at or above this are blocked
+    pub block_score: u32,
+
+    /// The risk score given to addresses the provider detected as a proxy or VPN, if their own score is lower
+    pub proxy_score: u32,
+
+    /// How long verdicts are cached in seconds
+    pub cache_ttl: u32,
+
+    /// How long a lookup may take in seconds before the address is allowed without a verdict
+    pub timeout: u32,
+
+    /// The header a trusted reverse proxy puts the client address in, the peer address is used if unset
+    pub client_ip_header: Option<String>,
+}
+
+impl Default for IpReputationConfig {
+    fn default() -> Self {
+        Self {
+            provider: "none".to_string(),
+            url: "http://localhost:9400".to_string(),
+            secret_key: "DUMMY_KEY__SAMPLE_TEXT".to_string(),
+            captcha_score: 50,
+            block_score: 90,
+            proxy_score: 50,
+            cache_ttl: 3600,
+            timeout: 2,
+            client_ip_header: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
 #[serde(default)]
 pub struct JwtConfig {
@@ -118,6 +342,9 @@ pub struct GrpcConfig {
 
     /// If we should use TLS for the gRPC server
     pub tls: Option<TlsConfig>,
+
+    /// Serve the admin API scuffle-admin uses, anyone who can connect to the gRPC server can use it
+    pub admin: bool,
 }
 
 impl Default for GrpcConfig {
@@ -125,6 +352,1072 @@ impl Default for GrpcConfig {
         Self {
             bind_address: "[::]:50051".parse().expect("failed to parse bind address"),
             tls: None,
+            admin: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct RevenueConfig {
+    /// The percentage of every transaction the platform keeps
+    pub platform_fee_percent: u32,
+
+    /// The price of a subscription in cents, before promotions
+    pub subscription_price: u32,
+}
+
+impl Default for RevenueConfig {
+    fn default() -> Self {
+        Self {
+            platform_fee_percent: 50,
+            subscription_price: 499,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct PayoutConfig {
+    /// The name of the payout provider
+    pub provider: String,
+
+    /// The base url of the payout provider API
+    pub url: String,
+
+    /// The secret key for the payout provider API
+    pub secret_key: String,
+}
+
+impl Default for PayoutConfig {
+    fn default() -> Self {
+        Self {
+            provider: "local".to_string(),
+            url: "http://localhost:9100".to_string(),
+            secret_key: "DUMMY_KEY__SAMPLE_TEXT".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct PaymentConfig {
+    /// The base url of the payment provider API
+    pub url: String,
+
+    /// The secret key for the payment provider API
+    pub secret_key: String,
+
+    /// The secret the payment provider signs webhook calls with
+    pub webhook_secret: String,
+
+    /// How far in seconds the timestamp of a webhook call may be off before it is rejected as a replay
+    pub webhook_tolerance: u32,
+}
+
+impl Default for PaymentConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:9200".to_string(),
+            secret_key: "DUMMY_KEY__SAMPLE_TEXT".to_string(),
+            webhook_secret: "DUMMY_KEY__SAMPLE_TEXT".to_string(),
+            webhook_tolerance: 5 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct EmoteConfig {
+    /// The number of emote slots every channel gets
+    pub base_slots: u32,
+
+    /// The number of sub points needed for each additional slot
+    pub sub_points_per_slot: u32,
+
+    /// The maximum number of emote slots a channel can have
+    pub max_slots: u32,
+
+    /// The maximum width and height of an emote in pixels
+    pub max_size: u32,
+
+    /// The maximum size of an emote image in bytes
+    pub max_file_size: u32,
+
+    /// The url of the image classifier used to score emotes
+    pub classifier_url: String,
+
+    /// Emotes with an NSFW score (0 - 100) at or above this are rejected without review
+    pub nsfw_threshold: u32,
+
+    /// The third-party emote providers channels can enable, mapped to the url of their emote set API
+    pub providers: HashMap<String, String>,
+
+    /// How long emote sets fetched from providers are cached in seconds
+    pub provider_cache_ttl: u32,
+}
+
+impl Default for EmoteConfig {
+    fn default() -> Self {
+        Self {
+            base_slots: 5,
+            sub_points_per_slot: 10,
+            max_slots: 50,
+            max_size: 112,
+            max_file_size: 1024 * 1024,
+            classifier_url: "http://localhost:9300".to_string(),
+            nsfw_threshold: 80,
+            providers: HashMap::new(),
+            provider_cache_ttl: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ImageProcessorConfig {
+    /// The RMQ queue image processing jobs are published to
+    pub queue: String,
+
+    /// The RMQ queue the image processor reports finished jobs to
+    pub result_queue: String,
+
+    /// The url processed images are served from
+    pub cdn_url: String,
+}
+
+impl Default for ImageProcessorConfig {
+    fn default() -> Self {
+        Self {
+            queue: "image_processor".to_string(),
+            result_queue: "image_processor_results".to_string(),
+            cdn_url: "http://localhost:9400".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ControlConfig {
+    /// The time a channel has to wait after an ad break ends before it can run the next one in seconds
+    pub ad_cooldown: u32,
+
+    /// How often live stats are pushed to connected control apps in seconds
+    pub stats_interval: u32,
+}
+
+impl Default for ControlConfig {
+    fn default() -> Self {
+        Self {
+            ad_cooldown: 480,
+            stats_interval: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// The secret used to encrypt integration credentials stored in the database
+    pub secret_key: String,
+
+    /// If personal data (emails and payout account ids) is encrypted before it is stored
+    pub encrypt_pii: bool,
+
+    /// Where the keys for personal data are loaded from, either "config" or "file"
+    pub key_provider: String,
+
+    /// The id of the key personal data is encrypted with, the other keys are only used to decrypt
+    pub current_key: String,
+
+    /// The keys for personal data by id, used by the config key provider
+    pub keys: HashMap<String, String>,
+
+    /// A JSON file mapping key ids to keys, used by the file key provider (like a secret mounted by a KMS agent)
+    pub keys_file: String,
+
+    /// How many rows are re-encrypted at a time when migrating to the current key
+    pub migration_batch_size: u32,
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        Self {
+            secret_key: "DUMMY_KEY__SAMPLE_TEXT".to_string(),
+            encrypt_pii: false,
+            key_provider: "config".to_string(),
+            current_key: "1".to_string(),
+            keys: HashMap::from([("1".to_string(), "DUMMY_KEY__SAMPLE_TEXT".to_string())]),
+            keys_file: "".to_string(),
+            migration_batch_size: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ObsConfig {
+    /// If the API should connect to the OBS instances of channels
+    pub enabled: bool,
+
+    /// How often connections are checked and failed connections retried in seconds
+    pub reconcile_interval: u32,
+
+    /// How long to wait for OBS to accept the connection in seconds
+    pub connect_timeout: u32,
+}
+
+impl Default for ObsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reconcile_interval: 30,
+            connect_timeout: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// The RMQ fanout exchange channel notifications are published to
+    pub exchange: String,
+
+    /// The RMQ queue the integrations of the API consume channel notifications from
+    pub queue: String,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            exchange: "channel_notifications".to_string(),
+            queue: "api_channel_notifications".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct DiscordConfig {
+    /// The base url of the Discord API, used when a bot posts announcements
+    pub api_url: String,
+
+    /// The url of the website, used to link to channels in announcements
+    pub website_url: String,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "https://discord.com/api/v10".to_string(),
+            website_url: "http://localhost:4000".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ImportConfig {
+    /// The RMQ queue channel import jobs are published to
+    pub queue: String,
+
+    /// The base url of the Twitch Helix API
+    pub twitch_api_url: String,
+
+    /// The client id of the Twitch application access tokens are issued to
+    pub twitch_client_id: String,
+
+    /// The base url of the YouTube Data API
+    pub youtube_api_url: String,
+}
+
+impl Default for ImportConfig {
+    fn default() -> Self {
+        Self {
+            queue: "channel_imports".to_string(),
+            twitch_api_url: "https://api.twitch.tv/helix".to_string(),
+            twitch_client_id: "DUMMY_CLIENT_ID".to_string(),
+            youtube_api_url: "https://www.googleapis.com/youtube/v3".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ModerationConfig {
+    /// The RMQ queue moderation jobs are published to
+    pub queue: String,
+
+    /// The most users a single job may ban
+    pub max_users: usize,
+
+    /// How far back in minutes a job may delete messages
+    pub max_minutes: u32,
+
+    /// The number of users banned, messages deleted or follows removed per query, progress is reported after each
+    pub batch_size: i64,
+
+    /// A minute with at least this many times the usual number of follows is a follow spike
+    pub follow_spike_multiplier: f64,
+
+    /// The fewest follows in a minute which are a follow spike, so small channels are not flagged for a handful of follows
+    pub follow_spike_min_follows: i64,
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            queue: "moderation_jobs".to_string(),
+            max_users: 1000,
+            max_minutes: 24 * 60,
+            batch_size: 100,
+            follow_spike_multiplier: 10.0,
+            follow_spike_min_follows: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct SuspensionConfig {
+    /// How often in seconds expired suspensions are marked as lifted
+    pub reinstatement_interval: u32,
+
+    /// How long in seconds to wait for an ingest to end a suspended user's stream
+    pub shutdown_timeout: u32,
+}
+
+impl Default for SuspensionConfig {
+    fn default() -> Self {
+        Self {
+            reinstatement_interval: 60,
+            shutdown_timeout: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct MailConfig {
+    /// The mail provider, either "none" to not send any emails or "http"
+    pub provider: String,
+
+    /// The url of the mail provider's send endpoint
+    pub url: String,
+
+    /// The secret key used to authenticate with the mail provider
+    pub secret_key: String,
+
+    /// The address emails are sent from
+    pub from: String,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            provider: "none".to_string(),
+            url: String::new(),
+            secret_key: String::new(),
+            from: "no-reply@scuffle.tv".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct LoginLinkConfig {
+    /// The page of the website login links point to, the token is added as the token query parameter
+    pub url: String,
+
+    /// How long in seconds a login link can be used
+    pub expiry: u32,
+
+    /// How many links can be requested for one account or from one address per window
+    pub max_requests: u32,
+
+    /// The rate limit window in seconds
+    pub rate_limit_window: u32,
+}
+
+impl Default for LoginLinkConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:4000/login/link".to_string(),
+            expiry: 15 * 60,
+            max_requests: 3,
+            rate_limit_window: 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct PasswordResetConfig {
+    /// The page of the website reset emails point to, the token is added as the token query parameter
+    pub url: String,
+
+    /// How long in seconds a reset token can be used
+    pub expiry: u32,
+
+    /// How many resets can be requested for one account or from one address per window
+    pub max_requests: u32,
+
+    /// The rate limit window in seconds
+    pub rate_limit_window: u32,
+}
+
+impl Default for PasswordResetConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:4000/reset-password".to_string(),
+            expiry: 60 * 60,
+            max_requests: 3,
+            rate_limit_window: 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct EmailChangeConfig {
+    /// The page of the website the confirmation email to the new address points to, the token is added as the token query parameter
+    pub confirm_url: String,
+
+    /// The page of the website the email to the old address points to, to undo the change
+    pub revert_url: String,
+
+    /// How long in seconds the new address can confirm the change
+    pub expiry: u32,
+
+    /// How long in seconds the old address can undo the change, also after it was confirmed
+    pub revert_expiry: u32,
+}
+
+impl Default for EmailChangeConfig {
+    fn default() -> Self {
+        Self {
+            confirm_url: "http://localhost:4000/confirm-email".to_string(),
+            revert_url: "http://localhost:4000/revert-email".to_string(),
+            expiry: 24 * 60 * 60,
+            revert_expiry: 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct InviteConfig {
+    /// Only let users register with an invite code, for controlled beta launches
+    pub required: bool,
+
+    /// How many invite codes each user can create
+    pub codes_per_user: u32,
+
+    /// The registration page of the website, the code is added as the code query parameter in the emails to admitted waitlist entries
+    pub url: String,
+}
+
+impl Default for InviteConfig {
+    fn default() -> Self {
+        Self {
+            required: false,
+            codes_per_user: 3,
+            url: "http://localhost:4000/register".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ElevationConfig {
+    /// How long in seconds sensitive actions are allowed after reauthenticating
+    pub duration: u32,
+}
+
+impl Default for ElevationConfig {
+    fn default() -> Self {
+        Self { duration: 10 * 60 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct DisplayColorConfig {
+    /// How long in seconds a user has to wait between two color changes
+    pub cooldown: u32,
+
+    /// How many times a user can change their color per window
+    pub max_changes: u32,
+
+    /// The rate limit window in seconds
+    pub rate_limit_window: u32,
+}
+
+impl Default for DisplayColorConfig {
+    fn default() -> Self {
+        Self {
+            cooldown: 30,
+            max_changes: 10,
+            rate_limit_window: 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct UsernameConfig {
+    /// How long in seconds a user has to wait between two username changes, changing the case is always allowed
+    pub cooldown: u32,
+}
+
+impl Default for UsernameConfig {
+    fn default() -> Self {
+        Self {
+            cooldown: 30 * 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ChatConfig {
+    /// How many of the last chat events of a channel are kept for clients to fetch after missing some
+    pub backfill_size: u32,
+
+    /// How long in milliseconds a subscription holds back events which arrived before an earlier one
+    pub reorder_window: u32,
+
+    /// How many VIPs a broadcaster can have in their chat
+    pub vip_slots: u32,
+
+    /// How many VIPs a broadcaster with the extended VIP slots permission can have in their chat
+    pub extended_vip_slots: u32,
+
+    /// How many messages a user can send in one chat per rate limit window
+    pub rate_limit: u32,
+
+    /// How many messages a verified bot can send in one chat per rate limit window
+    pub verified_bot_rate_limit: u32,
+
+    /// The rate limit window in seconds
+    pub rate_limit_window: u32,
+
+    /// How many commands a broadcaster can add to their chat
+    pub max_commands: u32,
+
+    /// How many timers a broadcaster can add to their chat
+    pub max_timers: u32,
+
+    /// The fewest seconds a timer can wait between two posts
+    pub min_timer_interval: u32,
+
+    /// How often in seconds the timers are checked for a due post, 0 to not post timers
+    pub timer_check_interval: u32,
+
+    /// How many chats get a timer posted per check, the rest get theirs with the next ones
+    pub timer_batch_size: u32,
+}
+
+impl Default for ChatConfig {
+    fn default() -> Self {
+        Self {
+            backfill_size: 1000,
+            reorder_window: 500,
+            vip_slots: 10,
+            extended_vip_slots: 100,
+            rate_limit: 20,
+            verified_bot_rate_limit: 100,
+            rate_limit_window: 30,
+            max_commands: 100,
+            max_timers: 10,
+            min_timer_interval: 5 * 60,
+            timer_check_interval: 30,
+            timer_batch_size: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct SubscriptionConfig {
+    /// How many events a subscriber can fall behind before it is closed, the client has to subscribe again
+    pub subscriber_buffer: u32,
+
+    /// How many subscribers of a subject share a fan-out task, more subscribers are spread over more tasks
+    pub shard_size: u32,
+
+    /// The most fan-out tasks one subject is spread over
+    pub max_shards: u32,
+
+    /// How many events wait for a fan-out task before new ones are dropped for its subscribers
+    pub shard_queue: u32,
+
+    /// How many subscribers a chat needs to get its own series on `/v1/health/metrics`, smaller chats are only in the totals
+    pub metrics_min_subscribers: u32,
+}
+
+impl Default for SubscriptionConfig {
+    fn default() -> Self {
+        Self {
+            subscriber_buffer: 64,
+            shard_size: 1000,
+            max_shards: 64,
+            shard_queue: 256,
+            metrics_min_subscribers: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct PresenceConfig {
+    /// How long in seconds a user counts as online after their last request or playback heartbeat
+    pub ttl: u32,
+
+    /// How often in seconds presence subscriptions check for changes
+    pub poll_interval: u32,
+}
+
+impl Default for PresenceConfig {
+    fn default() -> Self {
+        Self {
+            ttl: 120,
+            poll_interval: 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct DeprecationConfig {
+    /// The changelog page announcing deprecations, linked from responses which use a deprecated field or endpoint
+    pub changelog_url: String,
+
+    /// The deprecated fields and endpoints
+    pub surfaces: Vec<DeprecatedSurfaceConfig>,
+}
+
+impl Default for DeprecationConfig {
+    fn default() -> Self {
+        Self {
+            changelog_url: "https://scuffle.tv/docs/changelog".to_string(),
+            surfaces: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct VodConfig {
+    /// How long a playback token the edge accepts for a recording is valid in seconds
+    pub playback_token_ttl: u32,
+}
+
+impl Default for VodConfig {
+    fn default() -> Self {
+        Self {
+            playback_token_ttl: 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct VodDownloadConfig {
+    /// The RMQ queue VOD download jobs are published to
+    pub queue: String,
+
+    /// The RMQ queue the worker reports the progress of the jobs to
+    pub result_queue: String,
+
+    /// How many seconds a download url stays valid
+    pub url_expiry: u32,
+
+    /// How many days a finished MP4 can be downloaded
+    pub retention_days: u32,
+}
+
+impl Default for VodDownloadConfig {
+    fn default() -> Self {
+        Self {
+            queue: "vod_downloads".to_string(),
+            result_queue: "vod_download_results".to_string(),
+            url_expiry: 60 * 60,
+            retention_days: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct VodCaptionConfig {
+    /// If finished VODs are transcribed into captions
+    pub enabled: bool,
+
+    /// The RMQ queue transcription jobs are published to
+    pub queue: String,
+
+    /// The RMQ queue the worker reports the finished jobs to
+    pub result_queue: String,
+
+    /// The url of the Whisper-compatible transcription API the worker sends the audio to
+    pub backend_url: String,
+
+    /// The model the transcription API uses
+    pub model: String,
+
+    /// How many seconds a caption url stays valid
+    pub url_expiry: u32,
+}
+
+impl Default for VodCaptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            queue: "vod_captions".to_string(),
+            result_queue: "vod_caption_results".to_string(),
+            backend_url: "http://localhost:9000/v1/audio/transcriptions".to_string(),
+            model: "whisper-1".to_string(),
+            url_expiry: 60 * 60 * 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ReconciliationConfig {
+    /// How often in seconds the stream summaries are checked against the chat and follows, 0 to only run it from scuffle-admin
+    pub interval: u32,
+
+    /// How many days back streams are checked, older summaries are left alone
+    pub lookback_days: u32,
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            interval: 60 * 60,
+            lookback_days: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    /// How often in seconds users are checked for a due weekly digest, 0 to not send digests
+    pub interval: u32,
+
+    /// How many digests are sent per check, the rest are sent with the next ones
+    pub batch_size: u32,
+
+    /// The url of the unsubscribe route of the API, linked in every digest
+    pub unsubscribe_url: String,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            interval: 60 * 60,
+            batch_size: 100,
+            unsubscribe_url: "http://localhost:4000/v1/notifications/unsubscribe".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct DataExportConfig {
+    /// The RMQ queue data exports are published to
+    pub queue: String,
+
+    /// How many seconds a user has to wait after requesting an export before they can request another one
+    pub cooldown: u32,
+
+    /// How many seconds a download url stays valid
+    pub url_expiry: u32,
+
+    /// How many days a finished export can be downloaded
+    pub retention_days: u32,
+}
+
+impl Default for DataExportConfig {
+    fn default() -> Self {
+        Self {
+            queue: "data_exports".to_string(),
+            cooldown: 60 * 60 * 24,
+            url_expiry: 60 * 60,
+            retention_days: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// The url of the S3 compatible object storage, objects are addressed as {endpoint}/{bucket}/{key}
+    pub endpoint: String,
+
+    /// The region the bucket is in
+    pub region: String,
+
+    /// The bucket data exports are uploaded to, it should expire objects after the export retention
+    pub bucket: String,
+
+    /// The access key id of the object storage
+    pub access_key: String,
+
+    /// The secret access key of the object storage
+    pub secret_key: String,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://localhost:9000".to_string(),
+            region: "us-east-1".to_string(),
+            bucket: "scuffle-exports".to_string(),
+            access_key: String::new(),
+            secret_key: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct OAuthConfig {
+    /// The Google application
+    pub google: OAuthProviderConfig,
+
+    /// The Discord application
+    pub discord: OAuthProviderConfig,
+
+    /// The GitHub application
+    pub github: OAuthProviderConfig,
+}
+
+impl Default for OAuthConfig {
+    fn default() -> Self {
+        Self {
+            google: OAuthProviderConfig {
+                token_url: "https://oauth2.googleapis.com/token".to_string(),
+                user_url: "https://openidconnect.googleapis.com/v1/userinfo".to_string(),
+                ..Default::default()
+            },
+            discord: OAuthProviderConfig {
+                token_url: "https://discord.com/api/oauth2/token".to_string(),
+                user_url: "https://discord.com/api/users/@me".to_string(),
+                ..Default::default()
+            },
+            github: OAuthProviderConfig {
+                token_url: "https://github.com/login/oauth/access_token".to_string(),
+                user_url: "https://api.github.com/user".to_string(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct OAuthProviderConfig {
+    /// The client id of the application, logging in with the provider is disabled if empty
+    pub client_id: String,
+
+    /// The client secret of the application
+    pub client_secret: String,
+
+    /// The url authorization codes are exchanged for access tokens at
+    pub token_url: String,
+
+    /// The url the account of an access token is fetched from
+    pub user_url: String,
+}
+
+impl Default for OAuthProviderConfig {
+    fn default() -> Self {
+        Self {
+            client_id: String::new(),
+            client_secret: String::new(),
+            token_url: String::new(),
+            user_url: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ApprovalConfig {
+    /// How many seconds a second admin has to approve a destructive action before the proposal expires
+    pub window: u32,
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            window: 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ResidencyConfig {
+    /// The region of users who did not choose one, without regions everything is kept in the global storage
+    pub default_region: String,
+
+    /// The regions users can keep their data in, keyed by the region tag
+    pub regions: HashMap<String, RegionConfig>,
+}
+
+impl Default for ResidencyConfig {
+    fn default() -> Self {
+        Self {
+            default_region: String::new(),
+            regions: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct RegionConfig {
+    /// The object storage exports and recordings of the region are kept in
+    pub storage: StorageConfig,
+
+    /// The url of the edge which serves the streams and VODs of channels in the region
+    pub edge_url: String,
+}
+
+impl Default for RegionConfig {
+    fn default() -> Self {
+        Self {
+            storage: StorageConfig::default(),
+            edge_url: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct PasskeyConfig {
+    /// The WebAuthn relying party id, the domain of the website passkeys are registered on
+    pub rp_id: String,
+
+    /// The origins of the website, the browser reports the one a passkey was used on
+    pub origins: Vec<String>,
+
+    /// How many seconds a challenge can be answered
+    pub challenge_ttl: u32,
+
+    /// Whether admins need a passkey as second factor for admin mutations
+    pub required_for_admins: bool,
+}
+
+impl Default for PasskeyConfig {
+    fn default() -> Self {
+        Self {
+            rp_id: "localhost".to_string(),
+            origins: vec!["http://localhost:4000".to_string()],
+            challenge_ttl: 5 * 60,
+            required_for_admins: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct MediaRequestConfig {
+    /// The hosts viewers can request media from, their subdomains are allowed too
+    pub allowed_hosts: Vec<String>,
+
+    /// How many requests one viewer can have waiting in a channel at once
+    pub max_pending_per_viewer: u32,
+
+    /// How many requests can wait for approval in a channel at once
+    pub max_pending: u32,
+}
+
+impl Default for MediaRequestConfig {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: vec![
+                "youtube.com".to_string(),
+                "youtu.be".to_string(),
+                "soundcloud.com".to_string(),
+                "open.spotify.com".to_string(),
+            ],
+            max_pending_per_viewer: 3,
+            max_pending: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct StreamKeyConfig {
+    /// How many named stream keys a channel can have, besides its default key
+    pub max_keys: u32,
+
+    /// How long in seconds a regenerated key keeps working, so an encoder which reconnects is not cut off
+    pub rotation_grace: u32,
+}
+
+impl Default for StreamKeyConfig {
+    fn default() -> Self {
+        Self {
+            max_keys: 10,
+            rotation_grace: 15 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct SandboxStreamConfig {
+    /// The RTMP url of the ingest the test stream is sent to, without the stream key
+    pub ingest_url: String,
+
+    /// The ffmpeg binary which generates the test stream
+    pub ffmpeg_path: String,
+
+    /// How long to wait before restarting the test stream after ffmpeg exited in seconds
+    pub restart_delay: u32,
+}
+
+impl Default for SandboxStreamConfig {
+    fn default() -> Self {
+        Self {
+            ingest_url: "rtmp://127.0.0.1:1935/live".to_string(),
+            ffmpeg_path: "ffmpeg".to_string(),
+            restart_delay: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct DeprecatedSurfaceConfig {
+    /// A GraphQL field as `Type.field` or a REST endpoint as `METHOD /v1/path`, path parameters are written as `:name`
+    pub surface: String,
+
+    /// The day the deprecation was announced as YYYY-MM-DD
+    pub deprecated_at: String,
+
+    /// The day the field or endpoint is removed as YYYY-MM-DD
+    pub sunset: Option<String>,
+
+    /// What clients should use instead
+    pub replacement: Option<String>,
+}
+
+impl Default for DeprecatedSurfaceConfig {
+    fn default() -> Self {
+        Self {
+            surface: String::new(),
+            deprecated_at: String::new(),
+            sunset: None,
+            replacement: None,
         }
     }
 }
@@ -136,12 +1429,62 @@ impl Default for AppConfig {
             name: "scuffle-api".to_string(),
             logging: LoggingConfig::default(),
             api: ApiConfig::default(),
+            gql: GqlConfig::default(),
+            cookie_auth: CookieAuthConfig::default(),
+            security_headers: SecurityHeadersConfig {
+                route_policies: HashMap::from([(
+                    "/v1/gql/playground".to_string(),
+                    "default-src 'none'; script-src 'unsafe-inline' https://unpkg.com; style-src 'unsafe-inline' https://unpkg.com; font-src https://unpkg.com data:; img-src https://cdn.jsdelivr.net data:; connect-src 'self' ws: wss:".to_string(),
+                )]),
+                ..Default::default()
+            },
             database: DatabaseConfig::default(),
             grpc: GrpcConfig::default(),
             jwt: JwtConfig::default(),
             turnstile: TurnstileConfig::default(),
+            ip_reputation: IpReputationConfig::default(),
             rmq: RmqConfig::default(),
             redis: RedisConfig::default(),
+            revenue: RevenueConfig::default(),
+            payout: PayoutConfig::default(),
+            payment: PaymentConfig::default(),
+            emotes: EmoteConfig::default(),
+            image_processor: ImageProcessorConfig::default(),
+            control: ControlConfig::default(),
+            encryption: EncryptionConfig::default(),
+            obs: ObsConfig::default(),
+            notifications: NotificationsConfig::default(),
+            discord: DiscordConfig::default(),
+            import: ImportConfig::default(),
+            moderation: ModerationConfig::default(),
+            suspensions: SuspensionConfig::default(),
+            mail: MailConfig::default(),
+            login_links: LoginLinkConfig::default(),
+            password_resets: PasswordResetConfig::default(),
+            email_changes: EmailChangeConfig::default(),
+            invites: InviteConfig::default(),
+            elevation: ElevationConfig::default(),
+            display_colors: DisplayColorConfig::default(),
+            usernames: UsernameConfig::default(),
+            chat: ChatConfig::default(),
+            presence: PresenceConfig::default(),
+            subscriptions: SubscriptionConfig::default(),
+            deprecations: DeprecationConfig::default(),
+            vods: VodConfig::default(),
+            vod_downloads: VodDownloadConfig::default(),
+            vod_captions: VodCaptionConfig::default(),
+            reconciliation: ReconciliationConfig::default(),
+            digests: DigestConfig::default(),
+            data_exports: DataExportConfig::default(),
+            storage: StorageConfig::default(),
+            oauth: OAuthConfig::default(),
+            approvals: ApprovalConfig::default(),
+            residency: ResidencyConfig::default(),
+            passkeys: PasskeyConfig::default(),
+            media_requests: MediaRequestConfig::default(),
+            stream_keys: StreamKeyConfig::default(),
+            sandbox: false,
+            sandbox_stream: SandboxStreamConfig::default(),
         }
     }
 }
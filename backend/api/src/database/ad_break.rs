@@ -0,0 +1,36 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+/// The ad break lengths a channel can run, in seconds.
+pub const DURATIONS: [u32; 6] = [30, 60, 90, 120, 150, 180];
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// An ad break a channel started.
+pub struct Model {
+    /// The unique identifier for the ad break.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The length of the ad break in seconds.
+    pub duration_seconds: i64,
+    /// The time the ad break started.
+    pub started_at: DateTime<Utc>,
+}
+
+impl Model {
+    /// The time the channel can run the next ad break. The cooldown starts once this break is over.
+    pub fn next_allowed_at(&self, cooldown: Duration) -> DateTime<Utc> {
+        self.started_at + Duration::seconds(self.duration_seconds) + cooldown
+    }
+}
+
+/// Gets the most recent ad break of a channel.
+pub async fn last(db: &sqlx::PgPool, channel_id: Uuid) -> sqlx::Result<Option<Model>> {
+    sqlx::query_as!(
+        Model,
+        "SELECT * FROM ad_breaks WHERE channel_id = $1 ORDER BY started_at DESC LIMIT 1",
+        channel_id,
+    )
+    .fetch_optional(db)
+    .await
+}
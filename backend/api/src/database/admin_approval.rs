@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Kind {
+    #[default]
+    PermanentSuspension = 0,
+    PayoutVerification = 1,
+}
+
+impl From<i64> for Kind {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::PermanentSuspension,
+            1 => Self::PayoutVerification,
+            _ => Self::PermanentSuspension,
+        }
+    }
+}
+
+impl From<Kind> for i64 {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::PermanentSuspension => 0,
+            Kind::PayoutVerification => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Pending = 0,
+    Approved = 1,
+    Rejected = 2,
+    Expired = 3,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Pending,
+            1 => Self::Approved,
+            2 => Self::Rejected,
+            3 => Self::Expired,
+            _ => Self::Pending,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Pending => 0,
+            Status::Approved => 1,
+            Status::Rejected => 2,
+            Status::Expired => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A destructive admin action which one admin proposed and which only runs once a second admin approves it.
+pub struct Model {
+    /// The unique identifier for the approval.
+    pub id: Uuid,
+    /// What the action does.
+    pub kind: Kind,
+    /// The user to suspend or the payout method to verify.
+    pub target_id: Uuid,
+    /// The reason shown to the suspended user, or the review note of the payout method.
+    pub reason: String,
+    /// The status of the approval.
+    pub status: Status,
+    /// Foreign key to the users table, the admin who proposed the action. (None if their account was deleted)
+    pub proposed_by: Option<Uuid>,
+    /// Foreign key to the users table, the admin who approved or rejected the action.
+    pub decided_by: Option<Uuid>,
+    /// The time the action was proposed.
+    pub created_at: DateTime<Utc>,
+    /// The time the action can't be approved anymore.
+    pub expires_at: DateTime<Utc>,
+    /// The time the action was approved or rejected.
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+impl Model {
+    /// The status as admins see it. Pending approvals are only marked as expired once the target gets a new proposal.
+    pub fn current_status(&self) -> Status {
+        match self.status {
+            Status::Pending if self.expires_at <= Utc::now() => Status::Expired,
+            status => status,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Action {
+    #[default]
+    Proposed = 0,
+    Approved = 1,
+    Rejected = 2,
+}
+
+impl From<i64> for Action {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Proposed,
+            1 => Self::Approved,
+            2 => Self::Rejected,
+            _ => Self::Proposed,
+        }
+    }
+}
+
+impl From<Action> for i64 {
+    fn from(value: Action) -> Self {
+        match value {
+            Action::Proposed => 0,
+            Action::Approved => 1,
+            Action::Rejected => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// An entry in the audit trail of an admin approval.
+pub struct Model {
+    /// The unique identifier for the event.
+    pub id: Uuid,
+    /// Foreign key to the admin_approvals table.
+    pub approval_id: Uuid,
+    /// Foreign key to the users table, the admin who took the action. (None if their account was deleted)
+    pub actor_id: Option<Uuid>,
+    /// What happened to the approval.
+    pub action: Action,
+    /// The note given for the action.
+    pub note: String,
+    /// The time the action was taken.
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn record(
+    db: impl sqlx::PgExecutor<'_>,
+    approval_id: Uuid,
+    actor_id: Uuid,
+    action: Action,
+    note: &str,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "INSERT INTO admin_approval_events (approval_id, actor_id, action, note) VALUES ($1, $2, $3, $4)",
+        approval_id,
+        actor_id,
+        i64::from(action),
+        note,
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Pending = 0,
+    Approved = 1,
+    Rejected = 2,
+    /// An admin took the verification back after it was approved.
+    Revoked = 3,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Pending,
+            1 => Self::Approved,
+            2 => Self::Rejected,
+            3 => Self::Revoked,
+            _ => Self::Pending,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Pending => 0,
+            Status::Approved => 1,
+            Status::Rejected => 2,
+            Status::Revoked => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A bot account asking to be verified. Verified bots get a badge and can send more chat messages.
+pub struct Model {
+    /// The unique identifier for the application.
+    pub id: Uuid,
+    /// Foreign key to the users table, the bot account.
+    pub user_id: Uuid,
+    /// What the bot does.
+    pub description: String,
+    /// Where to find out more about the bot, empty if there is no website.
+    pub website_url: String,
+    /// The status of the application.
+    pub status: Status,
+    /// Foreign key to the users table, the admin who reviewed the application. (None if pending or their account was deleted)
+    pub reviewed_by: Option<Uuid>,
+    /// The note the admin left for the bot owner.
+    pub review_note: String,
+    /// The time the application was submitted.
+    pub created_at: DateTime<Utc>,
+    /// The time the application was reviewed or the verification revoked.
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::display_color::Color;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Layout {
+    #[default]
+    Standard = 0,
+    Theatre = 1,
+}
+
+impl From<i64> for Layout {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Standard,
+            1 => Self::Theatre,
+            _ => Self::Standard,
+        }
+    }
+}
+
+impl From<Layout> for i64 {
+    fn from(value: Layout) -> Self {
+        match value {
+            Layout::Standard => 0,
+            Layout::Theatre => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+/// How a channel's page looks. Channels without a row use the defaults.
+pub struct Model {
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The accent color, in the stored format of display colors. (empty for the site default)
+    pub accent_color: String,
+    /// The processed banner, its sizes are served from `banners/{channel_id}/{banner_id}`. (None if the channel has no banner)
+    pub banner_id: Option<Uuid>,
+    /// The image processor job of a new banner. (None if no banner is being processed)
+    pub banner_job_id: Option<Uuid>,
+    /// The id the new banner gets once its job has finished.
+    pub pending_banner_id: Option<Uuid>,
+    /// How the video and chat are arranged.
+    pub layout: Layout,
+    /// Whether the about panels are shown below the stream.
+    pub show_panels: bool,
+    /// Whether the schedule is shown below the stream.
+    pub show_schedule: bool,
+    /// The time the appearance was last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Model {
+    /// The appearance of a channel which never customized its page.
+    pub fn default_for(channel_id: Uuid) -> Self {
+        Self {
+            channel_id,
+            accent_color: String::new(),
+            banner_id: None,
+            banner_job_id: None,
+            pending_banner_id: None,
+            layout: Layout::Standard,
+            show_panels: true,
+            show_schedule: true,
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn accent_color(&self) -> Option<Color> {
+        Color::from_stored(&self.accent_color)
+    }
+}
+
+/// The prefix the sizes of a banner are stored under.
+pub fn banner_prefix(channel_id: Uuid, banner_id: Uuid) -> String {
+    format!("banners/{}/{}", channel_id, banner_id)
+}
+
+/// Gets the appearance of a channel, the defaults if it never customized its page.
+pub async fn for_channel(db: &sqlx::PgPool, channel_id: Uuid) -> sqlx::Result<Model> {
+    let appearance = sqlx::query_as!(
+        Model,
+        "SELECT * FROM channel_appearances WHERE channel_id = $1",
+        channel_id,
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(appearance.unwrap_or_else(|| Model::default_for(channel_id)))
+}
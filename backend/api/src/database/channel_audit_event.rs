@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Action {
+    #[default]
+    ChatArchiveEnabled = 0,
+    ChatArchiveDisabled = 1,
+    ModerationTwoFaEnabled = 2,
+    ModerationTwoFaDisabled = 3,
+}
+
+impl From<i64> for Action {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::ChatArchiveEnabled,
+            1 => Self::ChatArchiveDisabled,
+            2 => Self::ModerationTwoFaEnabled,
+            3 => Self::ModerationTwoFaDisabled,
+            _ => Self::ChatArchiveEnabled,
+        }
+    }
+}
+
+impl From<Action> for i64 {
+    fn from(value: Action) -> Self {
+        match value {
+            Action::ChatArchiveEnabled => 0,
+            Action::ChatArchiveDisabled => 1,
+            Action::ModerationTwoFaEnabled => 2,
+            Action::ModerationTwoFaDisabled => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// An entry in the audit log of a channel, kept for settings the community of a channel relies on.
+pub struct Model {
+    /// The unique identifier for the event.
+    pub id: Uuid,
+    /// Foreign key to the users table, the channel the setting belongs to.
+    pub channel_id: Uuid,
+    /// Foreign key to the users table, who changed the setting. (None if their account was deleted)
+    pub actor_id: Option<Uuid>,
+    /// What was changed.
+    pub action: Action,
+    /// The time the setting was changed.
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn record(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+    actor_id: Uuid,
+    action: Action,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "INSERT INTO channel_audit_events (channel_id, actor_id, action) VALUES ($1, $2, $3)",
+        channel_id,
+        actor_id,
+        i64::from(action),
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
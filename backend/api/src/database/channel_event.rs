@@ -0,0 +1,260 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Kind {
+    #[default]
+    Follow = 0,
+    Subscription = 1,
+    Raid = 2,
+    Cheer = 3,
+}
+
+impl From<i64> for Kind {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Follow,
+            1 => Self::Subscription,
+            2 => Self::Raid,
+            3 => Self::Cheer,
+            _ => Self::Follow,
+        }
+    }
+}
+
+impl From<Kind> for i64 {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::Follow => 0,
+            Kind::Subscription => 1,
+            Kind::Raid => 2,
+            Kind::Cheer => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// An event which happened in a channel and is shown as an alert by overlays.
+pub struct Model {
+    /// The unique identifier for the event.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The user who caused the event. (None if anonymous or deleted)
+    pub user_id: Option<Uuid>,
+    /// The kind of event.
+    pub kind: Kind,
+    /// Subscription months, raid viewers or cheered bits depending on the kind.
+    pub amount: i64,
+    /// The message attached to the event.
+    pub message: String,
+    /// The time the event happened.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A period in which a channel got far more follows than usual, e.g. during a follow-bot attack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FollowSpike {
+    /// The start of the first bucket of the spike.
+    pub starts_at: DateTime<Utc>,
+    /// The end of the last bucket of the spike.
+    pub ends_at: DateTime<Utc>,
+    /// The number of follows during the spike.
+    pub follows: i64,
+    /// The usual number of follows per bucket, the median of the analyzed period.
+    pub baseline: f64,
+}
+
+/// Finds the spikes in the follows of a channel, given the number of follows per bucket starting at `since`.
+/// A bucket is part of a spike if it has at least `min_follows` and `multiplier` times the baseline, adjacent ones are merged.
+pub fn follow_spikes(
+    since: DateTime<Utc>,
+    bucket: chrono::Duration,
+    counts: &[i64],
+    multiplier: f64,
+    min_follows: i64,
+) -> Vec<FollowSpike> {
+    let mut sorted = counts.to_vec();
+    sorted.sort_unstable();
+    let baseline = match sorted.len() {
+        0 => 0.0,
+        n if n % 2 == 0 => (sorted[n / 2 - 1] + sorted[n / 2]) as f64 / 2.0,
+        n => sorted[n / 2] as f64,
+    };
+
+    let threshold = (baseline * multiplier).max(min_follows as f64);
+
+    let mut spikes: Vec<FollowSpike> = Vec::new();
+    let mut previous = None;
+    for (i, &count) in counts.iter().enumerate() {
+        if (count as f64) < threshold {
+            continue;
+        }
+
+        let starts_at = since + bucket * i as i32;
+        match spikes.last_mut() {
+            Some(spike) if previous.map(|p| p + 1) == Some(i) => {
+                spike.ends_at = starts_at + bucket;
+                spike.follows += count;
+            }
+            _ => spikes.push(FollowSpike {
+                starts_at,
+                ends_at: starts_at + bucket,
+                follows: count,
+                baseline,
+            }),
+        }
+        previous = Some(i);
+    }
+
+    spikes
+}
+
+/// Whether a user follows a channel.
+pub async fn is_follower(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+    user_id: Uuid,
+) -> sqlx::Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM channel_events WHERE channel_id = $1 AND user_id = $2 AND kind = $3) AS "following!""#,
+        channel_id,
+        user_id,
+        i64::from(Kind::Follow),
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Whether a user subscribed to a channel, or was gifted a subscription, in the last 30 days.
+pub async fn is_subscriber(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+    user_id: Uuid,
+) -> sqlx::Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM channel_events WHERE channel_id = $1 AND user_id = $2 AND kind = $3 AND created_at > NOW() - INTERVAL '30 days') AS "subscribed!""#,
+        channel_id,
+        user_id,
+        i64::from(Kind::Subscription),
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// How a list of follows is ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FollowSort {
+    /// The last follow first.
+    Recent,
+    /// By username.
+    Alphabetical,
+    /// The live channels first, each group with the last follow first.
+    LiveFirst,
+}
+
+/// A follow seen from one side, the channel in a list of followed channels and the follower in a list of followers.
+#[derive(Debug, Clone)]
+pub struct Follow {
+    /// The user on the other side of the follow.
+    pub user_id: Uuid,
+    pub username: String,
+    /// Whether the user is live right now.
+    pub live: bool,
+    /// The time of the follow.
+    pub created_at: DateTime<Utc>,
+}
+
+/// The channels a user follows. A page starts after the follow of `after_id` at `after_time`,
+/// the username and live state of `after_id` are looked up for the other sorts.
+pub async fn following(
+    db: &sqlx::PgPool,
+    user_id: Uuid,
+    sort: FollowSort,
+    (after_time, after_id): (Option<DateTime<Utc>>, Option<Uuid>),
+    limit: i64,
+) -> sqlx::Result<Vec<Follow>> {
+    let follow = i64::from(Kind::Follow);
+
+    match sort {
+        FollowSort::Recent => sqlx::query_as!(
+            Follow,
+            r#"SELECT u.id AS user_id, u.username, EXISTS(SELECT 1 FROM streams s WHERE s.channel_id = u.id AND s.deleted = FALSE AND s.ended_at > NOW()) AS "live!", e.created_at FROM channel_events e JOIN users u ON u.id = e.channel_id WHERE e.user_id = $1 AND e.kind = $2 AND ($3::timestamptz IS NULL OR (e.created_at, u.id) < ($3, $4::uuid)) ORDER BY e.created_at DESC, u.id DESC LIMIT $5"#,
+            user_id,
+            follow,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(db)
+        .await,
+        FollowSort::Alphabetical => sqlx::query_as!(
+            Follow,
+            r#"SELECT u.id AS user_id, u.username, EXISTS(SELECT 1 FROM streams s WHERE s.channel_id = u.id AND s.deleted = FALSE AND s.ended_at > NOW()) AS "live!", e.created_at FROM channel_events e JOIN users u ON u.id = e.channel_id WHERE e.user_id = $1 AND e.kind = $2 AND ($3::uuid IS NULL OR u.username > (SELECT username FROM users WHERE id = $3)) ORDER BY u.username ASC LIMIT $4"#,
+            user_id,
+            follow,
+            after_id,
+            limit,
+        )
+        .fetch_all(db)
+        .await,
+        FollowSort::LiveFirst => sqlx::query_as!(
+            Follow,
+            r#"SELECT f.user_id AS "user_id!", f.username AS "username!", f.live AS "live!", f.created_at AS "created_at!" FROM (SELECT u.id AS user_id, u.username, EXISTS(SELECT 1 FROM streams s WHERE s.channel_id = u.id AND s.deleted = FALSE AND s.ended_at > NOW()) AS live, e.created_at FROM channel_events e JOIN users u ON u.id = e.channel_id WHERE e.user_id = $1 AND e.kind = $2) f WHERE ($3::timestamptz IS NULL OR (f.live, f.created_at, f.user_id) < (EXISTS(SELECT 1 FROM streams s WHERE s.channel_id = $4 AND s.deleted = FALSE AND s.ended_at > NOW()), $3, $4::uuid)) ORDER BY f.live DESC, f.created_at DESC, f.user_id DESC LIMIT $5"#,
+            user_id,
+            follow,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(db)
+        .await,
+    }
+}
+
+/// The followers of a channel, paged like `following`.
+pub async fn followers(
+    db: &sqlx::PgPool,
+    channel_id: Uuid,
+    sort: FollowSort,
+    (after_time, after_id): (Option<DateTime<Utc>>, Option<Uuid>),
+    limit: i64,
+) -> sqlx::Result<Vec<Follow>> {
+    let follow = i64::from(Kind::Follow);
+
+    match sort {
+        FollowSort::Recent => sqlx::query_as!(
+            Follow,
+            r#"SELECT u.id AS user_id, u.username, EXISTS(SELECT 1 FROM streams s WHERE s.channel_id = u.id AND s.deleted = FALSE AND s.ended_at > NOW()) AS "live!", e.created_at FROM channel_events e JOIN users u ON u.id = e.user_id WHERE e.channel_id = $1 AND e.kind = $2 AND ($3::timestamptz IS NULL OR (e.created_at, u.id) < ($3, $4::uuid)) ORDER BY e.created_at DESC, u.id DESC LIMIT $5"#,
+            channel_id,
+            follow,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(db)
+        .await,
+        FollowSort::Alphabetical => sqlx::query_as!(
+            Follow,
+            r#"SELECT u.id AS user_id, u.username, EXISTS(SELECT 1 FROM streams s WHERE s.channel_id = u.id AND s.deleted = FALSE AND s.ended_at > NOW()) AS "live!", e.created_at FROM channel_events e JOIN users u ON u.id = e.user_id WHERE e.channel_id = $1 AND e.kind = $2 AND ($3::uuid IS NULL OR u.username > (SELECT username FROM users WHERE id = $3)) ORDER BY u.username ASC LIMIT $4"#,
+            channel_id,
+            follow,
+            after_id,
+            limit,
+        )
+        .fetch_all(db)
+        .await,
+        FollowSort::LiveFirst => sqlx::query_as!(
+            Follow,
+            r#"SELECT f.user_id AS "user_id!", f.username AS "username!", f.live AS "live!", f.created_at AS "created_at!" FROM (SELECT u.id AS user_id, u.username, EXISTS(SELECT 1 FROM streams s WHERE s.channel_id = u.id AND s.deleted = FALSE AND s.ended_at > NOW()) AS live, e.created_at FROM channel_events e JOIN users u ON u.id = e.user_id WHERE e.channel_id = $1 AND e.kind = $2) f WHERE ($3::timestamptz IS NULL OR (f.live, f.created_at, f.user_id) < (EXISTS(SELECT 1 FROM streams s WHERE s.channel_id = $4 AND s.deleted = FALSE AND s.ended_at > NOW()), $3, $4::uuid)) ORDER BY f.live DESC, f.created_at DESC, f.user_id DESC LIMIT $5"#,
+            channel_id,
+            follow,
+            after_time,
+            after_id,
+            limit,
+        )
+        .fetch_all(db)
+        .await,
+    }
+}
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Platform {
+    #[default]
+    Twitch = 0,
+    Youtube = 1,
+}
+
+impl From<i64> for Platform {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Twitch,
+            1 => Self::Youtube,
+            _ => Self::Twitch,
+        }
+    }
+}
+
+impl From<Platform> for i64 {
+    fn from(value: Platform) -> Self {
+        match value {
+            Platform::Twitch => 0,
+            Platform::Youtube => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Queued = 0,
+    Running = 1,
+    Completed = 2,
+    Failed = 3,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Queued,
+            1 => Self::Running,
+            2 => Self::Completed,
+            3 => Self::Failed,
+            _ => Self::Queued,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Queued => 0,
+            Status::Running => 1,
+            Status::Completed => 2,
+            Status::Failed => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Step {
+    #[default]
+    Fetching = 0,
+    ProfileImage = 1,
+    Panels = 2,
+    Schedule = 3,
+    Emotes = 4,
+}
+
+impl From<i64> for Step {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Fetching,
+            1 => Self::ProfileImage,
+            2 => Self::Panels,
+            3 => Self::Schedule,
+            4 => Self::Emotes,
+            _ => Self::Fetching,
+        }
+    }
+}
+
+impl From<Step> for i64 {
+    fn from(value: Step) -> Self {
+        match value {
+            Step::Fetching => 0,
+            Step::ProfileImage => 1,
+            Step::Panels => 2,
+            Step::Schedule => 3,
+            Step::Emotes => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A background job importing a channel's profile image, panels, schedule and emotes from another platform.
+pub struct Model {
+    /// The unique identifier for the import.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The platform the channel is imported from.
+    pub platform: Platform,
+    /// The OAuth access token for the platform, encrypted with the encryption key. (None once the import has finished)
+    pub access_token_encrypted: Option<Vec<u8>>,
+    /// The status of the import.
+    pub status: Status,
+    /// What the import is currently importing.
+    pub step: Step,
+    /// The number of items found on the platform.
+    pub total_items: i64,
+    /// The number of items imported so far.
+    pub imported_items: i64,
+    /// The number of items which could not be imported, e.g. emotes with invalid names.
+    pub skipped_items: i64,
+    /// The reason the import failed.
+    pub error: String,
+    /// The time the import was started.
+    pub created_at: DateTime<Utc>,
+    /// The time the import last made progress.
+    pub updated_at: DateTime<Utc>,
+    /// The time the import finished.
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl Model {
+    /// The share of the found items which has been handled, from 0 to 1.
+    pub fn progress(&self) -> f64 {
+        match self.status {
+            Status::Completed => 1.0,
+            _ if self.total_items == 0 => 0.0,
+            _ => (self.imported_items + self.skipped_items) as f64 / self.total_items as f64,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A panel on a channel's about page.
+pub struct Model {
+    /// The unique identifier for the panel.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The position of the panel on the page, lowest first.
+    pub position: i64,
+    /// The title of the panel.
+    pub title: String,
+    /// The text of the panel.
+    pub description: String,
+    /// The url of the panel image.
+    pub image_url: Option<String>,
+    /// The url the panel links to.
+    pub link_url: Option<String>,
+    /// The time the panel was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// The most panels a channel can have on its about page.
+pub const MAX_PANELS: i64 = 20;
+
+pub const MAX_TITLE_LENGTH: usize = 64;
+pub const MAX_DESCRIPTION_LENGTH: usize = 5000;
+
+pub fn validate_title(title: &str) -> Result<(), &'static str> {
+    if title.chars().count() > MAX_TITLE_LENGTH {
+        return Err("Title must be at most 64 characters long");
+    }
+
+    Ok(())
+}
+
+/// Validates the text of a panel, before it is sanitized. The text is markdown and is sanitized like a bio.
+pub fn validate_description(description: &str) -> Result<(), &'static str> {
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err("Description must be at most 5000 characters long");
+    }
+
+    Ok(())
+}
+
+/// The prefix the image of a panel is stored under.
+pub fn image_prefix(channel_id: Uuid, upload_id: Uuid) -> String {
+    format!("panels/{}/{}", channel_id, upload_id)
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A planned stream on a channel's schedule.
+pub struct Model {
+    /// The unique identifier for the segment.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The title of the planned stream.
+    pub title: String,
+    /// The category of the planned stream.
+    pub category: String,
+    /// Whether the stream repeats every week.
+    pub recurring: bool,
+    /// The time the stream starts.
+    pub starts_at: DateTime<Utc>,
+    /// The time the stream ends. (None if open ended)
+    pub ends_at: Option<DateTime<Utc>>,
+    /// The time the segment was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Going live this long before a planned stream starts still counts as going live for it.
+fn early_start() -> Duration {
+    Duration::minutes(15)
+}
+
+/// How long an open ended stream is taken to last when looking for overlaps.
+fn open_ended_length() -> Duration {
+    Duration::hours(4)
+}
+
+impl Model {
+    /// The start and end of the occurrence which is running at `time`, or the next one if none is.
+    /// A segment which does not repeat only has one occurrence, which can also be in the past.
+    pub fn occurrence_at(&self, time: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let length = self
+            .ends_at
+            .map_or_else(open_ended_length, |ends_at| ends_at - self.starts_at);
+
+        let mut starts_at = self.starts_at;
+        if self.recurring && time > starts_at {
+            starts_at = starts_at + Duration::weeks((time - starts_at).num_weeks());
+            if starts_at + length <= time {
+                starts_at = starts_at + Duration::weeks(1);
+            }
+        }
+
+        (starts_at, starts_at + length)
+    }
+
+    /// Whether a stream going live at `time` is the one planned by this segment.
+    pub fn covers(&self, time: DateTime<Utc>) -> bool {
+        let (starts_at, ends_at) = self.occurrence_at(time);
+        starts_at - early_start() <= time && time < ends_at
+    }
+
+    /// Whether any occurrences of the two segments overlap.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        // Occurrences repeat the same way every week, so it is enough to look at
+        // the occurrences of each segment around the first occurrence of the other.
+        let hits = |a: &Self, b: &Self| {
+            let (b_starts_at, b_ends_at) = b.occurrence_at(b.starts_at);
+            let (a_starts_at, a_ends_at) = a.occurrence_at(b_starts_at);
+            a_starts_at < b_ends_at && b_starts_at < a_ends_at
+        };
+
+        hits(self, other) || hits(other, self)
+    }
+}
+
+/// The segments of a channel which have not ended yet, soonest first. Recurring segments are always included.
+pub async fn upcoming(db: impl sqlx::PgExecutor<'_>, channel_id: Uuid) -> sqlx::Result<Vec<Model>> {
+    sqlx::query_as!(
+        Model,
+        "SELECT * FROM channel_schedule_segments WHERE channel_id = $1 AND (recurring OR COALESCE(ends_at, starts_at) > NOW()) ORDER BY starts_at",
+        channel_id
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// The segment a stream of the channel going live at `time` was planned as.
+pub async fn covering(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+    time: DateTime<Utc>,
+) -> sqlx::Result<Option<Model>> {
+    let segments = sqlx::query_as!(
+        Model,
+        "SELECT * FROM channel_schedule_segments WHERE channel_id = $1 AND (recurring OR (starts_at <= $2 AND (ends_at IS NULL OR ends_at > $3))) ORDER BY starts_at",
+        channel_id,
+        time + early_start(),
+        time,
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(segments.into_iter().find(|segment| segment.covers(time)))
+}
@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// How a user relates to a channel, apart from following it.
+pub struct Model {
+    /// Foreign key to the users table, the channel.
+    pub channel_id: Uuid,
+    /// Foreign key to the users table, the viewer.
+    pub user_id: Uuid,
+    /// When the user muted the channel, None if it is not muted.
+    /// A muted channel is left out of the go-live emails and digests of the user, they still follow it.
+    pub muted_at: Option<DateTime<Utc>>,
+    /// When the user last chatted in the channel, only kept for verified bots to list the bots active in a channel.
+    pub bot_active_at: Option<DateTime<Utc>>,
+    /// Whether the user is notified when the channel goes live, they can turn it off for one channel and keep following it.
+    pub live_notifications: bool,
+    /// The time the row was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Whether a user muted a channel.
+pub async fn is_muted(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+    user_id: Uuid,
+) -> sqlx::Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM channel_users WHERE channel_id = $1 AND user_id = $2 AND muted_at IS NOT NULL) AS "muted!""#,
+        channel_id,
+        user_id,
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Whether a user is notified when a channel goes live, true unless they turned it off.
+pub async fn live_notifications(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+    user_id: Uuid,
+) -> sqlx::Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT NOT EXISTS(SELECT 1 FROM channel_users WHERE channel_id = $1 AND user_id = $2 AND live_notifications = FALSE) AS "enabled!""#,
+        channel_id,
+        user_id,
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Marks a verified bot as active in a channel.
+pub async fn record_bot_activity(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+    user_id: Uuid,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "INSERT INTO channel_users (channel_id, user_id, bot_active_at) VALUES ($1, $2, NOW()) ON CONFLICT (channel_id, user_id) DO UPDATE SET bot_active_at = EXCLUDED.bot_active_at",
+        channel_id,
+        user_id,
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::checkout;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A fundraiser a channel runs for a charity.
+pub struct Model {
+    /// The unique identifier for the campaign.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The title of the campaign.
+    pub title: String,
+    /// The charity which receives the donations.
+    pub beneficiary: String,
+    /// The amount the channel wants to raise in cents.
+    pub target_amount: i64,
+    /// The time the campaign was started.
+    pub created_at: DateTime<Utc>,
+    /// The time the campaign was ended.
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// The totals of the completed donations to a campaign.
+pub struct Totals {
+    /// The amount raised in cents.
+    pub raised_amount: i64,
+    /// The number of donations.
+    pub donation_count: i64,
+    /// The number of distinct users who donated.
+    pub donor_count: i64,
+    /// The largest single donation in cents.
+    pub largest_donation: i64,
+}
+
+/// Sums up the completed donations to a campaign.
+pub async fn totals(db: &sqlx::PgPool, campaign_id: Uuid) -> sqlx::Result<Totals> {
+    sqlx::query_as!(
+        Totals,
+        r#"SELECT
+            COALESCE(SUM(amount), 0)::bigint AS "raised_amount!",
+            COUNT(*)::bigint AS "donation_count!",
+            COUNT(DISTINCT user_id)::bigint AS "donor_count!",
+            COALESCE(MAX(amount), 0)::bigint AS "largest_donation!"
+        FROM charity_donations WHERE campaign_id = $1 AND status = $2"#,
+        campaign_id,
+        i64::from(checkout::Status::Completed),
+    )
+    .fetch_one(db)
+    .await
+}
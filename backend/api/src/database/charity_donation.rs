@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::checkout::Status;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A donation to a charity campaign, paid for at the payment provider like a checkout.
+pub struct Model {
+    /// The unique identifier for the donation.
+    pub id: Uuid,
+    /// Foreign key to the charity_campaigns table.
+    pub campaign_id: Uuid,
+    /// The user who donated.
+    pub user_id: Option<Uuid>,
+    /// The amount donated in cents.
+    pub amount: i64,
+    /// The message the donor left.
+    pub message: String,
+    /// The id of the checkout at the payment provider.
+    pub provider_checkout_id: String,
+    /// The status of the payment.
+    pub status: Status,
+    /// The time the donation was started.
+    pub created_at: DateTime<Utc>,
+    /// The time the payment completed or failed.
+    pub completed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A user who may not chat in a channel.
+pub struct Model {
+    /// Foreign key to the users table, the channel the user is banned from.
+    pub channel_id: Uuid,
+    /// Foreign key to the users table, the banned user.
+    pub user_id: Uuid,
+    /// Foreign key to the users table, the moderator who banned the user. (None if their account was deleted)
+    pub banned_by: Option<Uuid>,
+    /// The reason the user was banned.
+    pub reason: String,
+    /// The time the user was banned.
+    pub created_at: DateTime<Utc>,
+    /// The time the ban ends. (None if it is permanent)
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Whether the user may not chat in the channel, bans which ran out are left in the table until the user is banned again.
+pub async fn is_banned(db: &sqlx::PgPool, channel_id: Uuid, user_id: Uuid) -> sqlx::Result<bool> {
+    Ok(sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM chat_bans WHERE channel_id = $1 AND user_id = $2 AND (expires_at IS NULL OR expires_at > NOW()))",
+        channel_id,
+        user_id,
+    )
+    .fetch_one(db)
+    .await?
+    .unwrap_or(false))
+}
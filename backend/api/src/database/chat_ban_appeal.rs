@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Pending = 0,
+    Accepted = 1,
+    Denied = 2,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Pending,
+            1 => Self::Accepted,
+            2 => Self::Denied,
+            _ => Self::Pending,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Pending => 0,
+            Status::Accepted => 1,
+            Status::Denied => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A banned user asking to be unbanned from a channel. Each user can appeal once per channel.
+pub struct Model {
+    /// The unique identifier for the appeal.
+    pub id: Uuid,
+    /// Foreign key to the users table, the channel the user is banned from.
+    pub channel_id: Uuid,
+    /// Foreign key to the users table, the banned user.
+    pub user_id: Uuid,
+    /// Why the user should be unbanned.
+    pub message: String,
+    /// The status of the appeal.
+    pub status: Status,
+    /// Foreign key to the users table, the moderator who reviewed the appeal. (None if pending or their account was deleted)
+    pub reviewed_by: Option<Uuid>,
+    /// The note the moderator left for the user.
+    pub review_note: String,
+    /// The time the appeal was submitted.
+    pub created_at: DateTime<Utc>,
+    /// The time the appeal was reviewed.
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Action {
+    #[default]
+    Submitted = 0,
+    Accepted = 1,
+    Denied = 2,
+}
+
+impl From<i64> for Action {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Submitted,
+            1 => Self::Accepted,
+            2 => Self::Denied,
+            _ => Self::Submitted,
+        }
+    }
+}
+
+impl From<Action> for i64 {
+    fn from(value: Action) -> Self {
+        match value {
+            Action::Submitted => 0,
+            Action::Accepted => 1,
+            Action::Denied => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// An entry in the history of a ban appeal, kept so reviews can be audited later.
+pub struct Model {
+    /// The unique identifier for the event.
+    pub id: Uuid,
+    /// Foreign key to the chat_ban_appeals table.
+    pub appeal_id: Uuid,
+    /// Foreign key to the users table, who took the action. (None if their account was deleted)
+    pub actor_id: Option<Uuid>,
+    /// What happened to the appeal.
+    pub action: Action,
+    /// The message or review note of the action.
+    pub note: String,
+    /// The time the action was taken.
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// The longest name a command can have, without the leading `!`.
+pub const MAX_NAME_LENGTH: usize = 25;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+/// Who can use a command. Every level includes the ones above it, the broadcaster can use every command.
+pub enum Permission {
+    #[default]
+    Everyone = 0,
+    Vip = 1,
+    Broadcaster = 2,
+}
+
+impl From<i64> for Permission {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Everyone,
+            1 => Self::Vip,
+            2 => Self::Broadcaster,
+            _ => Self::Broadcaster,
+        }
+    }
+}
+
+impl From<Permission> for i64 {
+    fn from(value: Permission) -> Self {
+        match value {
+            Permission::Everyone => 0,
+            Permission::Vip => 1,
+            Permission::Broadcaster => 2,
+        }
+    }
+}
+
+impl Permission {
+    /// Whether a chatter with the given standing in the chat can use a command with this permission.
+    pub fn allows(&self, vip: bool, broadcaster: bool) -> bool {
+        match self {
+            Self::Everyone => true,
+            Self::Vip => vip || broadcaster,
+            Self::Broadcaster => broadcaster,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A command of a chat, a message starting with `!` and its name is answered with the response.
+pub struct Model {
+    /// The unique identifier for the command.
+    pub id: Uuid,
+    /// Foreign key to the users table, the channel the command belongs to.
+    pub channel_id: Uuid,
+    /// The name of the command, lowercase and without the leading `!`.
+    pub name: String,
+    /// The message the chat answers with.
+    pub response: String,
+    /// The seconds between two uses of the command in the chat, 0 if it can be used any time.
+    pub cooldown: i32,
+    /// Who can use the command.
+    pub permission: Permission,
+    /// How often the command was answered.
+    pub uses: i64,
+    /// The time the command was last answered.
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// The time the command was created.
+    pub created_at: DateTime<Utc>,
+    /// The time the command was last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The name of the command a message calls, lowercase. None if the message is not a command.
+/// Anything after the name is ignored, `!discord please` calls `discord`.
+pub fn parse(content: &str) -> Option<String> {
+    let name = content.strip_prefix('!')?.split_whitespace().next()?;
+    validate_name(name).ok()?;
+    Some(name.to_lowercase())
+}
+
+pub fn validate_name(name: &str) -> Result<(), &'static str> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err("Command name must be between 1 and 25 characters");
+    }
+
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err("Command name can only contain letters, numbers, _ and -");
+    }
+
+    Ok(())
+}
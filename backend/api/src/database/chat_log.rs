@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// A chat event as it was published, kept so clients can fetch the events they missed.
+pub struct Model {
+    /// The channel the event was published in.
+    pub channel_id: Uuid,
+    /// The position of the event in the chat of the channel.
+    pub sequence: i64,
+    /// The message the event is about.
+    pub message_id: Uuid,
+    /// The encoded event, without its sequence.
+    pub payload: Vec<u8>,
+    /// The time the event was published.
+    pub created_at: DateTime<Utc>,
+}
+
+/// The sequence of the last event published in the chat of a channel, 0 if there was none.
+pub async fn current_sequence(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+) -> sqlx::Result<i64> {
+    let sequence = sqlx::query_scalar!(
+        "SELECT sequence FROM chat_sequences WHERE channel_id = $1",
+        channel_id
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(sequence.unwrap_or_default())
+}
+
+/// Takes the next sequence of the channel for an event and stores the event with it.
+/// Both happen in one statement, so no two events can get the same sequence.
+pub async fn append(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+    message_id: Uuid,
+    payload: &[u8],
+) -> sqlx::Result<i64> {
+    sqlx::query_scalar!(
+        r#"WITH next AS (
+            INSERT INTO chat_sequences (channel_id, sequence) VALUES ($1, 1)
+            ON CONFLICT (channel_id) DO UPDATE SET sequence = chat_sequences.sequence + 1
+            RETURNING sequence
+        )
+        INSERT INTO chat_log (channel_id, sequence, message_id, payload)
+        SELECT $1, sequence, $2, $3 FROM next
+        RETURNING sequence"#,
+        channel_id,
+        message_id,
+        payload,
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Replaces the earlier events of a deleted message, so fetching missed events does not bring its content back.
+pub async fn redact(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+    message_id: Uuid,
+    before: i64,
+    payload: &[u8],
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "UPDATE chat_log SET payload = $4 WHERE channel_id = $1 AND message_id = $2 AND sequence < $3",
+        channel_id,
+        message_id,
+        before,
+        payload,
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Drops the events of a channel which fell out of the backfill.
+pub async fn prune(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+    up_to: i64,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "DELETE FROM chat_log WHERE channel_id = $1 AND sequence <= $2",
+        channel_id,
+        up_to,
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
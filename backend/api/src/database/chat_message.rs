@@ -14,3 +14,30 @@ pub struct Model {
     /// The time the message was created.
     pub created_at: DateTime<Utc>,
 }
+
+/// Top level domains which are mostly used for links, so `example.com` counts as a link even without a scheme.
+const LINK_TLDS: &[&str] = &[
+    "com", "net", "org", "io", "gg", "tv", "co", "me", "ly", "xyz", "info", "app", "dev",
+];
+
+/// Checks if a message contains a link, for chats which block them. Links are urls with a scheme,
+/// words starting with `www.` and bare domains with a common top level domain.
+pub fn contains_link(content: &str) -> bool {
+    content.split_whitespace().any(|word| {
+        let word = word
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+
+        if word.contains("://") || word.starts_with("www.") {
+            return true;
+        }
+
+        let host = word.split('/').next().unwrap_or_default();
+        match host.rsplit_once('.') {
+            Some((name, tld)) => {
+                !name.is_empty() && !name.ends_with('.') && LINK_TLDS.contains(&tld)
+            }
+            None => false,
+        }
+    })
+}
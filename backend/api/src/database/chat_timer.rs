@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// The longest name a timer can have.
+pub const MAX_NAME_LENGTH: usize = 25;
+
+#[derive(Debug, Clone, Default)]
+/// A timer of a chat, the message is posted every interval while the channel is live.
+pub struct Model {
+    /// The unique identifier for the timer.
+    pub id: Uuid,
+    /// Foreign key to the users table, the channel the timer belongs to.
+    pub channel_id: Uuid,
+    /// The name of the timer, lowercase. Only the broadcaster sees it.
+    pub name: String,
+    /// The message the timer posts.
+    pub message: String,
+    /// The seconds between two posts of the timer.
+    pub interval: i32,
+    /// How many chat events have to happen after a post before the timer posts again.
+    pub min_messages: i32,
+    /// Whether the timer posts at all.
+    pub enabled: bool,
+    /// The sequence of the chat when the timer last posted, or when it was created.
+    pub last_sequence: i64,
+    /// The time the timer last posted.
+    pub last_posted_at: Option<DateTime<Utc>>,
+    /// The time the timer was created.
+    pub created_at: DateTime<Utc>,
+    /// The time the timer was last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+pub fn validate_name(name: &str) -> Result<(), &'static str> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err("Timer name must be between 1 and 25 characters");
+    }
+
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err("Timer name can only contain letters, numbers, _ and -");
+    }
+
+    Ok(())
+}
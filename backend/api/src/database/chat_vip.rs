@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// A user the broadcaster made a VIP of their chat. VIPs get a badge and are not held to slow mode or link blocking.
+pub struct Model {
+    /// Foreign key to the users table, the channel the user is a VIP in.
+    pub channel_id: Uuid,
+    /// Foreign key to the users table, the VIP.
+    pub user_id: Uuid,
+    /// Foreign key to the users table, who made the user a VIP. (None if their account was deleted)
+    pub granted_by: Option<Uuid>,
+    /// The time the user was made a VIP.
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn is_vip(db: &sqlx::PgPool, channel_id: Uuid, user_id: Uuid) -> sqlx::Result<bool> {
+    Ok(sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM chat_vips WHERE channel_id = $1 AND user_id = $2)",
+        channel_id,
+        user_id,
+    )
+    .fetch_one(db)
+    .await?
+    .unwrap_or(false))
+}
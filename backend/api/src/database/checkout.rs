@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::revenue_transaction;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Kind {
+    #[default]
+    Subscription = 0,
+    GiftSubscription = 1,
+    Cheer = 2,
+}
+
+impl From<i64> for Kind {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Subscription,
+            1 => Self::GiftSubscription,
+            2 => Self::Cheer,
+            _ => Self::Subscription,
+        }
+    }
+}
+
+impl From<Kind> for i64 {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::Subscription => 0,
+            Kind::GiftSubscription => 1,
+            Kind::Cheer => 2,
+        }
+    }
+}
+
+impl From<Kind> for revenue_transaction::Kind {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::Subscription => Self::Subscription,
+            Kind::GiftSubscription => Self::Gift,
+            Kind::Cheer => Self::Bits,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Pending = 0,
+    Completed = 1,
+    Failed = 2,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Pending,
+            1 => Self::Completed,
+            2 => Self::Failed,
+            _ => Self::Pending,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Pending => 0,
+            Status::Completed => 1,
+            Status::Failed => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A purchase which was started by a user and is paid for at the payment provider.
+pub struct Model {
+    /// The unique identifier for the checkout.
+    pub id: Uuid,
+    /// The channel the purchase is for.
+    pub channel_id: Uuid,
+    /// The user who pays.
+    pub user_id: Uuid,
+    /// The user who receives the gift subscription.
+    pub recipient_id: Option<Uuid>,
+    /// What is being bought.
+    pub kind: Kind,
+    /// The amount to pay in cents, after promotions. For cheers this is also the amount of bits.
+    pub amount: i64,
+    /// The promotion which was applied.
+    pub promotion_id: Option<Uuid>,
+    /// The id of the checkout at the payment provider.
+    pub provider_checkout_id: String,
+    /// The status of the checkout.
+    pub status: Status,
+    /// The time the checkout was created.
+    pub created_at: DateTime<Utc>,
+    /// The time the payment completed or failed.
+    pub completed_at: Option<DateTime<Utc>>,
+    /// The message sent with a cheer.
+    pub message: String,
+}
@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// The animation shown for cheers of at least a certain amount of bits.
+pub struct Model {
+    /// The unique identifier for the tier.
+    pub id: Uuid,
+    /// The channel the tier belongs to, or none for the default tiers.
+    pub channel_id: Option<Uuid>,
+    /// The word typed in front of the amount, e.g. `Cheer` in `Cheer100`.
+    pub prefix: String,
+    /// The minimum amount of bits for this tier.
+    pub min_bits: i64,
+    /// The color of the tier as hex.
+    pub color: String,
+    /// The url of the animated image.
+    pub image_url: String,
+    /// The time the tier was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Finds the tier a cheer belongs to. If the channel has its own tiers they replace the default ones.
+pub fn resolve(tiers: &[Model], bits: i64) -> Option<&Model> {
+    let has_channel_tiers = tiers.iter().any(|t| t.channel_id.is_some());
+
+    tiers
+        .iter()
+        .filter(|t| t.channel_id.is_some() == has_channel_tiers)
+        .filter(|t| t.min_bits <= bits)
+        .max_by_key(|t| t.min_bits)
+}
+
+/// Gets the tiers of a channel together with the default tiers.
+pub async fn for_channel(db: &sqlx::PgPool, channel_id: Uuid) -> sqlx::Result<Vec<Model>> {
+    sqlx::query_as!(
+        Model,
+        "SELECT * FROM cheermote_tiers WHERE channel_id = $1 OR channel_id IS NULL ORDER BY min_bits ASC",
+        channel_id,
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Validates a cheermote prefix, which has to be 1 to 32 ascii letters.
+pub fn validate_prefix(prefix: &str) -> Result<(), &'static str> {
+    if prefix.is_empty() || prefix.len() > 32 {
+        return Err("Prefix must be between 1 and 32 characters");
+    }
+
+    if !prefix.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err("Prefix can only contain letters");
+    }
+
+    Ok(())
+}
+
+/// Validates a tier color, which has to be a hex color like `#9146ff`.
+pub fn validate_color(color: &str) -> Result<(), &'static str> {
+    match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => Ok(()),
+        _ => Err("Color must be a hex color like #9146ff"),
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Queued = 0,
+    Running = 1,
+    Completed = 2,
+    Failed = 3,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Queued,
+            1 => Self::Running,
+            2 => Self::Completed,
+            3 => Self::Failed,
+            _ => Self::Queued,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Queued => 0,
+            Status::Running => 1,
+            Status::Completed => 2,
+            Status::Failed => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A background job collecting everything stored about a user into an archive they can download.
+pub struct Model {
+    /// The unique identifier for the export.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// The status of the export.
+    pub status: Status,
+    /// The key of the archive in the object storage. (empty until the export completed)
+    pub object_key: String,
+    /// The size of the archive.
+    pub size_bytes: i64,
+    /// The reason the export failed.
+    pub error: String,
+    /// The time the export was requested.
+    pub created_at: DateTime<Utc>,
+    /// The time the export last changed.
+    pub updated_at: DateTime<Utc>,
+    /// The time the export finished.
+    pub completed_at: Option<DateTime<Utc>>,
+    /// The time the archive can't be downloaded anymore. (None until the export completed)
+    pub expires_at: Option<DateTime<Utc>>,
+    /// The region tag of the object storage the archive was uploaded to.
+    pub region: String,
+}
+
+impl Model {
+    /// Whether the archive can be downloaded.
+    pub fn downloadable(&self, now: DateTime<Utc>) -> bool {
+        self.status == Status::Completed && self.expires_at.map_or(false, |at| at > now)
+    }
+}
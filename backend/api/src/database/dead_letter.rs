@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use prost::Message;
+use uuid::Uuid;
+
+use crate::pb::scuffle::events::{
+    ChannelImportJob, ChannelNotification, DataExportJob, ImageProcessorJobResult, ModerationJob,
+    VodCaptionJobResult, VodDownloadJobResult,
+};
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Consumer {
+    #[default]
+    Notifications = 0,
+    Moderation = 1,
+    Import = 2,
+    ImageProcessorResults = 3,
+    DataExport = 4,
+    VodDownloadResults = 5,
+    VodCaptionResults = 6,
+}
+
+impl From<i64> for Consumer {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Notifications,
+            1 => Self::Moderation,
+            2 => Self::Import,
+            3 => Self::ImageProcessorResults,
+            4 => Self::DataExport,
+            5 => Self::VodDownloadResults,
+            6 => Self::VodCaptionResults,
+            _ => Self::Notifications,
+        }
+    }
+}
+
+impl From<Consumer> for i64 {
+    fn from(value: Consumer) -> Self {
+        match value {
+            Consumer::Notifications => 0,
+            Consumer::Moderation => 1,
+            Consumer::Import => 2,
+            Consumer::ImageProcessorResults => 3,
+            Consumer::DataExport => 4,
+            Consumer::VodDownloadResults => 5,
+            Consumer::VodCaptionResults => 6,
+        }
+    }
+}
+
+impl Consumer {
+    /// Decodes a payload with the protobuf message the consumer reads, so operators can see what a message was about.
+    pub fn decode(&self, payload: &[u8]) -> Result<String, prost::DecodeError> {
+        Ok(match self {
+            Self::Notifications => format!("{:?}", ChannelNotification::decode(payload)?),
+            Self::Moderation => format!("{:?}", ModerationJob::decode(payload)?),
+            Self::Import => format!("{:?}", ChannelImportJob::decode(payload)?),
+            Self::ImageProcessorResults => {
+                format!("{:?}", ImageProcessorJobResult::decode(payload)?)
+            }
+            Self::DataExport => format!("{:?}", DataExportJob::decode(payload)?),
+            Self::VodDownloadResults => format!("{:?}", VodDownloadJobResult::decode(payload)?),
+            Self::VodCaptionResults => format!("{:?}", VodCaptionJobResult::decode(payload)?),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A message a consumer failed to handle, kept until an operator retries or discards it.
+pub struct Model {
+    /// The unique identifier for the dead letter.
+    pub id: Uuid,
+    /// The consumer which failed to handle the message.
+    pub consumer: Consumer,
+    /// The message as it was delivered.
+    pub payload: Vec<u8>,
+    /// The error of the last failed attempt.
+    pub error: String,
+    /// The number of times handling the message failed.
+    pub attempts: i64,
+    /// The time the message first failed.
+    pub created_at: DateTime<Utc>,
+    /// The time the message last failed.
+    pub failed_at: DateTime<Utc>,
+    /// The time the message was last published again.
+    pub retried_at: Option<DateTime<Utc>>,
+    /// The time an operator dropped the message.
+    pub discarded_at: Option<DateTime<Utc>>,
+}
+
+/// Stores a failed message. A message which was retried from a dead letter carries its id,
+/// so failing again counts another attempt on that dead letter instead of adding a new one.
+pub async fn record(
+    db: impl sqlx::PgExecutor<'_>,
+    id: Option<Uuid>,
+    consumer: Consumer,
+    payload: &[u8],
+    error: &str,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "INSERT INTO dead_letters (id, consumer, payload, error) VALUES (COALESCE($1, gen_random_uuid()), $2, $3, $4) ON CONFLICT (id) DO UPDATE SET error = EXCLUDED.error, attempts = dead_letters.attempts + 1, failed_at = NOW(), retried_at = NULL",
+        id,
+        i64::from(consumer),
+        payload,
+        error,
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Counts a use of a deprecated field or endpoint by a client for today.
+pub async fn record(
+    db: &sqlx::PgPool,
+    surface: &str,
+    client: &str,
+    user_id: Option<Uuid>,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "INSERT INTO deprecated_usage_daily (surface, client, user_id, day, uses) VALUES ($1, $2, $3, date_trunc('day', NOW()), 1) ON CONFLICT (surface, client, day) DO UPDATE SET uses = deprecated_usage_daily.uses + 1, last_used_at = NOW()",
+        surface,
+        client,
+        user_id,
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+/// How often a client used a deprecated field or endpoint over a period of time.
+pub struct ClientUsage {
+    /// The client, see the `client` column.
+    pub client: String,
+    /// The user the client belongs to. (None if anonymous)
+    pub user_id: Option<Uuid>,
+    /// The total number of uses.
+    pub uses: i64,
+    /// The last time the client used it.
+    pub last_used_at: DateTime<Utc>,
+}
+
+/// Sums up the use of a deprecated field or endpoint per client over the last days, most uses first.
+pub async fn by_client(
+    db: &sqlx::PgPool,
+    surface: &str,
+    days: i64,
+) -> sqlx::Result<Vec<ClientUsage>> {
+    sqlx::query_as!(
+        ClientUsage,
+        r#"SELECT
+            client,
+            user_id,
+            SUM(uses)::bigint AS "uses!",
+            MAX(last_used_at) AS "last_used_at!"
+        FROM deprecated_usage_daily
+        WHERE surface = $1 AND day > NOW() - make_interval(days => $2::int)
+        GROUP BY client, user_id
+        ORDER BY 3 DESC, client ASC"#,
+        surface,
+        days,
+    )
+    .fetch_all(db)
+    .await
+}
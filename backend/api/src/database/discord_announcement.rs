@@ -0,0 +1,102 @@
+use uuid::Uuid;
+
+pub const MAX_TEMPLATE_LENGTH: usize = 2000;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq, Hash)]
+#[repr(i64)]
+pub enum Kind {
+    #[default]
+    GoLive = 0,
+    Offline = 1,
+    VodPublished = 2,
+}
+
+impl From<i64> for Kind {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::GoLive,
+            1 => Self::Offline,
+            2 => Self::VodPublished,
+            _ => Self::GoLive,
+        }
+    }
+}
+
+impl From<Kind> for i64 {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::GoLive => 0,
+            Kind::Offline => 1,
+            Kind::VodPublished => 2,
+        }
+    }
+}
+
+impl Kind {
+    pub const ALL: [Self; 3] = [Self::GoLive, Self::Offline, Self::VodPublished];
+
+    /// The template used until the channel writes its own.
+    pub fn default_template(self) -> &'static str {
+        match self {
+            Self::GoLive => "{channel} is now live: {title}\n{url}",
+            Self::Offline => "{channel} went offline. Thanks for watching!",
+            Self::VodPublished => "The recording of {title} is now available\n{url}",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// How a channel announces one kind of notification on Discord.
+pub struct Model {
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The notification this announces.
+    pub kind: Kind,
+    /// Whether the announcement is posted.
+    pub enabled: bool,
+    /// The message, with variables like `{title}` replaced when posting.
+    pub template: String,
+}
+
+impl Model {
+    /// The announcement of a channel which has not configured this kind yet.
+    pub fn default_for(channel_id: Uuid, kind: Kind) -> Self {
+        Self {
+            channel_id,
+            kind,
+            enabled: true,
+            template: kind.default_template().to_string(),
+        }
+    }
+}
+
+/// Gets the announcement settings of a channel, falling back to the defaults for kinds it has not configured.
+pub async fn for_channel(db: &sqlx::PgPool, channel_id: Uuid) -> sqlx::Result<Vec<Model>> {
+    let configured = sqlx::query_as!(
+        Model,
+        "SELECT * FROM discord_announcements WHERE channel_id = $1",
+        channel_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(Kind::ALL
+        .into_iter()
+        .map(|kind| {
+            configured
+                .iter()
+                .find(|a| a.kind == kind)
+                .cloned()
+                .unwrap_or_else(|| Model::default_for(channel_id, kind))
+        })
+        .collect())
+}
+
+/// Validates an announcement template.
+pub fn validate_template(template: &str) -> Result<(), &'static str> {
+    if template.trim().is_empty() || template.chars().count() > MAX_TEMPLATE_LENGTH {
+        return Err("Template must be between 1 and 2000 characters");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// Where a channel's Discord announcements are posted, either through a webhook or by a bot.
+pub struct Model {
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The webhook url, encrypted with the encryption key. (None if a bot posts the announcements)
+    pub webhook_url_encrypted: Option<Vec<u8>>,
+    /// The bot token, encrypted with the encryption key. (None if a webhook is used)
+    pub bot_token_encrypted: Option<Vec<u8>>,
+    /// The id of the Discord channel the bot posts in.
+    pub bot_channel_id: Option<String>,
+    /// The reason the last announcement could not be posted.
+    pub last_error: String,
+    /// The time the integration was last changed.
+    pub updated_at: DateTime<Utc>,
+    /// The time the integration was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Validates a Discord webhook url. The API posts to it, so it has to point at Discord.
+pub fn validate_webhook_url(url: &str) -> Result<(), &'static str> {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return Err("Webhook url must be a valid url");
+    };
+
+    let discord_host = matches!(
+        url.host_str(),
+        Some("discord.com" | "discordapp.com" | "canary.discord.com" | "ptb.discord.com")
+    );
+
+    if url.scheme() != "https" || !discord_host || !url.path().starts_with("/api/webhooks/") {
+        return Err("Webhook url must be a Discord webhook url");
+    }
+
+    Ok(())
+}
+
+/// Validates the id of the Discord channel a bot posts in.
+pub fn validate_bot_channel_id(id: &str) -> Result<(), &'static str> {
+    if id.is_empty() || id.len() > 20 || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err("Discord channel id must be a numeric id");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use super::user;
+use crate::pb;
+
+/// The page background of the light theme.
+pub const LIGHT_BACKGROUND: Rgb = Rgb(0xff, 0xff, 0xff);
+/// The page background of the dark theme.
+pub const DARK_BACKGROUND: Rgb = Rgb(0x18, 0x18, 0x1b);
+
+/// The contrast a display name needs against the background to stay readable, WCAG's minimum for large text.
+pub const MIN_CONTRAST: f64 = 3.0;
+
+/// The named colors users can pick from, with a variant for each theme.
+pub const PALETTE: &[(&str, Rgb, Rgb)] = &[
+    ("red", Rgb(0xd3, 0x2f, 0x2f), Rgb(0xff, 0x6b, 0x6b)),
+    ("orange", Rgb(0xe6, 0x51, 0x00), Rgb(0xff, 0xa9, 0x4d)),
+    ("yellow", Rgb(0x8d, 0x6e, 0x00), Rgb(0xff, 0xd4, 0x3b)),
+    ("green", Rgb(0x2e, 0x7d, 0x32), Rgb(0x69, 0xdb, 0x7c)),
+    ("teal", Rgb(0x00, 0x79, 0x6b), Rgb(0x38, 0xd9, 0xa9)),
+    ("blue", Rgb(0x15, 0x65, 0xc0), Rgb(0x74, 0xc0, 0xfc)),
+    ("purple", Rgb(0x6a, 0x1b, 0x9a), Rgb(0xb1, 0x97, 0xfc)),
+    ("pink", Rgb(0xad, 0x14, 0x57), Rgb(0xf7, 0x83, 0xac)),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// Parses a hex color like `#9146ff`.
+    pub fn parse_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+
+        Some(Self(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    /// The relative luminance as defined by WCAG, from 0 for black to 1 for white.
+    pub fn luminance(self) -> f64 {
+        let linear = |c: u8| {
+            let c = c as f64 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        0.2126 * linear(self.0) + 0.7152 * linear(self.1) + 0.0722 * linear(self.2)
+    }
+
+    /// The WCAG contrast ratio between two colors, from 1 for the same color to 21 for black on white.
+    pub fn contrast(self, other: Rgb) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+}
+
+/// A color with a variant for each theme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Color {
+    /// The palette name, None for custom colors.
+    pub name: Option<String>,
+    pub light: Rgb,
+    pub dark: Rgb,
+}
+
+impl Color {
+    /// Parses a palette name or a hex color. A custom dark variant can be given for hex colors,
+    /// otherwise the same color is used on both themes.
+    pub fn parse(color: &str, dark: Option<&str>) -> Result<Self, &'static str> {
+        let color = color.trim().to_lowercase();
+
+        if let Some((name, light, palette_dark)) = PALETTE.iter().find(|(n, ..)| *n == color) {
+            if dark.is_some() {
+                return Err("Palette colors already have a dark variant");
+            }
+
+            return Ok(Self {
+                name: Some(name.to_string()),
+                light: *light,
+                dark: *palette_dark,
+            });
+        }
+
+        let light = Rgb::parse_hex(&color)
+            .ok_or("Color must be a palette color or a hex color like #9146ff")?;
+        let dark = match dark {
+            Some(dark) => Rgb::parse_hex(&dark.trim().to_lowercase())
+                .ok_or("Dark color must be a hex color like #9146ff")?,
+            None => light,
+        };
+
+        Ok(Self {
+            name: None,
+            light,
+            dark,
+        })
+    }
+
+    /// Loads a color which was stored with `to_stored`. Empty means no color.
+    pub fn from_stored(stored: &str) -> Option<Self> {
+        let (color, dark) = match stored.split_once('/') {
+            Some((color, dark)) => (color, Some(dark)),
+            None => (stored, None),
+        };
+
+        Self::parse(color, dark).ok()
+    }
+
+    /// Palette colors are stored by name, so changes to the palette apply to everyone who picked them.
+    pub fn to_stored(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None if self.light == self.dark => self.light.to_hex(),
+            None => format!("{}/{}", self.light.to_hex(), self.dark.to_hex()),
+        }
+    }
+
+    /// Checks that both variants are readable on the background of their theme.
+    pub fn check_contrast(&self) -> Result<(), &'static str> {
+        if self.light.contrast(LIGHT_BACKGROUND) < MIN_CONTRAST {
+            return Err("Color is not readable on the light theme");
+        }
+
+        if self.dark.contrast(DARK_BACKGROUND) < MIN_CONTRAST {
+            return Err("Color is not readable on the dark theme");
+        }
+
+        Ok(())
+    }
+}
+
+/// The color of a display name, a solid color or a gradient between two colors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayColor {
+    pub color: Color,
+    pub gradient_end: Option<Color>,
+}
+
+impl DisplayColor {
+    /// Reads a stored color. The gradient is dropped for users who are no longer allowed to use one.
+    pub fn from_stored(color: &str, gradient_end: &str, gradient_allowed: bool) -> Option<Self> {
+        Some(Self {
+            color: Color::from_stored(color)?,
+            gradient_end: Color::from_stored(gradient_end).filter(|_| gradient_allowed),
+        })
+    }
+
+    pub fn of_user(user: &user::Model, gradient_allowed: bool) -> Option<Self> {
+        Self::from_stored(
+            &user.display_color,
+            &user.display_gradient_end,
+            gradient_allowed,
+        )
+    }
+}
+
+impl From<&Color> for pb::scuffle::events::ChatColor {
+    fn from(color: &Color) -> Self {
+        Self {
+            name: color.name.clone(),
+            light: color.light.to_hex(),
+            dark: color.dark.to_hex(),
+        }
+    }
+}
+
+impl From<&DisplayColor> for pb::scuffle::events::ChatDisplayColor {
+    fn from(color: &DisplayColor) -> Self {
+        Self {
+            color: Some((&color.color).into()),
+            gradient_end: color.gradient_end.as_ref().map(Into::into),
+        }
+    }
+}
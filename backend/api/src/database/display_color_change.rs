@@ -0,0 +1,70 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::config::DisplayColorConfig;
+
+#[derive(Debug, Clone, Default)]
+/// A change of the display color of a user, kept to limit how often colors can be changed.
+pub struct Model {
+    /// The unique identifier for the change.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// The stored color the user changed to. (empty if removed)
+    pub display_color: String,
+    /// The stored gradient end the user changed to. (empty if none)
+    pub display_gradient_end: String,
+    /// The time the color was changed.
+    pub created_at: DateTime<Utc>,
+}
+
+/// The changes of a user within the rate limit window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecentChanges {
+    pub count: i64,
+    pub oldest: Option<DateTime<Utc>>,
+    pub newest: Option<DateTime<Utc>>,
+}
+
+impl RecentChanges {
+    pub async fn of_user(
+        db: impl sqlx::PgExecutor<'_>,
+        user_id: Uuid,
+        window: u32,
+    ) -> sqlx::Result<Self> {
+        let row = sqlx::query!(
+            r#"SELECT COUNT(*) AS "count!", MIN(created_at) AS oldest, MAX(created_at) AS newest FROM display_color_changes WHERE user_id = $1 AND created_at > NOW() - $2 * INTERVAL '1 second'"#,
+            user_id,
+            window as i64,
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(Self {
+            count: row.count,
+            oldest: row.oldest,
+            newest: row.newest,
+        })
+    }
+
+    /// The time the next change is allowed, None if it is allowed now.
+    /// The cooldown stops quick cycling between colors, the window caps slower cycling over the day.
+    pub fn retry_at(
+        &self,
+        config: &DisplayColorConfig,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let cooldown_end = self
+            .newest
+            .map(|newest| newest + Duration::seconds(config.cooldown as i64))
+            .filter(|end| *end > now);
+
+        let window_end = self
+            .oldest
+            .filter(|_| self.count >= config.max_changes as i64)
+            .map(|oldest| oldest + Duration::seconds(config.rate_limit_window as i64))
+            .filter(|end| *end > now);
+
+        cooldown_end.max(window_end)
+    }
+}
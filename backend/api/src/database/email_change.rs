@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// A requested change of a user's email. The new address has to confirm it before `users.email` changes,
+/// and the old address gets a link to undo it. Tokens are generated and hashed like login link tokens.
+pub struct Model {
+    /// The unique identifier for the change.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// The encrypted email the user had when the change was requested.
+    pub old_email: String,
+    /// The encrypted email the user wants.
+    pub new_email: String,
+    /// The keyed hash of the new email.
+    pub new_email_hash: String,
+    /// The sha256 hash of the token sent to the new email as hex.
+    pub confirm_token_hash: String,
+    /// The sha256 hash of the token sent to the old email as hex.
+    pub revert_token_hash: String,
+    /// The time the change was requested.
+    pub created_at: DateTime<Utc>,
+    /// The time the confirmation token expires.
+    pub expires_at: DateTime<Utc>,
+    /// The time the revert token expires.
+    pub revert_expires_at: DateTime<Utc>,
+    /// The time the new email was confirmed. (None if not yet)
+    pub confirmed_at: Option<DateTime<Utc>>,
+    /// The time the old email undid the change. (None if it did not)
+    pub reverted_at: Option<DateTime<Utc>>,
+    /// The time a newer change replaced this one before it was confirmed. (None if none did)
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+impl Model {
+    /// Whether the change still waits for the new email to confirm it.
+    pub fn is_pending(&self, now: DateTime<Utc>) -> bool {
+        self.confirmed_at.is_none()
+            && self.reverted_at.is_none()
+            && self.cancelled_at.is_none()
+            && self.expires_at > now
+    }
+}
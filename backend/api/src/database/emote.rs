@@ -0,0 +1,200 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::revenue_transaction;
+use crate::config::EmoteConfig;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    PendingReview = 0,
+    Approved = 1,
+    Rejected = 2,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::PendingReview,
+            1 => Self::Approved,
+            2 => Self::Rejected,
+            _ => Self::PendingReview,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::PendingReview => 0,
+            Status::Approved => 1,
+            Status::Rejected => 2,
+        }
+    }
+}
+
+impl Status {
+    /// Whether a review can move an emote in this state to `to`. Approved emotes can still be taken down,
+    /// rejected ones only come back by submitting them again.
+    pub fn can_become(self, to: Status) -> bool {
+        match self {
+            Status::PendingReview => matches!(to, Status::Approved | Status::Rejected),
+            Status::Approved => to == Status::Rejected,
+            Status::Rejected => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// An emote uploaded by a channel. Pending and approved emotes take up one of the channel's slots.
+pub struct Model {
+    /// The unique identifier for the emote.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The name used to type the emote in chat.
+    pub name: String,
+    /// The url of the emote image.
+    pub image_url: String,
+    /// The width of the image in pixels.
+    pub width: i64,
+    /// The height of the image in pixels.
+    pub height: i64,
+    /// The NSFW score from 0 to 100 the image classifier gave the image.
+    pub nsfw_score: i64,
+    /// The review status.
+    pub status: Status,
+    /// A note explaining why the emote was rejected.
+    pub review_note: String,
+    /// The admin who reviewed the emote.
+    pub reviewed_by: Option<Uuid>,
+    /// The time the emote was uploaded.
+    pub created_at: DateTime<Utc>,
+    /// The time the emote was reviewed.
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+/// Validates an emote name.
+pub fn validate_name(name: &str) -> Result<(), &'static str> {
+    if name.len() < 2 {
+        return Err("Emote name must be at least 2 characters long");
+    }
+
+    if name.len() > 32 {
+        return Err("Emote name must be at most 32 characters long");
+    }
+
+    if !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err("Emote name must only contain alphanumeric characters");
+    }
+
+    Ok(())
+}
+
+/// Validates the dimensions of an emote image. Emotes have to be square.
+pub fn validate_dimensions(width: u32, height: u32, max_size: u32) -> Result<(), &'static str> {
+    if width != height {
+        return Err("Emote must be square");
+    }
+
+    if width == 0 || width > max_size {
+        return Err("Emote is too large");
+    }
+
+    Ok(())
+}
+
+/// Reads the width and height of a PNG, GIF or WebP image from its header.
+/// Returns None for other formats and for images too short to have a complete header.
+pub fn image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let u16_le =
+        |at: usize| Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?) as u32);
+    let u24_le = |at: usize| {
+        let b = data.get(at..at + 3)?;
+        Some(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16)
+    };
+
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        if data.get(12..16)? != b"IHDR" {
+            return None;
+        }
+
+        let width = u32::from_be_bytes(data.get(16..20)?.try_into().ok()?);
+        let height = u32::from_be_bytes(data.get(20..24)?.try_into().ok()?);
+        return Some((width, height));
+    }
+
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return Some((u16_le(6)?, u16_le(8)?));
+    }
+
+    if data.starts_with(b"RIFF") && data.get(8..12)? == b"WEBP" {
+        return match data.get(12..16)? {
+            // Lossy images start with a key frame, which has the size after its start code.
+            b"VP8 " => {
+                if data.get(23..26)? != [0x9d, 0x01, 0x2a] {
+                    return None;
+                }
+
+                Some((u16_le(26)? & 0x3fff, u16_le(28)? & 0x3fff))
+            }
+            // Lossless images pack the size minus one into 14 bits each.
+            b"VP8L" => {
+                if *data.get(20)? != 0x2f {
+                    return None;
+                }
+
+                let bits = u32::from_le_bytes(data.get(21..25)?.try_into().ok()?);
+                Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            }
+            // Extended images, like animated ones, have the canvas size minus one in 24 bits each.
+            b"VP8X" => Some((u24_le(24)? + 1, u24_le(27)? + 1)),
+            _ => None,
+        };
+    }
+
+    None
+}
+
+/// The prefix the image processor puts an emote image under, a resubmitted emote gets a new one.
+pub fn image_prefix(channel_id: Uuid, upload_id: Uuid) -> String {
+    format!("emotes/{}/{}", channel_id, upload_id)
+}
+
+/// Calculates the number of emote slots a channel with the given amount of sub points gets.
+pub fn slot_count(sub_points: i64, config: &EmoteConfig) -> i64 {
+    let bonus = match config.sub_points_per_slot {
+        0 => 0,
+        per_slot => sub_points.max(0) / per_slot as i64,
+    };
+
+    (config.base_slots as i64 + bonus).min(config.max_slots as i64)
+}
+
+/// Sub points are the number of subscriptions and gift subscriptions bought for a channel in the last 30 days.
+pub async fn sub_points(db: &sqlx::PgPool, channel_id: Uuid) -> sqlx::Result<i64> {
+    let kinds = vec![
+        i64::from(revenue_transaction::Kind::Subscription),
+        i64::from(revenue_transaction::Kind::Gift),
+    ];
+
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*)::bigint AS "sub_points!" FROM revenue_transactions WHERE channel_id = $1 AND kind = ANY($2) AND created_at > NOW() - INTERVAL '30 days'"#,
+        channel_id,
+        &kinds,
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Counts the slots a channel currently uses.
+pub async fn used_slots(db: &sqlx::PgPool, channel_id: Uuid) -> sqlx::Result<i64> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*)::bigint AS "used!" FROM emotes WHERE channel_id = $1 AND status != $2"#,
+        channel_id,
+        i64::from(Status::Rejected),
+    )
+    .fetch_one(db)
+    .await
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A third-party emote provider a channel enabled for its chat.
+pub struct Model {
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The name of the provider, as configured in the emote config.
+    pub provider: String,
+    /// The id of the channel's emote set at the provider.
+    pub external_id: String,
+    /// The time the provider was enabled.
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// An emote as returned by a provider's emote set API.
+pub struct ProviderEmote {
+    /// The id of the emote at the provider.
+    pub id: String,
+    /// The name used to type the emote in chat.
+    pub name: String,
+    /// The url of the emote image.
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An emote which can be used in a channel's chat, either our own or from a provider.
+pub struct Emote {
+    /// The name used to type the emote in chat.
+    pub name: String,
+    /// The url of the emote image.
+    pub url: String,
+    /// The provider the emote comes from, or none for the channel's own emotes.
+    pub provider: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An emote found in a chat message.
+pub struct EmoteToken {
+    /// The character offset the emote starts at.
+    pub start: usize,
+    /// The character offset after the end of the emote.
+    pub end: usize,
+    /// The emote.
+    pub emote: Emote,
+}
+
+/// Finds all words in a chat message which are emotes.
+/// If multiple emotes have the same name the first one wins, so own emotes should come before provider emotes.
+pub fn tokenize(content: &str, emotes: &[Emote]) -> Vec<EmoteToken> {
+    let chars = content.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+
+    // One past the end counts as whitespace, so the last word is handled too.
+    for i in 0..=chars.len() {
+        let is_whitespace = chars.get(i).map(|c| c.is_whitespace()).unwrap_or(true);
+
+        match (is_whitespace, word_start) {
+            (false, None) => word_start = Some(i),
+            (true, Some(start)) => {
+                let word = chars[start..i].iter().collect::<String>();
+                if let Some(emote) = emotes.iter().find(|e| e.name == word) {
+                    tokens.push(EmoteToken {
+                        start,
+                        end: i,
+                        emote: emote.clone(),
+                    });
+                }
+
+                word_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    tokens
+}
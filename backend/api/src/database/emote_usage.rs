@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::emote;
+
+/// Counts how often each of the given emote names appears as a word in a chat message.
+pub fn count_uses<'a>(content: &str, names: &'a [String]) -> HashMap<&'a str, i64> {
+    let mut uses = HashMap::new();
+
+    for word in content.split_whitespace() {
+        if let Some(name) = names.iter().find(|name| name.as_str() == word) {
+            *uses.entry(name.as_str()).or_insert(0) += 1;
+        }
+    }
+
+    uses
+}
+
+/// Adds the emotes used in a chat message to today's usage counts.
+pub async fn record(db: &sqlx::PgPool, channel_id: Uuid, content: &str) -> sqlx::Result<()> {
+    let emotes = sqlx::query!(
+        "SELECT id, name FROM emotes WHERE channel_id = $1 AND status = $2",
+        channel_id,
+        i64::from(emote::Status::Approved),
+    )
+    .fetch_all(db)
+    .await?;
+
+    let names = emotes.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+
+    for (name, uses) in count_uses(content, &names) {
+        let Some(emote) = emotes.iter().find(|e| e.name == name) else {
+            continue;
+        };
+
+        sqlx::query!(
+            "INSERT INTO emote_usage_daily (emote_id, channel_id, day, uses) VALUES ($1, $2, date_trunc('day', NOW()), $3) ON CONFLICT (emote_id, channel_id, day) DO UPDATE SET uses = emote_usage_daily.uses + $3",
+            emote.id,
+            channel_id,
+            uses,
+        )
+        .execute(db)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+/// How often an emote was used in its channel over a period of time.
+pub struct UsageSummary {
+    /// The emote.
+    pub emote_id: Uuid,
+    /// The total number of uses.
+    pub uses: i64,
+    /// The last day the emote was used on.
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// Sums up the usage of all approved emotes of a channel over the last days, least used first.
+/// Emotes which were not used at all are included with zero uses.
+pub async fn summary(
+    db: &sqlx::PgPool,
+    channel_id: Uuid,
+    days: i64,
+) -> sqlx::Result<Vec<UsageSummary>> {
+    sqlx::query_as!(
+        UsageSummary,
+        r#"SELECT
+            e.id AS "emote_id!",
+            COALESCE(SUM(u.uses), 0)::bigint AS "uses!",
+            MAX(u.day) AS "last_used"
+        FROM emotes e
+        LEFT JOIN emote_usage_daily u ON u.emote_id = e.id AND u.channel_id = e.channel_id AND u.day > NOW() - make_interval(days => $3::int)
+        WHERE e.channel_id = $1 AND e.status = $2
+        GROUP BY e.id
+        ORDER BY 2 ASC, e.created_at ASC"#,
+        channel_id,
+        i64::from(emote::Status::Approved),
+        days,
+    )
+    .fetch_all(db)
+    .await
+}
+
+#[derive(Debug, Clone, Default)]
+/// How often an emote was used on a single day.
+pub struct DailyUsage {
+    /// The start of the day.
+    pub day: DateTime<Utc>,
+    /// The number of uses.
+    pub uses: i64,
+}
+
+/// Gets the daily usage of an emote in its channel over the last days, oldest first.
+/// Days without any use are left out.
+pub async fn daily(db: &sqlx::PgPool, emote_id: Uuid, days: i64) -> sqlx::Result<Vec<DailyUsage>> {
+    sqlx::query_as!(
+        DailyUsage,
+        "SELECT u.day, u.uses FROM emote_usage_daily u JOIN emotes e ON e.id = u.emote_id AND e.channel_id = u.channel_id WHERE u.emote_id = $1 AND u.day > NOW() - make_interval(days => $2::int) ORDER BY u.day ASC",
+        emote_id,
+        days,
+    )
+    .fetch_all(db)
+    .await
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Provider {
+    #[default]
+    Google = 0,
+    Discord = 1,
+    Github = 2,
+}
+
+impl From<i64> for Provider {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Google,
+            1 => Self::Discord,
+            2 => Self::Github,
+            _ => Self::Google,
+        }
+    }
+}
+
+impl From<Provider> for i64 {
+    fn from(value: Provider) -> Self {
+        match value {
+            Provider::Google => 0,
+            Provider::Discord => 1,
+            Provider::Github => 2,
+        }
+    }
+}
+
+impl Provider {
+    /// The name of the provider as it is shown to users.
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Google => "Google",
+            Self::Discord => "Discord",
+            Self::Github => "GitHub",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// An account at an OAuth provider which is linked to a user, so the user can log in with it.
+pub struct Model {
+    /// The unique identifier for the link.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// The provider the account is at.
+    pub provider: Provider,
+    /// The id of the account at the provider.
+    pub provider_user_id: String,
+    /// The name of the account at the provider when it was linked.
+    pub provider_username: String,
+    /// The time the account was linked.
+    pub created_at: DateTime<Utc>,
+    /// The time the user last logged in with the account.
+    pub last_login_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// A friend of a user. Unlike follows, friendships are mutual, so each one is stored once for both users.
+pub struct Model {
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// Foreign key to the users table, the friend.
+    pub friend_id: Uuid,
+    /// The time the users became friends.
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// A request to become friends, until the asked user accepts or declines it.
+pub struct Request {
+    /// Foreign key to the users table, who asked.
+    pub user_id: Uuid,
+    /// Foreign key to the users table, who was asked.
+    pub friend_id: Uuid,
+    /// The time the request was sent.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Whether the users are friends.
+pub async fn are_friends(
+    db: impl sqlx::PgExecutor<'_>,
+    user_id: Uuid,
+    other_id: Uuid,
+) -> sqlx::Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM friends WHERE user_id = $1 AND friend_id = $2) AS "friends!""#,
+        user_id,
+        other_id,
+    )
+    .fetch_one(db)
+    .await
+}
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub const MAX_TITLE_LENGTH: usize = 140;
+pub const MAX_KEYWORD_LENGTH: usize = 25;
+pub const MAX_WEIGHT: u32 = 10;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Open = 0,
+    Drawn = 1,
+    Cancelled = 2,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Open,
+            1 => Self::Drawn,
+            2 => Self::Cancelled,
+            _ => Self::Cancelled,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Open => 0,
+            Status::Drawn => 1,
+            Status::Cancelled => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A giveaway a channel runs in its chat, viewers enter and one of them is drawn as the winner.
+pub struct Model {
+    /// The unique identifier for the giveaway.
+    pub id: Uuid,
+    /// Foreign key to the users table, the channel running the giveaway.
+    pub channel_id: Uuid,
+    /// What is given away.
+    pub title: String,
+    /// The chat message which enters the giveaway, lowercase. None if it is only entered with the button.
+    pub keyword: Option<String>,
+    /// Whether only followers of the channel can enter.
+    pub followers_only: bool,
+    /// The tickets a follower gets.
+    pub follower_weight: i32,
+    /// The tickets a subscriber gets.
+    pub subscriber_weight: i32,
+    /// The status of the giveaway.
+    pub status: Status,
+    /// The secret the winner is drawn with, only revealed once the giveaway is drawn.
+    pub seed: String,
+    /// The hash of the seed, public from the start so the seed can't be changed before the draw.
+    pub commitment: String,
+    /// Foreign key to the users table, the user who won. (None until drawn, or if nobody entered)
+    pub winner_id: Option<Uuid>,
+    /// The time the giveaway was started.
+    pub created_at: DateTime<Utc>,
+    /// The time the giveaway was drawn or cancelled.
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl Model {
+    /// The tickets a viewer gets when entering, the better of their follower and subscriber weight.
+    pub fn tickets(&self, follower: bool, subscriber: bool) -> i32 {
+        let mut tickets = 1;
+        if follower {
+            tickets = tickets.max(self.follower_weight);
+        }
+        if subscriber {
+            tickets = tickets.max(self.subscriber_weight);
+        }
+        tickets
+    }
+
+    /// Whether a chat message enters the giveaway.
+    pub fn matches_keyword(&self, content: &str) -> bool {
+        self.keyword.as_deref().map_or(false, |keyword| {
+            content.trim().eq_ignore_ascii_case(keyword)
+        })
+    }
+}
+
+/// Validates the title, keyword and weights of a new giveaway.
+pub fn validate(
+    title: &str,
+    keyword: Option<&str>,
+    follower_weight: u32,
+    subscriber_weight: u32,
+) -> Result<(), (&'static str, &'static str)> {
+    if title.trim().is_empty() || title.len() > MAX_TITLE_LENGTH {
+        return Err(("title", "Title must be between 1 and 140 characters"));
+    }
+
+    if let Some(keyword) = keyword {
+        if keyword.is_empty()
+            || keyword.len() > MAX_KEYWORD_LENGTH
+            || keyword.chars().any(char::is_whitespace)
+        {
+            return Err((
+                "keyword",
+                "Keyword must be a single word of at most 25 characters",
+            ));
+        }
+    }
+
+    if !(1..=MAX_WEIGHT).contains(&follower_weight) {
+        return Err(("followerWeight", "Weights must be between 1 and 10"));
+    }
+
+    if !(1..=MAX_WEIGHT).contains(&subscriber_weight) {
+        return Err(("subscriberWeight", "Weights must be between 1 and 10"));
+    }
+
+    Ok(())
+}
+
+/// Generates the secret a giveaway is drawn with.
+pub fn generate_seed() -> String {
+    let mut rng = rand::thread_rng();
+
+    (0..32)
+        .map(|_| char::from(rng.sample(rand::distributions::Alphanumeric)))
+        .collect()
+}
+
+/// The hash of a seed, published when the giveaway starts.
+pub fn commitment(seed: &str) -> String {
+    format!("{:x}", Sha256::digest(seed.as_bytes()))
+}
+
+/// Draws the winner from the user ids and tickets of the entries, None if nobody entered.
+/// The entries are hashed with the seed in the order of their user ids, the first 8 bytes of the hash
+/// modulo the total tickets pick the ticket that wins. Anyone with the seed and the entries gets the same winner.
+pub fn draw(seed: &str, entries: &[(Uuid, i32)]) -> Option<Uuid> {
+    let mut entries = entries
+        .iter()
+        .filter(|(_, tickets)| *tickets > 0)
+        .copied()
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|(user_id, _)| *user_id);
+
+    let total = entries
+        .iter()
+        .map(|(_, tickets)| *tickets as u64)
+        .sum::<u64>();
+    if total == 0 {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(seed.as_bytes());
+    for (user_id, tickets) in &entries {
+        hasher.update(user_id.as_bytes());
+        hasher.update(tickets.to_be_bytes());
+    }
+    let hash = hasher.finalize();
+
+    let mut ticket = u64::from_be_bytes(hash[..8].try_into().unwrap()) % total;
+    for (user_id, tickets) in entries {
+        if ticket < tickets as u64 {
+            return Some(user_id);
+        }
+        ticket -= tickets as u64;
+    }
+
+    None
+}
@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// A viewer who entered a giveaway.
+pub struct Model {
+    /// Foreign key to the giveaways table.
+    pub giveaway_id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// How many tickets the viewer has in the draw, from the weights of the giveaway.
+    pub tickets: i32,
+    /// The time the viewer entered.
+    pub created_at: DateTime<Utc>,
+}
@@ -34,6 +34,12 @@ pub enum Permission {
     StreamTranscoding,
     /// Has access to recorded streams
     StreamRecording,
+    /// Can use a gradient for their display name
+    DisplayNameGradient,
+    /// Can use any hex color for their display name, not only the palette
+    CustomDisplayColor,
+    /// Can have more VIPs in their chat
+    ExtendedVipSlots,
 }
 
 impl Default for Permission {
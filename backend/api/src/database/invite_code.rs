@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use uuid::Uuid;
+
+/// The characters of a code, without the ones which are easily mixed up like `0` and `O`.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+#[derive(Debug, Clone, Default)]
+/// A code which lets one person register while registration is invite only.
+pub struct Model {
+    /// The unique identifier for the code.
+    pub id: Uuid,
+    /// The code as it is entered, like `ABCD-EFGH`.
+    pub code: String,
+    /// Foreign key to the users table, the user who shared the code. (None for the codes of admitted waitlist entries)
+    pub created_by: Option<Uuid>,
+    /// Foreign key to the users table, the user who registered with the code.
+    pub used_by: Option<Uuid>,
+    /// The time the code was created.
+    pub created_at: DateTime<Utc>,
+    /// The time someone registered with the code. (None if not yet)
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+/// Generates a new code, two groups of four characters.
+pub fn generate_code() -> String {
+    let mut rng = rand::thread_rng();
+
+    let mut code: String = (0..8)
+        .map(|_| char::from(CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())]))
+        .collect();
+    code.insert(4, '-');
+
+    code
+}
+
+/// Brings a code the way a user typed it into the stored format.
+pub fn normalize_code(code: &str) -> String {
+    let code = code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect::<String>();
+
+    match code.len() {
+        8 => format!("{}-{}", &code[..4], &code[4..]),
+        _ => code,
+    }
+}
+
+/// Uses a code for a newly registered user. Returns false if the code does not exist or was already used.
+pub async fn redeem(
+    db: impl sqlx::PgExecutor<'_>,
+    code: &str,
+    user_id: Uuid,
+) -> sqlx::Result<bool> {
+    Ok(sqlx::query!(
+        "UPDATE invite_codes SET used_by = $2, used_at = NOW() WHERE code = $1 AND used_at IS NULL",
+        normalize_code(code),
+        user_id,
+    )
+    .execute(db)
+    .await?
+    .rows_affected()
+        > 0)
+}
@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum SubjectKind {
+    #[default]
+    User = 0,
+    Channel = 1,
+    Vod = 2,
+}
+
+impl From<i64> for SubjectKind {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::User,
+            1 => Self::Channel,
+            2 => Self::Vod,
+            _ => Self::User,
+        }
+    }
+}
+
+impl From<SubjectKind> for i64 {
+    fn from(value: SubjectKind) -> Self {
+        match value {
+            SubjectKind::User => 0,
+            SubjectKind::Channel => 1,
+            SubjectKind::Vod => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A legal hold preserves the data of a user, a channel or a VOD. Nothing under an active hold may be deleted,
+/// neither by the owner nor by cleanup jobs, until an admin releases it.
+pub struct Model {
+    /// The unique identifier for the hold.
+    pub id: Uuid,
+    /// What kind of data is held.
+    pub subject_kind: SubjectKind,
+    /// The id of the user, channel or stream which is held.
+    pub subject_id: Uuid,
+    /// Why the data is preserved, e.g. a case number.
+    pub reason: String,
+    /// Foreign key to the users table, the admin who placed the hold. (None if their account was deleted)
+    pub placed_by: Option<Uuid>,
+    /// Foreign key to the users table, the admin who released the hold. (None if active or their account was deleted)
+    pub released_by: Option<Uuid>,
+    /// Why the hold was released.
+    pub release_reason: String,
+    /// The time the hold was placed.
+    pub created_at: DateTime<Utc>,
+    /// The time the hold was released. (None if active)
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+/// Checks if a subject is under an active hold.
+pub async fn is_held(db: &sqlx::PgPool, kind: SubjectKind, id: Uuid) -> sqlx::Result<bool> {
+    Ok(sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM legal_holds WHERE subject_kind = $1 AND subject_id = $2 AND released_at IS NULL)",
+        i64::from(kind),
+        id,
+    )
+    .fetch_one(db)
+    .await?
+    .unwrap_or(false))
+}
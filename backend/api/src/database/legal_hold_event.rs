@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Action {
+    #[default]
+    Placed = 0,
+    Released = 1,
+}
+
+impl From<i64> for Action {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Placed,
+            1 => Self::Released,
+            _ => Self::Placed,
+        }
+    }
+}
+
+impl From<Action> for i64 {
+    fn from(value: Action) -> Self {
+        match value {
+            Action::Placed => 0,
+            Action::Released => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// An entry in the audit trail of a legal hold.
+pub struct Model {
+    /// The unique identifier for the event.
+    pub id: Uuid,
+    /// Foreign key to the legal_holds table.
+    pub hold_id: Uuid,
+    /// Foreign key to the users table, the admin who took the action. (None if their account was deleted)
+    pub actor_id: Option<Uuid>,
+    /// What happened to the hold.
+    pub action: Action,
+    /// The reason given for the action.
+    pub note: String,
+    /// The time the action was taken.
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::channel_event;
+
+#[derive(Debug, Clone, Default)]
+/// What happened in a channel since the current stream started.
+pub struct LiveStats {
+    /// The current stream. (None if the channel is offline)
+    pub stream_id: Option<Uuid>,
+    /// The time the current stream started.
+    pub started_at: Option<DateTime<Utc>>,
+    /// The number of chat messages sent during the stream.
+    pub chat_messages: i64,
+    /// The number of distinct users who sent a chat message during the stream.
+    pub unique_chatters: i64,
+    /// The number of follows the channel received during the stream.
+    pub new_follows: i64,
+    /// The number of subscriptions the channel received during the stream.
+    pub new_subscriptions: i64,
+    /// The number of bits cheered during the stream.
+    pub bits: i64,
+}
+
+impl LiveStats {
+    /// Whether the channel is live right now.
+    pub fn live(&self) -> bool {
+        self.stream_id.is_some()
+    }
+}
+
+/// Gets the live stats of the stream a channel is currently running. Offline channels get empty stats.
+pub async fn live_stats(db: &sqlx::PgPool, channel_id: Uuid) -> sqlx::Result<LiveStats> {
+    let stats = sqlx::query_as!(
+        LiveStats,
+        r#"SELECT
+            s.id AS "stream_id?",
+            s.created_at AS "started_at?",
+            (SELECT COUNT(*) FROM chat_messages m WHERE m.channel_id = s.channel_id AND m.created_at >= s.created_at)::bigint AS "chat_messages!",
+            (SELECT COUNT(DISTINCT m.author_id) FROM chat_messages m WHERE m.channel_id = s.channel_id AND m.created_at >= s.created_at)::bigint AS "unique_chatters!",
+            (SELECT COUNT(*) FROM channel_events e WHERE e.channel_id = s.channel_id AND e.kind = $2 AND e.created_at >= s.created_at)::bigint AS "new_follows!",
+            (SELECT COUNT(*) FROM channel_events e WHERE e.channel_id = s.channel_id AND e.kind = $3 AND e.created_at >= s.created_at)::bigint AS "new_subscriptions!",
+            (SELECT COALESCE(SUM(e.amount), 0) FROM channel_events e WHERE e.channel_id = s.channel_id AND e.kind = $4 AND e.created_at >= s.created_at)::bigint AS "bits!"
+        FROM streams s
+        WHERE s.channel_id = $1 AND s.deleted = FALSE AND s.ended_at > NOW()
+        ORDER BY s.created_at DESC
+        LIMIT 1"#,
+        channel_id,
+        i64::from(channel_event::Kind::Follow),
+        i64::from(channel_event::Kind::Subscription),
+        i64::from(channel_event::Kind::Cheer),
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(stats.unwrap_or_default())
+}
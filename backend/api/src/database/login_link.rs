@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// A passwordless login. The link is sent by email and confirmed from any device, the session is then
+/// handed to the device which requested it, so a link forwarded to someone else can't log them in.
+pub struct Model {
+    /// The unique identifier for the link.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// The sha256 hash of the token in the emailed link as hex.
+    pub token_hash: String,
+    /// The sha256 hash of the token the requesting device keeps as hex.
+    pub device_hash: String,
+    /// The address the link was requested from.
+    pub requested_ip: String,
+    /// The time the link was requested.
+    pub created_at: DateTime<Utc>,
+    /// The time the link expires.
+    pub expires_at: DateTime<Utc>,
+    /// The time the link in the email was opened. (None if not yet)
+    pub confirmed_at: Option<DateTime<Utc>>,
+    /// The time the link was exchanged for a session. (None if not yet)
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+impl Model {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+
+    /// Checks if the link can still be exchanged for a session.
+    pub fn can_login(&self) -> bool {
+        self.confirmed_at.is_some() && self.used_at.is_none() && !self.is_expired()
+    }
+}
+
+/// Generates a token for a link. Only its hash is stored.
+pub fn generate_token() -> String {
+    let mut rng = rand::thread_rng();
+
+    (0..48)
+        .map(|_| char::from(rng.sample(rand::distributions::Alphanumeric)))
+        .collect()
+}
+
+/// Hashes a token the way it is stored in the database.
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
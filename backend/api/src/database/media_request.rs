@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// The chat message which requests media, followed by its url.
+pub const REQUEST_COMMAND: &str = "!sr";
+
+pub const MAX_URL_LENGTH: usize = 512;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Pending = 0,
+    Approved = 1,
+    Rejected = 2,
+    /// The broadcaster played the request, it left the queue.
+    Played = 3,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Pending,
+            1 => Self::Approved,
+            2 => Self::Rejected,
+            3 => Self::Played,
+            _ => Self::Pending,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Pending => 0,
+            Status::Approved => 1,
+            Status::Rejected => 2,
+            Status::Played => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A song or video a viewer asked the broadcaster to play on stream.
+pub struct Model {
+    /// The unique identifier for the request.
+    pub id: Uuid,
+    /// Foreign key to the users table, the channel the request was made in.
+    pub channel_id: Uuid,
+    /// Foreign key to the users table, the viewer who made the request.
+    pub user_id: Uuid,
+    /// The url of the media, on one of the allowed hosts.
+    pub url: String,
+    /// The status of the request.
+    pub status: Status,
+    /// Foreign key to the users table, who approved or rejected the request. (None if pending or their account was deleted)
+    pub reviewed_by: Option<Uuid>,
+    /// The time the request was made.
+    pub created_at: DateTime<Utc>,
+    /// The time the request was approved or rejected.
+    pub reviewed_at: Option<DateTime<Utc>>,
+    /// The time the request was played.
+    pub played_at: Option<DateTime<Utc>>,
+}
+
+/// The url of a chat message which requests media, if it is one.
+pub fn command(content: &str) -> Option<&str> {
+    let (command, url) = content.trim().split_once(char::is_whitespace)?;
+
+    if !command.eq_ignore_ascii_case(REQUEST_COMMAND) {
+        return None;
+    }
+
+    Some(url.trim())
+}
+
+/// Validates the url of a request. It has to be a https link to one of the allowed hosts or their subdomains.
+pub fn validate_url(url: &str, allowed_hosts: &[String]) -> Result<(), &'static str> {
+    if url.len() > MAX_URL_LENGTH {
+        return Err("Url must be at most 512 characters long");
+    }
+
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err("Url must not contain spaces");
+    }
+
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return Err("Url must start with https://");
+    };
+
+    if !scheme.eq_ignore_ascii_case("https") {
+        return Err("Url must start with https://");
+    }
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+
+    // A user in front of the host, like https://youtube.com@example.com, would hide where the link really goes.
+    if authority.contains('@') {
+        return Err("Url is not a valid link");
+    }
+
+    let host = authority
+        .split(':')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches('.')
+        .to_ascii_lowercase();
+
+    if host.is_empty() {
+        return Err("Url is not a valid link");
+    }
+
+    let allowed = allowed_hosts.iter().any(|allowed| {
+        let allowed = allowed.to_ascii_lowercase();
+        host == allowed
+            || host
+                .strip_suffix(&allowed)
+                .map_or(false, |sub| sub.ends_with('.'))
+    });
+
+    if !allowed {
+        return Err("Media from this site can't be requested");
+    }
+
+    Ok(())
+}
@@ -1,11 +1,86 @@
+pub mod ad_break;
+pub mod admin_approval;
+pub mod admin_approval_event;
+pub mod bot_application;
+pub mod channel_appearance;
+pub mod channel_audit_event;
+pub mod channel_event;
+pub mod channel_import;
+pub mod channel_panel;
 pub mod channel_role;
 pub mod channel_role_grant;
+pub mod channel_schedule_segment;
+pub mod channel_user;
+pub mod charity_campaign;
+pub mod charity_donation;
+pub mod chat_ban;
+pub mod chat_ban_appeal;
+pub mod chat_ban_appeal_event;
+pub mod chat_command;
+pub mod chat_log;
 pub mod chat_message;
+pub mod chat_timer;
+pub mod chat_vip;
+pub mod checkout;
+pub mod cheermote_tier;
+pub mod data_export;
+pub mod dead_letter;
+pub mod deprecated_usage;
+pub mod discord_announcement;
+pub mod discord_integration;
+pub mod display_color;
+pub mod display_color_change;
+pub mod email_change;
+pub mod emote;
+pub mod emote_provider;
+pub mod emote_usage;
+pub mod external_account;
+pub mod friend;
+pub mod giveaway;
+pub mod giveaway_entry;
 pub mod global_role;
 pub mod global_role_grant;
+pub mod invite_code;
+pub mod legal_hold;
+pub mod legal_hold_event;
+pub mod live_stats;
+pub mod login_link;
+pub mod media_request;
+pub mod moderation_job;
+pub mod notification_settings;
+pub mod obs_connection;
+pub mod obs_mapping;
+pub mod passkey;
+pub mod passkey_challenge;
+pub mod password_reset;
+pub mod payout_ledger_entry;
+pub mod payout_method;
+pub mod personal_access_token;
+pub mod poll;
+pub mod promotion;
 pub mod protobuf;
+pub mod recovery_code;
+pub mod recovery_code_event;
+pub mod revenue_transaction;
 pub mod session;
 pub mod stream;
 pub mod stream_bitrate_update;
+pub mod stream_chapter;
 pub mod stream_event;
+pub mod stream_key;
+pub mod stream_lifecycle_transition;
+pub mod stream_marker;
+pub mod stream_session;
+pub mod tag;
 pub mod user;
+pub mod user_block;
+pub mod user_social_link;
+pub mod user_suspension;
+pub mod user_suspension_appeal;
+pub mod username_history;
+pub mod viewer_queue;
+pub mod viewer_queue_entry;
+pub mod vod_caption;
+pub mod vod_download;
+pub mod waitlist_entry;
+pub mod webhook_event;
@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Kind {
+    #[default]
+    BanUsers = 0,
+    DeleteMessages = 1,
+    RemoveFollows = 2,
+}
+
+impl From<i64> for Kind {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::BanUsers,
+            1 => Self::DeleteMessages,
+            2 => Self::RemoveFollows,
+            _ => Self::BanUsers,
+        }
+    }
+}
+
+impl From<Kind> for i64 {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::BanUsers => 0,
+            Kind::DeleteMessages => 1,
+            Kind::RemoveFollows => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Queued = 0,
+    Running = 1,
+    Completed = 2,
+    Failed = 3,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Queued,
+            1 => Self::Running,
+            2 => Self::Completed,
+            3 => Self::Failed,
+            _ => Self::Queued,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Queued => 0,
+            Status::Running => 1,
+            Status::Completed => 2,
+            Status::Failed => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A moderation action on many users or messages at once, run in the background.
+pub struct Model {
+    /// The unique identifier for the job.
+    pub id: Uuid,
+    /// Foreign key to the users table, the channel which is moderated.
+    pub channel_id: Uuid,
+    /// Foreign key to the users table, the moderator who started the job.
+    pub created_by: Uuid,
+    /// What the job does.
+    pub kind: Kind,
+    /// The status of the job.
+    pub status: Status,
+    /// The users to ban. (Only for ban jobs)
+    pub user_ids: Vec<Uuid>,
+    /// The reason recorded with the bans.
+    pub reason: String,
+    /// The text of the messages to delete. (Only for delete jobs)
+    pub pattern: String,
+    /// Messages sent or follows made before this time are kept. (Only for delete and follow jobs)
+    pub since: Option<DateTime<Utc>>,
+    /// Follows made after this time are kept. (Only for follow jobs)
+    pub until: Option<DateTime<Utc>>,
+    /// Only follows of accounts at most this many minutes old when following are removed. (Only for follow jobs)
+    pub max_account_age: Option<i64>,
+    /// Only follows of users who never chatted in the channel are removed. (Only for follow jobs)
+    pub only_silent: bool,
+    /// The number of users, messages or follows the job handles.
+    pub total_items: i64,
+    /// The number of users banned, messages deleted or follows removed so far.
+    pub processed_items: i64,
+    /// The reason the job failed.
+    pub error: String,
+    /// The time the job was started.
+    pub created_at: DateTime<Utc>,
+    /// The time the job last made progress.
+    pub updated_at: DateTime<Utc>,
+    /// The time the job finished.
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl Model {
+    /// The share of the items which has been handled, from 0 to 1.
+    pub fn progress(&self) -> f64 {
+        match self.status {
+            Status::Completed => 1.0,
+            _ if self.total_items == 0 => 0.0,
+            _ => self.processed_items as f64 / self.total_items as f64,
+        }
+    }
+}
+
+/// Turns the text a moderator searches for into a case-insensitive LIKE pattern matching messages containing it.
+/// Wildcards in the text are escaped, so `100%` only matches the literal text.
+pub fn like_pattern(text: &str) -> String {
+    let mut pattern = String::with_capacity(text.len() + 2);
+    pattern.push('%');
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
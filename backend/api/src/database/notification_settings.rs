@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::channel_event;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Category {
+    GoLive = 0,
+    NewFollower = 1,
+    Mentions = 2,
+    MarketingEmails = 3,
+    WeeklyDigest = 4,
+}
+
+impl From<Category> for i64 {
+    fn from(value: Category) -> Self {
+        match value {
+            Category::GoLive => 0,
+            Category::NewFollower => 1,
+            Category::Mentions => 2,
+            Category::MarketingEmails => 3,
+            Category::WeeklyDigest => 4,
+        }
+    }
+}
+
+impl Category {
+    /// The name of the category in unsubscribe links.
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::GoLive => "go_live",
+            Category::NewFollower => "new_follower",
+            Category::Mentions => "mentions",
+            Category::MarketingEmails => "marketing_emails",
+            Category::WeeklyDigest => "weekly_digest",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "go_live" => Some(Category::GoLive),
+            "new_follower" => Some(Category::NewFollower),
+            "mentions" => Some(Category::Mentions),
+            "marketing_emails" => Some(Category::MarketingEmails),
+            "weekly_digest" => Some(Category::WeeklyDigest),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+/// Which notifications a user gets. Users without a row use the defaults.
+pub struct Model {
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// Whether the user gets an email when a channel they follow goes live.
+    pub go_live: bool,
+    /// Whether the user is notified when someone follows their channel.
+    pub new_follower: bool,
+    /// Whether the user is notified when they are mentioned in chat.
+    pub mentions: bool,
+    /// Whether the user agreed to get marketing emails.
+    pub marketing_emails: bool,
+    /// Whether the user gets a weekly email of what the channels they follow did.
+    pub weekly_digest: bool,
+    /// The time the settings were last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Model {
+    /// The settings of a user who never changed them.
+    pub fn default_for(user_id: Uuid) -> Self {
+        Self {
+            user_id,
+            go_live: true,
+            new_follower: true,
+            mentions: true,
+            marketing_emails: false,
+            weekly_digest: true,
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn enabled(&self, category: Category) -> bool {
+        match category {
+            Category::GoLive => self.go_live,
+            Category::NewFollower => self.new_follower,
+            Category::Mentions => self.mentions,
+            Category::MarketingEmails => self.marketing_emails,
+            Category::WeeklyDigest => self.weekly_digest,
+        }
+    }
+}
+
+/// Gets the notification settings of a user, the defaults if they never changed them.
+pub async fn for_user(db: &sqlx::PgPool, user_id: Uuid) -> sqlx::Result<Model> {
+    let settings = sqlx::query_as!(
+        Model,
+        "SELECT * FROM notification_settings WHERE user_id = $1",
+        user_id,
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(settings.unwrap_or_else(|| Model::default_for(user_id)))
+}
+
+/// Turns a category on or off, leaving the others as they are.
+pub async fn set(
+    db: &sqlx::PgPool,
+    user_id: Uuid,
+    category: Category,
+    enabled: bool,
+) -> sqlx::Result<Model> {
+    // The row is created with the defaults first, so the update only has to touch the one category.
+    sqlx::query!(
+        "INSERT INTO notification_settings (user_id) VALUES ($1) ON CONFLICT DO NOTHING",
+        user_id,
+    )
+    .execute(db)
+    .await?;
+
+    sqlx::query_as!(
+        Model,
+        "UPDATE notification_settings SET go_live = CASE WHEN $2 = 0 THEN $3 ELSE go_live END, new_follower = CASE WHEN $2 = 1 THEN $3 ELSE new_follower END, mentions = CASE WHEN $2 = 2 THEN $3 ELSE mentions END, marketing_emails = CASE WHEN $2 = 3 THEN $3 ELSE marketing_emails END, weekly_digest = CASE WHEN $2 = 4 THEN $3 ELSE weekly_digest END, updated_at = NOW() WHERE user_id = $1 RETURNING *",
+        user_id,
+        i64::from(category),
+        enabled,
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// A follower who wants an email when the channel goes live.
+pub struct GoLiveRecipient {
+    pub user_id: Uuid,
+    /// The encrypted email of the follower.
+    pub email: String,
+}
+
+/// The followers of a channel with a verified email who did not turn off go-live emails, mute the channel or turn off its notifications.
+pub async fn go_live_recipients(
+    db: &sqlx::PgPool,
+    channel_id: Uuid,
+) -> sqlx::Result<Vec<GoLiveRecipient>> {
+    sqlx::query_as!(
+        GoLiveRecipient,
+        "SELECT DISTINCT u.id AS user_id, u.email FROM channel_events e JOIN users u ON u.id = e.user_id LEFT JOIN notification_settings n ON n.user_id = u.id WHERE e.channel_id = $1 AND e.kind = $2 AND u.email_verified = TRUE AND COALESCE(n.go_live, TRUE) AND NOT EXISTS (SELECT 1 FROM channel_users cu WHERE cu.channel_id = $1 AND cu.user_id = u.id AND (cu.muted_at IS NOT NULL OR cu.live_notifications = FALSE))",
+        channel_id,
+        i64::from(channel_event::Kind::Follow),
+    )
+    .fetch_all(db)
+    .await
+}
@@ -0,0 +1,96 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Connecting = 0,
+    Connected = 1,
+    Failed = 2,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Connecting,
+            1 => Self::Connected,
+            2 => Self::Failed,
+            _ => Self::Connecting,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Connecting => 0,
+            Status::Connected => 1,
+            Status::Failed => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// The obs-websocket server of a channel which the API connects to, to keep the stream info in sync with OBS.
+pub struct Model {
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The host OBS can be reached at.
+    pub host: String,
+    /// The port of the obs-websocket server.
+    pub port: i64,
+    /// The obs-websocket password, encrypted with the encryption key. (None if OBS has no password set)
+    pub password_encrypted: Option<Vec<u8>>,
+    /// Whether scene changes update the stream category and title.
+    pub sync_scenes: bool,
+    /// Whether profile changes update the stream category and title.
+    pub sync_profiles: bool,
+    /// Whether the stream info is updated from the current scene when OBS starts streaming.
+    pub update_on_go_live: bool,
+    /// The connection status.
+    pub status: Status,
+    /// The reason the last connection attempt failed.
+    pub last_error: String,
+    /// The time the current connection was established.
+    pub connected_at: Option<DateTime<Utc>>,
+    /// The time the connection settings were last changed.
+    pub updated_at: DateTime<Utc>,
+    /// The time the connection was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Validates the host of an OBS connection. The API connects to it, so it must not point into our own network.
+/// A hostname can resolve to anything later on, so the addresses are checked again when connecting.
+pub fn validate_host(host: &str) -> Result<(), &'static str> {
+    if host.is_empty() || host.len() > 255 {
+        return Err("Host must be between 1 and 255 characters");
+    }
+
+    if host.eq_ignore_ascii_case("localhost") || host.to_lowercase().ends_with(".localhost") {
+        return Err("Host must be publicly reachable");
+    }
+
+    if let Ok(ip) = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        if !common::net::is_public(ip) {
+            return Err("Host must be publicly reachable");
+        }
+
+        return Ok(());
+    }
+
+    if !host
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+    {
+        return Err("Host must be a hostname or an ip address");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Kind {
+    #[default]
+    Scene = 0,
+    Profile = 1,
+}
+
+impl From<i64> for Kind {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Scene,
+            1 => Self::Profile,
+            _ => Self::Scene,
+        }
+    }
+}
+
+impl From<Kind> for i64 {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::Scene => 0,
+            Kind::Profile => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// The stream info a channel uses while a scene or profile is active in OBS.
+pub struct Model {
+    /// The unique identifier for the mapping.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// Whether this maps a scene or a profile.
+    pub kind: Kind,
+    /// The name of the scene or profile in OBS.
+    pub source_name: String,
+    /// The stream category to switch to.
+    pub category: String,
+    /// The stream title to switch to. (None to keep the current title)
+    pub title: Option<String>,
+    /// The time the mapping was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Finds the mapping of a scene or profile.
+pub async fn find(
+    db: &sqlx::PgPool,
+    channel_id: Uuid,
+    kind: Kind,
+    source_name: &str,
+) -> sqlx::Result<Option<Model>> {
+    sqlx::query_as!(
+        Model,
+        "SELECT * FROM obs_mappings WHERE channel_id = $1 AND kind = $2 AND source_name = $3",
+        channel_id,
+        i64::from(kind),
+        source_name,
+    )
+    .fetch_optional(db)
+    .await
+}
@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A WebAuthn credential a user can log in with, either on its own or after their password.
+pub struct Model {
+    /// The unique identifier for the passkey.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// The base64url id the authenticator gave the credential.
+    pub credential_id: String,
+    /// The public key as an uncompressed P-256 point.
+    pub public_key: Vec<u8>,
+    /// The signature counter the authenticator reported last, 0 if it does not count.
+    pub sign_count: i64,
+    /// The name the user gave the passkey.
+    pub name: String,
+    /// The time the passkey was registered.
+    pub created_at: DateTime<Utc>,
+    /// The time the passkey was last used to log in. (None if never)
+    pub last_used_at: Option<DateTime<Utc>>,
+}
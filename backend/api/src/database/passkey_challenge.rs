@@ -0,0 +1,57 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Kind {
+    #[default]
+    Registration = 0,
+    Login = 1,
+}
+
+impl From<i64> for Kind {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Registration,
+            1 => Self::Login,
+            _ => Self::Registration,
+        }
+    }
+}
+
+impl From<Kind> for i64 {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::Registration => 0,
+            Kind::Login => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A challenge handed to `navigator.credentials`, it can only be answered once.
+pub struct Model {
+    /// The unique identifier for the challenge.
+    pub id: Uuid,
+    /// Foreign key to the users table. (None for passwordless logins, the passkey tells who logs in)
+    pub user_id: Option<Uuid>,
+    /// Whether a passkey is registered or used to log in with the challenge.
+    pub kind: Kind,
+    /// The random bytes the authenticator signs, as base64url.
+    pub challenge: String,
+    /// The time the challenge was created.
+    pub created_at: DateTime<Utc>,
+    /// The time the challenge can't be answered anymore.
+    pub expires_at: DateTime<Utc>,
+    /// The time the challenge was answered. (None if not yet)
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+/// Generates 32 random bytes for a challenge, as base64url like the client data of the authenticator has them.
+pub fn generate_challenge() -> String {
+    let mut challenge = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut challenge);
+    BASE64_URL.encode(challenge)
+}
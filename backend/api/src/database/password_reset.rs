@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// A request to reset the password of a user. The token is sent by email and can set a new password once.
+/// Tokens are generated and hashed like login link tokens.
+pub struct Model {
+    /// The unique identifier for the reset.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// The sha256 hash of the emailed token as hex.
+    pub token_hash: String,
+    /// The address the reset was requested from.
+    pub requested_ip: String,
+    /// The time the reset was requested.
+    pub created_at: DateTime<Utc>,
+    /// The time the token expires.
+    pub expires_at: DateTime<Utc>,
+    /// The time the token was used, or another reset of the user was. (None if not yet)
+    pub used_at: Option<DateTime<Utc>>,
+}
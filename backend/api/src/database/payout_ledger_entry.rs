@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// An entry in a channel's payout ledger.
+/// Entries are never updated or deleted, corrections are made by inserting a new entry.
+pub struct Model {
+    /// The unique identifier for the entry.
+    pub id: Uuid,
+    /// The channel the entry belongs to.
+    pub channel_id: Uuid,
+    /// The amount in cents. Positive amounts are credited to the channel, negative amounts are paid out.
+    pub amount: i64,
+    /// What the entry is for.
+    pub description: String,
+    /// The time the entry was created.
+    pub created_at: DateTime<Utc>,
+}
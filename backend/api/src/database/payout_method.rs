@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    PendingVerification = 0,
+    Verified = 1,
+    Blocked = 2,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::PendingVerification,
+            1 => Self::Verified,
+            2 => Self::Blocked,
+            _ => Self::PendingVerification,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::PendingVerification => 0,
+            Status::Verified => 1,
+            Status::Blocked => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// The account at the payout provider a channel gets paid out to.
+/// A channel can only use monetization features once its payout method is verified.
+pub struct Model {
+    /// The unique identifier for the payout method.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The name of the payout provider.
+    pub provider: String,
+    /// The id of the account at the payout provider.
+    pub provider_account_id: String,
+    /// The verification status.
+    pub status: Status,
+    /// A note left by the admin who reviewed the payout method.
+    pub review_note: String,
+    /// The admin who reviewed the payout method.
+    pub reviewed_by: Option<Uuid>,
+    /// The time the payout method was registered.
+    pub created_at: DateTime<Utc>,
+    /// The time the payout method was reviewed.
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+/// Checks if a channel has completed payout onboarding and may use monetization features.
+pub async fn is_onboarded(db: &sqlx::PgPool, channel_id: Uuid) -> sqlx::Result<bool> {
+    let status = sqlx::query_scalar!(
+        "SELECT status FROM payout_methods WHERE channel_id = $1",
+        channel_id,
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(status.map(Status::from) == Some(Status::Verified))
+}
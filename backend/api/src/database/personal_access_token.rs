@@ -0,0 +1,87 @@
+use bitmask_enum::bitmask;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// The prefix of every token, so leaked tokens are easy to recognize.
+pub const TOKEN_PREFIX: &str = "scuffle_pat_";
+
+#[derive(Debug, Clone, Default)]
+/// A token a user created to give an app access to their account without a session.
+pub struct Model {
+    /// The unique identifier for the token.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// The name the user gave the token.
+    pub name: String,
+    /// The sha256 hash of the token as hex.
+    pub token_hash: String,
+    /// What the token can be used for.
+    pub scopes: Scope,
+    /// The time the token expires. (None if it never expires)
+    pub expires_at: Option<DateTime<Utc>>,
+    /// The time the token was last used.
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// The time the token was created.
+    pub created_at: DateTime<Utc>,
+}
+
+#[bitmask(i64)]
+pub enum Scope {
+    /// Can control the stream and read live stats, used by Stream Deck and companion apps
+    Control,
+}
+
+impl Default for Scope {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl Model {
+    /// Checks if the token has not expired yet and was granted the given scope.
+    pub fn allows(&self, scope: Scope) -> bool {
+        if self.expires_at.map(|e| e < Utc::now()).unwrap_or(false) {
+            return false;
+        }
+
+        self.has_scope(scope)
+    }
+
+    /// Checks if the token was granted the given scope.
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes & scope == scope
+    }
+}
+
+/// Generates a new token. Only its hash is stored, so it can not be shown again later.
+pub fn generate_token() -> String {
+    let mut rng = rand::thread_rng();
+    let mut token = TOKEN_PREFIX.to_string();
+
+    for _ in 0..40 {
+        token.push(rng.sample(rand::distributions::Alphanumeric).into());
+    }
+
+    token
+}
+
+/// Hashes a token the way it is stored in the database.
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Validates the name of a token.
+pub fn validate_name(name: &str) -> Result<(), &'static str> {
+    if name.trim().is_empty() {
+        return Err("Token name can not be empty");
+    }
+
+    if name.len() > 64 {
+        return Err("Token name must be at most 64 characters long");
+    }
+
+    Ok(())
+}
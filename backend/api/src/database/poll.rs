@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+// Viewers should be able to read all options of a poll at a glance.
+pub const MIN_OPTIONS: usize = 2;
+pub const MAX_OPTIONS: usize = 5;
+pub const MAX_OPTION_LENGTH: usize = 25;
+pub const MIN_DURATION: u32 = 15;
+pub const MAX_DURATION: u32 = 1800;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A poll a channel started in chat.
+pub struct Model {
+    /// The unique identifier for the poll.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The question of the poll.
+    pub title: String,
+    /// The answers viewers can pick from.
+    pub options: Vec<String>,
+    /// The time the poll closes.
+    pub ends_at: DateTime<Utc>,
+    /// The time the poll was started.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Validates the question, options and duration in seconds of a new poll.
+pub fn validate(title: &str, options: &[String], duration: u32) -> Result<(), &'static str> {
+    if title.trim().is_empty() || title.len() > 140 {
+        return Err("Title must be between 1 and 140 characters");
+    }
+
+    if options.len() < MIN_OPTIONS || options.len() > MAX_OPTIONS {
+        return Err("A poll must have between 2 and 5 options");
+    }
+
+    if options
+        .iter()
+        .any(|o| o.trim().is_empty() || o.len() > MAX_OPTION_LENGTH)
+    {
+        return Err("Options must be between 1 and 25 characters");
+    }
+
+    if !(MIN_DURATION..=MAX_DURATION).contains(&duration) {
+        return Err("Duration must be between 15 and 1800 seconds");
+    }
+
+    Ok(())
+}
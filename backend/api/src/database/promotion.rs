@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::revenue_transaction;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Target {
+    #[default]
+    Subscriptions = 0,
+    GiftSubscriptions = 1,
+}
+
+impl From<i64> for Target {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Subscriptions,
+            1 => Self::GiftSubscriptions,
+            _ => Self::Subscriptions,
+        }
+    }
+}
+
+impl From<Target> for i64 {
+    fn from(value: Target) -> Self {
+        match value {
+            Target::Subscriptions => 0,
+            Target::GiftSubscriptions => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A discount campaign defined by an admin.
+pub struct Model {
+    /// The unique identifier for the promotion.
+    pub id: Uuid,
+    /// The name shown next to discounted prices.
+    pub name: String,
+    /// What the discount applies to.
+    pub target: Target,
+    /// The discount in percent.
+    pub percent_off: i64,
+    /// If the discount only applies to users who never subscribed to the channel before.
+    pub first_month_only: bool,
+    /// The channel the promotion is limited to, or all channels if none.
+    pub channel_id: Option<Uuid>,
+    /// The admin who created the promotion.
+    pub created_by: Option<Uuid>,
+    /// The time the promotion starts.
+    pub starts_at: DateTime<Utc>,
+    /// The time the promotion ends.
+    pub ends_at: DateTime<Utc>,
+    /// The time the promotion was created.
+    pub created_at: DateTime<Utc>,
+}
+
+impl Model {
+    /// Checks if a user is eligible for this promotion.
+    pub fn is_eligible(&self, first_subscription: bool) -> bool {
+        !self.first_month_only || first_subscription
+    }
+}
+
+/// Applies a discount to a price in cents. The discount is rounded down to the cent.
+pub fn discounted_price(price: i64, percent_off: i64) -> i64 {
+    price - price * percent_off.clamp(0, 100) / 100
+}
+
+#[derive(Debug, Clone, Default)]
+/// The price a user pays in cents, after promotions are applied.
+pub struct Price {
+    /// The price without any discount.
+    pub base_amount: i64,
+    /// The price the user pays.
+    pub amount: i64,
+    /// The promotion which was applied.
+    pub promotion: Option<Model>,
+}
+
+/// Finds the best promotion a user is eligible for and applies it to the base price.
+/// Without a user the price is calculated for someone who never subscribed to the channel.
+pub async fn price(
+    db: &sqlx::PgPool,
+    channel_id: Uuid,
+    user_id: Option<Uuid>,
+    target: Target,
+    base_amount: i64,
+) -> sqlx::Result<Price> {
+    let promotions = sqlx::query_as!(
+        Model,
+        "SELECT * FROM promotions WHERE target = $1 AND (channel_id IS NULL OR channel_id = $2) AND starts_at <= NOW() AND ends_at > NOW() ORDER BY percent_off DESC",
+        i64::from(target),
+        channel_id,
+    )
+    .fetch_all(db)
+    .await?;
+
+    let first_subscription = match user_id {
+        Some(user_id) if promotions.iter().any(|p| p.first_month_only) => {
+            sqlx::query_scalar!(
+                r#"SELECT NOT EXISTS(SELECT 1 FROM revenue_transactions WHERE channel_id = $1 AND user_id = $2 AND kind = $3) AS "first!""#,
+                channel_id,
+                user_id,
+                i64::from(revenue_transaction::Kind::Subscription),
+            )
+            .fetch_one(db)
+            .await?
+        }
+        _ => true,
+    };
+
+    let promotion = promotions
+        .into_iter()
+        .find(|p| p.is_eligible(first_subscription));
+
+    Ok(Price {
+        base_amount,
+        amount: promotion
+            .as_ref()
+            .map(|p| discounted_price(base_amount, p.percent_off))
+            .unwrap_or(base_amount),
+        promotion,
+    })
+}
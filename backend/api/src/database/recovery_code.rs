@@ -0,0 +1,70 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::recovery_code_event::Action;
+
+/// Characters codes are made of, without ones which are easily mistaken for each other.
+const ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A single-use code which logs in instead of a passkey, for users who lost theirs.
+pub struct Model {
+    /// The unique identifier for the code.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// The sha256 hash of the normalized code as hex.
+    pub code_hash: String,
+    /// The time the code was generated.
+    pub created_at: DateTime<Utc>,
+    /// The time the code was used. (None if not yet)
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+/// Generates a code like `k3m9p-q2xw7`. Only its hash is stored.
+pub fn generate_code() -> String {
+    let mut rng = rand::thread_rng();
+
+    let mut code = (0..10)
+        .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+        .collect::<String>();
+    code.insert(5, '-');
+    code
+}
+
+/// Hashes a code the way it is stored. Case, dashes and spaces don't matter, so codes can be typed in as they are read.
+pub fn hash_code(code: &str) -> String {
+    let normalized = code
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase();
+
+    format!("{:x}", Sha256::digest(normalized.as_bytes()))
+}
+
+/// Marks an unused code of a user as used and records it in the audit trail. Returns whether the code was valid.
+pub async fn consume(
+    db: impl sqlx::PgExecutor<'_>,
+    user_id: Uuid,
+    code: &str,
+    ip_address: Option<IpAddr>,
+    user_agent: Option<&str>,
+) -> sqlx::Result<bool> {
+    // Both happen in one statement, so a code can't be used twice by racing logins.
+    Ok(sqlx::query_scalar!(
+        "WITH used AS (UPDATE recovery_codes SET used_at = NOW() WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL RETURNING user_id) INSERT INTO recovery_code_events (user_id, action, ip_address, user_agent) SELECT user_id, $3, $4, $5 FROM used RETURNING id",
+        user_id,
+        hash_code(code),
+        i64::from(Action::Used),
+        ip_address.map(|ip| ip.to_string()).unwrap_or_default(),
+        user_agent.unwrap_or_default().chars().take(512).collect::<String>(),
+    )
+    .fetch_optional(db)
+    .await?
+    .is_some())
+}
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Action {
+    #[default]
+    Regenerated = 0,
+    Used = 1,
+}
+
+impl From<i64> for Action {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Regenerated,
+            1 => Self::Used,
+            _ => Self::Regenerated,
+        }
+    }
+}
+
+impl From<Action> for i64 {
+    fn from(value: Action) -> Self {
+        match value {
+            Action::Regenerated => 0,
+            Action::Used => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// An entry in the audit trail of a user's recovery codes.
+pub struct Model {
+    /// The unique identifier for the event.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// What happened to the codes.
+    pub action: Action,
+    /// The address the request came from.
+    pub ip_address: String,
+    /// The user agent of the request.
+    pub user_agent: String,
+    /// The time it happened.
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Kind {
+    #[default]
+    Subscription = 0,
+    Bits = 1,
+    Gift = 2,
+}
+
+impl From<i64> for Kind {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Subscription,
+            1 => Self::Bits,
+            2 => Self::Gift,
+            _ => Self::Subscription,
+        }
+    }
+}
+
+impl From<Kind> for i64 {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::Subscription => 0,
+            Kind::Bits => 1,
+            Kind::Gift => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// Money a channel earned, before the platform fee is taken.
+pub struct Model {
+    /// The unique identifier for the transaction.
+    pub id: Uuid,
+    /// The channel which earned the money.
+    pub channel_id: Uuid,
+    /// The user who paid.
+    pub user_id: Option<Uuid>,
+    /// What the money was paid for.
+    pub kind: Kind,
+    /// The amount paid in cents.
+    pub gross_amount: i64,
+    /// The part of the gross amount the platform keeps in cents.
+    pub platform_fee: i64,
+    /// The time the transaction happened.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Calculates the platform's share of a transaction, rounded down to the cent.
+pub fn platform_fee(gross_amount: i64, fee_percent: u32) -> i64 {
+    gross_amount * fee_percent.min(100) as i64 / 100
+}
+
+#[derive(Debug, Clone, Default)]
+/// The revenue of a channel in a single month, all amounts are in cents.
+pub struct MonthlyRevenue {
+    /// The start of the month.
+    pub month: DateTime<Utc>,
+    /// Income from subscriptions.
+    pub subscriptions: i64,
+    /// Income from bits.
+    pub bits: i64,
+    /// Income from gifts.
+    pub gifts: i64,
+    /// Total income before the platform fee.
+    pub gross: i64,
+    /// The part of the gross income the platform keeps.
+    pub platform_fee: i64,
+}
+
+impl MonthlyRevenue {
+    /// The income the channel keeps after the platform fee.
+    pub fn net(&self) -> i64 {
+        self.gross - self.platform_fee
+    }
+}
+
+/// Aggregates the revenue of a channel per month, newest first.
+pub async fn monthly_revenue(
+    db: &sqlx::PgPool,
+    channel_id: Uuid,
+) -> sqlx::Result<Vec<MonthlyRevenue>> {
+    sqlx::query_as!(
+        MonthlyRevenue,
+        r#"SELECT
+            date_trunc('month', created_at) AS "month!",
+            COALESCE(SUM(gross_amount) FILTER (WHERE kind = $2), 0)::bigint AS "subscriptions!",
+            COALESCE(SUM(gross_amount) FILTER (WHERE kind = $3), 0)::bigint AS "bits!",
+            COALESCE(SUM(gross_amount) FILTER (WHERE kind = $4), 0)::bigint AS "gifts!",
+            COALESCE(SUM(gross_amount), 0)::bigint AS "gross!",
+            COALESCE(SUM(platform_fee), 0)::bigint AS "platform_fee!"
+        FROM revenue_transactions
+        WHERE channel_id = $1
+        GROUP BY 1
+        ORDER BY 1 DESC"#,
+        channel_id,
+        Kind::Subscription as i64,
+        Kind::Bits as i64,
+        Kind::Gift as i64,
+    )
+    .fetch_all(db)
+    .await
+}
@@ -1,3 +1,5 @@
+use std::net::IpAddr;
+
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
@@ -15,6 +17,14 @@ pub struct Model {
     pub expires_at: DateTime<Utc>,
     /// The time the session was last used.
     pub last_used_at: DateTime<Utc>,
+    /// The time the user last reauthenticated plus the elevation duration. (None if they never did)
+    pub elevated_until: Option<DateTime<Utc>>,
+    /// The address the session was created from. (empty if unknown)
+    pub ip_address: String,
+    /// The user agent of the client which created the session. (empty if unknown)
+    pub user_agent: String,
+    /// The time a passkey or recovery code was last checked for the session. (None if it never was)
+    pub two_fa_at: Option<DateTime<Utc>>,
 }
 
 impl Model {
@@ -29,4 +39,56 @@ impl Model {
 
         true
     }
+
+    /// If the user recently reauthenticated, which sensitive actions require.
+    pub fn is_elevated(&self) -> bool {
+        self.elevated_until.map(|e| e > Utc::now()).unwrap_or(false)
+    }
+}
+
+/// User agents are cut off after this many characters, they are only shown to the user.
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// Creates a session, remembering the device it was created from so the user can tell their sessions apart.
+/// `two_fa` is set when the login checked a passkey or recovery code.
+pub async fn create(
+    db: impl sqlx::PgExecutor<'_>,
+    user_id: Uuid,
+    expires_at: DateTime<Utc>,
+    ip_address: Option<IpAddr>,
+    user_agent: Option<&str>,
+    two_fa: bool,
+) -> sqlx::Result<Model> {
+    let user_agent = user_agent
+        .unwrap_or_default()
+        .chars()
+        .take(MAX_USER_AGENT_LENGTH)
+        .collect::<String>();
+
+    sqlx::query_as!(
+        Model,
+        "INSERT INTO sessions (user_id, expires_at, ip_address, user_agent, two_fa_at) VALUES ($1, $2, $3, $4, CASE WHEN $5 THEN NOW() END) RETURNING *",
+        user_id,
+        expires_at,
+        ip_address.map(|ip| ip.to_string()).unwrap_or_default(),
+        user_agent,
+        two_fa,
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Revokes the sessions of a user which have not expired, except the one given. Returns the ids of the sessions revoked.
+pub async fn revoke_all(
+    db: impl sqlx::PgExecutor<'_>,
+    user_id: Uuid,
+    except: Option<Uuid>,
+) -> sqlx::Result<Vec<Uuid>> {
+    sqlx::query_scalar!(
+        "UPDATE sessions SET invalidated_at = NOW() WHERE user_id = $1 AND invalidated_at IS NULL AND expires_at > NOW() AND ($2::uuid IS NULL OR id <> $2) RETURNING id",
+        user_id,
+        except,
+    )
+    .fetch_all(db)
+    .await
 }
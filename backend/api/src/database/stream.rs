@@ -43,6 +43,107 @@ impl From<i64> for ReadyState {
     }
 }
 
+/// Where a stream is in its life, every service reads this instead of working it out from the ready state and end time.
+/// Only the moves in [`Lifecycle::can_become`] are allowed, each one is kept in `stream_lifecycle_transitions`.
+#[derive(Debug, Clone, Default, Copy, Eq, PartialEq)]
+#[repr(i64)]
+pub enum Lifecycle {
+    /// The ingest accepted the connection, nothing was transcoded yet.
+    #[default]
+    Requested = 0,
+    /// The variants of the stream are known.
+    Ready = 1,
+    /// Viewers can watch the stream.
+    Live = 2,
+    /// The connection dropped, the stream can still be resumed until it ends.
+    Reconnecting = 3,
+    Ended = 4,
+    /// The stream ended and its recording is being published.
+    ProcessingVod = 5,
+}
+
+impl From<Lifecycle> for i64 {
+    fn from(state: Lifecycle) -> Self {
+        match state {
+            Lifecycle::Requested => 0,
+            Lifecycle::Ready => 1,
+            Lifecycle::Live => 2,
+            Lifecycle::Reconnecting => 3,
+            Lifecycle::Ended => 4,
+            Lifecycle::ProcessingVod => 5,
+        }
+    }
+}
+
+impl From<i64> for Lifecycle {
+    fn from(state: i64) -> Self {
+        match state {
+            0 => Lifecycle::Requested,
+            1 => Lifecycle::Ready,
+            2 => Lifecycle::Live,
+            3 => Lifecycle::Reconnecting,
+            4 => Lifecycle::Ended,
+            5 => Lifecycle::ProcessingVod,
+            _ => Lifecycle::Requested,
+        }
+    }
+}
+
+impl Lifecycle {
+    /// Whether a stream in this state can move to `to`. Streams never go back, a resumed stream continues as a new one.
+    pub fn can_become(self, to: Lifecycle) -> bool {
+        match self {
+            Lifecycle::Requested => matches!(
+                to,
+                Lifecycle::Ready | Lifecycle::Live | Lifecycle::Reconnecting | Lifecycle::Ended
+            ),
+            Lifecycle::Ready => matches!(
+                to,
+                Lifecycle::Live | Lifecycle::Reconnecting | Lifecycle::Ended
+            ),
+            Lifecycle::Live => matches!(to, Lifecycle::Reconnecting | Lifecycle::Ended),
+            Lifecycle::Reconnecting => to == Lifecycle::Ended,
+            Lifecycle::Ended => to == Lifecycle::ProcessingVod,
+            Lifecycle::ProcessingVod => false,
+        }
+    }
+
+    /// Whether the stream is over, also if its recording is still being published.
+    pub fn is_over(self) -> bool {
+        matches!(self, Lifecycle::Ended | Lifecycle::ProcessingVod)
+    }
+}
+
+#[derive(Debug, Clone, Default, Copy, Eq, PartialEq)]
+#[repr(i64)]
+pub enum VodAccess {
+    #[default]
+    Public = 0,
+    Subscribers = 1,
+    EarlyAccess = 2,
+}
+
+impl From<VodAccess> for i64 {
+    fn from(access: VodAccess) -> Self {
+        match access {
+            VodAccess::Public => 0,
+            VodAccess::Subscribers => 1,
+            VodAccess::EarlyAccess => 2,
+        }
+    }
+}
+
+impl From<i64> for VodAccess {
+    fn from(access: i64) -> Self {
+        match access {
+            0 => VodAccess::Public,
+            1 => VodAccess::Subscribers,
+            2 => VodAccess::EarlyAccess,
+            _ => VodAccess::Public,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, sqlx::FromRow)]
 pub struct Model {
     /// The unique identifier for the stream.
@@ -61,6 +162,8 @@ pub struct Model {
     pub deleted: bool,
     /// Whether or not the stream is ready to be viewed.
     pub ready_state: ReadyState,
+    /// Where the stream is in its life, see [`Model::current_lifecycle`].
+    pub lifecycle: Lifecycle,
     /// Ingest Address address of the ingest server controlling the stream.
     pub ingest_address: String,
     /// The connection which owns the stream.
@@ -74,4 +177,37 @@ pub struct Model {
     pub updated_at: Option<DateTime<Utc>>,
     /// The time the stream ended. (will be in the future if the stream is live)
     pub ended_at: DateTime<Utc>,
+    /// Whether the chat messages sent during the stream were kept.
+    pub chat_archived: bool,
+    /// Who can watch the recording of the stream.
+    pub vod_access: VodAccess,
+    /// The time an early access recording becomes public. (None unless the access is early access)
+    pub vod_public_at: Option<DateTime<Utc>>,
+    /// The region tag the stream was recorded in, its recording stays there.
+    pub region: String,
+}
+
+impl Model {
+    /// The lifecycle of the stream at `now`. Streams whose ingest stopped reporting them are not moved on by anyone,
+    /// so a stream past its end time counts as ended even if it was never moved there.
+    pub fn current_lifecycle(&self, now: DateTime<Utc>) -> Lifecycle {
+        if !self.lifecycle.is_over() && self.ended_at <= now {
+            if self.recorded {
+                Lifecycle::ProcessingVod
+            } else {
+                Lifecycle::Ended
+            }
+        } else {
+            self.lifecycle
+        }
+    }
+
+    /// Whether the recording can be watched by viewers who are not subscribed to the channel.
+    pub fn vod_public(&self, now: DateTime<Utc>) -> bool {
+        match self.vod_access {
+            VodAccess::Public => true,
+            VodAccess::Subscribers => false,
+            VodAccess::EarlyAccess => self.vod_public_at.map_or(true, |at| at <= now),
+        }
+    }
 }
@@ -0,0 +1,156 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Chapters shorter than this are left out of the VOD, so fixing a typo in the title doesn't make a chapter of a few seconds.
+pub const MIN_CHAPTER_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A part of a stream with the same title and category, a new one starts whenever the broadcaster changes them.
+pub struct Model {
+    /// The unique identifier for the chapter.
+    pub id: Uuid,
+    /// Foreign key to the streams table.
+    pub stream_id: Uuid,
+    /// The title of the stream during the chapter.
+    pub title: String,
+    /// The category of the stream during the chapter.
+    pub category: String,
+    /// The position the chapter starts at in seconds since the stream started.
+    pub position_seconds: i64,
+    /// The time the chapter was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A chapter of a VOD as it is shown to viewers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    pub title: String,
+    pub category: String,
+    pub start_seconds: i64,
+    pub end_seconds: i64,
+}
+
+/// Starts a chapter of a stream with the given title and category, unless the last chapter already has them.
+pub async fn record(
+    db: impl sqlx::PgExecutor<'_>,
+    stream_id: Uuid,
+    position_seconds: i64,
+    title: &str,
+    category: &str,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "INSERT INTO stream_chapters (stream_id, title, category, position_seconds) SELECT $1, $2, $3, $4 WHERE NOT EXISTS (SELECT 1 FROM (SELECT title, category FROM stream_chapters WHERE stream_id = $1 ORDER BY position_seconds DESC, created_at DESC LIMIT 1) last WHERE last.title = $2 AND last.category = $3)",
+        stream_id,
+        title,
+        category,
+        position_seconds,
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// The chapters of a stream, in the order they were recorded.
+pub async fn for_stream(
+    db: impl sqlx::PgExecutor<'_>,
+    stream_id: Uuid,
+) -> sqlx::Result<Vec<Model>> {
+    sqlx::query_as!(
+        Model,
+        "SELECT * FROM stream_chapters WHERE stream_id = $1 ORDER BY position_seconds, created_at",
+        stream_id,
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Turns the recorded chapters of a stream into the chapters of its VOD, which is `duration_seconds` long.
+/// Chapters shorter than [`MIN_CHAPTER_SECONDS`] are taken over by the next one, or the previous one at the end,
+/// and neighbours left with the same title and category are joined. The first chapter starts at the start of the VOD.
+pub fn chapters(models: &[Model], duration_seconds: i64) -> Vec<Chapter> {
+    let mut chapters = Vec::<Chapter>::new();
+    let mut taken_over = None;
+
+    for (i, model) in models.iter().enumerate() {
+        let start = taken_over.take().unwrap_or(model.position_seconds);
+        let end = models
+            .get(i + 1)
+            .map_or(duration_seconds, |next| next.position_seconds)
+            .min(duration_seconds);
+
+        if end - model.position_seconds < MIN_CHAPTER_SECONDS {
+            if i + 1 < models.len() {
+                taken_over = Some(start);
+                continue;
+            }
+
+            if !chapters.is_empty() {
+                break;
+            }
+        }
+
+        match chapters.last_mut() {
+            Some(last) if last.title == model.title && last.category == model.category => {
+                last.end_seconds = end;
+            }
+            _ => chapters.push(Chapter {
+                title: model.title.clone(),
+                category: model.category.clone(),
+                start_seconds: if chapters.is_empty() { 0 } else { start },
+                end_seconds: end,
+            }),
+        }
+    }
+
+    if let Some(last) = chapters.last_mut() {
+        last.end_seconds = duration_seconds.max(last.start_seconds);
+    }
+
+    chapters
+}
+
+fn timestamp(seconds: i64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.000",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Escapes the text of a cue, it can't contain `&`, `<` or `-->` and ends at a blank line.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', " ")
+}
+
+/// Renders chapters as a WebVTT chapters track, which players show as markers on the seek bar.
+pub fn webvtt(chapters: &[Chapter]) -> String {
+    let mut out = String::from("WEBVTT\n");
+
+    for (i, chapter) in chapters.iter().enumerate() {
+        let label = match (chapter.title.is_empty(), chapter.category.is_empty()) {
+            (false, false) => format!("{} ({})", chapter.title, chapter.category),
+            (false, true) => chapter.title.clone(),
+            (true, false) => chapter.category.clone(),
+            (true, true) => format!("Chapter {}", i + 1),
+        };
+
+        write!(
+            out,
+            "\n{}\n{} --> {}\n{}\n",
+            i + 1,
+            timestamp(chapter.start_seconds),
+            timestamp(chapter.end_seconds),
+            escape(&label)
+        )
+        .unwrap();
+    }
+
+    out
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+pub const MAX_NAME_LENGTH: usize = 64;
+
+/// How many characters of a key are left readable when it is masked.
+const VISIBLE_CHARACTERS: usize = 4;
+
+#[derive(Debug, Clone, Default)]
+/// A named stream key a channel can go live with besides its default key, like one per encoder.
+pub struct Model {
+    /// The unique identifier for the key.
+    pub id: Uuid,
+    /// Foreign key to the users table, the channel the key goes live on.
+    pub channel_id: Uuid,
+    /// The name the broadcaster gave the key.
+    pub name: String,
+    /// The secret part of the key, after `live_{channel id}_`.
+    pub key: String,
+    /// The time the key was created.
+    pub created_at: DateTime<Utc>,
+    /// The time the key was last used to go live. (None if never)
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// The full key an encoder streams with, from the secret part of it.
+pub fn full_key(channel_id: Uuid, key: &str) -> String {
+    format!("live_{}_{}", channel_id.as_u128(), key)
+}
+
+/// The full key with all but the last characters of the secret hidden, so a key can be told apart without showing it.
+pub fn mask(channel_id: Uuid, key: &str) -> String {
+    let hidden = key.chars().count().saturating_sub(VISIBLE_CHARACTERS);
+    let masked = key
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if i < hidden { '*' } else { c })
+        .collect::<String>();
+
+    full_key(channel_id, &masked)
+}
+
+/// Validates the name of a key. Names are saved trimmed, so the length is checked without the surrounding whitespace.
+pub fn validate_name(name: &str) -> Result<(), &'static str> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err("Name must be between 1 and 64 characters");
+    }
+
+    Ok(())
+}
+
+/// Whether the secret part of a key is one of the named keys of a channel, or a key regenerated so recently it still works.
+/// A named key is marked as used.
+pub async fn authenticate(db: &sqlx::PgPool, channel_id: Uuid, key: &str) -> sqlx::Result<bool> {
+    let named = sqlx::query_scalar!(
+        "UPDATE stream_keys SET last_used_at = NOW() WHERE channel_id = $1 AND key = $2 RETURNING id",
+        channel_id,
+        key,
+    )
+    .fetch_optional(db)
+    .await?;
+
+    if named.is_some() {
+        return Ok(true);
+    }
+
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM retired_stream_keys WHERE channel_id = $1 AND key = $2 AND expires_at > NOW()) AS "retired!""#,
+        channel_id,
+        key,
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Keeps a regenerated key working until `expires_at`, and forgets the keys of the channel which stopped working.
+pub async fn retire(
+    conn: &mut sqlx::PgConnection,
+    channel_id: Uuid,
+    key: &str,
+    expires_at: DateTime<Utc>,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        "DELETE FROM retired_stream_keys WHERE channel_id = $1 AND expires_at <= NOW()",
+        channel_id,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO retired_stream_keys (channel_id, key, expires_at) VALUES ($1, $2, $3) ON CONFLICT (channel_id, key) DO UPDATE SET expires_at = EXCLUDED.expires_at",
+        channel_id,
+        key,
+        expires_at,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::stream::Lifecycle;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A move of a stream from one lifecycle state to the next.
+pub struct Model {
+    /// The unique identifier for the transition.
+    pub id: Uuid,
+    /// Foreign key to the streams table.
+    pub stream_id: Uuid,
+    /// The state the stream was in.
+    pub from_state: Lifecycle,
+    /// The state the stream moved to.
+    pub to_state: Lifecycle,
+    /// The time the stream moved.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Moves a stream to `to` and records the transition, returns None if the stream can't move there from where it is.
+/// The stream row is locked until the transaction ends, so two updates for the same stream can't both move it.
+pub async fn transition(
+    tx: &mut sqlx::PgConnection,
+    stream_id: Uuid,
+    to: Lifecycle,
+) -> sqlx::Result<Option<Model>> {
+    let from = sqlx::query_scalar!(
+        "SELECT lifecycle FROM streams WHERE id = $1 FOR UPDATE",
+        stream_id,
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .map(Lifecycle::from);
+
+    let Some(from) = from.filter(|from| from.can_become(to)) else {
+        return Ok(None);
+    };
+
+    sqlx::query!(
+        "UPDATE streams SET lifecycle = $2 WHERE id = $1",
+        stream_id,
+        i64::from(to),
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query_as!(
+        Model,
+        "INSERT INTO stream_lifecycle_transitions (stream_id, from_state, to_state) VALUES ($1, $2, $3) RETURNING *",
+        stream_id,
+        i64::from(from),
+        i64::from(to),
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map(Some)
+}
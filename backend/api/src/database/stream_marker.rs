@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A point in a stream the streamer marked to find it again in the VOD.
+pub struct Model {
+    /// The unique identifier for the marker.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// Foreign key to the streams table.
+    pub stream_id: Uuid,
+    /// What happened at this point.
+    pub description: String,
+    /// The position of the marker in seconds since the stream started.
+    pub position_seconds: i64,
+    /// The time the marker was created.
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A summary of a finished stream, written when the stream ends.
+pub struct Model {
+    /// The unique identifier for the session.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// Foreign key to the streams table.
+    pub stream_id: Uuid,
+    /// The number of distinct users who sent a chat message during the stream.
+    pub unique_chatters: i64,
+    /// The number of follows the channel received during the stream.
+    pub new_follows: i64,
+    /// The time the stream started.
+    pub started_at: DateTime<Utc>,
+    /// The time the stream ended.
+    pub ended_at: DateTime<Utc>,
+}
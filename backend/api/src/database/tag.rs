@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// The most tags a channel can have.
+pub const MAX_CHANNEL_TAGS: usize = 10;
+
+pub const MAX_NAME_LENGTH: usize = 25;
+pub const MAX_DESCRIPTION_LENGTH: usize = 200;
+
+#[derive(Debug, Clone, Default)]
+/// A tag from the list admins keep, broadcasters pick some for their channel so viewers can find it in the directory.
+pub struct Model {
+    /// The unique identifier for the tag.
+    pub id: Uuid,
+    /// The name of the tag, unique without case.
+    pub name: String,
+    /// What the tag is for.
+    pub description: String,
+    /// The time the tag was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Validates the name of a new tag. Names are letters, digits, spaces and hyphens so they read the same everywhere.
+pub fn validate_name(name: &str) -> Result<(), &'static str> {
+    let name = name.trim();
+
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err("Name must be between 1 and 25 characters");
+    }
+
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == ' ' || c == '-')
+    {
+        return Err("Name can only contain letters, digits, spaces and hyphens");
+    }
+
+    Ok(())
+}
+
+/// The names to look tags up by, without case and without repeats, in the order they were given.
+pub fn normalize(names: &[String]) -> Vec<String> {
+    let mut normalized = Vec::<String>::new();
+
+    for name in names {
+        let name = name.trim().to_lowercase();
+        if !name.is_empty() && !normalized.contains(&name) {
+            normalized.push(name);
+        }
+    }
+
+    normalized
+}
+
+/// The tags of a channel, in the order the broadcaster put them in.
+pub async fn for_channel(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+) -> sqlx::Result<Vec<Model>> {
+    sqlx::query_as!(
+        Model,
+        "SELECT t.* FROM tags t JOIN channel_tags ct ON ct.tag_id = t.id WHERE ct.channel_id = $1 ORDER BY ct.position",
+        channel_id,
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Replaces the tags of a channel with the given ones, in order. The tags have to exist already.
+pub async fn replace_for_channel(
+    conn: &mut sqlx::PgConnection,
+    channel_id: Uuid,
+    tag_ids: &[Uuid],
+) -> sqlx::Result<()> {
+    sqlx::query!("DELETE FROM channel_tags WHERE channel_id = $1", channel_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query!(
+        "INSERT INTO channel_tags (channel_id, tag_id, position) SELECT $1, tag_id, position - 1 FROM UNNEST($2::uuid[]) WITH ORDINALITY AS t(tag_id, position)",
+        channel_id,
+        tag_ids,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
@@ -1,11 +1,18 @@
 use argon2::{
     password_hash::{rand_core::OsRng, SaltString},
-    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
+    Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version,
 };
 use chrono::{DateTime, Utc};
+use common::{config::DatabaseConfig, database::retry};
 use rand::Rng;
 use uuid::Uuid;
 
+use super::{channel_event, stream_chapter};
+
+/// The Argon2 memory cost (in KiB), iterations and parallelism new passwords are hashed with.
+/// Raising them upgrades existing hashes the next time their user logs in.
+const PASSWORD_HASH_PARAMS: (u32, u32, u32) = (19456, 2, 1);
+
 #[derive(Debug, Clone, Default)]
 #[repr(i32)]
 pub enum LiveState {
@@ -26,6 +33,36 @@ impl From<i32> for LiveState {
     }
 }
 
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum PresenceVisibility {
+    #[default]
+    Nobody = 0,
+    Friends = 1,
+    Followers = 2,
+}
+
+impl From<i64> for PresenceVisibility {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Nobody,
+            1 => Self::Friends,
+            2 => Self::Followers,
+            _ => Self::Nobody,
+        }
+    }
+}
+
+impl From<PresenceVisibility> for i64 {
+    fn from(value: PresenceVisibility) -> Self {
+        match value {
+            PresenceVisibility::Nobody => 0,
+            PresenceVisibility::Friends => 1,
+            PresenceVisibility::Followers => 2,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Model {
     /// The unique identifier for the user.
@@ -40,6 +77,8 @@ pub struct Model {
     pub email: String,
     /// Whether the user has verified their email.
     pub email_verified: bool,
+    /// The keyed hash of the email, used to find the user by email. (None if not computed yet)
+    pub email_hash: Option<String>,
     /// The time the user was created.
     pub created_at: DateTime<Utc>,
     /// The time the user last logged in.
@@ -50,14 +89,55 @@ pub struct Model {
     pub stream_title: String,
     /// The description of the stream
     pub stream_description: String,
+    /// The category of the stream
+    pub stream_category: String,
+    /// The BCP-47 language tag of the stream (empty if not set)
+    pub stream_language: String,
+    /// The url of the profile image (empty if the user has none)
+    pub profile_image_url: String,
+    /// The image processor job of a new profile image (None if no image is being processed)
+    pub profile_image_job_id: Option<Uuid>,
+    /// The url the new profile image is served from once it is processed
+    pub pending_profile_image_url: Option<String>,
     /// Whether the stream transcoding is enabled
     pub stream_transcoding_enabled: bool,
     /// Whether the stream recording is enabled
     pub stream_recording_enabled: bool,
+    /// The color of the display name, a palette name or hex colors (empty if the user has none)
+    pub display_color: String,
+    /// The second color of a display name gradient, in the same format (empty if the name is a solid color)
+    pub display_gradient_end: String,
+    /// Who can see whether the user is online
+    pub presence_visibility: PresenceVisibility,
+    /// Whether the channel the user watches is shown to those who can see them online
+    pub presence_share_watching: bool,
+    /// The sanitized markdown shown on the profile page (empty if the user has none)
+    pub bio: String,
+    /// The seconds a user has to wait between two messages in the chat of the user (0 if slow mode is off)
+    pub chat_slow_mode: i32,
+    /// Whether messages with links are rejected in the chat of the user
+    pub chat_block_links: bool,
+    /// Whether chat messages in the chat of the user are kept after they were sent
+    pub chat_archive: bool,
+    /// The BCP-47 language tag of the language emails to the user are written in
+    pub locale: String,
+    /// The IANA time zone times are shown to the user in
+    pub timezone: String,
+    /// The region tag the data of the user is kept in, empty for the default region
+    pub data_region: String,
+    /// Whether logging in with the password also needs one of the user's passkeys
+    pub passkey_required: bool,
+    /// Whether moderating the channel, by the owner or an admin, needs a passkey as second factor
+    pub moderation_two_fa_required: bool,
+    /// When an admin verified the account as a bot, None if it is not a verified bot
+    pub bot_verified_at: Option<DateTime<Utc>>,
+    /// Whether viewers can request media in the channel of the user
+    pub media_requests_enabled: bool,
 }
 
 impl Model {
     /// Uses argon2 to verify the password hash against the provided password.
+    /// The algorithm and parameters are read from the hash, so hashes made with older parameters still verify.
     pub fn verify_password(&self, password: &str) -> bool {
         let hash = match PasswordHash::new(&self.password_hash) {
             Ok(hash) => hash,
@@ -72,16 +152,55 @@ impl Model {
             .is_ok()
     }
 
+    /// If the password hash was made with other parameters than new passwords are, and should be replaced.
+    pub fn password_needs_rehash(&self) -> bool {
+        let Ok(hash) = PasswordHash::new(&self.password_hash) else {
+            return true;
+        };
+
+        let Ok(params) = Params::try_from(&hash) else {
+            return true;
+        };
+
+        hash.algorithm != Algorithm::Argon2id.ident()
+            || hash.version != Some(Version::V0x13.into())
+            || (params.m_cost(), params.t_cost(), params.p_cost()) != PASSWORD_HASH_PARAMS
+    }
+
     pub fn get_stream_key(&self) -> String {
         format!("live_{}_{}", self.id.as_u128(), self.stream_key)
     }
 }
 
+fn password_hasher() -> Argon2<'static> {
+    let (m_cost, t_cost, p_cost) = PASSWORD_HASH_PARAMS;
+
+    Argon2::new(
+        Algorithm::Argon2id,
+        Version::V0x13,
+        Params::new(m_cost, t_cost, p_cost, None).expect("invalid password hash params"),
+    )
+}
+
+/// The start of every password hash made with the current parameters, hashes which don't start with it are legacy.
+pub fn password_hash_prefix() -> String {
+    let (m_cost, t_cost, p_cost) = PASSWORD_HASH_PARAMS;
+
+    format!(
+        "${}$v={}$m={},t={},p={}$",
+        Algorithm::Argon2id.ident(),
+        u32::from(Version::V0x13),
+        m_cost,
+        t_cost,
+        p_cost
+    )
+}
+
 /// Generates a new password hash using argon2.
 pub fn hash_password(password: &str) -> String {
     let salt = SaltString::generate(&mut OsRng);
 
-    let hash = Argon2::default()
+    let hash = password_hasher()
         .hash_password(password.as_bytes(), &salt)
         .expect("failed to hash password");
 
@@ -162,6 +281,137 @@ pub fn validate_email(email: &str) -> Result<(), &'static str> {
     Ok(())
 }
 
+/// Validates a locale, it has to be a well-formed and registered BCP-47 language tag like `en-US`.
+pub fn validate_locale(locale: &str) -> Result<(), &'static str> {
+    if locale.len() > 35 {
+        return Err("Locale must be at most 35 characters long");
+    }
+
+    language_tags::LanguageTag::parse(locale)
+        .ok()
+        .filter(|tag| tag.validate().is_ok())
+        .map(|_| ())
+        .ok_or("Locale is not a valid BCP-47 language tag")
+}
+
+/// Validates a time zone, it has to be a name from the IANA time zone database like `Europe/Berlin`.
+pub fn validate_timezone(timezone: &str) -> Result<(), &'static str> {
+    timezone
+        .parse::<chrono_tz::Tz>()
+        .map(|_| ())
+        .map_err(|_| "Timezone is not a valid IANA time zone")
+}
+
+/// Validates a bio, before it is sanitized.
+pub fn validate_bio(bio: &str) -> Result<(), &'static str> {
+    if bio.chars().count() > 1000 {
+        return Err("Bio must be at most 1000 characters long");
+    }
+
+    if bio.lines().count() > 50 {
+        return Err("Bio must be at most 50 lines long");
+    }
+
+    Ok(())
+}
+
+/// Makes a markdown bio safe to render on the profile page. Raw HTML is escaped so it shows as text,
+/// links which don't go to a http(s) or mailto url go nowhere, control characters are dropped
+/// and runs of blank lines are collapsed into one.
+pub fn sanitize_bio(bio: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in bio.lines() {
+        let line = line
+            .chars()
+            .filter(|c| *c == '\t' || !c.is_control())
+            .collect::<String>()
+            .replace('<', "&lt;");
+
+        if line.trim().is_empty() {
+            if lines.last().map_or(true, |l| l.is_empty()) {
+                continue;
+            }
+
+            lines.push(String::new());
+        } else {
+            lines.push(sanitize_links(&line));
+        }
+    }
+
+    if lines.last().map_or(false, |l| l.is_empty()) {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
+
+/// Replaces the destinations of inline links `[text](url)` and reference definitions `[label]: url` in a line.
+fn sanitize_links(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('[') {
+        if let Some(i) = trimmed
+            .find(']')
+            .filter(|i| trimmed[i + 1..].starts_with(':'))
+        {
+            let start = line.len() - trimmed.len() + i + 2;
+            out.push_str(&line[..start]);
+            rest = push_link_destination(&mut out, &line[start..]);
+        }
+    }
+
+    while let Some(i) = rest.find("](") {
+        out.push_str(&rest[..i + 2]);
+        rest = push_link_destination(&mut out, &rest[i + 2..]);
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Pushes the link destination at the start of `rest`, or `#` if it is not safe, and returns what follows it.
+fn push_link_destination<'a>(out: &mut String, rest: &'a str) -> &'a str {
+    let leading = rest.len() - rest.trim_start().len();
+    out.push_str(&rest[..leading]);
+
+    let rest = &rest[leading..];
+    // Destinations can contain balanced parentheses, like `(javascript:alert(1))`.
+    let mut depth = 0;
+    let end = rest
+        .find(|c: char| match c {
+            '(' => {
+                depth += 1;
+                false
+            }
+            ')' if depth == 0 => true,
+            ')' => {
+                depth -= 1;
+                false
+            }
+            c => c.is_whitespace(),
+        })
+        .unwrap_or(rest.len());
+    let (url, rest) = rest.split_at(end);
+
+    // Renderers decode entities and ignore the case of schemes, so anything else is dropped
+    // instead of trying to tell which other urls are harmless.
+    let lower = url.to_ascii_lowercase();
+    if url.is_empty()
+        || ["http://", "https://", "mailto:"]
+            .iter()
+            .any(|s| lower.starts_with(s))
+    {
+        out.push_str(url);
+    } else {
+        out.push('#');
+    }
+
+    rest
+}
+
 /// Generates a new stream key.
 pub fn generate_stream_key() -> String {
     let mut rng = rand::thread_rng();
@@ -173,3 +423,132 @@ pub fn generate_stream_key() -> String {
 
     key
 }
+
+pub const MAX_STREAM_TITLE_LENGTH: usize = 255;
+pub const MAX_STREAM_CATEGORY_LENGTH: usize = 64;
+
+/// The title, category and language of a channel's stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamInfo {
+    pub title: String,
+    pub category: String,
+    pub language: String,
+}
+
+/// Validates the language of a stream, it has to be a BCP-47 language tag like `en`. Empty means not set.
+pub fn validate_stream_language(language: &str) -> Result<(), &'static str> {
+    if language.is_empty() {
+        return Ok(());
+    }
+
+    validate_locale(language).map_err(|_| "Language is not a valid BCP-47 language tag")
+}
+
+/// Changes the title, category and / or language of a channel's stream. A running stream gets the new title right away,
+/// and a new chapter if the title or category changed.
+/// Returns the stream info after the update. The update is sent again if it conflicts with a concurrent one.
+pub async fn update_stream_info(
+    db: &sqlx::PgPool,
+    config: &DatabaseConfig,
+    channel_id: Uuid,
+    title: Option<&str>,
+    category: Option<&str>,
+    language: Option<&str>,
+) -> sqlx::Result<StreamInfo> {
+    retry(config, || {
+        store_stream_info(db, channel_id, title, category, language)
+    })
+    .await
+}
+
+async fn store_stream_info(
+    db: &sqlx::PgPool,
+    channel_id: Uuid,
+    title: Option<&str>,
+    category: Option<&str>,
+    language: Option<&str>,
+) -> sqlx::Result<StreamInfo> {
+    let mut tx = db.begin().await?;
+
+    let updated = sqlx::query!(
+        "UPDATE users SET stream_title = COALESCE($2, stream_title), stream_category = COALESCE($3, stream_category), stream_language = COALESCE($4, stream_language) WHERE id = $1 RETURNING stream_title, stream_category, stream_language",
+        channel_id,
+        title,
+        category,
+        language,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let streams = sqlx::query!(
+        "UPDATE streams SET title = $2 WHERE channel_id = $1 AND deleted = FALSE AND ended_at > NOW() RETURNING id, created_at",
+        channel_id,
+        updated.stream_title,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    // Changing the title or category mid-stream starts a new chapter of the VOD.
+    for stream in streams {
+        stream_chapter::record(
+            &mut *tx,
+            stream.id,
+            (Utc::now() - stream.created_at).num_seconds(),
+            &updated.stream_title,
+            &updated.stream_category,
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(StreamInfo {
+        title: updated.stream_title,
+        category: updated.stream_category,
+        language: updated.stream_language,
+    })
+}
+
+/// Whether a user lets the viewer see when they are online, the rules of `presence_visible_to` for a single user.
+pub async fn presence_visible(
+    db: impl sqlx::PgExecutor<'_>,
+    viewer_id: Uuid,
+    user_id: Uuid,
+) -> sqlx::Result<bool> {
+    if viewer_id == user_id {
+        return Ok(true);
+    }
+
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM users u WHERE u.id = $2 AND ((u.presence_visibility = $4 AND EXISTS (SELECT 1 FROM channel_events WHERE user_id = $1 AND channel_id = u.id AND kind = $3)) OR (u.presence_visibility IN ($4, $5) AND EXISTS (SELECT 1 FROM friends f WHERE f.user_id = $1 AND f.friend_id = u.id)))) AS "visible!""#,
+        viewer_id,
+        user_id,
+        i64::from(channel_event::Kind::Follow),
+        i64::from(PresenceVisibility::Followers),
+        i64::from(PresenceVisibility::Friends),
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// The users a user follows or is friends with who let them see when they are online, and whether they share what they watch.
+/// Friends see the presence of users who only show it to followers too, without having to follow them.
+pub async fn presence_visible_to(
+    db: impl sqlx::PgExecutor<'_>,
+    viewer_id: Uuid,
+) -> sqlx::Result<Vec<(Uuid, bool)>> {
+    let rows = sqlx::query!(
+        "SELECT u.id, u.presence_share_watching FROM users u WHERE (u.presence_visibility = $3 AND u.id IN (SELECT channel_id FROM channel_events WHERE user_id = $1 AND kind = $2)) OR (u.presence_visibility IN ($3, $4) AND u.id IN (SELECT friend_id FROM friends WHERE user_id = $1))",
+        viewer_id,
+        i64::from(channel_event::Kind::Follow),
+        i64::from(PresenceVisibility::Followers),
+        i64::from(PresenceVisibility::Friends),
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.id, row.presence_share_watching))
+        .collect())
+}
@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// A user another user blocked. They can't follow each other, and their chat messages are hidden from the blocker.
+pub struct Model {
+    /// Foreign key to the users table, who blocked.
+    pub user_id: Uuid,
+    /// Foreign key to the users table, the blocked user.
+    pub blocked_id: Uuid,
+    /// The time the user was blocked.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Whether either of the users blocked the other.
+pub async fn between(
+    db: impl sqlx::PgExecutor<'_>,
+    user_id: Uuid,
+    other_id: Uuid,
+) -> sqlx::Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM user_blocks WHERE (user_id = $1 AND blocked_id = $2) OR (user_id = $2 AND blocked_id = $1)) AS "blocked!""#,
+        user_id,
+        other_id,
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// The users a user blocked.
+pub async fn blocked_by(db: impl sqlx::PgExecutor<'_>, user_id: Uuid) -> sqlx::Result<Vec<Uuid>> {
+    sqlx::query_scalar!(
+        "SELECT blocked_id FROM user_blocks WHERE user_id = $1",
+        user_id,
+    )
+    .fetch_all(db)
+    .await
+}
@@ -0,0 +1,95 @@
+use uuid::Uuid;
+
+/// The most links a user can have on their profile.
+pub const MAX_LINKS: usize = 5;
+
+/// The schemes a link may use. Anything else, like `javascript:`, could run in the page of whoever clicks it.
+const ALLOWED_SCHEMES: &[&str] = &["https", "http", "mailto"];
+
+#[derive(Debug, Clone, Default)]
+/// A link on the profile of a user, like their Twitter or Discord server.
+pub struct Model {
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// The position of the link on the profile, lowest first.
+    pub position: i32,
+    /// The text the link is shown with.
+    pub label: String,
+    /// The url the link goes to.
+    pub url: String,
+}
+
+/// Validates the label of a link.
+pub fn validate_label(label: &str) -> Result<(), &'static str> {
+    if label.trim().is_empty() {
+        return Err("Label must not be empty");
+    }
+
+    if label.chars().count() > 32 {
+        return Err("Label must be at most 32 characters long");
+    }
+
+    Ok(())
+}
+
+/// Validates the url of a link.
+pub fn validate_url(url: &str) -> Result<(), &'static str> {
+    if url.len() > 512 {
+        return Err("Url must be at most 512 characters long");
+    }
+
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err("Url must not contain spaces");
+    }
+
+    let Some((scheme, rest)) = url.split_once(':') else {
+        return Err("Url must start with a scheme like https://");
+    };
+
+    if !ALLOWED_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()) {
+        return Err("Url must be a http(s) or mailto link");
+    }
+
+    let valid = match rest.strip_prefix("//") {
+        Some(rest) => !rest
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default()
+            .is_empty(),
+        None => scheme.eq_ignore_ascii_case("mailto") && rest.contains('@'),
+    };
+
+    if !valid {
+        return Err("Url is not a valid link");
+    }
+
+    Ok(())
+}
+
+/// Replaces the links of a user with the given labels and urls, in order. The links have to be validated already.
+pub async fn replace(
+    conn: &mut sqlx::PgConnection,
+    user_id: Uuid,
+    links: &[(String, String)],
+) -> sqlx::Result<Vec<Model>> {
+    sqlx::query!("DELETE FROM user_social_links WHERE user_id = $1", user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let (labels, urls): (Vec<_>, Vec<_>) = links.iter().cloned().unzip();
+
+    let mut links = sqlx::query_as!(
+        Model,
+        "INSERT INTO user_social_links (user_id, position, label, url) SELECT $1, position - 1, label, url FROM UNNEST($2::text[], $3::text[]) WITH ORDINALITY AS l(label, url, position) RETURNING *",
+        user_id,
+        &labels,
+        &urls,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    // RETURNING does not keep the order of the insert.
+    links.sort_by_key(|link| link.position);
+
+    Ok(links)
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A site-wide suspension of an account. Suspended users can log in to appeal, but can not use the rest of the site.
+pub struct Model {
+    /// The unique identifier for the suspension.
+    pub id: Uuid,
+    /// Foreign key to the users table, the suspended user.
+    pub user_id: Uuid,
+    /// Foreign key to the users table, the admin who suspended the user. (None if their account was deleted)
+    pub suspended_by: Option<Uuid>,
+    /// The reason shown to the user.
+    pub reason: String,
+    /// Foreign key to the users table, the admin who lifted the suspension. (None if it expired)
+    pub lifted_by: Option<Uuid>,
+    /// The reason the suspension was lifted.
+    pub lift_reason: String,
+    /// The time the user was suspended.
+    pub created_at: DateTime<Utc>,
+    /// The time the suspension ends on its own. (None if it is permanent)
+    pub expires_at: Option<DateTime<Utc>>,
+    /// The time the suspension was lifted or expired.
+    pub lifted_at: Option<DateTime<Utc>>,
+}
+
+impl Model {
+    pub fn is_active(&self) -> bool {
+        self.lifted_at.is_none() && self.expires_at.map(|e| e > Utc::now()).unwrap_or(true)
+    }
+}
+
+/// Gets the suspension a user is currently under, if any.
+pub async fn active(db: &sqlx::PgPool, user_id: Uuid) -> sqlx::Result<Option<Model>> {
+    sqlx::query_as!(
+        Model,
+        "SELECT * FROM user_suspensions WHERE user_id = $1 AND lifted_at IS NULL AND (expires_at IS NULL OR expires_at > NOW()) ORDER BY created_at DESC LIMIT 1",
+        user_id,
+    )
+    .fetch_optional(db)
+    .await
+}
@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// A username a user had before changing it.
+pub struct Model {
+    /// The unique identifier for the entry.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// The username before the change.
+    pub username: String,
+    /// The time the username was changed.
+    pub changed_at: DateTime<Utc>,
+}
+
+/// The time the user last changed their username, None if they never did.
+pub async fn last_change(
+    db: impl sqlx::PgExecutor<'_>,
+    user_id: Uuid,
+) -> sqlx::Result<Option<DateTime<Utc>>> {
+    sqlx::query_scalar!(
+        "SELECT MAX(changed_at) FROM username_history WHERE user_id = $1",
+        user_id
+    )
+    .fetch_one(db)
+    .await
+}
@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+pub const MAX_TITLE_LENGTH: usize = 140;
+pub const MAX_SIZE: u32 = 1000;
+
+/// The chat message which joins the queue of the chat.
+pub const JOIN_COMMAND: &str = "!join";
+/// The chat message which leaves the queue of the chat.
+pub const LEAVE_COMMAND: &str = "!leave";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Join,
+    Leave,
+}
+
+/// The queue command a chat message is, if it is one.
+pub fn command(content: &str) -> Option<Command> {
+    let content = content.trim();
+
+    if content.eq_ignore_ascii_case(JOIN_COMMAND) {
+        Some(Command::Join)
+    } else if content.eq_ignore_ascii_case(LEAVE_COMMAND) {
+        Some(Command::Leave)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A queue of viewers waiting to play with the broadcaster, a channel has at most one.
+pub struct Model {
+    /// Foreign key to the users table, the channel the queue is for.
+    pub channel_id: Uuid,
+    /// What the viewers queue for.
+    pub title: String,
+    /// Whether viewers can join. A closed queue keeps its viewers.
+    pub open: bool,
+    /// The most viewers who can wait at once. (None if unlimited)
+    pub max_size: Option<i32>,
+    /// The time the queue was created.
+    pub created_at: DateTime<Utc>,
+    /// The time the queue was last opened, closed or renamed.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Validates the title and size of a queue.
+pub fn validate(title: &str, max_size: Option<u32>) -> Result<(), (&'static str, &'static str)> {
+    if title.trim().is_empty() || title.len() > MAX_TITLE_LENGTH {
+        return Err(("title", "Title must be between 1 and 140 characters"));
+    }
+
+    if let Some(max_size) = max_size {
+        if !(1..=MAX_SIZE).contains(&max_size) {
+            return Err(("maxSize", "Size must be between 1 and 1000"));
+        }
+    }
+
+    Ok(())
+}
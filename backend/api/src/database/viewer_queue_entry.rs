@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// A viewer waiting in the queue of a channel.
+pub struct Model {
+    /// Foreign key to the viewer_queues table.
+    pub channel_id: Uuid,
+    /// Foreign key to the users table.
+    pub user_id: Uuid,
+    /// Where the viewer is in the queue, lower goes first. Only the order matters, not the value.
+    pub position: i64,
+    /// The time the viewer joined.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Where a viewer is in the queue of a channel counting from 1, None if they are not in it.
+pub async fn position(
+    db: impl sqlx::PgExecutor<'_>,
+    channel_id: Uuid,
+    user_id: Uuid,
+) -> sqlx::Result<Option<i64>> {
+    sqlx::query_scalar!(
+        r#"SELECT (SELECT COUNT(*) FROM viewer_queue_entries o WHERE o.channel_id = e.channel_id AND (o.position, o.user_id) < (e.position, e.user_id)) + 1 AS "position!" FROM viewer_queue_entries e WHERE e.channel_id = $1 AND e.user_id = $2"#,
+        channel_id,
+        user_id,
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// How many viewers wait in the queue of a channel.
+pub async fn count(db: impl sqlx::PgExecutor<'_>, channel_id: Uuid) -> sqlx::Result<i64> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM viewer_queue_entries WHERE channel_id = $1"#,
+        channel_id,
+    )
+    .fetch_one(db)
+    .await
+}
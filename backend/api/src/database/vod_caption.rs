@@ -0,0 +1,153 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// The most cues shown for one VOD in the search results.
+pub const MAX_SEARCH_CUES: usize = 5;
+
+/// How many characters of the lines said around a match a snippet shows at most, on each side.
+pub const SNIPPET_CONTEXT_CHARS: usize = 60;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Queued = 0,
+    Completed = 1,
+    Failed = 2,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Queued,
+            1 => Self::Completed,
+            2 => Self::Failed,
+            _ => Self::Queued,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Queued => 0,
+            Status::Completed => 1,
+            Status::Failed => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A background job transcribing the audio of a recording into captions.
+pub struct Model {
+    /// The unique identifier for the captions.
+    pub id: Uuid,
+    /// Foreign key to the streams table, a VOD has at most one set of captions.
+    pub stream_id: Uuid,
+    /// The status of the transcription.
+    pub status: Status,
+    /// The language the transcription backend heard, as a BCP-47 tag. (empty until completed)
+    pub language: String,
+    /// The key of the WebVTT file in the object storage.
+    pub object_key: String,
+    /// The region tag of the object storage the WebVTT file is uploaded to.
+    pub region: String,
+    /// The reason the transcription failed.
+    pub error: String,
+    /// The time the captions were queued.
+    pub created_at: DateTime<Utc>,
+    /// The time the captions last changed.
+    pub updated_at: DateTime<Utc>,
+    /// The time the transcription finished.
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A line of the captions, kept in the database so VODs can be searched by what was said.
+pub struct Cue {
+    /// Foreign key to the streams table.
+    pub stream_id: Uuid,
+    /// Where the cue starts in milliseconds since the start of the VOD.
+    pub start_ms: i64,
+    /// Where the cue ends in milliseconds since the start of the VOD.
+    pub end_ms: i64,
+    /// What was said.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A line of the captions which matched a search, with the lines said right before and after it.
+pub struct Match {
+    /// Foreign key to the streams table.
+    pub stream_id: Uuid,
+    /// Where the line starts in milliseconds since the start of the VOD.
+    pub start_ms: i64,
+    /// Where the line ends in milliseconds since the start of the VOD.
+    pub end_ms: i64,
+    /// What was said.
+    pub text: String,
+    /// The line said before. (None if it is the first line)
+    pub line_before: Option<String>,
+    /// The line said after. (None if it is the last line)
+    pub line_after: Option<String>,
+    /// How many lines of the VOD matched the search.
+    pub matches: i64,
+}
+
+/// The end of a line, starting at a word.
+fn tail(text: &str) -> Option<String> {
+    let Some((start, _)) = text
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_CONTEXT_CHARS - 1)
+        .filter(|(start, _)| *start > 0)
+    else {
+        return Some(text.to_string()).filter(|t| !t.is_empty());
+    };
+
+    let mut kept = &text[start..];
+    if !text[..start].ends_with(char::is_whitespace) {
+        kept = kept
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, rest)| rest);
+    }
+
+    let kept = kept.trim_start();
+    (!kept.is_empty()).then(|| format!("…{}", kept))
+}
+
+/// The start of a line, ending at a word.
+fn head(text: &str) -> Option<String> {
+    let Some((end, _)) = text.char_indices().nth(SNIPPET_CONTEXT_CHARS) else {
+        return Some(text.to_string()).filter(|t| !t.is_empty());
+    };
+
+    let mut kept = &text[..end];
+    if !text[end..].starts_with(char::is_whitespace) {
+        kept = kept
+            .rsplit_once(char::is_whitespace)
+            .map_or("", |(kept, _)| kept);
+    }
+
+    let kept = kept.trim_end();
+    (!kept.is_empty()).then(|| format!("{}…", kept))
+}
+
+/// Puts a matched line between the end of the line before and the start of the line after it, so it can be read in context.
+/// The lines around it are cut to [`SNIPPET_CONTEXT_CHARS`] at a word.
+pub fn snippet(before: Option<&str>, text: &str, after: Option<&str>) -> String {
+    [
+        before.and_then(tail),
+        Some(text.to_string()),
+        after.and_then(head),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// The key the WebVTT file of a VOD is uploaded to.
+pub fn object_key(channel_id: Uuid, stream_id: Uuid, caption_id: Uuid) -> String {
+    format!("captions/{}/{}/{}.vtt", channel_id, stream_id, caption_id)
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::pb::scuffle::types::StreamState;
+
+/// The codecs an MP4 can hold as they are, by the part of the codec string before the first dot.
+const MP4_CODECS: [&str; 7] = ["avc1", "avc3", "hev1", "hvc1", "av01", "mp4a", "opus"];
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Status {
+    #[default]
+    Queued = 0,
+    Running = 1,
+    Completed = 2,
+    Failed = 3,
+}
+
+impl From<i64> for Status {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::Queued,
+            1 => Self::Running,
+            2 => Self::Completed,
+            3 => Self::Failed,
+            _ => Self::Queued,
+        }
+    }
+}
+
+impl From<Status> for i64 {
+    fn from(value: Status) -> Self {
+        match value {
+            Status::Queued => 0,
+            Status::Running => 1,
+            Status::Completed => 2,
+            Status::Failed => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A background job turning a variant of a recording into an MP4 the broadcaster can download.
+pub struct Model {
+    /// The unique identifier for the download.
+    pub id: Uuid,
+    /// Foreign key to the streams table.
+    pub stream_id: Uuid,
+    /// Foreign key to the users table, who asked for the download.
+    pub requested_by: Uuid,
+    /// The name of the variant of the recording.
+    pub variant: String,
+    /// Whether the segments are put into the MP4 as they are, instead of being transcoded.
+    pub remux: bool,
+    /// The status of the download.
+    pub status: Status,
+    /// How far the job is, from 0 to 100.
+    pub progress: i64,
+    /// The key of the MP4 in the object storage.
+    pub object_key: String,
+    /// The region tag of the object storage the MP4 is uploaded to.
+    pub region: String,
+    /// The size of the MP4.
+    pub size_bytes: i64,
+    /// The reason the download failed.
+    pub error: String,
+    /// The time the download was requested.
+    pub created_at: DateTime<Utc>,
+    /// The time the download last changed.
+    pub updated_at: DateTime<Utc>,
+    /// The time the download finished.
+    pub completed_at: Option<DateTime<Utc>>,
+    /// The time the MP4 can't be downloaded anymore. (None until the download completed)
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl Model {
+    /// Whether the MP4 can be downloaded.
+    pub fn downloadable(&self, now: DateTime<Utc>) -> bool {
+        self.status == Status::Completed && self.expires_at.map_or(false, |at| at > now)
+    }
+}
+
+/// Whether a variant of a recording can be remuxed into an MP4, or has to be transcoded because one of its codecs
+/// can't go into one. None if the recording has no variant with this name.
+pub fn remux(state: &StreamState, variant: &str) -> Option<bool> {
+    let variant = state.variants.iter().find(|v| v.name == variant)?;
+
+    Some(variant.transcode_ids.iter().all(|id| {
+        state
+            .transcodes
+            .iter()
+            .find(|t| &t.id == id)
+            .map_or(false, |t| {
+                t.codec
+                    .split(',')
+                    .all(|codec| MP4_CODECS.contains(&codec.split('.').next().unwrap_or_default()))
+            })
+    }))
+}
+
+/// The key the MP4 of a download is stored under.
+pub fn object_key(channel_id: Uuid, stream_id: Uuid, download_id: Uuid) -> String {
+    format!("downloads/{}/{}/{}.mp4", channel_id, stream_id, download_id)
+}
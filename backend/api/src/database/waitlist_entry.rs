@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default)]
+/// Someone waiting for an invite while registration is invite only.
+pub struct Model {
+    /// The unique identifier for the entry.
+    pub id: Uuid,
+    /// The encrypted email the invite is sent to.
+    pub email: String,
+    /// The keyed hash of the email.
+    pub email_hash: String,
+    /// Foreign key to the invite_codes table, the code which was sent. (None if not admitted yet)
+    pub invite_code_id: Option<Uuid>,
+    /// Foreign key to the users table, the admin who admitted the entry.
+    pub admitted_by: Option<Uuid>,
+    /// The time the entry signed up.
+    pub created_at: DateTime<Utc>,
+    /// The time the entry was admitted. (None if not yet)
+    pub admitted_at: Option<DateTime<Utc>>,
+}
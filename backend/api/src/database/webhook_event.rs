@@ -0,0 +1,18 @@
+/// Marks a webhook event as processed, returns false if it was processed before.
+/// This has to run in the transaction which applies the event: a delivery which fails leaves no record, so the
+/// provider's retry is applied, and a duplicate delivered at the same time waits for the first and is then skipped.
+pub async fn claim(
+    tx: &mut sqlx::PgConnection,
+    source: &str,
+    event_id: &str,
+) -> sqlx::Result<bool> {
+    let claimed = sqlx::query!(
+        "INSERT INTO webhook_events (source, event_id) VALUES ($1, $2) ON CONFLICT (source, event_id) DO NOTHING RETURNING event_id",
+        source,
+        event_id,
+    )
+    .fetch_optional(tx)
+    .await?;
+
+    Ok(claimed.is_some())
+}
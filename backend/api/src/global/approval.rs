@@ -0,0 +1,238 @@
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::{
+    admin_approval::{self, Kind, Status},
+    admin_approval_event::{self, Action},
+    payout_method,
+};
+
+/// Why an approval could not be proposed, approved or rejected.
+#[derive(Debug)]
+pub enum ApprovalError {
+    /// The user or payout method the action is about does not exist.
+    TargetNotFound,
+    /// The action would not change anything, like suspending a user who is already suspended permanently.
+    AlreadyDone(&'static str),
+    /// The same action is already waiting for a second admin.
+    AlreadyProposed,
+    /// The approval does not exist or was already decided.
+    NotFound,
+    /// Nobody approved the action in time, it has to be proposed again.
+    Expired,
+    /// Admins can not approve their own proposals.
+    OwnProposal,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for ApprovalError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+/// Makes sure the action still does something, both when it is proposed and when it runs.
+async fn check_target(
+    db: impl sqlx::PgExecutor<'_>,
+    kind: Kind,
+    target_id: Uuid,
+) -> Result<(), ApprovalError> {
+    let done = match kind {
+        Kind::PermanentSuspension => sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM user_suspensions WHERE user_id = u.id AND lifted_at IS NULL AND expires_at IS NULL) FROM users u WHERE u.id = $1",
+            target_id,
+        )
+        .fetch_optional(db)
+        .await?
+        .map(|done| (done.unwrap_or(false), "User is already suspended permanently")),
+        Kind::PayoutVerification => sqlx::query_scalar!(
+            "SELECT status FROM payout_methods WHERE id = $1",
+            target_id,
+        )
+        .fetch_optional(db)
+        .await?
+        .map(|status| {
+            (
+                payout_method::Status::from(status) == payout_method::Status::Verified,
+                "Payout method is already verified",
+            )
+        }),
+    };
+
+    match done {
+        None => Err(ApprovalError::TargetNotFound),
+        Some((true, message)) => Err(ApprovalError::AlreadyDone(message)),
+        Some((false, _)) => Ok(()),
+    }
+}
+
+/// Runs an approved action.
+async fn execute(
+    db: impl sqlx::PgExecutor<'_>,
+    approval: &admin_approval::Model,
+) -> sqlx::Result<()> {
+    // The proposer decided on the action and wrote the reason, the approver is in the audit trail.
+    match approval.kind {
+        Kind::PermanentSuspension => sqlx::query!(
+            "INSERT INTO user_suspensions (user_id, suspended_by, reason) VALUES ($1, $2, $3)",
+            approval.target_id,
+            approval.proposed_by,
+            approval.reason,
+        )
+        .execute(db)
+        .await
+        .map(|_| ()),
+        Kind::PayoutVerification => sqlx::query!(
+            "UPDATE payout_methods SET status = $2, review_note = $3, reviewed_by = $4, reviewed_at = NOW() WHERE id = $1",
+            approval.target_id,
+            i64::from(payout_method::Status::Verified),
+            approval.reason,
+            approval.proposed_by,
+        )
+        .execute(db)
+        .await
+        .map(|_| ()),
+    }
+}
+
+impl GlobalState {
+    /// Proposes a destructive action, which runs once a second admin approves it within the configured window.
+    pub async fn propose_approval(
+        &self,
+        admin_id: Uuid,
+        kind: Kind,
+        target_id: Uuid,
+        reason: &str,
+    ) -> Result<admin_approval::Model, ApprovalError> {
+        let mut tx = self.db.begin().await?;
+
+        check_target(&mut *tx, kind, target_id).await?;
+
+        // Proposals nobody approved in time must not block new ones.
+        sqlx::query!(
+            "UPDATE admin_approvals SET status = $3 WHERE kind = $1 AND target_id = $2 AND status = $4 AND expires_at <= NOW()",
+            i64::from(kind),
+            target_id,
+            i64::from(Status::Expired),
+            i64::from(Status::Pending),
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let approval = sqlx::query_as!(
+            admin_approval::Model,
+            "INSERT INTO admin_approvals (kind, target_id, reason, proposed_by, expires_at) VALUES ($1, $2, $3, $4, NOW() + $5 * INTERVAL '1 second') ON CONFLICT (kind, target_id) WHERE status = 0 DO NOTHING RETURNING *",
+            i64::from(kind),
+            target_id,
+            reason,
+            admin_id,
+            self.config.approvals.window as i64,
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ApprovalError::AlreadyProposed)?;
+
+        admin_approval_event::record(&mut *tx, approval.id, admin_id, Action::Proposed, reason)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(approval)
+    }
+
+    /// Approves and runs a pending action. The approver has to be a different admin than the proposer.
+    pub async fn approve_approval(
+        &self,
+        admin_id: Uuid,
+        id: Uuid,
+        note: &str,
+    ) -> Result<admin_approval::Model, ApprovalError> {
+        let mut tx = self.db.begin().await?;
+
+        let approval = sqlx::query_as!(
+            admin_approval::Model,
+            "SELECT * FROM admin_approvals WHERE id = $1 AND status = $2 FOR UPDATE",
+            id,
+            i64::from(Status::Pending),
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ApprovalError::NotFound)?;
+
+        if approval.current_status() == Status::Expired {
+            return Err(ApprovalError::Expired);
+        }
+
+        if approval.proposed_by == Some(admin_id) {
+            return Err(ApprovalError::OwnProposal);
+        }
+
+        check_target(&mut *tx, approval.kind, approval.target_id).await?;
+        execute(&mut *tx, &approval).await?;
+
+        let approval = sqlx::query_as!(
+            admin_approval::Model,
+            "UPDATE admin_approvals SET status = $2, decided_by = $3, decided_at = NOW() WHERE id = $1 RETURNING *",
+            approval.id,
+            i64::from(Status::Approved),
+            admin_id,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        admin_approval_event::record(&mut *tx, approval.id, admin_id, Action::Approved, note)
+            .await?;
+
+        tx.commit().await?;
+
+        // The suspension is already saved and suspended users are rejected, so failing to log them out is only logged.
+        if approval.kind == Kind::PermanentSuspension {
+            if let Err(e) = self.revoke_sessions(approval.target_id).await {
+                tracing::error!(
+                    "failed to revoke sessions of suspended user {}: {}",
+                    approval.target_id,
+                    e
+                );
+            }
+
+            if let Err(e) = self.end_live_streams(approval.target_id).await {
+                tracing::error!(
+                    "failed to end streams of suspended user {}: {:#}",
+                    approval.target_id,
+                    e
+                );
+            }
+        }
+
+        Ok(approval)
+    }
+
+    /// Rejects a pending action, proposers can also withdraw their own.
+    pub async fn reject_approval(
+        &self,
+        admin_id: Uuid,
+        id: Uuid,
+        note: &str,
+    ) -> Result<admin_approval::Model, ApprovalError> {
+        let mut tx = self.db.begin().await?;
+
+        let approval = sqlx::query_as!(
+            admin_approval::Model,
+            "UPDATE admin_approvals SET status = $2, decided_by = $3, decided_at = NOW() WHERE id = $1 AND status = $4 RETURNING *",
+            id,
+            i64::from(Status::Rejected),
+            admin_id,
+            i64::from(Status::Pending),
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ApprovalError::NotFound)?;
+
+        admin_approval_event::record(&mut *tx, approval.id, admin_id, Action::Rejected, note)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(approval)
+    }
+}
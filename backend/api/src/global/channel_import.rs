@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use common::prelude::FutureTimeout;
+use lapin::{options::BasicPublishOptions, BasicProperties};
+use prost::Message;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::pb;
+
+impl GlobalState {
+    /// Queues a channel import, which the integrations pick up in the background.
+    pub async fn queue_channel_import(&self, import_id: Uuid) -> Result<()> {
+        let channel = self
+            .rmq
+            .aquire()
+            .timeout(Duration::from_secs(1))
+            .await
+            .map_err(|_| anyhow::anyhow!("failed to aquire channel: timed out"))??;
+
+        channel
+            .basic_publish(
+                "",
+                &self.config.import.queue,
+                BasicPublishOptions::default(),
+                pb::scuffle::events::ChannelImportJob {
+                    id: import_id.to_string(),
+                }
+                .encode_to_vec()
+                .as_slice(),
+                BasicProperties::default()
+                    .with_message_id(import_id.to_string().into())
+                    .with_content_type("application/octet-stream".into()),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
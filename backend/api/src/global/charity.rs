@@ -0,0 +1,25 @@
+use anyhow::Result;
+
+use super::GlobalState;
+use crate::{database::charity_campaign, pb};
+
+impl GlobalState {
+    /// Publishes the current progress of a charity campaign so overlays can update their progress bar.
+    pub async fn publish_charity_progress(&self, campaign: &charity_campaign::Model) -> Result<()> {
+        let totals = charity_campaign::totals(&self.db, campaign.id).await?;
+
+        self.publish_event(
+            campaign.channel_id,
+            &pb::scuffle::events::CharityCampaignProgress {
+                campaign_id: campaign.id.to_string(),
+                raised_amount: totals.raised_amount,
+                target_amount: campaign.target_amount,
+                donation_count: totals.donation_count,
+                ended: campaign.ended_at.is_some(),
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::Result;
+use chrono::Utc;
+use fred::{
+    prelude::KeysInterface,
+    types::{Expiration, SetOptions},
+};
+use prost::Message;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::{
+    database::{chat_command, chat_log, chat_timer},
+    pb,
+};
+
+impl GlobalState {
+    /// Publishes a chat event with the next sequence of the channel, and keeps it for clients which miss it.
+    /// Every chat event goes through here, a gap in the sequences a client sees means it missed events.
+    pub async fn publish_chat_message(
+        &self,
+        channel_id: Uuid,
+        mut event: pb::scuffle::events::ChatMessage,
+    ) -> Result<i64> {
+        let message_id = event.id.parse::<Uuid>()?;
+
+        event.sequence = 0;
+        let payload = event.encode_to_vec();
+
+        let sequence = chat_log::append(&*self.db, channel_id, message_id, &payload).await?;
+
+        if event.r#type == pb::scuffle::events::chat_message::Type::Deleted as i32 {
+            chat_log::redact(&*self.db, channel_id, message_id, sequence, &payload).await?;
+        }
+
+        let up_to = sequence - self.config.chat.backfill_size as i64;
+        if up_to > 0 {
+            chat_log::prune(&*self.db, channel_id, up_to).await?;
+        }
+
+        event.sequence = sequence;
+        self.publish_event(channel_id, &event).await?;
+
+        Ok(sequence)
+    }
+
+    /// Counts a message against the slow mode of a chat. Returns the seconds the user still has to wait
+    /// if they already sent a message within the slow mode, the message may be sent otherwise.
+    pub async fn check_slow_mode(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+        seconds: i64,
+    ) -> Result<Option<i64>> {
+        let key = format!("chat:{}:slow_mode:{}", channel_id, user_id);
+
+        let set: Option<String> = self
+            .redis
+            .set(
+                &key,
+                "1",
+                Some(Expiration::EX(seconds)),
+                Some(SetOptions::NX),
+                false,
+            )
+            .await?;
+
+        if set.is_some() {
+            return Ok(None);
+        }
+
+        let ttl: i64 = self.redis.ttl(&key).await?;
+        Ok(Some(ttl.max(1)))
+    }
+
+    /// Counts a message against the rate limit of a user in a chat. Verified bots get a higher limit.
+    /// Returns how many seconds the user has to wait if they are over it.
+    pub async fn check_chat_rate_limit(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+        verified_bot: bool,
+    ) -> Result<Option<i64>> {
+        let config = &self.config.chat;
+        let limit = match verified_bot {
+            true => config.verified_bot_rate_limit,
+            false => config.rate_limit,
+        };
+
+        let key = format!("chat:{}:rate_limit:{}", channel_id, user_id);
+
+        // The window starts with the first message, the counter expires with it.
+        let count: i64 = self.redis.incr(&key).await?;
+        if count == 1 {
+            let _: () = self
+                .redis
+                .expire(&key, config.rate_limit_window as i64)
+                .await?;
+        }
+
+        if count <= limit as i64 {
+            return Ok(None);
+        }
+
+        let ttl: i64 = self.redis.ttl(&key).await?;
+        if ttl < 0 {
+            // Setting the expiry after the first message failed, without it the user would be limited forever.
+            let _: () = self
+                .redis
+                .expire(&key, config.rate_limit_window as i64)
+                .await?;
+            return Ok(Some(config.rate_limit_window as i64));
+        }
+
+        Ok(Some(ttl.max(1)))
+    }
+
+    /// Answers a message which calls a command of the chat, returns the sequence of the answer.
+    /// Nothing is sent if the command does not exist, the author may not use it or it is on cooldown.
+    pub async fn answer_chat_command(
+        &self,
+        channel_id: Uuid,
+        content: &str,
+        vip: bool,
+        broadcaster: bool,
+    ) -> Result<Option<i64>> {
+        let Some(name) = chat_command::parse(content) else {
+            return Ok(None);
+        };
+
+        let command = sqlx::query_as!(
+            chat_command::Model,
+            "SELECT * FROM chat_commands WHERE channel_id = $1 AND name = $2",
+            channel_id,
+            name,
+        )
+        .fetch_optional(&*self.db)
+        .await?;
+
+        let Some(command) = command.filter(|c| c.permission.allows(vip, broadcaster)) else {
+            return Ok(None);
+        };
+
+        // The cooldown is shared by the whole chat, so a popular command is not answered once per chatter.
+        if command.cooldown > 0 {
+            let set: Option<String> = self
+                .redis
+                .set(
+                    format!("chat:{}:command:{}", channel_id, command.id),
+                    "1",
+                    Some(Expiration::EX(command.cooldown as i64)),
+                    Some(SetOptions::NX),
+                    false,
+                )
+                .await?;
+
+            if set.is_none() {
+                return Ok(None);
+            }
+        }
+
+        sqlx::query!(
+            "UPDATE chat_commands SET uses = uses + 1, last_used_at = NOW() WHERE id = $1",
+            command.id,
+        )
+        .execute(&*self.db)
+        .await?;
+
+        let sequence = self
+            .publish_chat_message(
+                channel_id,
+                pb::scuffle::events::ChatMessage {
+                    id: Uuid::new_v4().to_string(),
+                    channel_id: channel_id.to_string(),
+                    author_id: channel_id.to_string(),
+                    content: command.response,
+                    created_at: Utc::now().timestamp(),
+                    r#type: pb::scuffle::events::chat_message::Type::Command as i32,
+                    emotes: vec![],
+                    cheer: None,
+                    author_color: None,
+                    sequence: 0,
+                    author_vip: false,
+                    author_verified_bot: false,
+                },
+            )
+            .await?;
+
+        Ok(Some(sequence))
+    }
+
+    /// The timers which should post now, at most one per chat so a chat with several due timers is not flooded.
+    /// Timers only post while the channel is live, and once enough happened in the chat since their last post.
+    pub async fn due_chat_timers(&self, limit: i64) -> Result<Vec<chat_timer::Model>> {
+        Ok(sqlx::query_as!(
+            chat_timer::Model,
+            "SELECT DISTINCT ON (t.channel_id) t.* FROM chat_timers t LEFT JOIN chat_sequences s ON s.channel_id = t.channel_id WHERE t.enabled = TRUE AND (t.last_posted_at IS NULL OR t.last_posted_at + make_interval(secs => t.interval) <= NOW()) AND COALESCE(s.sequence, 0) - t.last_sequence >= t.min_messages AND EXISTS (SELECT 1 FROM streams st WHERE st.channel_id = t.channel_id AND st.deleted = FALSE AND st.ended_at > NOW()) ORDER BY t.channel_id, t.last_posted_at ASC NULLS FIRST LIMIT $1",
+            limit,
+        )
+        .fetch_all(&*self.db)
+        .await?)
+    }
+
+    /// Posts the message of a due timer, returns its sequence.
+    /// Nothing is sent if another instance of the API posted the timer since it was fetched.
+    pub async fn post_chat_timer(&self, timer: &chat_timer::Model) -> Result<Option<i64>> {
+        let claimed = sqlx::query!(
+            "UPDATE chat_timers SET last_posted_at = NOW() WHERE id = $1 AND last_posted_at IS NOT DISTINCT FROM $2",
+            timer.id,
+            timer.last_posted_at,
+        )
+        .execute(&*self.db)
+        .await?;
+
+        if claimed.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        let sequence = self
+            .publish_chat_message(
+                timer.channel_id,
+                pb::scuffle::events::ChatMessage {
+                    id: Uuid::new_v4().to_string(),
+                    channel_id: timer.channel_id.to_string(),
+                    author_id: timer.channel_id.to_string(),
+                    content: timer.message.clone(),
+                    created_at: Utc::now().timestamp(),
+                    r#type: pb::scuffle::events::chat_message::Type::Timer as i32,
+                    emotes: vec![],
+                    cheer: None,
+                    author_color: None,
+                    sequence: 0,
+                    author_vip: false,
+                    author_verified_bot: false,
+                },
+            )
+            .await?;
+
+        // The chat activity of the next post is counted from here, so the post itself does not count.
+        sqlx::query!(
+            "UPDATE chat_timers SET last_sequence = $2 WHERE id = $1",
+            timer.id,
+            sequence,
+        )
+        .execute(&*self.db)
+        .await?;
+
+        Ok(Some(sequence))
+    }
+}
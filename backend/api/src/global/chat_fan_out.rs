@@ -0,0 +1,142 @@
+use std::{cmp::Reverse, fmt::Write};
+
+use anyhow::Result;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::config::SubscriptionConfig;
+use crate::pb::{scuffle::events::ChatMessage, Event};
+use crate::subscription::TopicStats;
+
+/// The channel a subject carries the chat of, None for every other subject.
+pub fn chat_channel(topic: &str) -> Option<Uuid> {
+    let (prefix, suffix) = ChatMessage::SUBJECT.split_once("{}")?;
+    topic
+        .strip_prefix(prefix)?
+        .strip_suffix(suffix)?
+        .parse()
+        .ok()
+}
+
+/// The name, type, help and value of a metric.
+type Family = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&SubscriptionConfig, &TopicStats) -> u64,
+);
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+    writeln!(out, "# TYPE {} {}", name, kind).unwrap();
+}
+
+/// Renders the fan-out of the chats in the Prometheus text format.
+/// Only channels with `metrics_min_subscribers` get their own series, the others are in the totals.
+pub fn render_metrics(config: &SubscriptionConfig, chats: &[(Uuid, TopicStats)]) -> String {
+    let mut out = String::new();
+
+    header(
+        &mut out,
+        "scuffle_chat_fan_out_channels",
+        "gauge",
+        "Chats with at least one subscriber.",
+    );
+    writeln!(out, "scuffle_chat_fan_out_channels {}", chats.len()).unwrap();
+
+    header(
+        &mut out,
+        "scuffle_chat_fan_out_subscribers_all",
+        "gauge",
+        "Subscribers across every chat.",
+    );
+    writeln!(
+        out,
+        "scuffle_chat_fan_out_subscribers_all {}",
+        chats.iter().map(|(_, s)| s.subscribers).sum::<usize>()
+    )
+    .unwrap();
+
+    let large = chats
+        .iter()
+        .filter(|(_, s)| s.subscribers >= config.metrics_min_subscribers as usize)
+        .collect::<Vec<_>>();
+
+    let families: [Family; 7] = [
+        (
+            "scuffle_chat_fan_out_subscribers",
+            "gauge",
+            "Subscribers of the chat.",
+            |_, s| s.subscribers as u64,
+        ),
+        (
+            "scuffle_chat_fan_out_shards",
+            "gauge",
+            "Fan-out tasks the chat is spread over.",
+            |_, s| s.shards as u64,
+        ),
+        (
+            "scuffle_chat_fan_out_lag",
+            "gauge",
+            "The most messages waiting for one fan-out task of the chat.",
+            |_, s| s.lag as u64,
+        ),
+        (
+            "scuffle_chat_fan_out_at_capacity",
+            "gauge",
+            "Whether the chat is spread over the most fan-out tasks and they are full.",
+            |config, s| s.at_capacity(config) as u64,
+        ),
+        (
+            "scuffle_chat_fan_out_messages_total",
+            "counter",
+            "Messages fanned out in the chat.",
+            |_, s| s.published,
+        ),
+        (
+            "scuffle_chat_fan_out_dropped_total",
+            "counter",
+            "Messages a fan-out task of the chat was too far behind to take.",
+            |_, s| s.dropped,
+        ),
+        (
+            "scuffle_chat_fan_out_evicted_total",
+            "counter",
+            "Subscribers of the chat closed because they fell behind.",
+            |_, s| s.evicted,
+        ),
+    ];
+
+    for (name, kind, help, value) in families {
+        header(&mut out, name, kind, help);
+        for (channel_id, stats) in &large {
+            writeln!(
+                out,
+                "{}{{channel_id=\"{}\"}} {}",
+                name,
+                channel_id,
+                value(config, stats)
+            )
+            .unwrap();
+        }
+    }
+
+    out
+}
+
+impl GlobalState {
+    /// Takes a snapshot of the fan-out of every chat with subscribers, the most subscribers first.
+    pub async fn chat_fan_out(&self) -> Result<Vec<(Uuid, TopicStats)>> {
+        let mut chats = self
+            .subscription_manager
+            .stats()
+            .await?
+            .into_iter()
+            .filter_map(|stats| Some((chat_channel(&stats.topic)?, stats)))
+            .collect::<Vec<_>>();
+
+        chats.sort_by_key(|(id, stats)| (Reverse(stats.subscribers), *id));
+
+        Ok(chats)
+    }
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use serde::de::Error;
+use serde_json::json;
+
+use super::GlobalState;
+use crate::api::deadline::{self, DOWNSTREAM_TIMEOUT};
+
+impl GlobalState {
+    /// Asks the image classifier how likely an image is NSFW, as a score from 0 to 100.
+    pub async fn nsfw_score(&self, image_url: &str) -> Result<u32> {
+        let client = reqwest::Client::new();
+
+        let body = json!({
+            "url": image_url,
+        });
+
+        let res = client
+            .post(format!("{}/classify", self.config.emotes.classifier_url))
+            .json(&body)
+            .timeout(deadline::timeout(DOWNSTREAM_TIMEOUT))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let body = res.json::<serde_json::Value>().await?;
+
+        let probability = body["nsfw"]
+            .as_f64()
+            .ok_or(serde_json::Error::missing_field("nsfw"))?;
+
+        Ok((probability.clamp(0.0, 1.0) * 100.0).round() as u32)
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use common::prelude::FutureTimeout;
+use lapin::{options::BasicPublishOptions, BasicProperties};
+use prost::Message;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::pb;
+
+const BLOCK_SIZE: usize = 512;
+
+fn octal(field: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+}
+
+/// Packs files into an uncompressed ustar archive, which every operating system can open.
+/// Names have to be at most 100 bytes long, the exports only use short flat names.
+pub fn tar_archive(files: &[(&str, Vec<u8>)], mtime: i64) -> Vec<u8> {
+    let mut archive = Vec::new();
+
+    for (name, contents) in files {
+        let mut header = [0u8; BLOCK_SIZE];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        octal(&mut header[100..108], 0o644);
+        octal(&mut header[108..116], 0);
+        octal(&mut header[116..124], 0);
+        octal(&mut header[124..136], contents.len() as u64);
+        octal(&mut header[136..148], mtime.max(0) as u64);
+        header[156] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+
+        // The checksum is computed with its own field set to spaces.
+        header[148..156].fill(b' ');
+        let checksum: u32 = header.iter().map(|b| *b as u32).sum();
+        header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+
+        archive.extend_from_slice(&header);
+        archive.extend_from_slice(contents);
+        archive.resize(
+            archive.len() + (BLOCK_SIZE - contents.len() % BLOCK_SIZE) % BLOCK_SIZE,
+            0,
+        );
+    }
+
+    // The end of the archive is marked by two empty blocks.
+    archive.resize(archive.len() + BLOCK_SIZE * 2, 0);
+
+    archive
+}
+
+impl GlobalState {
+    /// Queues a data export, which the integrations pick up in the background.
+    pub async fn queue_data_export(&self, export_id: Uuid) -> Result<()> {
+        let channel = self
+            .rmq
+            .aquire()
+            .timeout(Duration::from_secs(1))
+            .await
+            .map_err(|_| anyhow::anyhow!("failed to aquire channel: timed out"))??;
+
+        channel
+            .basic_publish(
+                "",
+                &self.config.data_exports.queue,
+                BasicPublishOptions::default(),
+                pb::scuffle::events::DataExportJob {
+                    id: export_id.to_string(),
+                }
+                .encode_to_vec()
+                .as_slice(),
+                BasicProperties::default()
+                    .with_message_id(export_id.to_string().into())
+                    .with_content_type("application/octet-stream".into()),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
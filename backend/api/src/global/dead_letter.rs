@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use common::prelude::FutureTimeout;
+use lapin::{message::Delivery, options::BasicPublishOptions, BasicProperties};
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::dead_letter::{self, Consumer};
+
+impl GlobalState {
+    /// The queue a consumer reads from.
+    pub fn consumer_queue(&self, consumer: Consumer) -> &str {
+        match consumer {
+            Consumer::Notifications => &self.config.notifications.queue,
+            Consumer::Moderation => &self.config.moderation.queue,
+            Consumer::Import => &self.config.import.queue,
+            Consumer::ImageProcessorResults => &self.config.image_processor.result_queue,
+            Consumer::DataExport => &self.config.data_exports.queue,
+            Consumer::VodDownloadResults => &self.config.vod_downloads.result_queue,
+            Consumer::VodCaptionResults => &self.config.vod_captions.result_queue,
+        }
+    }
+
+    /// Keeps a message the consumer failed to handle, so an operator can retry it once the cause is fixed.
+    /// The message is acked either way, keeping a poison message from being redelivered forever.
+    pub async fn dead_letter(
+        &self,
+        consumer: Consumer,
+        delivery: &Delivery,
+        error: &anyhow::Error,
+    ) {
+        // Retried messages carry the id of their dead letter.
+        let id = delivery
+            .properties
+            .correlation_id()
+            .as_ref()
+            .and_then(|id| id.as_str().parse::<Uuid>().ok());
+
+        if let Err(e) = dead_letter::record(
+            &*self.db,
+            id,
+            consumer,
+            &delivery.data,
+            &format!("{:#}", error),
+        )
+        .await
+        {
+            tracing::error!(?consumer, "failed to record dead letter: {}", e);
+        }
+    }
+
+    /// Publishes a dead letter again. It goes straight to the queue of its consumer,
+    /// so a notification is not fanned out to the other consumers of the exchange a second time.
+    pub async fn retry_dead_letter(&self, letter: &dead_letter::Model) -> Result<()> {
+        let channel = self
+            .rmq
+            .aquire()
+            .timeout(Duration::from_secs(1))
+            .await
+            .map_err(|_| anyhow::anyhow!("failed to aquire channel: timed out"))??;
+
+        channel
+            .basic_publish(
+                "",
+                self.consumer_queue(letter.consumer),
+                BasicPublishOptions::default(),
+                &letter.payload,
+                BasicProperties::default()
+                    .with_correlation_id(letter.id.to_string().into())
+                    .with_content_type("application/octet-stream".into()),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use ring::constant_time;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::{channel_event, notification_settings};
+
+/// The number of days a digest covers, and how long users wait between two.
+pub const DIGEST_PERIOD_DAYS: i64 = 7;
+
+/// A user whose weekly digest is due.
+pub struct DigestRecipient {
+    pub user_id: Uuid,
+    pub display_name: String,
+    /// The encrypted email of the user.
+    pub email: String,
+    /// The IANA time zone the times in the digest are shown in.
+    pub timezone: String,
+}
+
+/// A stream of a followed channel the user did not chat in.
+pub struct DigestStream {
+    pub channel_username: String,
+    pub channel_display_name: String,
+    pub title: String,
+    /// Whether the stream was recorded, so it can still be watched as a VOD.
+    pub recorded: bool,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+}
+
+/// Writes the subject and text of a digest. The times are shown in the given time zone, UTC if it is unknown.
+pub fn render_digest(
+    recipient: &DigestRecipient,
+    streams: &[DigestStream],
+    website_url: &str,
+    unsubscribe_url: &str,
+) -> (String, String) {
+    let timezone = recipient
+        .timezone
+        .parse::<chrono_tz::Tz>()
+        .unwrap_or(chrono_tz::UTC);
+
+    let channels = streams
+        .iter()
+        .map(|s| s.channel_username.as_str())
+        .collect::<std::collections::BTreeSet<_>>()
+        .len();
+
+    let subject = match streams.len() {
+        1 => format!("You missed a stream of {}", streams[0].channel_display_name),
+        n if channels == 1 => format!(
+            "You missed {} streams of {}",
+            n, streams[0].channel_display_name
+        ),
+        n => format!("You missed {} streams of {} channels", n, channels),
+    };
+
+    let mut text = format!(
+        "Hi {},\n\nHere is what the channels you follow streamed this week.\n",
+        recipient.display_name
+    );
+
+    for stream in streams {
+        let minutes = (stream.ended_at - stream.started_at).num_minutes().max(1);
+        let duration = match minutes {
+            m if m < 60 => format!("{}m", m),
+            m => format!("{}h {}m", m / 60, m % 60),
+        };
+
+        let title = match stream.title.is_empty() {
+            true => String::new(),
+            false => format!(" \"{}\"", stream.title),
+        };
+
+        // Writing to a string can't fail.
+        let _ = write!(
+            text,
+            "\n{} streamed{} on {} for {}.\n",
+            stream.channel_display_name,
+            title,
+            stream
+                .started_at
+                .with_timezone(&timezone)
+                .format("%a, %b %-d at %H:%M %Z"),
+            duration,
+        );
+
+        let url = format!(
+            "{}/{}",
+            website_url.trim_end_matches('/'),
+            stream.channel_username
+        );
+        let _ = match stream.recorded {
+            true => writeln!(text, "The VOD is on their channel: {}", url),
+            false => writeln!(text, "{}", url),
+        };
+    }
+
+    let _ = write!(
+        text,
+        "\nYou get this email once a week. Unsubscribe: {}",
+        unsubscribe_url
+    );
+
+    (subject, text)
+}
+
+impl GlobalState {
+    /// The token of an unsubscribe link, so a link only turns off the category of the user it was sent to.
+    pub fn unsubscribe_token(
+        &self,
+        user_id: Uuid,
+        category: notification_settings::Category,
+    ) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.config.encryption.secret_key.as_bytes())
+            .expect("hmac accepts keys of any length");
+        mac.update(format!("unsubscribe:{}:{}", user_id, category.name()).as_bytes());
+
+        format!("{:x}", mac.finalize().into_bytes())
+    }
+
+    /// Checks a token made with [`GlobalState::unsubscribe_token`].
+    pub fn verify_unsubscribe_token(
+        &self,
+        user_id: Uuid,
+        category: notification_settings::Category,
+        token: &str,
+    ) -> bool {
+        let expected = self.unsubscribe_token(user_id, category);
+
+        constant_time::verify_slices_are_equal(expected.as_bytes(), token.as_bytes()).is_ok()
+    }
+
+    /// The link which turns a category of emails off without logging in.
+    pub fn unsubscribe_url(
+        &self,
+        user_id: Uuid,
+        category: notification_settings::Category,
+    ) -> String {
+        format!(
+            "{}/{}/{}/{}",
+            self.config.digests.unsubscribe_url.trim_end_matches('/'),
+            user_id,
+            category.name(),
+            self.unsubscribe_token(user_id, category),
+        )
+    }
+
+    /// The users with a verified email and digests turned on who did not get one for a week, those who waited
+    /// the longest first.
+    pub async fn due_digest_recipients(&self, limit: i64) -> sqlx::Result<Vec<DigestRecipient>> {
+        sqlx::query_as!(
+            DigestRecipient,
+            "SELECT u.id AS user_id, u.display_name, u.email, u.timezone FROM users u LEFT JOIN notification_settings n ON n.user_id = u.id LEFT JOIN email_digests d ON d.user_id = u.id WHERE u.email_verified = TRUE AND COALESCE(n.weekly_digest, TRUE) AND (d.sent_at IS NULL OR d.sent_at <= NOW() - make_interval(days => $1)) ORDER BY d.sent_at NULLS FIRST LIMIT $2",
+            DIGEST_PERIOD_DAYS as i32,
+            limit,
+        )
+        .fetch_all(&*self.db)
+        .await
+    }
+
+    /// The streams of the channels a user follows and did not mute which ended since the given time and which the user did not chat in.
+    pub async fn digest_streams(
+        &self,
+        user_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> sqlx::Result<Vec<DigestStream>> {
+        sqlx::query_as!(
+            DigestStream,
+            r#"SELECT c.username AS channel_username, c.display_name AS channel_display_name, s.title, s.recorded, s.created_at AS started_at, s.ended_at FROM streams s JOIN users c ON c.id = s.channel_id WHERE s.ended_at >= $2 AND s.ended_at <= NOW() AND s.deleted = FALSE AND EXISTS (SELECT 1 FROM channel_events e WHERE e.channel_id = s.channel_id AND e.user_id = $1 AND e.kind = $3) AND NOT EXISTS (SELECT 1 FROM channel_users cu WHERE cu.channel_id = s.channel_id AND cu.user_id = $1 AND cu.muted_at IS NOT NULL) AND NOT EXISTS (SELECT 1 FROM chat_messages m WHERE m.channel_id = s.channel_id AND m.author_id = $1 AND m.created_at BETWEEN s.created_at AND s.ended_at) ORDER BY s.created_at"#,
+            user_id,
+            since,
+            i64::from(channel_event::Kind::Follow),
+        )
+        .fetch_all(&*self.db)
+        .await
+    }
+
+    /// Sends the digest of a user and marks it as sent. Users whose channels did not stream are skipped until
+    /// next week without an email. Returns whether an email was sent.
+    pub async fn send_digest(&self, recipient: &DigestRecipient) -> Result<bool> {
+        let streams = self
+            .digest_streams(
+                recipient.user_id,
+                Utc::now() - Duration::days(DIGEST_PERIOD_DAYS),
+            )
+            .await?;
+
+        if !streams.is_empty() {
+            let (subject, text) = render_digest(
+                recipient,
+                &streams,
+                &self.config.discord.website_url,
+                &self.unsubscribe_url(
+                    recipient.user_id,
+                    notification_settings::Category::WeeklyDigest,
+                ),
+            );
+
+            let to = self.decrypt_pii(&recipient.email)?;
+            self.send_email(&to, &subject, &text).await?;
+        }
+
+        sqlx::query!(
+            "INSERT INTO email_digests (user_id, sent_at) VALUES ($1, NOW()) ON CONFLICT (user_id) DO UPDATE SET sent_at = NOW()",
+            recipient.user_id,
+        )
+        .execute(&*self.db)
+        .await?;
+
+        Ok(!streams.is_empty())
+    }
+}
@@ -0,0 +1,206 @@
+use chrono::{DateTime, Utc};
+use common::database::{retry, DatabaseError};
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::{
+    display_color::{Color, DisplayColor},
+    display_color_change::RecentChanges,
+    global_role::Permission,
+    user,
+};
+use crate::pb;
+
+/// Why a display color change was refused.
+#[derive(Debug)]
+pub enum DisplayColorError {
+    /// The argument is not a color, or it is not readable on one of the themes.
+    Invalid {
+        field: &'static str,
+        message: &'static str,
+    },
+    /// The user is not entitled to this kind of color.
+    NotAllowed {
+        field: &'static str,
+        message: &'static str,
+    },
+    /// The user changed their color too often and can change it again at the given time.
+    RateLimited(DateTime<Utc>),
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for DisplayColorError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+impl DatabaseError for DisplayColorError {
+    fn database_error(&self) -> Option<&sqlx::Error> {
+        match self {
+            Self::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Parses one color of a change, custom hex colors need their own permission on top of the palette.
+fn parse_color(
+    permissions: Permission,
+    color: &str,
+    dark: Option<&str>,
+    field: &'static str,
+) -> Result<Color, DisplayColorError> {
+    let color = Color::parse(color, dark)
+        .and_then(|c| c.check_contrast().map(|_| c))
+        .map_err(|message| DisplayColorError::Invalid { field, message })?;
+
+    if color.name.is_none() && !permissions.has_permission(Permission::CustomDisplayColor) {
+        return Err(DisplayColorError::NotAllowed {
+            field,
+            message: "You are only allowed to use palette colors",
+        });
+    }
+
+    Ok(color)
+}
+
+/// Validates a change and returns the color and gradient end as they are stored.
+pub fn stored_change(
+    permissions: Permission,
+    color: Option<&str>,
+    dark_color: Option<&str>,
+    gradient_end: Option<&str>,
+) -> Result<(String, String), DisplayColorError> {
+    let stored_color = match color {
+        Some(color) => parse_color(permissions, color, dark_color, "color")?.to_stored(),
+        None if dark_color.is_some() || gradient_end.is_some() => {
+            return Err(DisplayColorError::Invalid {
+                field: "color",
+                message: "A color is required",
+            });
+        }
+        None => String::new(),
+    };
+
+    let stored_gradient_end = match gradient_end {
+        Some(gradient_end) => {
+            if !permissions.has_permission(Permission::DisplayNameGradient) {
+                return Err(DisplayColorError::NotAllowed {
+                    field: "gradientEnd",
+                    message: "You are not allowed to use gradients",
+                });
+            }
+
+            parse_color(permissions, gradient_end, None, "gradientEnd")?.to_stored()
+        }
+        None => String::new(),
+    };
+
+    Ok((stored_color, stored_gradient_end))
+}
+
+impl GlobalState {
+    /// Changes the display color of a user. Every path which changes a color goes through here,
+    /// so chat can't be spammed with a cycling name color whichever client is used.
+    /// Setting the color the user already has is not counted as a change. The new color is published,
+    /// failing to publish it only logs.
+    pub async fn set_display_color(
+        &self,
+        user_id: Uuid,
+        permissions: Permission,
+        color: Option<&str>,
+        dark_color: Option<&str>,
+        gradient_end: Option<&str>,
+    ) -> Result<user::Model, DisplayColorError> {
+        let (stored_color, stored_gradient_end) =
+            stored_change(permissions, color, dark_color, gradient_end)?;
+
+        // Setting a color is idempotent, the color is unchanged the second time.
+        let user = retry(&self.config.database, || {
+            self.store_display_color(user_id, &stored_color, &stored_gradient_end)
+        })
+        .await?;
+
+        let gradient_allowed = permissions.has_permission(Permission::DisplayNameGradient);
+        let event = pb::scuffle::events::UserDisplayColorUpdated {
+            display_color: DisplayColor::of_user(&user, gradient_allowed)
+                .as_ref()
+                .map(Into::into),
+        };
+
+        if let Err(e) = self.publish_event(user.id, &event).await {
+            tracing::error!("failed to publish display color of user {}: {}", user.id, e);
+        }
+
+        Ok(user)
+    }
+
+    async fn store_display_color(
+        &self,
+        user_id: Uuid,
+        stored_color: &str,
+        stored_gradient_end: &str,
+    ) -> Result<user::Model, DisplayColorError> {
+        let mut tx = self.db.begin().await?;
+
+        let user = self
+            .change_display_color(&mut tx, user_id, stored_color, stored_gradient_end)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    /// Changes the display color of a user in the transaction of the caller, so it can be part of a bigger change.
+    /// The color has to come from `stored_change`, the user stays locked until the transaction ends.
+    pub(super) async fn change_display_color(
+        &self,
+        conn: &mut sqlx::PgConnection,
+        user_id: Uuid,
+        stored_color: &str,
+        stored_gradient_end: &str,
+    ) -> Result<user::Model, DisplayColorError> {
+        let config = &self.config.display_colors;
+
+        // Locking the user makes concurrent changes wait for each other, so they can't all pass the limit.
+        let user = sqlx::query_as!(
+            user::Model,
+            "SELECT * FROM users WHERE id = $1 FOR UPDATE",
+            user_id,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if user.display_color == stored_color && user.display_gradient_end == stored_gradient_end {
+            return Ok(user);
+        }
+
+        let recent = RecentChanges::of_user(&mut *conn, user_id, config.rate_limit_window).await?;
+        if let Some(retry_at) = recent.retry_at(config, Utc::now()) {
+            return Err(DisplayColorError::RateLimited(retry_at));
+        }
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET display_color = $2, display_gradient_end = $3 WHERE id = $1 RETURNING *",
+            user_id,
+            stored_color,
+            stored_gradient_end,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO display_color_changes (user_id, display_color, display_gradient_end) VALUES ($1, $2, $3)",
+            user_id,
+            stored_color,
+            stored_gradient_end,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(user)
+    }
+}
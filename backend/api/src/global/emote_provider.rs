@@ -0,0 +1,143 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use fred::{prelude::KeysInterface, types::Expiration};
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::api::deadline;
+use crate::database::{
+    emote,
+    emote_provider::{self, ProviderEmote},
+};
+
+/// Providers are called while sending a chat message, so a slow provider must not hold it up for long.
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long a provider which failed is left alone before it is called again.
+const PROVIDER_RETRY_SECONDS: i64 = 30;
+
+impl GlobalState {
+    async fn fetch_provider_emotes(
+        &self,
+        url: &str,
+        external_id: &str,
+    ) -> Result<Vec<ProviderEmote>> {
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("{}/{}", url, external_id))
+            .timeout(deadline::timeout(PROVIDER_TIMEOUT))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(res.json::<Vec<ProviderEmote>>().await?)
+    }
+
+    /// Gets the emote set of a provider from the cache, fetching it if it expired.
+    /// If the provider is down the last known emote set is used, or none if it was never fetched.
+    pub async fn provider_emotes(&self, provider: &str, external_id: &str) -> Vec<ProviderEmote> {
+        let Some(url) = self.config.emotes.providers.get(provider) else {
+            return Vec::new();
+        };
+
+        let key = format!("emote_provider:{}:{}", provider, external_id);
+        let stale_key = format!("{}:stale", key);
+
+        match self.redis.get::<Option<String>, _>(&key).await {
+            Ok(Some(cached)) => {
+                if let Ok(emotes) = serde_json::from_str(&cached) {
+                    return emotes;
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("failed to read emote provider cache: {}", e),
+        }
+
+        let (emotes, ttl) = match self.fetch_provider_emotes(url, external_id).await {
+            Ok(emotes) => (emotes, self.config.emotes.provider_cache_ttl as i64),
+            Err(e) => {
+                tracing::warn!("emote provider {} is unavailable: {:#}", provider, e);
+
+                let stale = self
+                    .redis
+                    .get::<Option<String>, _>(&stale_key)
+                    .await
+                    .ok()
+                    .flatten()
+                    .and_then(|s| serde_json::from_str(&s).ok())
+                    .unwrap_or_default();
+
+                (stale, PROVIDER_RETRY_SECONDS)
+            }
+        };
+
+        if let Ok(serialized) = serde_json::to_string(&emotes) {
+            let res: Result<(), _> = self
+                .redis
+                .set(
+                    &key,
+                    serialized.as_str(),
+                    Some(Expiration::EX(ttl)),
+                    None,
+                    false,
+                )
+                .await;
+            if let Err(e) = res {
+                tracing::warn!("failed to write emote provider cache: {}", e);
+            }
+
+            let res: Result<(), _> = self
+                .redis
+                .set(&stale_key, serialized.as_str(), None, None, false)
+                .await;
+            if let Err(e) = res {
+                tracing::warn!("failed to write emote provider cache: {}", e);
+            }
+        }
+
+        emotes
+    }
+
+    /// Gets all emotes which can be used in a channel's chat. The channel's own emotes come first.
+    pub async fn chat_emotes(&self, channel_id: Uuid) -> Result<Vec<emote_provider::Emote>> {
+        let mut emotes = sqlx::query!(
+            "SELECT name, image_url FROM emotes WHERE channel_id = $1 AND status = $2",
+            channel_id,
+            i64::from(emote::Status::Approved),
+        )
+        .fetch_all(&*self.db)
+        .await?
+        .into_iter()
+        .map(|e| emote_provider::Emote {
+            name: e.name,
+            url: e.image_url,
+            provider: None,
+        })
+        .collect::<Vec<_>>();
+
+        let providers = sqlx::query_as!(
+            emote_provider::Model,
+            "SELECT * FROM channel_emote_providers WHERE channel_id = $1 ORDER BY created_at ASC",
+            channel_id,
+        )
+        .fetch_all(&*self.db)
+        .await?;
+
+        for provider in providers {
+            emotes.extend(
+                self.provider_emotes(&provider.provider, &provider.external_id)
+                    .await
+                    .into_iter()
+                    .map(|e| emote_provider::Emote {
+                        name: e.name,
+                        url: e.url,
+                        provider: Some(provider.provider.clone()),
+                    }),
+            );
+        }
+
+        Ok(emotes)
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hmac::{Hmac, Mac};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    rand::{SecureRandom, SystemRandom},
+};
+use sha2::{Digest, Sha256};
+
+use super::GlobalState;
+use crate::config::EncryptionConfig;
+
+/// Encrypted column values look like `enc:<key id>:<base64 of the ciphertext>`.
+/// Values without the prefix were written before encryption was enabled and are read as they are.
+const COLUMN_PREFIX: &str = "enc:";
+
+fn key(secret_key: &str) -> Result<LessSafeKey> {
+    if secret_key.is_empty() {
+        return Err(anyhow!("no encryption key configured"));
+    }
+
+    let key = UnboundKey::new(&AES_256_GCM, &Sha256::digest(secret_key.as_bytes()))
+        .map_err(|_| anyhow!("invalid encryption key"))?;
+
+    Ok(LessSafeKey::new(key))
+}
+
+/// Encrypts a secret with AES-256-GCM. The random nonce is stored in front of the ciphertext.
+pub fn encrypt(secret_key: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let key = key(secret_key)?;
+
+    let mut nonce = [0; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| anyhow!("failed to generate nonce"))?;
+
+    let mut data = plaintext.to_vec();
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .map_err(|_| anyhow!("failed to encrypt"))?;
+
+    Ok([nonce.as_slice(), &data].concat())
+}
+
+/// Decrypts a secret encrypted with [`encrypt`].
+pub fn decrypt(secret_key: &str, ciphertext: &[u8]) -> Result<Vec<u8>> {
+    let key = key(secret_key)?;
+
+    if ciphertext.len() < NONCE_LEN {
+        return Err(anyhow!("ciphertext is too short"));
+    }
+
+    let (nonce, data) = ciphertext.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("invalid nonce"))?;
+
+    let mut data = data.to_vec();
+    let plaintext = key
+        .open_in_place(nonce, Aad::empty(), &mut data)
+        .map_err(|_| anyhow!("failed to decrypt"))?;
+
+    Ok(plaintext.to_vec())
+}
+
+/// The keys personal data is encrypted with. Keys are never removed when rotating,
+/// so rows which were not migrated to the current key yet can still be read.
+#[derive(Debug, Clone)]
+pub struct Keyring {
+    current: String,
+    keys: HashMap<String, String>,
+}
+
+impl Keyring {
+    pub fn new(current: String, keys: HashMap<String, String>) -> Result<Self> {
+        // Key ids end up in LIKE patterns when migrating, so they can't contain wildcards.
+        if let Some(id) = keys
+            .keys()
+            .find(|id| id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        {
+            bail!("invalid encryption key id: {:?}", id);
+        }
+
+        if !keys.contains_key(&current) {
+            bail!("the current encryption key {:?} does not exist", current);
+        }
+
+        Ok(Self { current, keys })
+    }
+
+    pub fn load(config: &EncryptionConfig) -> Result<Self> {
+        let keys = match config.key_provider.as_str() {
+            "config" => config.keys.clone(),
+            "file" => {
+                let file = std::fs::read(&config.keys_file)
+                    .with_context(|| format!("failed to read {}", config.keys_file))?;
+
+                serde_json::from_slice(&file).context("failed to parse encryption keys")?
+            }
+            provider => bail!("unknown encryption key provider: {}", provider),
+        };
+
+        Self::new(config.current_key.clone(), keys)
+    }
+
+    /// The start of every value encrypted with the current key.
+    pub fn current_prefix(&self) -> String {
+        format!("{}{}:", COLUMN_PREFIX, self.current)
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let ciphertext = encrypt(&self.keys[&self.current], plaintext.as_bytes())?;
+
+        Ok(format!(
+            "{}{}",
+            self.current_prefix(),
+            BASE64.encode(ciphertext)
+        ))
+    }
+
+    pub fn decrypt(&self, value: &str) -> Result<String> {
+        let Some(encrypted) = value.strip_prefix(COLUMN_PREFIX) else {
+            return Ok(value.to_string());
+        };
+
+        let (id, ciphertext) = encrypted
+            .split_once(':')
+            .ok_or_else(|| anyhow!("invalid encrypted value"))?;
+        let key = self
+            .keys
+            .get(id)
+            .ok_or_else(|| anyhow!("unknown encryption key: {}", id))?;
+
+        let plaintext = decrypt(key, &BASE64.decode(ciphertext)?)?;
+
+        Ok(String::from_utf8(plaintext)?)
+    }
+
+    /// If a value is not encrypted with the current key yet.
+    pub fn needs_migration(&self, value: &str) -> bool {
+        !value.starts_with(&self.current_prefix())
+    }
+}
+
+impl GlobalState {
+    /// Encrypts a credential of an integration before it is stored in the database.
+    pub fn encrypt_secret(&self, plaintext: &str) -> Result<Vec<u8>> {
+        encrypt(&self.config.encryption.secret_key, plaintext.as_bytes())
+    }
+
+    /// Decrypts a credential of an integration which was stored with [`GlobalState::encrypt_secret`].
+    pub fn decrypt_secret(&self, ciphertext: &[u8]) -> Result<String> {
+        let plaintext = decrypt(&self.config.encryption.secret_key, ciphertext)?;
+
+        Ok(String::from_utf8(plaintext)?)
+    }
+}
+
+impl GlobalState {
+    /// Encrypts personal data before it is stored, if encryption is enabled.
+    pub fn encrypt_pii(&self, plaintext: &str) -> Result<String> {
+        if !self.config.encryption.encrypt_pii {
+            return Ok(plaintext.to_string());
+        }
+
+        self.keyring.encrypt(plaintext)
+    }
+
+    /// Decrypts personal data stored with [`GlobalState::encrypt_pii`], plaintext values are returned as they are.
+    pub fn decrypt_pii(&self, value: &str) -> Result<String> {
+        self.keyring.decrypt(value)
+    }
+
+    /// The keyed hash of an email which users are found by. Encrypted emails can't be searched, their nonce is random.
+    pub fn email_hash(&self, email: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.config.encryption.secret_key.as_bytes())
+            .expect("hmac accepts keys of any length");
+        mac.update(email.to_lowercase().as_bytes());
+
+        format!("{:x}", mac.finalize().into_bytes())
+    }
+
+    fn reencrypt_pii(&self, value: &str) -> Result<String> {
+        self.keyring.encrypt(&self.keyring.decrypt(value)?)
+    }
+
+    /// Encrypts the personal data of existing rows with the current key, both plaintext rows and rows encrypted
+    /// with an older key. Rows are only updated if they did not change in the meantime, so it is safe to run on
+    /// every instance at once. Emails of accounts created before emails were hashed are hashed as well.
+    /// Returns the number of migrated rows.
+    pub async fn migrate_pii(&self) -> Result<u64> {
+        let prefix = format!("{}%", self.keyring.current_prefix());
+        let batch_size = self.config.encryption.migration_batch_size.max(1) as i64;
+        let mut migrated = 0;
+
+        loop {
+            let users = sqlx::query!(
+                "SELECT id, email FROM users WHERE email NOT LIKE $1 LIMIT $2",
+                prefix,
+                batch_size,
+            )
+            .fetch_all(&*self.db)
+            .await?;
+
+            if users.is_empty() {
+                break;
+            }
+
+            for user in users {
+                let result = sqlx::query!(
+                    "UPDATE users SET email = $1 WHERE id = $2 AND email = $3",
+                    self.reencrypt_pii(&user.email)?,
+                    user.id,
+                    user.email,
+                )
+                .execute(&*self.db)
+                .await?;
+
+                migrated += result.rows_affected();
+            }
+        }
+
+        // Accounts created before emails were hashed.
+        loop {
+            let users = sqlx::query!(
+                "SELECT id, email FROM users WHERE email_hash IS NULL LIMIT $1",
+                batch_size,
+            )
+            .fetch_all(&*self.db)
+            .await?;
+
+            if users.is_empty() {
+                break;
+            }
+
+            for user in users {
+                let result = sqlx::query!(
+                    "UPDATE users SET email_hash = $1 WHERE id = $2 AND email = $3",
+                    self.email_hash(&self.decrypt_pii(&user.email)?),
+                    user.id,
+                    user.email,
+                )
+                .execute(&*self.db)
+                .await?;
+
+                migrated += result.rows_affected();
+            }
+        }
+
+        loop {
+            let methods = sqlx::query!(
+                "SELECT id, provider_account_id FROM payout_methods WHERE provider_account_id NOT LIKE $1 LIMIT $2",
+                prefix,
+                batch_size,
+            )
+            .fetch_all(&*self.db)
+            .await?;
+
+            if methods.is_empty() {
+                break;
+            }
+
+            for method in methods {
+                let result = sqlx::query!(
+                    "UPDATE payout_methods SET provider_account_id = $1 WHERE id = $2 AND provider_account_id = $3",
+                    self.reencrypt_pii(&method.provider_account_id)?,
+                    method.id,
+                    method.provider_account_id,
+                )
+                .execute(&*self.db)
+                .await?;
+
+                migrated += result.rows_affected();
+            }
+        }
+
+        Ok(migrated)
+    }
+}
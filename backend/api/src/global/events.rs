@@ -0,0 +1,19 @@
+use std::fmt::Display;
+
+use fred::{error::RedisError, prelude::PubsubInterface};
+
+use super::GlobalState;
+use crate::pb::Event;
+
+impl GlobalState {
+    /// Publishes an event for the channel or user with the given id, subscriptions listen on the same subject.
+    pub async fn publish_event<E: Event>(
+        &self,
+        id: impl Display,
+        event: &E,
+    ) -> Result<(), RedisError> {
+        self.redis
+            .publish(E::subject(id), event.encode_to_vec().as_slice())
+            .await
+    }
+}
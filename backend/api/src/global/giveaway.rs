@@ -0,0 +1,170 @@
+use anyhow::Result;
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::{channel_event, giveaway, giveaway_entry, user};
+use crate::pb;
+
+/// Why a viewer could not enter a giveaway.
+#[derive(Debug)]
+pub enum EnterError {
+    /// Only followers of the channel can enter.
+    NotFollowing,
+    /// The viewer already has tickets in the draw.
+    AlreadyEntered,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for EnterError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+impl GlobalState {
+    /// Posts a message about a giveaway in the chat of the channel, in the name of the broadcaster.
+    pub async fn announce_giveaway(&self, channel_id: Uuid, content: String) -> Result<i64> {
+        self.publish_chat_message(
+            channel_id,
+            pb::scuffle::events::ChatMessage {
+                id: Uuid::new_v4().to_string(),
+                channel_id: channel_id.to_string(),
+                author_id: channel_id.to_string(),
+                content,
+                created_at: Utc::now().timestamp(),
+                r#type: pb::scuffle::events::chat_message::Type::Giveaway as i32,
+                emotes: vec![],
+                cheer: None,
+                author_color: None,
+                sequence: 0,
+                author_vip: false,
+                author_verified_bot: false,
+            },
+        )
+        .await
+    }
+
+    /// Enters a viewer into an open giveaway, their tickets are fixed by whether they follow or subscribe right now.
+    pub async fn enter_giveaway(
+        &self,
+        giveaway: &giveaway::Model,
+        user_id: Uuid,
+    ) -> Result<giveaway_entry::Model, EnterError> {
+        let follower = channel_event::is_follower(&*self.db, giveaway.channel_id, user_id).await?;
+        if giveaway.followers_only && !follower {
+            return Err(EnterError::NotFollowing);
+        }
+
+        let subscriber =
+            channel_event::is_subscriber(&*self.db, giveaway.channel_id, user_id).await?;
+
+        // Entering a giveaway which was drawn in the meantime inserts nothing.
+        sqlx::query_as!(
+            giveaway_entry::Model,
+            "INSERT INTO giveaway_entries (giveaway_id, user_id, tickets) SELECT id, $2, $3 FROM giveaways WHERE id = $1 AND status = $4 ON CONFLICT DO NOTHING RETURNING *",
+            giveaway.id,
+            user_id,
+            giveaway.tickets(follower, subscriber),
+            i64::from(giveaway::Status::Open),
+        )
+        .fetch_optional(&*self.db)
+        .await?
+        .ok_or(EnterError::AlreadyEntered)
+    }
+
+    /// Enters the author of a chat message into the open giveaway of the chat if the message is its keyword.
+    /// Returns whether the author was entered, viewers who can't enter are skipped without an answer.
+    pub async fn enter_giveaway_by_keyword(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+        content: &str,
+    ) -> Result<bool> {
+        let giveaway = sqlx::query_as!(
+            giveaway::Model,
+            "SELECT * FROM giveaways WHERE channel_id = $1 AND status = $2",
+            channel_id,
+            i64::from(giveaway::Status::Open),
+        )
+        .fetch_optional(&*self.db)
+        .await?;
+
+        let Some(giveaway) = giveaway.filter(|g| g.matches_keyword(content)) else {
+            return Ok(false);
+        };
+
+        match self.enter_giveaway(&giveaway, user_id).await {
+            Ok(_) => Ok(true),
+            Err(EnterError::NotFollowing | EnterError::AlreadyEntered) => Ok(false),
+            Err(EnterError::Database(e)) => Err(e.into()),
+        }
+    }
+
+    /// Draws the winner of the open giveaway of a channel and announces them in chat. None if no giveaway is open.
+    /// The seed is revealed with the result, so viewers can check the draw against the commitment.
+    pub async fn draw_giveaway(&self, channel_id: Uuid) -> Result<Option<giveaway::Model>> {
+        let mut tx = self.db.begin().await?;
+
+        let giveaway = sqlx::query_as!(
+            giveaway::Model,
+            "SELECT * FROM giveaways WHERE channel_id = $1 AND status = $2 FOR UPDATE",
+            channel_id,
+            i64::from(giveaway::Status::Open),
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(giveaway) = giveaway else {
+            return Ok(None);
+        };
+
+        let entries = sqlx::query!(
+            "SELECT user_id, tickets FROM giveaway_entries WHERE giveaway_id = $1",
+            giveaway.id,
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|entry| (entry.user_id, entry.tickets))
+        .collect::<Vec<_>>();
+
+        let winner_id = giveaway::draw(&giveaway.seed, &entries);
+
+        let giveaway = sqlx::query_as!(
+            giveaway::Model,
+            "UPDATE giveaways SET status = $2, winner_id = $3, closed_at = NOW() WHERE id = $1 RETURNING *",
+            giveaway.id,
+            i64::from(giveaway::Status::Drawn),
+            winner_id,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let winner = match winner_id {
+            Some(winner_id) => {
+                sqlx::query_as!(user::Model, "SELECT * FROM users WHERE id = $1", winner_id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+            }
+            None => None,
+        };
+
+        tx.commit().await?;
+
+        let content = match winner {
+            Some(winner) => format!(
+                "{} won the giveaway: {}!",
+                winner.display_name, giveaway.title
+            ),
+            None => format!("Nobody entered the giveaway: {}", giveaway.title),
+        };
+
+        // The result is saved, a failed announcement only leaves the chat without it.
+        if let Err(e) = self.announce_giveaway(giveaway.channel_id, content).await {
+            tracing::error!("failed to announce giveaway {}: {:#}", giveaway.id, e);
+        }
+
+        Ok(Some(giveaway))
+    }
+}
@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use common::prelude::FutureTimeout;
+use lapin::{options::BasicPublishOptions, BasicProperties};
+use prost::Message;
+use tokio::net::lookup_host;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::api::deadline::{self, DOWNSTREAM_TIMEOUT};
+use crate::pb;
+
+/// The sizes banners are cropped to, a wide one for desktop pages and a narrower one for phones.
+pub const BANNER_VARIANTS: [(&str, u32, u32); 2] = [("desktop", 1920, 480), ("mobile", 960, 320)];
+
+impl GlobalState {
+    /// Queues an uploaded image for the image processor, which converts it into an animated webp.
+    /// Returns the url the processed image will be served from once the job has finished.
+    pub async fn process_image(&self, source_url: &str, output_prefix: &str) -> Result<String> {
+        Ok(self.queue_image_job(source_url, output_prefix).await?.1)
+    }
+
+    /// Downloads an uploaded image, so it can be checked before it is accepted. Fails if it is larger than `max_bytes`.
+    /// The url comes from the user, so it has to be https and every address its host resolves to has to be public.
+    pub async fn fetch_image(&self, source_url: &str, max_bytes: usize) -> Result<Vec<u8>> {
+        let url = reqwest::Url::parse(source_url)?;
+        if url.scheme() != "https" {
+            bail!("image url must be https");
+        }
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("image url has no host"))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let addrs = lookup_host((host.as_str(), url.port_or_known_default().unwrap_or(443)))
+            .await?
+            .collect::<Vec<_>>();
+
+        if addrs.is_empty() || addrs.iter().any(|addr| !common::net::is_public(addr.ip())) {
+            bail!("image host resolves to an address which is not publicly reachable");
+        }
+
+        // The checked addresses are used for the request, so the host can't resolve somewhere else in the meantime.
+        let client = reqwest::Client::builder()
+            .resolve_to_addrs(&host, &addrs)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+
+        let mut res = client
+            .get(url)
+            .timeout(deadline::timeout(DOWNSTREAM_TIMEOUT))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        if res.content_length().unwrap_or_default() > max_bytes as u64 {
+            bail!("image is too large");
+        }
+
+        let mut data = Vec::new();
+        while let Some(chunk) = res.chunk().await? {
+            if data.len() + chunk.len() > max_bytes {
+                bail!("image is too large");
+            }
+
+            data.extend_from_slice(&chunk);
+        }
+
+        Ok(data)
+    }
+
+    /// Like [`GlobalState::process_image`], but also returns the id of the job, for callers which wait
+    /// for the image processor to report the job on the result queue before using the image.
+    pub async fn queue_image_job(
+        &self,
+        source_url: &str,
+        output_prefix: &str,
+    ) -> Result<(Uuid, String)> {
+        let job_id = self
+            .publish_image_job(source_url, output_prefix, &[])
+            .await?;
+
+        Ok((job_id, self.image_url(output_prefix, "animated")))
+    }
+
+    /// Queues an uploaded image for the image processor, which crops it to every one of the `variants`.
+    /// Returns the id of the job, the image of a variant is served from [`GlobalState::image_url`] once it has finished.
+    pub async fn queue_image_variants(
+        &self,
+        source_url: &str,
+        output_prefix: &str,
+        variants: &[(&str, u32, u32)],
+    ) -> Result<Uuid> {
+        let variants = variants
+            .iter()
+            .map(|&(name, width, height)| pb::scuffle::events::ImageVariant {
+                name: name.to_string(),
+                width,
+                height,
+            })
+            .collect::<Vec<_>>();
+
+        self.publish_image_job(source_url, output_prefix, &variants)
+            .await
+    }
+
+    /// The url the image of a variant of a processed image is served from.
+    pub fn image_url(&self, output_prefix: &str, variant: &str) -> String {
+        format!(
+            "{}/{}/{}.webp",
+            self.config.image_processor.cdn_url.trim_end_matches('/'),
+            output_prefix,
+            variant
+        )
+    }
+
+    async fn publish_image_job(
+        &self,
+        source_url: &str,
+        output_prefix: &str,
+        variants: &[pb::scuffle::events::ImageVariant],
+    ) -> Result<Uuid> {
+        let job_id = Uuid::new_v4();
+
+        let channel = self
+            .rmq
+            .aquire()
+            .timeout(Duration::from_secs(1))
+            .await
+            .map_err(|_| anyhow::anyhow!("failed to aquire channel: timed out"))??;
+
+        channel
+            .basic_publish(
+                "",
+                &self.config.image_processor.queue,
+                BasicPublishOptions::default(),
+                pb::scuffle::events::ImageProcessorJob {
+                    id: job_id.to_string(),
+                    source_url: source_url.to_string(),
+                    output_prefix: output_prefix.to_string(),
+                    variants: variants.to_vec(),
+                }
+                .encode_to_vec()
+                .as_slice(),
+                BasicProperties::default()
+                    .with_message_id(job_id.to_string().into())
+                    .with_reply_to(self.config.image_processor.result_queue.as_str().into())
+                    .with_content_type("application/octet-stream".into()),
+            )
+            .await?;
+
+        Ok(job_id)
+    }
+}
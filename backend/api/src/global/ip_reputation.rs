@@ -0,0 +1,137 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use fred::{prelude::KeysInterface, types::Expiration};
+use hyper::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+use super::GlobalState;
+use crate::api::deadline;
+use crate::config::IpReputationConfig;
+
+/// What the provider knows about an address.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Verdict {
+    /// How likely the address is used for abuse, from 0 to 100.
+    pub score: u32,
+    /// If the address belongs to a proxy, VPN or hosting provider.
+    #[serde(default)]
+    pub proxy: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Allow,
+    Captcha,
+    Block,
+}
+
+impl Verdict {
+    pub fn action(&self, config: &IpReputationConfig) -> Action {
+        let score = match self.proxy {
+            true => self.score.max(config.proxy_score),
+            false => self.score,
+        };
+
+        if score >= config.block_score {
+            Action::Block
+        } else if score >= config.captcha_score {
+            Action::Captcha
+        } else {
+            Action::Allow
+        }
+    }
+}
+
+/// The address of the client. Behind a reverse proxy the peer is the proxy, so its header is trusted instead.
+pub fn client_ip(
+    config: &IpReputationConfig,
+    headers: &HeaderMap,
+    remote_addr: SocketAddr,
+) -> IpAddr {
+    config
+        .client_ip_header
+        .as_deref()
+        .and_then(|name| headers.get(name))
+        .and_then(|value| value.to_str().ok())
+        // X-Forwarded-For style headers list every hop, the first one is the client.
+        .and_then(|value| value.split(',').next())
+        .and_then(|ip| ip.trim().parse().ok())
+        .unwrap_or_else(|| remote_addr.ip())
+}
+
+impl GlobalState {
+    async fn fetch_ip_verdict(&self, ip: IpAddr) -> Result<Verdict> {
+        let config = &self.config.ip_reputation;
+
+        match config.provider.as_str() {
+            "http" => {
+                let client = reqwest::Client::new();
+
+                let res = client
+                    .get(format!("{}/{}", config.url, ip))
+                    .bearer_auth(&config.secret_key)
+                    .timeout(deadline::timeout(Duration::from_secs(
+                        config.timeout as u64,
+                    )))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+
+                Ok(res.json::<Verdict>().await?)
+            }
+            provider => bail!("unknown ip reputation provider: {}", provider),
+        }
+    }
+
+    /// Gets the verdict of an address from the cache, looking it up if it expired.
+    /// Addresses are allowed when the provider is disabled or unavailable, a broken provider must not lock everyone out.
+    pub async fn ip_reputation(&self, ip: IpAddr) -> Action {
+        let config = &self.config.ip_reputation;
+        if config.provider == "none" {
+            return Action::Allow;
+        }
+
+        // The verdict is cached instead of the action, so changed thresholds apply right away.
+        let key = format!("ip_reputation:{}", ip);
+
+        match self.redis.get::<Option<String>, _>(&key).await {
+            Ok(Some(cached)) => {
+                if let Ok(verdict) = serde_json::from_str::<Verdict>(&cached) {
+                    return verdict.action(config);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("failed to read ip reputation cache: {}", e),
+        }
+
+        let verdict = match self.fetch_ip_verdict(ip).await {
+            Ok(verdict) => verdict,
+            Err(e) => {
+                tracing::warn!("failed to look up ip reputation: {:#}", e);
+                return Action::Allow;
+            }
+        };
+
+        if let Ok(serialized) = serde_json::to_string(&verdict) {
+            let res: Result<(), _> = self
+                .redis
+                .set(
+                    &key,
+                    serialized.as_str(),
+                    Some(Expiration::EX(config.cache_ttl as i64)),
+                    None,
+                    false,
+                )
+                .await;
+            if let Err(e) = res {
+                tracing::warn!("failed to write ip reputation cache: {}", e);
+            }
+        }
+
+        verdict.action(config)
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::{bail, Result};
+use serde_json::json;
+
+use super::GlobalState;
+use crate::api::deadline::{self, DOWNSTREAM_TIMEOUT};
+
+impl GlobalState {
+    /// Sends a plain text email. With the "none" provider emails are dropped, which is only meant for development.
+    pub async fn send_email(&self, to: &str, subject: &str, text: &str) -> Result<()> {
+        let config = &self.config.mail;
+
+        match config.provider.as_str() {
+            "none" => {
+                tracing::warn!(subject, "no mail provider configured, dropping email");
+                Ok(())
+            }
+            "http" => {
+                let client = reqwest::Client::new();
+
+                client
+                    .post(config.url.as_str())
+                    .bearer_auth(&config.secret_key)
+                    .json(&json!({
+                        "from": config.from,
+                        "to": to,
+                        "subject": subject,
+                        "text": text,
+                    }))
+                    .timeout(deadline::timeout(DOWNSTREAM_TIMEOUT))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+
+                Ok(())
+            }
+            provider => bail!("unknown mail provider: {}", provider),
+        }
+    }
+}
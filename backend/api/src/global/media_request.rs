@@ -0,0 +1,181 @@
+use anyhow::Result;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::media_request;
+use crate::pb;
+
+/// Why a viewer could not request media.
+#[derive(Debug)]
+pub enum SubmitError {
+    /// The channel does not take media requests.
+    Disabled,
+    /// The url is not a link to an allowed host.
+    InvalidUrl(&'static str),
+    /// The viewer has as many requests waiting as they are allowed.
+    TooMany,
+    /// The channel has as many requests waiting for approval as it allows.
+    QueueFull,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for SubmitError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+impl GlobalState {
+    /// Tells the overlays of a channel that its media requests changed.
+    /// The change is saved already, failing to publish it only logs.
+    pub async fn media_requests_changed(&self, channel_id: Uuid) {
+        let event = pb::scuffle::events::MediaRequestsChanged {};
+
+        if let Err(e) = self.publish_event(channel_id, &event).await {
+            tracing::error!("failed to publish media requests of {}: {}", channel_id, e);
+        }
+    }
+
+    /// Requests media in a channel for a viewer, it waits for the broadcaster to approve it.
+    pub async fn submit_media_request(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+        url: &str,
+    ) -> Result<media_request::Model, SubmitError> {
+        let config = &self.config.media_requests;
+
+        media_request::validate_url(url, &config.allowed_hosts).map_err(SubmitError::InvalidUrl)?;
+
+        // The limits are checked in the insert so a viewer can't get past them with requests made at the same time.
+        let request = sqlx::query_as!(
+            media_request::Model,
+            "INSERT INTO media_requests (channel_id, user_id, url) SELECT u.id, $2, $3 FROM users u WHERE u.id = $1 AND u.media_requests_enabled AND (SELECT COUNT(*) FROM media_requests WHERE channel_id = $1 AND user_id = $2 AND status IN ($4, $5)) < $6 AND (SELECT COUNT(*) FROM media_requests WHERE channel_id = $1 AND status = $4) < $7 RETURNING *",
+            channel_id,
+            user_id,
+            url,
+            i64::from(media_request::Status::Pending),
+            i64::from(media_request::Status::Approved),
+            config.max_pending_per_viewer as i64,
+            config.max_pending as i64,
+        )
+        .fetch_optional(&*self.db)
+        .await?;
+
+        if let Some(request) = request {
+            self.media_requests_changed(channel_id).await;
+            return Ok(request);
+        }
+
+        let enabled = sqlx::query_scalar!(
+            "SELECT media_requests_enabled FROM users WHERE id = $1",
+            channel_id,
+        )
+        .fetch_optional(&*self.db)
+        .await?
+        .unwrap_or_default();
+
+        if !enabled {
+            return Err(SubmitError::Disabled);
+        }
+
+        let mine = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM media_requests WHERE channel_id = $1 AND user_id = $2 AND status IN ($3, $4)"#,
+            channel_id,
+            user_id,
+            i64::from(media_request::Status::Pending),
+            i64::from(media_request::Status::Approved),
+        )
+        .fetch_one(&*self.db)
+        .await?;
+
+        match mine >= config.max_pending_per_viewer as i64 {
+            true => Err(SubmitError::TooMany),
+            false => Err(SubmitError::QueueFull),
+        }
+    }
+
+    /// Requests media for the author of a chat message if it is a request command.
+    /// Returns whether a request was made, requests which are not allowed are skipped without an answer.
+    pub async fn media_request_by_command(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+        content: &str,
+    ) -> Result<bool> {
+        let Some(url) = media_request::command(content) else {
+            return Ok(false);
+        };
+
+        match self.submit_media_request(channel_id, user_id, url).await {
+            Ok(_) => Ok(true),
+            Err(SubmitError::Database(e)) => Err(e.into()),
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Approves or rejects a request in a channel. Pending requests can be approved,
+    /// pending and approved ones rejected so the broadcaster can take a request back out of the queue.
+    /// Returns None if there is no such request which can be reviewed.
+    pub async fn review_media_request(
+        &self,
+        channel_id: Uuid,
+        id: Uuid,
+        reviewer_id: Uuid,
+        approved: bool,
+    ) -> Result<Option<media_request::Model>> {
+        let (status, from) = match approved {
+            true => (
+                media_request::Status::Approved,
+                vec![i64::from(media_request::Status::Pending)],
+            ),
+            false => (
+                media_request::Status::Rejected,
+                vec![
+                    i64::from(media_request::Status::Pending),
+                    i64::from(media_request::Status::Approved),
+                ],
+            ),
+        };
+
+        let request = sqlx::query_as!(
+            media_request::Model,
+            "UPDATE media_requests SET status = $3, reviewed_by = $4, reviewed_at = NOW() WHERE id = $1 AND channel_id = $2 AND status = ANY($5) RETURNING *",
+            id,
+            channel_id,
+            i64::from(status),
+            reviewer_id,
+            &from,
+        )
+        .fetch_optional(&*self.db)
+        .await?;
+
+        if request.is_some() {
+            self.media_requests_changed(channel_id).await;
+        }
+
+        Ok(request)
+    }
+
+    /// Takes the first approved request off the queue of a channel to play it, None if the queue is empty.
+    pub async fn play_next_media_request(
+        &self,
+        channel_id: Uuid,
+    ) -> Result<Option<media_request::Model>> {
+        let request = sqlx::query_as!(
+            media_request::Model,
+            "UPDATE media_requests SET status = $3, played_at = NOW() WHERE id = (SELECT id FROM media_requests WHERE channel_id = $1 AND status = $2 ORDER BY created_at, id LIMIT 1 FOR UPDATE SKIP LOCKED) RETURNING *",
+            channel_id,
+            i64::from(media_request::Status::Approved),
+            i64::from(media_request::Status::Played),
+        )
+        .fetch_optional(&*self.db)
+        .await?;
+
+        if request.is_some() {
+            self.media_requests_changed(channel_id).await;
+        }
+
+        Ok(request)
+    }
+}
@@ -18,7 +18,44 @@ use crate::dataloader::{
 };
 use crate::subscription::SubscriptionManager;
 
+pub mod approval;
+pub mod channel_import;
+pub mod charity;
+pub mod chat;
+pub mod chat_fan_out;
+pub mod classifier;
+pub mod data_export;
+pub mod dead_letter;
+pub mod digest;
+pub mod display_color;
+pub mod emote_provider;
+pub mod encryption;
+pub mod events;
+pub mod giveaway;
+pub mod image_processor;
+pub mod ip_reputation;
+pub mod mail;
+pub mod media_request;
+pub mod moderation;
+pub mod notifications;
+pub mod oauth;
+pub mod passkey;
+pub mod payment;
+pub mod payout;
+pub mod presence;
+pub mod profile;
+pub mod reconciliation;
+pub mod residency;
+pub mod sandbox;
+pub mod session;
+pub mod storage;
+pub mod stream_info;
+pub mod stream_lifecycle;
+pub mod suspension;
 pub mod turnstile;
+pub mod viewer_queue;
+pub mod vod_caption;
+pub mod vod_download;
 
 pub struct GlobalState {
     pub config: AppConfig,
@@ -32,6 +69,7 @@ pub struct GlobalState {
     pub subscription_manager: SubscriptionManager,
     pub rmq: common::rmq::ConnectionPool,
     pub redis: RedisPool,
+    pub keyring: encryption::Keyring,
 }
 
 impl GlobalState {
@@ -42,15 +80,20 @@ impl GlobalState {
         redis: RedisPool,
         ctx: Context,
     ) -> Self {
+        let keyring =
+            encryption::Keyring::load(&config.encryption).expect("failed to load encryption keys");
+        let subscription_manager = SubscriptionManager::new(&config.subscriptions);
+
         Self {
             config,
             ctx,
+            keyring,
             user_by_username_loader: UserByUsernameLoader::new(db.clone()),
             user_by_id_loader: UserByIdLoader::new(db.clone()),
             session_by_id_loader: SessionByIdLoader::new(db.clone()),
             user_permisions_by_id_loader: UserPermissionsByIdLoader::new(db.clone()),
             stream_by_id_loader: StreamByIdLoader::new(db.clone()),
-            subscription_manager: SubscriptionManager::default(),
+            subscription_manager,
             db,
             rmq,
             redis,
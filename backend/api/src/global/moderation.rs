@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use common::prelude::FutureTimeout;
+use lapin::{options::BasicPublishOptions, BasicProperties};
+use prost::Message;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::chat_ban;
+use crate::pb;
+
+impl GlobalState {
+    /// Queues a moderation job, which the integrations pick up in the background.
+    pub async fn queue_moderation_job(&self, job_id: Uuid) -> Result<()> {
+        let channel = self
+            .rmq
+            .aquire()
+            .timeout(Duration::from_secs(1))
+            .await
+            .map_err(|_| anyhow::anyhow!("failed to aquire channel: timed out"))??;
+
+        channel
+            .basic_publish(
+                "",
+                &self.config.moderation.queue,
+                BasicPublishOptions::default(),
+                pb::scuffle::events::ModerationJob {
+                    id: job_id.to_string(),
+                }
+                .encode_to_vec()
+                .as_slice(),
+                BasicProperties::default()
+                    .with_message_id(job_id.to_string().into())
+                    .with_content_type("application/octet-stream".into()),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Tells a user they were banned from the chat of a channel, with the ban, or unbanned.
+    /// The open chats of the user end when they are banned. The ban is saved already, failing to publish it only logs.
+    pub async fn chat_ban_changed(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+        ban: Option<&chat_ban::Model>,
+    ) {
+        let event = pb::scuffle::events::ChatBanChanged {
+            channel_id: channel_id.to_string(),
+            banned: ban.is_some(),
+            expires_at: ban.and_then(|ban| ban.expires_at).map(|e| e.timestamp()),
+        };
+
+        if let Err(e) = self.publish_event(user_id, &event).await {
+            tracing::error!(
+                "failed to publish ban of {} in {}: {}",
+                user_id,
+                channel_id,
+                e
+            );
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use common::prelude::FutureTimeout;
+use lapin::{options::BasicPublishOptions, BasicProperties};
+use prost::Message;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::pb::scuffle::events::{channel_notification::Kind, ChannelNotification};
+
+impl GlobalState {
+    /// Publishes a notification about a channel's stream. Every service bound to the exchange gets a copy,
+    /// the integrations of the API and the federation service fan it out to third-party services.
+    pub async fn notify(&self, channel_id: Uuid, kind: Kind, stream_id: Uuid) -> Result<()> {
+        let channel = self
+            .rmq
+            .aquire()
+            .timeout(Duration::from_secs(1))
+            .await
+            .map_err(|_| anyhow::anyhow!("failed to aquire channel: timed out"))??;
+
+        channel
+            .basic_publish(
+                &self.config.notifications.exchange,
+                "",
+                BasicPublishOptions::default(),
+                ChannelNotification {
+                    channel_id: channel_id.to_string(),
+                    kind: kind as i32,
+                    stream_id: stream_id.to_string(),
+                    created_at: Utc::now().timestamp(),
+                }
+                .encode_to_vec()
+                .as_slice(),
+                BasicProperties::default()
+                    .with_message_id(Uuid::new_v4().to_string().into())
+                    .with_content_type("application/octet-stream".into()),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::{anyhow, Result};
+
+use super::GlobalState;
+use crate::api::deadline::{self, DOWNSTREAM_TIMEOUT};
+use crate::config::OAuthProviderConfig;
+use crate::database::external_account::Provider;
+
+/// The account at a provider an authorization code was issued for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalIdentity {
+    /// The id of the account, which unlike the name never changes.
+    pub id: String,
+    pub username: String,
+}
+
+/// Reads the account from the user endpoint of a provider.
+pub fn parse_identity(provider: Provider, body: &serde_json::Value) -> Result<ExternalIdentity> {
+    let (id, username) = match provider {
+        Provider::Google => (body["sub"].as_str().map(str::to_string), &body["name"]),
+        Provider::Discord => (body["id"].as_str().map(str::to_string), &body["username"]),
+        // GitHub ids are numbers.
+        Provider::Github => (body["id"].as_u64().map(|id| id.to_string()), &body["login"]),
+    };
+
+    Ok(ExternalIdentity {
+        id: id
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| anyhow!("{} did not return an account id", provider.display_name()))?,
+        username: username.as_str().unwrap_or_default().to_string(),
+    })
+}
+
+impl GlobalState {
+    /// The application of a provider, None if logging in with it is not enabled.
+    pub fn oauth_provider(&self, provider: Provider) -> Option<&OAuthProviderConfig> {
+        let config = match provider {
+            Provider::Google => &self.config.oauth.google,
+            Provider::Discord => &self.config.oauth.discord,
+            Provider::Github => &self.config.oauth.github,
+        };
+
+        Some(config).filter(|c| !c.client_id.is_empty())
+    }
+
+    /// Exchanges an authorization code for the account it was issued for. Returns None if the provider
+    /// rejected the code, e.g. because it expired or was already used.
+    pub async fn fetch_external_identity(
+        &self,
+        provider: Provider,
+        code: &str,
+        redirect_uri: &str,
+    ) -> Result<Option<ExternalIdentity>> {
+        let config = self
+            .oauth_provider(provider)
+            .ok_or_else(|| anyhow!("{} is not enabled", provider.display_name()))?;
+
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(&config.token_url)
+            .header("Accept", "application/json")
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", redirect_uri),
+                ("client_id", config.client_id.as_str()),
+                ("client_secret", config.client_secret.as_str()),
+            ])
+            .timeout(deadline::timeout(DOWNSTREAM_TIMEOUT))
+            .send()
+            .await?;
+
+        if res.status().is_client_error() {
+            return Ok(None);
+        }
+
+        // GitHub answers rejected codes with an error in a successful response.
+        let token = res.error_for_status()?.json::<serde_json::Value>().await?;
+        let Some(access_token) = token["access_token"].as_str() else {
+            return Ok(None);
+        };
+
+        let body = client
+            .get(&config.user_url)
+            .bearer_auth(access_token)
+            .header("Accept", "application/json")
+            // GitHub rejects requests without a user agent.
+            .header("User-Agent", "scuffle")
+            .timeout(deadline::timeout(DOWNSTREAM_TIMEOUT))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<serde_json::Value>()
+            .await?;
+
+        parse_identity(provider, &body).map(Some)
+    }
+}
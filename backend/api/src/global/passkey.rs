@@ -0,0 +1,333 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
+use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_ASN1};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::config::PasskeyConfig;
+use crate::database::{
+    passkey,
+    passkey_challenge::{self, Kind},
+};
+
+/// The COSE id of ES256, ECDSA with P-256 and SHA-256. Every platform authenticator supports it, so it is the only one accepted.
+pub const ES256: i64 = -7;
+
+/// The DER SubjectPublicKeyInfo of a P-256 key up to the uncompressed point.
+const P256_SPKI_PREFIX: [u8; 26] = [
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a,
+    0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+/// What `navigator.credentials.create` returned, the binary fields as base64url.
+pub struct Attestation {
+    pub credential_id: String,
+    pub client_data_json: String,
+    /// From `getAuthenticatorData()`.
+    pub authenticator_data: String,
+    /// From `getPublicKey()`, a DER SubjectPublicKeyInfo.
+    pub public_key: String,
+    /// From `getPublicKeyAlgorithm()`.
+    pub public_key_algorithm: i64,
+}
+
+/// What `navigator.credentials.get` returned, the binary fields as base64url.
+pub struct Assertion {
+    pub credential_id: String,
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+}
+
+/// A passkey whose registration checked out.
+#[derive(Debug, PartialEq, Eq)]
+pub struct VerifiedPasskey {
+    /// The credential id as unpadded base64url, the way it is stored.
+    pub credential_id: String,
+    /// The uncompressed P-256 point.
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+}
+
+/// Why a passkey could not be registered or used.
+#[derive(Debug)]
+pub enum PasskeyError {
+    /// The response of the authenticator does not check out.
+    Invalid(&'static str),
+    /// The challenge does not exist, expired, was already answered or is for another user.
+    ChallengeNotFound,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for PasskeyError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, PasskeyError> {
+    BASE64_URL
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| PasskeyError::Invalid("Passkey response is not valid base64url"))
+}
+
+/// Checks the client data the browser collected, so the response is for this challenge and website.
+fn check_client_data(
+    config: &PasskeyConfig,
+    client_data: &[u8],
+    kind: &str,
+    challenge: &str,
+) -> Result<(), PasskeyError> {
+    #[derive(serde::Deserialize)]
+    struct ClientData {
+        #[serde(rename = "type")]
+        kind: String,
+        challenge: String,
+        origin: String,
+    }
+
+    let data: ClientData = serde_json::from_slice(client_data)
+        .map_err(|_| PasskeyError::Invalid("Client data is not valid"))?;
+
+    if data.kind != kind {
+        return Err(PasskeyError::Invalid(
+            "Client data is for a different ceremony",
+        ));
+    }
+
+    if data.challenge.trim_end_matches('=') != challenge {
+        return Err(PasskeyError::Invalid(
+            "Passkey response is for a different challenge",
+        ));
+    }
+
+    if !config.origins.contains(&data.origin) {
+        return Err(PasskeyError::Invalid(
+            "Passkey was used on an unknown origin",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks the authenticator data is for the relying party, returns the flags and the signature counter.
+fn check_authenticator_data(
+    config: &PasskeyConfig,
+    data: &[u8],
+    user_verified: bool,
+) -> Result<(u8, u32), PasskeyError> {
+    if data.len() < 37 {
+        return Err(PasskeyError::Invalid("Authenticator data is too short"));
+    }
+
+    if data[..32] != Sha256::digest(config.rp_id.as_bytes())[..] {
+        return Err(PasskeyError::Invalid("Passkey is for a different website"));
+    }
+
+    let flags = data[32];
+    if flags & FLAG_USER_PRESENT == 0 {
+        return Err(PasskeyError::Invalid("The user was not present"));
+    }
+
+    if user_verified && flags & FLAG_USER_VERIFIED == 0 {
+        return Err(PasskeyError::Invalid("The passkey did not verify the user"));
+    }
+
+    Ok((
+        flags,
+        u32::from_be_bytes([data[33], data[34], data[35], data[36]]),
+    ))
+}
+
+/// Checks the response to a registration challenge. Attestation statements are not checked,
+/// any authenticator is accepted as long as it holds the key.
+pub fn verify_attestation(
+    config: &PasskeyConfig,
+    challenge: &str,
+    attestation: &Attestation,
+) -> Result<VerifiedPasskey, PasskeyError> {
+    if attestation.public_key_algorithm != ES256 {
+        return Err(PasskeyError::Invalid("Only ES256 passkeys are supported"));
+    }
+
+    check_client_data(
+        config,
+        &decode(&attestation.client_data_json)?,
+        "webauthn.create",
+        challenge,
+    )?;
+
+    let data = decode(&attestation.authenticator_data)?;
+    let (flags, sign_count) = check_authenticator_data(config, &data, false)?;
+
+    // The attested credential data follows the counter, a 16 byte AAGUID, the length of the id and the id.
+    let credential_id = decode(&attestation.credential_id)?;
+    let matches = flags & FLAG_ATTESTED_CREDENTIAL != 0
+        && data.len() >= 55
+        && u16::from_be_bytes([data[53], data[54]]) as usize == credential_id.len()
+        && data.get(55..55 + credential_id.len()) == Some(credential_id.as_slice());
+    if !matches {
+        return Err(PasskeyError::Invalid(
+            "Authenticator data is for a different credential",
+        ));
+    }
+
+    let public_key = decode(&attestation.public_key)?;
+    match public_key.strip_prefix(&P256_SPKI_PREFIX[..]) {
+        Some(point) if point.len() == 65 && point[0] == 0x04 => Ok(VerifiedPasskey {
+            credential_id: BASE64_URL.encode(credential_id),
+            public_key: point.to_vec(),
+            sign_count,
+        }),
+        _ => Err(PasskeyError::Invalid("Public key is not a P-256 key")),
+    }
+}
+
+/// Checks the response to a login challenge against a stored passkey, returns the new signature counter.
+/// Passwordless logins need `user_verified`, the authenticator has to have checked a PIN or biometric.
+pub fn verify_assertion(
+    config: &PasskeyConfig,
+    challenge: &str,
+    passkey: &passkey::Model,
+    assertion: &Assertion,
+    user_verified: bool,
+) -> Result<u32, PasskeyError> {
+    let client_data = decode(&assertion.client_data_json)?;
+    check_client_data(config, &client_data, "webauthn.get", challenge)?;
+
+    let mut data = decode(&assertion.authenticator_data)?;
+    let (_, sign_count) = check_authenticator_data(config, &data, user_verified)?;
+
+    data.extend_from_slice(&Sha256::digest(&client_data));
+    UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, &passkey.public_key)
+        .verify(&data, &decode(&assertion.signature)?)
+        .map_err(|_| PasskeyError::Invalid("Passkey signature is not valid"))?;
+
+    // Authenticators which don't count always report 0, a counter which did not go up means the key was cloned.
+    if sign_count != 0 && sign_count as i64 <= passkey.sign_count {
+        return Err(PasskeyError::Invalid(
+            "Passkey signature counter went backwards",
+        ));
+    }
+
+    Ok(sign_count)
+}
+
+impl GlobalState {
+    /// Creates a challenge for `navigator.credentials`, valid for the configured time.
+    pub async fn create_passkey_challenge(
+        &self,
+        user_id: Option<Uuid>,
+        kind: Kind,
+    ) -> sqlx::Result<passkey_challenge::Model> {
+        sqlx::query_as!(
+            passkey_challenge::Model,
+            "INSERT INTO passkey_challenges (user_id, kind, challenge, expires_at) VALUES ($1, $2, $3, NOW() + $4 * INTERVAL '1 second') RETURNING *",
+            user_id,
+            i64::from(kind),
+            passkey_challenge::generate_challenge(),
+            self.config.passkeys.challenge_ttl as i64,
+        )
+        .fetch_one(&*self.db)
+        .await
+    }
+
+    /// Answers a challenge. A challenge can only be answered once, also if the answer turns out to be wrong.
+    async fn take_passkey_challenge(
+        &self,
+        id: Uuid,
+        kind: Kind,
+    ) -> Result<passkey_challenge::Model, PasskeyError> {
+        sqlx::query_as!(
+            passkey_challenge::Model,
+            "UPDATE passkey_challenges SET used_at = NOW() WHERE id = $1 AND kind = $2 AND used_at IS NULL AND expires_at > NOW() RETURNING *",
+            id,
+            i64::from(kind),
+        )
+        .fetch_optional(&*self.db)
+        .await?
+        .ok_or(PasskeyError::ChallengeNotFound)
+    }
+
+    /// Registers a passkey for a user with the response to their registration challenge.
+    pub async fn register_passkey(
+        &self,
+        user_id: Uuid,
+        challenge_id: Uuid,
+        name: &str,
+        attestation: &Attestation,
+    ) -> Result<passkey::Model, PasskeyError> {
+        let challenge = self
+            .take_passkey_challenge(challenge_id, Kind::Registration)
+            .await?;
+        if challenge.user_id != Some(user_id) {
+            return Err(PasskeyError::ChallengeNotFound);
+        }
+
+        let verified =
+            verify_attestation(&self.config.passkeys, &challenge.challenge, attestation)?;
+
+        sqlx::query_as!(
+            passkey::Model,
+            "INSERT INTO passkeys (user_id, credential_id, public_key, sign_count, name) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (credential_id) DO NOTHING RETURNING *",
+            user_id,
+            verified.credential_id,
+            verified.public_key,
+            verified.sign_count as i64,
+            name,
+        )
+        .fetch_optional(&*self.db)
+        .await?
+        .ok_or(PasskeyError::Invalid("Passkey is already registered"))
+    }
+
+    /// Checks a passkey login and moves the signature counter of the passkey forward.
+    /// `user_id` is set when the passkey is a second factor, the password then already told who logs in.
+    /// The challenge may be one without a user then, as after a login link or an OAuth login the client doesn't know who logs in.
+    pub async fn authenticate_passkey(
+        &self,
+        challenge_id: Uuid,
+        user_id: Option<Uuid>,
+        assertion: &Assertion,
+    ) -> Result<passkey::Model, PasskeyError> {
+        let challenge = self
+            .take_passkey_challenge(challenge_id, Kind::Login)
+            .await?;
+        if user_id.is_some() && challenge.user_id.is_some() && challenge.user_id != user_id {
+            return Err(PasskeyError::ChallengeNotFound);
+        }
+        let owner = user_id.or(challenge.user_id);
+
+        let credential_id = BASE64_URL.encode(decode(&assertion.credential_id)?);
+        let passkey = sqlx::query_as!(
+            passkey::Model,
+            "SELECT * FROM passkeys WHERE credential_id = $1",
+            credential_id,
+        )
+        .fetch_optional(&*self.db)
+        .await?
+        .filter(|passkey| owner.map_or(true, |user_id| passkey.user_id == user_id))
+        .ok_or(PasskeyError::Invalid("Unknown passkey"))?;
+
+        let sign_count = verify_assertion(
+            &self.config.passkeys,
+            &challenge.challenge,
+            &passkey,
+            assertion,
+            user_id.is_none(),
+        )?;
+
+        Ok(sqlx::query_as!(
+            passkey::Model,
+            "UPDATE passkeys SET sign_count = $2, last_used_at = NOW() WHERE id = $1 RETURNING *",
+            passkey.id,
+            sign_count as i64,
+        )
+        .fetch_one(&*self.db)
+        .await?)
+    }
+}
@@ -0,0 +1,47 @@
+use anyhow::Result;
+use serde::de::Error;
+use serde_json::json;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::api::deadline::{self, DOWNSTREAM_TIMEOUT};
+
+impl GlobalState {
+    /// Creates a checkout session at the payment provider and returns its id and the url the user pays at.
+    /// The reference is the id of our checkout or donation, the provider sends it back when calling the webhook.
+    pub async fn create_payment_checkout(
+        &self,
+        reference: Uuid,
+        amount: i64,
+        description: &str,
+    ) -> Result<(String, String)> {
+        let client = reqwest::Client::new();
+
+        let body = json!({
+            "reference": reference.to_string(),
+            "amount": amount,
+            "currency": "usd",
+            "description": description,
+        });
+
+        let res = client
+            .post(format!("{}/checkouts", self.config.payment.url))
+            .bearer_auth(&self.config.payment.secret_key)
+            .json(&body)
+            .timeout(deadline::timeout(DOWNSTREAM_TIMEOUT))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let body = res.json::<serde_json::Value>().await?;
+
+        let id = body["id"]
+            .as_str()
+            .ok_or(serde_json::Error::missing_field("id"))?;
+        let url = body["url"]
+            .as_str()
+            .ok_or(serde_json::Error::missing_field("url"))?;
+
+        Ok((id.to_string(), url.to_string()))
+    }
+}
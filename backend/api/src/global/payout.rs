@@ -0,0 +1,34 @@
+use anyhow::Result;
+use serde::de::Error;
+use serde_json::json;
+
+use super::GlobalState;
+use crate::api::deadline::{self, DOWNSTREAM_TIMEOUT};
+
+impl GlobalState {
+    /// Exchanges the token the payout provider handed out at the end of its hosted onboarding
+    /// (where bank details and tax forms are collected) for the id of the created account.
+    pub async fn register_payout_account(&self, onboarding_token: &str) -> Result<String> {
+        let client = reqwest::Client::new();
+
+        let body = json!({
+            "token": onboarding_token,
+        });
+
+        let res = client
+            .post(format!("{}/accounts", self.config.payout.url))
+            .bearer_auth(&self.config.payout.secret_key)
+            .json(&body)
+            .timeout(deadline::timeout(DOWNSTREAM_TIMEOUT))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let body = res.json::<serde_json::Value>().await?;
+
+        Ok(body["account_id"]
+            .as_str()
+            .ok_or(serde_json::Error::missing_field("account_id"))?
+            .to_string())
+    }
+}
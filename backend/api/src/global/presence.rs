@@ -0,0 +1,116 @@
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use fred::{error::RedisError, prelude::KeysInterface, types::Expiration};
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::user;
+use crate::pb::scuffle::events::UserCameOnline;
+
+/// A followed user who is online.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Presence {
+    pub user_id: Uuid,
+    pub last_active_at: DateTime<Utc>,
+    /// The channel the user is watching, if they share it.
+    pub watching: Option<Uuid>,
+}
+
+fn online_key(user_id: Uuid) -> String {
+    format!("presence:{}", user_id)
+}
+
+fn watching_key(user_id: Uuid) -> String {
+    format!("presence:{}:watching", user_id)
+}
+
+impl GlobalState {
+    /// Marks a user as online, and as watching a channel if one is given. Both expire after the presence ttl,
+    /// so users go offline by not being active anymore. Everyone is tracked, the privacy settings apply when reading.
+    /// Coming online is published so subscribers see it right away.
+    pub async fn touch_presence(
+        &self,
+        user_id: Uuid,
+        watching: Option<Uuid>,
+    ) -> Result<(), RedisError> {
+        let ttl = Some(Expiration::EX(self.config.presence.ttl as i64));
+
+        let previous: Option<String> = self
+            .redis
+            .set(
+                online_key(user_id),
+                Utc::now().timestamp(),
+                ttl.clone(),
+                None,
+                true,
+            )
+            .await?;
+
+        if previous.is_none() {
+            if let Err(e) = self.publish_event(user_id, &UserCameOnline {}).await {
+                tracing::warn!("failed to publish presence: {}", e);
+            }
+        }
+
+        if let Some(channel_id) = watching {
+            let _: () = self
+                .redis
+                .set(
+                    watching_key(user_id),
+                    channel_id.to_string(),
+                    ttl,
+                    None,
+                    false,
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether a user is online, regardless of who may see it.
+    pub async fn is_online(&self, user_id: Uuid) -> Result<bool, RedisError> {
+        let online: i64 = self.redis.exists(online_key(user_id)).await?;
+        Ok(online > 0)
+    }
+
+    /// The followed users and friends of a user who are online and let them see it, most recently active first.
+    pub async fn friends_presence(&self, user_id: Uuid) -> Result<Vec<Presence>> {
+        let visible = user::presence_visible_to(&*self.db, user_id).await?;
+        if visible.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys = visible
+            .iter()
+            .flat_map(|(id, _)| [online_key(*id), watching_key(*id)])
+            .collect::<Vec<_>>();
+        let values: Vec<Option<String>> = self.redis.mget(keys).await?;
+
+        let mut presences = visible
+            .iter()
+            .zip(values.chunks(2))
+            .filter_map(|((id, share_watching), values)| {
+                let last_active_at = values[0]
+                    .as_ref()
+                    .and_then(|v| v.parse().ok())
+                    .and_then(|t| Utc.timestamp_opt(t, 0).single())?;
+
+                let watching = values[1]
+                    .as_ref()
+                    .filter(|_| *share_watching)
+                    .and_then(|v| v.parse().ok());
+
+                Some(Presence {
+                    user_id: *id,
+                    last_active_at,
+                    watching,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        presences.sort_by(|a, b| b.last_active_at.cmp(&a.last_active_at));
+
+        Ok(presences)
+    }
+}
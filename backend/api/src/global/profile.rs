@@ -0,0 +1,96 @@
+use common::database::retry;
+use uuid::Uuid;
+
+use super::{display_color::DisplayColorError, GlobalState};
+use crate::database::{display_color::DisplayColor, user, user_social_link};
+use crate::pb;
+
+/// A change to the profile of a user, every field validated already. Unset fields are left as they are.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileChange {
+    /// The color and gradient end as they are stored, from `display_color::stored_change`.
+    pub display_color: Option<(String, String)>,
+    /// The sanitized bio.
+    pub bio: Option<String>,
+    /// The labels and urls of the links, in order.
+    pub social_links: Option<Vec<(String, String)>>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+}
+
+impl GlobalState {
+    /// Applies a profile change in one transaction, either every field changes or none does.
+    /// Profile pages are told with a single event, failing to publish it only logs.
+    pub async fn update_profile(
+        &self,
+        user_id: Uuid,
+        change: &ProfileChange,
+        gradient_allowed: bool,
+    ) -> Result<user::Model, DisplayColorError> {
+        let (user, links) = retry(&self.config.database, || {
+            self.store_profile(user_id, change)
+        })
+        .await?;
+
+        let event = pb::scuffle::events::UserProfileUpdated {
+            bio: user.bio.clone(),
+            display_color: DisplayColor::of_user(&user, gradient_allowed)
+                .as_ref()
+                .map(Into::into),
+            social_links: links
+                .into_iter()
+                .map(|link| pb::scuffle::events::UserSocialLink {
+                    label: link.label,
+                    url: link.url,
+                })
+                .collect(),
+        };
+
+        if let Err(e) = self.publish_event(user.id, &event).await {
+            tracing::error!("failed to publish profile of user {}: {}", user.id, e);
+        }
+
+        Ok(user)
+    }
+
+    async fn store_profile(
+        &self,
+        user_id: Uuid,
+        change: &ProfileChange,
+    ) -> Result<(user::Model, Vec<user_social_link::Model>), DisplayColorError> {
+        let mut tx = self.db.begin().await?;
+
+        if let Some((color, gradient_end)) = &change.display_color {
+            self.change_display_color(&mut tx, user_id, color, gradient_end)
+                .await?;
+        }
+
+        let links = match &change.social_links {
+            Some(links) => user_social_link::replace(&mut tx, user_id, links).await?,
+            None => {
+                sqlx::query_as!(
+                    user_social_link::Model,
+                    "SELECT * FROM user_social_links WHERE user_id = $1 ORDER BY position",
+                    user_id,
+                )
+                .fetch_all(&mut *tx)
+                .await?
+            }
+        };
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET bio = COALESCE($2, bio), locale = COALESCE($3, locale), timezone = COALESCE($4, timezone) WHERE id = $1 RETURNING *",
+            user_id,
+            change.bio,
+            change.locale,
+            change.timezone,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok((user, links))
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+
+use super::GlobalState;
+use crate::database::channel_event;
+
+/// What a reconciliation run repaired.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Reconciliation {
+    /// Ended streams which had no summary, usually because their ingest stopped reporting them
+    /// instead of sending the final state.
+    pub backfilled_sessions: u64,
+    /// Summaries whose unique chatters or new follows did not match the chat and follows anymore.
+    pub corrected_sessions: u64,
+}
+
+impl GlobalState {
+    /// Writes the missing summaries of streams which ended since the given time, and recounts the chatters and follows
+    /// of the existing ones. The chatters of streams whose chat was not kept can't be recounted, so they are left alone.
+    pub async fn reconcile_stream_sessions(
+        &self,
+        since: DateTime<Utc>,
+    ) -> sqlx::Result<Reconciliation> {
+        let follow = i64::from(channel_event::Kind::Follow);
+
+        let backfilled_sessions = sqlx::query!(
+            "INSERT INTO stream_sessions (channel_id, stream_id, unique_chatters, new_follows, started_at, ended_at) SELECT s.channel_id, s.id, (SELECT COUNT(DISTINCT m.author_id) FROM chat_messages m WHERE m.channel_id = s.channel_id AND m.created_at BETWEEN s.created_at AND s.ended_at), (SELECT COUNT(*) FROM channel_events e WHERE e.channel_id = s.channel_id AND e.kind = $2 AND e.created_at BETWEEN s.created_at AND s.ended_at), s.created_at, s.ended_at FROM streams s WHERE s.ended_at >= $1 AND s.ended_at <= NOW() AND s.deleted = FALSE ON CONFLICT (stream_id) DO NOTHING",
+            since,
+            follow,
+        )
+        .execute(&*self.db)
+        .await?
+        .rows_affected();
+
+        let corrected_sessions = sqlx::query!(
+            "WITH counts AS (SELECT ss.id, CASE WHEN s.chat_archived THEN (SELECT COUNT(DISTINCT m.author_id) FROM chat_messages m WHERE m.channel_id = ss.channel_id AND m.created_at BETWEEN ss.started_at AND ss.ended_at) ELSE ss.unique_chatters END AS unique_chatters, (SELECT COUNT(*) FROM channel_events e WHERE e.channel_id = ss.channel_id AND e.kind = $2 AND e.created_at BETWEEN ss.started_at AND ss.ended_at) AS new_follows FROM stream_sessions ss JOIN streams s ON s.id = ss.stream_id WHERE ss.ended_at >= $1) UPDATE stream_sessions ss SET unique_chatters = c.unique_chatters, new_follows = c.new_follows FROM counts c WHERE ss.id = c.id AND (ss.unique_chatters, ss.new_follows) IS DISTINCT FROM (c.unique_chatters, c.new_follows)",
+            since,
+            follow,
+        )
+        .execute(&*self.db)
+        .await?
+        .rows_affected();
+
+        Ok(Reconciliation {
+            backfilled_sessions,
+            corrected_sessions,
+        })
+    }
+}
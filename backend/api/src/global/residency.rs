@@ -0,0 +1,45 @@
+use super::GlobalState;
+use crate::config::StorageConfig;
+
+impl GlobalState {
+    /// The region the data of a user who chose `region` is kept in. Empty and unknown choices use the default region.
+    pub fn data_region<'a>(&'a self, region: &'a str) -> &'a str {
+        match self.config.residency.regions.contains_key(region) {
+            true => region,
+            false => &self.config.residency.default_region,
+        }
+    }
+
+    /// The regions users can choose from, sorted by their tag.
+    pub fn data_regions(&self) -> Vec<&str> {
+        let mut regions = self
+            .config
+            .residency
+            .regions
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        regions.sort_unstable();
+        regions
+    }
+
+    /// The object storage of a region. Without a configured region the global storage is used.
+    pub fn region_storage(&self, region: &str) -> &StorageConfig {
+        self.config
+            .residency
+            .regions
+            .get(self.data_region(region))
+            .map(|r| &r.storage)
+            .unwrap_or(&self.config.storage)
+    }
+
+    /// The edge which serves the streams and VODs of a region, empty if clients should use their default edge.
+    pub fn region_edge_url(&self, region: &str) -> &str {
+        self.config
+            .residency
+            .regions
+            .get(self.data_region(region))
+            .map(|r| r.edge_url.as_str())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::Result;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::{channel_event, global_role, user};
+
+/// The username, display name, stream title and category of the channels the sandbox is seeded with.
+/// The first channel is the one the test stream goes live on.
+const CHANNELS: [(&str, &str, &str, &str); 5] = [
+    (
+        "sandbox",
+        "Sandbox",
+        "Test stream, all day every day",
+        "Just Chatting",
+    ),
+    (
+        "alice",
+        "Alice",
+        "Speedrunning until I get a PB",
+        "Speedrunning",
+    ),
+    (
+        "bob",
+        "Bob",
+        "Building a synth from scratch",
+        "Science & Technology",
+    ),
+    ("carol", "Carol", "Late night drawing", "Art"),
+    ("dave", "Dave", "", ""),
+];
+
+/// Every sandbox user can log in with this password.
+pub const SANDBOX_PASSWORD: &str = "sandbox";
+
+/// The ids are fixed, so every developer gets the same users and stream keys.
+fn user_id(index: usize) -> Uuid {
+    Uuid::from_u128(0x5a4d_b0c5_0000_0000_0000_0000_0000_0000 | (index as u128 + 1))
+}
+
+fn stream_key(index: usize) -> String {
+    format!("sandbox{:017}", index + 1)
+}
+
+fn role_id() -> Uuid {
+    Uuid::from_u128(0x5a4d_b0c5_0000_0000_0000_0000_0000_0000)
+}
+
+impl GlobalState {
+    /// The stream key the test stream goes live with.
+    pub fn sandbox_stream_key(&self) -> String {
+        user::Model {
+            id: user_id(0),
+            stream_key: stream_key(0),
+            ..Default::default()
+        }
+        .get_stream_key()
+    }
+
+    /// Seeds the sandbox users, lets them go live and has each of them follow the channels before them.
+    /// Rows which are already there are left alone, so seeding again after a restart changes nothing.
+    pub async fn seed_sandbox(&self) -> Result<()> {
+        let mut tx = self.db.begin().await?;
+
+        sqlx::query!(
+            "INSERT INTO global_roles (id, name, description, rank, allowed_permissions, denied_permissions) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT DO NOTHING",
+            role_id(),
+            "Sandbox",
+            "Lets the sandbox users go live",
+            0,
+            (global_role::Permission::GoLive
+                | global_role::Permission::StreamTranscoding
+                | global_role::Permission::StreamRecording)
+                .bits(),
+            0,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let password_hash = user::hash_password(SANDBOX_PASSWORD);
+
+        for (index, (username, display_name, title, category)) in CHANNELS.iter().enumerate() {
+            let email = format!("{}@sandbox.scuffle.local", username);
+
+            sqlx::query!(
+                "INSERT INTO users (id, username, display_name, password_hash, email, email_hash, email_verified, stream_key, stream_title, stream_category, stream_transcoding_enabled, stream_recording_enabled) VALUES ($1, $2, $3, $4, $5, $6, TRUE, $7, $8, $9, TRUE, TRUE) ON CONFLICT DO NOTHING",
+                user_id(index),
+                username,
+                display_name,
+                password_hash,
+                self.encrypt_pii(&email)?,
+                self.email_hash(&email),
+                stream_key(index),
+                title,
+                category,
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            // The grant shares the id of its user, so it is only added once.
+            sqlx::query!(
+                "INSERT INTO global_role_grants (id, user_id, global_role_id) VALUES ($1, $1, $2) ON CONFLICT DO NOTHING",
+                user_id(index),
+                role_id(),
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            for channel in 0..index {
+                sqlx::query!(
+                    "INSERT INTO channel_events (channel_id, user_id, kind) SELECT $1, $2, $3 WHERE NOT EXISTS (SELECT 1 FROM channel_events WHERE channel_id = $1 AND user_id = $2 AND kind = $3)",
+                    user_id(channel),
+                    user_id(index),
+                    i64::from(channel_event::Kind::Follow),
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
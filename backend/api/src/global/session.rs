@@ -0,0 +1,32 @@
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::session;
+use crate::pb::scuffle::events::UserSessionsRevoked;
+
+impl GlobalState {
+    /// Revokes every session of a user, logging them out everywhere.
+    pub async fn revoke_sessions(&self, user_id: Uuid) -> sqlx::Result<u64> {
+        let revoked = session::revoke_all(&*self.db, user_id, None).await?;
+        self.publish_sessions_revoked(user_id, &revoked).await;
+
+        Ok(revoked.len() as u64)
+    }
+
+    /// Tells the websockets logged in with the sessions that they were revoked, so they are closed right away.
+    /// Call this once the sessions are revoked in the database. Failing to publish is only logged, the websockets
+    /// then find out with their next request.
+    pub async fn publish_sessions_revoked(&self, user_id: Uuid, session_ids: &[Uuid]) {
+        if session_ids.is_empty() {
+            return;
+        }
+
+        let event = UserSessionsRevoked {
+            session_ids: session_ids.iter().map(Uuid::to_string).collect(),
+        };
+
+        if let Err(e) = self.publish_event(user_id, &event).await {
+            tracing::warn!("failed to publish revoked sessions of {}: {}", user_id, e);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use super::GlobalState;
+use crate::api::deadline::{self, DOWNSTREAM_TIMEOUT};
+use crate::config::StorageConfig;
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Percent-encodes everything but the unreserved characters, and slashes unless `encode_slash` is set.
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b'/' if !encode_slash => "/".to_string(),
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Signs requests to an S3 compatible object storage with AWS signature version 4.
+pub struct Signer<'a> {
+    config: &'a StorageConfig,
+    now: DateTime<Utc>,
+}
+
+impl<'a> Signer<'a> {
+    pub fn new(config: &'a StorageConfig, now: DateTime<Utc>) -> Self {
+        Self { config, now }
+    }
+
+    /// The scheme with the host, the host as it is signed, and the path of an object.
+    fn locate(&self, key: &str) -> Result<(String, String, String)> {
+        let endpoint = reqwest::Url::parse(&self.config.endpoint)?;
+        let host = endpoint
+            .host_str()
+            .ok_or_else(|| anyhow!("storage endpoint has no host"))?;
+        let host = match endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+
+        // Without a bucket the endpoint is expected to point at one, like virtual-hosted buckets do.
+        let path = match self.config.bucket.is_empty() {
+            true => format!("/{}", uri_encode(key, false)),
+            false => format!(
+                "/{}/{}",
+                uri_encode(&self.config.bucket, true),
+                uri_encode(key, false)
+            ),
+        };
+
+        Ok((format!("{}://{}", endpoint.scheme(), host), host, path))
+    }
+
+    fn scope(&self) -> String {
+        format!(
+            "{}/{}/s3/aws4_request",
+            self.now.format("%Y%m%d"),
+            self.config.region
+        )
+    }
+
+    fn signature(&self, canonical_request: &str) -> String {
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            self.now.format("%Y%m%dT%H%M%SZ"),
+            self.scope(),
+            hex(&Sha256::digest(canonical_request.as_bytes())),
+        );
+
+        let key = [
+            self.now.format("%Y%m%d").to_string().as_str(),
+            &self.config.region,
+            "s3",
+            "aws4_request",
+        ]
+        .iter()
+        .fold(
+            format!("AWS4{}", self.config.secret_key).into_bytes(),
+            |key, part| hmac(&key, part),
+        );
+
+        hex(&hmac(&key, &string_to_sign))
+    }
+
+    /// A url anyone can download the object with until it expires.
+    pub fn presigned_get(&self, key: &str, expires_in: u32) -> Result<String> {
+        let (base, host, path) = self.locate(key)?;
+
+        // The parameters are already in the sorted order the canonical request needs.
+        let query = format!(
+            "X-Amz-Algorithm=AWS4-HMAC-SHA256&X-Amz-Credential={}&X-Amz-Date={}&X-Amz-Expires={}&X-Amz-SignedHeaders=host",
+            uri_encode(&format!("{}/{}", self.config.access_key, self.scope()), true),
+            self.now.format("%Y%m%dT%H%M%SZ"),
+            expires_in,
+        );
+
+        let canonical_request = format!(
+            "GET\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
+            path, query, host
+        );
+
+        Ok(format!(
+            "{}{}?{}&X-Amz-Signature={}",
+            base,
+            path,
+            query,
+            self.signature(&canonical_request)
+        ))
+    }
+
+    /// The url and the `x-amz-date`, `x-amz-content-sha256` and `authorization` headers of an upload.
+    pub fn put(&self, key: &str, body: &[u8]) -> Result<(String, [(&'static str, String); 3])> {
+        let (base, host, path) = self.locate(key)?;
+        let amz_date = self.now.format("%Y%m%dT%H%M%SZ").to_string();
+        let content_hash = hex(&Sha256::digest(body));
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            path, host, content_hash, amz_date, signed_headers, content_hash
+        );
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.config.access_key,
+            self.scope(),
+            signed_headers,
+            self.signature(&canonical_request)
+        );
+
+        Ok((
+            format!("{}{}", base, path),
+            [
+                ("x-amz-date", amz_date),
+                ("x-amz-content-sha256", content_hash),
+                ("authorization", authorization),
+            ],
+        ))
+    }
+}
+
+impl GlobalState {
+    /// Uploads an object to the object storage of a region, replacing it if it exists.
+    pub async fn put_object(
+        &self,
+        region: &str,
+        key: &str,
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Result<()> {
+        let (url, headers) =
+            Signer::new(self.region_storage(region), Utc::now()).put(key, &body)?;
+
+        let mut request = reqwest::Client::new()
+            .put(url)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .timeout(deadline::timeout(DOWNSTREAM_TIMEOUT));
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+
+        request.body(body).send().await?.error_for_status()?;
+
+        Ok(())
+    }
+
+    /// A signed url an object in the storage of a region can be downloaded with, valid for the given seconds.
+    pub fn object_url(&self, region: &str, key: &str, expires_in: u32) -> Result<String> {
+        Signer::new(self.region_storage(region), Utc::now()).presigned_get(key, expires_in)
+    }
+}
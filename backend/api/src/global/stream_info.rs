@@ -0,0 +1,43 @@
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::user::{self, StreamInfo};
+use crate::pb;
+
+impl GlobalState {
+    /// Changes the title, category and / or language of a channel's stream and tells the directory and channel pages.
+    /// Every path which changes the stream info goes through here. The change is saved either way, failing to publish only logs.
+    pub async fn update_stream_info(
+        &self,
+        channel_id: Uuid,
+        title: Option<&str>,
+        category: Option<&str>,
+        language: Option<&str>,
+    ) -> sqlx::Result<StreamInfo> {
+        let info = user::update_stream_info(
+            &self.db,
+            &self.config.database,
+            channel_id,
+            title,
+            category,
+            language,
+        )
+        .await?;
+
+        let event = pb::scuffle::events::ChannelStreamInfoUpdated {
+            title: info.title.clone(),
+            category: info.category.clone(),
+            language: info.language.clone(),
+        };
+
+        if let Err(e) = self.publish_event(channel_id, &event).await {
+            tracing::error!(
+                "failed to publish stream info of channel {}: {}",
+                channel_id,
+                e
+            );
+        }
+
+        Ok(info)
+    }
+}
@@ -0,0 +1,52 @@
+use fred::error::RedisError;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::{stream::Lifecycle, stream_lifecycle_transition};
+use crate::pb::scuffle::events::{stream_lifecycle_changed::State, StreamLifecycleChanged};
+
+impl From<Lifecycle> for State {
+    fn from(state: Lifecycle) -> Self {
+        match state {
+            Lifecycle::Requested => State::Requested,
+            Lifecycle::Ready => State::Ready,
+            Lifecycle::Live => State::Live,
+            Lifecycle::Reconnecting => State::Reconnecting,
+            Lifecycle::Ended => State::Ended,
+            Lifecycle::ProcessingVod => State::ProcessingVod,
+        }
+    }
+}
+
+impl From<State> for Lifecycle {
+    fn from(state: State) -> Self {
+        match state {
+            State::Requested => Lifecycle::Requested,
+            State::Ready => Lifecycle::Ready,
+            State::Live => Lifecycle::Live,
+            State::Reconnecting => Lifecycle::Reconnecting,
+            State::Ended => Lifecycle::Ended,
+            State::ProcessingVod => Lifecycle::ProcessingVod,
+        }
+    }
+}
+
+impl GlobalState {
+    /// Publishes a lifecycle transition to the channel, once the transaction which made it is committed.
+    pub async fn publish_lifecycle(
+        &self,
+        channel_id: Uuid,
+        transition: &stream_lifecycle_transition::Model,
+    ) -> Result<(), RedisError> {
+        self.publish_event(
+            channel_id,
+            &StreamLifecycleChanged {
+                stream_id: transition.stream_id.to_string(),
+                from: State::from(transition.from_state) as i32,
+                to: State::from(transition.to_state) as i32,
+                changed_at: transition.created_at.timestamp(),
+            },
+        )
+        .await
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use common::grpc::make_channel;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::api::deadline;
+use crate::database::stream;
+use crate::pb::scuffle::video::{ingest_client::IngestClient, ShutdownStreamRequest};
+
+impl GlobalState {
+    /// Asks the ingest of every live stream of a channel to end it.
+    /// Streams which can't be reached are logged and skipped, they end once their ingest stops reporting them.
+    pub async fn end_live_streams(&self, channel_id: Uuid) -> Result<()> {
+        let streams = sqlx::query_as!(
+            stream::Model,
+            "SELECT * FROM streams WHERE channel_id = $1 AND deleted = FALSE AND ended_at > NOW()",
+            channel_id,
+        )
+        .fetch_all(&*self.db)
+        .await?;
+
+        for stream in streams {
+            let result = async {
+                let channel = make_channel(
+                    vec![stream.ingest_address.clone()],
+                    Duration::from_secs(30),
+                    None,
+                )?;
+
+                tokio::time::timeout(
+                    deadline::timeout(Duration::from_secs(
+                        self.config.suspensions.shutdown_timeout as u64,
+                    )),
+                    IngestClient::new(channel).shutdown_stream(ShutdownStreamRequest {
+                        stream_id: stream.id.to_string(),
+                    }),
+                )
+                .await??;
+
+                anyhow::Ok(())
+            }
+            .await;
+
+            if let Err(e) = result {
+                tracing::warn!(stream_id = %stream.id, "failed to end stream: {:#}", e);
+            }
+        }
+
+        Ok(())
+    }
+}
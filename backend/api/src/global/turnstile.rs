@@ -3,6 +3,7 @@ use serde::de::Error;
 use serde_json::json;
 
 use super::GlobalState;
+use crate::api::deadline::{self, DOWNSTREAM_TIMEOUT};
 
 impl GlobalState {
     pub async fn validate_turnstile_token(&self, token: &str) -> Result<bool> {
@@ -17,6 +18,7 @@ impl GlobalState {
             .post(self.config.turnstile.url.as_str())
             .header("Content-Type", "application/json")
             .json(&body)
+            .timeout(deadline::timeout(DOWNSTREAM_TIMEOUT))
             .send()
             .await?;
 
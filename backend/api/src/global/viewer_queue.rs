@@ -0,0 +1,164 @@
+use anyhow::Result;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::{viewer_queue, viewer_queue_entry};
+use crate::pb;
+
+/// Why a viewer could not join a queue.
+#[derive(Debug)]
+pub enum JoinError {
+    /// The channel has no queue, or it is closed.
+    Closed,
+    /// The queue has as many viewers as it allows.
+    Full,
+    /// The viewer already waits in the queue.
+    AlreadyJoined,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for JoinError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+impl GlobalState {
+    /// Tells the viewers of a channel that its queue changed, and who was called from it.
+    /// The change is saved already, failing to publish it only logs.
+    pub async fn viewer_queue_changed(&self, channel_id: Uuid, called: &[Uuid]) {
+        let event = pb::scuffle::events::ViewerQueueChanged {
+            called_ids: called.iter().map(Uuid::to_string).collect(),
+        };
+
+        if let Err(e) = self.publish_event(channel_id, &event).await {
+            tracing::error!("failed to publish viewer queue of {}: {}", channel_id, e);
+        }
+    }
+
+    /// Adds a viewer to the end of the open queue of a channel.
+    pub async fn join_viewer_queue(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<viewer_queue_entry::Model, JoinError> {
+        // The queue is checked in the insert so a viewer can't join a queue which closed or filled up in the meantime.
+        let entry = sqlx::query_as!(
+            viewer_queue_entry::Model,
+            "INSERT INTO viewer_queue_entries (channel_id, user_id, position) SELECT q.channel_id, $2, COALESCE((SELECT MAX(position) FROM viewer_queue_entries WHERE channel_id = $1), 0) + 1 FROM viewer_queues q WHERE q.channel_id = $1 AND q.open AND (q.max_size IS NULL OR (SELECT COUNT(*) FROM viewer_queue_entries WHERE channel_id = $1) < q.max_size) ON CONFLICT DO NOTHING RETURNING *",
+            channel_id,
+            user_id,
+        )
+        .fetch_optional(&*self.db)
+        .await?;
+
+        if let Some(entry) = entry {
+            self.viewer_queue_changed(channel_id, &[]).await;
+            return Ok(entry);
+        }
+
+        let queue = sqlx::query_as!(
+            viewer_queue::Model,
+            "SELECT * FROM viewer_queues WHERE channel_id = $1",
+            channel_id,
+        )
+        .fetch_optional(&*self.db)
+        .await?;
+
+        if !queue.map_or(false, |q| q.open) {
+            return Err(JoinError::Closed);
+        }
+
+        match viewer_queue_entry::position(&*self.db, channel_id, user_id).await? {
+            Some(_) => Err(JoinError::AlreadyJoined),
+            None => Err(JoinError::Full),
+        }
+    }
+
+    /// Takes a viewer out of the queue of a channel. Returns false if they were not in it.
+    pub async fn leave_viewer_queue(&self, channel_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let left = sqlx::query!(
+            "DELETE FROM viewer_queue_entries WHERE channel_id = $1 AND user_id = $2",
+            channel_id,
+            user_id,
+        )
+        .execute(&*self.db)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if left {
+            self.viewer_queue_changed(channel_id, &[]).await;
+        }
+
+        Ok(left)
+    }
+
+    /// Joins or leaves the queue of the chat for the author of a chat message if it is a queue command.
+    /// Returns whether the queue changed, viewers who can't join are skipped without an answer.
+    pub async fn viewer_queue_by_command(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+        content: &str,
+    ) -> Result<bool> {
+        match viewer_queue::command(content) {
+            Some(viewer_queue::Command::Join) => {
+                match self.join_viewer_queue(channel_id, user_id).await {
+                    Ok(_) => Ok(true),
+                    Err(JoinError::Closed | JoinError::Full | JoinError::AlreadyJoined) => {
+                        Ok(false)
+                    }
+                    Err(JoinError::Database(e)) => Err(e.into()),
+                }
+            }
+            Some(viewer_queue::Command::Leave) => {
+                self.leave_viewer_queue(channel_id, user_id).await
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Calls the next viewers from the queue of a channel, first in line first. They are told with the change.
+    pub async fn pop_viewer_queue(
+        &self,
+        channel_id: Uuid,
+        count: i64,
+    ) -> Result<Vec<viewer_queue_entry::Model>> {
+        let mut entries = sqlx::query_as!(
+            viewer_queue_entry::Model,
+            "DELETE FROM viewer_queue_entries WHERE (channel_id, user_id) IN (SELECT channel_id, user_id FROM viewer_queue_entries WHERE channel_id = $1 ORDER BY position, user_id LIMIT $2 FOR UPDATE) RETURNING *",
+            channel_id,
+            count,
+        )
+        .fetch_all(&*self.db)
+        .await?;
+
+        // RETURNING does not keep the order of the subquery.
+        entries.sort_by_key(|entry| (entry.position, entry.user_id));
+
+        if !entries.is_empty() {
+            let called = entries
+                .iter()
+                .map(|entry| entry.user_id)
+                .collect::<Vec<_>>();
+            self.viewer_queue_changed(channel_id, &called).await;
+        }
+
+        Ok(entries)
+    }
+
+    /// Puts the viewers in the queue of a channel in a random order.
+    pub async fn shuffle_viewer_queue(&self, channel_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            "UPDATE viewer_queue_entries e SET position = s.position FROM (SELECT user_id, ROW_NUMBER() OVER (ORDER BY random()) AS position FROM viewer_queue_entries WHERE channel_id = $1) s WHERE e.channel_id = $1 AND e.user_id = s.user_id",
+            channel_id,
+        )
+        .execute(&*self.db)
+        .await?;
+
+        self.viewer_queue_changed(channel_id, &[]).await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use common::prelude::FutureTimeout;
+use lapin::{options::BasicPublishOptions, BasicProperties};
+use prost::Message;
+use uuid::Uuid;
+
+use super::GlobalState;
+use crate::database::{stream, vod_caption};
+use crate::pb;
+
+impl GlobalState {
+    /// Queues the transcription of a finished VOD for the worker, which uploads the captions and reports them on the result queue.
+    /// Returns None if the VOD already has captions, or is being transcribed. Failed transcriptions are queued again.
+    pub async fn queue_vod_captions(
+        &self,
+        stream: &stream::Model,
+    ) -> Result<Option<vod_caption::Model>> {
+        let id = Uuid::new_v4();
+
+        let Some(captions) = sqlx::query_as!(
+            vod_caption::Model,
+            "INSERT INTO vod_captions (id, stream_id, object_key, region) VALUES ($1, $2, $3, $4) ON CONFLICT (stream_id) DO UPDATE SET status = $5, error = '', updated_at = NOW(), completed_at = NULL WHERE vod_captions.status = $6 RETURNING *",
+            id,
+            stream.id,
+            vod_caption::object_key(stream.channel_id, stream.id, id),
+            stream.region,
+            i64::from(vod_caption::Status::Queued),
+            i64::from(vod_caption::Status::Failed),
+        )
+        .fetch_optional(&*self.db)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        if let Err(e) = self.publish_vod_caption_job(&captions).await {
+            sqlx::query!(
+                "UPDATE vod_captions SET status = $2, error = $3, updated_at = NOW(), completed_at = NOW() WHERE id = $1",
+                captions.id,
+                i64::from(vod_caption::Status::Failed),
+                "Failed to queue the captions",
+            )
+            .execute(&*self.db)
+            .await?;
+
+            return Err(e);
+        }
+
+        Ok(Some(captions))
+    }
+
+    async fn publish_vod_caption_job(&self, captions: &vod_caption::Model) -> Result<()> {
+        let channel = self
+            .rmq
+            .aquire()
+            .timeout(Duration::from_secs(1))
+            .await
+            .map_err(|_| anyhow::anyhow!("failed to aquire channel: timed out"))??;
+
+        channel
+            .basic_publish(
+                "",
+                &self.config.vod_captions.queue,
+                BasicPublishOptions::default(),
+                pb::scuffle::events::VodCaptionJob {
+                    id: captions.id.to_string(),
+                    stream_id: captions.stream_id.to_string(),
+                    object_key: captions.object_key.clone(),
+                    region: captions.region.clone(),
+                    backend_url: self.config.vod_captions.backend_url.clone(),
+                    model: self.config.vod_captions.model.clone(),
+                }
+                .encode_to_vec()
+                .as_slice(),
+                BasicProperties::default()
+                    .with_message_id(captions.id.to_string().into())
+                    .with_reply_to(self.config.vod_captions.result_queue.as_str().into())
+                    .with_content_type("application/octet-stream".into()),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
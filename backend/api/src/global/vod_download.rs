@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use common::prelude::FutureTimeout;
+use lapin::{options::BasicPublishOptions, BasicProperties};
+use prost::Message;
+
+use super::GlobalState;
+use crate::database::vod_download;
+use crate::pb;
+
+impl GlobalState {
+    /// Queues a VOD download for the worker, which reports its progress on the result queue.
+    pub async fn queue_vod_download(&self, download: &vod_download::Model) -> Result<()> {
+        let channel = self
+            .rmq
+            .aquire()
+            .timeout(Duration::from_secs(1))
+            .await
+            .map_err(|_| anyhow::anyhow!("failed to aquire channel: timed out"))??;
+
+        channel
+            .basic_publish(
+                "",
+                &self.config.vod_downloads.queue,
+                BasicPublishOptions::default(),
+                pb::scuffle::events::VodDownloadJob {
+                    id: download.id.to_string(),
+                    stream_id: download.stream_id.to_string(),
+                    variant: download.variant.clone(),
+                    remux: download.remux,
+                    object_key: download.object_key.clone(),
+                    region: download.region.clone(),
+                }
+                .encode_to_vec()
+                .as_slice(),
+                BasicProperties::default()
+                    .with_message_id(download.id.to_string().into())
+                    .with_reply_to(self.config.vod_downloads.result_queue.as_str().into())
+                    .with_content_type("application/octet-stream".into()),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use std::sync::{Arc, Weak};
+
+use chrono::Utc;
+use tonic::{async_trait, Request, Response, Status};
+
+use crate::database::{dead_letter, protobuf::ProtobufValue, stream, user};
+use crate::global::GlobalState;
+use crate::pb::scuffle::backend::{
+    admin_server, CreateUserRequest, CreateUserResponse, DeadLetterConsumer, EndLiveStreamsRequest,
+    EndLiveStreamsResponse, GrantRoleRequest, GrantRoleResponse, ListLiveStreamsRequest,
+    ListLiveStreamsResponse, LiveStream, ReconcileStreamSessionsRequest,
+    ReconcileStreamSessionsResponse, ResetStreamKeyRequest, ResetStreamKeyResponse,
+    RetryDeadLettersRequest, RetryDeadLettersResponse,
+};
+
+type Result<T> = std::result::Result<T, Status>;
+
+/// Logs an error with what failed, operators get the details from the logs of the API.
+fn internal<E: std::fmt::Display>(what: &'static str) -> impl FnOnce(E) -> Status {
+    move |e| {
+        tracing::error!("failed to {}: {}", what, e);
+        Status::internal(format!("failed to {}", what))
+    }
+}
+
+pub struct AdminServer {
+    global: Weak<GlobalState>,
+}
+
+impl AdminServer {
+    pub fn new(global: &Arc<GlobalState>) -> Self {
+        Self {
+            global: Arc::downgrade(global),
+        }
+    }
+
+    pub fn into_service(self) -> admin_server::AdminServer<Self> {
+        admin_server::AdminServer::new(self)
+    }
+
+    fn global(&self) -> Result<Arc<GlobalState>> {
+        self.global
+            .upgrade()
+            .ok_or_else(|| Status::internal("internal server error"))
+    }
+}
+
+async fn user_by_username(global: &GlobalState, username: &str) -> Result<user::Model> {
+    global
+        .user_by_username_loader
+        .load_one(username.to_lowercase())
+        .await
+        .map_err(internal("fetch user"))?
+        .ok_or_else(|| Status::not_found("user not found"))
+}
+
+#[async_trait]
+impl admin_server::Admin for AdminServer {
+    async fn create_user(
+        &self,
+        request: Request<CreateUserRequest>,
+    ) -> Result<Response<CreateUserResponse>> {
+        let global = self.global()?;
+        let request = request.into_inner();
+
+        let display_name = request.username.clone();
+        let username = request.username.to_lowercase();
+        let email = request.email.to_lowercase();
+
+        user::validate_username(&username).map_err(Status::invalid_argument)?;
+        user::validate_password(&request.password).map_err(Status::invalid_argument)?;
+        user::validate_email(&email).map_err(Status::invalid_argument)?;
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "INSERT INTO users (username, display_name, password_hash, email, email_hash, email_verified, stream_key) VALUES ($1, $2, $3, $4, $5, TRUE, $6) ON CONFLICT DO NOTHING RETURNING *",
+            username,
+            display_name,
+            user::hash_password(&request.password),
+            global.encrypt_pii(&email).map_err(internal("encrypt email"))?,
+            global.email_hash(&email),
+            user::generate_stream_key(),
+        )
+        .fetch_optional(&*global.db)
+        .await
+        .map_err(internal("create user"))?
+        .ok_or_else(|| Status::already_exists("username already taken"))?;
+
+        Ok(Response::new(CreateUserResponse {
+            user_id: user.id.to_string(),
+            stream_key: user.get_stream_key(),
+        }))
+    }
+
+    async fn grant_role(
+        &self,
+        request: Request<GrantRoleRequest>,
+    ) -> Result<Response<GrantRoleResponse>> {
+        let global = self.global()?;
+        let request = request.into_inner();
+
+        let user = user_by_username(&global, &request.username).await?;
+
+        let role_id =
+            sqlx::query_scalar!("SELECT id FROM global_roles WHERE name = $1", request.role)
+                .fetch_optional(&*global.db)
+                .await
+                .map_err(internal("fetch role"))?
+                .ok_or_else(|| Status::not_found("role not found"))?;
+
+        let granted = sqlx::query!(
+            "INSERT INTO global_role_grants (user_id, global_role_id) SELECT $1, $2 WHERE NOT EXISTS (SELECT 1 FROM global_role_grants WHERE user_id = $1 AND global_role_id = $2)",
+            user.id,
+            role_id,
+        )
+        .execute(&*global.db)
+        .await
+        .map_err(internal("grant role"))?
+        .rows_affected()
+            > 0;
+
+        Ok(Response::new(GrantRoleResponse { granted }))
+    }
+
+    async fn reset_stream_key(
+        &self,
+        request: Request<ResetStreamKeyRequest>,
+    ) -> Result<Response<ResetStreamKeyResponse>> {
+        let global = self.global()?;
+        let request = request.into_inner();
+
+        let user = user_by_username(&global, &request.username).await?;
+
+        let user = sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET stream_key = $2 WHERE id = $1 RETURNING *",
+            user.id,
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err(internal("reset stream key"))?;
+
+        Ok(Response::new(ResetStreamKeyResponse {
+            stream_key: user.get_stream_key(),
+        }))
+    }
+
+    async fn end_live_streams(
+        &self,
+        request: Request<EndLiveStreamsRequest>,
+    ) -> Result<Response<EndLiveStreamsResponse>> {
+        let global = self.global()?;
+        let request = request.into_inner();
+
+        let user = user_by_username(&global, &request.username).await?;
+
+        let streams = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM streams WHERE channel_id = $1 AND deleted = FALSE AND ended_at > NOW()",
+            user.id,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .map_err(internal("count live streams"))?
+        .unwrap_or(0);
+
+        global
+            .end_live_streams(user.id)
+            .await
+            .map_err(internal("end live streams"))?;
+
+        Ok(Response::new(EndLiveStreamsResponse {
+            streams: streams as u32,
+        }))
+    }
+
+    async fn list_live_streams(
+        &self,
+        request: Request<ListLiveStreamsRequest>,
+    ) -> Result<Response<ListLiveStreamsResponse>> {
+        let global = self.global()?;
+        let request = request.into_inner();
+
+        let channel_id = match &request.username {
+            Some(username) => Some(user_by_username(&global, username).await?.id),
+            None => None,
+        };
+
+        let streams = sqlx::query_as!(
+            stream::Model,
+            "SELECT * FROM streams WHERE ($1::uuid IS NULL OR channel_id = $1) AND deleted = FALSE AND ended_at > NOW() ORDER BY created_at",
+            channel_id,
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err(internal("fetch live streams"))?;
+
+        let channels = global
+            .user_by_id_loader
+            .load_many(streams.iter().map(|s| s.channel_id))
+            .await
+            .map_err(internal("fetch channels"))?;
+
+        let streams = streams
+            .into_iter()
+            .map(|s| LiveStream {
+                stream_id: s.id.to_string(),
+                channel_id: s.channel_id.to_string(),
+                username: channels
+                    .get(&s.channel_id)
+                    .map(|c| c.username.clone())
+                    .unwrap_or_default(),
+                title: s.title,
+                ready_state: format!("{:?}", s.ready_state),
+                ingest_address: s.ingest_address,
+                connection_id: s.connection_id.to_string(),
+                transcoded: s.transcoded,
+                recorded: s.recorded,
+                created_at: s.created_at.timestamp(),
+                updated_at: s.updated_at.map(|t| t.timestamp()),
+                state: match s.state {
+                    ProtobufValue::Some(state) => Some(state),
+                    _ => None,
+                },
+            })
+            .collect();
+
+        Ok(Response::new(ListLiveStreamsResponse { streams }))
+    }
+
+    async fn retry_dead_letters(
+        &self,
+        request: Request<RetryDeadLettersRequest>,
+    ) -> Result<Response<RetryDeadLettersResponse>> {
+        let global = self.global()?;
+        let request = request.into_inner();
+
+        let consumer = request
+            .consumer
+            .map(|c| {
+                DeadLetterConsumer::from_i32(c)
+                    .map(|c| dead_letter::Consumer::from(c as i64))
+                    .ok_or_else(|| Status::invalid_argument("invalid consumer"))
+            })
+            .transpose()?;
+
+        let ids = sqlx::query_scalar!(
+            "SELECT id FROM dead_letters WHERE retried_at IS NULL AND discarded_at IS NULL AND ($1::bigint IS NULL OR consumer = $1) ORDER BY failed_at",
+            consumer.map(i64::from),
+        )
+        .fetch_all(&*global.db)
+        .await
+        .map_err(internal("fetch dead letters"))?;
+
+        let mut response = RetryDeadLettersResponse::default();
+
+        for id in ids {
+            // Claiming the dead letter keeps an admin retrying it at the same time from publishing it twice.
+            let Some(letter) = sqlx::query_as!(
+                dead_letter::Model,
+                "UPDATE dead_letters SET retried_at = NOW() WHERE id = $1 AND retried_at IS NULL AND discarded_at IS NULL RETURNING *",
+                id
+            )
+            .fetch_optional(&*global.db)
+            .await
+            .map_err(internal("update dead letter"))?
+            else {
+                continue;
+            };
+
+            if let Err(e) = global.retry_dead_letter(&letter).await {
+                tracing::error!(%id, "failed to publish dead letter: {:#}", e);
+                response.failed += 1;
+
+                sqlx::query!(
+                    "UPDATE dead_letters SET retried_at = NULL WHERE id = $1",
+                    id
+                )
+                .execute(&*global.db)
+                .await
+                .map_err(internal("update dead letter"))?;
+            } else {
+                response.retried += 1;
+            }
+        }
+
+        Ok(Response::new(response))
+    }
+
+    async fn reconcile_stream_sessions(
+        &self,
+        request: Request<ReconcileStreamSessionsRequest>,
+    ) -> Result<Response<ReconcileStreamSessionsResponse>> {
+        let global = self.global()?;
+        let request = request.into_inner();
+
+        let lookback_days = request
+            .lookback_days
+            .unwrap_or(global.config.reconciliation.lookback_days);
+        let since = Utc::now() - chrono::Duration::days(lookback_days as i64);
+
+        let result = global
+            .reconcile_stream_sessions(since)
+            .await
+            .map_err(internal("reconcile stream sessions"))?;
+
+        tracing::info!(
+            backfilled = result.backfilled_sessions,
+            corrected = result.corrected_sessions,
+            "reconciled stream sessions on request"
+        );
+
+        Ok(Response::new(ReconcileStreamSessionsResponse {
+            backfilled_sessions: result.backfilled_sessions,
+            corrected_sessions: result.corrected_sessions,
+        }))
+    }
+}
@@ -2,15 +2,17 @@ use crate::global::GlobalState;
 use std::sync::{Arc, Weak};
 
 use crate::database::{
-    global_role,
-    stream::{self, ReadyState},
-    stream_event,
+    channel_event, channel_schedule_segment, global_role,
+    stream::{self, Lifecycle, ReadyState},
+    stream_chapter, stream_event, stream_lifecycle_transition, user_suspension,
 };
 use chrono::{Duration, TimeZone, Utc};
 use prost::Message;
 use tonic::{async_trait, Request, Response, Status};
 use uuid::Uuid;
 
+use crate::pb::scuffle::events::channel_notification as notification;
+
 use crate::pb::scuffle::backend::{
     api_server,
     update_live_stream_request::{event::Level, update::Update},
@@ -77,7 +79,13 @@ impl api_server::Api for ApiServer {
             .map_err(|_| Status::internal("failed to query database"))?
             .ok_or_else(|| Status::invalid_argument("invalid stream key"))?;
 
-        if channel.stream_key != stream_key {
+        // Besides the default key a channel can have named keys, and a key regenerated a moment ago still works
+        // so an encoder reconnecting during the rotation is not cut off.
+        if channel.stream_key != stream_key
+            && !crate::database::stream_key::authenticate(&global.db, channel_id, &stream_key)
+                .await
+                .map_err(|_| Status::internal("failed to query database"))?
+        {
             return Err(Status::invalid_argument(
                 "invalid stream key: incorrect stream key",
             ));
@@ -107,6 +115,14 @@ impl api_server::Api for ApiServer {
             ));
         }
 
+        if user_suspension::active(&global.db, channel_id)
+            .await
+            .map_err(|_| Status::internal("failed to query database"))?
+            .is_some()
+        {
+            return Err(Status::permission_denied("user is suspended"));
+        }
+
         // We need to create a new stream ID for this stream
         let mut tx = global.db.begin().await.map_err(|e| {
             tracing::error!("failed to begin transaction: {}", e);
@@ -122,17 +138,57 @@ impl api_server::Api for ApiServer {
             .has_permission(global_role::Permission::StreamTranscoding)
             && channel.stream_transcoding_enabled;
 
+        // Going live while a stream is planned fills in the title and category of the planned stream.
+        let planned =
+            match channel_schedule_segment::covering(&mut *tx, channel_id, Utc::now()).await {
+                Ok(planned) => planned,
+                Err(e) => {
+                    tracing::error!("failed to fetch schedule: {}", e);
+                    return Err(Status::internal("internal server error"));
+                }
+            };
+
+        let mut title = channel.stream_title.clone();
+        let mut category = channel.stream_category.clone();
+        if let Some(planned) = planned {
+            if !planned.title.is_empty() {
+                title = planned.title;
+            }
+
+            if !planned.category.is_empty() {
+                category = planned.category;
+            }
+
+            if let Err(e) = sqlx::query!(
+                "UPDATE users SET stream_title = $2, stream_category = $3 WHERE id = $1",
+                channel_id,
+                title,
+                category,
+            )
+            .execute(&mut *tx)
+            .await
+            {
+                tracing::error!("failed to update stream info: {}", e);
+                return Err(Status::internal("internal server error"));
+            }
+        }
+
+        // The recording stays in the region the channel keeps its data in, even if the channel moves later.
+        let region = global.data_region(&channel.data_region);
+
         let stream = match sqlx::query_as!(
             stream::Model,
-            "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id, ended_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+            "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id, ended_at, chat_archived, region) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *",
             channel_id,
-            channel.stream_title,
+            title,
             channel.stream_description,
             record,
             transcode,
             request.ingest_address,
             request.connection_id.parse::<Uuid>().map_err(|_| Status::invalid_argument("invalid connection ID: must be a valid UUID"))?,
             Utc::now() + chrono::Duration::seconds(300),
+            channel.chat_archive,
+            region,
         ).fetch_one(&mut *tx).await {
             Ok(stream) => stream,
             Err(e) => {
@@ -141,6 +197,11 @@ impl api_server::Api for ApiServer {
             }
         };
 
+        if let Err(e) = stream_chapter::record(&mut *tx, stream.id, 0, &title, &category).await {
+            tracing::error!("failed to insert chapter: {}", e);
+            return Err(Status::internal("internal server error"));
+        }
+
         if let Err(e) = tx.commit().await {
             tracing::error!("failed to commit transaction: {}", e);
             return Err(Status::internal("internal server error"));
@@ -151,6 +212,7 @@ impl api_server::Api for ApiServer {
             record,
             transcode,
             state: None,
+            region: stream.region,
         }))
     }
 
@@ -190,11 +252,8 @@ impl api_server::Api for ApiServer {
             return Err(Status::invalid_argument("invalid connection ID"));
         }
 
-        if stream.ended_at < Utc::now()
-            || stream.ready_state == ReadyState::Stopped
-            || stream.ready_state == ReadyState::Failed
-            || stream.ready_state == ReadyState::StoppedResumable
-        {
+        let lifecycle = stream.current_lifecycle(Utc::now());
+        if lifecycle.is_over() || lifecycle == Lifecycle::Reconnecting {
             return Err(Status::invalid_argument("stream has ended"));
         }
 
@@ -203,6 +262,9 @@ impl api_server::Api for ApiServer {
             Status::internal("internal server error")
         })?;
 
+        let mut notifications = Vec::new();
+        let mut transitions = Vec::new();
+
         for u in request.updates {
             let Some(update) = u.update else {
                 continue;
@@ -253,12 +315,22 @@ impl api_server::Api for ApiServer {
                                 Utc.timestamp_opt(u.timestamp as i64, 0).unwrap(),
                                 Utc.timestamp_opt(u.timestamp as i64, 0).unwrap() + chrono::Duration::seconds(300),
                             )
-                            .execute(&*global.db)
+                            .execute(&mut *tx)
                             .await
                             .map_err(|e| {
                                 tracing::error!("failed to update stream state: {}", e);
                                 Status::internal("internal server error")
                             })?;
+
+                            if state == StreamReadyState::Ready
+                                && stream.ready_state == ReadyState::NotReady
+                            {
+                                notifications.push(notification::Kind::GoLive);
+                            }
+
+                            if state == StreamReadyState::Ready {
+                                transitions.push(Lifecycle::Live);
+                            }
                         }
                         StreamReadyState::StoppedResumable => {
                             sqlx::query!(
@@ -267,10 +339,12 @@ impl api_server::Api for ApiServer {
                                 ReadyState::StoppedResumable as i64,
                                 Utc.timestamp_opt(u.timestamp as i64, 0).unwrap(),
                                 Utc.timestamp_opt(u.timestamp as i64, 0).unwrap() + Duration::seconds(300),
-                            ).execute(&*global.db).await.map_err(|e| {
+                            ).execute(&mut *tx).await.map_err(|e| {
                                 tracing::error!("failed to update stream state: {}", e);
                                 Status::internal("internal server error")
                             })?;
+
+                            transitions.push(Lifecycle::Reconnecting);
                         }
                         StreamReadyState::Stopped | StreamReadyState::Failed => {
                             sqlx::query!(
@@ -283,12 +357,32 @@ impl api_server::Api for ApiServer {
                                 },
                                 Utc.timestamp_opt(u.timestamp as i64, 0).unwrap()
                             )
-                            .execute(&*global.db)
+                            .execute(&mut *tx)
                             .await
                             .map_err(|e| {
                                 tracing::error!("failed to update stream state: {}", e);
                                 Status::internal("internal server error")
                             })?;
+
+                            // Keep a summary of the stream around so the streamer can review it later, even if it was not recorded.
+                            sqlx::query!(
+                                "INSERT INTO stream_sessions (channel_id, stream_id, unique_chatters, new_follows, started_at, ended_at) SELECT s.channel_id, s.id, (SELECT COUNT(DISTINCT m.author_id) FROM chat_messages m WHERE m.channel_id = s.channel_id AND m.created_at BETWEEN s.created_at AND s.ended_at), (SELECT COUNT(*) FROM channel_events e WHERE e.channel_id = s.channel_id AND e.kind = $2 AND e.created_at BETWEEN s.created_at AND s.ended_at), s.created_at, s.ended_at FROM streams s WHERE s.id = $1 ON CONFLICT (stream_id) DO NOTHING",
+                                stream_id,
+                                channel_event::Kind::Follow as i64,
+                            )
+                            .execute(&mut *tx)
+                            .await
+                            .map_err(|e| {
+                                tracing::error!("failed to insert stream session: {}", e);
+                                Status::internal("internal server error")
+                            })?;
+
+                            notifications.push(notification::Kind::Offline);
+                            transitions.push(Lifecycle::Ended);
+                            if stream.recorded {
+                                notifications.push(notification::Kind::VodPublished);
+                                transitions.push(Lifecycle::ProcessingVod);
+                            }
                         }
                     }
                 }
@@ -326,6 +420,23 @@ impl api_server::Api for ApiServer {
                         tracing::error!("failed to insert stream bitrate update: {}", e);
                         Status::internal("internal server error")
                     })?;
+
+                    if !v.variants.is_empty() {
+                        transitions.push(Lifecycle::Ready);
+                    }
+                }
+            }
+        }
+
+        // Moves the stream can't make from where it is are skipped, like a repeated ready state.
+        let mut transitioned = Vec::new();
+        for to in transitions {
+            match stream_lifecycle_transition::transition(&mut *tx, stream_id, to).await {
+                Ok(Some(transition)) => transitioned.push(transition),
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::error!("failed to update stream lifecycle: {}", e);
+                    return Err(Status::internal("internal server error"));
                 }
             }
         }
@@ -335,6 +446,54 @@ impl api_server::Api for ApiServer {
             return Err(Status::internal("internal server error"));
         }
 
+        for transition in &transitioned {
+            if let Err(e) = global
+                .publish_lifecycle(stream.channel_id, transition)
+                .await
+            {
+                tracing::error!("failed to publish stream lifecycle: {}", e);
+            }
+        }
+
+        // The recording is transcribed once it is published. A failure only logs, the broadcaster can queue it again.
+        if global.config.vod_captions.enabled
+            && transitioned
+                .iter()
+                .any(|t| t.to_state == Lifecycle::ProcessingVod)
+        {
+            if let Err(e) = global.queue_vod_captions(&stream).await {
+                tracing::error!("failed to queue vod captions: {}", e);
+            }
+        }
+
+        for kind in notifications {
+            // Streams resumed or restarted within a few minutes continue the previous one, so they are not announced again.
+            if kind == notification::Kind::GoLive {
+                let resumed = sqlx::query_scalar!(
+                    "SELECT EXISTS(SELECT 1 FROM streams WHERE channel_id = $1 AND id != $2 AND ready_state = $3 AND ended_at > NOW() - INTERVAL '5 minutes')",
+                    stream.channel_id,
+                    stream.id,
+                    ReadyState::Stopped as i64,
+                )
+                .fetch_one(&*global.db)
+                .await
+                .map_err(|e| {
+                    tracing::error!("failed to query database: {}", e);
+                    Status::internal("internal server error")
+                })?
+                .unwrap_or(false);
+
+                if resumed {
+                    continue;
+                }
+            }
+
+            // The stream update is already stored, a lost notification must not fail it.
+            if let Err(e) = global.notify(stream.channel_id, kind, stream.id).await {
+                tracing::error!("failed to queue channel notification: {}", e);
+            }
+        }
+
         Ok(Response::new(UpdateLiveStreamResponse {}))
     }
 
@@ -366,10 +525,7 @@ impl api_server::Api for ApiServer {
             return Err(Status::not_found("stream not found"));
         };
 
-        if old_stream.ended_at < Utc::now()
-            || old_stream.ready_state == ReadyState::Stopped
-            || old_stream.ready_state == ReadyState::Failed
-        {
+        if old_stream.current_lifecycle(Utc::now()).is_over() {
             return Err(Status::failed_precondition("stream has already ended"));
         }
 
@@ -382,7 +538,7 @@ impl api_server::Api for ApiServer {
 
         // Insert the new stream
         sqlx::query!(
-            "INSERT INTO streams (id, channel_id, title, description, ready_state, ingest_address, connection_id) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            "INSERT INTO streams (id, channel_id, title, description, ready_state, ingest_address, connection_id, chat_archived, region) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
             stream_id,
             old_stream.channel_id,
             old_stream.title,
@@ -390,6 +546,8 @@ impl api_server::Api for ApiServer {
             ReadyState::NotReady as i64,
             old_stream.ingest_address,
             old_stream.connection_id,
+            old_stream.chat_archived,
+            old_stream.region,
         ).execute(&mut *tx).await.map_err(|e| {
             tracing::error!("failed to insert stream: {}", e);
             Status::internal("internal server error")
@@ -408,10 +566,12 @@ impl api_server::Api for ApiServer {
             Status::internal("internal server error")
         })?;
 
+        let state = request.state.unwrap_or_default();
+
         sqlx::query!(
             "UPDATE streams SET updated_at = NOW(), state = $2 WHERE id = $1",
             stream_id,
-            request.state.unwrap_or_default().encode_to_vec(),
+            state.encode_to_vec(),
         )
         .execute(&mut *tx)
         .await
@@ -420,11 +580,38 @@ impl api_server::Api for ApiServer {
             Status::internal("internal server error")
         })?;
 
+        // The old stream ends here, the new one continues it and is ready once its variants are known.
+        let mut transitions = vec![(old_stream_id, Lifecycle::Ended)];
+        if !state.variants.is_empty() {
+            transitions.push((stream_id, Lifecycle::Ready));
+        }
+
+        let mut transitioned = Vec::new();
+        for (id, to) in transitions {
+            match stream_lifecycle_transition::transition(&mut *tx, id, to).await {
+                Ok(Some(transition)) => transitioned.push(transition),
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::error!("failed to update stream lifecycle: {}", e);
+                    return Err(Status::internal("internal server error"));
+                }
+            }
+        }
+
         if let Err(e) = tx.commit().await {
             tracing::error!("failed to commit transaction: {}", e);
             return Err(Status::internal("internal server error"));
         }
 
+        for transition in &transitioned {
+            if let Err(e) = global
+                .publish_lifecycle(old_stream.channel_id, transition)
+                .await
+            {
+                tracing::error!("failed to publish stream lifecycle: {}", e);
+            }
+        }
+
         Ok(Response::new(NewLiveStreamResponse {
             stream_id: stream_id.to_string(),
         }))
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use tokio::select;
 use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 
+pub mod admin;
 pub mod api;
 pub mod health;
 
@@ -26,6 +27,13 @@ pub async fn run(global: Arc<GlobalState>) -> Result<()> {
     }
     .add_service(api::ApiServer::new(&global).into_service())
     .add_service(health::HealthServer::new(&global).into_service())
+    .add_optional_service(
+        global
+            .config
+            .grpc
+            .admin
+            .then(|| admin::AdminServer::new(&global).into_service()),
+    )
     .serve_with_shutdown(global.config.grpc.bind_address, async {
         global.ctx.done().await;
     });
@@ -0,0 +1,43 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use tokio::select;
+
+use crate::global::GlobalState;
+
+/// Periodically posts the chat timers which are due, a batch of chats at a time.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    let config = &global.config.chat;
+    if config.timer_check_interval == 0 {
+        return Ok(());
+    }
+
+    let mut interval =
+        tokio::time::interval(Duration::from_secs(config.timer_check_interval as u64));
+
+    loop {
+        select! {
+            _ = interval.tick() => {}
+            _ = global.ctx.done() => break,
+        }
+
+        let timers = match global
+            .due_chat_timers(config.timer_batch_size.max(1) as i64)
+            .await
+        {
+            Ok(timers) => timers,
+            Err(e) => {
+                tracing::error!("failed to fetch due chat timers: {:#}", e);
+                continue;
+            }
+        };
+
+        for timer in &timers {
+            if let Err(e) = global.post_chat_timer(timer).await {
+                tracing::warn!(timer_id = %timer.id, channel_id = %timer.channel_id, "failed to post chat timer: {:#}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
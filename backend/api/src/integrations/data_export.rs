@@ -0,0 +1,241 @@
+use std::{pin::pin, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use futures_util::StreamExt;
+use lapin::{
+    message::Delivery,
+    options::{BasicAckOptions, BasicConsumeOptions, QueueDeclareOptions},
+    types::FieldTable,
+};
+use prost::Message;
+use serde_json::json;
+use tokio::select;
+use uuid::Uuid;
+
+use crate::{
+    database::{
+        channel_event,
+        data_export::{self, Status},
+        dead_letter::Consumer,
+        session, user,
+    },
+    global::{data_export::tar_archive, GlobalState},
+    pb::scuffle::events::DataExportJob,
+};
+
+/// Consumes the data exports queued by [`GlobalState::queue_data_export`].
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    global
+        .rmq
+        .aquire()
+        .await?
+        .queue_declare(
+            &global.config.data_exports.queue,
+            QueueDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    let mut consumer = pin!(global.rmq.basic_consume(
+        &global.config.data_exports.queue,
+        &global.config.name,
+        BasicConsumeOptions::default(),
+        FieldTable::default()
+    ));
+
+    loop {
+        select! {
+            m = consumer.next() => {
+                let Some(m) = m else {
+                    return Err(anyhow!("rmq stream closed"));
+                };
+
+                tokio::spawn(handle_message(global.clone(), m?));
+            }
+            _ = global.ctx.done() => return Ok(()),
+        }
+    }
+}
+
+async fn handle_message(global: Arc<GlobalState>, delivery: Delivery) {
+    let result = async {
+        let job = DataExportJob::decode(delivery.data.as_slice())?;
+        run_export(&global, job.id.parse()?).await
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::error!("failed to run data export: {:#}", e);
+        global
+            .dead_letter(Consumer::DataExport, &delivery, &e)
+            .await;
+    }
+
+    if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+        tracing::error!("failed to ack data export: {}", e);
+    }
+}
+
+async fn run_export(global: &Arc<GlobalState>, export_id: Uuid) -> Result<()> {
+    // Claiming the export makes redelivered jobs a no-op.
+    let Some(export) = sqlx::query_as!(
+        data_export::Model,
+        "UPDATE data_exports SET status = $2, updated_at = NOW() WHERE id = $1 AND status = $3 RETURNING *",
+        export_id,
+        i64::from(Status::Running),
+        i64::from(Status::Queued),
+    )
+    .fetch_optional(&*global.db)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let object_key = format!("exports/{}/{}.tar", export.user_id, export.id);
+
+    let result = async {
+        let user = sqlx::query_as!(
+            user::Model,
+            "SELECT * FROM users WHERE id = $1",
+            export.user_id
+        )
+        .fetch_optional(&*global.db)
+        .await?
+        .ok_or_else(|| anyhow!("user not found: {}", export.user_id))?;
+
+        // The archive is uploaded to the region the user keeps their data in, the download is signed for the same one.
+        let region = global.data_region(&user.data_region).to_string();
+        let archive = collect(global, &user).await?;
+        let size = archive.len() as i64;
+        global
+            .put_object(&region, &object_key, "application/x-tar", archive)
+            .await?;
+
+        Ok::<_, anyhow::Error>((size, region))
+    }
+    .await;
+
+    // The error is only logged, it could name the object storage and the user can't do anything about it.
+    let (status, object_key, size_bytes, error, expires_at, region) = match &result {
+        Ok((size, region)) => (
+            Status::Completed,
+            object_key.as_str(),
+            *size,
+            "",
+            Some(Utc::now() + Duration::days(global.config.data_exports.retention_days as i64)),
+            region.as_str(),
+        ),
+        Err(_) => (
+            Status::Failed,
+            "",
+            0,
+            "Failed to assemble the export",
+            None,
+            "",
+        ),
+    };
+
+    sqlx::query!(
+        "UPDATE data_exports SET status = $2, object_key = $3, size_bytes = $4, error = $5, expires_at = $6, region = $7, updated_at = NOW(), completed_at = NOW() WHERE id = $1",
+        export.id,
+        i64::from(status),
+        object_key,
+        size_bytes,
+        error,
+        expires_at,
+        region,
+    )
+    .execute(&*global.db)
+    .await?;
+
+    // The failure is kept on the export, the message itself was handled and is not dead-lettered.
+    if let Err(e) = result {
+        tracing::warn!(export_id = %export.id, "data export failed: {:#}", e);
+    }
+
+    Ok(())
+}
+
+fn to_json(value: serde_json::Value) -> Result<Vec<u8>> {
+    Ok(serde_json::to_vec_pretty(&value)?)
+}
+
+/// Collects the profile, follows, chat messages and sessions of a user into an archive with one JSON file each.
+async fn collect(global: &Arc<GlobalState>, user: &user::Model) -> Result<Vec<u8>> {
+    let user_id = user.id;
+
+    let profile = json!({
+        "id": user.id,
+        "username": user.username,
+        "display_name": user.display_name,
+        "email": global.decrypt_pii(&user.email)?,
+        "email_verified": user.email_verified,
+        "bio": user.bio,
+        "profile_image_url": user.profile_image_url,
+        "stream_title": user.stream_title,
+        "stream_description": user.stream_description,
+        "stream_category": user.stream_category,
+        "stream_language": user.stream_language,
+        "locale": user.locale,
+        "timezone": user.timezone,
+        "data_region": user.data_region,
+        "created_at": user.created_at,
+        "last_login_at": user.last_login_at,
+    });
+
+    let follows = sqlx::query!(
+        "SELECT c.username AS channel, e.created_at FROM channel_events e JOIN users c ON c.id = e.channel_id WHERE e.user_id = $1 AND e.kind = $2 ORDER BY e.created_at",
+        user_id,
+        i64::from(channel_event::Kind::Follow),
+    )
+    .fetch_all(&*global.db)
+    .await?
+    .into_iter()
+    .map(|f| json!({ "channel": f.channel, "followed_at": f.created_at }))
+    .collect::<Vec<_>>();
+
+    let chat_messages = sqlx::query!(
+        "SELECT c.username AS channel, m.content, m.created_at FROM chat_messages m JOIN users c ON c.id = m.channel_id WHERE m.author_id = $1 ORDER BY m.created_at",
+        user_id,
+    )
+    .fetch_all(&*global.db)
+    .await?
+    .into_iter()
+    .map(|m| json!({ "channel": m.channel, "content": m.content, "sent_at": m.created_at }))
+    .collect::<Vec<_>>();
+
+    let sessions = sqlx::query_as!(
+        session::Model,
+        "SELECT * FROM sessions WHERE user_id = $1 ORDER BY created_at",
+        user_id,
+    )
+    .fetch_all(&*global.db)
+    .await?
+    .into_iter()
+    .map(|s| {
+        json!({
+            "id": s.id,
+            "ip_address": s.ip_address,
+            "user_agent": s.user_agent,
+            "created_at": s.created_at,
+            "last_used_at": s.last_used_at,
+            "expires_at": s.expires_at,
+            "revoked_at": s.invalidated_at,
+        })
+    })
+    .collect::<Vec<_>>();
+
+    Ok(tar_archive(
+        &[
+            ("profile.json", to_json(profile)?),
+            ("follows.json", to_json(follows.into())?),
+            ("chat_messages.json", to_json(chat_messages.into())?),
+            ("sessions.json", to_json(sessions.into())?),
+        ],
+        Utc::now().timestamp(),
+    ))
+}
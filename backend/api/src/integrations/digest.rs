@@ -0,0 +1,52 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use tokio::select;
+
+use crate::global::GlobalState;
+
+/// Periodically sends the weekly digests which are due, a batch at a time.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    let config = &global.config.digests;
+    if config.interval == 0 {
+        return Ok(());
+    }
+
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval as u64));
+
+    loop {
+        select! {
+            _ = interval.tick() => {}
+            _ = global.ctx.done() => break,
+        }
+
+        let recipients = match global
+            .due_digest_recipients(config.batch_size.max(1) as i64)
+            .await
+        {
+            Ok(recipients) => recipients,
+            Err(e) => {
+                tracing::error!("failed to fetch due digests: {}", e);
+                continue;
+            }
+        };
+
+        let mut sent = 0;
+        for recipient in &recipients {
+            // A failed digest is not marked as sent, so it is tried again with the next batch.
+            match global.send_digest(recipient).await {
+                Ok(true) => sent += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::warn!(user_id = %recipient.user_id, "failed to send digest: {:#}", e)
+                }
+            }
+        }
+
+        if !recipients.is_empty() {
+            tracing::info!(due = recipients.len(), sent, "sent weekly digests");
+        }
+    }
+
+    Ok(())
+}
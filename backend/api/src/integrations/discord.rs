@@ -0,0 +1,180 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    database::{discord_announcement, discord_integration, user},
+    global::GlobalState,
+};
+
+/// Replaces the `{variable}` placeholders of a template. Unknown placeholders are left as they are.
+/// Values are inserted as is and never expanded again, so a stream title cannot pull in other variables.
+pub fn render_template(template: &str, variables: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find('}').and_then(|end| {
+            variables
+                .iter()
+                .find(|(name, _)| *name == &rest[1..end])
+                .map(|(_, value)| (end, value))
+        });
+
+        match value {
+            Some((end, value)) => {
+                rendered.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Posts the announcement for a notification, if the channel set up Discord and has the announcement enabled.
+pub async fn announce(
+    global: &Arc<GlobalState>,
+    channel_id: Uuid,
+    kind: discord_announcement::Kind,
+    stream_id: Uuid,
+) -> Result<()> {
+    let Some(integration) = sqlx::query_as!(
+        discord_integration::Model,
+        "SELECT * FROM discord_integrations WHERE channel_id = $1",
+        channel_id
+    )
+    .fetch_optional(&*global.db)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let announcement = discord_announcement::for_channel(&global.db, channel_id)
+        .await?
+        .into_iter()
+        .find(|a| a.kind == kind)
+        .unwrap_or_else(|| discord_announcement::Model::default_for(channel_id, kind));
+
+    if !announcement.enabled {
+        return Ok(());
+    }
+
+    send_announcement(global, &integration, &announcement, Some(stream_id)).await
+}
+
+/// Renders and posts an announcement. The outcome is stored on the integration, so the streamer can see why posting failed.
+/// Without a stream the current stream info of the channel is used, which is what test announcements show.
+pub async fn send_announcement(
+    global: &Arc<GlobalState>,
+    integration: &discord_integration::Model,
+    announcement: &discord_announcement::Model,
+    stream_id: Option<Uuid>,
+) -> Result<()> {
+    let channel = sqlx::query_as!(
+        user::Model,
+        "SELECT * FROM users WHERE id = $1",
+        integration.channel_id
+    )
+    .fetch_one(&*global.db)
+    .await?;
+
+    let title = match stream_id {
+        Some(stream_id) => {
+            sqlx::query_scalar!("SELECT title FROM streams WHERE id = $1", stream_id)
+                .fetch_optional(&*global.db)
+                .await?
+        }
+        None => None,
+    }
+    .unwrap_or_else(|| channel.stream_title.clone());
+
+    let url = format!(
+        "{}/{}",
+        global.config.discord.website_url.trim_end_matches('/'),
+        channel.username
+    );
+
+    let content = render_template(
+        &announcement.template,
+        &[
+            ("channel", &channel.display_name),
+            ("username", &channel.username),
+            ("title", &title),
+            ("category", &channel.stream_category),
+            ("url", &url),
+        ],
+    );
+
+    let result = post(global, integration, &content).await;
+
+    sqlx::query!(
+        "UPDATE discord_integrations SET last_error = $2 WHERE channel_id = $1",
+        integration.channel_id,
+        result
+            .as_ref()
+            .err()
+            .map(|e| format!("{:#}", e))
+            .unwrap_or_default(),
+    )
+    .execute(&*global.db)
+    .await?;
+
+    result
+}
+
+async fn post(
+    global: &Arc<GlobalState>,
+    integration: &discord_integration::Model,
+    content: &str,
+) -> Result<()> {
+    let client = reqwest::Client::new();
+
+    let request = match (
+        &integration.webhook_url_encrypted,
+        &integration.bot_token_encrypted,
+        &integration.bot_channel_id,
+    ) {
+        (Some(webhook_url), _, _) => client.post(global.decrypt_secret(webhook_url)?),
+        (None, Some(bot_token), Some(bot_channel_id)) => client
+            .post(format!(
+                "{}/channels/{}/messages",
+                global.config.discord.api_url, bot_channel_id
+            ))
+            .header(
+                "Authorization",
+                format!("Bot {}", global.decrypt_secret(bot_token)?),
+            ),
+        _ => bail!("no webhook or bot is set up"),
+    };
+
+    // Announcements never ping anyone, even if the template or stream title contains @everyone.
+    let body = json!({
+        "content": content,
+        "allowed_mentions": { "parse": [] },
+    });
+
+    // The webhook url contains its token, so it is stripped from errors before they are stored.
+    let res = request
+        .json(&body)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| anyhow!("failed to reach Discord: {}", e.without_url()))?;
+
+    if !res.status().is_success() {
+        bail!("Discord rejected the announcement: {}", res.status());
+    }
+
+    Ok(())
+}
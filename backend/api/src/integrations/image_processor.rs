@@ -0,0 +1,160 @@
+use std::{pin::pin, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use futures_util::StreamExt;
+use lapin::{
+    message::Delivery,
+    options::{BasicAckOptions, BasicConsumeOptions, QueueDeclareOptions},
+    types::FieldTable,
+};
+use prost::Message;
+use tokio::select;
+use uuid::Uuid;
+
+use crate::{
+    database::{channel_appearance, dead_letter::Consumer, user},
+    global::GlobalState,
+    pb,
+};
+
+/// Consumes the results the image processor reports for the jobs it was sent.
+/// Results of jobs nothing waits for, like emotes, are ignored.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    global
+        .rmq
+        .aquire()
+        .await?
+        .queue_declare(
+            &global.config.image_processor.result_queue,
+            QueueDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    let mut consumer = pin!(global.rmq.basic_consume(
+        &global.config.image_processor.result_queue,
+        &global.config.name,
+        BasicConsumeOptions::default(),
+        FieldTable::default()
+    ));
+
+    loop {
+        select! {
+            m = consumer.next() => {
+                let Some(m) = m else {
+                    return Err(anyhow!("rmq stream closed"));
+                };
+
+                tokio::spawn(handle_message(global.clone(), m?));
+            }
+            _ = global.ctx.done() => return Ok(()),
+        }
+    }
+}
+
+async fn handle_message(global: Arc<GlobalState>, delivery: Delivery) {
+    let result = async {
+        let result =
+            pb::scuffle::events::ImageProcessorJobResult::decode(delivery.data.as_slice())?;
+        let job_id = result.id.parse()?;
+
+        // A job belongs to at most one of them, the other one changes nothing.
+        finish_profile_picture(&global, job_id, result.error.clone()).await?;
+        finish_banner(&global, job_id, result.error).await
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::error!("failed to handle image processor result: {:#}", e);
+        global
+            .dead_letter(Consumer::ImageProcessorResults, &delivery, &e)
+            .await;
+    }
+
+    if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+        tracing::error!("failed to ack image processor result: {}", e);
+    }
+}
+
+/// Swaps in a processed profile picture. Only the latest upload of a user has its job id stored,
+/// so the result of an upload which was replaced in the meantime changes nothing.
+pub async fn finish_profile_picture(
+    global: &GlobalState,
+    job_id: Uuid,
+    error: Option<String>,
+) -> Result<()> {
+    let user = match &error {
+        None => sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET profile_image_url = pending_profile_image_url, profile_image_job_id = NULL, pending_profile_image_url = NULL WHERE profile_image_job_id = $1 RETURNING *",
+            job_id,
+        )
+        .fetch_optional(&*global.db)
+        .await?,
+        Some(_) => sqlx::query_as!(
+            user::Model,
+            "UPDATE users SET profile_image_job_id = NULL, pending_profile_image_url = NULL WHERE profile_image_job_id = $1 RETURNING *",
+            job_id,
+        )
+        .fetch_optional(&*global.db)
+        .await?,
+    };
+
+    if let (Some(user), Some(error)) = (user, error) {
+        // The old picture is kept, the user sees the upload is no longer pending and can try again.
+        tracing::warn!(user_id = %user.id, "failed to process profile picture: {}", error);
+    }
+
+    Ok(())
+}
+
+/// Swaps in a processed banner, like [`finish_profile_picture`] does for profile pictures,
+/// and tells open channel pages to reload the appearance.
+pub async fn finish_banner(
+    global: &GlobalState,
+    job_id: Uuid,
+    error: Option<String>,
+) -> Result<()> {
+    let appearance = match &error {
+        None => sqlx::query_as!(
+            channel_appearance::Model,
+            "UPDATE channel_appearances SET banner_id = pending_banner_id, banner_job_id = NULL, pending_banner_id = NULL, updated_at = NOW() WHERE banner_job_id = $1 RETURNING *",
+            job_id,
+        )
+        .fetch_optional(&*global.db)
+        .await?,
+        Some(_) => sqlx::query_as!(
+            channel_appearance::Model,
+            "UPDATE channel_appearances SET banner_job_id = NULL, pending_banner_id = NULL WHERE banner_job_id = $1 RETURNING *",
+            job_id,
+        )
+        .fetch_optional(&*global.db)
+        .await?,
+    };
+
+    let Some(appearance) = appearance else {
+        return Ok(());
+    };
+
+    if let Some(error) = error {
+        tracing::warn!(channel_id = %appearance.channel_id, "failed to process banner: {}", error);
+    }
+
+    // The banner is already swapped in, so retrying the result would not publish again.
+    if let Err(e) = global
+        .publish_event(
+            appearance.channel_id,
+            &pb::scuffle::events::ChannelAppearanceUpdated {
+                channel_id: appearance.channel_id.to_string(),
+            },
+        )
+        .await
+    {
+        tracing::error!(channel_id = %appearance.channel_id, "failed to publish appearance: {}", e);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,354 @@
+use std::{pin::pin, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use lapin::{
+    message::Delivery,
+    options::{BasicAckOptions, BasicConsumeOptions, QueueDeclareOptions},
+    types::FieldTable,
+};
+use prost::Message;
+use tokio::select;
+use uuid::Uuid;
+
+use crate::{
+    database::{
+        channel_import::{self, Platform, Status, Step},
+        dead_letter::Consumer,
+        emote,
+    },
+    global::GlobalState,
+    pb::scuffle::events::ChannelImportJob,
+};
+
+pub mod twitch;
+pub mod youtube;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// Everything fetched from the other platform, before it is imported.
+pub struct ImportedChannel {
+    pub profile_image_url: Option<String>,
+    pub panels: Vec<ImportedPanel>,
+    pub schedule: Vec<ImportedSegment>,
+    pub emotes: Vec<ImportedEmote>,
+}
+
+impl ImportedChannel {
+    pub fn total_items(&self) -> usize {
+        self.profile_image_url.iter().count()
+            + self.panels.len()
+            + self.schedule.len()
+            + self.emotes.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedPanel {
+    pub title: String,
+    pub description: String,
+    pub image_url: Option<String>,
+    pub link_url: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedSegment {
+    pub title: String,
+    pub category: String,
+    pub recurring: bool,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedEmote {
+    pub name: String,
+    pub image_url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Consumes the channel imports queued by [`GlobalState::queue_channel_import`].
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    global
+        .rmq
+        .aquire()
+        .await?
+        .queue_declare(
+            &global.config.import.queue,
+            QueueDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    let mut consumer = pin!(global.rmq.basic_consume(
+        &global.config.import.queue,
+        &global.config.name,
+        BasicConsumeOptions::default(),
+        FieldTable::default()
+    ));
+
+    loop {
+        select! {
+            m = consumer.next() => {
+                let Some(m) = m else {
+                    return Err(anyhow!("rmq stream closed"));
+                };
+
+                tokio::spawn(handle_message(global.clone(), m?));
+            }
+            _ = global.ctx.done() => return Ok(()),
+        }
+    }
+}
+
+async fn handle_message(global: Arc<GlobalState>, delivery: Delivery) {
+    let result = async {
+        let job = ChannelImportJob::decode(delivery.data.as_slice())?;
+        run_import(&global, job.id.parse()?).await
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::error!("failed to run channel import: {:#}", e);
+        global.dead_letter(Consumer::Import, &delivery, &e).await;
+    }
+
+    if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+        tracing::error!("failed to ack channel import: {}", e);
+    }
+}
+
+async fn run_import(global: &Arc<GlobalState>, import_id: Uuid) -> Result<()> {
+    // Claiming the import makes redelivered jobs a no-op.
+    let Some(import) = sqlx::query_as!(
+        channel_import::Model,
+        "UPDATE channel_imports SET status = $2, updated_at = NOW() WHERE id = $1 AND status = $3 RETURNING *",
+        import_id,
+        i64::from(Status::Running),
+        i64::from(Status::Queued),
+    )
+    .fetch_optional(&*global.db)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let result = import_channel(global, &import).await;
+
+    // The access token is only needed while importing, so it is removed either way.
+    sqlx::query!(
+        "UPDATE channel_imports SET status = $2, error = $3, access_token_encrypted = NULL, updated_at = NOW(), completed_at = NOW() WHERE id = $1",
+        import.id,
+        i64::from(match &result {
+            Ok(()) => Status::Completed,
+            Err(_) => Status::Failed,
+        }),
+        result
+            .as_ref()
+            .err()
+            .map(|e| format!("{:#}", e))
+            .unwrap_or_default(),
+    )
+    .execute(&*global.db)
+    .await?;
+
+    // The failure is kept on the import, the message itself was handled and is not dead-lettered.
+    if let Err(e) = result {
+        tracing::warn!(import_id = %import.id, "channel import failed: {:#}", e);
+    }
+
+    Ok(())
+}
+
+async fn set_step(global: &Arc<GlobalState>, import_id: Uuid, step: Step) -> sqlx::Result<()> {
+    sqlx::query!(
+        "UPDATE channel_imports SET step = $2, updated_at = NOW() WHERE id = $1",
+        import_id,
+        i64::from(step),
+    )
+    .execute(&*global.db)
+    .await?;
+
+    Ok(())
+}
+
+async fn record(global: &Arc<GlobalState>, import_id: Uuid, imported: bool) -> sqlx::Result<()> {
+    sqlx::query!(
+        "UPDATE channel_imports SET imported_items = imported_items + $2, skipped_items = skipped_items + $3, updated_at = NOW() WHERE id = $1",
+        import_id,
+        imported as i64,
+        !imported as i64,
+    )
+    .execute(&*global.db)
+    .await?;
+
+    Ok(())
+}
+
+async fn import_channel(global: &Arc<GlobalState>, import: &channel_import::Model) -> Result<()> {
+    let access_token = global.decrypt_secret(
+        import
+            .access_token_encrypted
+            .as_deref()
+            .ok_or_else(|| anyhow!("access token is missing"))?,
+    )?;
+
+    let channel = match import.platform {
+        Platform::Twitch => twitch::fetch(global, &access_token).await?,
+        Platform::Youtube => youtube::fetch(global, &access_token).await?,
+    };
+
+    sqlx::query!(
+        "UPDATE channel_imports SET total_items = $2, updated_at = NOW() WHERE id = $1",
+        import.id,
+        channel.total_items() as i64,
+    )
+    .execute(&*global.db)
+    .await?;
+
+    if let Some(source_url) = &channel.profile_image_url {
+        set_step(global, import.id, Step::ProfileImage).await?;
+
+        let image_url = global
+            .process_image(
+                source_url,
+                &format!("profile-images/{}/{}", import.channel_id, import.id),
+            )
+            .await?;
+
+        sqlx::query!(
+            "UPDATE users SET profile_image_url = $2 WHERE id = $1",
+            import.channel_id,
+            image_url,
+        )
+        .execute(&*global.db)
+        .await?;
+
+        record(global, import.id, true).await?;
+    }
+
+    set_step(global, import.id, Step::Panels).await?;
+    for (i, panel) in channel.panels.iter().enumerate() {
+        let image_url = match &panel.image_url {
+            Some(source_url) => Some(
+                global
+                    .process_image(
+                        source_url,
+                        &format!("panels/{}/{}/{}", import.channel_id, import.id, i),
+                    )
+                    .await?,
+            ),
+            None => None,
+        };
+
+        // Imported panels go below the ones the channel already has.
+        sqlx::query!(
+            "INSERT INTO channel_panels (channel_id, position, title, description, image_url, link_url) VALUES ($1, (SELECT COALESCE(MAX(position) + 1, 0) FROM channel_panels WHERE channel_id = $1), $2, $3, $4, $5)",
+            import.channel_id,
+            panel.title,
+            panel.description,
+            image_url,
+            panel.link_url,
+        )
+        .execute(&*global.db)
+        .await?;
+
+        record(global, import.id, true).await?;
+    }
+
+    set_step(global, import.id, Step::Schedule).await?;
+    for segment in &channel.schedule {
+        let past = !segment.recurring && segment.ends_at.unwrap_or(segment.starts_at) < Utc::now();
+        if !past {
+            sqlx::query!(
+                "INSERT INTO channel_schedule_segments (channel_id, title, category, recurring, starts_at, ends_at) VALUES ($1, $2, $3, $4, $5, $6)",
+                import.channel_id,
+                segment.title,
+                segment.category,
+                segment.recurring,
+                segment.starts_at,
+                segment.ends_at,
+            )
+            .execute(&*global.db)
+            .await?;
+        }
+
+        record(global, import.id, !past).await?;
+    }
+
+    set_step(global, import.id, Step::Emotes).await?;
+    for imported in &channel.emotes {
+        let imported = import_emote(global, import.channel_id, imported).await?;
+        record(global, import.id, imported).await?;
+    }
+
+    Ok(())
+}
+
+/// Submits an emote for review like an upload would be. Returns false if the emote was skipped,
+/// because its name or size is not allowed here, the channel has no free slots or already has an emote with the name.
+async fn import_emote(
+    global: &Arc<GlobalState>,
+    channel_id: Uuid,
+    imported: &ImportedEmote,
+) -> Result<bool> {
+    if emote::validate_name(&imported.name).is_err()
+        || emote::validate_dimensions(
+            imported.width,
+            imported.height,
+            global.config.emotes.max_size,
+        )
+        .is_err()
+    {
+        return Ok(false);
+    }
+
+    let exists = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM emotes WHERE channel_id = $1 AND name = $2)",
+        channel_id,
+        imported.name,
+    )
+    .fetch_one(&*global.db)
+    .await?
+    .unwrap_or(false);
+
+    let sub_points = emote::sub_points(&global.db, channel_id).await?;
+    let used = emote::used_slots(&global.db, channel_id).await?;
+
+    if exists || used >= emote::slot_count(sub_points, &global.config.emotes) {
+        return Ok(false);
+    }
+
+    let nsfw_score = global.nsfw_score(&imported.image_url).await?;
+    let (status, review_note) = if nsfw_score >= global.config.emotes.nsfw_threshold {
+        (emote::Status::Rejected, "Rejected by the automatic checks")
+    } else {
+        (emote::Status::PendingReview, "")
+    };
+
+    let image_url = global
+        .process_image(&imported.image_url, &format!("emotes/{}", Uuid::new_v4()))
+        .await?;
+
+    let inserted = sqlx::query!(
+        "INSERT INTO emotes (channel_id, name, image_url, width, height, nsfw_score, status, review_note, reviewed_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, CASE WHEN $7 = $9 THEN NOW() END) ON CONFLICT (channel_id, name) DO NOTHING",
+        channel_id,
+        imported.name,
+        image_url,
+        imported.width as i64,
+        imported.height as i64,
+        nsfw_score as i64,
+        i64::from(status),
+        review_note,
+        i64::from(emote::Status::Rejected),
+    )
+    .execute(&*global.db)
+    .await?
+    .rows_affected();
+
+    Ok(inserted > 0)
+}
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::{ImportedChannel, ImportedEmote, ImportedPanel, ImportedSegment};
+use crate::global::GlobalState;
+
+/// Twitch serves channel emotes at 112x112 in their largest scale.
+const EMOTE_SIZE: u32 = 112;
+
+#[derive(Debug, Deserialize)]
+struct Data<T> {
+    data: T,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct User {
+    pub id: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub profile_image_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Schedule {
+    #[serde(default)]
+    segments: Option<Vec<Segment>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Segment {
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub title: String,
+    pub category: Option<Category>,
+    #[serde(default)]
+    pub is_recurring: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Category {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Emote {
+    pub name: String,
+    pub images: EmoteImages,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmoteImages {
+    pub url_4x: String,
+}
+
+/// Converts the channel as the Helix API returns it into what gets imported.
+/// Helix does not expose panels, so the channel description becomes an about panel.
+pub fn imported_channel(user: User, segments: Vec<Segment>, emotes: Vec<Emote>) -> ImportedChannel {
+    ImportedChannel {
+        profile_image_url: Some(user.profile_image_url).filter(|url| !url.is_empty()),
+        panels: Some(user.description)
+            .filter(|d| !d.trim().is_empty())
+            .map(|description| ImportedPanel {
+                title: "About".to_string(),
+                description,
+                image_url: None,
+                link_url: None,
+            })
+            .into_iter()
+            .collect(),
+        schedule: segments
+            .into_iter()
+            .map(|s| ImportedSegment {
+                title: s.title,
+                category: s.category.map(|c| c.name).unwrap_or_default(),
+                recurring: s.is_recurring,
+                starts_at: s.start_time,
+                ends_at: s.end_time,
+            })
+            .collect(),
+        emotes: emotes
+            .into_iter()
+            .map(|e| ImportedEmote {
+                name: e.name,
+                image_url: e.images.url_4x,
+                width: EMOTE_SIZE,
+                height: EMOTE_SIZE,
+            })
+            .collect(),
+    }
+}
+
+async fn get<T: DeserializeOwned>(
+    global: &Arc<GlobalState>,
+    access_token: &str,
+    path: &str,
+) -> Result<Option<T>> {
+    let res = reqwest::Client::new()
+        .get(format!("{}{}", global.config.import.twitch_api_url, path))
+        .bearer_auth(access_token)
+        .header("Client-Id", &global.config.import.twitch_client_id)
+        .send()
+        .await?;
+
+    // Twitch answers with not found for channels without a schedule.
+    if res.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    Ok(Some(res.error_for_status()?.json::<Data<T>>().await?.data))
+}
+
+/// Fetches the channel the access token belongs to.
+pub async fn fetch(global: &Arc<GlobalState>, access_token: &str) -> Result<ImportedChannel> {
+    let user = get::<Vec<User>>(global, access_token, "/users")
+        .await?
+        .and_then(|users| users.into_iter().next())
+        .ok_or_else(|| anyhow!("Twitch did not return the user of the access token"))?;
+
+    let segments = get::<Schedule>(
+        global,
+        access_token,
+        &format!("/schedule?broadcaster_id={}&first=25", user.id),
+    )
+    .await?
+    .and_then(|s| s.segments)
+    .unwrap_or_default();
+
+    let emotes = get::<Vec<Emote>>(
+        global,
+        access_token,
+        &format!("/chat/emotes?broadcaster_id={}", user.id),
+    )
+    .await?
+    .unwrap_or_default();
+
+    Ok(imported_channel(user, segments, emotes))
+}
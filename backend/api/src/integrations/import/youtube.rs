@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::{ImportedChannel, ImportedPanel, ImportedSegment};
+use crate::global::GlobalState;
+
+#[derive(Debug, Deserialize)]
+struct List<T> {
+    #[serde(default = "Vec::new")]
+    items: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Channel {
+    pub snippet: ChannelSnippet,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelSnippet {
+    #[serde(default)]
+    pub description: String,
+    pub thumbnails: Thumbnails,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Thumbnails {
+    pub high: Option<Thumbnail>,
+    pub default: Option<Thumbnail>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Thumbnail {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Broadcast {
+    pub snippet: BroadcastSnippet,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BroadcastSnippet {
+    pub title: String,
+    pub scheduled_start_time: Option<DateTime<Utc>>,
+    pub scheduled_end_time: Option<DateTime<Utc>>,
+}
+
+/// Converts the channel as the YouTube Data API returns it into what gets imported.
+/// YouTube has no panels or channel emotes in its API, so the description becomes an about panel
+/// and the upcoming live streams make up the schedule.
+pub fn imported_channel(channel: Channel, broadcasts: Vec<Broadcast>) -> ImportedChannel {
+    let thumbnails = channel.snippet.thumbnails;
+
+    ImportedChannel {
+        profile_image_url: thumbnails.high.or(thumbnails.default).map(|t| t.url),
+        panels: Some(channel.snippet.description)
+            .filter(|d| !d.trim().is_empty())
+            .map(|description| ImportedPanel {
+                title: "About".to_string(),
+                description,
+                image_url: None,
+                link_url: None,
+            })
+            .into_iter()
+            .collect(),
+        schedule: broadcasts
+            .into_iter()
+            .filter_map(|b| {
+                Some(ImportedSegment {
+                    starts_at: b.snippet.scheduled_start_time?,
+                    ends_at: b.snippet.scheduled_end_time,
+                    title: b.snippet.title,
+                    category: String::new(),
+                    recurring: false,
+                })
+            })
+            .collect(),
+        emotes: Vec::new(),
+    }
+}
+
+async fn list<T: DeserializeOwned>(
+    global: &Arc<GlobalState>,
+    access_token: &str,
+    path: &str,
+) -> Result<Vec<T>> {
+    Ok(reqwest::Client::new()
+        .get(format!("{}{}", global.config.import.youtube_api_url, path))
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<List<T>>()
+        .await?
+        .items)
+}
+
+/// Fetches the channel the access token belongs to.
+pub async fn fetch(global: &Arc<GlobalState>, access_token: &str) -> Result<ImportedChannel> {
+    let channel = list::<Channel>(global, access_token, "/channels?part=snippet&mine=true")
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("the YouTube account has no channel"))?;
+
+    let broadcasts = list::<Broadcast>(
+        global,
+        access_token,
+        "/liveBroadcasts?part=snippet&broadcastStatus=upcoming&maxResults=25",
+    )
+    .await?;
+
+    Ok(imported_channel(channel, broadcasts))
+}
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::global::GlobalState;
+
+pub mod chat_timers;
+pub mod data_export;
+pub mod digest;
+pub mod discord;
+pub mod image_processor;
+pub mod import;
+pub mod moderation;
+pub mod notifications;
+pub mod obs;
+pub mod reconciliation;
+pub mod sandbox;
+pub mod suspensions;
+pub mod vod_caption;
+pub mod vod_download;
+
+/// Runs the integrations which keep channels in sync with third-party services, and the background jobs.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    tokio::try_join!(
+        obs::run(global.clone()),
+        notifications::run(global.clone()),
+        import::run(global.clone()),
+        data_export::run(global.clone()),
+        image_processor::run(global.clone()),
+        moderation::run(global.clone()),
+        sandbox::run(global.clone()),
+        reconciliation::run(global.clone()),
+        digest::run(global.clone()),
+        chat_timers::run(global.clone()),
+        suspensions::run(global.clone()),
+        vod_caption::run(global.clone()),
+        vod_download::run(global),
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,268 @@
+use std::{pin::pin, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use futures_util::StreamExt;
+use lapin::{
+    message::Delivery,
+    options::{BasicAckOptions, BasicConsumeOptions, QueueDeclareOptions},
+    types::FieldTable,
+};
+use prost::Message;
+use tokio::select;
+use uuid::Uuid;
+
+use crate::{
+    database::{
+        channel_event, chat_ban,
+        dead_letter::Consumer,
+        legal_hold::{self, SubjectKind},
+        moderation_job::{self, like_pattern, Kind, Status},
+    },
+    global::GlobalState,
+    pb,
+};
+
+/// Consumes the moderation jobs queued by [`GlobalState::queue_moderation_job`].
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    global
+        .rmq
+        .aquire()
+        .await?
+        .queue_declare(
+            &global.config.moderation.queue,
+            QueueDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    let mut consumer = pin!(global.rmq.basic_consume(
+        &global.config.moderation.queue,
+        &global.config.name,
+        BasicConsumeOptions::default(),
+        FieldTable::default()
+    ));
+
+    loop {
+        select! {
+            m = consumer.next() => {
+                let Some(m) = m else {
+                    return Err(anyhow!("rmq stream closed"));
+                };
+
+                tokio::spawn(handle_message(global.clone(), m?));
+            }
+            _ = global.ctx.done() => return Ok(()),
+        }
+    }
+}
+
+async fn handle_message(global: Arc<GlobalState>, delivery: Delivery) {
+    let result = async {
+        let job = pb::scuffle::events::ModerationJob::decode(delivery.data.as_slice())?;
+        run_job(&global, job.id.parse()?).await
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::error!("failed to run moderation job: {:#}", e);
+        global
+            .dead_letter(Consumer::Moderation, &delivery, &e)
+            .await;
+    }
+
+    if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+        tracing::error!("failed to ack moderation job: {}", e);
+    }
+}
+
+async fn run_job(global: &Arc<GlobalState>, job_id: Uuid) -> Result<()> {
+    // Claiming the job makes redelivered jobs a no-op.
+    let Some(job) = sqlx::query_as!(
+        moderation_job::Model,
+        "UPDATE moderation_jobs SET status = $2, updated_at = NOW() WHERE id = $1 AND status = $3 RETURNING *",
+        job_id,
+        i64::from(Status::Running),
+        i64::from(Status::Queued),
+    )
+    .fetch_optional(&*global.db)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let result = match job.kind {
+        Kind::BanUsers => ban_users(global, &job).await,
+        // The hold may have been placed after the job was queued.
+        Kind::DeleteMessages | Kind::RemoveFollows
+            if legal_hold::is_held(&global.db, SubjectKind::Channel, job.channel_id).await? =>
+        {
+            Err(anyhow!("the content of the channel is under legal hold"))
+        }
+        Kind::DeleteMessages => delete_messages(global, &job).await,
+        Kind::RemoveFollows => remove_follows(global, &job).await,
+    };
+
+    sqlx::query!(
+        "UPDATE moderation_jobs SET status = $2, error = $3, updated_at = NOW(), completed_at = NOW() WHERE id = $1",
+        job.id,
+        i64::from(match &result {
+            Ok(()) => Status::Completed,
+            Err(_) => Status::Failed,
+        }),
+        result
+            .as_ref()
+            .err()
+            .map(|e| format!("{:#}", e))
+            .unwrap_or_default(),
+    )
+    .execute(&*global.db)
+    .await?;
+
+    // The failure is kept on the job, the message itself was handled and is not dead-lettered.
+    if let Err(e) = result {
+        tracing::warn!(job_id = %job.id, "moderation job failed: {:#}", e);
+    }
+
+    Ok(())
+}
+
+async fn record(global: &Arc<GlobalState>, job_id: Uuid, processed: usize) -> sqlx::Result<()> {
+    sqlx::query!(
+        "UPDATE moderation_jobs SET processed_items = processed_items + $2, updated_at = NOW() WHERE id = $1",
+        job_id,
+        processed as i64,
+    )
+    .execute(&*global.db)
+    .await?;
+
+    Ok(())
+}
+
+async fn ban_users(global: &Arc<GlobalState>, job: &moderation_job::Model) -> Result<()> {
+    for user_ids in job
+        .user_ids
+        .chunks(global.config.moderation.batch_size as usize)
+    {
+        // Users who do not exist and the channel owner are skipped, users who are already banned keep their ban.
+        // A ban which ran out is replaced.
+        let bans = sqlx::query_as!(
+            chat_ban::Model,
+            "INSERT INTO chat_bans (channel_id, user_id, banned_by, reason) SELECT $1, id, $2, $3 FROM users WHERE id = ANY($4) AND id <> $1 ON CONFLICT (channel_id, user_id) DO UPDATE SET banned_by = excluded.banned_by, reason = excluded.reason, created_at = NOW(), expires_at = NULL WHERE chat_bans.expires_at <= NOW() RETURNING *",
+            job.channel_id,
+            job.created_by,
+            job.reason,
+            user_ids,
+        )
+        .fetch_all(&*global.db)
+        .await?;
+
+        for ban in &bans {
+            global
+                .chat_ban_changed(ban.channel_id, ban.user_id, Some(ban))
+                .await;
+        }
+
+        record(global, job.id, user_ids.len()).await?;
+    }
+
+    Ok(())
+}
+
+async fn delete_messages(global: &Arc<GlobalState>, job: &moderation_job::Model) -> Result<()> {
+    let since = job
+        .since
+        .ok_or_else(|| anyhow!("delete job has no start time"))?;
+    let pattern = like_pattern(&job.pattern);
+
+    // Messages sent after the job was started are left alone, so the job finishes during an ongoing raid.
+    loop {
+        let deleted = sqlx::query!(
+            "DELETE FROM chat_messages WHERE id IN (SELECT id FROM chat_messages WHERE channel_id = $1 AND created_at >= $2 AND created_at <= $3 AND content ILIKE $4 LIMIT $5) RETURNING id, author_id",
+            job.channel_id,
+            since,
+            job.created_at,
+            pattern,
+            global.config.moderation.batch_size,
+        )
+        .fetch_all(&*global.db)
+        .await?;
+
+        if deleted.is_empty() {
+            return Ok(());
+        }
+
+        // Chat clients remove the messages they already show once they hear about the deletion.
+        for message in &deleted {
+            let res = global
+                .publish_chat_message(
+                    job.channel_id,
+                    pb::scuffle::events::ChatMessage {
+                        id: message.id.to_string(),
+                        channel_id: job.channel_id.to_string(),
+                        author_id: message.author_id.to_string(),
+                        content: String::new(),
+                        created_at: Utc::now().timestamp(),
+                        r#type: pb::scuffle::events::chat_message::Type::Deleted as i32,
+                        emotes: Vec::new(),
+                        cheer: None,
+                        author_color: None,
+                        sequence: 0,
+                        author_vip: false,
+                        author_verified_bot: false,
+                    },
+                )
+                .await;
+            if let Err(e) = res {
+                tracing::warn!("failed to publish chat message deletion: {}", e);
+            }
+        }
+
+        record(global, job.id, deleted.len()).await?;
+    }
+}
+
+async fn remove_follows(global: &Arc<GlobalState>, job: &moderation_job::Model) -> Result<()> {
+    let (Some(since), Some(until)) = (job.since, job.until) else {
+        return Err(anyhow!("follow job has no time window"));
+    };
+
+    // Anonymous follows have no account to judge, so they are only removed when no account age is given.
+    loop {
+        let removed = sqlx::query!(
+            "DELETE FROM channel_events WHERE id IN (SELECT e.id FROM channel_events e LEFT JOIN users u ON u.id = e.user_id WHERE e.channel_id = $1 AND e.kind = $2 AND e.created_at BETWEEN $3 AND $4 AND ($5::bigint IS NULL OR e.created_at - u.created_at <= $5 * INTERVAL '1 minute') AND (NOT $6 OR NOT EXISTS (SELECT 1 FROM chat_messages m WHERE m.channel_id = e.channel_id AND m.author_id = e.user_id)) LIMIT $7)",
+            job.channel_id,
+            i64::from(channel_event::Kind::Follow),
+            since,
+            until,
+            job.max_account_age,
+            job.only_silent,
+            global.config.moderation.batch_size,
+        )
+        .execute(&*global.db)
+        .await?
+        .rows_affected();
+
+        if removed == 0 {
+            break;
+        }
+
+        record(global, job.id, removed as usize).await?;
+    }
+
+    // The summaries of the streams during the attack counted the removed follows.
+    sqlx::query!(
+        "UPDATE stream_sessions s SET new_follows = (SELECT COUNT(*) FROM channel_events e WHERE e.channel_id = s.channel_id AND e.kind = $2 AND e.created_at BETWEEN s.started_at AND s.ended_at) WHERE s.channel_id = $1 AND s.ended_at >= $3 AND s.started_at <= $4",
+        job.channel_id,
+        i64::from(channel_event::Kind::Follow),
+        since,
+        until,
+    )
+    .execute(&*global.db)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use std::{pin::pin, sync::Arc};
+
+use anyhow::{anyhow, bail, Result};
+use futures_util::StreamExt;
+use lapin::{
+    message::Delivery,
+    options::{
+        BasicAckOptions, BasicConsumeOptions, ExchangeDeclareOptions, QueueBindOptions,
+        QueueDeclareOptions,
+    },
+    types::FieldTable,
+    ExchangeKind,
+};
+use prost::Message;
+use tokio::select;
+use uuid::Uuid;
+
+use super::discord;
+use crate::{
+    database::{dead_letter::Consumer, discord_announcement, notification_settings},
+    global::GlobalState,
+    pb::scuffle::events::{channel_notification::Kind, ChannelNotification},
+};
+
+/// Consumes the channel notifications published by [`GlobalState::notify`] and fans them out to the integrations.
+/// The queue is shared by all API instances, so every notification is handled by a single one of them.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    let channel = global.rmq.aquire().await?;
+
+    channel
+        .exchange_declare(
+            &global.config.notifications.exchange,
+            ExchangeKind::Fanout,
+            ExchangeDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    channel
+        .queue_declare(
+            &global.config.notifications.queue,
+            QueueDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    channel
+        .queue_bind(
+            &global.config.notifications.queue,
+            &global.config.notifications.exchange,
+            "",
+            QueueBindOptions::default(),
+            FieldTable::default(),
+        )
+        .await?;
+
+    let mut consumer = pin!(global.rmq.basic_consume(
+        &global.config.notifications.queue,
+        &global.config.name,
+        BasicConsumeOptions::default(),
+        FieldTable::default()
+    ));
+
+    loop {
+        select! {
+            m = consumer.next() => {
+                let Some(m) = m else {
+                    return Err(anyhow!("rmq stream closed"));
+                };
+
+                tokio::spawn(handle_message(global.clone(), m?));
+            }
+            _ = global.ctx.done() => return Ok(()),
+        }
+    }
+}
+
+async fn handle_message(global: Arc<GlobalState>, delivery: Delivery) {
+    // Announcements are not retried on their own, a delayed go-live post is worse than a missing one.
+    // An operator can still retry the dead letter if the announcement matters.
+    if let Err(e) = handle_notification(&global, &delivery.data).await {
+        tracing::error!("failed to handle channel notification: {:#}", e);
+        global
+            .dead_letter(Consumer::Notifications, &delivery, &e)
+            .await;
+    }
+
+    if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+        tracing::error!("failed to ack channel notification: {}", e);
+    }
+}
+
+async fn handle_notification(global: &Arc<GlobalState>, data: &[u8]) -> Result<()> {
+    let notification = ChannelNotification::decode(data)?;
+
+    let channel_id = notification.channel_id.parse::<Uuid>()?;
+    let stream_id = notification.stream_id.parse::<Uuid>()?;
+
+    let kind = match Kind::from_i32(notification.kind) {
+        Some(Kind::GoLive) => discord_announcement::Kind::GoLive,
+        Some(Kind::Offline) => discord_announcement::Kind::Offline,
+        Some(Kind::VodPublished) => discord_announcement::Kind::VodPublished,
+        None => bail!("unknown notification kind: {}", notification.kind),
+    };
+
+    discord::announce(global, channel_id, kind, stream_id).await?;
+
+    if kind == discord_announcement::Kind::GoLive {
+        email_followers(global, channel_id, stream_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Emails the followers of a channel which went live, unless they turned go-live emails off.
+/// An email which fails to send is only logged, retrying the notification would email everyone else again.
+async fn email_followers(
+    global: &Arc<GlobalState>,
+    channel_id: Uuid,
+    stream_id: Uuid,
+) -> Result<()> {
+    let channel = global
+        .user_by_id_loader
+        .load_one(channel_id)
+        .await
+        .map_err(|e| anyhow!("failed to fetch channel: {}", e))?
+        .ok_or_else(|| anyhow!("channel not found: {}", channel_id))?;
+
+    let title = sqlx::query_scalar!("SELECT title FROM streams WHERE id = $1", stream_id)
+        .fetch_optional(&*global.db)
+        .await?
+        .unwrap_or_default();
+
+    let url = format!(
+        "{}/{}",
+        global.config.discord.website_url.trim_end_matches('/'),
+        channel.username
+    );
+
+    let subject = format!("{} is live", channel.display_name);
+    let text = match title.is_empty() {
+        true => format!("{} is live: {}", channel.display_name, url),
+        false => format!("{} is live: {}\n\n{}", channel.display_name, title, url),
+    } + "\n\nYou can turn these emails off in your notification settings.";
+
+    for recipient in notification_settings::go_live_recipients(&global.db, channel_id).await? {
+        let result = async {
+            let to = global.decrypt_pii(&recipient.email)?;
+            global.send_email(&to, &subject, &text).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!(user_id = %recipient.user_id, "failed to send go-live email: {:#}", e);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,437 @@
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use common::prelude::FutureTimeout;
+use fred::{
+    prelude::KeysInterface,
+    types::{Expiration, SetOptions},
+};
+use futures_util::{SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::{
+    net::{lookup_host, TcpStream},
+    select,
+    task::JoinHandle,
+};
+use tokio_tungstenite::tungstenite::{self, Message};
+use uuid::Uuid;
+
+use crate::{
+    database::{obs_connection, obs_mapping},
+    global::GlobalState,
+};
+
+/// The obs-websocket v5 events we listen to: General, Config, Scenes and Outputs.
+const EVENT_SUBSCRIPTIONS: u32 = (1 << 0) | (1 << 1) | (1 << 2) | (1 << 6);
+
+const CURRENT_SCENE_REQUEST_ID: &str = "current-scene";
+const CURRENT_PROFILE_REQUEST_ID: &str = "current-profile";
+
+#[derive(Debug, Deserialize)]
+/// A message of the obs-websocket v5 protocol.
+pub struct ObsMessage {
+    pub op: u8,
+    #[serde(default)]
+    pub d: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The messages from OBS we act on.
+pub enum ObsEvent {
+    SceneChanged(String),
+    ProfileChanged(String),
+    CurrentScene(String),
+    CurrentProfile(String),
+    StreamStarting,
+    Exiting,
+    Other,
+}
+
+impl From<&ObsMessage> for ObsEvent {
+    fn from(message: &ObsMessage) -> Self {
+        let d = &message.d;
+        let string = |value: &serde_json::Value| value.as_str().map(str::to_string);
+
+        let event = match message.op {
+            // Event
+            5 => match d["eventType"].as_str() {
+                Some("CurrentProgramSceneChanged") => {
+                    string(&d["eventData"]["sceneName"]).map(Self::SceneChanged)
+                }
+                Some("CurrentProfileChanged") => {
+                    string(&d["eventData"]["profileName"]).map(Self::ProfileChanged)
+                }
+                Some("StreamStateChanged")
+                    if d["eventData"]["outputState"] == "OBS_WEBSOCKET_OUTPUT_STARTING" =>
+                {
+                    Some(Self::StreamStarting)
+                }
+                Some("ExitStarted") => Some(Self::Exiting),
+                _ => None,
+            },
+            // RequestResponse
+            7 => match d["requestId"].as_str() {
+                Some(CURRENT_SCENE_REQUEST_ID) => {
+                    string(&d["responseData"]["currentProgramSceneName"]).map(Self::CurrentScene)
+                }
+                Some(CURRENT_PROFILE_REQUEST_ID) => {
+                    string(&d["responseData"]["currentProfileName"]).map(Self::CurrentProfile)
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        event.unwrap_or(Self::Other)
+    }
+}
+
+/// Computes the authentication string obs-websocket expects for a password protected server.
+pub fn auth_response(password: &str, salt: &str, challenge: &str) -> String {
+    let secret = BASE64.encode(Sha256::digest(format!("{}{}", password, salt)));
+
+    BASE64.encode(Sha256::digest(format!("{}{}", secret, challenge)))
+}
+
+/// Keeps a connection open to the OBS of every channel which set one up.
+/// Every channel is only connected to by one API instance, which holds a lock in redis while connected.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    if !global.config.obs.enabled {
+        global.ctx.done().await;
+        return Ok(());
+    }
+
+    let instance_id = Uuid::new_v4().to_string();
+    let mut connections = HashMap::new();
+    let mut interval = tokio::time::interval(Duration::from_secs(
+        global.config.obs.reconcile_interval.max(1) as u64,
+    ));
+
+    loop {
+        select! {
+            _ = interval.tick() => {}
+            _ = global.ctx.done() => break,
+        }
+
+        if let Err(e) = reconcile(&global, &instance_id, &mut connections).await {
+            tracing::error!("failed to reconcile obs connections: {:#}", e);
+        }
+    }
+
+    for (_, (_, handle)) in connections {
+        handle.abort();
+    }
+
+    Ok(())
+}
+
+/// Starts connections for new and changed settings, stops removed ones and retries failed ones.
+async fn reconcile(
+    global: &Arc<GlobalState>,
+    instance_id: &str,
+    connections: &mut HashMap<Uuid, (DateTime<Utc>, JoinHandle<()>)>,
+) -> Result<()> {
+    let settings = sqlx::query_as!(obs_connection::Model, "SELECT * FROM obs_connections")
+        .fetch_all(&*global.db)
+        .await?
+        .into_iter()
+        .map(|c| (c.channel_id, c))
+        .collect::<HashMap<_, _>>();
+
+    let mut removed = Vec::new();
+    connections.retain(|channel_id, (updated_at, handle)| {
+        let current = settings
+            .get(channel_id)
+            .map(|c| c.updated_at == *updated_at)
+            .unwrap_or(false);
+
+        if !current || handle.is_finished() {
+            handle.abort();
+            removed.push(*channel_id);
+            return false;
+        }
+
+        true
+    });
+
+    for channel_id in removed {
+        if !settings.contains_key(&channel_id) {
+            let _: () = global.redis.del(lock_key(channel_id)).await?;
+        }
+    }
+
+    let ttl = global.config.obs.reconcile_interval.max(1) as i64 * 3;
+    for (channel_id, connection) in settings {
+        if !acquire_lock(global, channel_id, instance_id, ttl).await? {
+            continue;
+        }
+
+        connections.entry(channel_id).or_insert_with(|| {
+            (
+                connection.updated_at,
+                tokio::spawn(connection_task(global.clone(), connection)),
+            )
+        });
+    }
+
+    Ok(())
+}
+
+fn lock_key(channel_id: Uuid) -> String {
+    format!("obs:{}:lock", channel_id)
+}
+
+/// Takes or refreshes the lock for connecting to a channel's OBS. Returns false if another instance holds it.
+async fn acquire_lock(
+    global: &Arc<GlobalState>,
+    channel_id: Uuid,
+    instance_id: &str,
+    ttl: i64,
+) -> Result<bool> {
+    let key = lock_key(channel_id);
+
+    let owner: Option<String> = global.redis.get(&key).await?;
+    match owner {
+        Some(owner) if owner == instance_id => {
+            let _: () = global.redis.expire(&key, ttl).await?;
+            Ok(true)
+        }
+        Some(_) => Ok(false),
+        None => {
+            let set: Option<String> = global
+                .redis
+                .set(
+                    &key,
+                    instance_id,
+                    Some(Expiration::EX(ttl)),
+                    Some(SetOptions::NX),
+                    false,
+                )
+                .await?;
+
+            Ok(set.is_some())
+        }
+    }
+}
+
+async fn connection_task(global: Arc<GlobalState>, connection: obs_connection::Model) {
+    let error = match session(&global, &connection).await {
+        Ok(()) => "OBS closed the connection".to_string(),
+        Err(e) => format!("{:#}", e),
+    };
+
+    if let Err(e) = sqlx::query!(
+        "UPDATE obs_connections SET status = $2, last_error = $3, connected_at = NULL WHERE channel_id = $1",
+        connection.channel_id,
+        i64::from(obs_connection::Status::Failed),
+        error,
+    )
+    .execute(&*global.db)
+    .await
+    {
+        tracing::error!("failed to update obs connection status: {}", e);
+    }
+}
+
+async fn next_message(
+    rx: &mut (impl Stream<Item = Result<Message, tungstenite::Error>> + Unpin),
+) -> Result<ObsMessage> {
+    loop {
+        match rx.next().await {
+            Some(Ok(Message::Text(text))) => return Ok(serde_json::from_str(&text)?),
+            Some(Ok(Message::Close(frame))) => bail!(
+                "OBS closed the connection: {}",
+                frame.map(|f| f.reason.to_string()).unwrap_or_default()
+            ),
+            Some(Ok(_)) => {}
+            Some(Err(e)) => return Err(e.into()),
+            None => bail!("connection to OBS lost"),
+        }
+    }
+}
+
+/// Resolves the host of an OBS connection. Every address it resolves to has to be public,
+/// the host could have been changed to point into our own network after it was saved.
+async fn resolve(host: &str, port: i64) -> Result<SocketAddr> {
+    let port = u16::try_from(port)?;
+    let addrs = lookup_host((host.trim_start_matches('[').trim_end_matches(']'), port))
+        .await?
+        .collect::<Vec<_>>();
+
+    if addrs.iter().any(|addr| !common::net::is_public(addr.ip())) {
+        bail!("OBS host resolves to an address which is not publicly reachable");
+    }
+
+    addrs
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("OBS host does not resolve"))
+}
+
+/// Connects to OBS and follows scene and profile changes until the connection closes.
+async fn session(global: &Arc<GlobalState>, connection: &obs_connection::Model) -> Result<()> {
+    let password = connection
+        .password_encrypted
+        .as_deref()
+        .map(|p| global.decrypt_secret(p))
+        .transpose()
+        .map_err(|_| anyhow!("failed to decrypt the OBS password"))?;
+
+    let host = match connection.host.contains(':') && !connection.host.starts_with('[') {
+        true => format!("[{}]", connection.host),
+        false => connection.host.clone(),
+    };
+
+    let (ws, _) = async {
+        let addr = resolve(&connection.host, connection.port).await?;
+        let stream = TcpStream::connect(addr).await?;
+
+        // The checked address is connected to, the host is only used for the handshake.
+        Ok::<_, anyhow::Error>(
+            tokio_tungstenite::client_async(format!("ws://{}:{}", host, connection.port), stream)
+                .await?,
+        )
+    }
+    .timeout(Duration::from_secs(
+        global.config.obs.connect_timeout as u64,
+    ))
+    .await
+    .map_err(|_| anyhow!("timed out connecting to OBS"))??;
+    let (mut tx, mut rx) = ws.split();
+
+    let hello = next_message(&mut rx).await?;
+    if hello.op != 0 {
+        bail!("expected hello from OBS, got op {}", hello.op);
+    }
+
+    let authentication = match (&hello.d["authentication"], password) {
+        (serde_json::Value::Null, _) => None,
+        (auth, Some(password)) => Some(auth_response(
+            &password,
+            auth["salt"].as_str().unwrap_or_default(),
+            auth["challenge"].as_str().unwrap_or_default(),
+        )),
+        (_, None) => bail!("OBS requires a password"),
+    };
+
+    tx.send(Message::Text(
+        json!({
+            "op": 1,
+            "d": {
+                "rpcVersion": 1,
+                "authentication": authentication,
+                "eventSubscriptions": EVENT_SUBSCRIPTIONS,
+            },
+        })
+        .to_string(),
+    ))
+    .await?;
+
+    let identified = next_message(&mut rx).await?;
+    if identified.op != 2 {
+        bail!("expected identified from OBS, got op {}", identified.op);
+    }
+
+    sqlx::query!(
+        "UPDATE obs_connections SET status = $2, last_error = '', connected_at = NOW() WHERE channel_id = $1",
+        connection.channel_id,
+        i64::from(obs_connection::Status::Connected),
+    )
+    .execute(&*global.db)
+    .await?;
+
+    for (request_type, request_id) in [
+        ("GetCurrentProgramScene", CURRENT_SCENE_REQUEST_ID),
+        ("GetProfileList", CURRENT_PROFILE_REQUEST_ID),
+    ] {
+        tx.send(Message::Text(
+            json!({
+                "op": 6,
+                "d": { "requestType": request_type, "requestId": request_id },
+            })
+            .to_string(),
+        ))
+        .await?;
+    }
+
+    let mut scene = None;
+    let mut profile = None;
+
+    loop {
+        let message = select! {
+            message = next_message(&mut rx) => message?,
+            _ = global.ctx.done() => {
+                tx.send(Message::Close(None)).await.ok();
+                return Ok(());
+            }
+        };
+
+        // The sources to apply, in order. Scenes are more specific than profiles, so they go last.
+        let apply = match ObsEvent::from(&message) {
+            ObsEvent::SceneChanged(name) => {
+                scene = Some(name.clone());
+                match connection.sync_scenes {
+                    true => vec![(obs_mapping::Kind::Scene, name)],
+                    false => vec![],
+                }
+            }
+            ObsEvent::ProfileChanged(name) => {
+                profile = Some(name.clone());
+                match connection.sync_profiles {
+                    true => vec![(obs_mapping::Kind::Profile, name)],
+                    false => vec![],
+                }
+            }
+            ObsEvent::CurrentScene(name) => {
+                scene = Some(name);
+                vec![]
+            }
+            ObsEvent::CurrentProfile(name) => {
+                profile = Some(name);
+                vec![]
+            }
+            ObsEvent::StreamStarting if connection.update_on_go_live => [
+                profile.clone().map(|p| (obs_mapping::Kind::Profile, p)),
+                scene.clone().map(|s| (obs_mapping::Kind::Scene, s)),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            ObsEvent::Exiting => return Ok(()),
+            _ => vec![],
+        };
+
+        for (kind, name) in apply {
+            if let Err(e) = apply_mapping(global, connection.channel_id, kind, &name).await {
+                tracing::error!("failed to apply obs mapping: {}", e);
+            }
+        }
+    }
+}
+
+/// Updates the stream info to what the channel mapped the scene or profile to, if it has a mapping for it.
+async fn apply_mapping(
+    global: &Arc<GlobalState>,
+    channel_id: Uuid,
+    kind: obs_mapping::Kind,
+    source_name: &str,
+) -> sqlx::Result<()> {
+    let Some(mapping) = obs_mapping::find(&global.db, channel_id, kind, source_name).await? else {
+        return Ok(());
+    };
+
+    global
+        .update_stream_info(
+            channel_id,
+            mapping.title.as_deref(),
+            Some(&mapping.category),
+            None,
+        )
+        .await?;
+
+    Ok(())
+}
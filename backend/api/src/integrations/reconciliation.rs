@@ -0,0 +1,37 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use tokio::select;
+
+use crate::global::GlobalState;
+
+/// Periodically repairs the stream summaries which drifted from the chat and follows they are counted from.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    let config = &global.config.reconciliation;
+    if config.interval == 0 {
+        return Ok(());
+    }
+
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval as u64));
+
+    loop {
+        select! {
+            _ = interval.tick() => {}
+            _ = global.ctx.done() => break,
+        }
+
+        let since = chrono::Utc::now() - chrono::Duration::days(config.lookback_days as i64);
+
+        match global.reconcile_stream_sessions(since).await {
+            Ok(result) if result == Default::default() => {}
+            Ok(result) => tracing::info!(
+                backfilled = result.backfilled_sessions,
+                corrected = result.corrected_sessions,
+                "reconciled stream sessions"
+            ),
+            Err(e) => tracing::error!("failed to reconcile stream sessions: {}", e),
+        }
+    }
+
+    Ok(())
+}
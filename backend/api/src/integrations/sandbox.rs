@@ -0,0 +1,63 @@
+use std::{process::Stdio, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use tokio::{process::Command, select};
+
+use crate::global::GlobalState;
+
+/// Keeps the test stream of the sandbox live. ffmpeg renders a test pattern with a tone and streams it
+/// to the ingest, and is started again whenever it exits, for example while the ingest restarts.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    if !global.config.sandbox {
+        return Ok(());
+    }
+
+    let config = &global.config.sandbox_stream;
+    let url = format!(
+        "{}/{}",
+        config.ingest_url.trim_end_matches('/'),
+        global.sandbox_stream_key()
+    );
+
+    loop {
+        let child = Command::new(&config.ffmpeg_path)
+            .args(["-hide_banner", "-loglevel", "error", "-re"])
+            .args(["-f", "lavfi", "-i", "testsrc2=size=1280x720:rate=30"])
+            .args(["-f", "lavfi", "-i", "sine=frequency=440:sample_rate=48000"])
+            .args([
+                "-c:v",
+                "libx264",
+                "-preset",
+                "veryfast",
+                "-tune",
+                "zerolatency",
+            ])
+            .args([
+                "-g", "60", "-pix_fmt", "yuv420p", "-c:a", "aac", "-b:a", "128k",
+            ])
+            .args(["-f", "flv"])
+            .arg(&url)
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .spawn();
+
+        match child {
+            Ok(mut child) => {
+                tracing::info!("started sandbox test stream");
+
+                select! {
+                    r = child.wait() => tracing::warn!("sandbox test stream stopped: {:?}", r),
+                    _ = global.ctx.done() => break,
+                }
+            }
+            Err(e) => tracing::error!("failed to start sandbox test stream: {}", e),
+        }
+
+        select! {
+            _ = tokio::time::sleep(Duration::from_secs(config.restart_delay as u64)) => {}
+            _ = global.ctx.done() => break,
+        }
+    }
+
+    Ok(())
+}
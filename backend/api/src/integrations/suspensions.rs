@@ -0,0 +1,38 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use tokio::select;
+
+use crate::global::GlobalState;
+
+/// Lifts timed suspensions once they expired, so they show up as lifted in the history.
+/// Access is already restored at the expiry, this only records it.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    let mut interval = tokio::time::interval(Duration::from_secs(
+        global.config.suspensions.reinstatement_interval.max(1) as u64,
+    ));
+
+    loop {
+        select! {
+            _ = interval.tick() => {}
+            _ = global.ctx.done() => break,
+        }
+
+        match reinstate(&global).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!(count, "reinstated suspended users"),
+            Err(e) => tracing::error!("failed to reinstate suspended users: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+async fn reinstate(global: &GlobalState) -> sqlx::Result<u64> {
+    Ok(sqlx::query!(
+        "UPDATE user_suspensions SET lifted_at = expires_at, lift_reason = 'Suspension expired' WHERE lifted_at IS NULL AND expires_at <= NOW()"
+    )
+    .execute(&*global.db)
+    .await?
+    .rows_affected())
+}
@@ -0,0 +1,140 @@
+use std::{pin::pin, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use futures_util::StreamExt;
+use lapin::{
+    message::Delivery,
+    options::{BasicAckOptions, BasicConsumeOptions, QueueDeclareOptions},
+    types::FieldTable,
+};
+use prost::Message;
+use tokio::select;
+use uuid::Uuid;
+
+use crate::{
+    database::{dead_letter::Consumer, user::validate_stream_language, vod_caption::Status},
+    global::GlobalState,
+    pb::scuffle::events::VodCaptionJobResult,
+};
+
+const MAX_LANGUAGE_LENGTH: usize = 16;
+
+/// Consumes the captions the worker reports for the transcriptions queued by [`GlobalState::queue_vod_captions`].
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    global
+        .rmq
+        .aquire()
+        .await?
+        .queue_declare(
+            &global.config.vod_captions.result_queue,
+            QueueDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    let mut consumer = pin!(global.rmq.basic_consume(
+        &global.config.vod_captions.result_queue,
+        &global.config.name,
+        BasicConsumeOptions::default(),
+        FieldTable::default()
+    ));
+
+    loop {
+        select! {
+            m = consumer.next() => {
+                let Some(m) = m else {
+                    return Err(anyhow!("rmq stream closed"));
+                };
+
+                tokio::spawn(handle_message(global.clone(), m?));
+            }
+            _ = global.ctx.done() => return Ok(()),
+        }
+    }
+}
+
+async fn handle_message(global: Arc<GlobalState>, delivery: Delivery) {
+    let result = async {
+        let result = VodCaptionJobResult::decode(delivery.data.as_slice())?;
+        record_result(&global, &result).await
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::error!("failed to handle vod caption result: {:#}", e);
+        global
+            .dead_letter(Consumer::VodCaptionResults, &delivery, &e)
+            .await;
+    }
+
+    if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+        tracing::error!("failed to ack vod caption result: {}", e);
+    }
+}
+
+/// Stores the captions the worker made for a VOD, and its cues so the VOD can be searched by them.
+/// Only queued captions are updated, so a redelivered result can't add the cues twice.
+pub async fn record_result(global: &GlobalState, result: &VodCaptionJobResult) -> Result<()> {
+    let caption_id: Uuid = result.id.parse()?;
+
+    if let Some(error) = &result.error {
+        // The backend's error could name its url and the broadcaster can't do anything about it, so it is only logged.
+        tracing::warn!(caption_id = %caption_id, "failed to make vod captions: {}", error);
+
+        sqlx::query!(
+            "UPDATE vod_captions SET status = $2, error = $3, updated_at = NOW(), completed_at = NOW() WHERE id = $1 AND status = $4",
+            caption_id,
+            i64::from(Status::Failed),
+            "Failed to make the captions",
+            i64::from(Status::Queued),
+        )
+        .execute(&*global.db)
+        .await?;
+
+        return Ok(());
+    }
+
+    // An unknown language is left empty rather than shown wrong to viewers.
+    let language = match validate_stream_language(&result.language) {
+        Ok(()) if result.language.len() <= MAX_LANGUAGE_LENGTH => result.language.as_str(),
+        _ => "",
+    };
+
+    let mut tx = global.db.begin().await?;
+
+    let Some(stream_id) = sqlx::query_scalar!(
+        "UPDATE vod_captions SET status = $2, language = $3, updated_at = NOW(), completed_at = NOW() WHERE id = $1 AND status = $4 RETURNING stream_id",
+        caption_id,
+        i64::from(Status::Completed),
+        language,
+        i64::from(Status::Queued),
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let cues = result
+        .cues
+        .iter()
+        .filter(|cue| !cue.text.trim().is_empty())
+        .collect::<Vec<_>>();
+
+    sqlx::query!(
+        "INSERT INTO vod_caption_cues (stream_id, start_ms, end_ms, text) SELECT $1, * FROM UNNEST($2::bigint[], $3::bigint[], $4::text[])",
+        stream_id,
+        &cues.iter().map(|cue| cue.start_ms as i64).collect::<Vec<_>>(),
+        &cues.iter().map(|cue| cue.end_ms as i64).collect::<Vec<_>>(),
+        &cues.iter().map(|cue| cue.text.trim().to_string()).collect::<Vec<_>>(),
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use std::{pin::pin, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use futures_util::StreamExt;
+use lapin::{
+    message::Delivery,
+    options::{BasicAckOptions, BasicConsumeOptions, QueueDeclareOptions},
+    types::FieldTable,
+};
+use prost::Message;
+use tokio::select;
+use uuid::Uuid;
+
+use crate::{
+    database::{dead_letter::Consumer, vod_download::Status},
+    global::GlobalState,
+    pb::scuffle::events::VodDownloadJobResult,
+};
+
+/// Consumes the progress the worker reports for the VOD downloads queued by [`GlobalState::queue_vod_download`].
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    global
+        .rmq
+        .aquire()
+        .await?
+        .queue_declare(
+            &global.config.vod_downloads.result_queue,
+            QueueDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    let mut consumer = pin!(global.rmq.basic_consume(
+        &global.config.vod_downloads.result_queue,
+        &global.config.name,
+        BasicConsumeOptions::default(),
+        FieldTable::default()
+    ));
+
+    loop {
+        select! {
+            m = consumer.next() => {
+                let Some(m) = m else {
+                    return Err(anyhow!("rmq stream closed"));
+                };
+
+                tokio::spawn(handle_message(global.clone(), m?));
+            }
+            _ = global.ctx.done() => return Ok(()),
+        }
+    }
+}
+
+async fn handle_message(global: Arc<GlobalState>, delivery: Delivery) {
+    let result = async {
+        let result = VodDownloadJobResult::decode(delivery.data.as_slice())?;
+        record_result(&global, &result).await
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::error!("failed to handle vod download result: {:#}", e);
+        global
+            .dead_letter(Consumer::VodDownloadResults, &delivery, &e)
+            .await;
+    }
+
+    if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+        tracing::error!("failed to ack vod download result: {}", e);
+    }
+}
+
+/// Stores what the worker reported about a download. Downloads which already finished are left alone,
+/// so a late progress report can't move one back.
+pub async fn record_result(global: &GlobalState, result: &VodDownloadJobResult) -> Result<()> {
+    let download_id: Uuid = result.id.parse()?;
+
+    if let Some(error) = &result.error {
+        // The error is only logged, it could name the object storage and the broadcaster can't do anything about it.
+        tracing::warn!(download_id = %download_id, "failed to make vod download: {}", error);
+
+        sqlx::query!(
+            "UPDATE vod_downloads SET status = $2, error = $3, updated_at = NOW(), completed_at = NOW() WHERE id = $1 AND status IN ($4, $5)",
+            download_id,
+            i64::from(Status::Failed),
+            "Failed to make the download",
+            i64::from(Status::Queued),
+            i64::from(Status::Running),
+        )
+        .execute(&*global.db)
+        .await?;
+    } else if result.done {
+        sqlx::query!(
+            "UPDATE vod_downloads SET status = $2, progress = 100, size_bytes = $3, expires_at = $4, updated_at = NOW(), completed_at = NOW() WHERE id = $1 AND status IN ($5, $6)",
+            download_id,
+            i64::from(Status::Completed),
+            result.size_bytes as i64,
+            Utc::now() + Duration::days(global.config.vod_downloads.retention_days as i64),
+            i64::from(Status::Queued),
+            i64::from(Status::Running),
+        )
+        .execute(&*global.db)
+        .await?;
+    } else {
+        sqlx::query!(
+            "UPDATE vod_downloads SET status = $2, progress = $3, updated_at = NOW() WHERE id = $1 AND status IN ($4, $5)",
+            download_id,
+            i64::from(Status::Running),
+            result.progress.min(100) as i64,
+            i64::from(Status::Queued),
+            i64::from(Status::Running),
+        )
+        .execute(&*global.db)
+        .await?;
+    }
+
+    Ok(())
+}
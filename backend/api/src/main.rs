@@ -1,9 +1,8 @@
-use std::{str::FromStr, sync::Arc, time::Duration};
+use std::{sync::Arc, time::Duration};
 
 use anyhow::{Context as _, Result};
 use common::{context::Context, logging, prelude::FutureTimeout, signal};
 use fred::types::ReconnectPolicy;
-use sqlx::{postgres::PgConnectOptions, ConnectOptions};
 use tokio::{select, signal::unix::SignalKind, time};
 
 mod api;
@@ -12,6 +11,7 @@ mod database;
 mod dataloader;
 mod global;
 mod grpc;
+mod integrations;
 mod pb;
 mod subscription;
 
@@ -29,14 +29,7 @@ async fn main() -> Result<()> {
 
     tracing::debug!("config: {:#?}", config);
 
-    let db = Arc::new(
-        sqlx::PgPool::connect_with(
-            PgConnectOptions::from_str(&config.database.uri)?
-                .disable_statement_logging()
-                .to_owned(),
-        )
-        .await?,
-    );
+    let db = Arc::new(common::database::connect(&config.database).await?);
 
     let (ctx, handler) = Context::new();
 
@@ -59,8 +52,34 @@ async fn main() -> Result<()> {
 
     let global = Arc::new(global::GlobalState::new(config, db, rmq, redis, ctx));
 
+    if global.config.sandbox {
+        tracing::warn!("running in sandbox mode, do not use this in production");
+        global
+            .seed_sandbox()
+            .await
+            .context("failed to seed sandbox")?;
+    }
+
+    // Rows are read the same way before and after they are migrated, so the API doesn't wait for it.
+    if global.config.encryption.encrypt_pii {
+        let global = global.clone();
+        tokio::spawn(async move {
+            match global.migrate_pii().await {
+                Ok(migrated) => tracing::info!(migrated, "encrypted personal data"),
+                Err(e) => tracing::error!("failed to encrypt personal data: {:#}", e),
+            }
+        });
+    }
+
+    tokio::spawn(common::database::monitor(
+        global.ctx.clone(),
+        global.db.clone(),
+        global.config.database.clone(),
+    ));
+
     let api_future = tokio::spawn(api::run(global.clone()));
     let grpc_future = tokio::spawn(grpc::run(global.clone()));
+    let integrations_future = tokio::spawn(integrations::run(global.clone()));
 
     // Listen on both sigint and sigterm and cancel the context when either is received
     let mut signal_handler = signal::SignalHandler::new()
@@ -70,6 +89,7 @@ async fn main() -> Result<()> {
     select! {
         r = api_future => tracing::error!("api stopped unexpectedly: {:?}", r),
         r = grpc_future => tracing::error!("grpc stopped unexpectedly: {:?}", r),
+        r = integrations_future => tracing::error!("integrations stopped unexpectedly: {:?}", r),
         r = global.rmq.handle_reconnects() => tracing::error!("rmq stopped unexpectedly: {:?}", r),
         r = global.subscription_manager.run(global.ctx.clone(), subscription_redis) => tracing::error!("subscription manager stopped unexpectedly: {:?}", r),
         _ = signal_handler.recv() => tracing::info!("shutting down"),
@@ -9,9 +9,24 @@ pub mod scuffle {
 
     pub mod events {
         tonic::include_proto!("scuffle.events");
+        include!(concat!(env!("OUT_DIR"), "/scuffle.events.subjects.rs"));
+    }
+
+    pub mod video {
+        tonic::include_proto!("scuffle.video");
     }
 }
 
 pub mod health {
     tonic::include_proto!("grpc.health.v1");
 }
+
+/// An event published on Redis, its subject is generated from the `@subject` of its proto definition.
+pub trait Event: prost::Message + Default {
+    /// The subject with `{}` in place of the id the event is published for.
+    const SUBJECT: &'static str;
+
+    fn subject(id: impl std::fmt::Display) -> String {
+        Self::SUBJECT.replacen("{}", &id.to_string(), 1)
+    }
+}
@@ -1,6 +1,11 @@
 use std::{
     collections::HashMap,
     ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
 use anyhow::Result;
@@ -8,42 +13,252 @@ use common::context::Context;
 use fred::{clients::SubscriberClient, prelude::PubsubInterface, types::RedisValue};
 use tokio::{
     select,
-    sync::{broadcast, mpsc, oneshot, Mutex},
+    sync::{
+        broadcast::{self, error::RecvError as BroadcastRecvError},
+        mpsc, oneshot, Mutex,
+    },
 };
 
+use crate::config::SubscriptionConfig;
+
 #[derive(Debug)]
 enum Event {
     Subscribe {
         topic: String,
-        tx: oneshot::Sender<broadcast::Receiver<RedisValue>>,
+        tx: oneshot::Sender<(
+            broadcast::Receiver<RedisValue>,
+            Arc<Counters>,
+            Arc<AtomicBool>,
+        )>,
     },
     Unsubscribe {
         topic: String,
     },
+    Stats {
+        tx: oneshot::Sender<Vec<TopicStats>>,
+    },
+}
+
+/// The counters of a subject, shared with its subscribers so evictions are counted where they happen.
+#[derive(Debug)]
+pub(crate) struct Counters {
+    since: Instant,
+    published: AtomicU64,
+    dropped: AtomicU64,
+    evicted: AtomicU64,
+}
+
+/// How the fan-out of a subject is doing, counted since it got its first subscriber.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicStats {
+    pub topic: String,
+    pub subscribers: usize,
+    pub shards: usize,
+    /// The most events waiting in the queue of one shard.
+    pub lag: usize,
+    /// The events received for the subject.
+    pub published: u64,
+    /// The events a shard was too far behind to take, counted once per shard. The subscribers of the shard are evicted.
+    pub dropped: u64,
+    /// The subscribers closed because they fell behind.
+    pub evicted: u64,
+    /// How long the subject has had subscribers.
+    pub age: Duration,
+}
+
+impl TopicStats {
+    /// Whether the subject is spread over the most shards and they are full, new subscribers then make every shard slower.
+    pub fn at_capacity(&self, config: &SubscriptionConfig) -> bool {
+        self.shards >= config.max_shards as usize
+            && self.subscribers >= self.shards * config.shard_size as usize
+    }
+}
+
+/// Picks the shard a new subscriber joins, the one with the fewest subscribers.
+/// Returns None if a new shard should be started because every shard is full.
+pub fn pick_shard(subscribers: &[usize], shard_size: usize, max_shards: usize) -> Option<usize> {
+    let emptiest = subscribers
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, count)| **count)
+        .map(|(i, _)| i);
+
+    match emptiest {
+        Some(i) if subscribers[i] < shard_size || subscribers.len() >= max_shards => Some(i),
+        _ => None,
+    }
+}
+
+/// A fan-out task and the subscribers it delivers to.
+struct Shard {
+    queue: mpsc::Sender<RedisValue>,
+    subscribers: broadcast::Sender<RedisValue>,
+    /// Set when the shard was dropped because its queue was full, its subscribers are then evicted once they read the queued events.
+    evicted: Arc<AtomicBool>,
+}
+
+impl Shard {
+    fn spawn(config: &SubscriptionConfig) -> Self {
+        let (queue, mut rx) = mpsc::channel::<RedisValue>(config.shard_queue.max(1) as usize);
+        let (subscribers, _) = broadcast::channel(config.subscriber_buffer.max(1) as usize);
+
+        // Waking the subscribers takes as long as there are subscribers, so it happens here and not in the manager.
+        // The task ends once the manager drops the shard.
+        let tx = subscribers.clone();
+        tokio::spawn(async move {
+            while let Some(value) = rx.recv().await {
+                tx.send(value).ok();
+            }
+        });
+
+        Self {
+            queue,
+            subscribers,
+            evicted: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// The subscribers of a subject, spread over shards so a subject with many of them does not hold up the others.
+pub(crate) struct Topic {
+    shards: Vec<Shard>,
+    counters: Arc<Counters>,
+}
+
+impl Topic {
+    pub(crate) fn new() -> Self {
+        Self {
+            shards: Vec::new(),
+            counters: Arc::new(Counters {
+                since: Instant::now(),
+                published: AtomicU64::new(0),
+                dropped: AtomicU64::new(0),
+                evicted: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub(crate) fn subscribe(
+        &mut self,
+        config: &SubscriptionConfig,
+    ) -> (
+        broadcast::Receiver<RedisValue>,
+        Arc<Counters>,
+        Arc<AtomicBool>,
+    ) {
+        let counts = self
+            .shards
+            .iter()
+            .map(|shard| shard.subscribers.receiver_count())
+            .collect::<Vec<_>>();
+
+        let i = match pick_shard(
+            &counts,
+            config.shard_size as usize,
+            config.max_shards as usize,
+        ) {
+            Some(i) => i,
+            None => {
+                self.shards.push(Shard::spawn(config));
+                self.shards.len() - 1
+            }
+        };
+
+        (
+            self.shards[i].subscribers.subscribe(),
+            self.counters.clone(),
+            self.shards[i].evicted.clone(),
+        )
+    }
+
+    /// Queues the event on every shard, returns how many were too far behind to take it.
+    /// Those shards are dropped, so their subscribers are evicted and subscribe again instead of missing events.
+    pub(crate) fn publish(&mut self, value: &RedisValue) -> usize {
+        let shards = self.shards.len();
+        self.shards
+            .retain(|shard| match shard.queue.try_send(value.clone()) {
+                Ok(()) => true,
+                Err(_) => {
+                    shard.evicted.store(true, Ordering::Relaxed);
+                    false
+                }
+            });
+        let dropped = shards - self.shards.len();
+
+        self.counters.published.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .dropped
+            .fetch_add(dropped as u64, Ordering::Relaxed);
+
+        dropped
+    }
+
+    fn stats(&self, topic: &str) -> TopicStats {
+        TopicStats {
+            topic: topic.to_string(),
+            subscribers: self
+                .shards
+                .iter()
+                .map(|shard| shard.subscribers.receiver_count())
+                .sum(),
+            shards: self.shards.len(),
+            lag: self
+                .shards
+                .iter()
+                .map(|shard| shard.queue.max_capacity() - shard.queue.capacity())
+                .max()
+                .unwrap_or_default(),
+            published: self.counters.published.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            evicted: self.counters.evicted.load(Ordering::Relaxed),
+            age: self.counters.since.elapsed(),
+        }
+    }
+
+    /// Stops the shards nobody listens to anymore, returns false once no shard is left.
+    fn prune(&mut self) -> bool {
+        self.shards
+            .retain(|shard| shard.subscribers.receiver_count() > 0);
+        !self.shards.is_empty()
+    }
 }
 
 pub struct SubscriptionManager {
+    config: SubscriptionConfig,
     events_tx: mpsc::UnboundedSender<Event>,
     events_rx: Mutex<mpsc::UnboundedReceiver<Event>>,
 }
 
-impl Default for SubscriptionManager {
-    fn default() -> Self {
+impl SubscriptionManager {
+    pub fn new(config: &SubscriptionConfig) -> Self {
         // Only one value is needed in the channel.
         // This is a way to get around we cannot await in a drop.
         let (events_tx, events_rx) = mpsc::unbounded_channel();
 
         Self {
+            config: config.clone(),
             events_rx: Mutex::new(events_rx),
             events_tx,
         }
     }
 }
 
+/// Why a subscription stopped delivering events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// The subscriber fell further behind than its buffer and was closed, it has to subscribe again.
+    Evicted,
+    /// The subscription manager stopped.
+    Closed,
+}
+
 pub struct SubscriberReceiver<'a> {
     topic: String,
     rx: broadcast::Receiver<RedisValue>,
     manager: &'a SubscriptionManager,
+    counters: Arc<Counters>,
+    shard_evicted: Arc<AtomicBool>,
+    evicted: bool,
 }
 
 impl Deref for SubscriberReceiver<'_> {
@@ -60,11 +275,38 @@ impl DerefMut for SubscriberReceiver<'_> {
     }
 }
 
+impl SubscriberReceiver<'_> {
+    /// Waits for the next event. A subscriber which missed events is evicted instead of skipping them,
+    /// every following call fails too.
+    pub async fn recv(&mut self) -> Result<RedisValue, RecvError> {
+        if self.evicted {
+            return Err(RecvError::Evicted);
+        }
+
+        match self.rx.recv().await {
+            Ok(value) => Ok(value),
+            Err(BroadcastRecvError::Lagged(missed)) => {
+                tracing::debug!(topic = %self.topic, missed, "evicted slow subscriber");
+                self.evicted = true;
+                self.counters.evicted.fetch_add(1, Ordering::Relaxed);
+                Err(RecvError::Evicted)
+            }
+            Err(BroadcastRecvError::Closed) if self.shard_evicted.load(Ordering::Relaxed) => {
+                tracing::debug!(topic = %self.topic, "evicted subscriber of a stuck shard");
+                self.evicted = true;
+                self.counters.evicted.fetch_add(1, Ordering::Relaxed);
+                Err(RecvError::Evicted)
+            }
+            Err(BroadcastRecvError::Closed) => Err(RecvError::Closed),
+        }
+    }
+}
+
 impl SubscriptionManager {
     pub async fn run(&self, ctx: Context, redis: SubscriberClient) -> Result<()> {
         let mut handle = redis.manage_subscriptions();
 
-        let mut topics = HashMap::<String, broadcast::Sender<RedisValue>>::new();
+        let mut topics = HashMap::<String, Topic>::new();
 
         let mut events_rx = self.events_rx.lock().await;
 
@@ -77,25 +319,27 @@ impl SubscriptionManager {
                         Event::Subscribe { topic, tx } => {
                             let topic = topic.to_lowercase();
 
-                            match topics.get(&topic) {
-                                Some(broadcast) => {
-                                    tx.send(broadcast.subscribe()).ok();
+                            match topics.get_mut(&topic) {
+                                Some(subs) => {
+                                    tx.send(subs.subscribe(&self.config)).ok();
                                 },
                                 None => {
-                                    let (btx, rx) = broadcast::channel(16);
-                                    if tx.send(rx).is_err() {
+                                    let mut subs = Topic::new();
+                                    if tx.send(subs.subscribe(&self.config)).is_err() {
                                         continue;
                                     }
 
-                                    topics.insert(topic.clone(), btx);
+                                    topics.insert(topic.clone(), subs);
 
                                     redis.subscribe(&topic).await?;
                                 }
                             };
                         }
                         Event::Unsubscribe { topic } => {
-                            if let Some(btx) = topics.get_mut(&topic) {
-                                if btx.receiver_count() == 0 {
+                            let topic = topic.to_lowercase();
+
+                            if let Some(subs) = topics.get_mut(&topic) {
+                                if !subs.prune() {
                                     topics.remove(&topic);
                                     redis.unsubscribe(&topic).await?;
                                 }
@@ -105,6 +349,9 @@ impl SubscriptionManager {
                                 break;
                             }
                         }
+                        Event::Stats { tx } => {
+                            tx.send(topics.iter().map(|(topic, subs)| subs.stats(topic)).collect()).ok();
+                        }
                     }
                 }
                 message = messages.recv() => {
@@ -112,11 +359,15 @@ impl SubscriptionManager {
 
                     let topic = message.channel.to_string().to_lowercase();
 
-                    let Some(subs) = topics.get(&topic) else {
+                    let Some(subs) = topics.get_mut(&topic) else {
                         continue;
                     };
 
-                    subs.send(message.value).ok();
+                    // A shard whose queue is full is stuck, its subscribers are evicted rather than holding up everyone else.
+                    let dropped = subs.publish(&message.value);
+                    if dropped > 0 {
+                        tracing::warn!(topic = %topic, dropped, "fan-out shards are behind, evicted their subscribers");
+                    }
                 }
                 r = &mut handle => {
                     r?;
@@ -139,14 +390,26 @@ impl SubscriptionManager {
             tx,
         })?;
 
-        let rx = rx.await?;
+        let (rx, counters, shard_evicted) = rx.await?;
 
         Ok(SubscriberReceiver {
             topic: topic.to_string(),
             rx,
             manager: self,
+            counters,
+            shard_evicted,
+            evicted: false,
         })
     }
+
+    /// Takes a snapshot of the fan-out of every subject with subscribers.
+    pub async fn stats(&self) -> Result<Vec<TopicStats>> {
+        let (tx, rx) = oneshot::channel();
+
+        self.events_tx.send(Event::Stats { tx })?;
+
+        Ok(rx.await?)
+    }
 }
 
 impl Drop for SubscriberReceiver<'_> {
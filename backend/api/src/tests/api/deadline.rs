@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use hyper::{HeaderMap, HeaderValue};
+
+use crate::{
+    api::deadline::{budget, remaining, run, timeout, X_REQUEST_TIMEOUT},
+    config::ApiConfig,
+};
+
+#[test]
+fn test_budget() {
+    let config = ApiConfig {
+        request_timeout: 30,
+        ..Default::default()
+    };
+
+    let tests = vec![
+        (None, Duration::from_secs(30)),
+        (Some("2500"), Duration::from_millis(2500)),
+        // Clients can't extend the deadline past the configured timeout.
+        (Some("60000"), Duration::from_secs(30)),
+        (Some("soon"), Duration::from_secs(30)),
+    ];
+
+    for (header, expected) in tests {
+        let mut headers = HeaderMap::new();
+        if let Some(header) = header {
+            headers.insert(X_REQUEST_TIMEOUT, HeaderValue::from_static(header));
+        }
+
+        assert_eq!(budget(&config, &headers), expected, "{:?}", header);
+    }
+}
+
+#[tokio::test]
+async fn test_run() {
+    assert_eq!(remaining(), None);
+    assert_eq!(timeout(Duration::from_secs(10)), Duration::from_secs(10));
+
+    let result = run(Duration::from_secs(1), async {
+        let remaining = remaining().unwrap();
+        assert!(remaining <= Duration::from_secs(1));
+
+        // Calls made for the request are cut short to its deadline.
+        assert!(timeout(Duration::from_secs(10)) <= Duration::from_secs(1));
+        assert_eq!(
+            timeout(Duration::from_millis(10)),
+            Duration::from_millis(10)
+        );
+    })
+    .await;
+    assert!(result.is_ok());
+
+    let result = run(
+        Duration::from_millis(10),
+        tokio::time::sleep(Duration::from_secs(10)),
+    )
+    .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use common::prelude::FutureTimeout;
+use hyper::{Method, StatusCode};
+
+use crate::{
+    api::{
+        deprecation::{self, Client, Deprecation},
+        run,
+    },
+    config::{ApiConfig, AppConfig, DeprecatedSurfaceConfig, DeprecationConfig},
+    tests::global::mock_global_state,
+};
+
+fn surface(surface: &str, sunset: Option<&str>) -> DeprecatedSurfaceConfig {
+    DeprecatedSurfaceConfig {
+        surface: surface.to_string(),
+        deprecated_at: "2023-08-01".to_string(),
+        sunset: sunset.map(|s| s.to_string()),
+        replacement: None,
+    }
+}
+
+#[test]
+fn test_path_matches() {
+    let tests = [
+        ("/v1/control/stats", "/v1/control/stats", true),
+        ("/v1/control/stats", "/v1/control/stats/", true),
+        ("/v1/control/stats", "/v1/control/ws", false),
+        (
+            "/v1/revenue/:channel_id/export",
+            "/v1/revenue/abc/export",
+            true,
+        ),
+        (
+            "/v1/revenue/:channel_id/export",
+            "/v1/revenue//export",
+            false,
+        ),
+        ("/v1/revenue/:channel_id/export", "/v1/revenue/abc", false),
+    ];
+
+    for (pattern, path, matches) in tests {
+        assert_eq!(
+            deprecation::path_matches(pattern, path),
+            matches,
+            "pattern: {}, path: {}",
+            pattern,
+            path
+        );
+    }
+}
+
+#[test]
+fn test_find() {
+    let config = DeprecationConfig {
+        surfaces: vec![
+            surface("Query.noop", None),
+            surface("GET /v1/control/stats", None),
+            surface("User.oldField", Some("Nov 1st")),
+        ],
+        ..Default::default()
+    };
+
+    assert!(deprecation::find_field(&config, "Query", "noop").is_some());
+    assert!(deprecation::find_field(&config, "Query", "channel").is_none());
+    assert!(deprecation::find_field(&config, "Subscription", "noop").is_none());
+    // Misconfigured days are ignored.
+    assert!(deprecation::find_field(&config, "User", "oldField").is_none());
+
+    assert!(deprecation::find_endpoint(&config, &Method::GET, "/v1/control/stats").is_some());
+    assert!(deprecation::find_endpoint(&config, &Method::POST, "/v1/control/stats").is_none());
+}
+
+#[test]
+fn test_headers() {
+    let config = DeprecationConfig {
+        changelog_url: "https://scuffle.tv/docs/changelog".to_string(),
+        surfaces: Vec::new(),
+    };
+
+    let deprecation = |deprecated_at: (i32, u32, u32), sunset: Option<(i32, u32, u32)>| {
+        let day = |(y, m, d)| Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap();
+        Deprecation {
+            surface: "Query.noop".to_string(),
+            deprecated_at: day(deprecated_at),
+            sunset: sunset.map(day),
+            replacement: None,
+        }
+    };
+
+    assert_eq!(deprecation::headers(&config, &[]), Vec::new());
+
+    assert_eq!(
+        deprecation::headers(&config, &[deprecation((2023, 8, 1), None)]),
+        vec![
+            ("Deprecation", "@1690848000".to_string()),
+            (
+                "Link",
+                "<https://scuffle.tv/docs/changelog>; rel=\"deprecation\"".to_string()
+            ),
+        ]
+    );
+
+    // The earliest days are announced.
+    assert_eq!(
+        deprecation::headers(
+            &DeprecationConfig {
+                changelog_url: String::new(),
+                ..config
+            },
+            &[
+                deprecation((2023, 8, 1), None),
+                deprecation((2023, 7, 1), Some((2023, 12, 1))),
+                deprecation((2023, 8, 10), Some((2023, 11, 1))),
+            ]
+        ),
+        vec![
+            ("Deprecation", "@1688169600".to_string()),
+            ("Sunset", "Wed, 01 Nov 2023 00:00:00 GMT".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_client_key() {
+    let id = uuid::Uuid::nil();
+
+    assert_eq!(
+        Client::AccessToken { id, user_id: id }.key(),
+        "token:00000000-0000-0000-0000-000000000000"
+    );
+    assert_eq!(
+        Client::Session { id, user_id: id }.key(),
+        "session:00000000-0000-0000-0000-000000000000"
+    );
+    assert_eq!(Client::Anonymous.key(), "anonymous");
+    assert_eq!(Client::Anonymous.user_id(), None);
+}
+
+#[tokio::test]
+async fn test_deprecated_endpoint_headers() {
+    let port = portpicker::pick_unused_port().expect("failed to pick port");
+    let (global, handler) = mock_global_state(AppConfig {
+        api: ApiConfig {
+            bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
+            tls: None,
+            ..Default::default()
+        },
+        deprecations: DeprecationConfig {
+            surfaces: vec![surface("GET /v1/health", Some("2023-11-01"))],
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    let handle = tokio::spawn(run(global));
+
+    // We need to wait for the server to start
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!("http://localhost:{}/v1/health", port))
+        .send()
+        .await
+        .expect("failed to get health");
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["deprecation"], "@1690848000");
+    assert_eq!(resp.headers()["sunset"], "Wed, 01 Nov 2023 00:00:00 GMT");
+
+    // The client uses Keep-Alive, so we need to drop it to release the global context
+    drop(client);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+    handle
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel api")
+        .expect("api failed")
+        .expect("api failed");
+}
@@ -9,8 +9,11 @@ use crate::{
     tests::global::mock_global_state,
 };
 
+mod deadline;
+mod deprecation;
 mod errors;
 mod v1;
+mod webhook;
 
 #[tokio::test]
 async fn test_api_v6() {
@@ -19,6 +22,7 @@ async fn test_api_v6() {
         api: ApiConfig {
             bind_address: format!("[::]:{}", port).parse().unwrap(),
             tls: None,
+            ..Default::default()
         },
         ..Default::default()
     })
@@ -64,6 +68,7 @@ async fn test_api_v4() {
         api: ApiConfig {
             bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
             tls: None,
+            ..Default::default()
         },
         ..Default::default()
     })
@@ -0,0 +1,74 @@
+use crate::api::v1::csp::{parse_reports, CspViolation};
+
+#[test]
+fn test_parse_legacy_report() {
+    let body = br#"{
+        "csp-report": {
+            "document-uri": "https://scuffle.tv/troy",
+            "violated-directive": "script-src 'self'",
+            "blocked-uri": "https://evil.example/x.js",
+            "disposition": "enforce",
+            "source-file": "https://scuffle.tv/app.js",
+            "line-number": 12
+        }
+    }"#;
+
+    assert_eq!(
+        parse_reports("application/csp-report", body).unwrap(),
+        vec![CspViolation {
+            document_uri: "https://scuffle.tv/troy".to_string(),
+            directive: "script-src".to_string(),
+            blocked_uri: "https://evil.example/x.js".to_string(),
+            disposition: "enforce".to_string(),
+            source_file: "https://scuffle.tv/app.js".to_string(),
+            line_number: 12,
+        }]
+    );
+}
+
+#[test]
+fn test_parse_reporting_api() {
+    let body = br#"[
+        {
+            "type": "csp-violation",
+            "body": {
+                "documentURL": "https://scuffle.tv/embed/troy",
+                "effectiveDirective": "frame-ancestors",
+                "blockedURL": "https://other.example",
+                "disposition": "report"
+            }
+        },
+        {
+            "type": "deprecation",
+            "body": {}
+        }
+    ]"#;
+
+    assert_eq!(
+        parse_reports("application/reports+json", body).unwrap(),
+        vec![CspViolation {
+            document_uri: "https://scuffle.tv/embed/troy".to_string(),
+            directive: "frame-ancestors".to_string(),
+            blocked_uri: "https://other.example".to_string(),
+            disposition: "report".to_string(),
+            ..Default::default()
+        }]
+    );
+}
+
+#[test]
+fn test_parse_invalid_report() {
+    let tests = vec![
+        ("application/csp-report", "{}"),
+        ("application/csp-report", "not json"),
+        ("application/reports+json", "{\"type\": \"csp-violation\"}"),
+    ];
+
+    for (content_type, body) in tests {
+        assert!(
+            parse_reports(content_type, body.as_bytes()).is_err(),
+            "{}",
+            body
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    api::v1::gql::ext::RequestExt,
+    database::{personal_access_token, session, user},
+};
+use async_graphql::{Request, Variables};
+use chrono::Utc;
+use serial_test::serial;
+use std::sync::Arc;
+
+use crate::{
+    api::v1::gql::{request_context::RequestContext, schema},
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_create_access_token() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+    let schema = schema();
+
+    let query = r#"
+        mutation {
+            accessToken {
+                create(name: "Stream Deck", scopes: [CONTROL]) {
+                    accessToken {
+                        name
+                        scopes
+                        expiresAt
+                    }
+                    secret
+                }
+            }
+        }
+    "#;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    // Creating a token requires a recent reauthentication.
+    let res = schema
+        .execute(
+            Request::from(query)
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+        .await;
+
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You need to reauthenticate to do this"
+    );
+
+    let reauthenticate = r#"
+        mutation Reauthenticate($password: String!) {
+            auth {
+                reauthenticate(password: $password)
+            }
+        }
+    "#;
+
+    let res = schema
+        .execute(
+            Request::from(reauthenticate)
+                .variables(Variables::from_json(
+                    serde_json::json!({ "password": "wrong" }),
+                ))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+        .await;
+
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(res.errors[0].message, "InvalidInput: Invalid password");
+
+    let res = schema
+        .execute(
+            Request::from(reauthenticate)
+                .variables(Variables::from_json(
+                    serde_json::json!({ "password": "test" }),
+                ))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+        .await;
+
+    assert_eq!(res.errors.len(), 0);
+
+    let res = schema
+        .execute(
+            Request::from(query)
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+        .await;
+
+    assert_eq!(res.errors.len(), 0);
+    let json = res.data.into_json().unwrap();
+    let created = &json["accessToken"]["create"];
+    assert_eq!(
+        created["accessToken"],
+        serde_json::json!({ "name": "Stream Deck", "scopes": ["CONTROL"], "expiresAt": null })
+    );
+
+    let secret = created["secret"].as_str().unwrap();
+    assert!(secret.starts_with(personal_access_token::TOKEN_PREFIX));
+
+    let token = sqlx::query_as!(
+        personal_access_token::Model,
+        "SELECT * FROM personal_access_tokens WHERE user_id = $1",
+        user.id,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    assert_eq!(token.token_hash, personal_access_token::hash_token(secret));
+    assert!(token.allows(personal_access_token::Scope::Control));
+}
@@ -0,0 +1,248 @@
+use serial_test::serial;
+
+use crate::{
+    database::{admin_approval, global_role, session, user_suspension},
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_permanent_suspension_approval() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (_, first_session) = create_user(&global, "first").await;
+    let (_, second_session) = create_user(&global, "second").await;
+    let (suspended, suspended_session) = create_user(&global, "suspended").await;
+
+    // Permanent suspensions can't be done by a single admin anymore.
+    let res = execute(
+        &global,
+        Some(&first_session),
+        global_role::Permission::Admin,
+        r#"
+            mutation Suspend($userId: UUID!) {
+                suspension {
+                    suspend(userId: $userId, reason: "Spam") {
+                        id
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "userId": suspended.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Permanent suspensions need a second admin, propose them with approval.proposePermanentSuspension"
+    );
+
+    let propose = r#"
+        mutation Propose($targetId: UUID!) {
+            approval {
+                proposePermanentSuspension(targetId: $targetId, reason: "Spam") {
+                    id
+                    status
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&first_session),
+        global_role::Permission::Admin,
+        propose,
+        serde_json::json!({ "targetId": suspended.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(
+        json["approval"]["proposePermanentSuspension"]["status"],
+        "PENDING"
+    );
+    let approval_id = json["approval"]["proposePermanentSuspension"]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let res = execute(
+        &global,
+        Some(&second_session),
+        global_role::Permission::Admin,
+        propose,
+        serde_json::json!({ "targetId": suspended.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: This action is already waiting for approval"
+    );
+
+    let approve = r#"
+        mutation Approve($id: UUID!) {
+            approval {
+                approve(id: $id) {
+                    status
+                    decidedBy
+                }
+            }
+        }
+    "#;
+
+    // Nothing happens until a second admin approves.
+    let res = execute(
+        &global,
+        Some(&first_session),
+        global_role::Permission::Admin,
+        approve,
+        serde_json::json!({ "id": approval_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: A second admin has to approve this"
+    );
+    assert!(user_suspension::active(&global.db, suspended.id)
+        .await
+        .unwrap()
+        .is_none());
+
+    let res = execute(
+        &global,
+        Some(&second_session),
+        global_role::Permission::Admin,
+        approve,
+        serde_json::json!({ "id": approval_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["approval"]["approve"]["status"], "APPROVED");
+    assert_eq!(
+        json["approval"]["approve"]["decidedBy"],
+        second_session.user_id.to_string()
+    );
+
+    let suspension = user_suspension::active(&global.db, suspended.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(suspension.expires_at.is_none());
+    assert_eq!(suspension.suspended_by, Some(first_session.user_id));
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "SELECT * FROM sessions WHERE id = $1",
+        suspended_session.id,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert!(!session.is_valid());
+
+    // Both admins are in the audit trail.
+    let res = execute(
+        &global,
+        Some(&first_session),
+        global_role::Permission::Admin,
+        r#"
+            query History($id: UUID!) {
+                approval {
+                    history(id: $id) {
+                        action
+                        actorId
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "id": approval_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["approval"]["history"],
+        serde_json::json!([
+            { "action": "PROPOSED", "actorId": first_session.user_id.to_string() },
+            { "action": "APPROVED", "actorId": second_session.user_id.to_string() },
+        ])
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_approval_expired() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (_, first_session) = create_user(&global, "first").await;
+    let (_, second_session) = create_user(&global, "second").await;
+    let (suspended, _) = create_user(&global, "suspended").await;
+
+    let approval = global
+        .propose_approval(
+            first_session.user_id,
+            admin_approval::Kind::PermanentSuspension,
+            suspended.id,
+            "Spam",
+        )
+        .await
+        .unwrap();
+
+    sqlx::query!(
+        "UPDATE admin_approvals SET expires_at = NOW() - INTERVAL '1 minute' WHERE id = $1",
+        approval.id
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let res = execute(
+        &global,
+        Some(&second_session),
+        global_role::Permission::Admin,
+        r#"
+            mutation Approve($id: UUID!) {
+                approval {
+                    approve(id: $id) {
+                        status
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "id": approval.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Approval expired, the action has to be proposed again"
+    );
+
+    // The expired proposal does not block a new one.
+    let approval = global
+        .propose_approval(
+            second_session.user_id,
+            admin_approval::Kind::PermanentSuspension,
+            suspended.id,
+            "Spam",
+        )
+        .await
+        .unwrap();
+    assert_eq!(approval.status, admin_approval::Status::Pending);
+}
@@ -1,6 +1,6 @@
 use std::{sync::Arc, time::Duration};
 
-use crate::database::{session, user};
+use crate::database::{login_link, session, user};
 use async_graphql::{Name, Request, Variables};
 use chrono::Utc;
 use common::prelude::FutureTimeout;
@@ -12,8 +12,8 @@ use crate::{
         gql::{ext::RequestExt, request_context::RequestContext, schema},
         jwt::JwtState,
     },
-    config::{AppConfig, TurnstileConfig},
-    tests::global::{mock_global_state, turnstile::mock_turnstile},
+    config::{AppConfig, MailConfig, OAuthConfig, PasswordResetConfig, TurnstileConfig},
+    tests::global::{mock_global_state, oauth::mock_oauth_provider, turnstile::mock_turnstile},
 };
 
 #[serial]
@@ -722,3 +722,474 @@ async fn test_serial_logout_with_token() {
         .await
         .expect("failed to cancel context");
 }
+
+#[serial]
+#[tokio::test]
+async fn test_serial_login_with_link() {
+    let (mut rx, addr, h1) = mock_turnstile().await;
+    // Nothing listens on this port, the request still succeeds when the email can't be sent.
+    let port = portpicker::pick_unused_port().expect("failed to pick port");
+    let (global, handler) = mock_global_state(AppConfig {
+        turnstile: TurnstileConfig {
+            url: addr,
+            secret_key: "DUMMY_KEY__DEADBEEF".to_string(),
+        },
+        mail: MailConfig {
+            provider: "http".to_string(),
+            url: format!("http://127.0.0.1:{}", port),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    // Inserted without an email hash, like accounts created before emails were hashed.
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "admin",
+        "admin@admin.com",
+        user::hash_password("admin"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let h2 = tokio::spawn(async move {
+        let (_, resp) = rx.recv().await.unwrap();
+        resp.send(true).unwrap();
+    });
+
+    let execute = |query: &'static str, variables: serde_json::Value| {
+        let global = global.clone();
+        async move {
+            schema()
+                .execute(
+                    Request::from(query)
+                        .variables(Variables::from_json(variables))
+                        .provide_global(global)
+                        .provide_context(Arc::new(RequestContext::new(false))),
+                )
+                .timeout(Duration::from_secs(5))
+                .await
+                .unwrap()
+        }
+    };
+
+    let res = execute(
+        r#"
+            mutation {
+                auth {
+                    requestLoginLink(email: "Admin@admin.com", captchaToken: "1234") {
+                        deviceToken
+                    }
+                }
+            }
+        "#,
+        json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let device_token = res.data.into_json().unwrap()["auth"]["requestLoginLink"]["deviceToken"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let login = r#"
+        mutation Login($deviceToken: String!) {
+            auth {
+                loginWithLink(deviceToken: $deviceToken) {
+                    userId
+                }
+            }
+        }
+    "#;
+
+    let res = execute(login, json!({ "deviceToken": device_token })).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Login link was not opened yet"
+    );
+
+    // The emailed token is only stored hashed, so the test swaps in one it knows.
+    sqlx::query!(
+        "UPDATE login_links SET token_hash = $1 WHERE user_id = $2",
+        login_link::hash_token("emailed"),
+        user.id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let res = execute(
+        r#"
+            mutation {
+                auth {
+                    confirmLoginLink(token: "emailed")
+                }
+            }
+        "#,
+        json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(login, json!({ "deviceToken": device_token })).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        json!({ "auth": { "loginWithLink": { "userId": user.id.to_string() } } })
+    );
+
+    // Links only log in once.
+    let res = execute(login, json!({ "deviceToken": device_token })).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Login link is invalid or expired"
+    );
+
+    h1.abort();
+
+    h1.timeout(Duration::from_secs(1)).await.unwrap().ok(); // ignore error because we aborted it
+    h2.timeout(Duration::from_secs(1)).await.unwrap().unwrap();
+
+    drop(execute);
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
+
+#[serial]
+#[tokio::test]
+async fn test_serial_reset_password() {
+    let (mut rx, addr, h1) = mock_turnstile().await;
+    // Nothing listens on this port, the request still succeeds when the email can't be sent.
+    let port = portpicker::pick_unused_port().expect("failed to pick port");
+    let (global, handler) = mock_global_state(AppConfig {
+        turnstile: TurnstileConfig {
+            url: addr,
+            secret_key: "DUMMY_KEY__DEADBEEF".to_string(),
+        },
+        mail: MailConfig {
+            provider: "http".to_string(),
+            url: format!("http://127.0.0.1:{}", port),
+            ..Default::default()
+        },
+        password_resets: PasswordResetConfig {
+            max_requests: 1,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "admin",
+        "admin@admin.com",
+        user::hash_password("admin"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let execute = |query: &'static str, variables: serde_json::Value| {
+        let global = global.clone();
+        async move {
+            schema()
+                .execute(
+                    Request::from(query)
+                        .variables(Variables::from_json(variables))
+                        .provide_global(global)
+                        .provide_context(Arc::new(RequestContext::new(false))),
+                )
+                .timeout(Duration::from_secs(5))
+                .await
+                .unwrap()
+        }
+    };
+
+    let h2 = tokio::spawn(async move {
+        let (_, resp) = rx.recv().await.unwrap();
+        resp.send(false).unwrap();
+        for _ in 0..3 {
+            let (_, resp) = rx.recv().await.unwrap();
+            resp.send(true).unwrap();
+        }
+    });
+
+    let request = r#"
+        mutation Request($email: String!) {
+            auth {
+                requestPasswordReset(email: $email, captchaToken: "1234")
+            }
+        }
+    "#;
+
+    let res = execute(request, json!({ "email": "admin@admin.com" })).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Captcha token is not valid"
+    );
+
+    // Unknown emails look the same to the client.
+    let res = execute(request, json!({ "email": "nobody@admin.com" })).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(request, json!({ "email": "Admin@admin.com" })).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(request, json!({ "email": "admin@admin.com" })).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Too many password resets were requested, try again later"
+    );
+
+    // The emailed token is only stored hashed, so the test swaps in one it knows.
+    sqlx::query!(
+        "UPDATE password_resets SET token_hash = $1 WHERE user_id = $2",
+        login_link::hash_token("emailed"),
+        user.id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let reset = r#"
+        mutation Reset($token: String!, $newPassword: String!) {
+            auth {
+                resetPassword(token: $token, newPassword: $newPassword)
+            }
+        }
+    "#;
+
+    let res = execute(
+        reset,
+        json!({ "token": "unknown", "newPassword": "N3w-password!" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Password reset is invalid or expired"
+    );
+
+    let res = execute(
+        reset,
+        json!({ "token": "emailed", "newPassword": "N3w-password!" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let updated = sqlx::query_as!(user::Model, "SELECT * FROM users WHERE id = $1", user.id)
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+    assert!(updated.verify_password("N3w-password!"));
+    assert!(!updated.verify_password("admin"));
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "SELECT * FROM sessions WHERE id = $1",
+        session.id
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert!(!session.is_valid());
+
+    // Tokens only work once.
+    let res = execute(
+        reset,
+        json!({ "token": "emailed", "newPassword": "An0ther-password!" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Password reset is invalid or expired"
+    );
+
+    h1.abort();
+
+    h1.timeout(Duration::from_secs(1)).await.unwrap().ok(); // ignore error because we aborted it
+    h2.timeout(Duration::from_secs(1)).await.unwrap().unwrap();
+
+    drop(execute);
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
+
+#[serial]
+#[tokio::test]
+async fn test_serial_login_with_external_account() {
+    let (github, h1) = mock_oauth_provider().await;
+    let (global, handler) = mock_global_state(AppConfig {
+        oauth: OAuthConfig {
+            github,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "admin",
+        "admin@admin.com",
+        user::hash_password("admin"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at, elevated_until) VALUES ($1, $2, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let execute = |query: &'static str, variables: serde_json::Value, logged_in: bool| {
+        let global = global.clone();
+        let session = session.clone();
+        async move {
+            let ctx = Arc::new(RequestContext::new(false));
+            if logged_in {
+                ctx.set_session(Some((session, Default::default())));
+            }
+
+            schema()
+                .execute(
+                    Request::from(query)
+                        .variables(Variables::from_json(variables))
+                        .provide_global(global)
+                        .provide_context(ctx),
+                )
+                .timeout(Duration::from_secs(5))
+                .await
+                .unwrap()
+        }
+    };
+
+    let link = r#"
+        mutation Link($provider: ExternalProvider!, $code: String!) {
+            user {
+                linkExternalAccount(provider: $provider, code: $code, redirectUri: "https://scuffle.tv/oauth") {
+                    providerUsername
+                }
+            }
+        }
+    "#;
+    let login = r#"
+        mutation Login($code: String!) {
+            auth {
+                loginWithExternalAccount(provider: GITHUB, code: $code, redirectUri: "https://scuffle.tv/oauth", updateContext: false) {
+                    userId
+                }
+            }
+        }
+    "#;
+
+    // Accounts have to be linked before they can log in.
+    let res = execute(login, json!({ "code": "good" }), false).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: No account is linked to this GitHub account"
+    );
+
+    let res = execute(link, json!({ "provider": "GOOGLE", "code": "good" }), true).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Logging in with Google is not enabled"
+    );
+
+    let res = execute(link, json!({ "provider": "GITHUB", "code": "bad" }), true).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Authorization code is invalid or expired"
+    );
+
+    let res = execute(link, json!({ "provider": "GITHUB", "code": "good" }), true).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        json!({ "user": { "linkExternalAccount": { "providerUsername": "troy" } } })
+    );
+
+    let res = execute(link, json!({ "provider": "GITHUB", "code": "good" }), true).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You already linked a GitHub account"
+    );
+
+    let res = execute(login, json!({ "code": "good" }), false).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        json!({ "auth": { "loginWithExternalAccount": { "userId": user.id.to_string() } } })
+    );
+
+    let sessions = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM sessions WHERE user_id = $1"#,
+        user.id
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(sessions, 2);
+
+    h1.abort();
+    drop(execute);
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
@@ -0,0 +1,203 @@
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    database::{chat_ban, global_role},
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_ban_appeal_accepted() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (channel, channel_session) = create_user(&global, "channel").await;
+    let (banned, banned_session) = create_user(&global, "banned").await;
+
+    sqlx::query!(
+        "INSERT INTO chat_bans (channel_id, user_id, banned_by) VALUES ($1, $2, $1)",
+        channel.id,
+        banned.id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let submit = r#"
+        mutation Submit($channelId: UUID!, $message: String!) {
+            banAppeal {
+                submit(channelId: $channelId, message: $message) {
+                    id
+                    status
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&banned_session),
+        global_role::Permission::default(),
+        submit,
+        serde_json::json!({ "channelId": channel.id, "message": "I was hacked" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["banAppeal"]["submit"]["status"], "PENDING");
+    let appeal_id = json["banAppeal"]["submit"]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Every user gets a single appeal per channel.
+    let res = execute(
+        &global,
+        Some(&banned_session),
+        global_role::Permission::default(),
+        submit,
+        serde_json::json!({ "channelId": channel.id, "message": "Please" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You already appealed your ban in this channel"
+    );
+
+    let accept = r#"
+        mutation Accept($id: UUID!, $note: String!) {
+            banAppeal {
+                accept(id: $id, note: $note) {
+                    status
+                    reviewNote
+                }
+            }
+        }
+    "#;
+
+    // Only the channel can review its appeals.
+    let res = execute(
+        &global,
+        Some(&banned_session),
+        global_role::Permission::default(),
+        accept,
+        serde_json::json!({ "id": appeal_id, "note": "ok" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        accept,
+        serde_json::json!({ "id": appeal_id, "note": "Welcome back" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["banAppeal"]["accept"]["status"], "ACCEPTED");
+    assert_eq!(json["banAppeal"]["accept"]["reviewNote"], "Welcome back");
+
+    assert!(!chat_ban::is_banned(&global.db, channel.id, banned.id)
+        .await
+        .unwrap());
+
+    let history = r#"
+        query History($id: UUID!) {
+            banAppeal {
+                history(id: $id) {
+                    action
+                    actorId
+                    note
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        history,
+        serde_json::json!({ "id": appeal_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["banAppeal"]["history"],
+        serde_json::json!([
+            { "action": "SUBMITTED", "actorId": banned.id, "note": "I was hacked" },
+            { "action": "ACCEPTED", "actorId": channel.id, "note": "Welcome back" },
+        ])
+    );
+
+    // Reviewed appeals can't be reviewed again.
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        accept,
+        serde_json::json!({ "id": appeal_id, "note": "" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Appeal was already reviewed"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_ban_appeal_not_banned() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (_, session) = create_user(&global, "test").await;
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::default(),
+        r#"
+            mutation Submit($channelId: UUID!, $message: String!) {
+                banAppeal {
+                    submit(channelId: $channelId, message: $message) {
+                        id
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "channelId": Uuid::new_v4(), "message": "Unban me" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You are not banned from this channel"
+    );
+}
@@ -0,0 +1,274 @@
+use serial_test::serial;
+
+use crate::{
+    config::{AppConfig, ChatConfig},
+    database::global_role,
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_bot_verification() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        chat: ChatConfig {
+            rate_limit: 1,
+            verified_bot_rate_limit: 2,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (bot, bot_session) = create_user(&global, "bot").await;
+    let (_, admin_session) = create_user(&global, "admin").await;
+    let (broadcaster, viewer_session) = create_user(&global, "broadcaster").await;
+
+    let apply = r#"
+        mutation Apply($websiteUrl: String!) {
+            bot {
+                apply(description: "Posts the schedule", websiteUrl: $websiteUrl) {
+                    id
+                    status
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&bot_session),
+        global_role::Permission::default(),
+        apply,
+        serde_json::json!({ "websiteUrl": "not a url" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Url must not contain spaces"
+    );
+
+    let res = execute(
+        &global,
+        Some(&bot_session),
+        global_role::Permission::default(),
+        apply,
+        serde_json::json!({ "websiteUrl": "https://bot.example" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["bot"]["apply"]["status"], "PENDING");
+    let application_id = json["bot"]["apply"]["id"].as_str().unwrap().to_string();
+
+    // Only one application can wait for review.
+    let res = execute(
+        &global,
+        Some(&bot_session),
+        global_role::Permission::default(),
+        apply,
+        serde_json::json!({ "websiteUrl": "" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Your bot is already verified or waiting for review"
+    );
+
+    let approve = r#"
+        mutation Approve($id: UUID!) {
+            bot {
+                approve(id: $id) {
+                    status
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&bot_session),
+        global_role::Permission::default(),
+        approve,
+        serde_json::json!({ "id": application_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You need to be an admin"
+    );
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        approve,
+        serde_json::json!({ "id": application_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["bot"]["approve"]["status"],
+        "APPROVED"
+    );
+
+    let send = r#"
+        mutation Send($channelId: UUID!) {
+            chat {
+                sendMessage(channelId: $channelId, content: "hello") {
+                    authorVerifiedBot
+                }
+            }
+        }
+    "#;
+
+    // Verified bots get the higher rate limit and the badge.
+    for _ in 0..2 {
+        let res = execute(
+            &global,
+            Some(&bot_session),
+            global_role::Permission::default(),
+            send,
+            serde_json::json!({ "channelId": broadcaster.id }),
+        )
+        .await;
+        assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+        assert_eq!(
+            res.data.into_json().unwrap()["chat"]["sendMessage"]["authorVerifiedBot"],
+            true
+        );
+    }
+
+    // Everyone else gets the lower one.
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        send,
+        serde_json::json!({ "channelId": bot.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["chat"]["sendMessage"]["authorVerifiedBot"],
+        false
+    );
+
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        send,
+        serde_json::json!({ "channelId": bot.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert!(res.errors[0]
+        .message
+        .starts_with("InvalidInput: You are sending messages too fast"));
+
+    let res = execute(
+        &global,
+        Some(&bot_session),
+        global_role::Permission::default(),
+        send,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert!(res.errors[0]
+        .message
+        .starts_with("InvalidInput: You are sending messages too fast"));
+
+    let active = r#"
+        query Active($channelId: UUID!, $userId: UUID!) {
+            bot {
+                activeInChannel(channelId: $channelId) {
+                    userId
+                }
+            }
+            userById(id: $userId) {
+                verifiedBot
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        active,
+        serde_json::json!({ "channelId": broadcaster.id, "userId": bot.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({
+            "bot": { "activeInChannel": [{ "userId": bot.id }] },
+            "userById": { "verifiedBot": true },
+        })
+    );
+
+    let revoke = r#"
+        mutation Revoke($userId: UUID!) {
+            bot {
+                revoke(userId: $userId, note: "Spam") {
+                    status
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        revoke,
+        serde_json::json!({ "userId": bot.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["bot"]["revoke"]["status"],
+        "REVOKED"
+    );
+
+    // A revoked bot is no longer listed, it can apply again.
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        active,
+        serde_json::json!({ "channelId": broadcaster.id, "userId": bot.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({
+            "bot": { "activeInChannel": [] },
+            "userById": { "verifiedBot": false },
+        })
+    );
+
+    let res = execute(
+        &global,
+        Some(&bot_session),
+        global_role::Permission::default(),
+        apply,
+        serde_json::json!({ "websiteUrl": "" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+}
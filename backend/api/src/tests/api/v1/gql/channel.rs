@@ -0,0 +1,1178 @@
+use crate::{
+    api::v1::gql::ext::RequestExt,
+    database::{channel_event, session, user},
+};
+use async_graphql::{Name, Request, Variables};
+use chrono::Utc;
+use futures_util::StreamExt;
+use serial_test::serial;
+use std::{sync::Arc, time::Duration};
+use uuid::Uuid;
+
+use crate::{
+    api::v1::gql::{request_context::RequestContext, schema},
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_recent_events_not_logged_in() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+    let schema = schema();
+
+    let query = r#"
+        query RecentEvents($channelId: UUID!) {
+            channel {
+                recentEvents(channelId: $channelId) {
+                    id
+                }
+            }
+        }
+    "#;
+
+    let ctx = Arc::new(RequestContext::default());
+
+    let mut variables = Variables::default();
+    variables.insert(
+        Name::new("channelId"),
+        async_graphql::Value::String(Uuid::new_v4().to_string()),
+    );
+
+    let res = schema
+        .execute(
+            Request::from(query)
+                .variables(variables)
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+        .await;
+
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You need to be logged in"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_recent_events() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+    let schema = schema();
+
+    let query = r#"
+        query RecentEvents($channelId: UUID!) {
+            channel {
+                recentEvents(channelId: $channelId, types: [FOLLOW, RAID], limit: 10) {
+                    type
+                    amount
+                    user {
+                        username
+                    }
+                }
+            }
+        }
+    "#;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let channel = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let viewer = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "viewer",
+        "viewer@test.com",
+        user::hash_password("viewer"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    for (kind, amount, created_at) in [
+        (
+            channel_event::Kind::Follow,
+            0,
+            Utc::now() - chrono::Duration::seconds(30),
+        ),
+        (
+            channel_event::Kind::Cheer,
+            100,
+            Utc::now() - chrono::Duration::seconds(20),
+        ),
+        (
+            channel_event::Kind::Raid,
+            42,
+            Utc::now() - chrono::Duration::seconds(10),
+        ),
+    ] {
+        sqlx::query!(
+            "INSERT INTO channel_events(channel_id, user_id, kind, amount, created_at) VALUES ($1, $2, $3, $4, $5)",
+            channel.id,
+            viewer.id,
+            kind as i64,
+            amount,
+            created_at,
+        )
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    }
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        channel.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let mut variables = Variables::default();
+    variables.insert(
+        Name::new("channelId"),
+        async_graphql::Value::String(channel.id.to_string()),
+    );
+
+    let res = schema
+        .execute(
+            Request::from(query)
+                .variables(variables)
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+        .await;
+
+    assert_eq!(res.errors.len(), 0);
+    let json = res.data.into_json();
+    assert!(json.is_ok());
+    assert_eq!(
+        json.unwrap(),
+        serde_json::json!({
+            "channel": {
+                "recentEvents": [
+                    { "type": "RAID", "amount": 42, "user": { "username": "viewer" } },
+                    { "type": "FOLLOW", "amount": 0, "user": { "username": "viewer" } },
+                ]
+            }
+        })
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_recent_events_pagination() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+    let schema = schema();
+
+    let query = r#"
+        query RecentEvents($channelId: UUID!, $after: Cursor) {
+            channel {
+                recentEvents(channelId: $channelId, after: $after, limit: 2) {
+                    id
+                    cursor
+                }
+            }
+        }
+    "#;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let channel = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    // All events share a timestamp, the page has to end between them.
+    let created_at = Utc::now() - chrono::Duration::seconds(10);
+    for _ in 0..3 {
+        sqlx::query!(
+            "INSERT INTO channel_events(channel_id, kind, amount, created_at) VALUES ($1, $2, $3, $4)",
+            channel.id,
+            channel_event::Kind::Follow as i64,
+            0,
+            created_at,
+        )
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    }
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        channel.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let mut ids = Vec::new();
+    let mut after = serde_json::Value::Null;
+    for expected in [2, 1, 0] {
+        let res = schema
+            .execute(
+                Request::from(query)
+                    .variables(Variables::from_json(serde_json::json!({
+                        "channelId": channel.id.to_string(),
+                        "after": after,
+                    })))
+                    .provide_global(global.clone())
+                    .provide_context(ctx.clone()),
+            )
+            .await;
+
+        assert_eq!(res.errors.len(), 0);
+        let json = res.data.into_json().unwrap();
+        let events = json["channel"]["recentEvents"].as_array().unwrap().clone();
+        assert_eq!(events.len(), expected);
+
+        if let Some(last) = events.last() {
+            after = last["cursor"].clone();
+        }
+        ids.extend(events.into_iter().map(|e| e["id"].clone()));
+    }
+
+    ids.sort_by_key(|id| id.to_string());
+    ids.dedup();
+    assert_eq!(ids.len(), 3);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_channel_appearance() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+    let schema = schema();
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let channel = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        channel.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let execute = |query: &'static str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let res = execute(
+        r#"
+        query Appearance($channelId: UUID!) {
+            channel {
+                appearance(channelId: $channelId) {
+                    accentColor {
+                        light
+                    }
+                    banner {
+                        id
+                    }
+                    layout
+                    showPanels
+                }
+            }
+        }
+    "#,
+        serde_json::json!({ "channelId": channel.id.to_string() }),
+    )
+    .await;
+
+    assert_eq!(res.errors.len(), 0);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["appearance"],
+        serde_json::json!({ "accentColor": null, "banner": null, "layout": "STANDARD", "showPanels": true })
+    );
+
+    let update = r#"
+        mutation UpdateAppearance($channelId: UUID!, $accentColor: String) {
+            channel {
+                updateAppearance(channelId: $channelId, accentColor: $accentColor, layout: THEATRE, showPanels: false) {
+                    accentColor {
+                        name
+                        dark
+                    }
+                    layout
+                    showPanels
+                    showSchedule
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        update,
+        serde_json::json!({ "channelId": channel.id.to_string(), "accentColor": "teal" }),
+    )
+    .await;
+
+    assert_eq!(res.errors.len(), 0);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["updateAppearance"],
+        serde_json::json!({
+            "accentColor": { "name": "teal", "dark": "#38d9a9" },
+            "layout": "THEATRE",
+            "showPanels": false,
+            "showSchedule": true,
+        })
+    );
+
+    let res = execute(
+        update,
+        serde_json::json!({ "channelId": channel.id.to_string(), "accentColor": "#ffffff" }),
+    )
+    .await;
+
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Color is not readable on the light theme"
+    );
+
+    let banner = r#"
+        mutation SetBanner($channelId: UUID!, $sourceUrl: String) {
+            channel {
+                setBanner(channelId: $channelId, sourceUrl: $sourceUrl) {
+                    banner {
+                        id
+                    }
+                    bannerPending
+                    layout
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        banner,
+        serde_json::json!({ "channelId": channel.id.to_string(), "sourceUrl": "http://example.com/banner.png" }),
+    )
+    .await;
+
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Source url must be a valid https url"
+    );
+
+    // Removing the banner keeps the rest of the appearance.
+    let res = execute(
+        banner,
+        serde_json::json!({ "channelId": channel.id.to_string() }),
+    )
+    .await;
+
+    assert_eq!(res.errors.len(), 0);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["setBanner"],
+        serde_json::json!({ "banner": null, "bannerPending": false, "layout": "THEATRE" })
+    );
+
+    let res = execute(
+        update,
+        serde_json::json!({ "channelId": Uuid::new_v4().to_string(), "accentColor": "teal" }),
+    )
+    .await;
+
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You are not allowed to access this channel"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_channel_panels() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+    let schema = schema();
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let channel = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        channel.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let execute = |query: &'static str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let create = r#"
+        mutation CreatePanel($channelId: UUID!, $title: String!, $description: String!, $imageSourceUrl: String, $linkUrl: String) {
+            channel {
+                createPanel(channelId: $channelId, title: $title, description: $description, imageSourceUrl: $imageSourceUrl, linkUrl: $linkUrl) {
+                    id
+                    position
+                    title
+                    description
+                    linkUrl
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        create,
+        serde_json::json!({ "channelId": channel.id, "title": " ", "description": "" }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: A panel needs a title, a text or an image"
+    );
+
+    let res = execute(
+        create,
+        serde_json::json!({ "channelId": channel.id, "title": "About", "description": "", "imageSourceUrl": "http://example.com/panel.png" }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Source url must be a valid https url"
+    );
+
+    let res = execute(
+        create,
+        serde_json::json!({ "channelId": channel.id, "title": "About", "description": "", "linkUrl": "javascript:alert(1)" }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Url must be a http(s) or mailto link"
+    );
+
+    let res = execute(
+        create,
+        serde_json::json!({ "channelId": channel.id, "title": "About", "description": "Hi <b>there</b>" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let about = res.data.into_json().unwrap()["channel"]["createPanel"].clone();
+    assert_eq!(about["position"], 0);
+    assert_eq!(about["description"], "Hi &lt;b>there&lt;/b>");
+
+    let res = execute(
+        create,
+        serde_json::json!({ "channelId": channel.id, "title": "Rules", "description": "Be nice", "linkUrl": "https://example.com/rules" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let rules = res.data.into_json().unwrap()["channel"]["createPanel"].clone();
+    assert_eq!(rules["position"], 1);
+
+    let res = execute(
+        r#"
+        mutation UpdatePanel($channelId: UUID!, $id: UUID!) {
+            channel {
+                updatePanel(channelId: $channelId, id: $id, title: "House rules", description: "Be nice") {
+                    title
+                    linkUrl
+                }
+            }
+        }
+    "#,
+        serde_json::json!({ "channelId": channel.id, "id": rules["id"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["updatePanel"],
+        serde_json::json!({ "title": "House rules", "linkUrl": null })
+    );
+
+    let reorder = r#"
+        mutation ReorderPanels($channelId: UUID!, $ids: [UUID!]!) {
+            channel {
+                reorderPanels(channelId: $channelId, ids: $ids) {
+                    title
+                    position
+                }
+            }
+        }
+    "#;
+
+    // Leaving a panel out or giving one twice is refused.
+    for ids in [
+        serde_json::json!([rules["id"]]),
+        serde_json::json!([rules["id"], rules["id"]]),
+    ] {
+        let res = execute(
+            reorder,
+            serde_json::json!({ "channelId": channel.id, "ids": ids }),
+        )
+        .await;
+        assert_eq!(
+            res.errors[0].message,
+            "InvalidInput: Every panel of the channel has to be given once"
+        );
+    }
+
+    let res = execute(
+        reorder,
+        serde_json::json!({ "channelId": channel.id, "ids": [rules["id"], about["id"]] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["reorderPanels"],
+        serde_json::json!([
+            { "title": "House rules", "position": 0 },
+            { "title": "About", "position": 1 },
+        ])
+    );
+
+    let delete = r#"
+        mutation DeletePanel($channelId: UUID!, $id: UUID!) {
+            channel {
+                deletePanel(channelId: $channelId, id: $id)
+            }
+        }
+    "#;
+
+    let res = execute(
+        delete,
+        serde_json::json!({ "channelId": channel.id, "id": about["id"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["deletePanel"],
+        true
+    );
+
+    let res = execute(
+        delete,
+        serde_json::json!({ "channelId": channel.id, "id": about["id"] }),
+    )
+    .await;
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["deletePanel"],
+        false
+    );
+
+    let res = execute(
+        r#"
+        query Panels($channelId: UUID!) {
+            channel {
+                panels(channelId: $channelId) {
+                    title
+                }
+            }
+        }
+    "#,
+        serde_json::json!({ "channelId": channel.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["panels"],
+        serde_json::json!([{ "title": "House rules" }])
+    );
+
+    let res = execute(
+        delete,
+        serde_json::json!({ "channelId": Uuid::new_v4(), "id": rules["id"] }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You are not allowed to access this channel"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_schedule_conflicts() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+    let schema = schema();
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let channel = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        channel.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let execute = |query: &'static str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let add = r#"
+        mutation Add($channelId: UUID!, $title: String!, $recurring: Boolean!, $startsAt: DateRFC3339!, $endsAt: DateRFC3339) {
+            channel {
+                addScheduleSegment(channelId: $channelId, title: $title, recurring: $recurring, startsAt: $startsAt, endsAt: $endsAt) {
+                    id
+                    title
+                }
+            }
+        }
+    "#;
+
+    let starts_at = Utc::now() + chrono::Duration::days(1);
+
+    let res = execute(
+        add,
+        serde_json::json!({
+            "channelId": channel.id,
+            "title": "Weekly speedruns",
+            "recurring": true,
+            "startsAt": starts_at.to_rfc3339(),
+            "endsAt": (starts_at + chrono::Duration::hours(3)).to_rfc3339(),
+        }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let segment_id = res.data.into_json().unwrap()["channel"]["addScheduleSegment"]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Two weeks later the recurring stream is running.
+    let res = execute(
+        add,
+        serde_json::json!({
+            "channelId": channel.id,
+            "title": "Collab",
+            "recurring": false,
+            "startsAt": (starts_at + chrono::Duration::weeks(2) + chrono::Duration::hours(2)).to_rfc3339(),
+            "endsAt": null,
+        }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Conflict: This overlaps with \"Weekly speedruns\" on the schedule"
+    );
+
+    let res = execute(
+        add,
+        serde_json::json!({
+            "channelId": channel.id,
+            "title": "Collab",
+            "recurring": false,
+            "startsAt": (starts_at + chrono::Duration::hours(3)).to_rfc3339(),
+            "endsAt": starts_at.to_rfc3339(),
+        }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: A stream must end after it starts"
+    );
+
+    let res = execute(
+        r#"
+        mutation Remove($channelId: UUID!, $id: UUID!) {
+            channel {
+                removeScheduleSegment(channelId: $channelId, id: $id)
+            }
+        }
+    "#,
+        serde_json::json!({ "channelId": channel.id, "id": segment_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["removeScheduleSegment"],
+        true
+    );
+
+    let res = execute(
+        add,
+        serde_json::json!({
+            "channelId": channel.id,
+            "title": "Collab",
+            "recurring": false,
+            "startsAt": (starts_at + chrono::Duration::weeks(2) + chrono::Duration::hours(2)).to_rfc3339(),
+            "endsAt": null,
+        }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_stream_info() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+    let schema = schema();
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut sessions = Vec::new();
+    let mut users = Vec::new();
+    for name in ["broadcaster", "viewer"] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let session = sqlx::query_as!(
+            session::Model,
+            "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+            user.id,
+            Utc::now() + chrono::Duration::seconds(120)
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        users.push(user);
+        sessions.push(session);
+    }
+    let channel_id = users[0].id;
+
+    let execute = |session: &session::Model, query: &str, variables: serde_json::Value| {
+        let ctx = Arc::new(RequestContext::new(false));
+        ctx.set_session(Some((session.clone(), Default::default())));
+
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+    };
+
+    let mut updates = schema.execute_stream(
+        Request::from(
+            r#"
+                subscription StreamInfo($channelId: UUID!) {
+                    channelStreamInfo(channelId: $channelId) {
+                        title
+                        category
+                        language
+                    }
+                }
+            "#,
+        )
+        .variables(Variables::from_json(
+            serde_json::json!({ "channelId": channel_id }),
+        ))
+        .provide_global(global.clone())
+        .provide_context(Arc::new(RequestContext::new(false))),
+    );
+
+    let res = tokio::time::timeout(Duration::from_secs(1), updates.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channelStreamInfo"],
+        serde_json::json!({ "title": "", "category": "", "language": "" })
+    );
+
+    let set_title = r#"
+        mutation SetTitle($channelId: UUID!, $title: String!) {
+            channel {
+                setTitle(channelId: $channelId, title: $title) {
+                    title
+                }
+            }
+        }
+    "#;
+
+    // Only the broadcaster can change the stream info.
+    let res = execute(
+        &sessions[1],
+        set_title,
+        serde_json::json!({ "channelId": channel_id, "title": "Speedruns" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &sessions[0],
+        set_title,
+        serde_json::json!({ "channelId": channel_id, "title": "a".repeat(256) }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Title must be at most 255 characters"
+    );
+
+    // The limit counts characters, not bytes.
+    let res = execute(
+        &sessions[0],
+        set_title,
+        serde_json::json!({ "channelId": channel_id, "title": "é".repeat(255) }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(
+        &sessions[0],
+        set_title,
+        serde_json::json!({ "channelId": channel_id, "title": " Any% speedruns " }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(
+        &sessions[0],
+        r#"
+            mutation SetCategory($channelId: UUID!) {
+                channel {
+                    setCategory(channelId: $channelId, category: "Celeste") {
+                        category
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "channelId": channel_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let set_language = r#"
+        mutation SetLanguage($channelId: UUID!, $language: String!) {
+            channel {
+                setLanguage(channelId: $channelId, language: $language) {
+                    title
+                    category
+                    language
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &sessions[0],
+        set_language,
+        serde_json::json!({ "channelId": channel_id, "language": "english" }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Language is not a valid BCP-47 language tag"
+    );
+
+    let res = execute(
+        &sessions[0],
+        set_language,
+        serde_json::json!({ "channelId": channel_id, "language": "en" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["setLanguage"],
+        serde_json::json!({ "title": "Any% speedruns", "category": "Celeste", "language": "en" })
+    );
+
+    // Every change is published, the rejected ones are not.
+    let mut published = Vec::new();
+    for _ in 0..3 {
+        let res = tokio::time::timeout(Duration::from_secs(1), updates.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+        published.push(res.data.into_json().unwrap()["channelStreamInfo"].clone());
+    }
+    assert_eq!(
+        published,
+        vec![
+            serde_json::json!({ "title": "Any% speedruns", "category": "", "language": "" }),
+            serde_json::json!({ "title": "Any% speedruns", "category": "Celeste", "language": "" }),
+            serde_json::json!({ "title": "Any% speedruns", "category": "Celeste", "language": "en" }),
+        ]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_stream_sessions() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+    let schema = schema();
+
+    let query = r#"
+        query StreamSessions($channelId: UUID!, $after: Cursor) {
+            channel {
+                streamSessions(channelId: $channelId, after: $after, limit: 2) {
+                    streamId
+                    uniqueChatters
+                    cursor
+                }
+            }
+        }
+    "#;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut users = Vec::new();
+    for username in ["test", "other"] {
+        users.push(sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+            username,
+            format!("{}@test.com", username),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        ).fetch_one(&*global.db).await.unwrap());
+    }
+    let channel = &users[0];
+
+    // All sessions share an end time, the page has to end between them.
+    let ended_at = Utc::now() - chrono::Duration::minutes(10);
+    let mut streams = Vec::new();
+    for chatters in 0..3i64 {
+        let stream_id = sqlx::query_scalar!(
+            "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id, created_at, ended_at) VALUES ($1, '', '', FALSE, FALSE, '', $2, $3, $4) RETURNING id",
+            channel.id,
+            Uuid::new_v4(),
+            ended_at - chrono::Duration::hours(1),
+            ended_at,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        sqlx::query!(
+            "INSERT INTO stream_sessions (channel_id, stream_id, unique_chatters, new_follows, started_at, ended_at) VALUES ($1, $2, $3, 0, $4, $5)",
+            channel.id,
+            stream_id,
+            chatters,
+            ended_at - chrono::Duration::hours(1),
+            ended_at,
+        )
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+        streams.push(stream_id.to_string());
+    }
+
+    let mut sessions = Vec::new();
+    for user in &users {
+        sessions.push(
+            sqlx::query_as!(
+                session::Model,
+                "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+                user.id,
+                Utc::now() + chrono::Duration::seconds(120)
+            )
+            .fetch_one(&*global.db)
+            .await
+            .unwrap(),
+        );
+    }
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((sessions[0].clone(), Default::default())));
+
+    let mut seen = Vec::new();
+    let mut after = serde_json::Value::Null;
+    for expected in [2, 1, 0] {
+        let res = schema
+            .execute(
+                Request::from(query)
+                    .variables(Variables::from_json(serde_json::json!({
+                        "channelId": channel.id.to_string(),
+                        "after": after,
+                    })))
+                    .provide_global(global.clone())
+                    .provide_context(ctx.clone()),
+            )
+            .await;
+
+        assert_eq!(res.errors.len(), 0);
+        let json = res.data.into_json().unwrap();
+        let page = json["channel"]["streamSessions"]
+            .as_array()
+            .unwrap()
+            .clone();
+        assert_eq!(page.len(), expected);
+
+        if let Some(last) = page.last() {
+            after = last["cursor"].clone();
+        }
+        seen.extend(
+            page.into_iter()
+                .map(|s| s["streamId"].as_str().unwrap().to_string()),
+        );
+    }
+
+    seen.sort();
+    streams.sort();
+    assert_eq!(seen, streams);
+
+    // Other users can't see the history of the channel.
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((sessions[1].clone(), Default::default())));
+
+    let res = schema
+        .execute(
+            Request::from(query)
+                .variables(Variables::from_json(serde_json::json!({
+                    "channelId": channel.id.to_string(),
+                })))
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+        .await;
+
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You are not allowed to access this channel"
+    );
+}
@@ -1,6 +1,7 @@
 use crate::{
     api::v1::gql::ext::RequestExt,
-    database::{chat_message, session, user},
+    config::{AppConfig, ChatConfig},
+    database::{chat_message, session, stream, user},
     pb,
 };
 use async_graphql::{Name, Request, Variables};
@@ -262,6 +263,7 @@ async fn test_serial_send_message_success() {
     assert_eq!(message.author_id, user.id.to_string());
     assert_eq!(message.channel_id, channel.id.to_string());
     assert_eq!(message.content, "message");
+    assert_eq!(message.sequence, 1);
     assert_eq!(
         message.id,
         json["chat"]["sendMessage"]["id"].as_str().unwrap()
@@ -356,3 +358,1025 @@ async fn test_serial_send_message_too_long() {
     assert!(json.is_ok());
     assert_eq!(res.errors[0].message, "InvalidInput: Message too long");
 }
+
+#[tokio::test]
+#[serial]
+async fn test_serial_chat_backfill() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        chat: ChatConfig {
+            backfill_size: 3,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let channel = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "based",
+        "based@based.com",
+        user::hash_password("based"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let event = |id: Uuid, content: &str, r#type: pb::scuffle::events::chat_message::Type| {
+        pb::scuffle::events::ChatMessage {
+            id: id.to_string(),
+            channel_id: channel.id.to_string(),
+            author_id: channel.id.to_string(),
+            content: content.to_string(),
+            created_at: Utc::now().timestamp(),
+            r#type: r#type as i32,
+            emotes: vec![],
+            cheer: None,
+            author_color: None,
+            sequence: 0,
+            author_vip: false,
+            author_verified_bot: false,
+        }
+    };
+
+    let spam = Uuid::new_v4();
+    for (id, content, r#type) in [
+        (
+            Uuid::new_v4(),
+            "first",
+            pb::scuffle::events::chat_message::Type::User,
+        ),
+        (spam, "spam", pb::scuffle::events::chat_message::Type::User),
+        (
+            Uuid::new_v4(),
+            "third",
+            pb::scuffle::events::chat_message::Type::User,
+        ),
+        (spam, "", pb::scuffle::events::chat_message::Type::Deleted),
+    ] {
+        global
+            .publish_chat_message(channel.id, event(id, content, r#type))
+            .await
+            .unwrap();
+    }
+
+    let query = r#"
+        query Messages($channelId: UUID!, $afterSequence: Int!) {
+            chat {
+                messages(channelId: $channelId, afterSequence: $afterSequence) {
+                    content
+                    type
+                    sequence
+                }
+            }
+        }
+    "#;
+
+    let res = schema()
+        .execute(
+            Request::from(query)
+                .variables(Variables::from_json(serde_json::json!({
+                    "channelId": channel.id.to_string(),
+                    "afterSequence": 0,
+                })))
+                .provide_global(global.clone())
+                .provide_context(Arc::new(RequestContext::new(false))),
+        )
+        .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    // Only the last 3 events are kept, and the deleted message does not come back.
+    assert_eq!(
+        res.data.into_json().unwrap()["chat"]["messages"],
+        serde_json::json!([
+            { "content": "", "type": "DELETED", "sequence": 2 },
+            { "content": "third", "type": "USER", "sequence": 3 },
+            { "content": "", "type": "DELETED", "sequence": 4 },
+        ])
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_chat_backfill_viewer() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut users = Vec::new();
+    for name in ["broadcaster", "viewer", "spammer"] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+        users.push(user);
+    }
+    let (broadcaster, viewer, spammer) = (&users[0], &users[1], &users[2]);
+
+    sqlx::query!(
+        "INSERT INTO user_blocks(user_id, blocked_id) VALUES ($1, $2)",
+        viewer.id,
+        spammer.id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    for (author, content) in [
+        (spammer, "spam"),
+        (spammer, "more spam"),
+        (broadcaster, "hello"),
+    ] {
+        global
+            .publish_chat_message(
+                broadcaster.id,
+                pb::scuffle::events::ChatMessage {
+                    id: Uuid::new_v4().to_string(),
+                    channel_id: broadcaster.id.to_string(),
+                    author_id: author.id.to_string(),
+                    content: content.to_string(),
+                    created_at: Utc::now().timestamp(),
+                    r#type: pb::scuffle::events::chat_message::Type::User as i32,
+                    emotes: vec![],
+                    cheer: None,
+                    author_color: None,
+                    sequence: 0,
+                    author_vip: false,
+                    author_verified_bot: false,
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        viewer.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let backfill = || {
+        schema().execute(
+            Request::from(
+                r#"
+                    query Messages($channelId: UUID!) {
+                        chat {
+                            messages(channelId: $channelId, afterSequence: 0, limit: 1) {
+                                content
+                                sequence
+                            }
+                        }
+                    }
+                "#,
+            )
+            .variables(Variables::from_json(serde_json::json!({
+                "channelId": broadcaster.id.to_string(),
+            })))
+            .provide_global(global.clone())
+            .provide_context(ctx.clone()),
+        )
+    };
+
+    // The first page only has spam, which must not end the backfill early.
+    let res = backfill().await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["chat"]["messages"],
+        serde_json::json!([{ "content": "hello", "sequence": 3 }])
+    );
+
+    sqlx::query!(
+        "INSERT INTO chat_bans (channel_id, user_id, banned_by) VALUES ($1, $2, $1)",
+        broadcaster.id,
+        viewer.id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let res = backfill().await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You are banned from this chat"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_chat_vips() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        chat: ChatConfig {
+            vip_slots: 1,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut contexts = Vec::new();
+    let mut users = Vec::new();
+    for name in ["broadcaster", "vip", "viewer"] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let session = sqlx::query_as!(
+            session::Model,
+            "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+            user.id,
+            Utc::now() + chrono::Duration::seconds(120)
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let ctx = Arc::new(RequestContext::new(false));
+        ctx.set_session(Some((session, Default::default())));
+        contexts.push(ctx);
+        users.push(user);
+    }
+    let (broadcaster, vip, viewer) = (&users[0], &users[1], &users[2]);
+
+    let schema = schema();
+    let execute = |ctx: &Arc<RequestContext>, query: &str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let settings = r#"
+        mutation SetSettings($channelId: UUID!) {
+            chat {
+                setSettings(channelId: $channelId, slowMode: 30, blockLinks: true) {
+                    slowMode
+                    blockLinks
+                }
+            }
+        }
+    "#;
+
+    // Only the broadcaster can change the rules of their chat.
+    let res = execute(
+        &contexts[2],
+        settings,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &contexts[0],
+        settings,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["chat"]["setSettings"],
+        serde_json::json!({ "slowMode": 30, "blockLinks": true })
+    );
+
+    let add_vip = r#"
+        mutation AddVip($channelId: UUID!, $userId: UUID!) {
+            chat {
+                addVip(channelId: $channelId, userId: $userId) {
+                    userId
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &contexts[0],
+        add_vip,
+        serde_json::json!({ "channelId": broadcaster.id, "userId": vip.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(
+        &contexts[0],
+        add_vip,
+        serde_json::json!({ "channelId": broadcaster.id, "userId": viewer.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: All 1 VIP slots are taken, remove a VIP first"
+    );
+
+    let send = r#"
+        mutation SendChatMessage($channelId: UUID!, $content: String!) {
+            chat {
+                sendMessage(channelId: $channelId, content: $content) {
+                    authorVip
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &contexts[2],
+        send,
+        serde_json::json!({ "channelId": broadcaster.id, "content": "visit example.com" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Links are not allowed in this chat"
+    );
+
+    let res = execute(
+        &contexts[2],
+        send,
+        serde_json::json!({ "channelId": broadcaster.id, "content": "hello" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["chat"]["sendMessage"]["authorVip"],
+        false
+    );
+
+    let res = execute(
+        &contexts[2],
+        send,
+        serde_json::json!({ "channelId": broadcaster.id, "content": "hello again" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert!(res.errors[0]
+        .message
+        .starts_with("InvalidInput: Slow mode is on, wait"));
+
+    // VIPs are not held to slow mode or link blocking.
+    for _ in 0..2 {
+        let res = execute(
+            &contexts[1],
+            send,
+            serde_json::json!({ "channelId": broadcaster.id, "content": "https://example.com" }),
+        )
+        .await;
+        assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+        assert_eq!(
+            res.data.into_json().unwrap()["chat"]["sendMessage"]["authorVip"],
+            true
+        );
+    }
+
+    let remove_vip = r#"
+        mutation RemoveVip($channelId: UUID!, $userId: UUID!) {
+            chat {
+                removeVip(channelId: $channelId, userId: $userId)
+            }
+        }
+    "#;
+
+    let res = execute(
+        &contexts[0],
+        remove_vip,
+        serde_json::json!({ "channelId": broadcaster.id, "userId": vip.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(res.data.into_json().unwrap()["chat"]["removeVip"], true);
+
+    let res = execute(
+        &contexts[1],
+        send,
+        serde_json::json!({ "channelId": broadcaster.id, "content": "https://example.com" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_chat_archive() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "broadcaster",
+        "broadcaster@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let stream = sqlx::query_as!(stream::Model,
+        "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        user.id,
+        "test",
+        "test",
+        false,
+        false,
+        "some address",
+        Uuid::new_v4(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert!(stream.chat_archived);
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let schema = schema();
+    let execute = |query: &str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let send = r#"
+        mutation SendChatMessage($channelId: UUID!, $content: String!) {
+            chat {
+                sendMessage(channelId: $channelId, content: $content) {
+                    id
+                }
+            }
+        }
+    "#;
+
+    let export = r#"
+        query Export($streamId: UUID!) {
+            chat {
+                export(streamId: $streamId) {
+                    content
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        send,
+        serde_json::json!({ "channelId": user.id, "content": "kept" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(export, serde_json::json!({ "streamId": stream.id })).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["chat"]["export"],
+        serde_json::json!([{ "content": "kept" }])
+    );
+
+    let res = execute(
+        r#"
+            mutation SetSettings($channelId: UUID!) {
+                chat {
+                    setSettings(channelId: $channelId, archive: false) {
+                        archive
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "channelId": user.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["chat"]["setSettings"]["archive"],
+        false
+    );
+
+    // Messages are still sent, but not stored.
+    let res = execute(
+        send,
+        serde_json::json!({ "channelId": user.id, "content": "not kept" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let stored = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM chat_messages WHERE channel_id = $1",
+        user.id
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(stored, Some(1));
+
+    // The live stream is missing messages now, so its chat can't be exported anymore.
+    let res = execute(export, serde_json::json!({ "streamId": stream.id })).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: The chat of this stream was not kept"
+    );
+
+    let res = execute(
+        r#"
+            query AuditLog($channelId: UUID!) {
+                channel {
+                    auditLog(channelId: $channelId) {
+                        action
+                        actorId
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "channelId": user.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["auditLog"],
+        serde_json::json!([{ "action": "CHAT_ARCHIVE_DISABLED", "actorId": user.id }])
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_chat_commands() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        chat: ChatConfig {
+            max_commands: 2,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut contexts = Vec::new();
+    let mut users = Vec::new();
+    for name in ["broadcaster", "vip", "viewer"] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let session = sqlx::query_as!(
+            session::Model,
+            "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+            user.id,
+            Utc::now() + chrono::Duration::seconds(120)
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let ctx = Arc::new(RequestContext::new(false));
+        ctx.set_session(Some((session, Default::default())));
+        contexts.push(ctx);
+        users.push(user);
+    }
+    let (broadcaster, vip, viewer) = (&users[0], &users[1], &users[2]);
+
+    sqlx::query!(
+        "INSERT INTO chat_vips (channel_id, user_id) VALUES ($1, $2)",
+        broadcaster.id,
+        vip.id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let schema = schema();
+    let execute = |ctx: &Arc<RequestContext>, query: &str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let set = r#"
+        mutation SetCommand($channelId: UUID!, $name: String!, $response: String!, $permission: ChatCommandPermission!) {
+            chat {
+                setCommand(channelId: $channelId, name: $name, response: $response, cooldown: 60, permission: $permission) {
+                    name
+                    response
+                    permission
+                }
+            }
+        }
+    "#;
+    let command = |name: &str, response: &str, permission: &str| {
+        serde_json::json!({
+            "channelId": broadcaster.id,
+            "name": name,
+            "response": response,
+            "permission": permission,
+        })
+    };
+
+    // Only the broadcaster can add commands to their chat.
+    let res = execute(&contexts[2], set, command("discord", "Join us", "EVERYONE")).await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &contexts[0],
+        set,
+        command("!Discord", "Join us", "EVERYONE"),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["chat"]["setCommand"],
+        serde_json::json!({ "name": "discord", "response": "Join us", "permission": "EVERYONE" })
+    );
+
+    let res = execute(&contexts[0], set, command("mods", "VIPs only", "VIP")).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(&contexts[0], set, command("dis cord", "Nope", "EVERYONE")).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Command name can only contain letters, numbers, _ and -"
+    );
+
+    let res = execute(&contexts[0], set, command("third", "Too many", "EVERYONE")).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: A chat can have at most 2 commands, remove a command first"
+    );
+
+    // Changing a command does not count against the limit.
+    let res = execute(
+        &contexts[0],
+        set,
+        command("discord", "Join the discord", "EVERYONE"),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let send = r#"
+        mutation Send($channelId: UUID!, $content: String!) {
+            chat {
+                sendMessage(channelId: $channelId, content: $content) {
+                    id
+                }
+            }
+        }
+    "#;
+
+    // The second call is on cooldown, and viewers can't use the VIP command.
+    for (ctx, content) in [
+        (&contexts[2], "!discord now"),
+        (&contexts[2], "!DISCORD"),
+        (&contexts[2], "!mods"),
+        (&contexts[1], "!mods"),
+    ] {
+        let res = execute(
+            ctx,
+            send,
+            serde_json::json!({ "channelId": broadcaster.id, "content": content }),
+        )
+        .await;
+        assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    }
+
+    let res = execute(
+        &contexts[2],
+        r#"
+            query Messages($channelId: UUID!) {
+                chat {
+                    messages(channelId: $channelId, afterSequence: 0) {
+                        authorId
+                        content
+                        type
+                    }
+                    commands(channelId: $channelId) {
+                        name
+                        uses
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let data = res.data.into_json().unwrap();
+    assert_eq!(
+        data["chat"]["messages"],
+        serde_json::json!([
+            { "authorId": viewer.id, "content": "!discord now", "type": "USER" },
+            { "authorId": broadcaster.id, "content": "Join the discord", "type": "COMMAND" },
+            { "authorId": viewer.id, "content": "!DISCORD", "type": "USER" },
+            { "authorId": viewer.id, "content": "!mods", "type": "USER" },
+            { "authorId": vip.id, "content": "!mods", "type": "USER" },
+            { "authorId": broadcaster.id, "content": "VIPs only", "type": "COMMAND" },
+        ])
+    );
+    assert_eq!(
+        data["chat"]["commands"],
+        serde_json::json!([
+            { "name": "discord", "uses": 1 },
+            { "name": "mods", "uses": 1 },
+        ])
+    );
+
+    let remove = r#"
+        mutation Remove($channelId: UUID!) {
+            chat {
+                removeCommand(channelId: $channelId, name: "!discord")
+            }
+        }
+    "#;
+
+    let res = execute(
+        &contexts[0],
+        remove,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(res.data.into_json().unwrap()["chat"]["removeCommand"], true);
+
+    let res = execute(
+        &contexts[0],
+        remove,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["chat"]["removeCommand"],
+        false
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_chat_timers() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        chat: ChatConfig {
+            max_timers: 1,
+            min_timer_interval: 60,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut contexts = Vec::new();
+    let mut users = Vec::new();
+    for name in ["broadcaster", "viewer"] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let session = sqlx::query_as!(
+            session::Model,
+            "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+            user.id,
+            Utc::now() + chrono::Duration::seconds(120)
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let ctx = Arc::new(RequestContext::new(false));
+        ctx.set_session(Some((session, Default::default())));
+        contexts.push(ctx);
+        users.push(user);
+    }
+    let (broadcaster, viewer) = (&users[0], &users[1]);
+
+    let schema = schema();
+    let execute = |ctx: &Arc<RequestContext>, query: &str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let set = r#"
+        mutation SetTimer($channelId: UUID!, $name: String!, $interval: Int!) {
+            chat {
+                setTimer(channelId: $channelId, name: $name, message: "Follow the socials", interval: $interval, minMessages: 2) {
+                    name
+                    interval
+                    minMessages
+                    enabled
+                }
+            }
+        }
+    "#;
+    let timer = |name: &str, interval: u32| serde_json::json!({ "channelId": broadcaster.id, "name": name, "interval": interval });
+
+    // Only the broadcaster can add timers to their chat.
+    let res = execute(&contexts[1], set, timer("socials", 60)).await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(&contexts[0], set, timer("socials", 30)).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Interval must be between 60 seconds and a day"
+    );
+
+    let res = execute(&contexts[0], set, timer("Socials", 60)).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["chat"]["setTimer"],
+        serde_json::json!({ "name": "socials", "interval": 60, "minMessages": 2, "enabled": true })
+    );
+
+    let res = execute(&contexts[0], set, timer("other", 60)).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: A chat can have at most 1 timers, remove a timer first"
+    );
+
+    // Timers only post while the channel is live.
+    assert!(global.due_chat_timers(10).await.unwrap().is_empty());
+
+    sqlx::query!(
+        "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        broadcaster.id,
+        "live",
+        "",
+        false,
+        false,
+        "",
+        Uuid::new_v4(),
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    // The chat is too quiet until two messages were sent.
+    let send = r#"
+        mutation Send($channelId: UUID!, $content: String!) {
+            chat {
+                sendMessage(channelId: $channelId, content: $content) {
+                    id
+                }
+            }
+        }
+    "#;
+    for content in ["hi", "hello"] {
+        assert!(global.due_chat_timers(10).await.unwrap().is_empty());
+
+        let res = execute(
+            &contexts[1],
+            send,
+            serde_json::json!({ "channelId": broadcaster.id, "content": content }),
+        )
+        .await;
+        assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    }
+
+    let due = global.due_chat_timers(10).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert!(global.post_chat_timer(&due[0]).await.unwrap().is_some());
+
+    // A second instance holding the same timer does not post it again.
+    assert!(global.post_chat_timer(&due[0]).await.unwrap().is_none());
+    assert!(global.due_chat_timers(10).await.unwrap().is_empty());
+
+    let res = execute(
+        &contexts[0],
+        r#"
+            query Messages($channelId: UUID!) {
+                chat {
+                    messages(channelId: $channelId, afterSequence: 0) {
+                        authorId
+                        content
+                        type
+                    }
+                    timers(channelId: $channelId) {
+                        name
+                        lastPostedAt
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let data = res.data.into_json().unwrap();
+    assert_eq!(
+        data["chat"]["messages"],
+        serde_json::json!([
+            { "authorId": viewer.id, "content": "hi", "type": "USER" },
+            { "authorId": viewer.id, "content": "hello", "type": "USER" },
+            { "authorId": broadcaster.id, "content": "Follow the socials", "type": "TIMER" },
+        ])
+    );
+    assert!(data["chat"]["timers"][0]["lastPostedAt"].is_string());
+
+    let res = execute(
+        &contexts[0],
+        r#"
+            mutation Remove($channelId: UUID!) {
+                chat {
+                    removeTimer(channelId: $channelId, name: "socials")
+                }
+            }
+        "#,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(res.data.into_json().unwrap()["chat"]["removeTimer"], true);
+}
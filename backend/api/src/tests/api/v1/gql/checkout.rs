@@ -0,0 +1,205 @@
+use crate::{
+    api::v1::gql::ext::RequestExt,
+    config::{AppConfig, PaymentConfig},
+    database::{checkout, session, user},
+};
+use async_graphql::{Name, Request, Variables};
+use chrono::Utc;
+use serial_test::serial;
+use std::sync::Arc;
+
+use crate::{
+    api::v1::gql::{request_context::RequestContext, schema},
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_create_checkout_not_onboarded() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+    let schema = schema();
+
+    let query = r#"
+        mutation CreateCheckout($channelId: UUID!) {
+            checkout {
+                create(channelId: $channelId) {
+                    id
+                }
+            }
+        }
+    "#;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let channel = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let viewer = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "viewer",
+        "viewer@test.com",
+        user::hash_password("viewer"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        viewer.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let mut variables = Variables::default();
+    variables.insert(
+        Name::new("channelId"),
+        async_graphql::Value::String(channel.id.to_string()),
+    );
+
+    let res = schema
+        .execute(
+            Request::from(query)
+                .variables(variables)
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+        .await;
+
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: This channel can not receive payments yet"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_create_checkout_provider_error() {
+    // Nothing listens on this port, so the payment provider call fails.
+    let port = portpicker::pick_unused_port().expect("failed to pick port");
+    let (global, _handler) = mock_global_state(AppConfig {
+        payment: PaymentConfig {
+            url: format!("http://127.0.0.1:{}", port),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+    let schema = schema();
+
+    let query = r#"
+        mutation CreateCheckout($channelId: UUID!) {
+            checkout {
+                create(channelId: $channelId) {
+                    id
+                }
+            }
+        }
+    "#;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let channel = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let viewer = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "viewer",
+        "viewer@test.com",
+        user::hash_password("viewer"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    sqlx::query!(
+        "INSERT INTO payout_methods(channel_id, provider, provider_account_id, status) VALUES ($1, 'test', 'acct_1', 1)",
+        channel.id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        viewer.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let mut variables = Variables::default();
+    variables.insert(
+        Name::new("channelId"),
+        async_graphql::Value::String(channel.id.to_string()),
+    );
+
+    let res = schema
+        .execute(
+            Request::from(query)
+                .variables(variables)
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+        .await;
+
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InternalServerError: Failed to create checkout with payment provider"
+    );
+
+    // The checkout can never be paid, so it must not be left pending.
+    let statuses =
+        sqlx::query_scalar!("SELECT status FROM checkouts WHERE user_id = $1", viewer.id,)
+            .fetch_all(&*global.db)
+            .await
+            .unwrap();
+    assert_eq!(statuses, vec![i64::from(checkout::Status::Failed)]);
+}
@@ -0,0 +1,262 @@
+use chrono::Utc;
+use lapin::{
+    options::{BasicGetOptions, QueueDeclareOptions, QueuePurgeOptions},
+    types::FieldTable,
+};
+use prost::Message;
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    config::{AppConfig, ModerationConfig},
+    database::{
+        dead_letter::{self, Consumer},
+        global_role, session, user,
+    },
+    pb::scuffle::events::ModerationJob,
+    tests::{api::v1::gql::execute, global::mock_global_state},
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_dead_letters() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        moderation: ModerationConfig {
+            queue: "test_dead_letters".to_string(),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM dead_letters")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let channel = global.rmq.aquire().await.unwrap();
+    channel
+        .queue_declare(
+            "test_dead_letters",
+            QueueDeclareOptions::default(),
+            FieldTable::default(),
+        )
+        .await
+        .unwrap();
+    channel
+        .queue_purge("test_dead_letters", QueuePurgeOptions::default())
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "admin",
+        "admin@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let job_id = Uuid::new_v4().to_string();
+    let payload = ModerationJob { id: job_id.clone() }.encode_to_vec();
+    dead_letter::record(
+        &*global.db,
+        None,
+        Consumer::Moderation,
+        &payload,
+        "connection refused",
+    )
+    .await
+    .unwrap();
+    dead_letter::record(
+        &*global.db,
+        None,
+        Consumer::Notifications,
+        &[0x0f, 0xff],
+        "invalid wire type",
+    )
+    .await
+    .unwrap();
+
+    let list = r#"
+        query List($consumer: DeadLetterConsumer) {
+            deadLetter {
+                deadLetters(consumer: $consumer) {
+                    id
+                    consumer
+                    decoded
+                    decodeError
+                    error
+                    attempts
+                }
+            }
+        }
+    "#;
+
+    // Only admins can see dead letters.
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::default(),
+        list,
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::Admin,
+        list,
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    let letters = json["deadLetter"]["deadLetters"].as_array().unwrap();
+    assert_eq!(letters.len(), 2);
+
+    // The poison message failed last, so it comes first.
+    assert_eq!(letters[0]["consumer"], "NOTIFICATIONS");
+    assert_eq!(letters[0]["decoded"], serde_json::Value::Null);
+    assert_ne!(letters[0]["decodeError"], "");
+    let poison_id = letters[0]["id"].as_str().unwrap().to_string();
+
+    assert_eq!(letters[1]["consumer"], "MODERATION");
+    assert_eq!(
+        letters[1]["decoded"],
+        format!("ModerationJob {{ id: \"{}\" }}", job_id)
+    );
+    assert_eq!(letters[1]["error"], "connection refused");
+    assert_eq!(letters[1]["attempts"], 1);
+    let job_letter_id = letters[1]["id"].as_str().unwrap().to_string();
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::Admin,
+        r#"
+            mutation Retry($id: UUID!) {
+                deadLetter {
+                    retry(id: $id) {
+                        retriedAt
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "id": job_letter_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    // The message is published to the queue of the consumer, carrying its dead letter.
+    let message = channel
+        .basic_get("test_dead_letters", BasicGetOptions { no_ack: true })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(message.delivery.data, payload);
+    assert_eq!(
+        message
+            .delivery
+            .properties
+            .correlation_id()
+            .as_ref()
+            .unwrap()
+            .as_str(),
+        job_letter_id
+    );
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::Admin,
+        list,
+        serde_json::json!({ "consumer": "MODERATION" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["deadLetter"]["deadLetters"],
+        serde_json::json!([])
+    );
+
+    // Failing again counts on the same dead letter.
+    dead_letter::record(
+        &*global.db,
+        Some(job_letter_id.parse().unwrap()),
+        Consumer::Moderation,
+        &payload,
+        "connection reset",
+    )
+    .await
+    .unwrap();
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::Admin,
+        list,
+        serde_json::json!({ "consumer": "MODERATION" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["deadLetter"]["deadLetters"][0]["id"], job_letter_id);
+    assert_eq!(json["deadLetter"]["deadLetters"][0]["attempts"], 2);
+    assert_eq!(
+        json["deadLetter"]["deadLetters"][0]["error"],
+        "connection reset"
+    );
+
+    let discard = r#"
+        mutation Discard($id: UUID!) {
+            deadLetter {
+                discard(id: $id) {
+                    discardedAt
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::Admin,
+        discard,
+        serde_json::json!({ "id": poison_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::Admin,
+        discard,
+        serde_json::json!({ "id": poison_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: The dead letter was already retried or discarded"
+    );
+}
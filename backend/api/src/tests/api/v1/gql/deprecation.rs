@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use async_graphql::Request;
+use serial_test::serial;
+
+use crate::{
+    api::v1::gql::{ext::RequestExt, request_context::RequestContext, schema},
+    config::{AppConfig, DeprecatedSurfaceConfig, DeprecationConfig},
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_deprecated_field() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        deprecations: DeprecationConfig {
+            surfaces: vec![DeprecatedSurfaceConfig {
+                surface: "Query.noop".to_string(),
+                deprecated_at: "2023-08-01".to_string(),
+                sunset: Some("2023-11-01".to_string()),
+                replacement: Some("Nothing, it does nothing".to_string()),
+            }],
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM deprecated_usage_daily")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let schema = schema();
+    let execute = |query: &'static str| {
+        schema.execute(
+            Request::from(query)
+                .provide_global(global.clone())
+                .provide_context(Arc::new(RequestContext::new(false))),
+        )
+    };
+
+    let res = execute("query { noop }").await;
+    assert_eq!(res.errors.len(), 0);
+    assert_eq!(res.http_headers["deprecation"], "@1690848000");
+    assert_eq!(res.http_headers["sunset"], "Wed, 01 Nov 2023 00:00:00 GMT");
+    assert_eq!(
+        res.extensions["deprecations"].clone().into_json().unwrap(),
+        serde_json::json!([{
+            "surface": "Query.noop",
+            "deprecatedAt": "2023-08-01T00:00:00+00:00",
+            "sunset": "2023-11-01T00:00:00+00:00",
+            "replacement": "Nothing, it does nothing",
+        }])
+    );
+
+    // Fields which are not deprecated don't warn.
+    let res = execute("query { deprecation { deprecations { surface } } }").await;
+    assert_eq!(res.errors.len(), 0);
+    assert!(res.http_headers.get("deprecation").is_none());
+    assert!(!res.extensions.contains_key("deprecations"));
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({ "deprecation": { "deprecations": [{ "surface": "Query.noop" }] } })
+    );
+
+    execute("query { noop }").await;
+
+    let usage = sqlx::query!(
+        r#"SELECT client, user_id, uses FROM deprecated_usage_daily WHERE surface = 'Query.noop'"#
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(usage.client, "anonymous");
+    assert_eq!(usage.user_id, None);
+    assert_eq!(usage.uses, 2);
+}
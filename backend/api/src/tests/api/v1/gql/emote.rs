@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    config::{AppConfig, EmoteConfig},
+    database::{emote, global_role},
+    global::GlobalState,
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+async fn create_emote(
+    global: &Arc<GlobalState>,
+    channel_id: Uuid,
+    name: &str,
+    status: emote::Status,
+) -> emote::Model {
+    sqlx::query_as!(
+        emote::Model,
+        "INSERT INTO emotes (channel_id, name, image_url, width, height, nsfw_score, status) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        channel_id,
+        name,
+        format!("https://cdn.test/emotes/{}.webp", name),
+        112,
+        112,
+        0,
+        i64::from(status),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_emote_review() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (_, admin_session) = create_user(&global, "admin").await;
+    let (broadcaster, broadcaster_session) = create_user(&global, "broadcaster").await;
+
+    let pending = create_emote(
+        &global,
+        broadcaster.id,
+        "pending",
+        emote::Status::PendingReview,
+    )
+    .await;
+
+    let review = r#"
+        mutation Review($id: UUID!, $approved: Boolean!) {
+            emote {
+                review(id: $id, approved: $approved) {
+                    status
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        review,
+        serde_json::json!({ "id": pending.id, "approved": true }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You need to be an admin"
+    );
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        review,
+        serde_json::json!({ "id": pending.id, "approved": true }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["emote"]["review"]["status"],
+        "APPROVED"
+    );
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        review,
+        serde_json::json!({ "id": pending.id, "approved": true }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Only pending emotes can be approved"
+    );
+
+    // Approved emotes can still be taken down.
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        review,
+        serde_json::json!({ "id": pending.id, "approved": false }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["emote"]["review"]["status"],
+        "REJECTED"
+    );
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        review,
+        serde_json::json!({ "id": pending.id, "approved": false }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: This emote was already rejected"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_chat_emotes() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (broadcaster, _) = create_user(&global, "broadcaster").await;
+    let (other, _) = create_user(&global, "other").await;
+
+    create_emote(&global, broadcaster.id, "Kappa", emote::Status::Approved).await;
+    create_emote(&global, broadcaster.id, "PogChamp", emote::Status::Approved).await;
+    create_emote(
+        &global,
+        broadcaster.id,
+        "pending",
+        emote::Status::PendingReview,
+    )
+    .await;
+    create_emote(&global, other.id, "other", emote::Status::Approved).await;
+
+    let chat = r#"
+        query Chat($channelId: UUID!, $names: [String!]! = []) {
+            emote {
+                chat(channelId: $channelId, names: $names) {
+                    name
+                    url
+                    provider
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        None,
+        global_role::Permission::default(),
+        chat,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let mut names = res.data.into_json().unwrap()["emote"]["chat"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["name"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, vec!["Kappa", "PogChamp"]);
+
+    let res = execute(
+        &global,
+        None,
+        global_role::Permission::default(),
+        chat,
+        serde_json::json!({ "channelId": broadcaster.id, "names": ["Kappa", "pending", "other"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["emote"]["chat"],
+        serde_json::json!([{
+            "name": "Kappa",
+            "url": "https://cdn.test/emotes/Kappa.webp",
+            "provider": null,
+        }])
+    );
+
+    let res = execute(
+        &global,
+        None,
+        global_role::Permission::default(),
+        chat,
+        serde_json::json!({ "channelId": broadcaster.id, "names": vec!["Kappa"; 101] }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: At most 100 names can be resolved at once"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_emote_submit_checks() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        emotes: EmoteConfig {
+            base_slots: 1,
+            sub_points_per_slot: 0,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (broadcaster, broadcaster_session) = create_user(&global, "broadcaster").await;
+
+    let submit = r#"
+        mutation Submit($channelId: UUID!, $imageUrl: String!) {
+            emote {
+                submit(channelId: $channelId, name: "pog", imageUrl: $imageUrl) {
+                    id
+                }
+            }
+        }
+    "#;
+
+    // The image is fetched by the server, it must not reach into our own network.
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        submit,
+        serde_json::json!({ "channelId": broadcaster.id, "imageUrl": "https://localhost/pog.png" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Emote image could not be fetched or is too large"
+    );
+
+    create_emote(&global, broadcaster.id, "taken", emote::Status::Approved).await;
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        submit,
+        serde_json::json!({ "channelId": broadcaster.id, "imageUrl": "https://cdn.test/pog.png" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: All emote slots are in use"
+    );
+}
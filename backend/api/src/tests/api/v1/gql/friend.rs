@@ -0,0 +1,369 @@
+use serial_test::serial;
+
+use crate::{
+    database::global_role,
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+const SEND: &str = r#"
+    mutation($userId: UUID!) {
+        friend {
+            sendRequest(userId: $userId) {
+                userId
+                incoming
+            }
+        }
+    }
+"#;
+
+const ACCEPT: &str = r#"
+    mutation($userId: UUID!) {
+        friend {
+            acceptRequest(userId: $userId) {
+                userId
+            }
+        }
+    }
+"#;
+
+const FRIENDS: &str = r#"
+    query {
+        friend {
+            friends {
+                user {
+                    username
+                }
+            }
+        }
+    }
+"#;
+
+fn friends(res: async_graphql::Response) -> Vec<String> {
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    res.data.into_json().unwrap()["friend"]["friends"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["user"]["username"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn removed(res: async_graphql::Response, field: &str) -> bool {
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    res.data.into_json().unwrap()["friend"][field]
+        .as_bool()
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_friend_requests() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (alice, alice_session) = create_user(&global, "alice").await;
+    let (bob, bob_session) = create_user(&global, "bob").await;
+    let (carol, carol_session) = create_user(&global, "carol").await;
+
+    let res = execute(
+        &global,
+        Some(&alice_session),
+        global_role::Permission::default(),
+        SEND,
+        serde_json::json!({ "userId": alice.id }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You can't befriend yourself"
+    );
+
+    let res = execute(
+        &global,
+        Some(&alice_session),
+        global_role::Permission::default(),
+        SEND,
+        serde_json::json!({ "userId": bob.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({ "friend": { "sendRequest": { "userId": bob.id.to_string(), "incoming": false } } })
+    );
+
+    // Asking twice keeps the first request.
+    let res = execute(
+        &global,
+        Some(&alice_session),
+        global_role::Permission::default(),
+        SEND,
+        serde_json::json!({ "userId": bob.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(
+        &global,
+        Some(&bob_session),
+        global_role::Permission::default(),
+        SEND,
+        serde_json::json!({ "userId": alice.id }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: This user already asked you to be friends, accept their request instead"
+    );
+
+    let res = execute(
+        &global,
+        Some(&bob_session),
+        global_role::Permission::default(),
+        "query { friend { requests { userId incoming } } }",
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({ "friend": { "requests": [{ "userId": alice.id.to_string(), "incoming": true }] } })
+    );
+
+    let res = execute(
+        &global,
+        Some(&alice_session),
+        global_role::Permission::default(),
+        ACCEPT,
+        serde_json::json!({ "userId": bob.id }),
+    )
+    .await;
+    assert_eq!(res.errors[0].message, "NotFound: Friend request not found");
+
+    let res = execute(
+        &global,
+        Some(&bob_session),
+        global_role::Permission::default(),
+        ACCEPT,
+        serde_json::json!({ "userId": alice.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({ "friend": { "acceptRequest": { "userId": alice.id.to_string() } } })
+    );
+
+    // The friendship is listed for both users and the request is gone.
+    assert_eq!(
+        friends(
+            execute(
+                &global,
+                Some(&alice_session),
+                global_role::Permission::default(),
+                FRIENDS,
+                serde_json::json!({})
+            )
+            .await
+        ),
+        vec!["bob"]
+    );
+    assert_eq!(
+        friends(
+            execute(
+                &global,
+                Some(&bob_session),
+                global_role::Permission::default(),
+                FRIENDS,
+                serde_json::json!({})
+            )
+            .await
+        ),
+        vec!["alice"]
+    );
+
+    let res = execute(
+        &global,
+        Some(&alice_session),
+        global_role::Permission::default(),
+        "query { friend { requests(incoming: false) { userId } } }",
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({ "friend": { "requests": [] } })
+    );
+
+    let res = execute(
+        &global,
+        Some(&alice_session),
+        global_role::Permission::default(),
+        SEND,
+        serde_json::json!({ "userId": bob.id }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You are already friends"
+    );
+
+    // Declining and cancelling only remove a pending request once.
+    const DECLINE: &str = "mutation($userId: UUID!) { friend { declineRequest(userId: $userId) } }";
+    const CANCEL: &str = "mutation($userId: UUID!) { friend { cancelRequest(userId: $userId) } }";
+
+    let res = execute(
+        &global,
+        Some(&alice_session),
+        global_role::Permission::default(),
+        SEND,
+        serde_json::json!({ "userId": carol.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert!(removed(
+        execute(
+            &global,
+            Some(&carol_session),
+            global_role::Permission::default(),
+            DECLINE,
+            serde_json::json!({ "userId": alice.id })
+        )
+        .await,
+        "declineRequest"
+    ));
+    assert!(!removed(
+        execute(
+            &global,
+            Some(&carol_session),
+            global_role::Permission::default(),
+            DECLINE,
+            serde_json::json!({ "userId": alice.id })
+        )
+        .await,
+        "declineRequest"
+    ));
+
+    let res = execute(
+        &global,
+        Some(&alice_session),
+        global_role::Permission::default(),
+        SEND,
+        serde_json::json!({ "userId": carol.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert!(removed(
+        execute(
+            &global,
+            Some(&alice_session),
+            global_role::Permission::default(),
+            CANCEL,
+            serde_json::json!({ "userId": carol.id })
+        )
+        .await,
+        "cancelRequest"
+    ));
+
+    // Removing a friend ends the friendship for both users.
+    const REMOVE: &str = "mutation($userId: UUID!) { friend { remove(userId: $userId) } }";
+
+    assert!(removed(
+        execute(
+            &global,
+            Some(&bob_session),
+            global_role::Permission::default(),
+            REMOVE,
+            serde_json::json!({ "userId": alice.id })
+        )
+        .await,
+        "remove"
+    ));
+    assert!(!removed(
+        execute(
+            &global,
+            Some(&alice_session),
+            global_role::Permission::default(),
+            REMOVE,
+            serde_json::json!({ "userId": bob.id })
+        )
+        .await,
+        "remove"
+    ));
+    assert!(friends(
+        execute(
+            &global,
+            Some(&alice_session),
+            global_role::Permission::default(),
+            FRIENDS,
+            serde_json::json!({})
+        )
+        .await
+    )
+    .is_empty());
+
+    // Blocking a user removes the friendship, and they can't ask again.
+    let res = execute(
+        &global,
+        Some(&alice_session),
+        global_role::Permission::default(),
+        SEND,
+        serde_json::json!({ "userId": carol.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let res = execute(
+        &global,
+        Some(&carol_session),
+        global_role::Permission::default(),
+        ACCEPT,
+        serde_json::json!({ "userId": alice.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(
+        &global,
+        Some(&carol_session),
+        global_role::Permission::default(),
+        "mutation($userId: UUID!) { user { blockUser(userId: $userId) { userId } } }",
+        serde_json::json!({ "userId": alice.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    assert!(friends(
+        execute(
+            &global,
+            Some(&alice_session),
+            global_role::Permission::default(),
+            FRIENDS,
+            serde_json::json!({})
+        )
+        .await
+    )
+    .is_empty());
+
+    let res = execute(
+        &global,
+        Some(&alice_session),
+        global_role::Permission::default(),
+        SEND,
+        serde_json::json!({ "userId": carol.id }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You can't befriend this user"
+    );
+}
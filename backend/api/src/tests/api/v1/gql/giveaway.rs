@@ -0,0 +1,272 @@
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    database::{channel_event, giveaway, global_role},
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_giveaway() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (broadcaster, broadcaster_session) = create_user(&global, "broadcaster").await;
+    let (fan, fan_session) = create_user(&global, "fan").await;
+    let (_, viewer_session) = create_user(&global, "viewer").await;
+
+    // The fan follows and subscribes, the viewer does neither.
+    for kind in [
+        channel_event::Kind::Follow,
+        channel_event::Kind::Subscription,
+    ] {
+        sqlx::query!(
+            "INSERT INTO channel_events (channel_id, user_id, kind, amount, message) VALUES ($1, $2, $3, $4, $5)",
+            broadcaster.id,
+            fan.id,
+            i64::from(kind),
+            1,
+            "",
+        )
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    }
+
+    let channel = serde_json::json!({ "channelId": broadcaster.id });
+
+    let start = r#"
+        mutation Start($channelId: UUID!) {
+            giveaway {
+                start(channelId: $channelId, title: "A game key", keyword: "!Enter", followersOnly: true, subscriberWeight: 3) {
+                    keyword
+                    status
+                    commitment
+                    seed
+                }
+            }
+        }
+    "#;
+
+    // Only the broadcaster can start a giveaway.
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        start,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        start,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let started = res.data.into_json().unwrap()["giveaway"]["start"].clone();
+    assert_eq!(started["keyword"], "!enter");
+    assert_eq!(started["status"], "OPEN");
+    assert_eq!(started["seed"], serde_json::Value::Null);
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        start,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: A giveaway is already running in this chat"
+    );
+
+    // The fan enters with the keyword in chat.
+    let res = execute(
+        &global,
+        Some(&fan_session),
+        global_role::Permission::default(),
+        r#"
+            mutation Send($channelId: UUID!) {
+                chat {
+                    sendMessage(channelId: $channelId, content: "!enter") {
+                        id
+                    }
+                }
+            }
+        "#,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let enter = r#"
+        mutation Enter($channelId: UUID!) {
+            giveaway {
+                enter(channelId: $channelId) {
+                    tickets
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&fan_session),
+        global_role::Permission::default(),
+        enter,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You already entered this giveaway"
+    );
+
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        enter,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: Only followers can enter this giveaway"
+    );
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        r#"
+            mutation Draw($channelId: UUID!) {
+                giveaway {
+                    draw(channelId: $channelId) {
+                        id
+                        status
+                        seed
+                        entryCount
+                        winner {
+                            username
+                        }
+                    }
+                }
+            }
+        "#,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let drawn = res.data.into_json().unwrap()["giveaway"]["draw"].clone();
+    assert_eq!(drawn["status"], "DRAWN");
+    assert_eq!(drawn["entryCount"], 1);
+    assert_eq!(drawn["winner"]["username"], "fan");
+
+    // The revealed seed matches the commitment, and reproduces the winner from the entries.
+    let seed = drawn["seed"].as_str().unwrap();
+    assert_eq!(giveaway::commitment(seed), started["commitment"]);
+
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        r#"
+            query Entries($giveawayId: UUID!) {
+                giveaway {
+                    entries(giveawayId: $giveawayId) {
+                        userId
+                        tickets
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "giveawayId": drawn["id"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let entries = res.data.into_json().unwrap()["giveaway"]["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                e["userId"].as_str().unwrap().parse::<Uuid>().unwrap(),
+                e["tickets"].as_i64().unwrap() as i32,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(entries, vec![(fan.id, 3)]);
+    assert_eq!(giveaway::draw(seed, &entries), Some(fan.id));
+
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        r#"
+            query Messages($channelId: UUID!) {
+                chat {
+                    messages(channelId: $channelId, afterSequence: 0) {
+                        content
+                        type
+                    }
+                }
+            }
+        "#,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["chat"]["messages"],
+        serde_json::json!([
+            { "content": "Giveaway: A game key! Type !enter in chat to enter.", "type": "GIVEAWAY" },
+            { "content": "!enter", "type": "USER" },
+            { "content": "fan won the giveaway: A game key!", "type": "GIVEAWAY" },
+        ])
+    );
+
+    // Nothing is open after the draw.
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        r#"
+            mutation Cancel($channelId: UUID!) {
+                giveaway {
+                    cancel(channelId: $channelId) {
+                        status
+                    }
+                }
+            }
+        "#,
+        channel,
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "NotFound: This chat has no open giveaway"
+    );
+}
@@ -0,0 +1,94 @@
+use hyper::{HeaderMap, HeaderValue};
+
+use crate::{
+    api::v1::gql::{handlers::has_introspection_token, Schemas},
+    config::GqlConfig,
+};
+
+const INTROSPECTION_QUERY: &str = "query { __schema { queryType { name } } }";
+
+#[tokio::test]
+async fn test_introspection_disabled() {
+    let schemas = Schemas::new(&GqlConfig {
+        introspection: false,
+        ..Default::default()
+    });
+
+    let res = schemas.public.execute(INTROSPECTION_QUERY).await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = schemas.public.execute("query { noop }").await;
+    assert_eq!(res.errors.len(), 0);
+
+    let res = schemas.introspection.execute(INTROSPECTION_QUERY).await;
+    assert_eq!(res.errors.len(), 0);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({ "__schema": { "queryType": { "name": "Query" } } })
+    );
+}
+
+#[tokio::test]
+async fn test_introspection_enabled() {
+    let schemas = Schemas::new(&GqlConfig::default());
+
+    let res = schemas.public.execute(INTROSPECTION_QUERY).await;
+    assert_eq!(res.errors.len(), 0);
+}
+
+#[tokio::test]
+async fn test_suggestions() {
+    let tests = vec![(true, true), (false, false)];
+
+    for (suggestions, expected) in tests {
+        let schemas = Schemas::new(&GqlConfig {
+            suggestions,
+            ..Default::default()
+        });
+
+        let res = schemas.public.execute("query { nop }").await;
+        assert_eq!(res.errors.len(), 1);
+        assert_eq!(
+            res.errors[0].message.contains("Did you mean"),
+            expected,
+            "{}",
+            res.errors[0].message
+        );
+    }
+}
+
+#[test]
+fn test_has_introspection_token() {
+    let config = GqlConfig {
+        introspection_tokens: vec!["partner-token".to_string(), "other-token".to_string()],
+        ..Default::default()
+    };
+
+    let tests = vec![
+        (Some("partner-token"), true),
+        (Some("other-token"), true),
+        (Some("partner-token2"), false),
+        (Some("partner"), false),
+        (Some(""), false),
+        (None, false),
+    ];
+
+    for (token, expected) in tests {
+        let mut headers = HeaderMap::new();
+        if let Some(token) = token {
+            headers.insert("x-introspection-token", HeaderValue::from_static(token));
+        }
+
+        assert_eq!(
+            has_introspection_token(&config, &headers),
+            expected,
+            "{:?}",
+            token
+        );
+    }
+
+    assert!(!has_introspection_token(
+        &GqlConfig::default(),
+        &HeaderMap::new()
+    ));
+}
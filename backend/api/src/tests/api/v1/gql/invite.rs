@@ -0,0 +1,184 @@
+use std::{sync::Arc, time::Duration};
+
+use async_graphql::{Request, Variables};
+use chrono::Utc;
+use common::prelude::FutureTimeout;
+use serial_test::serial;
+
+use crate::{
+    api::v1::gql::{ext::RequestExt, request_context::RequestContext, schema},
+    config::{AppConfig, InviteConfig, TurnstileConfig},
+    database::{global_role::Permission, session, user},
+    dataloader::user_permissions::UserPermission,
+    tests::global::{mock_global_state, turnstile::mock_turnstile},
+};
+
+#[serial]
+#[tokio::test]
+async fn test_serial_invite_codes() {
+    let (mut rx, addr, h1) = mock_turnstile().await;
+    let (global, handler) = mock_global_state(AppConfig {
+        turnstile: TurnstileConfig {
+            url: addr,
+            secret_key: "DUMMY_KEY__LOREM_IPSUM".to_string(),
+        },
+        invites: InviteConfig {
+            required: true,
+            codes_per_user: 1,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM invite_codes")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "admin",
+        "admin@admin.com",
+        user::hash_password("admin"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let schema = schema();
+    let execute = |query: &str, variables: serde_json::Value, logged_in: bool| {
+        let ctx = Arc::new(RequestContext::new(false));
+        if logged_in {
+            ctx.set_session(Some((
+                session.clone(),
+                UserPermission {
+                    user_id: user.id,
+                    permissions: Permission::Admin,
+                    roles: vec![],
+                },
+            )));
+        }
+
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+    };
+
+    let create_code = "mutation { invite { createCode { code usedAt } } }";
+    let register = r#"
+        mutation Register($username: String!, $code: String) {
+            auth {
+                register(username: $username, password: "SuperStr0ngP@ssword!", email: "invited@admin.com", captchaToken: "1234", inviteCode: $code) {
+                    userId
+                }
+            }
+        }
+    "#;
+
+    let res = execute(create_code, serde_json::json!({}), true).await;
+    assert_eq!(res.errors.len(), 0);
+    let code = res.data.into_json().unwrap()["invite"]["createCode"]["code"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let res = execute(create_code, serde_json::json!({}), true).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You have no invite codes left"
+    );
+
+    let res = execute(
+        register,
+        serde_json::json!({ "username": "invited" }),
+        false,
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: An invite code is required to register"
+    );
+
+    let h2 = tokio::spawn(async move {
+        for _ in 0..2 {
+            let (_, resp) = rx.recv().await.unwrap();
+            resp.send(true).unwrap();
+        }
+    });
+
+    // Codes can be typed in lowercase and without the dash.
+    let res = execute(
+        register,
+        serde_json::json!({ "username": "invited", "code": code.to_lowercase().replace('-', "") }),
+        false,
+    )
+    .timeout(Duration::from_secs(2))
+    .await
+    .unwrap();
+    assert_eq!(res.errors.len(), 0);
+
+    let res = execute(
+        register,
+        serde_json::json!({ "username": "invited2", "code": code }),
+        false,
+    )
+    .timeout(Duration::from_secs(2))
+    .await
+    .unwrap();
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Invite code is invalid or was already used"
+    );
+
+    let res = execute(
+        "{ invite { stats { codesCreated codesUsed tree { depth users } } } }",
+        serde_json::json!({}),
+        true,
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0);
+    assert_eq!(
+        res.data.into_json().unwrap()["invite"]["stats"],
+        serde_json::json!({
+            "codesCreated": 1,
+            "codesUsed": 1,
+            "tree": [{ "depth": 1, "users": 1 }],
+        })
+    );
+
+    h1.abort();
+
+    h1.timeout(Duration::from_secs(1)).await.unwrap().ok(); // ignore error because we aborted it
+    h2.timeout(Duration::from_secs(1)).await.unwrap().unwrap();
+
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
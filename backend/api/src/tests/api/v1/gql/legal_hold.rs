@@ -0,0 +1,152 @@
+use serial_test::serial;
+
+use crate::{
+    database::global_role,
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_legal_hold_blocks_deletion() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (admin, admin_session) = create_user(&global, "admin").await;
+    let (channel, channel_session) = create_user(&global, "channel").await;
+
+    let place = r#"
+        mutation Place($subjectId: UUID!, $reason: String!) {
+            legalHold {
+                place(subjectKind: CHANNEL, subjectId: $subjectId, reason: $reason) {
+                    id
+                    active
+                }
+            }
+        }
+    "#;
+
+    // Only admins can place holds.
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        place,
+        serde_json::json!({ "subjectId": channel.id, "reason": "Case 1234" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        place,
+        serde_json::json!({ "subjectId": channel.id, "reason": "Case 1234" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["legalHold"]["place"]["active"], true);
+    let hold_id = json["legalHold"]["place"]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // A subject can only be held once at a time.
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        place,
+        serde_json::json!({ "subjectId": channel.id, "reason": "Case 5678" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: The subject is already under legal hold"
+    );
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        r#"
+            mutation Delete($channelId: UUID!) {
+                moderation {
+                    deleteMessages(channelId: $channelId, pattern: "spam", minutes: 10) {
+                        id
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "channelId": channel.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: The content of this channel is under legal hold"
+    );
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        r#"
+            mutation Release($id: UUID!) {
+                legalHold {
+                    release(id: $id, reason: "Case closed") {
+                        active
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "id": hold_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["legalHold"]["release"]["active"],
+        false
+    );
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        r#"
+            query History($id: UUID!) {
+                legalHold {
+                    history(id: $id) {
+                        action
+                        actorId
+                        note
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "id": hold_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["legalHold"]["history"],
+        serde_json::json!([
+            { "action": "PLACED", "actorId": admin.id, "note": "Case 1234" },
+            { "action": "RELEASED", "actorId": admin.id, "note": "Case closed" },
+        ])
+    );
+}
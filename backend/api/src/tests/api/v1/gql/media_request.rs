@@ -0,0 +1,291 @@
+use std::{sync::Arc, time::Duration};
+
+use async_graphql::{Request, Variables};
+use futures_util::StreamExt;
+use serial_test::serial;
+
+use crate::{
+    api::v1::gql::{ext::RequestExt, request_context::RequestContext, schema},
+    database::{global_role, session},
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+async fn next_queue(
+    stream: &mut (impl futures_util::Stream<Item = async_graphql::Response> + Unpin),
+) -> serde_json::Value {
+    let res = tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .expect("failed to execute stream")
+        .unwrap();
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    res.data.into_json().unwrap()["mediaRequestQueue"].clone()
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_media_requests() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (broadcaster, broadcaster_session) = create_user(&global, "broadcaster").await;
+    let (viewer, viewer_session) = create_user(&global, "viewer").await;
+
+    let schema = schema();
+    let context = |session: &session::Model| {
+        let ctx = Arc::new(RequestContext::new(false));
+        ctx.set_session(Some((session.clone(), Default::default())));
+        ctx
+    };
+    let channel = serde_json::json!({ "channelId": broadcaster.id });
+
+    let submit = r#"
+        mutation Submit($channelId: UUID!, $url: String!) {
+            mediaRequest {
+                submit(channelId: $channelId, url: $url) {
+                    id
+                    userId
+                    status
+                }
+            }
+        }
+    "#;
+    let enable = r#"
+        mutation Enable($channelId: UUID!) {
+            mediaRequest {
+                setEnabled(channelId: $channelId, enabled: true)
+            }
+        }
+    "#;
+
+    let song = serde_json::json!({
+        "channelId": broadcaster.id,
+        "url": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+    });
+
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        submit,
+        song.clone(),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: This channel does not take media requests"
+    );
+
+    // Only the broadcaster can turn media requests on.
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        enable,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        enable,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        submit,
+        serde_json::json!({
+            "channelId": broadcaster.id,
+            "url": "https://example.com/song.mp3",
+        }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Media from this site can't be requested"
+    );
+
+    let mut queue = schema.execute_stream(
+        Request::from(
+            r#"
+                subscription Queue($channelId: UUID!) {
+                    mediaRequestQueue(channelId: $channelId) {
+                        enabled
+                        nowPlaying {
+                            url
+                        }
+                        requests {
+                            url
+                        }
+                    }
+                }
+            "#,
+        )
+        .variables(Variables::from_json(channel.clone()))
+        .provide_global(global.clone()),
+    );
+    assert_eq!(
+        next_queue(&mut queue).await,
+        serde_json::json!({ "enabled": true, "nowPlaying": null, "requests": [] })
+    );
+
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        submit,
+        song.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let request = res.data.into_json().unwrap()["mediaRequest"]["submit"].clone();
+    assert_eq!(request["userId"], viewer.id.to_string());
+    assert_eq!(request["status"], "PENDING");
+
+    // Pending requests are not in the queue yet.
+    assert_eq!(
+        next_queue(&mut queue).await,
+        serde_json::json!({ "enabled": true, "nowPlaying": null, "requests": [] })
+    );
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        r#"
+            query Pending($channelId: UUID!) {
+                mediaRequest {
+                    pending(channelId: $channelId) {
+                        id
+                    }
+                }
+            }
+        "#,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["mediaRequest"]["pending"],
+        serde_json::json!([{ "id": request["id"] }])
+    );
+
+    let review = serde_json::json!({ "channelId": broadcaster.id, "id": request["id"] });
+    let approve = r#"
+        mutation Approve($channelId: UUID!, $id: UUID!) {
+            mediaRequest {
+                approve(channelId: $channelId, id: $id) {
+                    status
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&viewer_session),
+        global_role::Permission::default(),
+        approve,
+        review.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        approve,
+        review.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    assert_eq!(
+        next_queue(&mut queue).await,
+        serde_json::json!({
+            "enabled": true,
+            "nowPlaying": null,
+            "requests": [{ "url": song["url"] }],
+        })
+    );
+
+    // An approved request can't be approved again.
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        approve,
+        review.clone(),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "NotFound: Request not found or already reviewed"
+    );
+
+    let next = r#"
+        mutation Next($channelId: UUID!) {
+            mediaRequest {
+                next(channelId: $channelId) {
+                    status
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        next,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["mediaRequest"]["next"],
+        serde_json::json!({ "status": "PLAYED" })
+    );
+
+    assert_eq!(
+        next_queue(&mut queue).await,
+        serde_json::json!({
+            "enabled": true,
+            "nowPlaying": { "url": song["url"] },
+            "requests": [],
+        })
+    );
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        next,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["mediaRequest"]["next"],
+        serde_json::Value::Null
+    );
+}
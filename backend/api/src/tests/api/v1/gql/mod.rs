@@ -1,23 +1,113 @@
-use async_graphql::http::WebSocketProtocols;
+use async_graphql::{http::WebSocketProtocols, Request, Variables};
+use chrono::Utc;
 use common::prelude::FutureTimeout;
 use futures_util::{SinkExt, StreamExt};
 use http::HeaderValue;
 use hyper_tungstenite::tungstenite::client::IntoClientRequest;
 use serde_json::json;
-use std::time::Duration;
+use serial_test::serial;
+use std::{sync::Arc, time::Duration};
 
 use crate::{
     api,
-    api::v1::gql::{schema, PLAYGROUND_HTML},
+    api::v1::{
+        gql::{ext::RequestExt, request_context::RequestContext, schema, PLAYGROUND_HTML},
+        jwt::JwtState,
+    },
     config::{ApiConfig, AppConfig},
+    database::{global_role, session, user},
+    dataloader::user_permissions::UserPermission,
+    global::GlobalState,
     tests::global::mock_global_state,
 };
 
+mod access_token;
+mod approval;
 mod auth;
+mod ban_appeal;
+mod bot;
+mod channel;
 mod chat;
+mod checkout;
+mod dead_letter;
+mod deprecation;
+mod emote;
 mod errors;
+mod friend;
+mod giveaway;
+mod introspection;
+mod invite;
+mod legal_hold;
+mod media_request;
 mod models;
+mod moderation;
+mod pagination;
+mod payout;
+mod presence;
+mod stream_key;
 mod subscription;
+mod suspension;
+mod tag;
+mod two_fa;
+mod user;
+mod viewer_queue;
+mod vod;
+
+/// Creates a user with the password `test` and logs them in.
+async fn create_user(global: &Arc<GlobalState>, username: &str) -> (user::Model, session::Model) {
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        username,
+        format!("{}@test.com", username),
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    (user, session)
+}
+
+/// Runs a query as the user of the session with the given global permissions, or logged out without a session.
+async fn execute(
+    global: &Arc<GlobalState>,
+    session: Option<&session::Model>,
+    permissions: global_role::Permission,
+    query: &str,
+    variables: serde_json::Value,
+) -> async_graphql::Response {
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(session.map(|session| {
+        (
+            session.clone(),
+            UserPermission {
+                user_id: session.user_id,
+                permissions,
+                roles: vec![],
+            },
+        )
+    }));
+
+    schema()
+        .execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+        .await
+}
 
 #[tokio::test]
 async fn test_query_noop() {
@@ -55,6 +145,7 @@ async fn test_query_noop_via_http() {
         api: ApiConfig {
             bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
             tls: None,
+            ..Default::default()
         },
         ..Default::default()
     })
@@ -206,6 +297,7 @@ async fn test_playground() {
         api: ApiConfig {
             bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
             tls: None,
+            ..Default::default()
         },
         ..Default::default()
     })
@@ -244,3 +336,118 @@ async fn test_playground() {
         .unwrap()
         .unwrap();
 }
+
+#[serial]
+#[tokio::test]
+async fn test_serial_websocket_closed_on_revoked_session() {
+    let port = portpicker::pick_unused_port().expect("failed to pick port");
+
+    let (global, handler) = mock_global_state(AppConfig {
+        api: ApiConfig {
+            bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
+            tls: None,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    ).fetch_one(&*global.db).await.unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions (user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        chrono::Utc::now() + chrono::Duration::seconds(30)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let token = JwtState::from(session).serialize(&global).unwrap();
+
+    let h = tokio::spawn(api::run(global.clone()));
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let mut req = format!("ws://localhost:{}/v1/gql", port)
+        .into_client_request()
+        .unwrap();
+    req.headers_mut().insert(
+        http::header::SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_static(WebSocketProtocols::GraphQLWS.sec_websocket_protocol()),
+    );
+
+    let (mut ws_stream, _) = tokio_tungstenite::connect_async(req).await.unwrap();
+
+    let msg = json!({
+        "type": "connection_init",
+        "payload": { "sessionToken": token }
+    });
+
+    ws_stream
+        .send(tokio_tungstenite::tungstenite::Message::Text(
+            serde_json::to_string(&msg).unwrap(),
+        ))
+        .await
+        .unwrap();
+
+    let msg = serde_json::from_str::<serde_json::Value>(
+        ws_stream
+            .next()
+            .await
+            .unwrap()
+            .unwrap()
+            .to_string()
+            .as_str(),
+    )
+    .unwrap();
+    assert_eq!(msg, json!({ "type": "connection_ack" }));
+
+    // Give the websocket a moment to subscribe to the revoked sessions.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(global.revoke_sessions(user.id).await.unwrap(), 1);
+
+    let msg = ws_stream
+        .next()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("websocket was not closed")
+        .unwrap()
+        .unwrap();
+    match msg {
+        tokio_tungstenite::tungstenite::Message::Close(Some(frame)) => {
+            assert_eq!(
+                frame.code,
+                tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode::Policy
+            );
+            assert_eq!(frame.reason, "session was revoked");
+        }
+        msg => panic!("expected a close frame, got {:?}", msg),
+    }
+
+    drop(ws_stream);
+
+    handler
+        .cancel()
+        .timeout(std::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    h.timeout(std::time::Duration::from_secs(1))
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
@@ -0,0 +1,46 @@
+use uuid::Uuid;
+
+use crate::{
+    api::v1::gql::models::events::EmoteReview,
+    pb::{scuffle::events::EmoteReviewed, Event},
+};
+
+#[test]
+fn test_event_subject() {
+    let id = Uuid::parse_str("01890a5d-ac96-774b-bcce-b302099a8057").unwrap();
+
+    assert_eq!(
+        EmoteReviewed::subject(id),
+        "user:01890a5d-ac96-774b-bcce-b302099a8057:emotes:reviews"
+    );
+}
+
+#[test]
+fn test_event_gql_object() {
+    let emote_id = Uuid::new_v4();
+
+    let review = EmoteReview::try_from(EmoteReviewed {
+        emote_id: emote_id.to_string(),
+        name: "PogChamp".to_string(),
+        approved: false,
+        note: "Too blurry".to_string(),
+    })
+    .unwrap();
+
+    assert_eq!(
+        review,
+        EmoteReview {
+            emote_id,
+            name: "PogChamp".to_string(),
+            approved: false,
+            note: "Too blurry".to_string(),
+        }
+    );
+
+    // Ids which are not UUIDs are refused instead of reaching the client.
+    assert!(EmoteReview::try_from(EmoteReviewed {
+        emote_id: "not-an-id".to_string(),
+        ..Default::default()
+    })
+    .is_err());
+}
@@ -1,4 +1,6 @@
 mod date;
+mod events;
 mod global_roles;
 mod session;
+mod ulid;
 mod user;
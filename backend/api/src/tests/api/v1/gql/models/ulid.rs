@@ -0,0 +1,73 @@
+use async_graphql::{ScalarType, Value};
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::api::v1::gql::models::ulid::{decode, encode, GqlUlid};
+
+#[test]
+fn test_ulid_encoding() {
+    let cases = [
+        (
+            "00000000-0000-0000-0000-000000000000",
+            "00000000000000000000000000",
+        ),
+        (
+            "01890a5d-ac96-774b-bcce-b302099a8057",
+            "01H455VB4PEX5VSKNK084SN02Q",
+        ),
+        (
+            "ffffffff-ffff-ffff-ffff-ffffffffffff",
+            "7ZZZZZZZZZZZZZZZZZZZZZZZZZ",
+        ),
+    ];
+
+    for (uuid, ulid) in cases {
+        let id = Uuid::parse_str(uuid).unwrap();
+        assert_eq!(encode(id), ulid);
+        assert_eq!(decode(ulid), Some(id));
+    }
+}
+
+#[test]
+fn test_ulid_decode() {
+    let cases = [
+        // Lowercase and the letters read as digits are accepted.
+        ("01h455vb4pex5vskNk084sn02q", true),
+        ("O1H455VB4PEX5VSKNK084SN02Q", true),
+        ("0IH455VB4PEX5VSKNK084SN02Q", true),
+        // Too short, too long, not in the alphabet, and larger than 128 bits.
+        ("01H455VB4PEX5VSKNK084SN02", false),
+        ("01H455VB4PEX5VSKNK084SN02QQ", false),
+        ("01H455VB4PEX5VSKNK084SN0UQ", false),
+        ("80000000000000000000000000", false),
+    ];
+
+    for (ulid, valid) in cases {
+        assert_eq!(decode(ulid).is_some(), valid, "{}", ulid);
+    }
+}
+
+#[test]
+fn test_ulid_scalar() {
+    let id = Uuid::parse_str("01890a5d-ac96-774b-bcce-b302099a8057").unwrap();
+
+    assert_eq!(
+        ScalarType::to_value(&GqlUlid(id)),
+        Value::from("01H455VB4PEX5VSKNK084SN02Q")
+    );
+
+    let parsed = GqlUlid::parse(Value::from("01H455VB4PEX5VSKNK084SN02Q")).unwrap();
+    assert_eq!(parsed.0, id);
+
+    // UUIDs are still accepted as input.
+    let parsed = GqlUlid::parse(Value::from(id.to_string())).unwrap();
+    assert_eq!(parsed.0, id);
+
+    assert!(GqlUlid::parse(Value::from("not an id")).is_err());
+    assert!(GqlUlid::parse(Value::from(1)).is_err());
+
+    assert_eq!(
+        GqlUlid(id).timestamp(),
+        Utc.timestamp_millis_opt(1688096058518).unwrap()
+    );
+}
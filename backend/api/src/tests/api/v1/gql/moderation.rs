@@ -0,0 +1,372 @@
+use std::{sync::Arc, time::Duration};
+
+use async_graphql::{Name, Request, Variables};
+use chrono::Utc;
+use common::prelude::FutureTimeout;
+use futures_util::StreamExt;
+use serial_test::serial;
+
+use crate::{
+    api::v1::gql::{ext::RequestExt, request_context::RequestContext, schema},
+    database::{chat_ban, global_role, session},
+    global::GlobalState,
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+fn request(
+    global: &Arc<GlobalState>,
+    session: &session::Model,
+    query: &str,
+    variables: Vec<(&str, String)>,
+) -> Request {
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session.clone(), Default::default())));
+
+    let mut vars = Variables::default();
+    for (name, value) in variables {
+        vars.insert(Name::new(name), async_graphql::Value::String(value));
+    }
+
+    Request::from(query)
+        .variables(vars)
+        .provide_global(global.clone())
+        .provide_context(ctx)
+}
+
+const BAN: &str = r#"
+    mutation Ban($channelId: UUID!, $userId: UUID!, $reason: String!, $expiresAt: DateRFC3339) {
+        moderation {
+            banUser(channelId: $channelId, userId: $userId, reason: $reason, expiresAt: $expiresAt) {
+                userId
+                reason
+                expiresAt
+                bannedBy {
+                    username
+                }
+            }
+        }
+    }
+"#;
+
+const UNBAN: &str = r#"
+    mutation Unban($channelId: UUID!, $userId: UUID!) {
+        moderation {
+            unbanUser(channelId: $channelId, userId: $userId)
+        }
+    }
+"#;
+
+#[tokio::test]
+#[serial]
+async fn test_serial_ban_user() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (channel, channel_session) = create_user(&global, "channel").await;
+    let (first, first_session) = create_user(&global, "first").await;
+    let (second, _) = create_user(&global, "second").await;
+    let (expired, _) = create_user(&global, "expired").await;
+
+    let ban = |user_id: uuid::Uuid, reason: &str, expires_at: Option<chrono::DateTime<Utc>>| {
+        serde_json::json!({
+            "channelId": channel.id,
+            "userId": user_id.to_string(),
+            "reason": reason,
+            "expiresAt": expires_at.map(|expires_at| expires_at.to_rfc3339()),
+        })
+    };
+
+    // Only the broadcaster can ban.
+    let res = execute(
+        &global,
+        Some(&first_session),
+        global_role::Permission::default(),
+        BAN,
+        ban(second.id, "", None),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        BAN,
+        ban(channel.id, "", None),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: The broadcaster can't be banned from their own chat"
+    );
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        BAN,
+        ban(first.id, "", Some(Utc::now() - chrono::Duration::hours(1))),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: The ban must end in the future"
+    );
+
+    let expires_at = Utc::now() + chrono::Duration::hours(1);
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        BAN,
+        ban(first.id, "spam", Some(expires_at)),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["moderation"]["banUser"]["reason"], "spam");
+    assert_eq!(
+        json["moderation"]["banUser"]["bannedBy"]["username"],
+        "channel"
+    );
+    assert!(json["moderation"]["banUser"]["expiresAt"].is_string());
+    assert!(chat_ban::is_banned(&global.db, channel.id, first.id)
+        .await
+        .unwrap());
+
+    // Banning again replaces the ban, here with a permanent one.
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        BAN,
+        ban(first.id, "more spam", None),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["moderation"]["banUser"]["reason"], "more spam");
+    assert!(json["moderation"]["banUser"]["expiresAt"].is_null());
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        BAN,
+        ban(second.id, "", None),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    sqlx::query!(
+        "INSERT INTO chat_bans (channel_id, user_id, banned_by, expires_at) VALUES ($1, $2, $1, $3)",
+        channel.id,
+        expired.id,
+        Utc::now() - chrono::Duration::minutes(1),
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+    assert!(!chat_ban::is_banned(&global.db, channel.id, expired.id)
+        .await
+        .unwrap());
+
+    let list = r#"
+        query Banned($channelId: UUID!, $after: Cursor) {
+            moderation {
+                bannedUsers(channelId: $channelId, after: $after, limit: 1) {
+                    userId
+                    cursor
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        list,
+        serde_json::json!({ "channelId": channel.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    let page = json["moderation"]["bannedUsers"].as_array().unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0]["userId"], second.id.to_string());
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        list,
+        serde_json::json!({ "channelId": channel.id, "after": page[0]["cursor"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    let page = json["moderation"]["bannedUsers"].as_array().unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0]["userId"], first.id.to_string());
+
+    // The ban which ran out is not listed.
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        list,
+        serde_json::json!({ "channelId": channel.id, "after": page[0]["cursor"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["moderation"]["bannedUsers"], serde_json::json!([]));
+
+    let unban = |user_id: uuid::Uuid| {
+        serde_json::json!({
+            "channelId": channel.id,
+            "userId": user_id.to_string(),
+        })
+    };
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        UNBAN,
+        unban(first.id),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["moderation"]["unbanUser"],
+        true
+    );
+    assert!(!chat_ban::is_banned(&global.db, channel.id, first.id)
+        .await
+        .unwrap());
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        UNBAN,
+        unban(first.id),
+    )
+    .await;
+    assert_eq!(
+        res.data.into_json().unwrap()["moderation"]["unbanUser"],
+        false
+    );
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        UNBAN,
+        unban(expired.id),
+    )
+    .await;
+    assert_eq!(
+        res.data.into_json().unwrap()["moderation"]["unbanUser"],
+        false
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_ban_ends_chat_subscription() {
+    let (global, handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (channel, channel_session) = create_user(&global, "channel").await;
+    let (viewer, viewer_session) = create_user(&global, "viewer").await;
+
+    let subscription = r#"
+        subscription ChatWatch($channelId: UUID!) {
+            chatMessages(channelId: $channelId) {
+                content
+            }
+        }
+    "#;
+
+    let schema = schema();
+    let mut stream = schema.execute_stream(request(
+        &global,
+        &viewer_session,
+        subscription,
+        vec![("channelId", channel.id.to_string())],
+    ));
+
+    let res = tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .expect("failed to execute stream")
+        .unwrap();
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(
+        &global,
+        Some(&channel_session),
+        global_role::Permission::default(),
+        BAN,
+        serde_json::json!({ "channelId": channel.id, "userId": viewer.id, "reason": "" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .expect("subscription did not end")
+        .unwrap();
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You are banned from this chat"
+    );
+
+    // A banned user can't subscribe again.
+    let mut stream = schema.execute_stream(request(
+        &global,
+        &viewer_session,
+        subscription,
+        vec![("channelId", channel.id.to_string())],
+    ));
+    let res = tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .expect("failed to execute stream")
+        .unwrap();
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You are banned from this chat"
+    );
+
+    drop(stream);
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
@@ -0,0 +1,51 @@
+use async_graphql::{ScalarType, Value};
+use chrono::{TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::api::v1::gql::pagination::{page_limit, Cursor};
+
+#[test]
+fn test_cursor_scalar() {
+    let id = Uuid::parse_str("01890a5d-ac96-774b-bcce-b302099a8057").unwrap();
+    let time = Utc.timestamp_opt(1688096058, 518_123_000).unwrap();
+
+    let cursor = Cursor::new(time, id);
+    let value = ScalarType::to_value(&cursor);
+    assert_eq!(
+        value,
+        Value::from("1688096058518123_01H455VB4PEX5VSKNK084SN02Q")
+    );
+    assert_eq!(Cursor::parse(value).unwrap(), cursor);
+
+    // Times before 1970 are negative.
+    let cursor = Cursor::new(Utc.timestamp_opt(-1, 250_000_000).unwrap(), id);
+    assert_eq!(
+        Cursor::parse(ScalarType::to_value(&cursor)).unwrap(),
+        cursor
+    );
+
+    for invalid in [
+        "",
+        "1688096058518123",
+        "1688096058518123_",
+        "now_01H455VB4PEX5VSKNK084SN02Q",
+        "1688096058518123_01890a5d-ac96-774b-bcce-b302099a8057",
+    ] {
+        assert!(Cursor::parse(Value::from(invalid)).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_page_limit() {
+    let cases = [
+        (None, Some(20)),
+        (Some(1), Some(1)),
+        (Some(100), Some(100)),
+        (Some(0), None),
+        (Some(101), None),
+    ];
+
+    for (limit, expected) in cases {
+        assert_eq!(page_limit(limit, 20, 100).ok(), expected, "{:?}", limit);
+    }
+}
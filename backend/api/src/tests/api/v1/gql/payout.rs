@@ -0,0 +1,78 @@
+use crate::{
+    api::v1::gql::ext::RequestExt,
+    database::{session, user},
+};
+use async_graphql::Request;
+use chrono::Utc;
+use serial_test::serial;
+use std::sync::Arc;
+
+use crate::{
+    api::v1::gql::{request_context::RequestContext, schema},
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_pending_review_not_admin() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+    let schema = schema();
+
+    let query = r#"
+        query {
+            payout {
+                pendingReview {
+                    id
+                }
+            }
+        }
+    "#;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let res = schema
+        .execute(
+            Request::from(query)
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+        .await;
+
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You need to be an admin"
+    );
+}
@@ -0,0 +1,280 @@
+use std::{sync::Arc, time::Duration};
+
+use async_graphql::{Name, Request, Value, Variables};
+use chrono::Utc;
+use fred::prelude::KeysInterface;
+use futures_util::StreamExt;
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    api::v1::gql::{ext::RequestExt, request_context::RequestContext, schema},
+    database::{channel_event, session, user},
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_friends_presence() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut users = Vec::new();
+    for (name, visibility, share_watching) in [
+        ("viewer", user::PresenceVisibility::Nobody, false),
+        ("friend", user::PresenceVisibility::Friends, true),
+        ("followed", user::PresenceVisibility::Followers, false),
+        ("hidden", user::PresenceVisibility::Nobody, true),
+        ("no_friend", user::PresenceVisibility::Friends, true),
+        ("unfollowed", user::PresenceVisibility::Followers, false),
+    ] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key, presence_visibility, presence_share_watching) VALUES ($1, $1, $2, $3, $4, $5, $6) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+            i64::from(visibility),
+            share_watching,
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+        users.push(user);
+    }
+    let (viewer, friend, followed, hidden, no_friend, unfollowed) = (
+        &users[0], &users[1], &users[2], &users[3], &users[4], &users[5],
+    );
+
+    // Following each other doesn't make users friends, only an accepted friend request does.
+    for (user_id, channel_id) in [
+        (viewer.id, friend.id),
+        (viewer.id, followed.id),
+        (viewer.id, hidden.id),
+        (viewer.id, no_friend.id),
+        (no_friend.id, viewer.id),
+    ] {
+        sqlx::query!(
+            "INSERT INTO channel_events (channel_id, user_id, kind) VALUES ($1, $2, $3)",
+            channel_id,
+            user_id,
+            i64::from(channel_event::Kind::Follow),
+        )
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    }
+
+    // Friends see the presence of users who show it to followers without following them.
+    for friend_id in [friend.id, unfollowed.id] {
+        sqlx::query!(
+            "INSERT INTO friends (user_id, friend_id) VALUES ($1, $2), ($2, $1)",
+            viewer.id,
+            friend_id,
+        )
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    }
+
+    let watched = Uuid::new_v4();
+    for user in &users[1..] {
+        global.touch_presence(user.id, Some(watched)).await.unwrap();
+    }
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        viewer.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let res = schema()
+        .execute(
+            Request::from(
+                r#"
+                    query {
+                        presence {
+                            friendsPresence {
+                                user {
+                                    username
+                                }
+                                watchingChannelId
+                            }
+                        }
+                    }
+                "#,
+            )
+            .variables(Variables::default())
+            .provide_global(global.clone())
+            .provide_context(ctx),
+        )
+        .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let mut presences = res.data.into_json().unwrap()["presence"]["friendsPresence"]
+        .as_array()
+        .unwrap()
+        .clone();
+    presences.sort_by_key(|p| p["user"]["username"].as_str().unwrap().to_string());
+
+    assert_eq!(
+        presences,
+        vec![
+            serde_json::json!({ "user": { "username": "followed" }, "watchingChannelId": null }),
+            serde_json::json!({ "user": { "username": "friend" }, "watchingChannelId": watched.to_string() }),
+            serde_json::json!({ "user": { "username": "unfollowed" }, "watchingChannelId": null }),
+        ]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_user_online() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut users = Vec::new();
+    for (name, visibility) in [
+        ("viewer", user::PresenceVisibility::Nobody),
+        ("friend", user::PresenceVisibility::Friends),
+        ("hidden", user::PresenceVisibility::Nobody),
+    ] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key, presence_visibility) VALUES ($1, $1, $2, $3, $4, $5) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+            i64::from(visibility),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+        users.push(user);
+    }
+    let (viewer, friend, hidden) = (&users[0], &users[1], &users[2]);
+
+    for channel_id in [friend.id, hidden.id] {
+        sqlx::query!(
+            "INSERT INTO channel_events (channel_id, user_id, kind) VALUES ($1, $2, $3)",
+            channel_id,
+            viewer.id,
+            i64::from(channel_event::Kind::Follow),
+        )
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    }
+
+    sqlx::query!(
+        "INSERT INTO friends (user_id, friend_id) VALUES ($1, $2), ($2, $1)",
+        viewer.id,
+        friend.id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    for user in &users {
+        let _: () = global
+            .redis
+            .del(format!("presence:{}", user.id))
+            .await
+            .unwrap();
+    }
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        viewer.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let schema = schema();
+
+    let subscribe = |user_id: Uuid| {
+        let mut variables = Variables::default();
+        variables.insert(Name::new("userId"), Value::from(user_id.to_string()));
+
+        schema.execute_stream(
+            Request::from("subscription($userId: UUID!) { userOnline(userId: $userId) }")
+                .variables(variables)
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    // Users who do not share their presence cannot be listened to.
+    let res = subscribe(hidden.id).next().await.unwrap();
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: this user does not let you see when they are online"
+    );
+
+    let mut stream = subscribe(friend.id);
+
+    let res = tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({ "userOnline": false })
+    );
+
+    global.touch_presence(friend.id, None).await.unwrap();
+    global.touch_presence(hidden.id, None).await.unwrap();
+
+    let res = tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({ "userOnline": true })
+    );
+
+    let is_online = |user_id: Uuid| {
+        let mut variables = Variables::default();
+        variables.insert(Name::new("id"), Value::from(user_id.to_string()));
+
+        schema.execute(
+            Request::from("query($id: UUID!) { userById(id: $id) { isOnline } }")
+                .variables(variables)
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    for (user_id, expected) in [(viewer.id, false), (friend.id, true), (hidden.id, false)] {
+        let res = is_online(user_id).await;
+        assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+        assert_eq!(
+            res.data.into_json().unwrap(),
+            serde_json::json!({ "userById": { "isOnline": expected } })
+        );
+    }
+}
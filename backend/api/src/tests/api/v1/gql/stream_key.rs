@@ -0,0 +1,86 @@
+use chrono::Utc;
+use serial_test::serial;
+
+use crate::{
+    config::AppConfig,
+    database::global_role,
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_stream_key_delete_elevated() {
+    let (global, _handler) = mock_global_state(AppConfig::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (channel, session) = create_user(&global, "broadcaster").await;
+
+    let key_id = sqlx::query_scalar!(
+        "INSERT INTO stream_keys(channel_id, name, key) VALUES ($1, $2, $3) RETURNING id",
+        channel.id,
+        "OBS",
+        "DEADBEEF",
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let delete = r#"
+        mutation Delete($channelId: UUID!, $id: UUID!) {
+            streamKey {
+                delete(channelId: $channelId, id: $id)
+            }
+        }
+    "#;
+    let variables = serde_json::json!({ "channelId": channel.id, "id": key_id });
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::default(),
+        delete,
+        variables.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You need to reauthenticate to do this"
+    );
+
+    sqlx::query!(
+        "UPDATE sessions SET elevated_until = $1 WHERE id = $2",
+        Utc::now() + chrono::Duration::seconds(120),
+        session.id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::default(),
+        delete,
+        variables,
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(res.data.into_json().unwrap()["streamKey"]["delete"], true);
+
+    let remaining = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM stream_keys WHERE channel_id = $1",
+        channel.id,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(remaining, Some(0));
+}
@@ -2,7 +2,8 @@ use std::{sync::Arc, time::Duration};
 
 use crate::{
     api::v1::gql::{ext::RequestExt, request_context::RequestContext, schema},
-    database::{session, user},
+    config::{AppConfig, ChatConfig},
+    database::{global_role::Permission, session, user},
     pb,
     tests::global::mock_global_state,
 };
@@ -142,6 +143,109 @@ async fn test_serial_user_display_name_subscription() {
         .expect("failed to cancel context");
 }
 
+async fn next_update(
+    stream: &mut (impl futures_util::Stream<Item = async_graphql::Response> + Unpin),
+) -> serde_json::Value {
+    let res = tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .expect("failed to execute stream")
+        .unwrap();
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    res.data.into_json().unwrap()["userUpdates"].clone()
+}
+
+#[serial]
+#[tokio::test]
+async fn test_serial_user_updates_subscription() {
+    let (global, handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    let user =
+        sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "admin",
+        "admin@admin.com",
+        user::hash_password("admin"),
+        user::generate_stream_key(),
+    )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+    {
+        let query = r#"
+            subscription($userId: UUID!) {
+                userUpdates(userId: $userId) {
+                    username
+                    displayName
+                    displayColor {
+                        color {
+                            name
+                        }
+                    }
+                }
+            }
+        "#;
+
+        let mut variables = Variables::default();
+        variables.insert(Name::new("userId"), Value::from(user.id.to_string()));
+
+        let mut stream = schema().execute_stream(
+            Request::from(query)
+                .variables(variables)
+                .provide_global(global.clone())
+                .provide_context(Arc::new(RequestContext::new(false))),
+        );
+
+        assert_eq!(
+            next_update(&mut stream).await,
+            serde_json::json!({ "username": "admin", "displayName": "admin", "displayColor": null })
+        );
+
+        global
+            .publish_event(
+                user.id,
+                &pb::scuffle::events::UserDisplayName {
+                    display_name: Some("Admin".to_string()),
+                    username: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            next_update(&mut stream).await,
+            serde_json::json!({ "username": "admin", "displayName": "Admin", "displayColor": null })
+        );
+
+        // Changing the color keeps the name from before.
+        global
+            .set_display_color(user.id, Permission::none(), Some("blue"), None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            next_update(&mut stream).await,
+            serde_json::json!({
+                "username": "admin",
+                "displayName": "Admin",
+                "displayColor": { "color": { "name": "blue" } },
+            })
+        );
+    }
+
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
+
 #[serial]
 #[tokio::test]
 async fn test_serial_chat_subscribe() {
@@ -240,25 +344,29 @@ async fn test_serial_chat_subscribe() {
         );
 
         // The above is the initial event send.
-        // We now need to publish an event to the redis channel to trigger the subscription.
-        let count: i32 = global
-            .redis
-            .publish(
-                format!("user:{}:chat:messages", user.id),
+        // We now need to publish an event to trigger the subscription.
+        let sequence = global
+            .publish_chat_message(
+                user.id,
                 pb::scuffle::events::ChatMessage {
                     author_id: user.id.to_string(),
                     channel_id: user.id.to_string(),
                     content: "Hello world!".to_string(),
                     id: "00000000-0000-0000-0000-000000000001".to_string(),
                     created_at: chrono::Utc::now().timestamp(),
-                }
-                .encode_to_vec()
-                .as_slice(),
+                    r#type: pb::scuffle::events::chat_message::Type::User as i32,
+                    emotes: vec![],
+                    cheer: None,
+                    author_color: None,
+                    sequence: 0,
+                    author_vip: false,
+                    author_verified_bot: false,
+                },
             )
             .await
-            .expect("failed to publish to redis");
+            .expect("failed to publish chat message");
 
-        assert_eq!(count, 1);
+        assert_eq!(sequence, 1);
 
         let res = tokio::time::timeout(Duration::from_secs(1), stream.next())
             .await
@@ -375,6 +483,13 @@ async fn test_serial_chat_subscribe() {
                 content: "Hello world!".to_string(),
                 id: "00000000-0000-0000-0000-000000000002".to_string(),
                 created_at: chrono::Utc::now().timestamp(),
+                r#type: pb::scuffle::events::chat_message::Type::User as i32,
+                emotes: vec![],
+                cheer: None,
+                author_color: None,
+                sequence: 3,
+                author_vip: false,
+                author_verified_bot: false,
             }
             .encode_to_vec()
             .as_slice(),
@@ -391,3 +506,118 @@ async fn test_serial_chat_subscribe() {
         .await
         .expect("failed to cancel context");
 }
+
+async fn next_message(
+    stream: &mut (impl futures_util::Stream<Item = async_graphql::Response> + Unpin),
+) -> serde_json::Value {
+    let res = tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .expect("failed to execute stream")
+        .unwrap();
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    res.data.into_json().unwrap()["chatMessages"].clone()
+}
+
+#[serial]
+#[tokio::test]
+async fn test_serial_chat_subscribe_in_order() {
+    let (global, handler) = mock_global_state(AppConfig {
+        chat: ChatConfig {
+            reorder_window: 100,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "admin",
+        "admin@admin.com",
+        user::hash_password("admin"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let query = r#"
+        subscription ChatWatch($channelId: UUID!) {
+            chatMessages(channelId: $channelId) {
+                content
+                sequence
+            }
+        }
+    "#;
+
+    let mut variables = Variables::default();
+    variables.insert(Name::new("channelId"), Value::from(user.id.to_string()));
+
+    let schema = schema();
+    let mut stream = schema.execute_stream(
+        Request::from(query)
+            .variables(variables)
+            .provide_global(global.clone())
+            .provide_context(Arc::new(RequestContext::new(false))),
+    );
+
+    assert_eq!(
+        next_message(&mut stream).await,
+        serde_json::json!({ "content": "Welcome to the chat!", "sequence": 0 })
+    );
+
+    // Events published by different instances can arrive out of order.
+    for sequence in [2, 1, 1, 4] {
+        let _: i32 = global
+            .redis
+            .publish(
+                format!("user:{}:chat:messages", user.id),
+                pb::scuffle::events::ChatMessage {
+                    author_id: user.id.to_string(),
+                    channel_id: user.id.to_string(),
+                    content: format!("message {}", sequence),
+                    id: uuid::Uuid::new_v4().to_string(),
+                    created_at: chrono::Utc::now().timestamp(),
+                    r#type: pb::scuffle::events::chat_message::Type::User as i32,
+                    emotes: vec![],
+                    cheer: None,
+                    author_color: None,
+                    sequence,
+                    author_vip: false,
+                    author_verified_bot: false,
+                }
+                .encode_to_vec()
+                .as_slice(),
+            )
+            .await
+            .expect("failed to publish to redis");
+    }
+
+    assert_eq!(
+        next_message(&mut stream).await,
+        serde_json::json!({ "content": "message 1", "sequence": 1 })
+    );
+    assert_eq!(
+        next_message(&mut stream).await,
+        serde_json::json!({ "content": "message 2", "sequence": 2 })
+    );
+
+    // The duplicate is dropped, and after the window the missing event is skipped.
+    assert_eq!(
+        next_message(&mut stream).await,
+        serde_json::json!({ "content": "message 4", "sequence": 4 })
+    );
+
+    drop(stream);
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
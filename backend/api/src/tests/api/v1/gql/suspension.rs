@@ -0,0 +1,178 @@
+use chrono::Utc;
+use serial_test::serial;
+
+use crate::{
+    database::{global_role, session, user_suspension},
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_suspension_appeal_accepted() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (_, admin_session) = create_user(&global, "admin").await;
+    let (suspended, suspended_session) = create_user(&global, "suspended").await;
+
+    let suspend = r#"
+        mutation Suspend($userId: UUID!, $reason: String!) {
+            suspension {
+                suspend(userId: $userId, reason: $reason, durationHours: 24) {
+                    id
+                    active
+                    expiresAt
+                }
+            }
+        }
+    "#;
+
+    // Only admins can suspend users.
+    let res = execute(
+        &global,
+        Some(&suspended_session),
+        global_role::Permission::default(),
+        suspend,
+        serde_json::json!({ "userId": suspended.id, "reason": "Spam" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        suspend,
+        serde_json::json!({ "userId": suspended.id, "reason": "Spam" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["suspension"]["suspend"]["active"], true);
+    assert!(json["suspension"]["suspend"]["expiresAt"].is_string());
+
+    // The suspended user is logged out everywhere.
+    let session = sqlx::query_as!(
+        session::Model,
+        "SELECT * FROM sessions WHERE id = $1",
+        suspended_session.id,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert!(!session.is_valid());
+
+    let suspended_session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        suspended.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    // Logging in again only allows appealing.
+    let res = execute(
+        &global,
+        Some(&suspended_session),
+        global_role::Permission::default(),
+        r#"
+            mutation Send($channelId: UUID!) {
+                chat {
+                    sendMessage(channelId: $channelId, content: "hi") {
+                        id
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "channelId": suspended.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: Your account is suspended"
+    );
+
+    let appeal = r#"
+        mutation Appeal($message: String!) {
+            suspension {
+                appeal(message: $message) {
+                    id
+                    status
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&suspended_session),
+        global_role::Permission::default(),
+        appeal,
+        serde_json::json!({ "message": "It was a bot" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["suspension"]["appeal"]["status"], "PENDING");
+    let appeal_id = json["suspension"]["appeal"]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Every suspension gets a single appeal.
+    let res = execute(
+        &global,
+        Some(&suspended_session),
+        global_role::Permission::default(),
+        appeal,
+        serde_json::json!({ "message": "Please" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You already appealed this suspension"
+    );
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        r#"
+            mutation Accept($id: UUID!) {
+                suspension {
+                    acceptAppeal(id: $id, note: "Sorry") {
+                        status
+                        reviewNote
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "id": appeal_id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["suspension"]["acceptAppeal"]["status"], "ACCEPTED");
+    assert_eq!(json["suspension"]["acceptAppeal"]["reviewNote"], "Sorry");
+
+    assert!(user_suspension::active(&global.db, suspended.id)
+        .await
+        .unwrap()
+        .is_none());
+}
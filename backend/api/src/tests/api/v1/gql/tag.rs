@@ -0,0 +1,263 @@
+use serial_test::serial;
+
+use crate::{
+    database::{global_role, stream},
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_channel_tags() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM tags")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM streams")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (admin, admin_session) = create_user(&global, "admin").await;
+    let (broadcaster, broadcaster_session) = create_user(&global, "broadcaster").await;
+
+    let create = r#"
+        mutation Create($name: String!) {
+            tag {
+                create(name: $name) {
+                    name
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        create,
+        serde_json::json!({ "name": "Speedrun" }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You need to be an admin"
+    );
+
+    for name in ["Speedrun", "English", "Chill"] {
+        let res = execute(
+            &global,
+            Some(&admin_session),
+            global_role::Permission::Admin,
+            create,
+            serde_json::json!({ "name": name }),
+        )
+        .await;
+        assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    }
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        create,
+        serde_json::json!({ "name": "speedrun" }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: A tag with this name already exists"
+    );
+
+    let set_tags = r#"
+        mutation SetTags($channelId: UUID!, $tags: [String!]!) {
+            channel {
+                setTags(channelId: $channelId, tags: $tags) {
+                    name
+                }
+            }
+        }
+    "#;
+
+    // Only the broadcaster can pick the tags of their channel.
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        set_tags,
+        serde_json::json!({ "channelId": admin.id, "tags": ["Chill"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        set_tags,
+        serde_json::json!({ "channelId": broadcaster.id, "tags": ["Chill", "Cooking"] }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: There is no tag called cooking"
+    );
+
+    let too_many = (0..11).map(|i| format!("tag {}", i)).collect::<Vec<_>>();
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        set_tags,
+        serde_json::json!({ "channelId": broadcaster.id, "tags": too_many }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: A channel can have at most 10 tags"
+    );
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        set_tags,
+        serde_json::json!({ "channelId": broadcaster.id, "tags": ["speedrun", "English", "SPEEDRUN"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["setTags"],
+        serde_json::json!([{ "name": "Speedrun" }, { "name": "English" }])
+    );
+
+    let live = sqlx::query_as!(
+        stream::Model,
+        "INSERT INTO streams (channel_id, title, description, ingest_address, connection_id, lifecycle) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+        broadcaster.id,
+        "test",
+        "test",
+        "some address",
+        uuid::Uuid::new_v4(),
+        i64::from(stream::Lifecycle::Live),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let directory = r#"
+        query Directory($tags: [String!]!) {
+            channel {
+                directory(tags: $tags) {
+                    channelId
+                    streamId
+                    tags {
+                        name
+                    }
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::default(),
+        directory,
+        serde_json::json!({ "tags": ["english", "Speedrun"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["directory"],
+        serde_json::json!([{
+            "channelId": broadcaster.id,
+            "streamId": live.id,
+            "tags": [{ "name": "Speedrun" }, { "name": "English" }],
+        }])
+    );
+
+    // A channel has to have every tag to be listed.
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::default(),
+        directory,
+        serde_json::json!({ "tags": ["English", "Chill"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["directory"],
+        serde_json::json!([])
+    );
+
+    // Removing a tag takes it off the channel.
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        r#"
+            query {
+                tag {
+                    list {
+                        id
+                        name
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let list = res.data.into_json().unwrap()["tag"]["list"].clone();
+    assert_eq!(list[0]["name"], "Chill");
+    assert_eq!(list[1]["name"], "English");
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        r#"
+            mutation Remove($id: UUID!) {
+                tag {
+                    remove(id: $id)
+                }
+            }
+        "#,
+        serde_json::json!({ "id": list[1]["id"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::default(),
+        r#"
+            query Tags($channelId: UUID!) {
+                channel {
+                    tags(channelId: $channelId) {
+                        name
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["channel"]["tags"],
+        serde_json::json!([{ "name": "Speedrun" }])
+    );
+}
@@ -0,0 +1,475 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use common::prelude::FutureTimeout;
+use serial_test::serial;
+
+use crate::{
+    config::{AppConfig, PasskeyConfig, TurnstileConfig},
+    database::{global_role, login_link, recovery_code, session, user},
+    global::GlobalState,
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::{mock_global_state, turnstile::mock_turnstile},
+    },
+};
+
+#[serial]
+#[tokio::test]
+async fn test_serial_recovery_codes() {
+    let (mut rx, addr, h1) = mock_turnstile().await;
+    let (global, handler) = mock_global_state(AppConfig {
+        turnstile: TurnstileConfig {
+            url: addr,
+            secret_key: "DUMMY_KEY__DEADBEEF".to_string(),
+        },
+        ..Default::default()
+    })
+    .await;
+
+    let h2 = tokio::spawn(async move {
+        while let Some((_, resp)) = rx.recv().await {
+            resp.send(true).unwrap();
+        }
+    });
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key, passkey_required) VALUES ($1, $1, $2, $3, $4, TRUE) RETURNING *",
+        "admin",
+        "admin@admin.com",
+        user::hash_password("admin"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at, elevated_until) VALUES ($1, $2, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::default(),
+        "mutation { twoFa { regenerateRecoveryCodes } }",
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let codes: Vec<String> = serde_json::from_value(
+        res.data.into_json().unwrap()["twoFa"]["regenerateRecoveryCodes"].clone(),
+    )
+    .unwrap();
+    assert_eq!(codes.len(), 10);
+
+    let login = r#"
+        mutation Login($recoveryCode: String) {
+            auth {
+                login(username: "admin", password: "admin", captchaToken: "1234", recoveryCode: $recoveryCode) {
+                    userId
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        None,
+        global_role::Permission::default(),
+        login,
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: A passkey or recovery code is required to log in"
+    );
+
+    // Codes can be typed in upper case.
+    let res = execute(
+        &global,
+        None,
+        global_role::Permission::default(),
+        login,
+        serde_json::json!({ "recoveryCode": codes[0].to_uppercase() }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    // Each code only logs in once.
+    let res = execute(
+        &global,
+        None,
+        global_role::Permission::default(),
+        login,
+        serde_json::json!({ "recoveryCode": codes[0] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Recovery code is not valid or was already used"
+    );
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::default(),
+        "{ twoFa { recoveryCodesRemaining recoveryCodeEvents { action } } }",
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({
+            "twoFa": {
+                "recoveryCodesRemaining": 9,
+                "recoveryCodeEvents": [{ "action": "USED" }, { "action": "REGENERATED" }],
+            }
+        })
+    );
+
+    h1.abort();
+    h2.abort();
+
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
+
+async fn set_two_fa(global: &Arc<GlobalState>, user_id: uuid::Uuid, enabled: bool) {
+    sqlx::query!(
+        "UPDATE users SET passkey_required = $2 WHERE id = $1",
+        user_id,
+        enabled
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+}
+
+async fn check_two_fa(global: &Arc<GlobalState>, session_id: uuid::Uuid) {
+    sqlx::query!(
+        "UPDATE sessions SET two_fa_at = NOW() WHERE id = $1",
+        session_id
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+}
+
+const REMOVE_SEGMENT: &str = r#"
+    mutation Remove($channelId: UUID!) {
+        channel {
+            removeScheduleSegment(channelId: $channelId, id: "00000000-0000-0000-0000-000000000000")
+        }
+    }
+"#;
+
+#[serial]
+#[tokio::test]
+async fn test_serial_moderation_two_fa() {
+    let (global, handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    let (channel, session) = create_user(&global, "channel").await;
+    let none = global_role::Permission::default();
+
+    let set_required = r#"
+        mutation Set($channelId: UUID!) {
+            channel {
+                setModerationTwoFaRequired(channelId: $channelId, required: true)
+            }
+        }
+    "#;
+    let vars = serde_json::json!({ "channelId": channel.id });
+
+    // Requiring it without having it would lock the owner out.
+    let res = execute(&global, Some(&session), none, set_required, vars.clone()).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Enable two-factor authentication before requiring it"
+    );
+
+    set_two_fa(&global, channel.id, true).await;
+    let res = execute(&global, Some(&session), none, set_required, vars.clone()).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    // The session was not logged in with the passkey.
+    let res = execute(&global, Some(&session), none, REMOVE_SEGMENT, vars.clone()).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "TwoFaRequired: You need to reauthenticate with your passkey or a recovery code to do this"
+    );
+
+    check_two_fa(&global, session.id).await;
+    let res = execute(&global, Some(&session), none, REMOVE_SEGMENT, vars.clone()).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    set_two_fa(&global, channel.id, false).await;
+    let res = execute(&global, Some(&session), none, REMOVE_SEGMENT, vars.clone()).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "TwoFaRequired: You need to enable two-factor authentication to do this"
+    );
+
+    // Only changes are rejected.
+    let res = execute(
+        &global,
+        Some(&session),
+        none,
+        "query Get($channelId: UUID!) { channel { moderationTwoFaRequired(channelId: $channelId) } }",
+        vars.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({ "channel": { "moderationTwoFaRequired": true } })
+    );
+
+    // Admins need it as well when they moderate the channel.
+    let (admin, admin_session) = create_user(&global, "admin").await;
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        REMOVE_SEGMENT,
+        vars.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "TwoFaRequired: You need to enable two-factor authentication to do this"
+    );
+
+    set_two_fa(&global, admin.id, true).await;
+    check_two_fa(&global, admin_session.id).await;
+    let res = execute(
+        &global,
+        Some(&admin_session),
+        global_role::Permission::Admin,
+        REMOVE_SEGMENT,
+        vars,
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
+
+#[serial]
+#[tokio::test]
+async fn test_serial_admin_two_fa() {
+    let (global, handler) = mock_global_state(AppConfig {
+        passkeys: PasskeyConfig {
+            required_for_admins: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    let (channel, _) = create_user(&global, "channel").await;
+    let (admin, session) = create_user(&global, "admin").await;
+    let vars = serde_json::json!({ "channelId": channel.id });
+
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::Admin,
+        REMOVE_SEGMENT,
+        vars.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "TwoFaRequired: You need to enable two-factor authentication to do this"
+    );
+
+    // Admins can still look around without it.
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::Admin,
+        "query Get($channelId: UUID!) { channel { moderationTwoFaRequired(channelId: $channelId) } }",
+        vars.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    set_two_fa(&global, admin.id, true).await;
+    check_two_fa(&global, session.id).await;
+    let res = execute(
+        &global,
+        Some(&session),
+        global_role::Permission::Admin,
+        REMOVE_SEGMENT,
+        vars,
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
+
+#[serial]
+#[tokio::test]
+async fn test_serial_second_factor_without_password() {
+    let (global, handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    let (user, session) = create_user(&global, "admin").await;
+    set_two_fa(&global, user.id, true).await;
+    let none = global_role::Permission::default();
+
+    for code in ["first", "second"] {
+        sqlx::query!(
+            "INSERT INTO recovery_codes(user_id, code_hash) VALUES ($1, $2)",
+            user.id,
+            recovery_code::hash_code(code),
+        )
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    }
+
+    sqlx::query!(
+        "INSERT INTO login_links(user_id, token_hash, device_hash, expires_at, confirmed_at) VALUES ($1, $2, $3, $4, NOW())",
+        user.id,
+        login_link::hash_token("emailed"),
+        login_link::hash_token("device"),
+        Utc::now() + chrono::Duration::seconds(120),
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let login = r#"
+        mutation Login($recoveryCode: String) {
+            auth {
+                loginWithLink(deviceToken: "device", recoveryCode: $recoveryCode) {
+                    userId
+                }
+            }
+        }
+    "#;
+
+    let res = execute(&global, None, none, login, serde_json::json!({})).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: A passkey or recovery code is required to log in"
+    );
+
+    // The link was not used up by the failed attempt.
+    let res = execute(
+        &global,
+        None,
+        none,
+        login,
+        serde_json::json!({ "recoveryCode": "first" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    // The session remembers the second factor, for what requires two-factor authentication.
+    let checked = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM sessions WHERE user_id = $1 AND two_fa_at IS NOT NULL",
+        user.id,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(checked, Some(1));
+
+    let reauthenticate = r#"
+        mutation Reauthenticate($recoveryCode: String) {
+            auth {
+                reauthenticate(password: "test", recoveryCode: $recoveryCode)
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&session),
+        none,
+        reauthenticate,
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: A passkey or recovery code is required to reauthenticate"
+    );
+
+    let res = execute(
+        &global,
+        Some(&session),
+        none,
+        reauthenticate,
+        serde_json::json!({ "recoveryCode": "second" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let two_fa_at = sqlx::query_scalar!("SELECT two_fa_at FROM sessions WHERE id = $1", session.id)
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+    assert!(two_fa_at.is_some());
+
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
@@ -0,0 +1,1831 @@
+use std::sync::Arc;
+
+use async_graphql::{Request, Variables};
+use chrono::Utc;
+use serial_test::serial;
+
+use crate::{
+    api::v1::gql::{ext::RequestExt, request_context::RequestContext, schema},
+    config::{AppConfig, DisplayColorConfig},
+    database::{
+        channel_event, global_role::Permission, login_link, notification_settings, session, user,
+    },
+    dataloader::user_permissions::UserPermission,
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_set_display_color() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        display_colors: DisplayColorConfig {
+            cooldown: 0,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let query = r#"
+        mutation SetDisplayColor($color: String, $darkColor: String, $gradientEnd: String) {
+            user {
+                setDisplayColor(color: $color, darkColor: $darkColor, gradientEnd: $gradientEnd) {
+                    displayColor {
+                        color {
+                            name
+                            light
+                            dark
+                        }
+                        gradientEnd {
+                            light
+                        }
+                    }
+                }
+            }
+        }
+    "#;
+
+    let schema = schema();
+    let execute = |permissions: Permission, variables: serde_json::Value| {
+        let ctx = Arc::new(RequestContext::new(false));
+        ctx.set_session(Some((
+            session.clone(),
+            UserPermission {
+                user_id: user.id,
+                permissions,
+                roles: vec![],
+            },
+        )));
+
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+    };
+
+    let res = execute(Permission::none(), serde_json::json!({ "color": "blue" })).await;
+    assert_eq!(res.errors.len(), 0);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["setDisplayColor"]["displayColor"],
+        serde_json::json!({
+            "color": { "name": "blue", "light": "#1565c0", "dark": "#74c0fc" },
+            "gradientEnd": null,
+        })
+    );
+
+    let res = execute(
+        Permission::none(),
+        serde_json::json!({ "color": "#000080", "darkColor": "#ffff00" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You are only allowed to use palette colors"
+    );
+
+    let res = execute(
+        Permission::CustomDisplayColor,
+        serde_json::json!({ "color": "#000080", "darkColor": "#ffff00" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["setDisplayColor"]["displayColor"]["color"],
+        serde_json::json!({ "name": null, "light": "#000080", "dark": "#ffff00" })
+    );
+
+    // Navy disappears on the dark theme without a dark variant.
+    let res = execute(
+        Permission::CustomDisplayColor,
+        serde_json::json!({ "color": "#000080" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Color is not readable on the dark theme"
+    );
+
+    let res = execute(
+        Permission::none(),
+        serde_json::json!({ "color": "blue", "gradientEnd": "pink" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You are not allowed to use gradients"
+    );
+
+    let res = execute(Permission::none(), serde_json::json!({})).await;
+    assert_eq!(res.errors.len(), 0);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["setDisplayColor"]["displayColor"],
+        serde_json::Value::Null
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_display_color_rate_limit() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        display_colors: DisplayColorConfig {
+            cooldown: 0,
+            max_changes: 2,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let query = r#"
+        mutation SetDisplayColor($color: String) {
+            user {
+                setDisplayColor(color: $color) {
+                    id
+                }
+            }
+        }
+    "#;
+
+    let schema = schema();
+    let execute = |color: &str| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(serde_json::json!({ "color": color })))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    assert_eq!(execute("red").await.errors.len(), 0);
+    assert_eq!(execute("green").await.errors.len(), 0);
+
+    // Picking the current color again is not a change.
+    assert_eq!(execute("green").await.errors.len(), 0);
+
+    let res = execute("blue").await;
+    assert_eq!(res.errors.len(), 1);
+    assert!(res.errors[0]
+        .message
+        .starts_with("InvalidInput: You are changing your color too often"));
+
+    let stored = sqlx::query!("SELECT display_color FROM users WHERE id = $1", user.id)
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+    assert_eq!(stored.display_color, "green");
+
+    let changes = sqlx::query!(
+        r#"SELECT COUNT(*) AS "count!" FROM display_color_changes WHERE user_id = $1"#,
+        user.id
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(changes.count, 2);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_set_username() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    sqlx::query!(
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4)",
+        "taken",
+        "taken@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let query = r#"
+        mutation SetUsername($username: String!) {
+            user {
+                setUsername(username: $username) {
+                    username
+                    displayName
+                }
+            }
+        }
+    "#;
+
+    let schema = schema();
+    let execute = |username: &str| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(
+                    serde_json::json!({ "username": username }),
+                ))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let res = execute("Taken").await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Username already taken"
+    );
+
+    let res = execute("Renamed").await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["setUsername"],
+        serde_json::json!({ "username": "renamed", "displayName": "Renamed" })
+    );
+
+    // Changing the case is not a new username, so the cooldown does not apply.
+    let res = execute("RENAMED").await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["setUsername"]["displayName"],
+        "RENAMED"
+    );
+
+    let res = execute("other").await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You changed your username recently, try again in 30 days"
+    );
+
+    let history = sqlx::query_scalar!(
+        "SELECT username FROM username_history WHERE user_id = $1",
+        user.id
+    )
+    .fetch_all(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(history, vec!["test".to_string()]);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_set_bio() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let query = r#"
+        mutation SetBio($bio: String) {
+            user {
+                setBio(bio: $bio) {
+                    bio
+                }
+            }
+        }
+    "#;
+
+    let schema = schema();
+    let execute = |bio: Option<String>| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(serde_json::json!({ "bio": bio })))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let res = execute(Some("a".repeat(1001))).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Bio must be at most 1000 characters long"
+    );
+
+    let res = execute(Some(
+        "Hi, I stream *speedruns*.\n\n\n<b>[me](javascript:alert(1))</b>".to_string(),
+    ))
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["setBio"]["bio"],
+        "Hi, I stream *speedruns*.\n\n&lt;b>[me](#)&lt;/b>"
+    );
+
+    let res = execute(None).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["setBio"]["bio"],
+        serde_json::Value::Null
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_set_social_links() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let query = r#"
+        mutation SetSocialLinks($links: [SocialLinkInput!]!) {
+            user {
+                setSocialLinks(links: $links) {
+                    socialLinks {
+                        label
+                        url
+                    }
+                }
+            }
+        }
+    "#;
+
+    let schema = schema();
+    let execute = |links: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(serde_json::json!({ "links": links })))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let res = execute(serde_json::json!([
+        { "label": "Site", "url": "https://example.com" },
+        { "label": "Bad", "url": "javascript:alert(1)" },
+    ]))
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Url must be a http(s) or mailto link"
+    );
+
+    let links = serde_json::json!([
+        { "label": "YouTube", "url": "https://youtube.com/@test" },
+        { "label": "Discord", "url": "https://discord.gg/test" },
+        { "label": "Mail", "url": "mailto:test@test.com" },
+    ]);
+    let res = execute(links.clone()).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["setSocialLinks"]["socialLinks"],
+        links
+    );
+
+    // Setting the links again replaces them, in the new order.
+    let links = serde_json::json!([
+        { "label": "Discord", "url": "https://discord.gg/test" },
+        { "label": "YouTube", "url": "https://youtube.com/@test" },
+    ]);
+    let res = execute(links.clone()).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["setSocialLinks"]["socialLinks"],
+        links
+    );
+
+    let res = execute(serde_json::json!((0..6)
+        .map(
+            |i| serde_json::json!({ "label": format!("Link {}", i), "url": "https://example.com" })
+        )
+        .collect::<Vec<_>>()))
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You can have at most 5 links"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_update_profile() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        display_colors: DisplayColorConfig {
+            cooldown: 0,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        user.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let query = r#"
+        mutation UpdateProfile($input: ProfileInput!) {
+            user {
+                updateProfile(input: $input) {
+                    bio
+                    locale
+                    displayColor {
+                        color {
+                            name
+                        }
+                    }
+                    socialLinks {
+                        label
+                        url
+                    }
+                }
+            }
+        }
+    "#;
+
+    let schema = schema();
+    let execute = |input: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(serde_json::json!({ "input": input })))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let links = serde_json::json!([
+        { "label": "YouTube", "url": "https://youtube.com/@test" },
+        { "label": "Mail", "url": "mailto:test@test.com" },
+    ]);
+    let res = execute(serde_json::json!({
+        "displayColor": { "color": "blue" },
+        "bio": "Hi, I stream *speedruns*.",
+        "socialLinks": links,
+        "locale": "de-DE",
+    }))
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["updateProfile"],
+        serde_json::json!({
+            "bio": "Hi, I stream *speedruns*.",
+            "locale": "de-DE",
+            "displayColor": { "color": { "name": "blue" } },
+            "socialLinks": links,
+        })
+    );
+
+    // One invalid field rejects the whole change.
+    let res = execute(serde_json::json!({
+        "displayColor": { "color": "red" },
+        "bio": "Changed",
+        "socialLinks": [{ "label": "Bad", "url": "javascript:alert(1)" }],
+    }))
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Url must be a http(s) or mailto link"
+    );
+
+    let stored = sqlx::query_as!(user::Model, "SELECT * FROM users WHERE id = $1", user.id)
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+    assert_eq!(stored.bio, "Hi, I stream *speedruns*.");
+    assert_eq!(stored.display_color, "blue");
+
+    // Unset fields are left as they are.
+    let res = execute(serde_json::json!({ "bio": "" })).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["updateProfile"],
+        serde_json::json!({
+            "bio": null,
+            "locale": "de-DE",
+            "displayColor": { "color": { "name": "blue" } },
+            "socialLinks": links,
+        })
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_block_user() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut contexts = Vec::new();
+    let mut users = Vec::new();
+    for name in ["broadcaster", "troll"] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let session = sqlx::query_as!(
+            session::Model,
+            "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+            user.id,
+            Utc::now() + chrono::Duration::seconds(120)
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let ctx = Arc::new(RequestContext::new(false));
+        ctx.set_session(Some((session, Default::default())));
+        contexts.push(ctx);
+        users.push(user);
+    }
+    let (broadcaster, troll) = (&users[0], &users[1]);
+
+    sqlx::query!(
+        "INSERT INTO channel_events (channel_id, user_id, kind, amount, message) VALUES ($1, $2, $3, $4, $5)",
+        broadcaster.id,
+        troll.id,
+        i64::from(channel_event::Kind::Follow),
+        0,
+        "",
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "INSERT INTO chat_vips (channel_id, user_id) VALUES ($1, $2)",
+        broadcaster.id,
+        troll.id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let schema = schema();
+    let execute = |ctx: &Arc<RequestContext>, query: &str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let block = r#"
+        mutation Block($userId: UUID!) {
+            user {
+                blockUser(userId: $userId) {
+                    userId
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &contexts[0],
+        block,
+        serde_json::json!({ "userId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You can't block yourself"
+    );
+
+    // Blocking twice is fine.
+    for _ in 0..2 {
+        let res = execute(
+            &contexts[0],
+            block,
+            serde_json::json!({ "userId": troll.id }),
+        )
+        .await;
+        assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    }
+
+    let res = execute(
+        &contexts[0],
+        r#"
+            query {
+                user {
+                    blockedUsers {
+                        userId
+                        user {
+                            username
+                        }
+                    }
+                }
+            }
+        "#,
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["blockedUsers"],
+        serde_json::json!([{ "userId": troll.id, "user": { "username": "troll" } }])
+    );
+
+    // The follow and the VIP status are gone.
+    let follows = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM channel_events WHERE channel_id = $1",
+        broadcaster.id
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(follows, Some(0));
+    let vips = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM chat_vips WHERE channel_id = $1",
+        broadcaster.id
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(vips, Some(0));
+
+    let send = r#"
+        mutation SendChatMessage($channelId: UUID!, $content: String!) {
+            chat {
+                sendMessage(channelId: $channelId, content: $content) {
+                    id
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &contexts[1],
+        send,
+        serde_json::json!({ "channelId": broadcaster.id, "content": "hello" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You can't chat in this channel"
+    );
+
+    let unblock = r#"
+        mutation Unblock($userId: UUID!) {
+            user {
+                unblockUser(userId: $userId)
+            }
+        }
+    "#;
+
+    let res = execute(
+        &contexts[0],
+        unblock,
+        serde_json::json!({ "userId": troll.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(res.data.into_json().unwrap()["user"]["unblockUser"], true);
+
+    let res = execute(
+        &contexts[0],
+        unblock,
+        serde_json::json!({ "userId": troll.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(res.data.into_json().unwrap()["user"]["unblockUser"], false);
+
+    let res = execute(
+        &contexts[1],
+        send,
+        serde_json::json!({ "channelId": broadcaster.id, "content": "hello" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_sessions() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let expires_at = Utc::now() + chrono::Duration::seconds(120);
+    let current = session::create(
+        &*global.db,
+        user.id,
+        expires_at,
+        Some("127.0.0.1".parse().unwrap()),
+        Some("Mozilla/5.0 (X11; Linux x86_64; rv:109.0) Gecko/20100101 Firefox/117.0"),
+        false,
+    )
+    .await
+    .unwrap();
+    let other = session::create(&*global.db, user.id, expires_at, None, None, false)
+        .await
+        .unwrap();
+
+    // Logged out and expired sessions are not listed.
+    let logged_out = session::create(&*global.db, user.id, expires_at, None, None, false)
+        .await
+        .unwrap();
+    sqlx::query!(
+        "UPDATE sessions SET invalidated_at = NOW() WHERE id = $1",
+        logged_out.id
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+    session::create(
+        &*global.db,
+        user.id,
+        Utc::now() - chrono::Duration::seconds(1),
+        None,
+        None,
+        false,
+    )
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((current.clone(), Default::default())));
+
+    let schema = schema();
+    let execute = |query: &str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let list = r#"
+        query {
+            user {
+                sessions {
+                    id
+                    current
+                    ipAddress
+                    userAgent
+                }
+            }
+        }
+    "#;
+
+    let res = execute(list, serde_json::json!({})).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    let mut sessions = json["user"]["sessions"].as_array().unwrap().clone();
+    sessions.sort_by_key(|s| !s["current"].as_bool().unwrap());
+    assert_eq!(
+        sessions,
+        vec![
+            serde_json::json!({
+                "id": current.id,
+                "current": true,
+                "ipAddress": "127.0.0.1",
+                "userAgent": "Mozilla/5.0 (X11; Linux x86_64; rv:109.0) Gecko/20100101 Firefox/117.0",
+            }),
+            serde_json::json!({
+                "id": other.id,
+                "current": false,
+                "ipAddress": "",
+                "userAgent": "",
+            }),
+        ]
+    );
+
+    let revoke = r#"
+        mutation Revoke($id: UUID!) {
+            user {
+                revokeSession(id: $id)
+            }
+        }
+    "#;
+
+    let res = execute(revoke, serde_json::json!({ "id": other.id })).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(res.data.into_json().unwrap()["user"]["revokeSession"], true);
+
+    let res = execute(revoke, serde_json::json!({ "id": logged_out.id })).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["revokeSession"],
+        false
+    );
+
+    session::create(&*global.db, user.id, expires_at, None, None, false)
+        .await
+        .unwrap();
+
+    let res = execute(
+        "mutation { user { revokeOtherSessions } }",
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["revokeOtherSessions"],
+        1
+    );
+
+    let res = execute(list, serde_json::json!({})).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["sessions"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_notification_settings() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut users = Vec::new();
+    for username in ["channel", "viewer1", "viewer2"] {
+        users.push(sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, email_verified, password_hash, stream_key) VALUES ($1, $1, $2, TRUE, $3, $4) RETURNING *",
+            username,
+            global.encrypt_pii(&format!("{}@test.com", username)).unwrap(),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        ).fetch_one(&*global.db).await.unwrap());
+    }
+
+    for viewer in &users[1..] {
+        sqlx::query!(
+            "INSERT INTO channel_events (channel_id, user_id, kind) VALUES ($1, $2, $3)",
+            users[0].id,
+            viewer.id,
+            i64::from(channel_event::Kind::Follow),
+        )
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    }
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        users[1].id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let schema = schema();
+    let execute = |query: &str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let get = r#"
+        query($id: UUID!) {
+            userById(id: $id) {
+                notificationSettings {
+                    goLive
+                    newFollower
+                    mentions
+                    marketingEmails
+                }
+            }
+        }
+    "#;
+    let set = r#"
+        mutation($category: NotificationCategory!, $enabled: Boolean!) {
+            user {
+                setNotificationSetting(category: $category, enabled: $enabled) {
+                    goLive
+                    newFollower
+                    mentions
+                    marketingEmails
+                }
+            }
+        }
+    "#;
+
+    let res = execute(get, serde_json::json!({ "id": users[1].id })).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["userById"]["notificationSettings"],
+        serde_json::json!({
+            "goLive": true,
+            "newFollower": true,
+            "mentions": true,
+            "marketingEmails": false,
+        })
+    );
+
+    // Only the user can see their settings.
+    let res = execute(get, serde_json::json!({ "id": users[2].id })).await;
+    assert_eq!(res.errors.len(), 1);
+
+    for (category, enabled) in [("GO_LIVE", false), ("MARKETING_EMAILS", true)] {
+        let res = execute(
+            set,
+            serde_json::json!({ "category": category, "enabled": enabled }),
+        )
+        .await;
+        assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    }
+
+    let res = execute(get, serde_json::json!({ "id": users[1].id })).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["userById"]["notificationSettings"],
+        serde_json::json!({
+            "goLive": false,
+            "newFollower": true,
+            "mentions": true,
+            "marketingEmails": true,
+        })
+    );
+
+    // The follower who turned go-live emails off is not emailed anymore.
+    let recipients = notification_settings::go_live_recipients(&global.db, users[0].id)
+        .await
+        .unwrap();
+    assert_eq!(recipients.len(), 1);
+    assert_eq!(recipients[0].user_id, users[2].id);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_email_change() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut users = Vec::new();
+    for (username, email) in [("admin", "admin@admin.com"), ("other", "taken@admin.com")] {
+        users.push(
+            sqlx::query_as!(user::Model,
+                "INSERT INTO users(username, display_name, email, email_hash, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4, $5) RETURNING *",
+                username,
+                email,
+                global.email_hash(email),
+                user::hash_password("admin"),
+                user::generate_stream_key(),
+            )
+            .fetch_one(&*global.db)
+            .await
+            .unwrap(),
+        );
+    }
+    let user = &users[0];
+    let state = &*global;
+
+    let new_session = || async move {
+        sqlx::query_as!(
+            session::Model,
+            "INSERT INTO sessions(user_id, expires_at, elevated_until) VALUES ($1, $2, $2) RETURNING *",
+            user.id,
+            Utc::now() + chrono::Duration::seconds(120)
+        )
+        .fetch_one(&*state.db)
+        .await
+        .unwrap()
+    };
+
+    let execute =
+        |query: &'static str, variables: serde_json::Value, session: Option<&session::Model>| {
+            let global = global.clone();
+            let session = session.cloned();
+            async move {
+                let ctx = Arc::new(RequestContext::new(false));
+                if let Some(session) = session {
+                    ctx.set_session(Some((session, Default::default())));
+                }
+
+                schema()
+                    .execute(
+                        Request::from(query)
+                            .variables(Variables::from_json(variables))
+                            .provide_global(global)
+                            .provide_context(ctx),
+                    )
+                    .await
+            }
+        };
+
+    // The tokens are only stored hashed, so the test swaps in ones it knows.
+    let swap_tokens = |confirm: &'static str, revert: &'static str| async move {
+        sqlx::query!(
+            "UPDATE email_changes SET confirm_token_hash = $2, revert_token_hash = $3 WHERE user_id = $1 AND confirmed_at IS NULL AND reverted_at IS NULL AND cancelled_at IS NULL",
+            user.id,
+            login_link::hash_token(confirm),
+            login_link::hash_token(revert),
+        )
+        .execute(&*state.db)
+        .await
+        .unwrap();
+    };
+
+    let current_email = || async move {
+        let user = sqlx::query_as!(user::Model, "SELECT * FROM users WHERE id = $1", user.id)
+            .fetch_one(&*state.db)
+            .await
+            .unwrap();
+        (state.decrypt_pii(&user.email).unwrap(), user.email_hash)
+    };
+
+    let request = r#"
+        mutation Request($email: String!) {
+            user {
+                requestEmailChange(email: $email)
+            }
+        }
+    "#;
+    let pending = r#"
+        query {
+            user {
+                pendingEmail
+            }
+        }
+    "#;
+    let confirm = r#"
+        mutation Confirm($token: String!) {
+            auth {
+                confirmEmailChange(token: $token)
+            }
+        }
+    "#;
+    let revert = r#"
+        mutation Revert($token: String!) {
+            auth {
+                revertEmailChange(token: $token)
+            }
+        }
+    "#;
+
+    let session = new_session().await;
+
+    let res = execute(
+        request,
+        serde_json::json!({ "email": "admin@admin.com" }),
+        Some(&session),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: This is already your email"
+    );
+
+    let res = execute(
+        request,
+        serde_json::json!({ "email": " New@Admin.com" }),
+        Some(&session),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    // Nothing changes until the new address confirms.
+    assert_eq!(current_email().await.0, "admin@admin.com");
+    let res = execute(pending, serde_json::json!({}), Some(&session)).await;
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({ "user": { "pendingEmail": "new@admin.com" } })
+    );
+
+    swap_tokens("confirm", "revert").await;
+
+    let res = execute(confirm, serde_json::json!({ "token": "confirm" }), None).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        current_email().await,
+        (
+            "new@admin.com".to_string(),
+            Some(global.email_hash("new@admin.com"))
+        )
+    );
+
+    let res = execute(pending, serde_json::json!({}), Some(&session)).await;
+    assert_eq!(
+        res.data.into_json().unwrap(),
+        serde_json::json!({ "user": { "pendingEmail": null } })
+    );
+
+    let res = execute(confirm, serde_json::json!({ "token": "confirm" }), None).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Email change is invalid or expired"
+    );
+
+    // The old address can still undo it, which logs out everywhere.
+    let res = execute(revert, serde_json::json!({ "token": "revert" }), None).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        current_email().await,
+        (
+            "admin@admin.com".to_string(),
+            Some(global.email_hash("admin@admin.com"))
+        )
+    );
+
+    let revoked = sqlx::query_as!(
+        session::Model,
+        "SELECT * FROM sessions WHERE id = $1",
+        session.id
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert!(!revoked.is_valid());
+
+    let res = execute(revert, serde_json::json!({ "token": "revert" }), None).await;
+    assert_eq!(res.errors.len(), 1);
+
+    // An email of another account can't be confirmed.
+    let session = new_session().await;
+    let res = execute(
+        request,
+        serde_json::json!({ "email": "taken@admin.com" }),
+        Some(&session),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    swap_tokens("confirm-taken", "revert-taken").await;
+
+    let res = execute(
+        confirm,
+        serde_json::json!({ "token": "confirm-taken" }),
+        None,
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Email is already in use"
+    );
+    assert_eq!(current_email().await.0, "admin@admin.com");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_mute_channel() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut users = Vec::new();
+    for name in ["broadcaster", "viewer"] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, email_verified, password_hash, stream_key) VALUES ($1, $1, $2, TRUE, $3, $4) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+        users.push(user);
+    }
+    let (broadcaster, viewer) = (&users[0], &users[1]);
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        viewer.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    sqlx::query!(
+        "INSERT INTO channel_events (channel_id, user_id, kind, amount, message) VALUES ($1, $2, $3, $4, $5)",
+        broadcaster.id,
+        viewer.id,
+        i64::from(channel_event::Kind::Follow),
+        0,
+        "",
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let schema = schema();
+    let execute = |query: &str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+    let (db, channel_id) = (&*global.db, broadcaster.id);
+    let recipients = || async move {
+        notification_settings::go_live_recipients(db, channel_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.user_id)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(recipients().await, vec![viewer.id]);
+
+    let mute = r#"
+        mutation Mute($channelId: UUID!) {
+            user {
+                muteChannel(channelId: $channelId) {
+                    channelId
+                }
+            }
+        }
+    "#;
+
+    let res = execute(mute, serde_json::json!({ "channelId": viewer.id })).await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You can't mute your own channel"
+    );
+
+    // Muting twice is fine.
+    for _ in 0..2 {
+        let res = execute(mute, serde_json::json!({ "channelId": broadcaster.id })).await;
+        assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    }
+
+    let res = execute(
+        r#"
+            query Muted($username: String!) {
+                user {
+                    mutedChannels {
+                        channelId
+                        channel {
+                            username
+                        }
+                    }
+                }
+                userByUsername(username: $username) {
+                    muted
+                }
+            }
+        "#,
+        serde_json::json!({ "username": "broadcaster" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let data = res.data.into_json().unwrap();
+    assert_eq!(
+        data["user"]["mutedChannels"],
+        serde_json::json!([{ "channelId": broadcaster.id, "channel": { "username": "broadcaster" } }])
+    );
+    assert_eq!(data["userByUsername"]["muted"], true);
+
+    // The viewer still follows, but gets no go-live email.
+    let follows = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM channel_events WHERE channel_id = $1",
+        broadcaster.id
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(follows, Some(1));
+    assert_eq!(recipients().await, vec![]);
+
+    let unmute = r#"
+        mutation Unmute($channelId: UUID!) {
+            user {
+                unmuteChannel(channelId: $channelId)
+            }
+        }
+    "#;
+
+    let res = execute(unmute, serde_json::json!({ "channelId": broadcaster.id })).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(res.data.into_json().unwrap()["user"]["unmuteChannel"], true);
+
+    let res = execute(unmute, serde_json::json!({ "channelId": broadcaster.id })).await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["unmuteChannel"],
+        false
+    );
+
+    assert_eq!(recipients().await, vec![viewer.id]);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_follow_notifications() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut users = Vec::new();
+    for name in ["broadcaster", "viewer"] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, email_verified, password_hash, stream_key) VALUES ($1, $1, $2, TRUE, $3, $4) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+        users.push(user);
+    }
+    let (broadcaster, viewer) = (&users[0], &users[1]);
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        viewer.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    sqlx::query!(
+        "INSERT INTO channel_events (channel_id, user_id, kind, amount, message) VALUES ($1, $2, $3, $4, $5)",
+        broadcaster.id,
+        viewer.id,
+        i64::from(channel_event::Kind::Follow),
+        0,
+        "",
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let schema = schema();
+    let execute = |query: &str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+    let (db, channel_id) = (&*global.db, broadcaster.id);
+    let recipients = || async move {
+        notification_settings::go_live_recipients(db, channel_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.user_id)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(recipients().await, vec![viewer.id]);
+
+    let set = r#"
+        mutation Set($channelId: UUID!, $enabled: Boolean!) {
+            user {
+                setFollowNotifications(channelId: $channelId, enabled: $enabled)
+            }
+        }
+    "#;
+
+    let res = execute(
+        set,
+        serde_json::json!({ "channelId": viewer.id, "enabled": false }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You don't follow this channel"
+    );
+
+    let res = execute(
+        set,
+        serde_json::json!({ "channelId": broadcaster.id, "enabled": false }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["setFollowNotifications"],
+        false
+    );
+    assert_eq!(recipients().await, vec![]);
+
+    let res = execute(
+        r#"
+            query Channel($username: String!) {
+                userByUsername(username: $username) {
+                    liveNotifications
+                    muted
+                }
+            }
+        "#,
+        serde_json::json!({ "username": "broadcaster" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["userByUsername"],
+        serde_json::json!({ "liveNotifications": false, "muted": false })
+    );
+
+    let res = execute(
+        set,
+        serde_json::json!({ "channelId": broadcaster.id, "enabled": true }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["user"]["setFollowNotifications"],
+        true
+    );
+    assert_eq!(recipients().await, vec![viewer.id]);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_follow_lists() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut users = Vec::new();
+    for name in ["viewer", "zed", "amy", "bob"] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+        users.push(user);
+    }
+    let (viewer, zed, amy, bob) = (&users[0], &users[1], &users[2], &users[3]);
+
+    // The viewer follows zed first and bob last, bob also follows amy.
+    for (i, (channel, follower)) in [(zed, viewer), (amy, viewer), (bob, viewer), (amy, bob)]
+        .into_iter()
+        .enumerate()
+    {
+        sqlx::query!(
+            "INSERT INTO channel_events (channel_id, user_id, kind, created_at) VALUES ($1, $2, $3, $4)",
+            channel.id,
+            follower.id,
+            i64::from(channel_event::Kind::Follow),
+            Utc::now() - chrono::Duration::minutes(10 - i as i64),
+        )
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    }
+
+    sqlx::query!(
+        "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        zed.id,
+        "live",
+        "",
+        false,
+        false,
+        "",
+        uuid::Uuid::new_v4(),
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let schema = schema();
+    let list = |field: &str, username: &str, sort: &str, after: Option<String>| {
+        let query = format!(
+            r#"
+                query List($username: String!, $sort: FollowSort!, $after: Cursor) {{
+                    userByUsername(username: $username) {{
+                        {}(sort: $sort, after: $after, limit: 2) {{
+                            live
+                            cursor
+                            user {{
+                                username
+                            }}
+                        }}
+                    }}
+                }}
+            "#,
+            field
+        );
+        let variables = serde_json::json!({ "username": username, "sort": sort, "after": after });
+        let schema = &schema;
+        let global = global.clone();
+        let field = field.to_string();
+        async move {
+            let res = schema
+                .execute(
+                    Request::from(query)
+                        .variables(Variables::from_json(variables))
+                        .provide_global(global)
+                        .provide_context(Arc::new(RequestContext::new(false))),
+                )
+                .await;
+            assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+            let page = res.data.into_json().unwrap()["userByUsername"][&field]
+                .as_array()
+                .unwrap()
+                .clone();
+            let names = page
+                .iter()
+                .map(|f| {
+                    format!(
+                        "{}{}",
+                        f["user"]["username"].as_str().unwrap(),
+                        if f["live"] == true { " (live)" } else { "" }
+                    )
+                })
+                .collect::<Vec<_>>();
+            let cursor = page
+                .last()
+                .map(|f| f["cursor"].as_str().unwrap().to_string());
+            (names, cursor)
+        }
+    };
+
+    for (sort, first, second) in [
+        ("RECENT", vec!["bob", "amy"], vec!["zed (live)"]),
+        ("ALPHABETICAL", vec!["amy", "bob"], vec!["zed (live)"]),
+        ("LIVE_FIRST", vec!["zed (live)", "bob"], vec!["amy"]),
+    ] {
+        let (names, cursor) = list("following", "viewer", sort, None).await;
+        assert_eq!(names, first, "sort: {}", sort);
+
+        let (names, cursor) = list("following", "viewer", sort, cursor).await;
+        assert_eq!(names, second, "sort: {}", sort);
+
+        let (names, _) = list("following", "viewer", sort, cursor).await;
+        assert!(names.is_empty(), "sort: {}", sort);
+    }
+
+    let (names, _) = list("followers", "amy", "RECENT", None).await;
+    assert_eq!(names, vec!["bob", "viewer"]);
+
+    let (names, _) = list("followers", "amy", "ALPHABETICAL", None).await;
+    assert_eq!(names, vec!["bob", "viewer"]);
+
+    let (names, _) = list("followers", "zed", "LIVE_FIRST", None).await;
+    assert_eq!(names, vec!["viewer"]);
+}
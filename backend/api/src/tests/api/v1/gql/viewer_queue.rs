@@ -0,0 +1,289 @@
+use std::{sync::Arc, time::Duration};
+
+use async_graphql::{Request, Variables};
+use futures_util::StreamExt;
+use serial_test::serial;
+
+use crate::{
+    api::v1::gql::{ext::RequestExt, request_context::RequestContext, schema},
+    database::{global_role, session},
+    tests::{
+        api::v1::gql::{create_user, execute},
+        global::mock_global_state,
+    },
+};
+
+async fn next_position(
+    stream: &mut (impl futures_util::Stream<Item = async_graphql::Response> + Unpin),
+) -> serde_json::Value {
+    let res = tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .expect("failed to execute stream")
+        .unwrap();
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    res.data.into_json().unwrap()["viewerQueuePosition"].clone()
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_viewer_queue() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let (broadcaster, broadcaster_session) = create_user(&global, "broadcaster").await;
+    let (first, first_session) = create_user(&global, "first").await;
+    let (second, second_session) = create_user(&global, "second").await;
+    let (_, third_session) = create_user(&global, "third").await;
+
+    let schema = schema();
+    let context = |session: &session::Model| {
+        let ctx = Arc::new(RequestContext::new(false));
+        ctx.set_session(Some((session.clone(), Default::default())));
+        ctx
+    };
+    let channel = serde_json::json!({ "channelId": broadcaster.id });
+
+    let open = r#"
+        mutation Open($channelId: UUID!) {
+            viewerQueue {
+                open(channelId: $channelId, title: "Fall Guys", maxSize: 2) {
+                    open
+                    maxSize
+                    size
+                }
+            }
+        }
+    "#;
+    let join = r#"
+        mutation Join($channelId: UUID!) {
+            viewerQueue {
+                join(channelId: $channelId) {
+                    position
+                    size
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&first_session),
+        global_role::Permission::default(),
+        join,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors[0].message, "InvalidInput: This queue is closed");
+
+    // Only the broadcaster can open the queue.
+    let res = execute(
+        &global,
+        Some(&first_session),
+        global_role::Permission::default(),
+        open,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        open,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["viewerQueue"]["open"],
+        serde_json::json!({ "open": true, "maxSize": 2, "size": 0 })
+    );
+
+    let mut positions = schema.execute_stream(
+        Request::from(
+            r#"
+                subscription Position($channelId: UUID!) {
+                    viewerQueuePosition(channelId: $channelId) {
+                        position
+                        size
+                        called
+                    }
+                }
+            "#,
+        )
+        .variables(Variables::from_json(channel.clone()))
+        .provide_global(global.clone())
+        .provide_context(context(&first_session)),
+    );
+    assert_eq!(
+        next_position(&mut positions).await,
+        serde_json::json!({ "position": null, "size": 0, "called": false })
+    );
+
+    let res = execute(
+        &global,
+        Some(&first_session),
+        global_role::Permission::default(),
+        join,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["viewerQueue"]["join"],
+        serde_json::json!({ "position": 1, "size": 1 })
+    );
+    assert_eq!(
+        next_position(&mut positions).await,
+        serde_json::json!({ "position": 1, "size": 1, "called": false })
+    );
+
+    let res = execute(
+        &global,
+        Some(&first_session),
+        global_role::Permission::default(),
+        join,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: You are already in this queue"
+    );
+
+    // Viewers can join from chat too.
+    assert!(global
+        .viewer_queue_by_command(broadcaster.id, second.id, "!join")
+        .await
+        .unwrap());
+    assert_eq!(
+        next_position(&mut positions).await,
+        serde_json::json!({ "position": 1, "size": 2, "called": false })
+    );
+
+    let res = execute(
+        &global,
+        Some(&third_session),
+        global_role::Permission::default(),
+        join,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors[0].message, "InvalidInput: This queue is full");
+
+    let entries = r#"
+        query Entries($channelId: UUID!) {
+            viewerQueue {
+                entries(channelId: $channelId) {
+                    userId
+                    position
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &global,
+        Some(&second_session),
+        global_role::Permission::default(),
+        entries,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        entries,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["viewerQueue"]["entries"],
+        serde_json::json!([
+            { "userId": first.id, "position": 1 },
+            { "userId": second.id, "position": 2 },
+        ])
+    );
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        r#"
+            mutation Shuffle($channelId: UUID!) {
+                viewerQueue {
+                    shuffle(channelId: $channelId) {
+                        userId
+                    }
+                }
+            }
+        "#,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let mut shuffled = res.data.into_json().unwrap()["viewerQueue"]["shuffle"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["userId"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    let first_in_line = shuffled[0].clone();
+    shuffled.sort();
+    let mut expected = vec![first.id.to_string(), second.id.to_string()];
+    expected.sort();
+    assert_eq!(shuffled, expected);
+
+    // The shuffle only sends a position if it moved the viewer.
+    if first_in_line != first.id.to_string() {
+        assert_eq!(
+            next_position(&mut positions).await,
+            serde_json::json!({ "position": 2, "size": 2, "called": false })
+        );
+    }
+
+    let res = execute(
+        &global,
+        Some(&broadcaster_session),
+        global_role::Permission::default(),
+        r#"
+            mutation Pop($channelId: UUID!) {
+                viewerQueue {
+                    pop(channelId: $channelId, count: 2) {
+                        userId
+                    }
+                }
+            }
+        "#,
+        channel.clone(),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["viewerQueue"]["pop"][0]["userId"],
+        first_in_line
+    );
+
+    assert_eq!(
+        next_position(&mut positions).await,
+        serde_json::json!({ "position": null, "size": 0, "called": true })
+    );
+
+    assert!(!global
+        .viewer_queue_by_command(broadcaster.id, second.id, "!leave")
+        .await
+        .unwrap());
+}
@@ -0,0 +1,693 @@
+use std::sync::Arc;
+
+use async_graphql::{Request, Variables};
+use chrono::Utc;
+use prost::Message;
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    api::v1::{
+        gql::{ext::RequestExt, request_context::RequestContext, schema},
+        jwt::PlaybackToken,
+    },
+    database::{channel_event, session, stream, stream_chapter, user},
+    integrations::vod_download::record_result,
+    pb::scuffle::{
+        events::VodDownloadJobResult,
+        types::{stream_state, StreamState},
+    },
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_vod_access() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut contexts = Vec::new();
+    let mut users = Vec::new();
+    for name in ["broadcaster", "viewer"] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let session = sqlx::query_as!(
+            session::Model,
+            "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+            user.id,
+            Utc::now() + chrono::Duration::seconds(120)
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let ctx = Arc::new(RequestContext::new(false));
+        ctx.set_session(Some((session, Default::default())));
+        contexts.push(ctx);
+        users.push(user);
+    }
+    let (broadcaster, viewer) = (&users[0], &users[1]);
+
+    let vod = sqlx::query_as!(stream::Model,
+        "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id, ended_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+        broadcaster.id,
+        "test",
+        "test",
+        true,
+        false,
+        "some address",
+        Uuid::new_v4(),
+        Utc::now() - chrono::Duration::minutes(1),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let schema = schema();
+    let execute = |ctx: &Arc<RequestContext>, query: &str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let set_access = r#"
+        mutation SetAccess($streamId: UUID!, $access: VodAccess!, $publicAt: DateRFC3339) {
+            vod {
+                setAccess(streamId: $streamId, access: $access, publicAt: $publicAt) {
+                    access
+                    locked
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &contexts[1],
+        set_access,
+        serde_json::json!({ "streamId": vod.id, "access": "SUBSCRIBERS" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+
+    let res = execute(
+        &contexts[0],
+        set_access,
+        serde_json::json!({ "streamId": vod.id, "access": "EARLY_ACCESS", "publicAt": Utc::now().to_rfc3339() }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Early access needs a time in the future the VOD becomes public"
+    );
+
+    let res = execute(
+        &contexts[0],
+        set_access,
+        serde_json::json!({ "streamId": vod.id, "access": "SUBSCRIBERS" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["vod"]["setAccess"],
+        serde_json::json!({ "access": "SUBSCRIBERS", "locked": false })
+    );
+
+    let vods = r#"
+        query Vods($channelId: UUID!) {
+            vod {
+                vods(channelId: $channelId) {
+                    id
+                    locked
+                }
+            }
+        }
+    "#;
+
+    let token = r#"
+        mutation PlaybackToken($streamId: UUID!) {
+            vod {
+                playbackToken(streamId: $streamId)
+            }
+        }
+    "#;
+
+    let res = execute(
+        &contexts[1],
+        vods,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["vod"]["vods"],
+        serde_json::json!([{ "id": vod.id, "locked": true }])
+    );
+
+    let res = execute(
+        &contexts[1],
+        token,
+        serde_json::json!({ "streamId": vod.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: This VOD is only available to subscribers"
+    );
+
+    // Subscribing unlocks the VOD.
+    sqlx::query!(
+        "INSERT INTO channel_events (channel_id, user_id, kind, amount, message) VALUES ($1, $2, $3, $4, $5)",
+        broadcaster.id,
+        viewer.id,
+        i64::from(channel_event::Kind::Subscription),
+        1,
+        "",
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let res = execute(
+        &contexts[1],
+        vods,
+        serde_json::json!({ "channelId": broadcaster.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["vod"]["vods"][0]["locked"],
+        false
+    );
+
+    let res = execute(
+        &contexts[1],
+        token,
+        serde_json::json!({ "streamId": vod.id }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    let playback =
+        PlaybackToken::verify(&global, json["vod"]["playbackToken"].as_str().unwrap()).unwrap();
+    assert_eq!(playback.stream_id, vod.id);
+    assert_eq!(playback.user_id, Some(viewer.id));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_vod_download() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut contexts = Vec::new();
+    let mut users = Vec::new();
+    for name in ["broadcaster", "viewer"] {
+        let user = sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+            name,
+            format!("{}@test.com", name),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let session = sqlx::query_as!(
+            session::Model,
+            "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+            user.id,
+            Utc::now() + chrono::Duration::seconds(120)
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        let ctx = Arc::new(RequestContext::new(false));
+        ctx.set_session(Some((session, Default::default())));
+        contexts.push(ctx);
+        users.push(user);
+    }
+
+    let state = StreamState {
+        variants: vec![stream_state::Variant {
+            name: "source".to_string(),
+            group: "aac".to_string(),
+            transcode_ids: vec!["video".to_string(), "audio".to_string()],
+        }],
+        transcodes: vec![
+            stream_state::Transcode {
+                id: "video".to_string(),
+                codec: "avc1.640028".to_string(),
+                ..Default::default()
+            },
+            stream_state::Transcode {
+                id: "audio".to_string(),
+                codec: "mp4a.40.2".to_string(),
+                ..Default::default()
+            },
+        ],
+        groups: vec![],
+    };
+
+    let vod = sqlx::query_as!(stream::Model,
+        "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id, ended_at, state) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *",
+        users[0].id,
+        "test",
+        "test",
+        true,
+        true,
+        "some address",
+        Uuid::new_v4(),
+        Utc::now() - chrono::Duration::minutes(1),
+        state.encode_to_vec(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let schema = schema();
+    let execute = |ctx: &Arc<RequestContext>, query: &str, variables: serde_json::Value| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(variables))
+                .provide_global(global.clone())
+                .provide_context(ctx.clone()),
+        )
+    };
+
+    let request = r#"
+        mutation RequestDownload($streamId: UUID!, $quality: String!) {
+            vod {
+                requestDownload(streamId: $streamId, quality: $quality) {
+                    id
+                    status
+                    transcoded
+                }
+            }
+        }
+    "#;
+
+    // Only the broadcaster can download their VODs.
+    let res = execute(
+        &contexts[1],
+        request,
+        serde_json::json!({ "streamId": vod.id, "quality": "source" }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "Unauthorized: You are not allowed to access this channel"
+    );
+
+    let res = execute(
+        &contexts[0],
+        request,
+        serde_json::json!({ "streamId": vod.id, "quality": "720p" }),
+    )
+    .await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: This VOD has no such quality"
+    );
+
+    let res = execute(
+        &contexts[0],
+        request,
+        serde_json::json!({ "streamId": vod.id, "quality": "source" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    let download = &json["vod"]["requestDownload"];
+    assert_eq!(download["status"], "QUEUED");
+    assert_eq!(download["transcoded"], false);
+
+    // Asking again while the first one runs returns it.
+    let res = execute(
+        &contexts[0],
+        request,
+        serde_json::json!({ "streamId": vod.id, "quality": "source" }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["vod"]["requestDownload"]["id"],
+        download["id"]
+    );
+
+    let poll = r#"
+        query Download($id: UUID!) {
+            vod {
+                download(id: $id) {
+                    status
+                    progress
+                    downloadUrl
+                }
+            }
+        }
+    "#;
+
+    let res = execute(
+        &contexts[0],
+        poll,
+        serde_json::json!({ "id": download["id"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["vod"]["download"],
+        serde_json::json!({ "status": "QUEUED", "progress": 0, "downloadUrl": null })
+    );
+
+    record_result(
+        &global,
+        &VodDownloadJobResult {
+            id: download["id"].as_str().unwrap().to_string(),
+            progress: 100,
+            done: true,
+            size_bytes: 1024,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let res = execute(
+        &contexts[0],
+        poll,
+        serde_json::json!({ "id": download["id"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    assert_eq!(json["vod"]["download"]["status"], "COMPLETED");
+    assert!(json["vod"]["download"]["downloadUrl"]
+        .as_str()
+        .unwrap()
+        .contains(&format!("downloads/{}/{}/", users[0].id, vod.id)));
+
+    let res = execute(
+        &contexts[1],
+        poll,
+        serde_json::json!({ "id": download["id"] }),
+    )
+    .await;
+    assert_eq!(res.errors.len(), 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_vod_chapters() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let broadcaster = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key, stream_title, stream_category) VALUES ($1, $1, $2, $3, $4, $5, $6) RETURNING *",
+        "broadcaster",
+        "broadcaster@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+        "Hello",
+        "Chatting",
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let stream = sqlx::query_as!(stream::Model,
+        "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id, created_at, ended_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *",
+        broadcaster.id,
+        "Hello",
+        "",
+        true,
+        false,
+        "some address",
+        Uuid::new_v4(),
+        Utc::now() - chrono::Duration::minutes(30),
+        Utc::now() + chrono::Duration::minutes(5),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    stream_chapter::record(&*global.db, stream.id, 0, "Hello", "Chatting")
+        .await
+        .unwrap();
+
+    // Only the language changed, that doesn't start a chapter.
+    global
+        .update_stream_info(broadcaster.id, None, None, Some("en"))
+        .await
+        .unwrap();
+    global
+        .update_stream_info(broadcaster.id, Some("Speedrun"), Some("Minecraft"), None)
+        .await
+        .unwrap();
+
+    let chapters = stream_chapter::for_stream(&*global.db, stream.id)
+        .await
+        .unwrap();
+    assert_eq!(chapters.len(), 2);
+    assert_eq!(chapters[1].title, "Speedrun");
+    assert_eq!(chapters[1].category, "Minecraft");
+
+    sqlx::query!(
+        "UPDATE streams SET ended_at = NOW() WHERE id = $1",
+        stream.id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let res = schema()
+        .execute(
+            Request::from(
+                r#"
+                query Vods($channelId: UUID!) {
+                    vod {
+                        vods(channelId: $channelId) {
+                            chapters {
+                                title
+                                category
+                                startSeconds
+                                endSeconds
+                            }
+                            chaptersTrack
+                        }
+                    }
+                }
+            "#,
+            )
+            .variables(Variables::from_json(
+                serde_json::json!({ "channelId": broadcaster.id }),
+            ))
+            .provide_global(global.clone())
+            .provide_context(Arc::new(RequestContext::new(false))),
+        )
+        .await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+
+    let json = res.data.into_json().unwrap();
+    let vod = &json["vod"]["vods"][0];
+    let chapters = vod["chapters"].as_array().unwrap();
+    assert_eq!(chapters.len(), 2);
+    assert_eq!(chapters[0]["title"], "Hello");
+    assert_eq!(chapters[0]["category"], "Chatting");
+    assert_eq!(chapters[0]["startSeconds"], 0);
+    assert_eq!(chapters[0]["endSeconds"], chapters[1]["startSeconds"]);
+    assert_eq!(chapters[1]["title"], "Speedrun");
+    assert_eq!(chapters[1]["category"], "Minecraft");
+    assert!(vod["chaptersTrack"]
+        .as_str()
+        .unwrap()
+        .contains("Speedrun (Minecraft)"));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_vod_transcript_search() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM sessions")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let broadcaster = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "broadcaster",
+        "broadcaster@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions(user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        broadcaster.id,
+        Utc::now() + chrono::Duration::seconds(120)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let mut vods = Vec::new();
+    for (minutes, access, cues) in [
+        (
+            20,
+            stream::VodAccess::Public,
+            vec!["Hello everyone", "Today we speedrun Minecraft", "Let's go"],
+        ),
+        (
+            10,
+            stream::VodAccess::Subscribers,
+            vec!["Minecraft again for the subscribers"],
+        ),
+    ] {
+        let vod = sqlx::query_as!(stream::Model,
+            "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id, created_at, ended_at, vod_access) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *",
+            broadcaster.id,
+            "test",
+            "",
+            true,
+            false,
+            "some address",
+            Uuid::new_v4(),
+            Utc::now() - chrono::Duration::minutes(minutes),
+            Utc::now() - chrono::Duration::minutes(minutes - 5),
+            i64::from(access),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+
+        for (i, text) in cues.into_iter().enumerate() {
+            sqlx::query!(
+                "INSERT INTO vod_caption_cues (stream_id, start_ms, end_ms, text) VALUES ($1, $2, $3, $4)",
+                vod.id,
+                i as i64 * 2000,
+                i as i64 * 2000 + 1500,
+                text,
+            )
+            .execute(&*global.db)
+            .await
+            .unwrap();
+        }
+
+        vods.push(vod);
+    }
+
+    let query = r#"
+        query Search($channelId: UUID!, $query: String!) {
+            vod {
+                searchTranscripts(channelId: $channelId, query: $query) {
+                    vod {
+                        id
+                    }
+                    matches
+                    snippets {
+                        startMs
+                        endMs
+                        line
+                        text
+                    }
+                }
+            }
+        }
+    "#;
+
+    let schema = schema();
+    let search = |ctx: Arc<RequestContext>, text: &str| {
+        schema.execute(
+            Request::from(query)
+                .variables(Variables::from_json(
+                    serde_json::json!({ "channelId": broadcaster.id, "query": text }),
+                ))
+                .provide_global(global.clone())
+                .provide_context(ctx),
+        )
+    };
+
+    let res = search(Arc::new(RequestContext::new(false)), "m").await;
+    assert_eq!(
+        res.errors[0].message,
+        "InvalidInput: Search text must be between 2 and 100 characters"
+    );
+
+    // The subscriber only VOD is left out for anonymous viewers.
+    let res = search(Arc::new(RequestContext::new(false)), "minecraft").await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    assert_eq!(
+        res.data.into_json().unwrap()["vod"]["searchTranscripts"],
+        serde_json::json!([{
+            "vod": { "id": vods[0].id.to_string() },
+            "matches": 1,
+            "snippets": [{
+                "startMs": 2000,
+                "endMs": 3500,
+                "line": "Today we speedrun Minecraft",
+                "text": "Hello everyone Today we speedrun Minecraft Let's go",
+            }],
+        }])
+    );
+
+    let ctx = Arc::new(RequestContext::new(false));
+    ctx.set_session(Some((session, Default::default())));
+
+    let res = search(ctx, "minecraft").await;
+    assert_eq!(res.errors.len(), 0, "{:?}", res.errors);
+    let json = res.data.into_json().unwrap();
+    let results = json["vod"]["searchTranscripts"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["vod"]["id"], vods[1].id.to_string());
+    assert_eq!(
+        results[0]["snippets"][0]["text"],
+        "Minecraft again for the subscribers"
+    );
+}
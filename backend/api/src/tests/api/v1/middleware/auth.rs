@@ -8,7 +8,7 @@ use serial_test::serial;
 
 use crate::{
     api::{self, v1::jwt::JwtState},
-    config::{ApiConfig, AppConfig},
+    config::{ApiConfig, AppConfig, CookieAuthConfig},
     tests::global::mock_global_state,
 };
 
@@ -21,6 +21,7 @@ async fn test_serial_auth_middleware() {
         api: ApiConfig {
             bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
             tls: None,
+            ..Default::default()
         },
         ..Default::default()
     })
@@ -126,6 +127,7 @@ async fn test_serial_auth_middleware_failed() {
         api: ApiConfig {
             bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
             tls: None,
+            ..Default::default()
         },
         ..Default::default()
     })
@@ -201,3 +203,165 @@ async fn test_serial_auth_middleware_failed() {
         .unwrap()
         .unwrap();
 }
+
+#[serial]
+#[tokio::test]
+async fn test_serial_cookie_session() {
+    let port = portpicker::pick_unused_port().expect("failed to pick port");
+    let (global, handler) = mock_global_state(AppConfig {
+        api: ApiConfig {
+            bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
+            tls: None,
+            ..Default::default()
+        },
+        cookie_auth: CookieAuthConfig {
+            enabled: true,
+            allowed_origins: vec!["http://localhost:4000".to_string()],
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .expect("failed to clear users");
+    let id = sqlx::query!(
+        "INSERT INTO users (username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING id",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .map(|row| row.id)
+    .fetch_one(&*global.db)
+    .await
+    .expect("failed to insert user");
+
+    let session = sqlx::query_as!(
+        session::Model,
+        "INSERT INTO sessions (user_id, expires_at) VALUES ($1, $2) RETURNING *",
+        id,
+        Utc::now() + Duration::seconds(30)
+    )
+    .fetch_one(&*global.db)
+    .await
+    .expect("failed to insert session");
+
+    let token = JwtState::from(session)
+        .serialize(&global)
+        .expect("failed to create token");
+
+    let handle = tokio::spawn(api::run(global.clone()));
+
+    // We need to wait for the server to start
+    tokio::time::sleep(time::Duration::from_millis(300)).await;
+
+    let client = reqwest::Client::new();
+    let url = format!("http://localhost:{}/v1/gql", port);
+    let set_cookies = |resp: &reqwest::Response| {
+        resp.headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .map(|v| v.to_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    let cookie_value = |cookies: &[String], name: &str| {
+        cookies
+            .iter()
+            .find_map(|c| c.strip_prefix(&format!("{}=", name)))
+            .and_then(|c| c.split(';').next())
+            .unwrap()
+            .to_string()
+    };
+
+    let resp = client
+        .post(&url)
+        .json(&json!({
+            "query": "mutation Login($token: String!) { auth { loginWithToken(sessionToken: $token) { id } } }",
+            "variables": { "token": token },
+        }))
+        .send()
+        .await
+        .expect("failed to log in");
+    assert_eq!(resp.status(), http::StatusCode::OK);
+
+    // The browser gets the session as a cookie scripts can't read, and a CSRF token they can.
+    let cookies = set_cookies(&resp);
+    assert_eq!(cookies.len(), 2, "{:?}", cookies);
+    let session_cookie = cookies
+        .iter()
+        .find(|c| c.starts_with("scuffle_session="))
+        .unwrap();
+    assert!(session_cookie.contains("; HttpOnly"));
+    assert!(session_cookie.contains("; Secure"));
+    assert!(session_cookie.contains("; SameSite=Strict"));
+    let csrf_cookie = cookies
+        .iter()
+        .find(|c| c.starts_with("scuffle_csrf="))
+        .unwrap();
+    assert!(!csrf_cookie.contains("HttpOnly"));
+    assert_eq!(cookie_value(&cookies, "scuffle_session"), token);
+    let csrf_token = cookie_value(&cookies, "scuffle_csrf");
+    let cookie_header = format!("scuffle_session={}; scuffle_csrf={}", token, csrf_token);
+
+    let resp = client
+        .post(&url)
+        .header(header::COOKIE, &cookie_header)
+        .header(header::ORIGIN, "http://localhost:4000")
+        .header("X-CSRF-Token", &csrf_token)
+        .json(&json!({ "query": "{ user { sessions { current } } }" }))
+        .send()
+        .await
+        .expect("failed to fetch sessions");
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    let body: Value = resp.json().await.expect("failed to read body");
+    assert_eq!(
+        body["data"],
+        json!({ "user": { "sessions": [{ "current": true }] } })
+    );
+
+    // Without the CSRF token the cookie is not accepted.
+    let resp = client
+        .post(&url)
+        .header(header::COOKIE, &cookie_header)
+        .header(header::ORIGIN, "http://localhost:4000")
+        .json(&json!({ "query": "{ user { sessions { current } } }" }))
+        .send()
+        .await
+        .expect("failed to fetch sessions");
+    assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+
+    let resp = client
+        .post(&url)
+        .header(header::COOKIE, &cookie_header)
+        .header(header::ORIGIN, "http://localhost:4000")
+        .header("X-CSRF-Token", &csrf_token)
+        .json(&json!({ "query": "mutation { auth { logout } }" }))
+        .send()
+        .await
+        .expect("failed to log out");
+    assert_eq!(resp.status(), http::StatusCode::OK);
+
+    let cookies = set_cookies(&resp);
+    assert_eq!(cookies.len(), 2, "{:?}", cookies);
+    assert!(cookies.iter().all(|c| c.contains("Max-Age=0")));
+
+    // The client uses Keep-Alive, so we need to drop it to release the global context
+    drop(global);
+    drop(client);
+
+    handler
+        .cancel()
+        .timeout(time::Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+
+    handle
+        .timeout(time::Duration::from_secs(1))
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
@@ -0,0 +1,193 @@
+use hyper::{HeaderMap, HeaderValue, Method};
+
+use crate::{
+    api::middleware::csrf::{cookie, login_cookies, logout_cookies, request_origin, verify},
+    config::CookieAuthConfig,
+};
+
+fn headers(values: &[(&'static str, &'static str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in values {
+        headers.append(*name, HeaderValue::from_static(value));
+    }
+    headers
+}
+
+#[test]
+fn test_cookie() {
+    let headers = headers(&[
+        ("cookie", "theme=dark; scuffle_session=abc.def"),
+        ("cookie", "scuffle_csrf=123"),
+    ]);
+
+    assert_eq!(cookie(&headers, "scuffle_session"), Some("abc.def"));
+    assert_eq!(cookie(&headers, "scuffle_csrf"), Some("123"));
+    assert_eq!(cookie(&headers, "theme"), Some("dark"));
+    assert_eq!(cookie(&headers, "session"), None);
+}
+
+#[test]
+fn test_login_cookies() {
+    let config = CookieAuthConfig {
+        domain: "scuffle.tv".to_string(),
+        ..Default::default()
+    };
+
+    let [session, csrf] = login_cookies(&config, "abc.def", 60);
+    assert_eq!(
+        session,
+        "scuffle_session=abc.def; Path=/; Max-Age=60; Secure; SameSite=Strict; HttpOnly; Domain=scuffle.tv"
+    );
+    assert!(csrf.starts_with("scuffle_csrf="));
+    assert!(csrf.ends_with("; Path=/; Max-Age=60; Secure; SameSite=Strict; Domain=scuffle.tv"));
+
+    // Every login gets a new CSRF token.
+    let [_, other] = login_cookies(&config, "abc.def", 60);
+    assert_ne!(csrf, other);
+
+    assert_eq!(
+        logout_cookies(&config),
+        [
+            "scuffle_session=; Path=/; Max-Age=0; Secure; SameSite=Strict; HttpOnly; Domain=scuffle.tv".to_string(),
+            "scuffle_csrf=; Path=/; Max-Age=0; Secure; SameSite=Strict; Domain=scuffle.tv".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_request_origin() {
+    let tests = vec![
+        (
+            headers(&[("origin", "https://scuffle.tv")]),
+            Some("https://scuffle.tv"),
+        ),
+        (
+            headers(&[("referer", "https://scuffle.tv:8443/troy?tab=videos")]),
+            Some("https://scuffle.tv:8443"),
+        ),
+        (
+            headers(&[
+                ("origin", "https://a.example"),
+                ("referer", "https://scuffle.tv/"),
+            ]),
+            Some("https://a.example"),
+        ),
+        (headers(&[("referer", "/relative")]), None),
+        (headers(&[]), None),
+    ];
+
+    for (headers, expected) in tests {
+        assert_eq!(
+            request_origin(&headers).as_deref(),
+            expected,
+            "{:?}",
+            headers
+        );
+    }
+}
+
+#[test]
+fn test_verify() {
+    let config = CookieAuthConfig {
+        enabled: true,
+        allowed_origins: vec!["https://scuffle.tv/".to_string()],
+        ..Default::default()
+    };
+
+    let tests = vec![
+        (
+            Method::POST,
+            headers(&[
+                ("origin", "https://scuffle.tv"),
+                ("cookie", "scuffle_csrf=token"),
+                ("x-csrf-token", "token"),
+            ]),
+            Ok(()),
+        ),
+        (
+            Method::POST,
+            headers(&[
+                ("referer", "https://scuffle.tv/settings"),
+                ("cookie", "scuffle_csrf=token"),
+                ("x-csrf-token", "token"),
+            ]),
+            Ok(()),
+        ),
+        (
+            Method::POST,
+            headers(&[
+                ("origin", "https://evil.example"),
+                ("cookie", "scuffle_csrf=token"),
+                ("x-csrf-token", "token"),
+            ]),
+            Err("origin not allowed"),
+        ),
+        (
+            Method::POST,
+            headers(&[("cookie", "scuffle_csrf=token"), ("x-csrf-token", "token")]),
+            Err("missing origin"),
+        ),
+        (
+            Method::POST,
+            headers(&[("origin", "https://scuffle.tv"), ("x-csrf-token", "token")]),
+            Err("missing csrf cookie"),
+        ),
+        (
+            Method::POST,
+            headers(&[
+                ("origin", "https://scuffle.tv"),
+                ("cookie", "scuffle_csrf="),
+                ("x-csrf-token", ""),
+            ]),
+            Err("missing csrf cookie"),
+        ),
+        (
+            Method::POST,
+            headers(&[
+                ("origin", "https://scuffle.tv"),
+                ("cookie", "scuffle_csrf=token"),
+            ]),
+            Err("missing csrf token"),
+        ),
+        (
+            Method::POST,
+            headers(&[
+                ("origin", "https://scuffle.tv"),
+                ("cookie", "scuffle_csrf=token"),
+                ("x-csrf-token", "other"),
+            ]),
+            Err("invalid csrf token"),
+        ),
+        (Method::GET, headers(&[]), Ok(())),
+        (
+            Method::GET,
+            headers(&[("origin", "https://scuffle.tv")]),
+            Ok(()),
+        ),
+        (
+            Method::GET,
+            headers(&[("origin", "https://evil.example")]),
+            Err("origin not allowed"),
+        ),
+        (
+            Method::GET,
+            headers(&[("upgrade", "websocket")]),
+            Err("missing origin"),
+        ),
+        (
+            Method::GET,
+            headers(&[("upgrade", "websocket"), ("origin", "https://scuffle.tv")]),
+            Ok(()),
+        ),
+    ];
+
+    for (method, headers, expected) in tests {
+        assert_eq!(
+            verify(&config, &method, &headers),
+            expected,
+            "{} {:?}",
+            method,
+            headers
+        );
+    }
+}
@@ -1,2 +1,4 @@
+mod csp;
 mod gql;
 mod middleware;
+mod payments;
@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use common::prelude::FutureTimeout;
+use hyper::StatusCode;
+use serial_test::serial;
+
+use crate::{
+    api::{
+        run,
+        webhook::{self, X_WEBHOOK_SIGNATURE, X_WEBHOOK_TIMESTAMP},
+    },
+    config::{ApiConfig, AppConfig, PaymentConfig},
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_webhook_replay() {
+    let port = portpicker::pick_unused_port().expect("failed to pick port");
+    let (global, handler) = mock_global_state(AppConfig {
+        api: ApiConfig {
+            bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
+            tls: None,
+            ..Default::default()
+        },
+        payment: PaymentConfig {
+            webhook_secret: "secret".to_string(),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    sqlx::query!("DELETE FROM webhook_events")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let handle = tokio::spawn(run(global.clone()));
+
+    // We need to wait for the server to start
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // No checkout or donation has this reference, the event is still recorded as processed.
+    let body = serde_json::json!({
+        "id": "evt_1",
+        "reference": uuid::Uuid::new_v4(),
+        "status": "completed",
+    })
+    .to_string();
+
+    let client = reqwest::Client::new();
+    let send = |timestamp: i64, secret: &str| {
+        client
+            .post(format!("http://localhost:{}/v1/payments/webhook", port))
+            .header(X_WEBHOOK_TIMESTAMP, timestamp)
+            .header(
+                X_WEBHOOK_SIGNATURE,
+                webhook::sign(secret, timestamp, body.as_bytes()),
+            )
+            .body(body.clone())
+            .send()
+    };
+
+    let now = Utc::now().timestamp();
+
+    let resp = send(now, "secret").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json, serde_json::json!({ "success": true }));
+
+    let resp = send(now, "secret").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "success": true, "duplicate": true })
+    );
+
+    let resp = send(now - 3600, "secret").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let resp = send(now, "other").await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let events = sqlx::query!(
+        r#"SELECT COUNT(*) AS "count!" FROM webhook_events WHERE source = 'payments'"#
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(events.count, 1);
+
+    // The client uses Keep-Alive, so we need to drop it to release the global context
+    drop(client);
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+    handle
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel api")
+        .expect("api failed")
+        .expect("api failed");
+}
@@ -0,0 +1,55 @@
+use chrono::{TimeZone, Utc};
+use hyper::{HeaderMap, HeaderValue};
+
+use crate::api::webhook::{self, X_WEBHOOK_SIGNATURE, X_WEBHOOK_TIMESTAMP};
+
+#[test]
+fn test_verify() {
+    let now = Utc.with_ymd_and_hms(2023, 8, 18, 12, 0, 0).unwrap();
+    let body = br#"{"id":"evt_1"}"#;
+
+    let headers = |timestamp: Option<i64>, signature: Option<String>| {
+        let mut headers = HeaderMap::new();
+        if let Some(timestamp) = timestamp {
+            headers.insert(X_WEBHOOK_TIMESTAMP, HeaderValue::from(timestamp));
+        }
+        if let Some(signature) = signature {
+            headers.insert(X_WEBHOOK_SIGNATURE, signature.parse().unwrap());
+        }
+        headers
+    };
+
+    let at = |seconds: i64| now.timestamp() + seconds;
+    let signed = |timestamp: i64| Some(webhook::sign("secret", timestamp, body));
+
+    let tests = [
+        (headers(Some(at(0)), signed(at(0))), Ok(())),
+        (headers(Some(at(-299)), signed(at(-299))), Ok(())),
+        // Clocks drift both ways.
+        (headers(Some(at(60)), signed(at(60))), Ok(())),
+        (
+            headers(Some(at(-301)), signed(at(-301))),
+            Err("timestamp is too old"),
+        ),
+        // The timestamp is part of the signature, so it can't be refreshed.
+        (
+            headers(Some(at(0)), signed(at(-301))),
+            Err("invalid signature"),
+        ),
+        (
+            headers(Some(at(0)), Some(webhook::sign("other", at(0), body))),
+            Err("invalid signature"),
+        ),
+        (headers(None, signed(at(0))), Err("missing timestamp")),
+        (headers(Some(at(0)), None), Err("missing signature")),
+    ];
+
+    for (i, (headers, result)) in tests.into_iter().enumerate() {
+        assert_eq!(
+            webhook::verify("secret", 300, &headers, body, now),
+            result,
+            "test {}",
+            i
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{Duration, TimeZone, Utc};
+
+use crate::database::channel_event::{follow_spikes, FollowSpike};
+
+#[test]
+fn test_follow_spikes() {
+    let since = Utc.timestamp_opt(1_690_000_000, 0).unwrap();
+    let minute = Duration::minutes(1);
+
+    let tests = vec![
+        // Quiet channels are only flagged once the minimum is reached.
+        (vec![0, 0, 5, 0, 0], vec![]),
+        (
+            vec![0, 0, 25, 0, 0],
+            vec![FollowSpike {
+                starts_at: since + minute * 2,
+                ends_at: since + minute * 3,
+                follows: 25,
+                baseline: 0.0,
+            }],
+        ),
+        // Adjacent minutes are one spike.
+        (
+            vec![0, 30, 40, 0, 20, 0, 0, 0],
+            vec![
+                FollowSpike {
+                    starts_at: since + minute,
+                    ends_at: since + minute * 3,
+                    follows: 70,
+                    baseline: 0.0,
+                },
+                FollowSpike {
+                    starts_at: since + minute * 4,
+                    ends_at: since + minute * 5,
+                    follows: 20,
+                    baseline: 0.0,
+                },
+            ],
+        ),
+        // Busy channels need ten times their usual follows.
+        (vec![10, 12, 90, 11, 10], vec![]),
+        (
+            vec![10, 12, 150, 11, 10],
+            vec![FollowSpike {
+                starts_at: since + minute * 2,
+                ends_at: since + minute * 3,
+                follows: 150,
+                baseline: 11.0,
+            }],
+        ),
+        (vec![], vec![]),
+    ];
+
+    for (counts, expected) in tests {
+        assert_eq!(
+            follow_spikes(since, minute, &counts, 10.0, 20),
+            expected,
+            "{:?}",
+            counts
+        );
+    }
+}
@@ -0,0 +1,24 @@
+use crate::database::channel_import::{Model, Status};
+
+#[test]
+fn test_progress() {
+    let tests = vec![
+        (Status::Queued, 0, 0, 0, 0.0),
+        (Status::Running, 10, 0, 0, 0.0),
+        (Status::Running, 10, 4, 1, 0.5),
+        (Status::Failed, 10, 2, 0, 0.2),
+        (Status::Completed, 0, 0, 0, 1.0),
+    ];
+
+    for (status, total_items, imported_items, skipped_items, expected) in tests {
+        let import = Model {
+            status,
+            total_items,
+            imported_items,
+            skipped_items,
+            ..Default::default()
+        };
+
+        assert_eq!(import.progress(), expected);
+    }
+}
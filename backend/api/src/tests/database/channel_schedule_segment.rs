@@ -0,0 +1,108 @@
+use chrono::{Duration, TimeZone, Utc};
+
+use crate::database::channel_schedule_segment;
+
+fn segment(
+    starts_at: chrono::DateTime<Utc>,
+    hours: Option<i64>,
+    recurring: bool,
+) -> channel_schedule_segment::Model {
+    channel_schedule_segment::Model {
+        starts_at,
+        ends_at: hours.map(|hours| starts_at + Duration::hours(hours)),
+        recurring,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_occurrence_at() {
+    let monday = Utc.with_ymd_and_hms(2023, 8, 28, 18, 0, 0).unwrap();
+    let weekly = segment(monday, Some(2), true);
+
+    // Running occurrences are returned, otherwise the next one.
+    assert_eq!(
+        weekly.occurrence_at(monday + Duration::weeks(3) + Duration::hours(1)),
+        (
+            monday + Duration::weeks(3),
+            monday + Duration::weeks(3) + Duration::hours(2)
+        )
+    );
+    assert_eq!(
+        weekly.occurrence_at(monday + Duration::weeks(3) + Duration::hours(2)),
+        (
+            monday + Duration::weeks(4),
+            monday + Duration::weeks(4) + Duration::hours(2)
+        )
+    );
+    assert_eq!(
+        weekly.occurrence_at(monday - Duration::weeks(1)),
+        (monday, monday + Duration::hours(2))
+    );
+
+    let once = segment(monday, None, false);
+    assert_eq!(
+        once.occurrence_at(monday + Duration::weeks(1)),
+        (monday, monday + Duration::hours(4))
+    );
+}
+
+#[test]
+fn test_covers() {
+    let monday = Utc.with_ymd_and_hms(2023, 8, 28, 18, 0, 0).unwrap();
+    let weekly = segment(monday, Some(2), true);
+
+    assert!(weekly.covers(monday - Duration::minutes(10)));
+    assert!(!weekly.covers(monday - Duration::minutes(20)));
+    assert!(weekly.covers(monday + Duration::weeks(2) + Duration::minutes(90)));
+    assert!(!weekly.covers(monday + Duration::weeks(2) + Duration::hours(3)));
+
+    let once = segment(monday, Some(2), false);
+    assert!(once.covers(monday + Duration::hours(1)));
+    assert!(!once.covers(monday + Duration::weeks(1) + Duration::hours(1)));
+}
+
+#[test]
+fn test_overlaps() {
+    let monday = Utc.with_ymd_and_hms(2023, 8, 28, 18, 0, 0).unwrap();
+    let weekly = segment(monday, Some(2), true);
+
+    let tests = vec![
+        (segment(monday + Duration::hours(1), Some(2), false), true),
+        (segment(monday + Duration::hours(2), Some(2), false), false),
+        (segment(monday - Duration::hours(1), Some(1), false), false),
+        (segment(monday - Duration::hours(1), None, false), true),
+        // A later week of the recurring stream.
+        (
+            segment(
+                monday + Duration::weeks(5) + Duration::hours(1),
+                Some(1),
+                false,
+            ),
+            true,
+        ),
+        (segment(monday - Duration::weeks(1), Some(2), false), false),
+        // Recurring streams overlap in the weeks after the later one starts.
+        (
+            segment(
+                monday - Duration::weeks(2) + Duration::hours(1),
+                Some(2),
+                true,
+            ),
+            true,
+        ),
+        (
+            segment(
+                monday + Duration::weeks(2) + Duration::days(1),
+                Some(2),
+                true,
+            ),
+            false,
+        ),
+    ];
+
+    for (other, result) in tests {
+        assert_eq!(weekly.overlaps(&other), result, "{:?}", other);
+        assert_eq!(other.overlaps(&weekly), result, "{:?}", other);
+    }
+}
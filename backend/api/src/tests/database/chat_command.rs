@@ -0,0 +1,35 @@
+use crate::database::chat_command::{self, Permission};
+
+#[test]
+fn test_parse() {
+    let tests = vec![
+        ("!discord", Some("discord")),
+        ("!Discord please", Some("discord")),
+        ("!so_me-2", Some("so_me-2")),
+        ("discord", None),
+        ("! discord", None),
+        ("!", None),
+        ("!!discord", None),
+        ("!dis.cord", None),
+        ("!abcdefghijklmnopqrstuvwxyz", None),
+    ];
+
+    for (content, name) in tests {
+        assert_eq!(
+            chat_command::parse(content).as_deref(),
+            name,
+            "content: {}",
+            content
+        );
+    }
+}
+
+#[test]
+fn test_permission_allows() {
+    assert!(Permission::Everyone.allows(false, false));
+    assert!(!Permission::Vip.allows(false, false));
+    assert!(Permission::Vip.allows(true, false));
+    assert!(Permission::Vip.allows(false, true));
+    assert!(!Permission::Broadcaster.allows(true, false));
+    assert!(Permission::Broadcaster.allows(false, true));
+}
@@ -0,0 +1,27 @@
+use crate::database::chat_message;
+
+#[test]
+fn test_contains_link() {
+    let tests = vec![
+        ("hello world", false),
+        ("https://example.com", true),
+        ("see ftp://files", true),
+        ("go to www.example", true),
+        ("example.com", true),
+        ("(example.gg/path)", true),
+        ("e.g. this", false),
+        ("the end.", false),
+        ("version 1.2", false),
+        ("hello.world", false),
+        (".com", false),
+    ];
+
+    for (content, contains_link) in tests {
+        assert_eq!(
+            chat_message::contains_link(content),
+            contains_link,
+            "content: {}",
+            content
+        );
+    }
+}
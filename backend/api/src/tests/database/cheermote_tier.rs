@@ -0,0 +1,85 @@
+use uuid::Uuid;
+
+use crate::database::cheermote_tier;
+
+fn tier(channel_id: Option<Uuid>, min_bits: i64) -> cheermote_tier::Model {
+    cheermote_tier::Model {
+        id: Uuid::new_v4(),
+        channel_id,
+        prefix: "Cheer".to_string(),
+        min_bits,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_resolve_picks_highest_reached_tier() {
+    let tiers = vec![tier(None, 1), tier(None, 100), tier(None, 1000)];
+
+    let tests = vec![
+        (0, None),
+        (1, Some(1)),
+        (99, Some(1)),
+        (100, Some(100)),
+        (5000, Some(1000)),
+    ];
+
+    for (bits, min_bits) in tests {
+        assert_eq!(
+            cheermote_tier::resolve(&tiers, bits).map(|t| t.min_bits),
+            min_bits,
+            "bits: {}",
+            bits
+        );
+    }
+}
+
+#[test]
+fn test_resolve_channel_tiers_replace_defaults() {
+    let channel_id = Some(Uuid::new_v4());
+    let tiers = vec![tier(None, 1), tier(None, 100), tier(channel_id, 50)];
+
+    assert!(cheermote_tier::resolve(&tiers, 10).is_none());
+
+    let resolved = cheermote_tier::resolve(&tiers, 500).unwrap();
+    assert_eq!(resolved.channel_id, channel_id);
+    assert_eq!(resolved.min_bits, 50);
+}
+
+#[test]
+fn test_validate_prefix() {
+    let tests = vec![
+        ("Cheer", Ok(())),
+        ("", Err("Prefix must be between 1 and 32 characters")),
+        ("Cheer1", Err("Prefix can only contain letters")),
+    ];
+
+    for (prefix, result) in tests {
+        assert_eq!(
+            cheermote_tier::validate_prefix(prefix),
+            result,
+            "prefix: {}",
+            prefix
+        );
+    }
+}
+
+#[test]
+fn test_validate_color() {
+    let tests = vec![
+        ("#9146ff", Ok(())),
+        ("#9146FF", Ok(())),
+        ("9146ff", Err("Color must be a hex color like #9146ff")),
+        ("#fff", Err("Color must be a hex color like #9146ff")),
+        ("#zzzzzz", Err("Color must be a hex color like #9146ff")),
+    ];
+
+    for (color, result) in tests {
+        assert_eq!(
+            cheermote_tier::validate_color(color),
+            result,
+            "color: {}",
+            color
+        );
+    }
+}
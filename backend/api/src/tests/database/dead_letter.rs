@@ -0,0 +1,23 @@
+use prost::Message;
+
+use crate::{database::dead_letter::Consumer, pb::scuffle::events::ModerationJob};
+
+#[test]
+fn test_decode() {
+    let payload = ModerationJob {
+        id: "0f1c7f2a-5a4a-4d4e-9a55-2b9f3e1c6d7e".to_string(),
+    }
+    .encode_to_vec();
+
+    assert_eq!(
+        Consumer::Moderation.decode(&payload).unwrap(),
+        "ModerationJob { id: \"0f1c7f2a-5a4a-4d4e-9a55-2b9f3e1c6d7e\" }"
+    );
+    assert_eq!(
+        Consumer::Import.decode(&payload).unwrap(),
+        "ChannelImportJob { id: \"0f1c7f2a-5a4a-4d4e-9a55-2b9f3e1c6d7e\" }"
+    );
+
+    // A poison message, the wire type of the first field is invalid.
+    assert!(Consumer::Notifications.decode(&[0x0f, 0xff]).is_err());
+}
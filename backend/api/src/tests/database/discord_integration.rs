@@ -0,0 +1,41 @@
+use crate::database::discord_integration;
+
+#[test]
+fn test_validate_webhook_url() {
+    let tests = vec![
+        ("https://discord.com/api/webhooks/123/abc", Ok(())),
+        ("https://discordapp.com/api/webhooks/123/abc", Ok(())),
+        ("not a url", Err("Webhook url must be a valid url")),
+        (
+            "http://discord.com/api/webhooks/123/abc",
+            Err("Webhook url must be a Discord webhook url"),
+        ),
+        (
+            "https://discord.com.example.com/api/webhooks/123/abc",
+            Err("Webhook url must be a Discord webhook url"),
+        ),
+        (
+            "https://discord.com/api/channels/123",
+            Err("Webhook url must be a Discord webhook url"),
+        ),
+    ];
+
+    for (url, expected) in tests {
+        assert_eq!(
+            discord_integration::validate_webhook_url(url),
+            expected,
+            "{}",
+            url
+        );
+    }
+}
+
+#[test]
+fn test_validate_bot_channel_id() {
+    assert_eq!(
+        discord_integration::validate_bot_channel_id("1104120686499369041"),
+        Ok(())
+    );
+    assert!(discord_integration::validate_bot_channel_id("").is_err());
+    assert!(discord_integration::validate_bot_channel_id("general").is_err());
+}
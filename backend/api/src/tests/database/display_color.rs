@@ -0,0 +1,104 @@
+use crate::database::display_color::{Color, DisplayColor, Rgb};
+use crate::database::user;
+
+#[test]
+fn test_parse_color() {
+    let tests = [
+        ("blue", None, Ok(("#1565c0", "#74c0fc"))),
+        (" Blue ", None, Ok(("#1565c0", "#74c0fc"))),
+        ("#9146FF", None, Ok(("#9146ff", "#9146ff"))),
+        ("#6a1b9a", Some("#b197fc"), Ok(("#6a1b9a", "#b197fc"))),
+        (
+            "blue",
+            Some("#b197fc"),
+            Err("Palette colors already have a dark variant"),
+        ),
+        (
+            "9146ff",
+            None,
+            Err("Color must be a palette color or a hex color like #9146ff"),
+        ),
+        (
+            "#fff",
+            None,
+            Err("Color must be a palette color or a hex color like #9146ff"),
+        ),
+        (
+            "#6a1b9a",
+            Some("purple"),
+            Err("Dark color must be a hex color like #9146ff"),
+        ),
+    ];
+
+    for (color, dark, result) in tests {
+        assert_eq!(
+            Color::parse(color, dark).map(|c| (c.light.to_hex(), c.dark.to_hex())),
+            result.map(|(l, d)| (l.to_string(), d.to_string())),
+            "color: {}",
+            color
+        );
+    }
+}
+
+#[test]
+fn test_check_contrast() {
+    let tests = [
+        ("#9146ff", None, Ok(())),
+        (
+            "#ffff00",
+            None,
+            Err("Color is not readable on the light theme"),
+        ),
+        (
+            "#000080",
+            None,
+            Err("Color is not readable on the dark theme"),
+        ),
+        ("#000080", Some("#ffff00"), Ok(())),
+    ];
+
+    for (color, dark, result) in tests {
+        assert_eq!(
+            Color::parse(color, dark).unwrap().check_contrast(),
+            result,
+            "color: {}",
+            color
+        );
+    }
+
+    assert!((Rgb(0, 0, 0).contrast(Rgb(0xff, 0xff, 0xff)) - 21.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_stored_color() {
+    for (color, dark, stored) in [
+        ("purple", None, "purple"),
+        ("#9146FF", None, "#9146ff"),
+        ("#6a1b9a", Some("#B197FC"), "#6a1b9a/#b197fc"),
+    ] {
+        let color = Color::parse(color, dark).unwrap();
+        assert_eq!(color.to_stored(), stored);
+        assert_eq!(Color::from_stored(stored), Some(color));
+    }
+
+    assert_eq!(Color::from_stored(""), None);
+
+    let user = user::Model {
+        display_color: "purple".to_string(),
+        display_gradient_end: "#9146ff".to_string(),
+        ..Default::default()
+    };
+
+    // Users who lost the gradient permission keep the first color.
+    let color = DisplayColor::of_user(&user, false).unwrap();
+    assert_eq!(color.color.name.as_deref(), Some("purple"));
+    assert_eq!(color.gradient_end, None);
+
+    let color = DisplayColor::of_user(&user, true).unwrap();
+    assert_eq!(
+        color.gradient_end.map(|c| c.light.to_hex()).as_deref(),
+        Some("#9146ff")
+    );
+
+    assert_eq!(DisplayColor::of_user(&user::Model::default(), true), None);
+}
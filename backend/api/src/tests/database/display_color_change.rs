@@ -0,0 +1,57 @@
+use chrono::{Duration, TimeZone, Utc};
+
+use crate::config::DisplayColorConfig;
+use crate::database::display_color_change::RecentChanges;
+
+#[test]
+fn test_retry_at() {
+    let config = DisplayColorConfig {
+        cooldown: 30,
+        max_changes: 3,
+        rate_limit_window: 60 * 60,
+    };
+
+    let now = Utc.with_ymd_and_hms(2023, 8, 16, 12, 0, 0).unwrap();
+    let ago = |seconds: i64| Some(now - Duration::seconds(seconds));
+
+    let tests = [
+        (RecentChanges::default(), None),
+        (
+            RecentChanges {
+                count: 1,
+                oldest: ago(10),
+                newest: ago(10),
+            },
+            ago(-20),
+        ),
+        (
+            RecentChanges {
+                count: 2,
+                oldest: ago(600),
+                newest: ago(60),
+            },
+            None,
+        ),
+        (
+            RecentChanges {
+                count: 3,
+                oldest: ago(600),
+                newest: ago(60),
+            },
+            ago(-3000),
+        ),
+        // The window ends before the cooldown.
+        (
+            RecentChanges {
+                count: 3,
+                oldest: ago(3590),
+                newest: ago(10),
+            },
+            ago(-20),
+        ),
+    ];
+
+    for (recent, retry_at) in tests {
+        assert_eq!(recent.retry_at(&config, now), retry_at, "{:?}", recent);
+    }
+}
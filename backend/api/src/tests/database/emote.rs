@@ -0,0 +1,124 @@
+use crate::{config::EmoteConfig, database::emote};
+
+#[test]
+fn test_validate_names() {
+    let tests = vec![
+        ("Kappa", Ok(())),
+        ("pog123", Ok(())),
+        ("a", Err("Emote name must be at least 2 characters long")),
+        (
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            Err("Emote name must be at most 32 characters long"),
+        ),
+        (
+            "not_allowed",
+            Err("Emote name must only contain alphanumeric characters"),
+        ),
+    ];
+
+    for (name, result) in tests {
+        assert_eq!(emote::validate_name(name), result, "name: {}", name);
+    }
+}
+
+#[test]
+fn test_image_dimensions() {
+    let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    png.extend(112u32.to_be_bytes());
+    png.extend(56u32.to_be_bytes());
+
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend(28u16.to_le_bytes());
+    gif.extend(28u16.to_le_bytes());
+
+    let webp = |chunk: &[u8], header: &[u8]| {
+        let mut data = b"RIFF\0\0\0\0WEBP".to_vec();
+        data.extend(chunk);
+        data.extend([0; 4]);
+        data.extend(header);
+        data
+    };
+    let lossy = webp(b"VP8 ", &[0, 0, 0, 0x9d, 0x01, 0x2a, 64, 0, 32, 0]);
+    let lossless = webp(
+        b"VP8L",
+        &[0x2f, 0x6f, 0xc0, 0x1b, 0x00], // 112 x 112
+    );
+    let extended = webp(b"VP8X", &[0, 0, 0, 0, 111, 0, 0, 111, 0, 0]);
+
+    let tests: Vec<(&[u8], Option<(u32, u32)>)> = vec![
+        (&png, Some((112, 56))),
+        (&gif, Some((28, 28))),
+        (&lossy, Some((64, 32))),
+        (&lossless, Some((112, 112))),
+        (&extended, Some((112, 112))),
+        (&png[..20], None),
+        (b"<svg></svg>", None),
+        (b"", None),
+    ];
+
+    for (data, result) in tests {
+        assert_eq!(emote::image_dimensions(data), result, "data: {:?}", data);
+    }
+}
+
+#[test]
+fn test_validate_dimensions() {
+    let tests = vec![
+        (112, 112, Ok(())),
+        (28, 28, Ok(())),
+        (112, 56, Err("Emote must be square")),
+        (128, 128, Err("Emote is too large")),
+        (0, 0, Err("Emote is too large")),
+    ];
+
+    for (width, height, result) in tests {
+        assert_eq!(
+            emote::validate_dimensions(width, height, 112),
+            result,
+            "width: {}, height: {}",
+            width,
+            height
+        );
+    }
+}
+
+#[test]
+fn test_slot_count() {
+    let config = EmoteConfig {
+        base_slots: 5,
+        sub_points_per_slot: 10,
+        max_slots: 8,
+        ..Default::default()
+    };
+
+    let tests = vec![(0, 5), (9, 5), (10, 6), (25, 7), (1000, 8), (-5, 5)];
+
+    for (sub_points, result) in tests {
+        assert_eq!(
+            emote::slot_count(sub_points, &config),
+            result,
+            "sub_points: {}",
+            sub_points
+        );
+    }
+
+    let config = EmoteConfig {
+        sub_points_per_slot: 0,
+        ..config
+    };
+
+    assert_eq!(emote::slot_count(1000, &config), 5);
+}
+
+#[test]
+fn test_status_transitions() {
+    use emote::Status::*;
+
+    assert!(PendingReview.can_become(Approved));
+    assert!(PendingReview.can_become(Rejected));
+    assert!(Approved.can_become(Rejected));
+    assert!(!Approved.can_become(Approved));
+    assert!(!Approved.can_become(PendingReview));
+    assert!(!Rejected.can_become(Approved));
+    assert!(!Rejected.can_become(Rejected));
+}
@@ -0,0 +1,37 @@
+use crate::database::emote_provider::{self, Emote};
+
+#[test]
+fn test_tokenize() {
+    let emotes = vec![
+        Emote {
+            name: "Kappa".to_string(),
+            url: "https://cdn.scuffle.tv/kappa.png".to_string(),
+            provider: None,
+        },
+        Emote {
+            name: "Kappa".to_string(),
+            url: "https://provider.example/kappa.png".to_string(),
+            provider: Some("provider".to_string()),
+        },
+        Emote {
+            name: "catJAM".to_string(),
+            url: "https://provider.example/catjam.gif".to_string(),
+            provider: Some("provider".to_string()),
+        },
+    ];
+
+    let tokens = emote_provider::tokenize("héllo Kappa  catJAM", &emotes);
+
+    assert_eq!(tokens.len(), 2);
+
+    assert_eq!(tokens[0].start, 6);
+    assert_eq!(tokens[0].end, 11);
+    assert_eq!(tokens[0].emote, emotes[0]);
+
+    assert_eq!(tokens[1].start, 13);
+    assert_eq!(tokens[1].end, 19);
+    assert_eq!(tokens[1].emote, emotes[2]);
+
+    assert!(emote_provider::tokenize("Kappa123 kappa", &emotes).is_empty());
+    assert!(emote_provider::tokenize("", &emotes).is_empty());
+}
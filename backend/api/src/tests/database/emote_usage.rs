@@ -0,0 +1,14 @@
+use crate::database::emote_usage;
+
+#[test]
+fn test_count_uses() {
+    let names = vec!["Kappa".to_string(), "PogChamp".to_string()];
+
+    let uses = emote_usage::count_uses("Kappa hello Kappa PogChamp kappa Kappa123", &names);
+
+    assert_eq!(uses.len(), 2);
+    assert_eq!(uses.get("Kappa"), Some(&2));
+    assert_eq!(uses.get("PogChamp"), Some(&1));
+
+    assert!(emote_usage::count_uses("no emotes here", &names).is_empty());
+}
@@ -0,0 +1,87 @@
+use uuid::Uuid;
+
+use crate::database::giveaway::{self, Model};
+
+#[test]
+fn test_draw() {
+    let a = Uuid::from_u128(1);
+    let b = Uuid::from_u128(2);
+
+    assert_eq!(giveaway::draw("seed", &[]), None);
+    assert_eq!(giveaway::draw("seed", &[(a, 0)]), None);
+    assert_eq!(giveaway::draw("seed", &[(a, 1)]), Some(a));
+
+    // The order the entries are read in does not change the winner.
+    for i in 0..100 {
+        let seed = format!("seed {}", i);
+        assert_eq!(
+            giveaway::draw(&seed, &[(a, 1), (b, 3)]),
+            giveaway::draw(&seed, &[(b, 3), (a, 1)]),
+        );
+    }
+
+    let wins = (0..1000)
+        .filter(|i| giveaway::draw(&format!("seed {}", i), &[(a, 1), (b, 9)]) == Some(b))
+        .count();
+    assert!((850..=950).contains(&wins), "wins: {}", wins);
+}
+
+#[test]
+fn test_commitment() {
+    assert_eq!(
+        giveaway::commitment("abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+
+    let seed = giveaway::generate_seed();
+    assert_eq!(seed.len(), 32);
+    assert_ne!(seed, giveaway::generate_seed());
+}
+
+#[test]
+fn test_tickets() {
+    let giveaway = Model {
+        follower_weight: 2,
+        subscriber_weight: 5,
+        ..Default::default()
+    };
+
+    assert_eq!(giveaway.tickets(false, false), 1);
+    assert_eq!(giveaway.tickets(true, false), 2);
+    assert_eq!(giveaway.tickets(false, true), 5);
+    assert_eq!(giveaway.tickets(true, true), 5);
+}
+
+#[test]
+fn test_matches_keyword() {
+    let giveaway = Model {
+        keyword: Some("!enter".to_string()),
+        ..Default::default()
+    };
+
+    assert!(giveaway.matches_keyword("!enter"));
+    assert!(giveaway.matches_keyword(" !ENTER "));
+    assert!(!giveaway.matches_keyword("!enter please"));
+    assert!(!Model::default().matches_keyword("!enter"));
+}
+
+#[test]
+fn test_validate() {
+    assert!(giveaway::validate("A game key", Some("!enter"), 1, 3).is_ok());
+    assert!(giveaway::validate("A game key", None, 1, 1).is_ok());
+    assert_eq!(
+        giveaway::validate(" ", None, 1, 1),
+        Err(("title", "Title must be between 1 and 140 characters"))
+    );
+    assert_eq!(
+        giveaway::validate("A game key", Some("two words"), 1, 1),
+        Err((
+            "keyword",
+            "Keyword must be a single word of at most 25 characters"
+        ))
+    );
+    assert_eq!(
+        giveaway::validate("A game key", None, 1, 11),
+        Err(("subscriberWeight", "Weights must be between 1 and 10"))
+    );
+}
@@ -0,0 +1,29 @@
+use crate::database::invite_code;
+
+#[test]
+fn test_generate_code() {
+    let code = invite_code::generate_code();
+
+    assert_eq!(code.len(), 9);
+    assert_eq!(&code[4..5], "-");
+    assert!(code
+        .chars()
+        .filter(|c| *c != '-')
+        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+    assert!(!code.contains(['0', 'O', '1', 'I']));
+    assert_eq!(invite_code::normalize_code(&code), code);
+}
+
+#[test]
+fn test_normalize_code() {
+    let tests = vec![
+        ("ABCD-EFGH", "ABCD-EFGH"),
+        ("abcdefgh", "ABCD-EFGH"),
+        (" abcd efgh ", "ABCD-EFGH"),
+        ("abc", "ABC"),
+    ];
+
+    for (code, normalized) in tests {
+        assert_eq!(invite_code::normalize_code(code), normalized, "{}", code);
+    }
+}
@@ -0,0 +1,44 @@
+use chrono::Utc;
+
+use crate::database::login_link;
+
+#[test]
+fn test_generate_token() {
+    let token = login_link::generate_token();
+
+    assert_eq!(token.len(), 48);
+    assert!(token.chars().all(|c| c.is_ascii_alphanumeric()));
+    assert_ne!(token, login_link::generate_token());
+}
+
+#[test]
+fn test_can_login() {
+    let now = Utc::now();
+    let later = now + chrono::Duration::minutes(5);
+    let earlier = now - chrono::Duration::minutes(5);
+
+    let tests = vec![
+        (later, None, None, false),
+        (later, Some(now), None, true),
+        (later, Some(now), Some(now), false),
+        (earlier, Some(earlier), None, false),
+    ];
+
+    for (expires_at, confirmed_at, used_at, can_login) in tests {
+        let link = login_link::Model {
+            expires_at,
+            confirmed_at,
+            used_at,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            link.can_login(),
+            can_login,
+            "{:?} {:?} {:?}",
+            expires_at,
+            confirmed_at,
+            used_at
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use crate::database::media_request;
+
+#[test]
+fn test_command() {
+    assert_eq!(
+        media_request::command("!sr https://youtu.be/dQw4w9WgXcQ"),
+        Some("https://youtu.be/dQw4w9WgXcQ")
+    );
+    assert_eq!(
+        media_request::command("  !SR   https://youtu.be/dQw4w9WgXcQ "),
+        Some("https://youtu.be/dQw4w9WgXcQ")
+    );
+    assert_eq!(media_request::command("!sr"), None);
+    assert_eq!(media_request::command("!srs https://youtu.be/a"), None);
+    assert_eq!(media_request::command("sr https://youtu.be/a"), None);
+}
+
+#[test]
+fn test_validate_url() {
+    let allowed = vec!["youtube.com".to_string(), "youtu.be".to_string()];
+
+    assert_eq!(
+        media_request::validate_url("https://youtube.com/watch?v=dQw4w9WgXcQ", &allowed),
+        Ok(())
+    );
+    assert_eq!(
+        media_request::validate_url("https://www.YouTube.com/watch?v=dQw4w9WgXcQ", &allowed),
+        Ok(())
+    );
+    assert_eq!(
+        media_request::validate_url("HTTPS://youtu.be:443/dQw4w9WgXcQ", &allowed),
+        Ok(())
+    );
+    assert_eq!(
+        media_request::validate_url("http://youtube.com/watch", &allowed),
+        Err("Url must start with https://")
+    );
+    assert_eq!(
+        media_request::validate_url("youtube.com/watch", &allowed),
+        Err("Url must start with https://")
+    );
+    assert_eq!(
+        media_request::validate_url("https://notyoutube.com/watch", &allowed),
+        Err("Media from this site can't be requested")
+    );
+    assert_eq!(
+        media_request::validate_url("https://youtube.com.example.com/watch", &allowed),
+        Err("Media from this site can't be requested")
+    );
+    assert_eq!(
+        media_request::validate_url("https://youtube.com@example.com/watch", &allowed),
+        Err("Url is not a valid link")
+    );
+    assert_eq!(
+        media_request::validate_url("https:///watch", &allowed),
+        Err("Url is not a valid link")
+    );
+    assert_eq!(
+        media_request::validate_url("https://youtube.com/watch?v=a b", &allowed),
+        Err("Url must not contain spaces")
+    );
+}
@@ -1,2 +1,35 @@
+mod channel_event;
+mod channel_import;
+mod channel_schedule_segment;
+mod chat_command;
+mod chat_message;
+mod cheermote_tier;
+mod dead_letter;
+mod discord_integration;
+mod display_color;
+mod display_color_change;
+mod emote;
+mod emote_provider;
+mod emote_usage;
+mod giveaway;
 mod global_role;
+mod invite_code;
+mod login_link;
+mod media_request;
+mod moderation_job;
+mod obs_connection;
+mod personal_access_token;
+mod poll;
+mod promotion;
+mod recovery_code;
+mod revenue_transaction;
+mod stream;
+mod stream_chapter;
+mod stream_key;
+mod tag;
 mod user;
+mod user_social_link;
+mod user_suspension;
+mod viewer_queue;
+mod vod_caption;
+mod vod_download;
@@ -0,0 +1,38 @@
+use crate::database::moderation_job::{like_pattern, Model, Status};
+
+#[test]
+fn test_progress() {
+    let tests = vec![
+        (Status::Queued, 0, 0, 0.0),
+        (Status::Running, 10, 0, 0.0),
+        (Status::Running, 10, 5, 0.5),
+        (Status::Failed, 10, 2, 0.2),
+        (Status::Completed, 0, 0, 1.0),
+    ];
+
+    for (status, total_items, processed_items, expected) in tests {
+        let job = Model {
+            status,
+            total_items,
+            processed_items,
+            ..Default::default()
+        };
+
+        assert_eq!(job.progress(), expected);
+    }
+}
+
+#[test]
+fn test_like_pattern() {
+    let tests = vec![
+        ("spam", "%spam%"),
+        ("100%", "%100\\%%"),
+        ("free_nitro", "%free\\_nitro%"),
+        ("c:\\x", "%c:\\\\x%"),
+        ("", "%%"),
+    ];
+
+    for (text, expected) in tests {
+        assert_eq!(like_pattern(text), expected, "{}", text);
+    }
+}
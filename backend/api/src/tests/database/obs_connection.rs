@@ -0,0 +1,29 @@
+use crate::database::obs_connection;
+
+#[test]
+fn test_validate_host() {
+    let tests = vec![
+        ("obs.example.com", Ok(())),
+        ("203.0.113.7", Ok(())),
+        ("[2001:db8::1]", Ok(())),
+        ("", Err("Host must be between 1 and 255 characters")),
+        ("localhost", Err("Host must be publicly reachable")),
+        ("obs.localhost", Err("Host must be publicly reachable")),
+        ("127.0.0.1", Err("Host must be publicly reachable")),
+        ("10.0.0.5", Err("Host must be publicly reachable")),
+        ("192.168.1.20", Err("Host must be publicly reachable")),
+        ("169.254.169.254", Err("Host must be publicly reachable")),
+        ("[::1]", Err("Host must be publicly reachable")),
+        ("[fd00::1]", Err("Host must be publicly reachable")),
+        ("[fe80::1]", Err("Host must be publicly reachable")),
+        ("[::ffff:127.0.0.1]", Err("Host must be publicly reachable")),
+        (
+            "obs.example.com/path",
+            Err("Host must be a hostname or an ip address"),
+        ),
+    ];
+
+    for (host, expected) in tests {
+        assert_eq!(obs_connection::validate_host(host), expected, "{}", host);
+    }
+}
@@ -0,0 +1,39 @@
+use chrono::Utc;
+
+use crate::database::personal_access_token;
+
+#[test]
+fn test_generate_token() {
+    let token = personal_access_token::generate_token();
+
+    assert!(token.starts_with(personal_access_token::TOKEN_PREFIX));
+    assert_eq!(token.len(), personal_access_token::TOKEN_PREFIX.len() + 40);
+    assert_ne!(token, personal_access_token::generate_token());
+}
+
+#[test]
+fn test_hash_token() {
+    assert_eq!(
+        personal_access_token::hash_token("test"),
+        "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+    );
+}
+
+#[test]
+fn test_allows() {
+    let token = personal_access_token::Model {
+        scopes: personal_access_token::Scope::Control,
+        ..Default::default()
+    };
+    assert!(token.allows(personal_access_token::Scope::Control));
+
+    let expired = personal_access_token::Model {
+        scopes: personal_access_token::Scope::Control,
+        expires_at: Some(Utc::now() - chrono::Duration::seconds(1)),
+        ..Default::default()
+    };
+    assert!(!expired.allows(personal_access_token::Scope::Control));
+
+    let unscoped = personal_access_token::Model::default();
+    assert!(!unscoped.allows(personal_access_token::Scope::Control));
+}
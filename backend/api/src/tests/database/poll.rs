@@ -0,0 +1,45 @@
+use crate::database::poll;
+
+#[test]
+fn test_validate() {
+    let options = |o: &[&str]| o.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+
+    let tests = vec![
+        ("Best map?", options(&["Dust", "Mirage"]), 60, Ok(())),
+        (
+            "",
+            options(&["Dust", "Mirage"]),
+            60,
+            Err("Title must be between 1 and 140 characters"),
+        ),
+        (
+            "Best map?",
+            options(&["Dust"]),
+            60,
+            Err("A poll must have between 2 and 5 options"),
+        ),
+        (
+            "Best map?",
+            options(&["Dust", " "]),
+            60,
+            Err("Options must be between 1 and 25 characters"),
+        ),
+        (
+            "Best map?",
+            options(&["Dust", "Mirage"]),
+            5,
+            Err("Duration must be between 15 and 1800 seconds"),
+        ),
+    ];
+
+    for (title, options, duration, result) in tests {
+        assert_eq!(
+            poll::validate(title, &options, duration),
+            result,
+            "title: {}, options: {:?}, duration: {}",
+            title,
+            options,
+            duration
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use crate::database::promotion;
+
+#[test]
+fn test_discounted_price() {
+    let tests = vec![
+        (499, 50, 250),
+        (499, 25, 375),
+        (1000, 10, 900),
+        (1000, 100, 0),
+        (1000, 0, 1000),
+        (1000, 150, 0),
+    ];
+
+    for (price, percent_off, result) in tests {
+        assert_eq!(
+            promotion::discounted_price(price, percent_off),
+            result,
+            "price: {}, percent_off: {}",
+            price,
+            percent_off
+        );
+    }
+}
+
+#[test]
+fn test_is_eligible() {
+    let mut promotion = promotion::Model::default();
+
+    assert!(promotion.is_eligible(true));
+    assert!(promotion.is_eligible(false));
+
+    promotion.first_month_only = true;
+
+    assert!(promotion.is_eligible(true));
+    assert!(!promotion.is_eligible(false));
+}
@@ -0,0 +1,24 @@
+use crate::database::recovery_code;
+
+#[test]
+fn test_generate_code() {
+    let code = recovery_code::generate_code();
+
+    assert_eq!(code.len(), 11);
+    assert_eq!(code.chars().nth(5), Some('-'));
+    assert!(code
+        .chars()
+        .all(|c| c == '-' || c.is_ascii_lowercase() || c.is_ascii_digit()));
+    assert_ne!(code, recovery_code::generate_code());
+}
+
+#[test]
+fn test_hash_code() {
+    let hash = recovery_code::hash_code("k3m9p-q2xw7");
+
+    assert_eq!(hash.len(), 64);
+    assert_eq!(recovery_code::hash_code("K3M9P-Q2XW7"), hash);
+    assert_eq!(recovery_code::hash_code(" k3m9p q2xw7 "), hash);
+    assert_eq!(recovery_code::hash_code("k3m9pq2xw7"), hash);
+    assert_ne!(recovery_code::hash_code("k3m9p-q2xw8"), hash);
+}
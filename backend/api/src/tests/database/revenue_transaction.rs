@@ -0,0 +1,23 @@
+use crate::database::revenue_transaction;
+
+#[test]
+fn test_platform_fee() {
+    let tests = vec![
+        (1000, 50, 500),
+        (999, 50, 499),
+        (499, 30, 149),
+        (1000, 0, 0),
+        (1000, 100, 1000),
+        (1000, 150, 1000),
+    ];
+
+    for (gross_amount, fee_percent, result) in tests {
+        assert_eq!(
+            revenue_transaction::platform_fee(gross_amount, fee_percent),
+            result,
+            "gross_amount: {}, fee_percent: {}",
+            gross_amount,
+            fee_percent
+        );
+    }
+}
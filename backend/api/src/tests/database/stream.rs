@@ -0,0 +1,60 @@
+use chrono::{Duration, Utc};
+
+use crate::database::stream::{self, Lifecycle};
+
+#[test]
+fn test_lifecycle_transitions() {
+    assert!(Lifecycle::Requested.can_become(Lifecycle::Ready));
+    assert!(Lifecycle::Requested.can_become(Lifecycle::Live));
+    assert!(Lifecycle::Ready.can_become(Lifecycle::Live));
+    assert!(Lifecycle::Live.can_become(Lifecycle::Reconnecting));
+    assert!(Lifecycle::Live.can_become(Lifecycle::Ended));
+    assert!(Lifecycle::Reconnecting.can_become(Lifecycle::Ended));
+    assert!(Lifecycle::Ended.can_become(Lifecycle::ProcessingVod));
+
+    assert!(!Lifecycle::Live.can_become(Lifecycle::Live));
+    assert!(!Lifecycle::Live.can_become(Lifecycle::Ready));
+    assert!(!Lifecycle::Reconnecting.can_become(Lifecycle::Live));
+    assert!(!Lifecycle::Ended.can_become(Lifecycle::Live));
+    assert!(!Lifecycle::Requested.can_become(Lifecycle::ProcessingVod));
+    assert!(!Lifecycle::ProcessingVod.can_become(Lifecycle::Ended));
+}
+
+#[test]
+fn test_lifecycle_roundtrip() {
+    for state in [
+        Lifecycle::Requested,
+        Lifecycle::Ready,
+        Lifecycle::Live,
+        Lifecycle::Reconnecting,
+        Lifecycle::Ended,
+        Lifecycle::ProcessingVod,
+    ] {
+        assert_eq!(Lifecycle::from(i64::from(state)), state);
+    }
+}
+
+#[test]
+fn test_current_lifecycle() {
+    let now = Utc::now();
+
+    let live = stream::Model {
+        lifecycle: Lifecycle::Live,
+        ended_at: now + Duration::minutes(5),
+        ..Default::default()
+    };
+    assert_eq!(live.current_lifecycle(now), Lifecycle::Live);
+
+    // The ingest stopped reporting the stream without ending it.
+    let gone = stream::Model {
+        ended_at: now - Duration::minutes(1),
+        ..live.clone()
+    };
+    assert_eq!(gone.current_lifecycle(now), Lifecycle::Ended);
+
+    let recorded = stream::Model {
+        recorded: true,
+        ..gone
+    };
+    assert_eq!(recorded.current_lifecycle(now), Lifecycle::ProcessingVod);
+}
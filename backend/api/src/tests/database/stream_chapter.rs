@@ -0,0 +1,85 @@
+use crate::database::stream_chapter::{self, Chapter};
+
+fn model(title: &str, category: &str, position_seconds: i64) -> stream_chapter::Model {
+    stream_chapter::Model {
+        title: title.to_string(),
+        category: category.to_string(),
+        position_seconds,
+        ..Default::default()
+    }
+}
+
+fn chapter(title: &str, category: &str, start_seconds: i64, end_seconds: i64) -> Chapter {
+    Chapter {
+        title: title.to_string(),
+        category: category.to_string(),
+        start_seconds,
+        end_seconds,
+    }
+}
+
+#[test]
+fn test_chapters() {
+    assert_eq!(stream_chapter::chapters(&[], 600), vec![]);
+
+    // A stream which went live late still starts its first chapter at the start of the VOD.
+    assert_eq!(
+        stream_chapter::chapters(&[model("Hello", "Chatting", 5)], 600),
+        vec![chapter("Hello", "Chatting", 0, 600)]
+    );
+
+    // Short VODs keep their only chapter.
+    assert_eq!(
+        stream_chapter::chapters(&[model("Hello", "Chatting", 0)], 30),
+        vec![chapter("Hello", "Chatting", 0, 30)]
+    );
+
+    assert_eq!(
+        stream_chapter::chapters(
+            &[
+                model("Hello", "Chatting", 0),
+                model("Speedrun", "Chatting", 300),
+                model("Speedrun", "Minecraft", 310),
+                model("Speedrn", "Chatting", 1200),
+                model("Speedrun", "Chatting", 1230),
+                model("Bye", "Chatting", 2400),
+                model("Bye bye", "Chatting", 2590),
+            ],
+            2600,
+        ),
+        vec![
+            chapter("Hello", "Chatting", 0, 300),
+            chapter("Speedrun", "Minecraft", 300, 1200),
+            chapter("Speedrun", "Chatting", 1200, 2400),
+            chapter("Bye", "Chatting", 2400, 2600),
+        ]
+    );
+
+    // A quick change back joins the chapters around it.
+    assert_eq!(
+        stream_chapter::chapters(
+            &[
+                model("Hello", "Chatting", 0),
+                model("Hello!", "Chatting", 300),
+                model("Hello", "Chatting", 320),
+            ],
+            600,
+        ),
+        vec![chapter("Hello", "Chatting", 0, 600)]
+    );
+}
+
+#[test]
+fn test_webvtt() {
+    assert_eq!(stream_chapter::webvtt(&[]), "WEBVTT\n");
+
+    assert_eq!(
+        stream_chapter::webvtt(&[
+            chapter("Hello", "Chatting", 0, 300),
+            chapter("Q&A <3", "", 300, 4000),
+            chapter("", "Minecraft", 4000, 4100),
+            chapter("", "", 4100, 4200),
+        ]),
+        "WEBVTT\n\n1\n00:00:00.000 --> 00:05:00.000\nHello (Chatting)\n\n2\n00:05:00.000 --> 01:06:40.000\nQ&amp;A &lt;3\n\n3\n01:06:40.000 --> 01:08:20.000\nMinecraft\n\n4\n01:08:20.000 --> 01:10:00.000\nChapter 4\n"
+    );
+}
@@ -0,0 +1,33 @@
+use uuid::Uuid;
+
+use crate::database::stream_key;
+
+#[test]
+fn test_mask() {
+    let channel_id = Uuid::from_u128(42);
+
+    assert_eq!(
+        stream_key::full_key(channel_id, "abcdefgh"),
+        "live_42_abcdefgh"
+    );
+    assert_eq!(stream_key::mask(channel_id, "abcdefgh"), "live_42_****efgh");
+    assert_eq!(stream_key::mask(channel_id, "abc"), "live_42_abc");
+}
+
+#[test]
+fn test_validate_name() {
+    assert_eq!(stream_key::validate_name("OBS"), Ok(()));
+    assert_eq!(
+        stream_key::validate_name(" "),
+        Err("Name must be between 1 and 64 characters")
+    );
+    assert_eq!(
+        stream_key::validate_name(&"a".repeat(65)),
+        Err("Name must be between 1 and 64 characters")
+    );
+    assert_eq!(
+        stream_key::validate_name(&format!(" {} ", "a".repeat(64))),
+        Ok(())
+    );
+    assert_eq!(stream_key::validate_name(&"é".repeat(64)), Ok(()));
+}
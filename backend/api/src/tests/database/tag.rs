@@ -0,0 +1,28 @@
+use crate::database::tag;
+
+#[test]
+fn test_validate_name() {
+    assert_eq!(tag::validate_name("Speedrun"), Ok(()));
+    assert_eq!(tag::validate_name("Co-op 2"), Ok(()));
+    assert_eq!(
+        tag::validate_name("  "),
+        Err("Name must be between 1 and 25 characters")
+    );
+    assert_eq!(
+        tag::validate_name(&"a".repeat(26)),
+        Err("Name must be between 1 and 25 characters")
+    );
+    assert_eq!(
+        tag::validate_name("no_underscores"),
+        Err("Name can only contain letters, digits, spaces and hyphens")
+    );
+}
+
+#[test]
+fn test_normalize() {
+    let names = ["English", " english", "", "Speedrun", "SPEEDRUN ", "Chill"]
+        .map(String::from)
+        .to_vec();
+
+    assert_eq!(tag::normalize(&names), vec!["english", "speedrun", "chill"]);
+}
@@ -25,6 +25,34 @@ fn test_hash_password() {
     .verify_password(password));
 }
 
+#[test]
+fn test_password_needs_rehash() {
+    let tests = vec![
+        (user::hash_password("mypassword"), false),
+        // Hashed with a lower memory cost than new passwords are.
+        (
+            "$argon2id$v=19$m=16,t=2,p=1$MTJ0bmNqYzhDaXZsS1BkZw$/PWmjCzvNhg1aeUVaV8Z9w".to_string(),
+            true,
+        ),
+        ("not a hash".to_string(), true),
+    ];
+
+    for (hash, needs_rehash) in tests {
+        let user = user::Model {
+            password_hash: hash.clone(),
+            ..Default::default()
+        };
+
+        assert_eq!(user.password_needs_rehash(), needs_rehash, "{}", hash);
+        assert_eq!(
+            hash.starts_with(&user::password_hash_prefix()),
+            !needs_rehash,
+            "{}",
+            hash
+        );
+    }
+}
+
 #[test]
 fn test_validate_usernames() {
     let tests = vec![
@@ -96,3 +124,109 @@ fn test_validate_email() {
         assert_eq!(user::validate_email(email), result, "email: {}", email);
     }
 }
+
+#[test]
+fn test_validate_locale() {
+    let tests = vec![
+        ("en-US", Ok(())),
+        ("de", Ok(())),
+        ("zh-Hant-TW", Ok(())),
+        ("en_US", Err("Locale is not a valid BCP-47 language tag")),
+        (
+            "xx-YY-zzzz",
+            Err("Locale is not a valid BCP-47 language tag"),
+        ),
+        ("", Err("Locale is not a valid BCP-47 language tag")),
+    ];
+
+    for (locale, result) in tests {
+        assert_eq!(user::validate_locale(locale), result, "locale: {}", locale);
+    }
+}
+
+#[test]
+fn test_validate_stream_language() {
+    let tests = vec![
+        ("en", Ok(())),
+        ("pt-BR", Ok(())),
+        ("", Ok(())),
+        (
+            "english",
+            Err("Language is not a valid BCP-47 language tag"),
+        ),
+    ];
+
+    for (language, result) in tests {
+        assert_eq!(
+            user::validate_stream_language(language),
+            result,
+            "language: {}",
+            language
+        );
+    }
+}
+
+#[test]
+fn test_validate_timezone() {
+    let tests = vec![
+        ("UTC", Ok(())),
+        ("Europe/Berlin", Ok(())),
+        ("America/Argentina/Buenos_Aires", Ok(())),
+        (
+            "Europe/Atlantis",
+            Err("Timezone is not a valid IANA time zone"),
+        ),
+        ("+02:00", Err("Timezone is not a valid IANA time zone")),
+    ];
+
+    for (timezone, result) in tests {
+        assert_eq!(
+            user::validate_timezone(timezone),
+            result,
+            "timezone: {}",
+            timezone
+        );
+    }
+}
+
+#[test]
+fn test_validate_bio() {
+    assert_eq!(user::validate_bio(""), Ok(()));
+    assert_eq!(user::validate_bio(&"ä".repeat(1000)), Ok(()));
+    assert_eq!(
+        user::validate_bio(&"a".repeat(1001)),
+        Err("Bio must be at most 1000 characters long")
+    );
+    assert_eq!(
+        user::validate_bio(&"a\n".repeat(51)),
+        Err("Bio must be at most 50 lines long")
+    );
+}
+
+#[test]
+fn test_sanitize_bio() {
+    let tests = vec![
+        ("**Hello**  \nworld", "**Hello**  \nworld"),
+        (
+            "<script>alert(1)</script>",
+            "&lt;script>alert(1)&lt;/script>",
+        ),
+        ("a\n\n\n\nb\n\n", "a\n\nb"),
+        ("\n\na\u{7}\u{200b}b\r\nc", "a\u{200b}b\nc"),
+        (
+            "[site](https://example.com \"title\") and [mail](MAILTO:me@example.com)",
+            "[site](https://example.com \"title\") and [mail](MAILTO:me@example.com)",
+        ),
+        ("[click](javascript:alert(1))", "[click](#)"),
+        ("[click]( JavaScript:alert(1))", "[click]( #)"),
+        ("[click](javascript&#58;alert(1))", "[click](#)"),
+        ("[relative](/settings)", "[relative](#)"),
+        ("![img](data:image/png;base64,AAAA)", "![img](#)"),
+        ("  [ref]: vbscript:msgbox", "  [ref]: #"),
+        ("[ref]: https://example.com", "[ref]: https://example.com"),
+    ];
+
+    for (bio, sanitized) in tests {
+        assert_eq!(user::sanitize_bio(bio), sanitized, "bio: {:?}", bio);
+    }
+}
@@ -0,0 +1,46 @@
+use crate::database::user_social_link;
+
+#[test]
+fn test_validate_url() {
+    let tests = vec![
+        ("https://twitter.com/scuffle", Ok(())),
+        ("HTTP://example.com", Ok(())),
+        ("mailto:me@example.com", Ok(())),
+        (
+            "example.com",
+            Err("Url must start with a scheme like https://"),
+        ),
+        (
+            "javascript:alert(1)",
+            Err("Url must be a http(s) or mailto link"),
+        ),
+        (
+            "ftp://example.com",
+            Err("Url must be a http(s) or mailto link"),
+        ),
+        ("https://", Err("Url is not a valid link")),
+        ("https:example.com", Err("Url is not a valid link")),
+        ("mailto:nobody", Err("Url is not a valid link")),
+        (
+            "https://example.com/a b",
+            Err("Url must not contain spaces"),
+        ),
+    ];
+
+    for (url, result) in tests {
+        assert_eq!(user_social_link::validate_url(url), result, "url: {}", url);
+    }
+}
+
+#[test]
+fn test_validate_label() {
+    assert_eq!(user_social_link::validate_label("Twitter"), Ok(()));
+    assert_eq!(
+        user_social_link::validate_label("  "),
+        Err("Label must not be empty")
+    );
+    assert_eq!(
+        user_social_link::validate_label(&"a".repeat(33)),
+        Err("Label must be at most 32 characters long")
+    );
+}
@@ -0,0 +1,32 @@
+use chrono::Utc;
+
+use crate::database::user_suspension;
+
+#[test]
+fn test_is_active() {
+    let now = Utc::now();
+
+    let tests = vec![
+        (None, None, true),
+        (Some(now + chrono::Duration::hours(1)), None, true),
+        (Some(now - chrono::Duration::hours(1)), None, false),
+        (None, Some(now), false),
+        (Some(now + chrono::Duration::hours(1)), Some(now), false),
+    ];
+
+    for (expires_at, lifted_at, active) in tests {
+        let suspension = user_suspension::Model {
+            expires_at,
+            lifted_at,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            suspension.is_active(),
+            active,
+            "{:?} {:?}",
+            expires_at,
+            lifted_at
+        );
+    }
+}
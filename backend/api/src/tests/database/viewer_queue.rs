@@ -0,0 +1,32 @@
+use crate::database::viewer_queue::{self, Command};
+
+#[test]
+fn test_command() {
+    assert_eq!(viewer_queue::command("!join"), Some(Command::Join));
+    assert_eq!(viewer_queue::command("  !JOIN "), Some(Command::Join));
+    assert_eq!(viewer_queue::command("!leave"), Some(Command::Leave));
+    assert_eq!(viewer_queue::command("!join now"), None);
+    assert_eq!(viewer_queue::command("join"), None);
+}
+
+#[test]
+fn test_validate() {
+    assert_eq!(viewer_queue::validate("Fall Guys", None), Ok(()));
+    assert_eq!(viewer_queue::validate("Fall Guys", Some(1000)), Ok(()));
+    assert_eq!(
+        viewer_queue::validate(" ", None),
+        Err(("title", "Title must be between 1 and 140 characters"))
+    );
+    assert_eq!(
+        viewer_queue::validate(&"a".repeat(141), None),
+        Err(("title", "Title must be between 1 and 140 characters"))
+    );
+    assert_eq!(
+        viewer_queue::validate("Fall Guys", Some(0)),
+        Err(("maxSize", "Size must be between 1 and 1000"))
+    );
+    assert_eq!(
+        viewer_queue::validate("Fall Guys", Some(1001)),
+        Err(("maxSize", "Size must be between 1 and 1000"))
+    );
+}
@@ -0,0 +1,33 @@
+use crate::database::vod_caption::{snippet, SNIPPET_CONTEXT_CHARS};
+
+#[test]
+fn test_snippet() {
+    assert_eq!(snippet(None, "hello", None), "hello");
+    assert_eq!(
+        snippet(Some("before"), "hello", Some("after")),
+        "before hello after"
+    );
+    assert_eq!(snippet(Some(""), "hello", Some("")), "hello");
+
+    let words = "word ".repeat(SNIPPET_CONTEXT_CHARS);
+    let long = words.trim_end();
+
+    // The lines around the match are cut at a word.
+    let text = snippet(Some(long), "hello", Some(long));
+    let (before, rest) = text.split_once(" hello ").unwrap();
+    assert!(before.starts_with("…word"));
+    assert!(before.chars().count() <= SNIPPET_CONTEXT_CHARS + 1);
+    assert!(rest.ends_with("word…"));
+    assert!(rest.chars().count() <= SNIPPET_CONTEXT_CHARS + 1);
+
+    // A line without a word boundary in reach is left out.
+    let word = "a".repeat(SNIPPET_CONTEXT_CHARS * 2);
+    assert_eq!(snippet(Some(&word), "hello", Some(&word)), "hello");
+
+    // Exactly as long as the context is kept whole.
+    let exact = "b".repeat(SNIPPET_CONTEXT_CHARS);
+    assert_eq!(
+        snippet(Some(&exact), "hello", None),
+        format!("{} hello", exact)
+    );
+}
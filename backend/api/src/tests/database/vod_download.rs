@@ -0,0 +1,72 @@
+use chrono::{Duration, Utc};
+
+use crate::{
+    database::vod_download::{self, Status},
+    pb::scuffle::types::{stream_state, StreamState},
+};
+
+fn state(video_codec: &str) -> StreamState {
+    StreamState {
+        variants: vec![
+            stream_state::Variant {
+                name: "source".to_string(),
+                group: "aac".to_string(),
+                transcode_ids: vec!["video".to_string(), "audio".to_string()],
+            },
+            stream_state::Variant {
+                name: "audio-only".to_string(),
+                group: "aac".to_string(),
+                transcode_ids: vec!["audio".to_string()],
+            },
+        ],
+        transcodes: vec![
+            stream_state::Transcode {
+                id: "video".to_string(),
+                codec: video_codec.to_string(),
+                ..Default::default()
+            },
+            stream_state::Transcode {
+                id: "audio".to_string(),
+                codec: "mp4a.40.2".to_string(),
+                ..Default::default()
+            },
+        ],
+        groups: vec![],
+    }
+}
+
+#[test]
+fn test_remux() {
+    assert_eq!(
+        vod_download::remux(&state("avc1.640028"), "source"),
+        Some(true)
+    );
+    assert_eq!(
+        vod_download::remux(&state("vp09.00.10.08"), "source"),
+        Some(false)
+    );
+    // The audio-only variant does not have the video transcode.
+    assert_eq!(
+        vod_download::remux(&state("vp09.00.10.08"), "audio-only"),
+        Some(true)
+    );
+    assert_eq!(vod_download::remux(&state("avc1.640028"), "720p"), None);
+}
+
+#[test]
+fn test_downloadable() {
+    let now = Utc::now();
+    let mut download = vod_download::Model {
+        status: Status::Completed,
+        expires_at: Some(now + Duration::days(1)),
+        ..Default::default()
+    };
+    assert!(download.downloadable(now));
+
+    download.expires_at = Some(now - Duration::seconds(1));
+    assert!(!download.downloadable(now));
+
+    download.status = Status::Running;
+    download.expires_at = None;
+    assert!(!download.downloadable(now));
+}
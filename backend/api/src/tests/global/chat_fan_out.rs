@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::config::SubscriptionConfig;
+use crate::global::chat_fan_out::{chat_channel, render_metrics};
+use crate::subscription::TopicStats;
+
+fn stats(topic: &str, subscribers: usize, shards: usize) -> TopicStats {
+    TopicStats {
+        topic: topic.to_string(),
+        subscribers,
+        shards,
+        lag: 3,
+        published: 120,
+        dropped: 2,
+        evicted: 1,
+        age: Duration::from_secs(60),
+    }
+}
+
+#[test]
+fn test_chat_channel() {
+    let id = Uuid::new_v4();
+
+    assert_eq!(
+        chat_channel(&format!("user:{}:chat:messages", id)),
+        Some(id)
+    );
+    assert_eq!(chat_channel(&format!("user:{}:chat:blocks", id)), None);
+    assert_eq!(chat_channel("user:nope:chat:messages"), None);
+}
+
+#[test]
+fn test_at_capacity() {
+    let config = SubscriptionConfig {
+        shard_size: 10,
+        max_shards: 2,
+        ..Default::default()
+    };
+
+    assert!(!stats("", 15, 2).at_capacity(&config));
+    assert!(!stats("", 20, 1).at_capacity(&config));
+    assert!(stats("", 20, 2).at_capacity(&config));
+    assert!(stats("", 25, 2).at_capacity(&config));
+}
+
+#[test]
+fn test_render_metrics() {
+    let config = SubscriptionConfig {
+        shard_size: 10,
+        max_shards: 2,
+        metrics_min_subscribers: 10,
+        ..Default::default()
+    };
+
+    let big = Uuid::new_v4();
+    let small = Uuid::new_v4();
+    let metrics = render_metrics(
+        &config,
+        &[(big, stats("", 20, 2)), (small, stats("", 5, 1))],
+    );
+
+    assert!(metrics.contains("scuffle_chat_fan_out_channels 2\n"));
+    assert!(metrics.contains("scuffle_chat_fan_out_subscribers_all 25\n"));
+    assert!(metrics.contains(&format!(
+        "scuffle_chat_fan_out_subscribers{{channel_id=\"{}\"}} 20\n",
+        big
+    )));
+    assert!(metrics.contains(&format!(
+        "scuffle_chat_fan_out_at_capacity{{channel_id=\"{}\"}} 1\n",
+        big
+    )));
+    assert!(metrics.contains(&format!(
+        "scuffle_chat_fan_out_messages_total{{channel_id=\"{}\"}} 120\n",
+        big
+    )));
+    assert!(metrics.contains("# TYPE scuffle_chat_fan_out_dropped_total counter\n"));
+    // Small chats are only in the totals.
+    assert!(!metrics.contains(&small.to_string()));
+}
@@ -0,0 +1,41 @@
+use crate::global::data_export::tar_archive;
+
+#[test]
+fn test_tar_archive() {
+    let archive = tar_archive(
+        &[
+            ("profile.json", b"{}".to_vec()),
+            ("follows.json", vec![b'a'; 512]),
+        ],
+        1693994400,
+    );
+
+    // A header and a padded block for each file, then two empty blocks.
+    assert_eq!(archive.len(), 512 * 6);
+
+    let header = &archive[..512];
+    assert_eq!(&header[..13], b"profile.json\0");
+    assert_eq!(&header[124..136], b"00000000002\0");
+    assert_eq!(&header[136..148], b"14476046640\0");
+    assert_eq!(header[156], b'0');
+    assert_eq!(&header[257..265], b"ustar\x0000");
+
+    let checksum = std::str::from_utf8(&header[148..154]).unwrap();
+    let expected: u32 = header
+        .iter()
+        .enumerate()
+        .map(|(i, b)| match i {
+            148..=155 => b' ' as u32,
+            _ => *b as u32,
+        })
+        .sum();
+    assert_eq!(u32::from_str_radix(checksum, 8).unwrap(), expected);
+
+    assert_eq!(&archive[512..514], b"{}");
+    assert!(archive[514..1024].iter().all(|b| *b == 0));
+
+    // Files filling whole blocks are not padded.
+    assert_eq!(&archive[1024..1037], b"follows.json\0");
+    assert!(archive[1536..2048].iter().all(|b| *b == b'a'));
+    assert!(archive[2048..].iter().all(|b| *b == 0));
+}
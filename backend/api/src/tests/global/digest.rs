@@ -0,0 +1,131 @@
+use chrono::{Duration, TimeZone, Utc};
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::database::{channel_event, notification_settings, user};
+use crate::global::digest::{render_digest, DigestRecipient, DigestStream};
+use crate::tests::global::mock_global_state;
+
+#[test]
+fn test_render_digest() {
+    let recipient = DigestRecipient {
+        user_id: Uuid::nil(),
+        display_name: "Viewer".to_string(),
+        email: String::new(),
+        timezone: "Europe/Berlin".to_string(),
+    };
+
+    let started_at = Utc.with_ymd_and_hms(2023, 9, 4, 16, 0, 0).unwrap();
+    let stream = |title: &str, recorded: bool, minutes: i64| DigestStream {
+        channel_username: "troy".to_string(),
+        channel_display_name: "Troy".to_string(),
+        title: title.to_string(),
+        recorded,
+        started_at,
+        ended_at: started_at + Duration::minutes(minutes),
+    };
+
+    let (subject, text) = render_digest(
+        &recipient,
+        &[stream("Speedruns", true, 125), stream("", false, 30)],
+        "https://scuffle.tv/",
+        "https://api.scuffle.tv/unsubscribe",
+    );
+
+    assert_eq!(subject, "You missed 2 streams of Troy");
+    assert_eq!(
+        text,
+        "Hi Viewer,\n\nHere is what the channels you follow streamed this week.\n\
+        \nTroy streamed \"Speedruns\" on Mon, Sep 4 at 18:00 CEST for 2h 5m.\nThe VOD is on their channel: https://scuffle.tv/troy\n\
+        \nTroy streamed on Mon, Sep 4 at 18:00 CEST for 30m.\nhttps://scuffle.tv/troy\n\
+        \nYou get this email once a week. Unsubscribe: https://api.scuffle.tv/unsubscribe"
+    );
+
+    // Unknown time zones fall back to UTC.
+    let (_, text) = render_digest(
+        &DigestRecipient {
+            timezone: "Nowhere".to_string(),
+            ..recipient
+        },
+        &[stream("Speedruns", true, 125)],
+        "https://scuffle.tv",
+        "",
+    );
+    assert!(text.contains("on Mon, Sep 4 at 16:00 UTC"));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_send_digest() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut users = Vec::new();
+    for username in ["channel", "viewer"] {
+        users.push(sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, email_verified, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4, $5) RETURNING *",
+            username,
+            format!("{}@test.com", username),
+            username == "viewer",
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        ).fetch_one(&*global.db).await.unwrap());
+    }
+    let (channel, viewer) = (&users[0], &users[1]);
+
+    sqlx::query!(
+        "INSERT INTO channel_events (channel_id, user_id, kind) VALUES ($1, $2, $3)",
+        channel.id,
+        viewer.id,
+        i64::from(channel_event::Kind::Follow),
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let now = Utc::now();
+    sqlx::query!(
+        "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id, created_at, ended_at) VALUES ($1, 'Speedruns', '', TRUE, FALSE, '', $2, $3, $4)",
+        channel.id,
+        Uuid::new_v4(),
+        now - Duration::hours(3),
+        now - Duration::hours(1),
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    // Only the viewer has a verified email.
+    let recipients = global.due_digest_recipients(10).await.unwrap();
+    assert_eq!(recipients.len(), 1);
+    assert_eq!(recipients[0].user_id, viewer.id);
+
+    assert!(global.send_digest(&recipients[0]).await.unwrap());
+    assert!(global.due_digest_recipients(10).await.unwrap().is_empty());
+
+    let category = notification_settings::Category::WeeklyDigest;
+    let token = global.unsubscribe_token(viewer.id, category);
+    assert!(global.verify_unsubscribe_token(viewer.id, category, &token));
+    assert!(!global.verify_unsubscribe_token(channel.id, category, &token));
+    assert!(!global.verify_unsubscribe_token(
+        viewer.id,
+        notification_settings::Category::GoLive,
+        &token
+    ));
+
+    // Users who turned digests off are not due anymore, even after a week.
+    sqlx::query!("UPDATE email_digests SET sent_at = NOW() - INTERVAL '8 days'")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    assert_eq!(global.due_digest_recipients(10).await.unwrap().len(), 1);
+
+    notification_settings::set(&global.db, viewer.id, category, false)
+        .await
+        .unwrap();
+    assert!(global.due_digest_recipients(10).await.unwrap().is_empty());
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use crate::global::encryption::{decrypt, encrypt, Keyring};
+
+#[test]
+fn test_encryption_round_trip() {
+    let encrypted = encrypt("secret", b"obs password").unwrap();
+    assert_ne!(encrypted, b"obs password");
+    assert_eq!(decrypt("secret", &encrypted).unwrap(), b"obs password");
+
+    // Every encryption uses a fresh nonce.
+    assert_ne!(encrypt("secret", b"obs password").unwrap(), encrypted);
+
+    assert!(decrypt("other secret", &encrypted).is_err());
+    assert!(decrypt("secret", &encrypted[..8]).is_err());
+}
+
+#[test]
+fn test_keyring_rotation() {
+    let old = Keyring::new(
+        "1".to_string(),
+        HashMap::from([("1".to_string(), "old secret".to_string())]),
+    )
+    .unwrap();
+
+    let encrypted = old.encrypt("troy@scuffle.tv").unwrap();
+    assert!(encrypted.starts_with("enc:1:"));
+    assert_eq!(old.decrypt(&encrypted).unwrap(), "troy@scuffle.tv");
+    assert!(!old.needs_migration(&encrypted));
+
+    let new = Keyring::new(
+        "2".to_string(),
+        HashMap::from([
+            ("1".to_string(), "old secret".to_string()),
+            ("2".to_string(), "new secret".to_string()),
+        ]),
+    )
+    .unwrap();
+
+    // Values encrypted with a previous key can still be read, but should be migrated.
+    assert_eq!(new.decrypt(&encrypted).unwrap(), "troy@scuffle.tv");
+    assert!(new.needs_migration(&encrypted));
+    assert!(new
+        .encrypt("troy@scuffle.tv")
+        .unwrap()
+        .starts_with("enc:2:"));
+
+    // Plaintext from before encryption was enabled is read as it is.
+    assert_eq!(new.decrypt("troy@scuffle.tv").unwrap(), "troy@scuffle.tv");
+    assert!(new.needs_migration("troy@scuffle.tv"));
+
+    assert!(new.decrypt("enc:3:AAAA").is_err());
+    assert!(new.decrypt("enc:2:not base64").is_err());
+}
+
+#[test]
+fn test_keyring_invalid() {
+    let tests = vec![
+        ("1", vec![("2", "secret")]),
+        ("1", vec![("1", "secret"), ("a_b", "secret")]),
+        ("", vec![("", "secret")]),
+    ];
+
+    for (current, keys) in tests {
+        let keys = keys
+            .into_iter()
+            .map(|(id, key)| (id.to_string(), key.to_string()))
+            .collect();
+
+        assert!(
+            Keyring::new(current.to_string(), keys).is_err(),
+            "{}",
+            current
+        );
+    }
+}
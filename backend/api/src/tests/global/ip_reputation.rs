@@ -0,0 +1,100 @@
+use hyper::{HeaderMap, HeaderValue};
+
+use crate::{
+    config::IpReputationConfig,
+    global::ip_reputation::{client_ip, Action, Verdict},
+};
+
+#[test]
+fn test_verdict_action() {
+    let config = IpReputationConfig::default();
+
+    let tests = vec![
+        (
+            Verdict {
+                score: 0,
+                proxy: false,
+            },
+            Action::Allow,
+        ),
+        (
+            Verdict {
+                score: 49,
+                proxy: false,
+            },
+            Action::Allow,
+        ),
+        (
+            Verdict {
+                score: 50,
+                proxy: false,
+            },
+            Action::Captcha,
+        ),
+        (
+            Verdict {
+                score: 89,
+                proxy: false,
+            },
+            Action::Captcha,
+        ),
+        (
+            Verdict {
+                score: 90,
+                proxy: false,
+            },
+            Action::Block,
+        ),
+        (
+            Verdict {
+                score: 0,
+                proxy: true,
+            },
+            Action::Captcha,
+        ),
+        (
+            Verdict {
+                score: 95,
+                proxy: true,
+            },
+            Action::Block,
+        ),
+    ];
+
+    for (verdict, action) in tests {
+        assert_eq!(verdict.action(&config), action, "{:?}", verdict);
+    }
+}
+
+#[test]
+fn test_client_ip() {
+    let remote = "10.0.0.1:1234".parse().unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "x-forwarded-for",
+        HeaderValue::from_static("203.0.113.7, 10.0.0.1"),
+    );
+
+    // The header is only trusted when the API is configured to be behind a proxy.
+    let config = IpReputationConfig::default();
+    assert_eq!(
+        client_ip(&config, &headers, remote),
+        "10.0.0.1".parse::<std::net::IpAddr>().unwrap()
+    );
+
+    let config = IpReputationConfig {
+        client_ip_header: Some("X-Forwarded-For".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        client_ip(&config, &headers, remote),
+        "203.0.113.7".parse::<std::net::IpAddr>().unwrap()
+    );
+
+    headers.insert("x-forwarded-for", HeaderValue::from_static("garbage"));
+    assert_eq!(
+        client_ip(&config, &headers, remote),
+        "10.0.0.1".parse::<std::net::IpAddr>().unwrap()
+    );
+}
@@ -10,6 +10,17 @@ use common::{
 use fred::types::ServerConfig;
 use tokio::select;
 
+pub mod chat_fan_out;
+pub mod data_export;
+pub mod digest;
+pub mod encryption;
+pub mod ip_reputation;
+pub mod oauth;
+pub mod passkey;
+pub mod reconciliation;
+pub mod residency;
+pub mod sandbox;
+pub mod storage;
 pub mod turnstile;
 
 pub async fn mock_global_state(mut config: AppConfig) -> (Arc<GlobalState>, Handler) {
@@ -0,0 +1,89 @@
+use hyper::server::conn::Http;
+use serde_json::json;
+use tokio::net::TcpListener;
+
+use crate::config::OAuthProviderConfig;
+use crate::database::external_account::Provider;
+use crate::global::oauth::{parse_identity, ExternalIdentity};
+
+/// A provider which accepts the authorization code `good` for the account `1234` named `troy`.
+pub async fn mock_oauth_provider() -> (OAuthProviderConfig, tokio::task::JoinHandle<()>) {
+    // Bind to a random port
+    let listener = TcpListener::bind("0.0.0.0:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(Http::new().serve_connection(
+                socket,
+                hyper::service::service_fn(|req| async move {
+                    let path = req.uri().path().to_string();
+                    let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+
+                    let (status, body) = match path.as_str() {
+                        "/token" if String::from_utf8_lossy(&body).contains("code=good") => {
+                            (200, json!({ "access_token": "access" }))
+                        }
+                        "/token" => (400, json!({ "error": "invalid_grant" })),
+                        _ => (200, json!({ "id": "1234", "username": "troy" })),
+                    };
+
+                    Ok::<_, hyper::Error>(
+                        hyper::Response::builder()
+                            .status(status)
+                            .body(hyper::Body::from(body.to_string()))
+                            .unwrap(),
+                    )
+                }),
+            ));
+        }
+    });
+
+    (
+        OAuthProviderConfig {
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            token_url: format!("{}/token", addr),
+            user_url: format!("{}/user", addr),
+        },
+        handle,
+    )
+}
+
+#[test]
+fn test_parse_identity() {
+    assert_eq!(
+        parse_identity(
+            Provider::Google,
+            &json!({ "sub": "1089", "name": "Troy", "email": "troy@scuffle.tv" })
+        )
+        .unwrap(),
+        ExternalIdentity {
+            id: "1089".to_string(),
+            username: "Troy".to_string(),
+        }
+    );
+
+    assert_eq!(
+        parse_identity(
+            Provider::Github,
+            &json!({ "id": 583231, "login": "octocat" })
+        )
+        .unwrap(),
+        ExternalIdentity {
+            id: "583231".to_string(),
+            username: "octocat".to_string(),
+        }
+    );
+
+    // The name is only informational, the id is what accounts are linked by.
+    assert_eq!(
+        parse_identity(Provider::Discord, &json!({ "id": "80351110224678912" }))
+            .unwrap()
+            .username,
+        ""
+    );
+    assert!(parse_identity(Provider::Discord, &json!({ "id": 80351110224678912u64 })).is_err());
+    assert!(parse_identity(Provider::Google, &json!({ "sub": "" })).is_err());
+}
@@ -0,0 +1,215 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
+use ring::{
+    rand::SystemRandom,
+    signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING},
+};
+use sha2::{Digest, Sha256};
+
+use crate::config::PasskeyConfig;
+use crate::database::passkey;
+use crate::global::passkey::{
+    verify_assertion, verify_attestation, Assertion, Attestation, PasskeyError, ES256,
+};
+
+const CHALLENGE: &str = "c2N1ZmZsZS1wYXNza2V5LXRlc3QtY2hhbGxlbmdl";
+const CREDENTIAL_ID: &[u8] = b"test credential";
+
+fn config() -> PasskeyConfig {
+    PasskeyConfig {
+        rp_id: "scuffle.test".to_string(),
+        origins: vec!["https://scuffle.test".to_string()],
+        ..Default::default()
+    }
+}
+
+fn key_pair() -> EcdsaKeyPair {
+    let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &SystemRandom::new())
+        .unwrap();
+    EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref()).unwrap()
+}
+
+fn client_data(kind: &str, origin: &str) -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "type": kind,
+        "challenge": CHALLENGE,
+        "origin": origin,
+    }))
+    .unwrap()
+}
+
+fn authenticator_data(flags: u8, sign_count: u32, credential: bool) -> Vec<u8> {
+    let mut data = Sha256::digest(b"scuffle.test").to_vec();
+    data.push(flags);
+    data.extend_from_slice(&sign_count.to_be_bytes());
+
+    if credential {
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(&(CREDENTIAL_ID.len() as u16).to_be_bytes());
+        data.extend_from_slice(CREDENTIAL_ID);
+        // The COSE key is not read, the public key is passed as a SubjectPublicKeyInfo.
+        data.extend_from_slice(&[0xa5]);
+    }
+
+    data
+}
+
+fn attestation(key_pair: &EcdsaKeyPair, origin: &str) -> Attestation {
+    let mut spki = hex_decode("3059301306072a8648ce3d020106082a8648ce3d030107034200");
+    spki.extend_from_slice(key_pair.public_key().as_ref());
+
+    Attestation {
+        credential_id: BASE64_URL.encode(CREDENTIAL_ID),
+        client_data_json: BASE64_URL.encode(client_data("webauthn.create", origin)),
+        authenticator_data: BASE64_URL.encode(authenticator_data(0x45, 1, true)),
+        public_key: BASE64_URL.encode(spki),
+        public_key_algorithm: ES256,
+    }
+}
+
+fn assertion(key_pair: &EcdsaKeyPair, flags: u8, sign_count: u32) -> Assertion {
+    let client_data = client_data("webauthn.get", "https://scuffle.test");
+    let authenticator_data = authenticator_data(flags, sign_count, false);
+
+    let mut message = authenticator_data.clone();
+    message.extend_from_slice(&Sha256::digest(&client_data));
+    let signature = key_pair.sign(&SystemRandom::new(), &message).unwrap();
+
+    Assertion {
+        credential_id: BASE64_URL.encode(CREDENTIAL_ID),
+        client_data_json: BASE64_URL.encode(client_data),
+        authenticator_data: BASE64_URL.encode(authenticator_data),
+        signature: BASE64_URL.encode(signature.as_ref()),
+    }
+}
+
+fn hex_decode(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn invalid<T: std::fmt::Debug>(result: Result<T, PasskeyError>) -> &'static str {
+    match result {
+        Err(PasskeyError::Invalid(message)) => message,
+        other => panic!("expected an invalid passkey, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_verify_attestation() {
+    let key_pair = key_pair();
+
+    let verified = verify_attestation(
+        &config(),
+        CHALLENGE,
+        &attestation(&key_pair, "https://scuffle.test"),
+    )
+    .unwrap();
+    assert_eq!(verified.credential_id, BASE64_URL.encode(CREDENTIAL_ID));
+    assert_eq!(verified.public_key, key_pair.public_key().as_ref());
+    assert_eq!(verified.sign_count, 1);
+
+    assert_eq!(
+        invalid(verify_attestation(
+            &config(),
+            CHALLENGE,
+            &attestation(&key_pair, "https://evil.test")
+        )),
+        "Passkey was used on an unknown origin"
+    );
+    assert_eq!(
+        invalid(verify_attestation(
+            &config(),
+            "b3RoZXI",
+            &attestation(&key_pair, "https://scuffle.test")
+        )),
+        "Passkey response is for a different challenge"
+    );
+
+    let mut rs256 = attestation(&key_pair, "https://scuffle.test");
+    rs256.public_key_algorithm = -257;
+    assert_eq!(
+        invalid(verify_attestation(&config(), CHALLENGE, &rs256)),
+        "Only ES256 passkeys are supported"
+    );
+
+    let mut other_credential = attestation(&key_pair, "https://scuffle.test");
+    other_credential.credential_id = BASE64_URL.encode(b"other");
+    assert_eq!(
+        invalid(verify_attestation(&config(), CHALLENGE, &other_credential)),
+        "Authenticator data is for a different credential"
+    );
+}
+
+#[test]
+fn test_verify_assertion() {
+    let key_pair = key_pair();
+    let passkey = passkey::Model {
+        public_key: key_pair.public_key().as_ref().to_vec(),
+        sign_count: 1,
+        ..Default::default()
+    };
+
+    // User present and verified.
+    assert_eq!(
+        verify_assertion(
+            &config(),
+            CHALLENGE,
+            &passkey,
+            &assertion(&key_pair, 0x05, 2),
+            true
+        )
+        .unwrap(),
+        2
+    );
+
+    // Only present is enough for a second factor, not for a passwordless login.
+    let present = assertion(&key_pair, 0x01, 2);
+    assert!(verify_assertion(&config(), CHALLENGE, &passkey, &present, false).is_ok());
+    assert_eq!(
+        invalid(verify_assertion(
+            &config(),
+            CHALLENGE,
+            &passkey,
+            &present,
+            true
+        )),
+        "The passkey did not verify the user"
+    );
+
+    assert_eq!(
+        invalid(verify_assertion(
+            &config(),
+            CHALLENGE,
+            &passkey,
+            &assertion(&key_pair, 0x05, 1),
+            true
+        )),
+        "Passkey signature counter went backwards"
+    );
+
+    // Authenticators which don't count report 0 every time.
+    assert_eq!(
+        verify_assertion(
+            &config(),
+            CHALLENGE,
+            &passkey,
+            &assertion(&key_pair, 0x05, 0),
+            true
+        )
+        .unwrap(),
+        0
+    );
+
+    assert_eq!(
+        invalid(verify_assertion(
+            &config(),
+            CHALLENGE,
+            &passkey,
+            &assertion(&self::key_pair(), 0x05, 2),
+            true
+        )),
+        "Passkey signature is not valid"
+    );
+}
@@ -0,0 +1,130 @@
+use chrono::{Duration, Utc};
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::database::{channel_event, stream_session, user};
+use crate::global::reconciliation::Reconciliation;
+use crate::tests::global::mock_global_state;
+
+#[tokio::test]
+#[serial]
+async fn test_serial_reconcile_stream_sessions() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let mut users = Vec::new();
+    for username in ["channel", "viewer1", "viewer2"] {
+        users.push(sqlx::query_as!(user::Model,
+            "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+            username,
+            format!("{}@test.com", username),
+            user::hash_password("test"),
+            user::generate_stream_key(),
+        ).fetch_one(&*global.db).await.unwrap());
+    }
+    let channel = &users[0];
+
+    let now = Utc::now();
+
+    // One stream ended without a summary, the other has one with counts which drifted.
+    let mut streams = Vec::new();
+    for hours in [6, 3] {
+        streams.push(sqlx::query_scalar!(
+            "INSERT INTO streams (channel_id, title, description, recorded, transcoded, ingest_address, connection_id, created_at, ended_at) VALUES ($1, '', '', FALSE, FALSE, '', $2, $3, $4) RETURNING id",
+            channel.id,
+            Uuid::new_v4(),
+            now - Duration::hours(hours),
+            now - Duration::hours(hours - 1),
+        ).fetch_one(&*global.db).await.unwrap());
+    }
+
+    sqlx::query!(
+        "INSERT INTO stream_sessions (channel_id, stream_id, unique_chatters, new_follows, started_at, ended_at) VALUES ($1, $2, 5, 5, $3, $4)",
+        channel.id,
+        streams[1],
+        now - Duration::hours(3),
+        now - Duration::hours(2),
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    for (hours, viewer) in [(6, &users[1]), (6, &users[2]), (3, &users[1])] {
+        let at = now - Duration::hours(hours) + Duration::minutes(10);
+
+        for _ in 0..2 {
+            sqlx::query!(
+                "INSERT INTO chat_messages (channel_id, author_id, content, created_at) VALUES ($1, $2, 'hi', $3)",
+                channel.id,
+                viewer.id,
+                at,
+            )
+            .execute(&*global.db)
+            .await
+            .unwrap();
+        }
+
+        sqlx::query!(
+            "INSERT INTO channel_events (channel_id, user_id, kind, created_at) VALUES ($1, $2, $3, $4)",
+            channel.id,
+            viewer.id,
+            i64::from(channel_event::Kind::Follow),
+            at,
+        )
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    }
+
+    let since = now - Duration::days(1);
+
+    let result = global.reconcile_stream_sessions(since).await.unwrap();
+    assert_eq!(
+        result,
+        Reconciliation {
+            backfilled_sessions: 1,
+            corrected_sessions: 1,
+        }
+    );
+
+    let sessions = sqlx::query_as!(
+        stream_session::Model,
+        "SELECT * FROM stream_sessions ORDER BY started_at"
+    )
+    .fetch_all(&*global.db)
+    .await
+    .unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0].stream_id, streams[0]);
+    assert_eq!(sessions[0].unique_chatters, 2);
+    assert_eq!(sessions[0].new_follows, 2);
+    assert_eq!(sessions[1].unique_chatters, 1);
+    assert_eq!(sessions[1].new_follows, 1);
+
+    // Nothing is left to repair.
+    let result = global.reconcile_stream_sessions(since).await.unwrap();
+    assert_eq!(result, Reconciliation::default());
+
+    // The chatters of a stream whose chat was not kept are left alone.
+    sqlx::query!(
+        "UPDATE streams SET chat_archived = FALSE WHERE id = $1",
+        streams[1]
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "UPDATE stream_sessions SET unique_chatters = 7 WHERE stream_id = $1",
+        streams[1]
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let result = global.reconcile_stream_sessions(since).await.unwrap();
+    assert_eq!(result, Reconciliation::default());
+}
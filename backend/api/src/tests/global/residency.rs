@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use serial_test::serial;
+
+use crate::config::{AppConfig, RegionConfig, ResidencyConfig, StorageConfig};
+use crate::tests::global::mock_global_state;
+
+fn region(bucket: &str, edge_url: &str) -> RegionConfig {
+    RegionConfig {
+        storage: StorageConfig {
+            bucket: bucket.to_string(),
+            ..Default::default()
+        },
+        edge_url: edge_url.to_string(),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_data_region() {
+    let (global, _handler) = mock_global_state(AppConfig {
+        residency: ResidencyConfig {
+            default_region: "eu".to_string(),
+            regions: HashMap::from([
+                (
+                    "eu".to_string(),
+                    region("exports-eu", "https://eu.edge.test"),
+                ),
+                (
+                    "us".to_string(),
+                    region("exports-us", "https://us.edge.test"),
+                ),
+            ]),
+        },
+        ..Default::default()
+    })
+    .await;
+
+    assert_eq!(global.data_regions(), vec!["eu", "us"]);
+
+    assert_eq!(global.data_region("us"), "us");
+    assert_eq!(global.data_region(""), "eu");
+    assert_eq!(global.data_region("moon"), "eu");
+
+    assert_eq!(global.region_storage("us").bucket, "exports-us");
+    assert_eq!(global.region_storage("").bucket, "exports-eu");
+    assert_eq!(global.region_edge_url("us"), "https://us.edge.test");
+    assert_eq!(global.region_edge_url("moon"), "https://eu.edge.test");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_data_region_unconfigured() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    // Without regions everything stays in the global storage and the default edge.
+    assert!(global.data_regions().is_empty());
+    assert_eq!(global.data_region("eu"), "");
+    assert_eq!(global.region_storage("eu"), &global.config.storage);
+    assert_eq!(global.region_edge_url("eu"), "");
+}
@@ -0,0 +1,48 @@
+use serial_test::serial;
+
+use crate::database::{global_role::Permission, user};
+use crate::global::sandbox::SANDBOX_PASSWORD;
+use crate::tests::global::mock_global_state;
+
+#[tokio::test]
+#[serial]
+async fn test_serial_seed_sandbox() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM global_roles")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    // Seeding again changes nothing.
+    global.seed_sandbox().await.unwrap();
+    global.seed_sandbox().await.unwrap();
+
+    let users = sqlx::query_as!(user::Model, "SELECT * FROM users ORDER BY id")
+        .fetch_all(&*global.db)
+        .await
+        .unwrap();
+    assert_eq!(users.len(), 5);
+    assert_eq!(users[0].username, "sandbox");
+    assert!(users[0].verify_password(SANDBOX_PASSWORD));
+    assert_eq!(users[0].get_stream_key(), global.sandbox_stream_key());
+
+    let follows = sqlx::query_scalar!("SELECT COUNT(*) FROM channel_events WHERE kind = 0")
+        .fetch_one(&*global.db)
+        .await
+        .unwrap();
+    assert_eq!(follows, Some(10));
+
+    let permissions = global
+        .user_permisions_by_id_loader
+        .load_one(users[0].id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(permissions.permissions.has_permission(Permission::GoLive));
+    assert_eq!(permissions.roles.len(), 1);
+}
@@ -0,0 +1,129 @@
+use crate::config::{AppConfig, GrpcConfig};
+use crate::database::global_role::Permission;
+use crate::grpc::run;
+use crate::pb::scuffle::backend::{
+    admin_client::AdminClient, CreateUserRequest, GrantRoleRequest, ListLiveStreamsRequest,
+    ResetStreamKeyRequest,
+};
+use crate::tests::global::mock_global_state;
+use common::grpc::make_channel;
+use common::prelude::FutureTimeout;
+use serial_test::serial;
+use std::time::Duration;
+
+#[serial]
+#[tokio::test]
+async fn test_serial_grpc_admin() {
+    let port = portpicker::pick_unused_port().expect("failed to pick port");
+
+    let (global, handler) = mock_global_state(AppConfig {
+        grpc: GrpcConfig {
+            bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
+            admin: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    let db = global.db.clone();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM global_roles")
+        .execute(&*db)
+        .await
+        .unwrap();
+
+    sqlx::query!(
+        "INSERT INTO global_roles(name, description, rank, allowed_permissions, denied_permissions) VALUES ($1, $2, $3, $4, $5)",
+        "Go Live",
+        "Allows a user to go live",
+        0,
+        Permission::GoLive.bits(),
+        0,
+    ).execute(&*db).await.unwrap();
+
+    let handle = tokio::spawn(run(global));
+
+    let channel = make_channel(
+        vec![format!("localhost:{}", port)],
+        Duration::from_secs(0),
+        None,
+    )
+    .unwrap();
+
+    let mut client = AdminClient::new(channel);
+
+    let created = client
+        .create_user(CreateUserRequest {
+            username: "Admin".to_string(),
+            email: "admin@test.com".to_string(),
+            password: "Password123!".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let err = client
+        .create_user(CreateUserRequest {
+            username: "admin".to_string(),
+            email: "admin2@test.com".to_string(),
+            password: "Password123!".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::AlreadyExists);
+
+    let reset = client
+        .reset_stream_key(ResetStreamKeyRequest {
+            username: "admin".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_ne!(reset.stream_key, created.stream_key);
+
+    let grant = |username: &str, role: &str| GrantRoleRequest {
+        username: username.to_string(),
+        role: role.to_string(),
+    };
+
+    let resp = client.grant_role(grant("admin", "Go Live")).await.unwrap();
+    assert!(resp.into_inner().granted);
+    let resp = client.grant_role(grant("admin", "Go Live")).await.unwrap();
+    assert!(!resp.into_inner().granted);
+
+    let err = client
+        .grant_role(grant("admin", "Missing"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+    let err = client
+        .grant_role(grant("missing", "Go Live"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+
+    let resp = client
+        .list_live_streams(ListLiveStreamsRequest {
+            username: Some("admin".to_string()),
+        })
+        .await
+        .unwrap();
+    assert!(resp.into_inner().streams.is_empty());
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+
+    handle
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel grpc")
+        .expect("grpc failed")
+        .expect("grpc failed");
+}
@@ -1,5 +1,5 @@
 use crate::config::{AppConfig, GrpcConfig};
-use crate::database::{global_role::Permission, user};
+use crate::database::{global_role::Permission, stream_key, user};
 use crate::database::{stream, stream_bitrate_update, stream_event};
 use crate::grpc::run;
 use crate::pb;
@@ -177,6 +177,262 @@ async fn test_serial_grpc_authenticate_valid_stream_key() {
         .expect("grpc failed");
 }
 
+#[serial]
+#[tokio::test]
+async fn test_serial_grpc_authenticate_schedule_prefill() {
+    let port = portpicker::pick_unused_port().expect("failed to pick port");
+
+    let (global, handler) = mock_global_state(AppConfig {
+        grpc: GrpcConfig {
+            bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    let db = global.db.clone();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM global_roles")
+        .execute(&*db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM global_role_grants")
+        .execute(&*db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users (username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    ).fetch_one(&*db).await.unwrap();
+
+    let go_live_role_id = sqlx::query!(
+        "INSERT INTO global_roles(name, description, rank, allowed_permissions, denied_permissions, created_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+        "Go Live",
+        "Allows a user to go live",
+        0,
+        Permission::GoLive.bits(),
+        0,
+        chrono::Utc::now(),
+    ).map(|r| r.id).fetch_one(&*db).await.unwrap();
+
+    sqlx::query!(
+        "INSERT INTO global_role_grants (user_id, global_role_id) VALUES ($1, $2)",
+        user.id,
+        go_live_role_id
+    )
+    .execute(&*db)
+    .await
+    .unwrap();
+
+    sqlx::query!(
+        "INSERT INTO channel_schedule_segments (channel_id, title, category, recurring, starts_at, ends_at) VALUES ($1, $2, $3, $4, $5, $6)",
+        user.id,
+        "Planned stream",
+        "Speedruns",
+        false,
+        Utc::now() + chrono::Duration::minutes(5),
+        Utc::now() + chrono::Duration::hours(2),
+    )
+    .execute(&*db)
+    .await
+    .unwrap();
+
+    let handle = tokio::spawn(run(global));
+
+    let channel = make_channel(
+        vec![format!("localhost:{}", port)],
+        Duration::from_secs(0),
+        None,
+    )
+    .unwrap();
+
+    let mut client = pb::scuffle::backend::api_client::ApiClient::new(channel);
+    let resp = client
+        .authenticate_live_stream(pb::scuffle::backend::AuthenticateLiveStreamRequest {
+            app_name: "test".to_string(),
+            stream_key: user.get_stream_key(),
+            ip_address: "127.0.0.1".to_string(),
+            ingest_address: "127.0.0.1:1234".to_string(),
+            connection_id: Uuid::new_v4().to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    // Going live shortly before the planned stream takes its title and category.
+    let stream = sqlx::query_as!(
+        stream::Model,
+        "SELECT * FROM streams WHERE id = $1",
+        resp.stream_id.parse::<Uuid>().unwrap()
+    )
+    .fetch_one(&*db)
+    .await
+    .unwrap();
+    assert_eq!(stream.title, "Planned stream");
+
+    let channel = sqlx::query_as!(user::Model, "SELECT * FROM users WHERE id = $1", user.id)
+        .fetch_one(&*db)
+        .await
+        .unwrap();
+    assert_eq!(channel.stream_title, "Planned stream");
+    assert_eq!(channel.stream_category, "Speedruns");
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+
+    handle
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel grpc")
+        .expect("grpc failed")
+        .expect("grpc failed");
+}
+
+#[serial]
+#[tokio::test]
+async fn test_serial_grpc_authenticate_named_stream_key() {
+    let port = portpicker::pick_unused_port().expect("failed to pick port");
+
+    let (global, handler) = mock_global_state(AppConfig {
+        grpc: GrpcConfig {
+            bind_address: format!("0.0.0.0:{}", port).parse().unwrap(),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    let db = global.db.clone();
+    sqlx::query!("DELETE FROM users")
+        .execute(&*db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM global_roles")
+        .execute(&*db)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM global_role_grants")
+        .execute(&*db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users (username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    ).fetch_one(&*db).await.unwrap();
+
+    let go_live_role_id = sqlx::query!(
+        "INSERT INTO global_roles(name, description, rank, allowed_permissions, denied_permissions, created_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+        "Go Live",
+        "Allows a user to go live",
+        0,
+        Permission::GoLive.bits(),
+        0,
+        chrono::Utc::now(),
+    ).map(|r| r.id).fetch_one(&*db).await.unwrap();
+
+    sqlx::query!(
+        "INSERT INTO global_role_grants (user_id, global_role_id) VALUES ($1, $2)",
+        user.id,
+        go_live_role_id
+    )
+    .execute(&*db)
+    .await
+    .unwrap();
+
+    let named = sqlx::query_as!(
+        stream_key::Model,
+        "INSERT INTO stream_keys (channel_id, name, key) VALUES ($1, $2, $3) RETURNING *",
+        user.id,
+        "Backup encoder",
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*db)
+    .await
+    .unwrap();
+
+    let retired = user::generate_stream_key();
+    let expired = user::generate_stream_key();
+    sqlx::query!(
+        "INSERT INTO retired_stream_keys (channel_id, key, expires_at) VALUES ($1, $2, $3), ($1, $4, $5)",
+        user.id,
+        retired,
+        Utc::now() + chrono::Duration::minutes(5),
+        expired,
+        Utc::now() - chrono::Duration::minutes(5),
+    )
+    .execute(&*db)
+    .await
+    .unwrap();
+
+    let handle = tokio::spawn(run(global));
+
+    let channel = make_channel(
+        vec![format!("localhost:{}", port)],
+        Duration::from_secs(0),
+        None,
+    )
+    .unwrap();
+
+    let client = pb::scuffle::backend::api_client::ApiClient::new(channel);
+    let authenticate = |key: &str| {
+        let mut client = client.clone();
+        let request = pb::scuffle::backend::AuthenticateLiveStreamRequest {
+            app_name: "test".to_string(),
+            stream_key: stream_key::full_key(user.id, key),
+            ip_address: "127.0.0.1".to_string(),
+            ingest_address: "127.0.0.1:1234".to_string(),
+            connection_id: Uuid::new_v4().to_string(),
+        };
+        async move { client.authenticate_live_stream(request).await }
+    };
+
+    authenticate(&named.key).await.unwrap();
+
+    let last_used_at = sqlx::query_scalar!(
+        "SELECT last_used_at FROM stream_keys WHERE id = $1",
+        named.id
+    )
+    .fetch_one(&*db)
+    .await
+    .unwrap();
+    assert!(last_used_at.is_some());
+
+    // A key regenerated a moment ago still works, so an encoder can reconnect during a rotation.
+    authenticate(&retired).await.unwrap();
+
+    let resp = authenticate(&expired).await.unwrap_err();
+    assert_eq!(resp.code(), tonic::Code::InvalidArgument);
+    assert_eq!(resp.message(), "invalid stream key: incorrect stream key");
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+
+    handle
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel grpc")
+        .expect("grpc failed")
+        .expect("grpc failed");
+}
+
 #[serial]
 #[tokio::test]
 async fn test_serial_grpc_authenticate_valid_stream_key_ext() {
@@ -497,6 +753,16 @@ async fn test_serial_grpc_update_live_stream_state() {
         assert_eq!(s.ready_state, stream::ReadyState::Failed);
         assert_eq!(s.updated_at.unwrap().timestamp() as u64, timestamp);
         assert_eq!(s.ended_at.timestamp() as u64, timestamp);
+
+        // The ended stream is summarized in the channel's session history.
+        let ended_at = sqlx::query_scalar!(
+            "SELECT ended_at FROM stream_sessions WHERE stream_id = $1",
+            s.id,
+        )
+        .fetch_one(&*db)
+        .await
+        .unwrap();
+        assert_eq!(ended_at, s.ended_at);
     }
 
     for i in 0..2 {
@@ -24,6 +24,7 @@ async fn test_grpc_tls_rsa() {
                 key: dir.join("server.rsa.key").to_str().unwrap().to_string(),
                 domain: Some("localhost".to_string()),
             }),
+            ..Default::default()
         },
         ..Default::default()
     })
@@ -95,6 +96,7 @@ async fn test_grpc_tls_ec() {
                 key: dir.join("server.ec.key").to_str().unwrap().to_string(),
                 domain: Some("localhost".to_string()),
             }),
+            ..Default::default()
         },
         ..Default::default()
     })
@@ -0,0 +1,31 @@
+use crate::integrations::discord::render_template;
+
+#[test]
+fn test_render_template() {
+    let variables = [
+        ("channel", "Troy"),
+        ("title", "Ranked {category} grind"),
+        ("category", "Valorant"),
+    ];
+
+    let tests = vec![
+        (
+            "{channel} is live: {title}",
+            "Troy is live: Ranked {category} grind",
+        ),
+        ("{channel} plays {category}", "Troy plays Valorant"),
+        ("{unknown} stays", "{unknown} stays"),
+        ("unclosed {channel", "unclosed {channel"),
+        ("{{channel}}", "{Troy}"),
+        ("no variables", "no variables"),
+    ];
+
+    for (template, expected) in tests {
+        assert_eq!(
+            render_template(template, &variables),
+            expected,
+            "{}",
+            template
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    database::{channel_appearance, user},
+    integrations::image_processor::{finish_banner, finish_profile_picture},
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_finish_profile_picture() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let job_id = Uuid::new_v4();
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key, profile_image_url, profile_image_job_id, pending_profile_image_url) VALUES ($1, $1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+        "https://cdn.test/old.webp",
+        job_id,
+        "https://cdn.test/new.webp",
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let fetch = || async {
+        sqlx::query_as!(user::Model, "SELECT * FROM users WHERE id = $1", user.id)
+            .fetch_one(&*global.db)
+            .await
+            .unwrap()
+    };
+
+    // The result of an upload which was replaced changes nothing.
+    finish_profile_picture(&global, Uuid::new_v4(), None)
+        .await
+        .unwrap();
+    let current = fetch().await;
+    assert_eq!(current.profile_image_url, "https://cdn.test/old.webp");
+    assert_eq!(current.profile_image_job_id, Some(job_id));
+
+    finish_profile_picture(&global, job_id, None).await.unwrap();
+    let current = fetch().await;
+    assert_eq!(current.profile_image_url, "https://cdn.test/new.webp");
+    assert_eq!(current.profile_image_job_id, None);
+    assert_eq!(current.pending_profile_image_url, None);
+
+    // A failed upload keeps the current picture.
+    let job_id = Uuid::new_v4();
+    sqlx::query!(
+        "UPDATE users SET profile_image_job_id = $2, pending_profile_image_url = $3 WHERE id = $1",
+        user.id,
+        job_id,
+        "https://cdn.test/broken.webp",
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    finish_profile_picture(&global, job_id, Some("unsupported format".to_string()))
+        .await
+        .unwrap();
+    let current = fetch().await;
+    assert_eq!(current.profile_image_url, "https://cdn.test/new.webp");
+    assert_eq!(current.profile_image_job_id, None);
+    assert_eq!(current.pending_profile_image_url, None);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_serial_finish_banner() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let (old_id, new_id, job_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    sqlx::query!(
+        "INSERT INTO channel_appearances (channel_id, banner_id, banner_job_id, pending_banner_id) VALUES ($1, $2, $3, $4)",
+        user.id,
+        old_id,
+        job_id,
+        new_id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    let fetch = || async {
+        channel_appearance::for_channel(&global.db, user.id)
+            .await
+            .unwrap()
+    };
+
+    // A failed upload keeps the current banner.
+    finish_banner(&global, job_id, Some("image too small".to_string()))
+        .await
+        .unwrap();
+    let current = fetch().await;
+    assert_eq!(current.banner_id, Some(old_id));
+    assert_eq!(current.banner_job_id, None);
+    assert_eq!(current.pending_banner_id, None);
+
+    let job_id = Uuid::new_v4();
+    sqlx::query!(
+        "UPDATE channel_appearances SET banner_job_id = $2, pending_banner_id = $3 WHERE channel_id = $1",
+        user.id,
+        job_id,
+        new_id,
+    )
+    .execute(&*global.db)
+    .await
+    .unwrap();
+
+    // The result of a profile picture job changes nothing.
+    finish_banner(&global, Uuid::new_v4(), None).await.unwrap();
+    assert_eq!(fetch().await.banner_id, Some(old_id));
+
+    finish_banner(&global, job_id, None).await.unwrap();
+    let current = fetch().await;
+    assert_eq!(current.banner_id, Some(new_id));
+    assert_eq!(current.banner_job_id, None);
+    assert_eq!(current.pending_banner_id, None);
+}
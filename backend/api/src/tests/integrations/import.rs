@@ -0,0 +1,144 @@
+use chrono::{TimeZone, Utc};
+use serde_json::json;
+
+use crate::integrations::import::{
+    twitch, youtube, ImportedChannel, ImportedEmote, ImportedPanel, ImportedSegment,
+};
+
+#[test]
+fn test_twitch_imported_channel() {
+    let user = serde_json::from_value(json!({
+        "id": "141981764",
+        "login": "twitchdev",
+        "display_name": "TwitchDev",
+        "description": "Supporting third-party developers building Twitch integrations.",
+        "profile_image_url": "https://static-cdn.jtvnw.net/jtv_user_pictures/profile_image-300x300.png",
+    }))
+    .unwrap();
+
+    let segments = serde_json::from_value(json!([
+        {
+            "id": "eyJzZWdtZW50SUQiOiJlNGFjYzcyNC0zNzFmLTQwMmMtODFjYS0yM2FkYTc5NzU5ZDQiLCJpc29ZZWFyIjoyMDIxLCJpc29XZWVrIjoyNn0=",
+            "start_time": "2021-07-01T18:00:00Z",
+            "end_time": "2021-07-01T19:00:00Z",
+            "title": "TwitchDev Monthly Update",
+            "canceled_until": null,
+            "category": { "id": "509670", "name": "Science & Technology" },
+            "is_recurring": true,
+        },
+        {
+            "id": "eyJzZWdtZW50SUQiOiI4ZjE2MzdlZC0yYTA0LTQxYmQtODZjMC0yNmQ0N2M1NjQyNWYiLCJpc29ZZWFyIjoyMDIxLCJpc29XZWVrIjoyNn0=",
+            "start_time": "2021-07-02T18:00:00Z",
+            "end_time": null,
+            "title": "",
+            "canceled_until": null,
+            "category": null,
+            "is_recurring": false,
+        },
+    ]))
+    .unwrap();
+
+    let emotes = serde_json::from_value(json!([
+        {
+            "id": "304456832",
+            "name": "twitchdevPitchfork",
+            "images": {
+                "url_1x": "https://static-cdn.jtvnw.net/emoticons/v2/304456832/static/light/1.0",
+                "url_2x": "https://static-cdn.jtvnw.net/emoticons/v2/304456832/static/light/2.0",
+                "url_4x": "https://static-cdn.jtvnw.net/emoticons/v2/304456832/static/light/3.0",
+            },
+            "tier": "1000",
+            "emote_type": "subscriptions",
+            "format": ["static"],
+        },
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        twitch::imported_channel(user, segments, emotes),
+        ImportedChannel {
+            profile_image_url: Some(
+                "https://static-cdn.jtvnw.net/jtv_user_pictures/profile_image-300x300.png"
+                    .to_string()
+            ),
+            panels: vec![ImportedPanel {
+                title: "About".to_string(),
+                description: "Supporting third-party developers building Twitch integrations."
+                    .to_string(),
+                image_url: None,
+                link_url: None,
+            }],
+            schedule: vec![
+                ImportedSegment {
+                    title: "TwitchDev Monthly Update".to_string(),
+                    category: "Science & Technology".to_string(),
+                    recurring: true,
+                    starts_at: Utc.with_ymd_and_hms(2021, 7, 1, 18, 0, 0).unwrap(),
+                    ends_at: Some(Utc.with_ymd_and_hms(2021, 7, 1, 19, 0, 0).unwrap()),
+                },
+                ImportedSegment {
+                    title: "".to_string(),
+                    category: "".to_string(),
+                    recurring: false,
+                    starts_at: Utc.with_ymd_and_hms(2021, 7, 2, 18, 0, 0).unwrap(),
+                    ends_at: None,
+                },
+            ],
+            emotes: vec![ImportedEmote {
+                name: "twitchdevPitchfork".to_string(),
+                image_url: "https://static-cdn.jtvnw.net/emoticons/v2/304456832/static/light/3.0"
+                    .to_string(),
+                width: 112,
+                height: 112,
+            }],
+        }
+    );
+}
+
+#[test]
+fn test_youtube_imported_channel() {
+    let channel = serde_json::from_value(json!({
+        "kind": "youtube#channel",
+        "id": "UC_x5XG1OV2P6uZZ5FSM9Ttw",
+        "snippet": {
+            "title": "Google for Developers",
+            "description": "",
+            "thumbnails": {
+                "default": { "url": "https://yt3.ggpht.com/default.jpg", "width": 88, "height": 88 },
+                "high": { "url": "https://yt3.ggpht.com/high.jpg", "width": 800, "height": 800 },
+            },
+        },
+    }))
+    .unwrap();
+
+    let broadcasts = serde_json::from_value(json!([
+        {
+            "id": "Xt6PfQ2Zm6U",
+            "snippet": {
+                "title": "Android Dev Summit",
+                "scheduledStartTime": "2023-10-25T16:00:00Z",
+            },
+        },
+        {
+            "id": "Rk4Yk2Q7A1s",
+            "snippet": { "title": "Unscheduled" },
+        },
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        youtube::imported_channel(channel, broadcasts),
+        ImportedChannel {
+            profile_image_url: Some("https://yt3.ggpht.com/high.jpg".to_string()),
+            panels: vec![],
+            schedule: vec![ImportedSegment {
+                title: "Android Dev Summit".to_string(),
+                category: "".to_string(),
+                recurring: false,
+                starts_at: Utc.with_ymd_and_hms(2023, 10, 25, 16, 0, 0).unwrap(),
+                ends_at: None,
+            }],
+            emotes: vec![],
+        }
+    );
+}
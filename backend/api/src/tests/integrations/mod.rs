@@ -0,0 +1,6 @@
+mod discord;
+mod image_processor;
+mod import;
+mod obs;
+mod vod_caption;
+mod vod_download;
@@ -0,0 +1,59 @@
+use serde_json::json;
+
+use crate::integrations::obs::{auth_response, ObsEvent, ObsMessage};
+
+#[test]
+fn test_auth_response() {
+    // The example from the obs-websocket protocol documentation.
+    assert_eq!(
+        auth_response(
+            "supersecretpassword",
+            "lM1GncleQOaCu9lT1yeUZhFYnqhsLLP1G5lAGo3ixaI=",
+            "+IxH4CnCiqpX1rM9scsNynZzbOe4KhDeYcTNS3PDaeY=",
+        ),
+        "1Ct943GAT+6YQUUX47Ia/ncufilbe6+oD6lY+5kaCu4="
+    );
+}
+
+#[test]
+fn test_obs_event() {
+    let tests = vec![
+        (
+            json!({"op": 5, "d": {"eventType": "CurrentProgramSceneChanged", "eventData": {"sceneName": "Just Chatting"}}}),
+            ObsEvent::SceneChanged("Just Chatting".to_string()),
+        ),
+        (
+            json!({"op": 5, "d": {"eventType": "CurrentProfileChanged", "eventData": {"profileName": "Gaming"}}}),
+            ObsEvent::ProfileChanged("Gaming".to_string()),
+        ),
+        (
+            json!({"op": 5, "d": {"eventType": "StreamStateChanged", "eventData": {"outputActive": false, "outputState": "OBS_WEBSOCKET_OUTPUT_STARTING"}}}),
+            ObsEvent::StreamStarting,
+        ),
+        (
+            json!({"op": 5, "d": {"eventType": "StreamStateChanged", "eventData": {"outputActive": true, "outputState": "OBS_WEBSOCKET_OUTPUT_STARTED"}}}),
+            ObsEvent::Other,
+        ),
+        (
+            json!({"op": 5, "d": {"eventType": "ExitStarted"}}),
+            ObsEvent::Exiting,
+        ),
+        (
+            json!({"op": 7, "d": {"requestId": "current-scene", "responseData": {"currentProgramSceneName": "Starting Soon"}}}),
+            ObsEvent::CurrentScene("Starting Soon".to_string()),
+        ),
+        (
+            json!({"op": 7, "d": {"requestId": "current-profile", "responseData": {"currentProfileName": "Gaming", "profiles": ["Gaming"]}}}),
+            ObsEvent::CurrentProfile("Gaming".to_string()),
+        ),
+        (
+            json!({"op": 5, "d": {"eventType": "CurrentProgramSceneChanged", "eventData": {}}}),
+            ObsEvent::Other,
+        ),
+    ];
+
+    for (message, expected) in tests {
+        let message: ObsMessage = serde_json::from_value(message).unwrap();
+        assert_eq!(ObsEvent::from(&message), expected);
+    }
+}
@@ -0,0 +1,155 @@
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    database::{stream, user, vod_caption},
+    integrations::vod_caption::record_result,
+    pb::scuffle::events::{vod_caption_job_result::Cue, VodCaptionJobResult},
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_record_result() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let insert_vod = || async {
+        sqlx::query_as!(stream::Model,
+            "INSERT INTO streams (channel_id, title, description, recorded, ingest_address, connection_id) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+            user.id,
+            "test",
+            "test",
+            true,
+            "some address",
+            Uuid::new_v4(),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap()
+    };
+    let db = &*global.db;
+    let fetch = |id: Uuid| async move {
+        sqlx::query_as!(
+            vod_caption::Model,
+            "SELECT * FROM vod_captions WHERE id = $1",
+            id
+        )
+        .fetch_one(db)
+        .await
+        .unwrap()
+    };
+    let cues = |stream_id: Uuid| async move {
+        sqlx::query_as!(
+            vod_caption::Cue,
+            "SELECT * FROM vod_caption_cues WHERE stream_id = $1 ORDER BY start_ms",
+            stream_id
+        )
+        .fetch_all(db)
+        .await
+        .unwrap()
+    };
+
+    let vod = insert_vod().await;
+    let captions = global.queue_vod_captions(&vod).await.unwrap().unwrap();
+    assert_eq!(captions.status, vod_caption::Status::Queued);
+    assert!(global.queue_vod_captions(&vod).await.unwrap().is_none());
+
+    let result = VodCaptionJobResult {
+        id: captions.id.to_string(),
+        language: "en".to_string(),
+        cues: vec![
+            Cue {
+                start_ms: 0,
+                end_ms: 2500,
+                text: " Hello chat ".to_string(),
+            },
+            Cue {
+                start_ms: 2500,
+                end_ms: 3000,
+                text: " ".to_string(),
+            },
+            Cue {
+                start_ms: 3000,
+                end_ms: 5000,
+                text: "Welcome to the stream".to_string(),
+            },
+        ],
+        error: None,
+    };
+
+    record_result(&global, &result).await.unwrap();
+    let captions = fetch(captions.id).await;
+    assert_eq!(captions.status, vod_caption::Status::Completed);
+    assert_eq!(captions.language, "en");
+    assert!(captions.completed_at.is_some());
+
+    let stored = cues(vod.id).await;
+    assert_eq!(
+        stored
+            .iter()
+            .map(|cue| (cue.start_ms, cue.end_ms, cue.text.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (0, 2500, "Hello chat"),
+            (3000, 5000, "Welcome to the stream")
+        ]
+    );
+
+    // A redelivered result doesn't add the cues again.
+    record_result(&global, &result).await.unwrap();
+    assert_eq!(cues(vod.id).await.len(), 2);
+    assert!(global.queue_vod_captions(&vod).await.unwrap().is_none());
+
+    let vod = insert_vod().await;
+    let captions = global.queue_vod_captions(&vod).await.unwrap().unwrap();
+
+    record_result(
+        &global,
+        &VodCaptionJobResult {
+            id: captions.id.to_string(),
+            error: Some("backend returned 500".to_string()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let failed = fetch(captions.id).await;
+    assert_eq!(failed.status, vod_caption::Status::Failed);
+    assert_eq!(failed.error, "Failed to make the captions");
+
+    // Failed captions can be queued again, an unknown language is left empty.
+    let requeued = global.queue_vod_captions(&vod).await.unwrap().unwrap();
+    assert_eq!(requeued.id, captions.id);
+    assert_eq!(requeued.status, vod_caption::Status::Queued);
+    assert_eq!(requeued.error, "");
+
+    record_result(
+        &global,
+        &VodCaptionJobResult {
+            id: captions.id.to_string(),
+            language: "not a language".to_string(),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let captions = fetch(captions.id).await;
+    assert_eq!(captions.status, vod_caption::Status::Completed);
+    assert_eq!(captions.language, "");
+}
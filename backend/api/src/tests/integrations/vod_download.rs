@@ -0,0 +1,137 @@
+use serial_test::serial;
+use uuid::Uuid;
+
+use crate::{
+    database::{stream, user, vod_download},
+    integrations::vod_download::record_result,
+    pb::scuffle::events::VodDownloadJobResult,
+    tests::global::mock_global_state,
+};
+
+#[tokio::test]
+#[serial]
+async fn test_serial_record_result() {
+    let (global, _handler) = mock_global_state(Default::default()).await;
+
+    sqlx::query!("DELETE FROM users")
+        .execute(&*global.db)
+        .await
+        .unwrap();
+
+    let user = sqlx::query_as!(user::Model,
+        "INSERT INTO users(username, display_name, email, password_hash, stream_key) VALUES ($1, $1, $2, $3, $4) RETURNING *",
+        "test",
+        "test@test.com",
+        user::hash_password("test"),
+        user::generate_stream_key(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let vod = sqlx::query_as!(stream::Model,
+        "INSERT INTO streams (channel_id, title, description, recorded, ingest_address, connection_id) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+        user.id,
+        "test",
+        "test",
+        true,
+        "some address",
+        Uuid::new_v4(),
+    )
+    .fetch_one(&*global.db)
+    .await
+    .unwrap();
+
+    let insert = || async {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            vod_download::Model,
+            "INSERT INTO vod_downloads (id, stream_id, requested_by, variant, remux, object_key) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+            id,
+            vod.id,
+            user.id,
+            "source",
+            true,
+            vod_download::object_key(user.id, vod.id, id),
+        )
+        .fetch_one(&*global.db)
+        .await
+        .unwrap()
+    };
+    let db = &*global.db;
+    let fetch = |id: Uuid| async move {
+        sqlx::query_as!(
+            vod_download::Model,
+            "SELECT * FROM vod_downloads WHERE id = $1",
+            id
+        )
+        .fetch_one(db)
+        .await
+        .unwrap()
+    };
+
+    let download = insert().await;
+
+    record_result(
+        &global,
+        &VodDownloadJobResult {
+            id: download.id.to_string(),
+            progress: 40,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let current = fetch(download.id).await;
+    assert_eq!(current.status, vod_download::Status::Running);
+    assert_eq!(current.progress, 40);
+
+    record_result(
+        &global,
+        &VodDownloadJobResult {
+            id: download.id.to_string(),
+            progress: 100,
+            done: true,
+            size_bytes: 1024,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let current = fetch(download.id).await;
+    assert_eq!(current.status, vod_download::Status::Completed);
+    assert_eq!(current.size_bytes, 1024);
+    assert!(current.downloadable(chrono::Utc::now()));
+
+    // A late progress report does not move a finished download back.
+    record_result(
+        &global,
+        &VodDownloadJobResult {
+            id: download.id.to_string(),
+            progress: 90,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let current = fetch(download.id).await;
+    assert_eq!(current.status, vod_download::Status::Completed);
+    assert_eq!(current.progress, 100);
+
+    // The error the worker reports is not shown to the broadcaster.
+    let download = insert().await;
+    record_result(
+        &global,
+        &VodDownloadJobResult {
+            id: download.id.to_string(),
+            error: Some("s3: access denied".to_string()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let current = fetch(download.id).await;
+    assert_eq!(current.status, vod_download::Status::Failed);
+    assert_eq!(current.error, "Failed to make the download");
+    assert!(!current.downloadable(chrono::Utc::now()));
+}
@@ -4,3 +4,5 @@ mod database;
 mod dataloader;
 mod global;
 mod grpc;
+mod integrations;
+mod subscription;
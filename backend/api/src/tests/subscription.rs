@@ -0,0 +1,149 @@
+use std::{sync::atomic::Ordering, time::Duration};
+
+use common::prelude::FutureTimeout;
+use fred::{prelude::PubsubInterface, types::RedisValue};
+use serial_test::serial;
+
+use crate::{
+    config::{AppConfig, SubscriptionConfig},
+    subscription::{pick_shard, RecvError, Topic},
+    tests::global::mock_global_state,
+};
+
+#[test]
+fn test_pick_shard() {
+    // The first subscriber starts a shard.
+    assert_eq!(pick_shard(&[], 2, 4), None);
+    assert_eq!(pick_shard(&[1], 2, 4), Some(0));
+    assert_eq!(pick_shard(&[2], 2, 4), None);
+    // Subscribers join the emptiest shard, also once one emptied out.
+    assert_eq!(pick_shard(&[2, 1], 2, 4), Some(1));
+    assert_eq!(pick_shard(&[0, 2], 2, 4), Some(0));
+    // At the most shards they keep growing past their size.
+    assert_eq!(pick_shard(&[2, 2, 2, 2], 2, 4), Some(0));
+    assert_eq!(pick_shard(&[3, 2, 2, 2], 2, 4), Some(1));
+}
+
+#[tokio::test]
+async fn test_full_shard_evicts_subscribers() {
+    let config = SubscriptionConfig {
+        shard_queue: 2,
+        ..Default::default()
+    };
+
+    let mut topic = Topic::new();
+    let (mut rx, _, evicted) = topic.subscribe(&config);
+
+    // The fan-out task does not run before this test waits, so the queue fills up.
+    assert_eq!(topic.publish(&RedisValue::from("first")), 0);
+    assert_eq!(topic.publish(&RedisValue::from("second")), 0);
+    assert_eq!(topic.publish(&RedisValue::from("third")), 1);
+    assert!(evicted.load(Ordering::Relaxed));
+
+    // The queued events are still delivered, then the subscriber sees the shard is gone.
+    for event in ["first", "second"] {
+        let value = rx
+            .recv()
+            .timeout(Duration::from_secs(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(value.as_str().as_deref(), Some(event));
+    }
+    assert!(rx
+        .recv()
+        .timeout(Duration::from_secs(1))
+        .await
+        .unwrap()
+        .is_err());
+
+    // New subscribers get a new shard.
+    let (_rx, _, evicted) = topic.subscribe(&config);
+    assert!(!evicted.load(Ordering::Relaxed));
+    assert_eq!(topic.publish(&RedisValue::from("fourth")), 0);
+}
+
+#[serial]
+#[tokio::test]
+async fn test_serial_sharded_fan_out() {
+    let (global, handler) = mock_global_state(AppConfig {
+        subscriptions: SubscriptionConfig {
+            subscriber_buffer: 2,
+            shard_size: 2,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+
+    let topic = "test:fan_out";
+
+    let mut subscribers = Vec::new();
+    for _ in 0..5 {
+        subscribers.push(global.subscription_manager.subscribe(topic).await.unwrap());
+    }
+
+    // Every subscriber gets the event, whichever shard it is on.
+    let count: i32 = global.redis.publish(topic, "first").await.unwrap();
+    assert_eq!(count, 1);
+
+    for subscriber in &mut subscribers {
+        let value = subscriber
+            .recv()
+            .timeout(Duration::from_secs(1))
+            .await
+            .expect("event not delivered")
+            .unwrap();
+        assert_eq!(value.as_str().as_deref(), Some("first"));
+    }
+
+    // A subscriber which stops reading is closed once it falls behind its buffer, the others are not held up.
+    let mut slow = subscribers.pop().unwrap();
+    for event in ["second", "third", "fourth"] {
+        let _: i32 = global.redis.publish(topic, event).await.unwrap();
+
+        for subscriber in &mut subscribers {
+            let value = subscriber
+                .recv()
+                .timeout(Duration::from_secs(1))
+                .await
+                .expect("event not delivered")
+                .unwrap();
+            assert_eq!(value.as_str().as_deref(), Some(event));
+        }
+    }
+
+    assert!(matches!(slow.recv().await, Err(RecvError::Evicted)));
+    assert!(matches!(slow.recv().await, Err(RecvError::Evicted)));
+
+    // The eviction is counted once, the events were delivered to every shard.
+    let stats = global
+        .subscription_manager
+        .stats()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|stats| stats.topic == topic)
+        .unwrap();
+    assert_eq!(stats.subscribers, 5);
+    assert_eq!(stats.shards, 3);
+    assert_eq!(stats.published, 4);
+    assert_eq!(stats.dropped, 0);
+    assert_eq!(stats.evicted, 1);
+
+    drop(slow);
+    drop(subscribers);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Once every shard is empty the subject is unsubscribed.
+    let count: i32 = global.redis.publish(topic, "fifth").await.unwrap();
+    assert_eq!(count, 0);
+
+    drop(global);
+
+    handler
+        .cancel()
+        .timeout(Duration::from_secs(1))
+        .await
+        .expect("failed to cancel context");
+}
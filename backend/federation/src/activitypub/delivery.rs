@@ -0,0 +1,145 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    header,
+};
+use serde_json::Value;
+use tokio::net::lookup_host;
+
+use super::{
+    objects::{RemoteActor, CONTENT_TYPE},
+    signature::{self, SignatureHeader, SIGNED_HEADERS},
+};
+use crate::{database::actor, global::GlobalState};
+
+/// The format of the Date header, RFC 7231 IMF-fixdate.
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// Actors larger than this are rejected, they are a few kilobytes with their key.
+const MAX_ACTOR_SIZE: usize = 256 * 1024;
+
+/// Resolves hosts for the http client and rejects the ones with an address which is not public.
+/// Actors and inboxes are urls remote servers choose, they must not reach into our own network.
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = lookup_host((name.as_str(), 0)).await?.collect::<Vec<_>>();
+
+            if addrs.iter().any(|addr| !common::net::is_public(addr.ip())) {
+                return Err(anyhow!(
+                    "{} resolves to an address which is not publicly reachable",
+                    name.as_str()
+                )
+                .into());
+            }
+
+            Ok::<Addrs, Box<dyn std::error::Error + Send + Sync>>(Box::new(addrs.into_iter()))
+        })
+    }
+}
+
+/// The value of the Host header for a request to this url.
+/// Hostnames are checked by the resolver when connecting, ip addresses are not resolved so they are checked here.
+fn host(url: &url::Url) -> Result<String> {
+    if url.scheme() != "https" && url.scheme() != "http" {
+        bail!("unsupported url scheme: {}", url.scheme());
+    }
+
+    let public = match url.host() {
+        Some(url::Host::Ipv4(ip)) => common::net::is_public(ip.into()),
+        Some(url::Host::Ipv6(ip)) => common::net::is_public(ip.into()),
+        _ => true,
+    };
+    if !public {
+        bail!("url is not publicly reachable");
+    }
+
+    let host = url.host_str().ok_or_else(|| anyhow!("url has no host"))?;
+
+    Ok(match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    })
+}
+
+/// Fetches a remote actor. The key id of a signature is the actor url with a fragment, which is stripped here.
+pub async fn fetch_actor(global: &GlobalState, url: &str) -> Result<RemoteActor> {
+    let mut url = url::Url::parse(url)?;
+    url.set_fragment(None);
+    host(&url)?;
+
+    let mut response = global
+        .http
+        .get(url)
+        .header(header::ACCEPT, CONTENT_TYPE)
+        .send()
+        .await?
+        .error_for_status()
+        .map_err(|e| e.without_url())?;
+
+    if response
+        .content_length()
+        .map_or(false, |len| len > MAX_ACTOR_SIZE as u64)
+    {
+        bail!("actor is too large");
+    }
+
+    // The length can be left out, so the body is also counted while reading it.
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if body.len() > MAX_ACTOR_SIZE {
+            bail!("actor is too large");
+        }
+    }
+
+    Ok(serde_json::from_slice(&body)?)
+}
+
+/// Posts a signed activity to a remote inbox.
+pub async fn deliver(
+    global: &GlobalState,
+    actor: &actor::Model,
+    key_id: &str,
+    inbox: &str,
+    activity: &Value,
+) -> Result<()> {
+    let url = url::Url::parse(inbox)?;
+    let host = host(&url)?;
+
+    let body = serde_json::to_vec(activity)?;
+    let digest = signature::digest(&body);
+    let date = Utc::now().format(HTTP_DATE_FORMAT).to_string();
+
+    let signing_string =
+        signature::signing_string(&SIGNED_HEADERS, "POST", url.path(), |name| match name {
+            "host" => Some(host.as_str()),
+            "date" => Some(date.as_str()),
+            "digest" => Some(digest.as_str()),
+            _ => None,
+        })?;
+
+    let signature = SignatureHeader::new(
+        key_id.to_string(),
+        signature::sign(&actor.private_key_pem, &signing_string)?,
+    );
+
+    global
+        .http
+        .post(url)
+        .header(header::HOST, &host)
+        .header(header::DATE, &date)
+        .header("Digest", &digest)
+        .header("Signature", signature.to_header_value())
+        .header(header::CONTENT_TYPE, CONTENT_TYPE)
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()
+        .map_err(|e| e.without_url())?;
+
+    Ok(())
+}
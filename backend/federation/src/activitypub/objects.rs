@@ -0,0 +1,264 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::database::{activity, channel};
+
+pub const CONTEXT: &str = "https://www.w3.org/ns/activitystreams";
+pub const SECURITY_CONTEXT: &str = "https://w3id.org/security/v1";
+pub const PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+/// The content type of ActivityPub documents.
+pub const CONTENT_TYPE: &str = "application/activity+json";
+
+/// The urls channels and their activities are published under.
+pub struct Urls<'a> {
+    /// The public url of the federation service without a trailing slash.
+    pub public_url: &'a str,
+    /// The url of the website without a trailing slash.
+    pub website_url: &'a str,
+}
+
+impl Urls<'_> {
+    pub fn actor(&self, username: &str) -> String {
+        format!("{}/users/{}", self.public_url, username)
+    }
+
+    pub fn key(&self, username: &str) -> String {
+        format!("{}#main-key", self.actor(username))
+    }
+
+    pub fn inbox(&self, username: &str) -> String {
+        format!("{}/inbox", self.actor(username))
+    }
+
+    pub fn outbox(&self, username: &str) -> String {
+        format!("{}/outbox", self.actor(username))
+    }
+
+    pub fn followers(&self, username: &str) -> String {
+        format!("{}/followers", self.actor(username))
+    }
+
+    pub fn activity(&self, id: Uuid) -> String {
+        format!("{}/activities/{}", self.public_url, id)
+    }
+
+    pub fn channel(&self, username: &str) -> String {
+        format!("{}/{}", self.website_url, username)
+    }
+}
+
+/// The host (and port, if it is not the default one) of the public url, used as the domain of `acct:` handles.
+pub fn domain(public_url: &str) -> Option<String> {
+    let url = url::Url::parse(public_url).ok()?;
+    let host = url.host_str()?;
+
+    Some(match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    })
+}
+
+/// Returns the username a WebFinger resource refers to, if it is a handle on our domain.
+pub fn webfinger_username<'a>(resource: &'a str, domain: &str) -> Option<&'a str> {
+    let handle = resource.strip_prefix("acct:")?;
+    let (username, host) = handle.trim_start_matches('@').rsplit_once('@')?;
+
+    (host.eq_ignore_ascii_case(domain) && !username.is_empty()).then_some(username)
+}
+
+pub fn webfinger(urls: &Urls, domain: &str, username: &str) -> Value {
+    json!({
+        "subject": format!("acct:{}@{}", username, domain),
+        "aliases": [urls.actor(username), urls.channel(username)],
+        "links": [
+            {
+                "rel": "self",
+                "type": CONTENT_TYPE,
+                "href": urls.actor(username),
+            },
+            {
+                "rel": "http://webfinger.net/rel/profile-page",
+                "type": "text/html",
+                "href": urls.channel(username),
+            },
+        ],
+    })
+}
+
+pub fn actor(urls: &Urls, channel: &channel::Model, public_key_pem: &str) -> Value {
+    let mut actor = json!({
+        "@context": [CONTEXT, SECURITY_CONTEXT],
+        "id": urls.actor(&channel.username),
+        "type": "Person",
+        "preferredUsername": channel.username,
+        "name": channel.display_name,
+        "summary": escape_html(&channel.stream_description),
+        "url": urls.channel(&channel.username),
+        "inbox": urls.inbox(&channel.username),
+        "outbox": urls.outbox(&channel.username),
+        "followers": urls.followers(&channel.username),
+        "published": channel.created_at.to_rfc3339(),
+        "manuallyApprovesFollowers": false,
+        "discoverable": true,
+        "publicKey": {
+            "id": urls.key(&channel.username),
+            "owner": urls.actor(&channel.username),
+            "publicKeyPem": public_key_pem,
+        },
+    });
+
+    if !channel.profile_image_url.is_empty() {
+        actor["icon"] = json!({
+            "type": "Image",
+            "url": channel.profile_image_url,
+        });
+    }
+
+    actor
+}
+
+/// The HTML content of the note announcing an activity.
+pub fn note_content(urls: &Urls, channel: &channel::Model, activity: &activity::Model) -> String {
+    let url = urls.channel(&channel.username);
+    let text = match activity.kind {
+        activity::Kind::GoLive => {
+            format!("{} is now live: {}", channel.display_name, activity.title)
+        }
+        activity::Kind::VodPublished => {
+            format!("The recording of {} is now available", activity.title)
+        }
+    };
+
+    format!(
+        r#"<p>{}</p><p><a href="{}">{}</a></p>"#,
+        escape_html(&text),
+        escape_html(&url),
+        escape_html(&url)
+    )
+}
+
+/// A Create activity wrapping a public note, addressed to the followers of the channel.
+pub fn create(urls: &Urls, channel: &channel::Model, activity: &activity::Model) -> Value {
+    let id = urls.activity(activity.id);
+    let published = activity.created_at.to_rfc3339();
+
+    json!({
+        "@context": CONTEXT,
+        "id": id,
+        "type": "Create",
+        "actor": urls.actor(&channel.username),
+        "published": published,
+        "to": [PUBLIC],
+        "cc": [urls.followers(&channel.username)],
+        "object": {
+            "id": format!("{}#note", id),
+            "type": "Note",
+            "attributedTo": urls.actor(&channel.username),
+            "published": published,
+            "url": urls.channel(&channel.username),
+            "content": note_content(urls, channel, activity),
+            "to": [PUBLIC],
+            "cc": [urls.followers(&channel.username)],
+        },
+    })
+}
+
+/// Accepts a follow request. The follow is embedded as received, so the remote server can match it.
+pub fn accept(urls: &Urls, username: &str, follow: Value) -> Value {
+    json!({
+        "@context": CONTEXT,
+        "id": format!("{}#accepts/{}", urls.actor(username), Uuid::new_v4()),
+        "type": "Accept",
+        "actor": urls.actor(username),
+        "object": follow,
+    })
+}
+
+pub fn ordered_collection(id: String, total_items: i64, items: Option<Vec<Value>>) -> Value {
+    let mut collection = json!({
+        "@context": CONTEXT,
+        "id": id,
+        "type": "OrderedCollection",
+        "totalItems": total_items,
+    });
+
+    if let Some(items) = items {
+        collection["orderedItems"] = Value::Array(items);
+    }
+
+    collection
+}
+
+/// The parts of a remote actor we need to verify its requests and deliver to it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteActor {
+    pub id: String,
+    pub inbox: String,
+    #[serde(default)]
+    pub endpoints: Option<RemoteEndpoints>,
+    pub public_key: RemotePublicKey,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteEndpoints {
+    #[serde(default)]
+    pub shared_inbox: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemotePublicKey {
+    pub id: String,
+    pub owner: String,
+    pub public_key_pem: String,
+}
+
+impl RemoteActor {
+    pub fn shared_inbox(&self) -> Option<&str> {
+        self.endpoints.as_ref()?.shared_inbox.as_deref()
+    }
+}
+
+/// An activity posted to an inbox.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IncomingActivity {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub actor: String,
+    pub object: Value,
+}
+
+/// The id of an object, objects can be referenced by their id or embedded.
+pub fn object_id(object: &Value) -> Option<&str> {
+    match object {
+        Value::String(id) => Some(id),
+        Value::Object(object) => object.get("id")?.as_str(),
+        _ => None,
+    }
+}
+
+/// The type of an embedded object.
+pub fn object_type(object: &Value) -> Option<&str> {
+    object.get("type")?.as_str()
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,152 @@
+//! HTTP signatures as used by Mastodon and most other ActivityPub servers
+//! (draft-cavage-http-signatures with rsa-sha256).
+
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rsa::{
+    pkcs1v15::{Signature, SigningKey, VerifyingKey},
+    pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding},
+    signature::{SignatureEncoding, Signer, Verifier},
+    RsaPrivateKey, RsaPublicKey,
+};
+use sha2::{Digest, Sha256};
+
+/// The size of the keys generated for channel actors.
+pub const KEY_BITS: usize = 2048;
+
+/// The headers our signatures cover. Inbox requests have to sign at least these.
+pub const SIGNED_HEADERS: [&str; 4] = ["(request-target)", "host", "date", "digest"];
+
+/// Generates a new key pair, returned as PKCS#8 PEM private key and SPKI PEM public key.
+pub fn generate_key_pair() -> Result<(String, String)> {
+    let key = RsaPrivateKey::new(&mut rand::thread_rng(), KEY_BITS)?;
+
+    let private_key_pem = key.to_pkcs8_pem(LineEnding::LF)?.to_string();
+    let public_key_pem = key.to_public_key().to_public_key_pem(LineEnding::LF)?;
+
+    Ok((private_key_pem, public_key_pem))
+}
+
+/// The value of the Digest header for a request body.
+pub fn digest(body: &[u8]) -> String {
+    format!("SHA-256={}", STANDARD.encode(Sha256::digest(body)))
+}
+
+/// Builds the string a signature is computed over.
+/// `header` looks up the value of a request header by its lowercase name.
+pub fn signing_string<'a>(
+    headers: &[impl AsRef<str>],
+    method: &str,
+    path: &str,
+    header: impl Fn(&str) -> Option<&'a str>,
+) -> Result<String> {
+    headers
+        .iter()
+        .map(|name| {
+            let name = name.as_ref();
+            if name == "(request-target)" {
+                return Ok(format!(
+                    "(request-target): {} {}",
+                    method.to_lowercase(),
+                    path
+                ));
+            }
+
+            let value = header(name).ok_or_else(|| anyhow!("missing signed header: {}", name))?;
+            Ok(format!("{}: {}", name, value.trim()))
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(|lines| lines.join("\n"))
+}
+
+/// Signs a signing string with the private key of a channel's actor.
+pub fn sign(private_key_pem: &str, signing_string: &str) -> Result<Vec<u8>> {
+    let key = SigningKey::<Sha256>::new(RsaPrivateKey::from_pkcs8_pem(private_key_pem)?);
+
+    Ok(key.sign(signing_string.as_bytes()).to_vec())
+}
+
+/// Verifies a signature over a signing string with the public key of the remote actor.
+pub fn verify(public_key_pem: &str, signing_string: &str, signature: &[u8]) -> Result<()> {
+    let key = VerifyingKey::<Sha256>::new(RsaPublicKey::from_public_key_pem(public_key_pem)?);
+
+    key.verify(signing_string.as_bytes(), &Signature::try_from(signature)?)
+        .map_err(|_| anyhow!("signature does not match"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A parsed Signature header.
+pub struct SignatureHeader {
+    /// The url of the key, usually the actor url with a fragment.
+    pub key_id: String,
+    /// The signed headers, in the order they appear in the signing string.
+    pub headers: Vec<String>,
+    /// The raw signature.
+    pub signature: Vec<u8>,
+}
+
+impl SignatureHeader {
+    /// A header for a signature over [`SIGNED_HEADERS`].
+    pub fn new(key_id: String, signature: Vec<u8>) -> Self {
+        Self {
+            key_id,
+            headers: SIGNED_HEADERS.iter().map(|h| h.to_string()).collect(),
+            signature,
+        }
+    }
+
+    /// Parses a header like `keyId="...",algorithm="rsa-sha256",headers="...",signature="..."`.
+    pub fn parse(value: &str) -> Result<Self> {
+        let mut key_id = None;
+        let mut headers = None;
+        let mut signature = None;
+
+        let mut rest = value.trim();
+        while !rest.is_empty() {
+            let (name, after) = rest
+                .split_once('=')
+                .ok_or_else(|| anyhow!("malformed signature parameter"))?;
+            let after = after
+                .strip_prefix('"')
+                .ok_or_else(|| anyhow!("signature parameter is not quoted"))?;
+            let (param, after) = after
+                .split_once('"')
+                .ok_or_else(|| anyhow!("unterminated signature parameter"))?;
+
+            match name.trim() {
+                "keyId" => key_id = Some(param.to_string()),
+                "headers" => headers = Some(param.to_string()),
+                "signature" => signature = Some(STANDARD.decode(param)?),
+                // hs2019 means the algorithm is derived from the key, which is always RSA for us.
+                "algorithm" if param != "rsa-sha256" && param != "hs2019" => {
+                    bail!("unsupported signature algorithm: {}", param)
+                }
+                _ => {}
+            }
+
+            rest = after.trim_start_matches([',', ' ']);
+        }
+
+        Ok(Self {
+            key_id: key_id.ok_or_else(|| anyhow!("missing keyId"))?,
+            // Without a headers parameter only the date is signed.
+            headers: headers
+                .as_deref()
+                .unwrap_or("date")
+                .split_whitespace()
+                .map(|h| h.to_lowercase())
+                .collect(),
+            signature: signature.ok_or_else(|| anyhow!("missing signature"))?,
+        })
+    }
+
+    /// Formats the header value sent with our own requests.
+    pub fn to_header_value(&self) -> String {
+        format!(
+            r#"keyId="{}",algorithm="rsa-sha256",headers="{}",signature="{}""#,
+            self.key_id,
+            self.headers.join(" "),
+            STANDARD.encode(&self.signature)
+        )
+    }
+}
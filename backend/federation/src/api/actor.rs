@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use hyper::{Body, Request, Response, StatusCode};
+use routerify::{prelude::RequestExt, Router};
+use uuid::Uuid;
+
+use super::{
+    error::{Result, ResultExt, RouteError},
+    ext::RequestExt as _,
+    inbox,
+};
+use crate::{
+    activitypub::objects::{self, CONTENT_TYPE},
+    database::{activity, actor, channel},
+    global::GlobalState,
+};
+
+/// The number of activities shown in the outbox, older ones can still be fetched by their url.
+const OUTBOX_LIMIT: i64 = 20;
+
+async fn channel_from_request(global: &GlobalState, req: &Request<Body>) -> Result<channel::Model> {
+    let username = req
+        .param("username")
+        .ok_or((StatusCode::BAD_REQUEST, "invalid username"))?;
+
+    channel::by_username(&global.db, username)
+        .await
+        .map_err_route("failed to fetch channel")?
+        .ok_or(RouteError::from((
+            StatusCode::NOT_FOUND,
+            "channel not found",
+        )))
+}
+
+async fn actor(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+    let channel = channel_from_request(&global, &req).await?;
+
+    let actor = actor::get_or_create(&global.db, channel.id)
+        .await
+        .map_err_route("failed to fetch actor")?;
+
+    Ok(super::document(
+        CONTENT_TYPE,
+        objects::actor(&global.urls(), &channel, &actor.public_key_pem),
+    ))
+}
+
+async fn outbox(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+    let channel = channel_from_request(&global, &req).await?;
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM federation_activities WHERE channel_id = $1"#,
+        channel.id,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .map_err_route("failed to count activities")?;
+
+    let activities = sqlx::query_as!(
+        activity::Model,
+        "SELECT * FROM federation_activities WHERE channel_id = $1 ORDER BY created_at DESC LIMIT $2",
+        channel.id,
+        OUTBOX_LIMIT,
+    )
+    .fetch_all(&*global.db)
+    .await
+    .map_err_route("failed to fetch activities")?;
+
+    let urls = global.urls();
+    let items = activities
+        .iter()
+        .map(|activity| objects::create(&urls, &channel, activity))
+        .collect();
+
+    Ok(super::document(
+        CONTENT_TYPE,
+        objects::ordered_collection(urls.outbox(&channel.username), total, Some(items)),
+    ))
+}
+
+/// Only the number of followers is public, who follows a channel is not.
+async fn followers(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+    let channel = channel_from_request(&global, &req).await?;
+
+    let total = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM federation_followers WHERE channel_id = $1"#,
+        channel.id,
+    )
+    .fetch_one(&*global.db)
+    .await
+    .map_err_route("failed to count followers")?;
+
+    Ok(super::document(
+        CONTENT_TYPE,
+        objects::ordered_collection(global.urls().followers(&channel.username), total, None),
+    ))
+}
+
+pub(super) async fn activity(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+
+    let id = req
+        .param("id")
+        .and_then(|id| id.parse::<Uuid>().ok())
+        .ok_or((StatusCode::BAD_REQUEST, "invalid activity id"))?;
+
+    let activity = sqlx::query_as!(
+        activity::Model,
+        "SELECT * FROM federation_activities WHERE id = $1",
+        id,
+    )
+    .fetch_optional(&*global.db)
+    .await
+    .map_err_route("failed to fetch activity")?
+    .ok_or(RouteError::from((
+        StatusCode::NOT_FOUND,
+        "activity not found",
+    )))?;
+
+    let channel = channel::by_id(&global.db, activity.channel_id)
+        .await
+        .map_err_route("failed to fetch channel")?
+        .ok_or(RouteError::from((
+            StatusCode::NOT_FOUND,
+            "channel not found",
+        )))?;
+
+    Ok(super::document(
+        CONTENT_TYPE,
+        objects::create(&global.urls(), &channel, &activity),
+    ))
+}
+
+pub fn routes(_: &Arc<GlobalState>) -> Router<Body, RouteError> {
+    Router::builder()
+        .get("/:username", actor)
+        .get("/:username/outbox", outbox)
+        .get("/:username/followers", followers)
+        .post("/:username/inbox", inbox::inbox)
+        .build()
+        .expect("failed to build router")
+}
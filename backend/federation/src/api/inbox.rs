@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Utc};
+use hyper::{header, Body, HeaderMap, Request, Response, StatusCode};
+use routerify::prelude::RequestExt;
+use serde_json::{json, Value};
+
+use super::{
+    error::{Result, ResultExt, RouteError},
+    ext::RequestExt as _,
+    macros::make_response,
+};
+use crate::{
+    activitypub::{
+        delivery,
+        objects::{self, IncomingActivity, RemoteActor},
+        signature::{self, SignatureHeader, SIGNED_HEADERS},
+    },
+    database::{actor, channel},
+    global::GlobalState,
+};
+
+/// Activities larger than this are rejected, follows and undos are a few hundred bytes.
+const MAX_BODY_SIZE: usize = 256 * 1024;
+
+/// Checks the HTTP signature of an inbox request and returns the actor that signed it.
+/// The key is fetched from the remote server, so the signer has to be an actor that still exists.
+async fn verify_request(
+    global: &GlobalState,
+    method: &str,
+    path: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> anyhow::Result<RemoteActor> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let digest = header("digest").ok_or_else(|| anyhow!("missing digest"))?;
+    if digest != signature::digest(body) {
+        bail!("digest does not match the body");
+    }
+
+    let date = header("date").ok_or_else(|| anyhow!("missing date"))?;
+    let date = DateTime::parse_from_rfc2822(date)?.with_timezone(&Utc);
+    if (Utc::now() - date).num_seconds().unsigned_abs()
+        > global.config.federation.max_clock_skew as u64
+    {
+        bail!("date is too far off");
+    }
+
+    let signature =
+        SignatureHeader::parse(header("signature").ok_or_else(|| anyhow!("missing signature"))?)?;
+    if let Some(missing) = SIGNED_HEADERS
+        .iter()
+        .find(|h| !signature.headers.iter().any(|s| s == *h))
+    {
+        bail!("{} is not signed", missing);
+    }
+
+    let actor = delivery::fetch_actor(global, &signature.key_id).await?;
+    if actor.public_key.id != signature.key_id || actor.public_key.owner != actor.id {
+        bail!("key does not belong to the actor");
+    }
+
+    let signing_string = signature::signing_string(&signature.headers, method, path, header)?;
+    signature::verify(
+        &actor.public_key.public_key_pem,
+        &signing_string,
+        &signature.signature,
+    )?;
+
+    Ok(actor)
+}
+
+/// Receives activities for a channel. Follows are accepted right away and undone follows remove the follower,
+/// every other activity is acknowledged and ignored.
+pub(super) async fn inbox(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+
+    let username = req
+        .param("username")
+        .ok_or((StatusCode::BAD_REQUEST, "invalid username"))?;
+
+    let channel = channel::by_username(&global.db, username)
+        .await
+        .map_err_route("failed to fetch channel")?
+        .ok_or(RouteError::from((
+            StatusCode::NOT_FOUND,
+            "channel not found",
+        )))?;
+
+    let method = req.method().to_string();
+    let path = req
+        .uri()
+        .path_and_query()
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let headers = req.headers().clone();
+
+    let too_large = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok())
+        .map(|len| len > MAX_BODY_SIZE)
+        .unwrap_or(false);
+    if too_large {
+        return Err(RouteError::from((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "activity too large",
+        )));
+    }
+
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .map_err_route("failed to read body")?;
+    if body.len() > MAX_BODY_SIZE {
+        return Err(RouteError::from((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "activity too large",
+        )));
+    }
+
+    let remote = verify_request(&global, &method, &path, &headers, &body)
+        .await
+        .map_err(|e| RouteError::from((StatusCode::UNAUTHORIZED, "invalid signature", e)))?;
+
+    let raw: Value = serde_json::from_slice(&body)
+        .map_err(|_| RouteError::from((StatusCode::BAD_REQUEST, "invalid activity")))?;
+    let activity: IncomingActivity = serde_json::from_value(raw.clone())
+        .map_err(|_| RouteError::from((StatusCode::BAD_REQUEST, "invalid activity")))?;
+
+    // A server may only act on behalf of its own actors.
+    if activity.actor != remote.id {
+        return Err(RouteError::from((
+            StatusCode::FORBIDDEN,
+            "activity was not signed by its actor",
+        )));
+    }
+
+    let actor_url = global.urls().actor(&channel.username);
+
+    match activity.kind.as_str() {
+        "Follow" if objects::object_id(&activity.object) == Some(actor_url.as_str()) => {
+            sqlx::query!(
+                "INSERT INTO federation_followers (channel_id, actor_url, inbox_url, shared_inbox_url) VALUES ($1, $2, $3, $4) ON CONFLICT (channel_id, actor_url) DO UPDATE SET inbox_url = $3, shared_inbox_url = $4",
+                channel.id,
+                remote.id,
+                remote.inbox,
+                remote.shared_inbox(),
+            )
+            .execute(&*global.db)
+            .await
+            .map_err_route("failed to insert follower")?;
+
+            tokio::spawn(accept_follow(global.clone(), channel, remote, raw));
+        }
+        "Undo"
+            if objects::object_type(&activity.object) == Some("Follow")
+                && objects::object_id(&activity.object["object"]) == Some(actor_url.as_str()) =>
+        {
+            sqlx::query!(
+                "DELETE FROM federation_followers WHERE channel_id = $1 AND actor_url = $2",
+                channel.id,
+                remote.id,
+            )
+            .execute(&*global.db)
+            .await
+            .map_err_route("failed to delete follower")?;
+        }
+        kind => tracing::debug!(kind, actor = remote.id, "ignoring activity"),
+    }
+
+    Ok(make_response!(
+        StatusCode::ACCEPTED,
+        json!({ "success": true })
+    ))
+}
+
+/// Delivers the Accept for a follow. Remote servers only show the follow once they got it.
+async fn accept_follow(
+    global: Arc<GlobalState>,
+    channel: channel::Model,
+    remote: RemoteActor,
+    follow: Value,
+) {
+    let result = async {
+        let actor = actor::get_or_create(&global.db, channel.id).await?;
+        let urls = global.urls();
+
+        delivery::deliver(
+            &global,
+            &actor,
+            &urls.key(&channel.username),
+            &remote.inbox,
+            &objects::accept(&urls, &channel.username, follow),
+        )
+        .await
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::warn!(actor = remote.id, "failed to accept follow: {:#}", e);
+    }
+}
@@ -0,0 +1,104 @@
+use anyhow::Result;
+use hyper::{header, server::conn::Http, Body, Response, StatusCode};
+use routerify::{RequestInfo, RequestServiceBuilder, Router};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::{net::TcpSocket, select};
+
+use crate::{api::macros::make_response, global::GlobalState};
+
+use self::error::{RouteError, ShouldLog};
+
+mod actor;
+mod error;
+mod ext;
+mod inbox;
+mod macros;
+mod webfinger;
+
+async fn error_handler(
+    err: Box<(dyn std::error::Error + Send + Sync + 'static)>,
+    info: RequestInfo,
+) -> Response<Body> {
+    match err.downcast::<RouteError>() {
+        Ok(err) => {
+            let location = err.location();
+
+            err.span().in_scope(|| match err.should_log() {
+                ShouldLog::Yes => {
+                    tracing::error!(location = location.to_string(), error = ?err, "http error")
+                }
+                ShouldLog::Debug => {
+                    tracing::debug!(location = location.to_string(), error = ?err, "http error")
+                }
+                ShouldLog::No => (),
+            });
+
+            err.response()
+        }
+        Err(err) => {
+            tracing::error!(error = ?err, info = ?info, "unhandled http error");
+            make_response!(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                json!({ "message": "Internal Server Error", "success": false })
+            )
+        }
+    }
+}
+
+/// A JSON document with a content type other than `application/json`, like ActivityPub objects.
+fn document(content_type: &str, document: Value) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Body::from(document.to_string()))
+        .expect("failed to build response")
+}
+
+pub fn routes(global: &Arc<GlobalState>) -> Router<Body, RouteError> {
+    let weak = Arc::downgrade(global);
+    Router::builder()
+        .data(weak)
+        // Our error handler
+        .err_handler_with_info(error_handler)
+        .scope("/.well-known", webfinger::routes(global))
+        .scope("/users", actor::routes(global))
+        .get("/activities/:id", actor::activity)
+        .build()
+        .expect("failed to build router")
+}
+
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    tracing::info!("Listening on {}", global.config.api.bind_address);
+    let socket = if global.config.api.bind_address.is_ipv6() {
+        TcpSocket::new_v6()?
+    } else {
+        TcpSocket::new_v4()?
+    };
+
+    socket.set_reuseaddr(true)?;
+    socket.set_reuseport(true)?;
+    socket.bind(global.config.api.bind_address)?;
+    let listener = socket.listen(1024)?;
+
+    // The request service holds a Weak reference to the global state, so open keep-alive connections don't block the shutdown.
+    let request_service =
+        RequestServiceBuilder::new(routes(&global)).expect("failed to build request service");
+
+    loop {
+        select! {
+            _ = global.ctx.done() => {
+                return Ok(());
+            },
+            r = listener.accept() => {
+                let (socket, addr) = r?;
+                tracing::debug!("Accepted connection from {}", addr);
+
+                tokio::spawn(Http::new().serve_connection(
+                    socket,
+                    request_service.build(addr),
+                ));
+            },
+        }
+    }
+}
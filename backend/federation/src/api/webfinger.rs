@@ -0,0 +1,53 @@
+use std::{collections::HashMap, sync::Arc};
+
+use hyper::{Body, Request, Response, StatusCode};
+use routerify::Router;
+
+use super::{
+    error::{Result, ResultExt, RouteError},
+    ext::RequestExt as _,
+};
+use crate::{activitypub::objects, database::channel, global::GlobalState};
+
+/// Resolves `acct:username@domain` handles to the actor of the channel, this is how users search for channels.
+async fn webfinger(req: Request<Body>) -> Result<Response<Body>> {
+    let global = req.get_global()?;
+
+    let params: HashMap<String, String> = req
+        .uri()
+        .query()
+        .map(|v| {
+            url::form_urlencoded::parse(v.as_bytes())
+                .into_owned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let domain = objects::domain(global.public_url())
+        .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "invalid public url"))?;
+
+    let username = params
+        .get("resource")
+        .and_then(|resource| objects::webfinger_username(resource, &domain))
+        .ok_or((StatusCode::BAD_REQUEST, "invalid resource"))?;
+
+    let channel = channel::by_username(&global.db, username)
+        .await
+        .map_err_route("failed to fetch channel")?
+        .ok_or(RouteError::from((
+            StatusCode::NOT_FOUND,
+            "channel not found",
+        )))?;
+
+    Ok(super::document(
+        "application/jrd+json",
+        objects::webfinger(&global.urls(), &domain, &channel.username),
+    ))
+}
+
+pub fn routes(_: &Arc<GlobalState>) -> Router<Body, RouteError> {
+    Router::builder()
+        .get("/webfinger", webfinger)
+        .build()
+        .expect("failed to build router")
+}
@@ -0,0 +1,105 @@
+use std::net::SocketAddr;
+
+use anyhow::Result;
+use common::config::{DatabaseConfig, LoggingConfig, RmqConfig};
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+/// The federation service publishes channels as ActivityPub actors
+pub struct AppConfig {
+    /// The path to the config file
+    pub config_file: Option<String>,
+
+    /// Name of this instance
+    pub name: String,
+
+    ///  The logging config
+    pub logging: LoggingConfig,
+
+    /// API Config
+    pub api: ApiConfig,
+
+    /// Database Config
+    pub database: DatabaseConfig,
+
+    /// RMQ Config
+    pub rmq: RmqConfig,
+
+    /// Federation Config
+    pub federation: FederationConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Bind address for the ActivityPub and WebFinger endpoints
+    pub bind_address: SocketAddr,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: "[::]:4100".parse().expect("failed to parse bind address"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct FederationConfig {
+    /// The public url of this service, actor ids and the WebFinger domain are derived from it
+    pub public_url: String,
+
+    /// The url of the website, used to link to channels in activities
+    pub website_url: String,
+
+    /// The RMQ fanout exchange the API publishes channel notifications to
+    pub exchange: String,
+
+    /// The RMQ queue this service consumes channel notifications from
+    pub queue: String,
+
+    /// How long to wait for a remote server when fetching actors or delivering activities in seconds
+    pub request_timeout: u32,
+
+    /// How far the date of a signed inbox request may be off from our clock in seconds
+    pub max_clock_skew: u32,
+}
+
+impl Default for FederationConfig {
+    fn default() -> Self {
+        Self {
+            public_url: "http://localhost:4100".to_string(),
+            website_url: "http://localhost:4000".to_string(),
+            exchange: "channel_notifications".to_string(),
+            queue: "federation_channel_notifications".to_string(),
+            request_timeout: 10,
+            max_clock_skew: 3600,
+        }
+    }
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            config_file: Some("config".to_string()),
+            name: "scuffle-federation".to_string(),
+            logging: LoggingConfig::default(),
+            api: ApiConfig::default(),
+            database: DatabaseConfig::default(),
+            rmq: RmqConfig::default(),
+            federation: FederationConfig::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn parse() -> Result<Self> {
+        let (mut config, config_file) =
+            common::config::parse::<Self>(!cfg!(test), Self::default().config_file)?;
+
+        config.config_file = config_file;
+
+        Ok(config)
+    }
+}
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(i64)]
+pub enum Kind {
+    #[default]
+    GoLive = 0,
+    VodPublished = 1,
+}
+
+impl From<i64> for Kind {
+    fn from(value: i64) -> Self {
+        match value {
+            0 => Self::GoLive,
+            1 => Self::VodPublished,
+            _ => Self::GoLive,
+        }
+    }
+}
+
+impl From<Kind> for i64 {
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::GoLive => 0,
+            Kind::VodPublished => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// An activity published by a channel's actor, kept so the outbox and the activity urls can be fetched.
+pub struct Model {
+    /// The unique identifier for the activity.
+    pub id: Uuid,
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// What happened.
+    pub kind: Kind,
+    /// Foreign key to the streams table.
+    pub stream_id: Uuid,
+    /// The title of the stream when the activity was published.
+    pub title: String,
+    /// The time the activity was published.
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,53 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::activitypub::signature;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+/// The key pair a channel's actor signs its deliveries with.
+pub struct Model {
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The PKCS#8 PEM encoded private key.
+    pub private_key_pem: String,
+    /// The SPKI PEM encoded public key, published on the actor.
+    pub public_key_pem: String,
+    /// The time the key pair was generated.
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn by_channel_id(db: &sqlx::PgPool, channel_id: Uuid) -> sqlx::Result<Option<Model>> {
+    sqlx::query_as!(
+        Model,
+        "SELECT * FROM federation_actors WHERE channel_id = $1",
+        channel_id,
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Returns the key pair of a channel, generating one the first time its actor is requested.
+pub async fn get_or_create(db: &sqlx::PgPool, channel_id: Uuid) -> Result<Model> {
+    if let Some(actor) = by_channel_id(db, channel_id).await? {
+        return Ok(actor);
+    }
+
+    // Generating an RSA key takes a while, so keep it off the async workers.
+    let (private_key_pem, public_key_pem) =
+        tokio::task::spawn_blocking(signature::generate_key_pair).await??;
+
+    // Two requests can race to create the actor, the first key pair wins.
+    sqlx::query!(
+        "INSERT INTO federation_actors (channel_id, private_key_pem, public_key_pem) VALUES ($1, $2, $3) ON CONFLICT (channel_id) DO NOTHING",
+        channel_id,
+        private_key_pem,
+        public_key_pem,
+    )
+    .execute(db)
+    .await?;
+
+    Ok(by_channel_id(db, channel_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("actor not found after insert"))?)
+}
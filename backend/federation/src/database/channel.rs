@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// The parts of a user the federation service publishes about a channel.
+pub struct Model {
+    /// The unique identifier for the user.
+    pub id: Uuid,
+    /// The username of the user, used as the preferred username of the actor.
+    pub username: String,
+    /// The display name of the user.
+    pub display_name: String,
+    /// The title of the stream
+    pub stream_title: String,
+    /// The description of the stream, published as the summary of the actor
+    pub stream_description: String,
+    /// The url of the profile image (empty if the user has none)
+    pub profile_image_url: String,
+    /// The time the user was created.
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn by_username(db: &sqlx::PgPool, username: &str) -> sqlx::Result<Option<Model>> {
+    sqlx::query_as!(
+        Model,
+        "SELECT id, username, display_name, stream_title, stream_description, profile_image_url, created_at FROM users WHERE username = $1",
+        username.to_lowercase(),
+    )
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn by_id(db: &sqlx::PgPool, id: Uuid) -> sqlx::Result<Option<Model>> {
+    sqlx::query_as!(
+        Model,
+        "SELECT id, username, display_name, stream_title, stream_description, profile_image_url, created_at FROM users WHERE id = $1",
+        id,
+    )
+    .fetch_optional(db)
+    .await
+}
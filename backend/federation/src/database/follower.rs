@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+/// A remote actor following a channel.
+pub struct Model {
+    /// Foreign key to the users table.
+    pub channel_id: Uuid,
+    /// The id of the remote actor.
+    pub actor_url: String,
+    /// The inbox of the remote actor.
+    pub inbox_url: String,
+    /// The inbox shared by all actors on the remote server, if it has one.
+    pub shared_inbox_url: Option<String>,
+    /// The time the follow was accepted.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Returns the inboxes to deliver a channel's activities to. Followers on the same server share an inbox
+/// where possible, so the server gets every activity once.
+pub async fn delivery_inboxes(db: &sqlx::PgPool, channel_id: Uuid) -> sqlx::Result<Vec<String>> {
+    sqlx::query_scalar!(
+        r#"SELECT DISTINCT COALESCE(shared_inbox_url, inbox_url) AS "inbox!" FROM federation_followers WHERE channel_id = $1"#,
+        channel_id,
+    )
+    .fetch_all(db)
+    .await
+}
@@ -0,0 +1,4 @@
+pub mod activity;
+pub mod actor;
+pub mod channel;
+pub mod follower;
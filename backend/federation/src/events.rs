@@ -0,0 +1,153 @@
+use std::{pin::pin, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use futures_util::{future::join_all, StreamExt};
+use lapin::{
+    message::Delivery,
+    options::{
+        BasicAckOptions, BasicConsumeOptions, ExchangeDeclareOptions, QueueBindOptions,
+        QueueDeclareOptions,
+    },
+    types::FieldTable,
+    ExchangeKind,
+};
+use prost::Message;
+use tokio::select;
+use uuid::Uuid;
+
+use crate::{
+    activitypub::{delivery, objects},
+    database::{activity, actor, channel, follower},
+    global::GlobalState,
+    pb::scuffle::events::{channel_notification::Kind, ChannelNotification},
+};
+
+/// Consumes the channel notifications the API publishes and delivers the matching activities to the followers.
+/// The queue is shared by all federation instances, so every notification is delivered once.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    let channel = global.rmq.aquire().await?;
+
+    channel
+        .exchange_declare(
+            &global.config.federation.exchange,
+            ExchangeKind::Fanout,
+            ExchangeDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    channel
+        .queue_declare(
+            &global.config.federation.queue,
+            QueueDeclareOptions {
+                durable: true,
+                ..Default::default()
+            },
+            FieldTable::default(),
+        )
+        .await?;
+
+    channel
+        .queue_bind(
+            &global.config.federation.queue,
+            &global.config.federation.exchange,
+            "",
+            QueueBindOptions::default(),
+            FieldTable::default(),
+        )
+        .await?;
+
+    let mut consumer = pin!(global.rmq.basic_consume(
+        &global.config.federation.queue,
+        &global.config.name,
+        BasicConsumeOptions::default(),
+        FieldTable::default()
+    ));
+
+    loop {
+        select! {
+            m = consumer.next() => {
+                let Some(m) = m else {
+                    return Err(anyhow!("rmq stream closed"));
+                };
+
+                tokio::spawn(handle_message(global.clone(), m?));
+            }
+            _ = global.ctx.done() => return Ok(()),
+        }
+    }
+}
+
+async fn handle_message(global: Arc<GlobalState>, delivery: Delivery) {
+    // Like the Discord announcements, activities are not retried when the notification fails.
+    if let Err(e) = handle_notification(&global, &delivery.data).await {
+        tracing::error!("failed to handle channel notification: {:#}", e);
+    }
+
+    if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+        tracing::error!("failed to ack channel notification: {}", e);
+    }
+}
+
+async fn handle_notification(global: &Arc<GlobalState>, data: &[u8]) -> Result<()> {
+    let notification = ChannelNotification::decode(data)?;
+
+    let kind = match Kind::from_i32(notification.kind) {
+        Some(Kind::GoLive) => activity::Kind::GoLive,
+        Some(Kind::VodPublished) => activity::Kind::VodPublished,
+        // Going offline is not worth a post on the followers' timelines.
+        Some(Kind::Offline) => return Ok(()),
+        None => return Err(anyhow!("unknown notification kind: {}", notification.kind)),
+    };
+
+    let channel_id = notification.channel_id.parse::<Uuid>()?;
+    let stream_id = notification.stream_id.parse::<Uuid>()?;
+
+    // Channels nobody looked up over ActivityPub have no actor, and nobody to deliver to.
+    let Some(actor) = actor::by_channel_id(&global.db, channel_id).await? else {
+        return Ok(());
+    };
+
+    let Some(channel) = channel::by_id(&global.db, channel_id).await? else {
+        return Ok(());
+    };
+
+    let title = sqlx::query_scalar!("SELECT title FROM streams WHERE id = $1", stream_id)
+        .fetch_optional(&*global.db)
+        .await?
+        .unwrap_or_else(|| channel.stream_title.clone());
+
+    let activity = sqlx::query_as!(
+        activity::Model,
+        "INSERT INTO federation_activities (channel_id, kind, stream_id, title) VALUES ($1, $2, $3, $4) RETURNING *",
+        channel_id,
+        i64::from(kind),
+        stream_id,
+        title,
+    )
+    .fetch_one(&*global.db)
+    .await?;
+
+    let urls = global.urls();
+    let create = objects::create(&urls, &channel, &activity);
+    let key_id = urls.key(&channel.username);
+
+    let inboxes = follower::delivery_inboxes(&global.db, channel_id).await?;
+    let results = join_all(
+        inboxes
+            .iter()
+            .map(|inbox| delivery::deliver(global, &actor, &key_id, inbox, &create)),
+    )
+    .await;
+
+    for (inbox, result) in inboxes.iter().zip(results) {
+        if let Err(e) = result {
+            tracing::warn!(inbox, "failed to deliver activity: {:#}", e);
+        }
+    }
+
+    Ok(())
+}
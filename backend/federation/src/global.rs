@@ -0,0 +1,57 @@
+use std::{sync::Arc, time::Duration};
+
+use common::context::Context;
+
+use crate::{
+    activitypub::{delivery::PublicResolver, objects::Urls},
+    config::AppConfig,
+};
+
+pub struct GlobalState {
+    pub config: AppConfig,
+    pub db: Arc<sqlx::PgPool>,
+    pub ctx: Context,
+    pub rmq: common::rmq::ConnectionPool,
+    /// Used to fetch remote actors and deliver activities to their inboxes
+    pub http: reqwest::Client,
+}
+
+impl GlobalState {
+    pub fn new(
+        config: AppConfig,
+        db: Arc<sqlx::PgPool>,
+        rmq: common::rmq::ConnectionPool,
+        ctx: Context,
+    ) -> Self {
+        // Redirects are not followed, they could lead to an address which was never checked.
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("scuffle-federation/", env!("CARGO_PKG_VERSION")))
+            .dns_resolver(Arc::new(PublicResolver))
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(Duration::from_secs(
+                config.federation.request_timeout as u64,
+            ))
+            .build()
+            .expect("failed to build http client");
+
+        Self {
+            config,
+            db,
+            ctx,
+            rmq,
+            http,
+        }
+    }
+
+    /// The public url of this service without a trailing slash.
+    pub fn public_url(&self) -> &str {
+        self.config.federation.public_url.trim_end_matches('/')
+    }
+
+    pub fn urls(&self) -> Urls<'_> {
+        Urls {
+            public_url: self.public_url(),
+            website_url: self.config.federation.website_url.trim_end_matches('/'),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Context as _, Result};
+use common::{context::Context, logging, prelude::FutureTimeout, signal};
+use tokio::{select, signal::unix::SignalKind, time};
+
+mod activitypub;
+mod api;
+mod config;
+mod database;
+mod events;
+mod global;
+mod pb;
+
+#[cfg(test)]
+mod tests;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let config = config::AppConfig::parse()?;
+    logging::init(&config.logging.level, config.logging.mode)?;
+
+    if let Some(file) = &config.config_file {
+        tracing::info!(file = file, "loaded config from file");
+    }
+
+    tracing::debug!("config: {:#?}", config);
+
+    let db = Arc::new(common::database::connect(&config.database).await?);
+
+    let (ctx, handler) = Context::new();
+
+    let rmq = common::rmq::ConnectionPool::connect(
+        config.rmq.uri.clone(),
+        lapin::ConnectionProperties::default(),
+        Duration::from_secs(30),
+        1,
+    )
+    .timeout(Duration::from_secs(5))
+    .await
+    .context("failed to connect to rabbitmq, timedout")?
+    .context("failed to connect to rabbitmq")?;
+
+    let global = Arc::new(global::GlobalState::new(config, db, rmq, ctx));
+
+    tokio::spawn(common::database::monitor(
+        global.ctx.clone(),
+        global.db.clone(),
+        global.config.database.clone(),
+    ));
+
+    let api_future = tokio::spawn(api::run(global.clone()));
+    let events_future = tokio::spawn(events::run(global.clone()));
+
+    // Listen on both sigint and sigterm and cancel the context when either is received
+    let mut signal_handler = signal::SignalHandler::new()
+        .with_signal(SignalKind::interrupt())
+        .with_signal(SignalKind::terminate());
+
+    select! {
+        r = api_future => tracing::error!("api stopped unexpectedly: {:?}", r),
+        r = events_future => tracing::error!("events stopped unexpectedly: {:?}", r),
+        r = global.rmq.handle_reconnects() => tracing::error!("rmq stopped unexpectedly: {:?}", r),
+        _ = signal_handler.recv() => tracing::info!("shutting down"),
+    }
+
+    // We cannot have a context in scope when we cancel the handler, otherwise it will deadlock.
+    drop(global);
+
+    // Cancel the context
+    tracing::info!("waiting for tasks to finish");
+
+    select! {
+        _ = time::sleep(Duration::from_secs(60)) => tracing::warn!("force shutting down"),
+        _ = signal_handler.recv() => tracing::warn!("force shutting down"),
+        _ = handler.cancel() => tracing::info!("shutting down"),
+    }
+
+    Ok(())
+}
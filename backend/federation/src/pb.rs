@@ -0,0 +1,5 @@
+pub mod scuffle {
+    pub mod events {
+        tonic::include_proto!("scuffle.events");
+    }
+}
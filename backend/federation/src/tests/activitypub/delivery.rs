@@ -0,0 +1,11 @@
+use reqwest::dns::Resolve;
+
+use crate::activitypub::delivery::PublicResolver;
+
+#[tokio::test]
+async fn test_public_resolver() {
+    assert!(PublicResolver
+        .resolve("localhost".parse().unwrap())
+        .await
+        .is_err());
+}
@@ -0,0 +1,3 @@
+mod delivery;
+mod objects;
+mod signature;
@@ -0,0 +1,156 @@
+use chrono::Utc;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    activitypub::objects::{
+        accept, create, domain, escape_html, note_content, object_id, webfinger_username, Urls,
+        PUBLIC,
+    },
+    database::{activity, channel},
+};
+
+const URLS: Urls = Urls {
+    public_url: "https://fed.scuffle.tv",
+    website_url: "https://scuffle.tv",
+};
+
+fn channel() -> channel::Model {
+    channel::Model {
+        id: Uuid::new_v4(),
+        username: "troy".to_string(),
+        display_name: "Troy".to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_domain() {
+    let tests = vec![
+        ("https://fed.scuffle.tv", Some("fed.scuffle.tv")),
+        ("https://fed.scuffle.tv/", Some("fed.scuffle.tv")),
+        ("http://localhost:4100", Some("localhost:4100")),
+        ("https://fed.scuffle.tv:443", Some("fed.scuffle.tv")),
+        ("not a url", None),
+    ];
+
+    for (url, expected) in tests {
+        assert_eq!(domain(url).as_deref(), expected, "{}", url);
+    }
+}
+
+#[test]
+fn test_webfinger_username() {
+    let tests = vec![
+        ("acct:troy@fed.scuffle.tv", Some("troy")),
+        ("acct:@troy@fed.scuffle.tv", Some("troy")),
+        ("acct:troy@FED.scuffle.tv", Some("troy")),
+        ("acct:troy@mastodon.social", None),
+        ("acct:@fed.scuffle.tv", None),
+        ("troy@fed.scuffle.tv", None),
+        ("https://fed.scuffle.tv/users/troy", None),
+    ];
+
+    for (resource, expected) in tests {
+        assert_eq!(
+            webfinger_username(resource, "fed.scuffle.tv"),
+            expected,
+            "{}",
+            resource
+        );
+    }
+}
+
+#[test]
+fn test_note_content() {
+    let channel = channel();
+    let mut activity = activity::Model {
+        id: Uuid::new_v4(),
+        channel_id: channel.id,
+        kind: activity::Kind::GoLive,
+        stream_id: Uuid::new_v4(),
+        title: "<b>Ranked</b> & chill".to_string(),
+        created_at: Utc::now(),
+    };
+
+    assert_eq!(
+        note_content(&URLS, &channel, &activity),
+        r#"<p>Troy is now live: &lt;b&gt;Ranked&lt;/b&gt; &amp; chill</p><p><a href="https://scuffle.tv/troy">https://scuffle.tv/troy</a></p>"#
+    );
+
+    activity.kind = activity::Kind::VodPublished;
+    activity.title = "Ranked".to_string();
+    assert_eq!(
+        note_content(&URLS, &channel, &activity),
+        r#"<p>The recording of Ranked is now available</p><p><a href="https://scuffle.tv/troy">https://scuffle.tv/troy</a></p>"#
+    );
+}
+
+#[test]
+fn test_create() {
+    let channel = channel();
+    let activity = activity::Model {
+        id: Uuid::new_v4(),
+        channel_id: channel.id,
+        kind: activity::Kind::GoLive,
+        stream_id: Uuid::new_v4(),
+        title: "Ranked".to_string(),
+        created_at: Utc::now(),
+    };
+
+    let create = create(&URLS, &channel, &activity);
+
+    assert_eq!(
+        create["id"],
+        format!("https://fed.scuffle.tv/activities/{}", activity.id)
+    );
+    assert_eq!(create["actor"], "https://fed.scuffle.tv/users/troy");
+    assert_eq!(create["to"], json!([PUBLIC]));
+    assert_eq!(
+        create["cc"],
+        json!(["https://fed.scuffle.tv/users/troy/followers"])
+    );
+    assert_eq!(create["object"]["type"], "Note");
+    assert_eq!(create["object"]["attributedTo"], create["actor"]);
+}
+
+#[test]
+fn test_accept() {
+    let follow = json!({
+        "id": "https://mastodon.social/1234",
+        "type": "Follow",
+        "actor": "https://mastodon.social/users/alice",
+        "object": "https://fed.scuffle.tv/users/troy",
+    });
+
+    let accept = accept(&URLS, "troy", follow.clone());
+
+    assert_eq!(accept["type"], "Accept");
+    assert_eq!(accept["actor"], "https://fed.scuffle.tv/users/troy");
+    assert_eq!(accept["object"], follow);
+}
+
+#[test]
+fn test_object_id() {
+    let tests = vec![
+        (json!("https://a.example/1"), Some("https://a.example/1")),
+        (
+            json!({ "id": "https://a.example/2", "type": "Follow" }),
+            Some("https://a.example/2"),
+        ),
+        (json!({ "type": "Follow" }), None),
+        (json!(null), None),
+    ];
+
+    for (object, expected) in tests {
+        assert_eq!(object_id(&object), expected, "{}", object);
+    }
+}
+
+#[test]
+fn test_escape_html() {
+    assert_eq!(
+        escape_html(r#"<a href="x">'&'</a>"#),
+        "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+    );
+}
@@ -0,0 +1,101 @@
+use crate::activitypub::signature::{
+    digest, generate_key_pair, sign, signing_string, verify, SignatureHeader, SIGNED_HEADERS,
+};
+
+#[test]
+fn test_digest() {
+    assert_eq!(
+        digest(b""),
+        "SHA-256=47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+    );
+}
+
+#[test]
+fn test_signing_string() {
+    let header = |name: &str| match name {
+        "host" => Some("example.com"),
+        "date" => Some("Tue, 07 Jun 2022 20:51:35 GMT"),
+        "digest" => Some(" SHA-256=abc "),
+        _ => None,
+    };
+
+    assert_eq!(
+        signing_string(&SIGNED_HEADERS, "POST", "/users/troy/inbox", header).unwrap(),
+        "(request-target): post /users/troy/inbox\nhost: example.com\ndate: Tue, 07 Jun 2022 20:51:35 GMT\ndigest: SHA-256=abc"
+    );
+
+    assert!(signing_string(&["content-type"], "POST", "/", header).is_err());
+}
+
+#[test]
+fn test_sign_verify() {
+    let (private_key_pem, public_key_pem) = generate_key_pair().unwrap();
+    let (_, other_public_key_pem) = generate_key_pair().unwrap();
+
+    let signature = sign(&private_key_pem, "(request-target): post /inbox").unwrap();
+
+    assert!(verify(&public_key_pem, "(request-target): post /inbox", &signature).is_ok());
+    assert!(verify(
+        &public_key_pem,
+        "(request-target): post /outbox",
+        &signature
+    )
+    .is_err());
+    assert!(verify(
+        &other_public_key_pem,
+        "(request-target): post /inbox",
+        &signature
+    )
+    .is_err());
+}
+
+#[test]
+fn test_signature_header() {
+    let header = SignatureHeader::new(
+        "https://example.com/users/troy#main-key".to_string(),
+        vec![1, 2, 3],
+    );
+    assert_eq!(
+        header.to_header_value(),
+        r#"keyId="https://example.com/users/troy#main-key",algorithm="rsa-sha256",headers="(request-target) host date digest",signature="AQID""#
+    );
+    assert_eq!(
+        SignatureHeader::parse(&header.to_header_value()).unwrap(),
+        header
+    );
+
+    let tests = vec![
+        (
+            r#"keyId="https://a.example/actor#key", headers="(request-target) Host Date", signature="AQID""#,
+            Some(vec!["(request-target)", "host", "date"]),
+        ),
+        (
+            r#"keyId="https://a.example/actor#key",signature="AQID""#,
+            Some(vec!["date"]),
+        ),
+        (
+            r#"keyId="https://a.example/actor#key",algorithm="hs2019",signature="AQID""#,
+            Some(vec!["date"]),
+        ),
+        (
+            r#"keyId="https://a.example/actor#key",algorithm="hmac-sha256",signature="AQID""#,
+            None,
+        ),
+        (r#"headers="date",signature="AQID""#, None),
+        (r#"keyId="https://a.example/actor#key""#, None),
+        (
+            r#"keyId=https://a.example/actor#key,signature="AQID""#,
+            None,
+        ),
+    ];
+
+    for (value, expected) in tests {
+        let parsed = SignatureHeader::parse(value).ok().map(|h| h.headers);
+        assert_eq!(
+            parsed,
+            expected.map(|h| h.into_iter().map(String::from).collect()),
+            "{}",
+            value
+        );
+    }
+}
@@ -0,0 +1,2 @@
+mod activitypub;
+mod config;
@@ -0,0 +1,19 @@
+CREATE TABLE channel_events (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    user_id uuid DEFAULT NULL, -- foreign key to users(id), NULL = anonymous
+    kind int NOT NULL, -- 0 = follow, 1 = subscription, 2 = raid, 3 = cheer
+    amount bigint NOT NULL DEFAULT 0, -- subscription months, raid viewers or cheered bits
+    message text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE INDEX channel_events_channel_id_created_at_idx ON channel_events (channel_id, created_at);
+
+-- Foreign keys
+
+ALTER TABLE channel_events ADD CONSTRAINT channel_events_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE channel_events ADD CONSTRAINT channel_events_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1,25 @@
+CREATE TABLE stream_sessions (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    stream_id uuid NOT NULL, -- foreign key to streams(id)
+    peak_viewers bigint NOT NULL DEFAULT 0,
+    average_viewers bigint NOT NULL DEFAULT 0,
+    unique_chatters bigint NOT NULL DEFAULT 0,
+    new_follows bigint NOT NULL DEFAULT 0,
+    -- Timestamps
+    started_at timestamptz NOT NULL,
+    ended_at timestamptz NOT NULL
+);
+
+-- Indexes
+
+CREATE INDEX stream_sessions_channel_id_ended_at_idx ON stream_sessions (channel_id, ended_at);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS stream_sessions ADD CONSTRAINT stream_sessions_stream_id_unique UNIQUE (stream_id);
+
+-- Foreign keys
+
+ALTER TABLE stream_sessions ADD CONSTRAINT stream_sessions_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE stream_sessions ADD CONSTRAINT stream_sessions_stream_id_fkey FOREIGN KEY (stream_id) REFERENCES streams(id) ON DELETE CASCADE;
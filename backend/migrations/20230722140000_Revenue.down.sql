@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS revenue_transactions CASCADE;
+DROP TABLE IF EXISTS payout_ledger_entries CASCADE;
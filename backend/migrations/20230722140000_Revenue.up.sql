@@ -0,0 +1,26 @@
+CREATE TABLE revenue_transactions (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- references users(id), kept when the user is deleted
+    user_id uuid DEFAULT NULL, -- references users(id), the paying user
+    kind int NOT NULL, -- 0 = subscription, 1 = bits, 2 = gift
+    gross_amount bigint NOT NULL CHECK (gross_amount >= 0), -- in cents
+    platform_fee bigint NOT NULL CHECK (platform_fee >= 0 AND platform_fee <= gross_amount), -- in cents
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Entries are only ever inserted, corrections are made by inserting a new entry.
+CREATE TABLE payout_ledger_entries (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- references users(id), kept when the user is deleted
+    amount bigint NOT NULL, -- in cents, positive = credited to the channel, negative = paid out
+    description text NOT NULL,
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE INDEX revenue_transactions_channel_id_created_at_idx ON revenue_transactions (channel_id, created_at);
+
+CREATE INDEX payout_ledger_entries_channel_id_created_at_idx ON payout_ledger_entries (channel_id, created_at);
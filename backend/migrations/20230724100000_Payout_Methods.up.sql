@@ -0,0 +1,25 @@
+CREATE TABLE payout_methods (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    provider varchar(32) NOT NULL,
+    provider_account_id varchar(255) NOT NULL,
+    status int NOT NULL DEFAULT 0, -- 0 = pending verification, 1 = verified, 2 = blocked
+    review_note text NOT NULL DEFAULT '',
+    reviewed_by uuid DEFAULT NULL, -- foreign key to users(id)
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    reviewed_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE INDEX payout_methods_status_idx ON payout_methods (status);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS payout_methods ADD CONSTRAINT payout_methods_channel_id_unique UNIQUE (channel_id);
+
+-- Foreign keys
+
+ALTER TABLE payout_methods ADD CONSTRAINT payout_methods_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE payout_methods ADD CONSTRAINT payout_methods_reviewed_by_fkey FOREIGN KEY (reviewed_by) REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1,26 @@
+CREATE TABLE promotions (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    name varchar(64) NOT NULL,
+    target int NOT NULL, -- 0 = subscriptions, 1 = gift subscriptions
+    percent_off int NOT NULL CHECK (percent_off > 0 AND percent_off <= 100),
+    first_month_only boolean NOT NULL DEFAULT FALSE, -- only applies to users who never subscribed to the channel
+    channel_id uuid DEFAULT NULL, -- foreign key to users(id), NULL = all channels
+    created_by uuid DEFAULT NULL, -- foreign key to users(id)
+    -- Timestamps
+    starts_at timestamptz NOT NULL,
+    ends_at timestamptz NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE INDEX promotions_starts_at_ends_at_idx ON promotions (starts_at, ends_at);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS promotions ADD CONSTRAINT promotions_ends_after_start CHECK (ends_at > starts_at);
+
+-- Foreign keys
+
+ALTER TABLE promotions ADD CONSTRAINT promotions_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE promotions ADD CONSTRAINT promotions_created_by_fkey FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL;
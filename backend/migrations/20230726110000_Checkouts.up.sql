@@ -0,0 +1,25 @@
+CREATE TABLE checkouts (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    user_id uuid NOT NULL, -- foreign key to users(id), the paying user
+    recipient_id uuid DEFAULT NULL, -- foreign key to users(id), set for gift subscriptions
+    kind int NOT NULL, -- 0 = subscription, 1 = gift subscription
+    amount bigint NOT NULL CHECK (amount >= 0), -- in cents, after promotions
+    promotion_id uuid DEFAULT NULL, -- foreign key to promotions(id)
+    provider_checkout_id varchar(255) NOT NULL DEFAULT '',
+    status int NOT NULL DEFAULT 0, -- 0 = pending, 1 = completed, 2 = failed
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    completed_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE INDEX checkouts_user_id_created_at_idx ON checkouts (user_id, created_at);
+
+-- Foreign keys
+
+ALTER TABLE checkouts ADD CONSTRAINT checkouts_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE checkouts ADD CONSTRAINT checkouts_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE checkouts ADD CONSTRAINT checkouts_recipient_id_fkey FOREIGN KEY (recipient_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE checkouts ADD CONSTRAINT checkouts_promotion_id_fkey FOREIGN KEY (promotion_id) REFERENCES promotions(id) ON DELETE SET NULL;
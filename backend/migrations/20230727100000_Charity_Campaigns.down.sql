@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS charity_donations CASCADE;
+DROP TABLE IF EXISTS charity_campaigns CASCADE;
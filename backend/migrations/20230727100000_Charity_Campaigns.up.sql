@@ -0,0 +1,37 @@
+CREATE TABLE charity_campaigns (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    title varchar(128) NOT NULL,
+    beneficiary varchar(128) NOT NULL,
+    target_amount bigint NOT NULL CHECK (target_amount > 0), -- in cents
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    ended_at timestamptz DEFAULT NULL
+);
+
+-- Donations go to the beneficiary and are kept out of the channel's revenue and payout ledger.
+CREATE TABLE charity_donations (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    campaign_id uuid NOT NULL, -- foreign key to charity_campaigns(id)
+    user_id uuid DEFAULT NULL, -- foreign key to users(id)
+    amount bigint NOT NULL CHECK (amount > 0), -- in cents
+    message text NOT NULL DEFAULT '',
+    provider_checkout_id varchar(255) NOT NULL DEFAULT '',
+    status int NOT NULL DEFAULT 0, -- 0 = pending, 1 = completed, 2 = failed
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    completed_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+-- A channel can only run one campaign at a time.
+CREATE UNIQUE INDEX charity_campaigns_channel_id_active_idx ON charity_campaigns (channel_id) WHERE ended_at IS NULL;
+
+CREATE INDEX charity_donations_campaign_id_status_idx ON charity_donations (campaign_id, status);
+
+-- Foreign keys
+
+ALTER TABLE charity_campaigns ADD CONSTRAINT charity_campaigns_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE charity_donations ADD CONSTRAINT charity_donations_campaign_id_fkey FOREIGN KEY (campaign_id) REFERENCES charity_campaigns(id) ON DELETE CASCADE;
+ALTER TABLE charity_donations ADD CONSTRAINT charity_donations_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL;
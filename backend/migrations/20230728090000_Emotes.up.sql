@@ -0,0 +1,28 @@
+CREATE TABLE emotes (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    name varchar(32) NOT NULL,
+    image_url text NOT NULL,
+    width int NOT NULL,
+    height int NOT NULL,
+    nsfw_score int NOT NULL DEFAULT 0, -- 0 - 100, as reported by the image classifier
+    status int NOT NULL DEFAULT 0, -- 0 = pending review, 1 = approved, 2 = rejected
+    review_note text NOT NULL DEFAULT '',
+    reviewed_by uuid DEFAULT NULL, -- foreign key to users(id), NULL if rejected by the automatic checks
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    reviewed_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE INDEX emotes_status_created_at_idx ON emotes (status, created_at);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS emotes ADD CONSTRAINT emotes_channel_id_name_unique UNIQUE (channel_id, name);
+
+-- Foreign keys
+
+ALTER TABLE emotes ADD CONSTRAINT emotes_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE emotes ADD CONSTRAINT emotes_reviewed_by_fkey FOREIGN KEY (reviewed_by) REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS emote_usage_daily CASCADE;
@@ -0,0 +1,16 @@
+CREATE TABLE emote_usage_daily (
+    emote_id uuid NOT NULL, -- foreign key to emotes(id)
+    channel_id uuid NOT NULL, -- foreign key to users(id), the channel the emote was used in
+    day timestamptz NOT NULL, -- the start of the day (UTC)
+    uses bigint NOT NULL DEFAULT 0,
+    PRIMARY KEY (emote_id, channel_id, day)
+);
+
+-- Indexes
+
+CREATE INDEX emote_usage_daily_channel_id_day_idx ON emote_usage_daily (channel_id, day);
+
+-- Foreign keys
+
+ALTER TABLE emote_usage_daily ADD CONSTRAINT emote_usage_daily_emote_id_fkey FOREIGN KEY (emote_id) REFERENCES emotes(id) ON DELETE CASCADE;
+ALTER TABLE emote_usage_daily ADD CONSTRAINT emote_usage_daily_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
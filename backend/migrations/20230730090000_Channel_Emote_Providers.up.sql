@@ -0,0 +1,12 @@
+CREATE TABLE channel_emote_providers (
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    provider varchar(32) NOT NULL, -- one of the providers in the emote config
+    external_id varchar(255) NOT NULL, -- the id of the channel's emote set at the provider
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (channel_id, provider)
+);
+
+-- Foreign keys
+
+ALTER TABLE channel_emote_providers ADD CONSTRAINT channel_emote_providers_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,3 @@
+ALTER TABLE checkouts DROP COLUMN IF EXISTS message;
+
+DROP TABLE IF EXISTS cheermote_tiers CASCADE;
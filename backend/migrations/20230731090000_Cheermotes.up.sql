@@ -0,0 +1,24 @@
+CREATE TABLE cheermote_tiers (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid DEFAULT NULL, -- foreign key to users(id), NULL = default tiers for all channels
+    prefix varchar(32) NOT NULL,
+    min_bits bigint NOT NULL CHECK (min_bits > 0), -- the tier is used for cheers of at least this many bits
+    color varchar(7) NOT NULL, -- hex color, e.g. #9146ff
+    image_url text NOT NULL, -- the animated image, processed by the image processor
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE checkouts ADD COLUMN message text NOT NULL DEFAULT ''; -- the cheer message, checkout kind 2 = cheer
+
+-- Indexes
+
+CREATE INDEX cheermote_tiers_channel_id_idx ON cheermote_tiers (channel_id);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS cheermote_tiers ADD CONSTRAINT cheermote_tiers_channel_id_min_bits_unique UNIQUE (channel_id, min_bits);
+
+-- Foreign keys
+
+ALTER TABLE cheermote_tiers ADD CONSTRAINT cheermote_tiers_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS polls CASCADE;
+DROP TABLE IF EXISTS ad_breaks CASCADE;
+DROP TABLE IF EXISTS stream_markers CASCADE;
+DROP TABLE IF EXISTS personal_access_tokens CASCADE;
+
+ALTER TABLE users DROP COLUMN IF EXISTS stream_category;
@@ -0,0 +1,60 @@
+ALTER TABLE users ADD COLUMN stream_category varchar(64) NOT NULL DEFAULT '';
+
+CREATE TABLE personal_access_tokens (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    name varchar(64) NOT NULL,
+    token_hash varchar(64) NOT NULL, -- sha256 of the token as hex, the token itself is only shown once
+    scopes bigint NOT NULL DEFAULT 0,
+    -- Timestamps
+    expires_at timestamptz DEFAULT NULL, -- NULL = never expires
+    last_used_at timestamptz DEFAULT NULL,
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE stream_markers (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    stream_id uuid NOT NULL, -- foreign key to streams(id)
+    description varchar(140) NOT NULL DEFAULT '',
+    position_seconds bigint NOT NULL, -- seconds since the stream started
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE ad_breaks (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    duration_seconds bigint NOT NULL CHECK (duration_seconds > 0),
+    -- Timestamps
+    started_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE polls (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    title varchar(140) NOT NULL,
+    options text[] NOT NULL,
+    -- Timestamps
+    ends_at timestamptz NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE INDEX personal_access_tokens_user_id_idx ON personal_access_tokens (user_id);
+CREATE INDEX stream_markers_stream_id_idx ON stream_markers (stream_id);
+CREATE INDEX ad_breaks_channel_id_started_at_idx ON ad_breaks (channel_id, started_at);
+CREATE INDEX polls_channel_id_ends_at_idx ON polls (channel_id, ends_at);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS personal_access_tokens ADD CONSTRAINT personal_access_tokens_token_hash_unique UNIQUE (token_hash);
+
+-- Foreign keys
+
+ALTER TABLE personal_access_tokens ADD CONSTRAINT personal_access_tokens_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE stream_markers ADD CONSTRAINT stream_markers_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE stream_markers ADD CONSTRAINT stream_markers_stream_id_fkey FOREIGN KEY (stream_id) REFERENCES streams(id) ON DELETE CASCADE;
+ALTER TABLE ad_breaks ADD CONSTRAINT ad_breaks_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE polls ADD CONSTRAINT polls_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
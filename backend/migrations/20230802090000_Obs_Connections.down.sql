@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS obs_mappings CASCADE;
+DROP TABLE IF EXISTS obs_connections CASCADE;
@@ -0,0 +1,39 @@
+CREATE TABLE obs_connections (
+    channel_id uuid PRIMARY KEY, -- foreign key to users(id)
+    host varchar(255) NOT NULL,
+    port int NOT NULL CHECK (port > 0 AND port < 65536),
+    password_encrypted bytea DEFAULT NULL, -- NULL = OBS has no password set
+    sync_scenes boolean NOT NULL DEFAULT TRUE,
+    sync_profiles boolean NOT NULL DEFAULT FALSE,
+    update_on_go_live boolean NOT NULL DEFAULT TRUE,
+    status int NOT NULL DEFAULT 0, -- 0 = connecting, 1 = connected, 2 = failed
+    last_error text NOT NULL DEFAULT '',
+    -- Timestamps
+    connected_at timestamptz DEFAULT NULL,
+    updated_at timestamptz NOT NULL DEFAULT NOW(),
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE obs_mappings (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    kind int NOT NULL, -- 0 = scene, 1 = profile
+    source_name varchar(255) NOT NULL, -- the name of the scene or profile in OBS
+    category varchar(64) NOT NULL,
+    title varchar(255) DEFAULT NULL, -- NULL = keep the current title
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE INDEX obs_mappings_channel_id_idx ON obs_mappings (channel_id);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS obs_mappings ADD CONSTRAINT obs_mappings_channel_id_kind_source_name_unique UNIQUE (channel_id, kind, source_name);
+
+-- Foreign keys
+
+ALTER TABLE obs_connections ADD CONSTRAINT obs_connections_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE obs_mappings ADD CONSTRAINT obs_mappings_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
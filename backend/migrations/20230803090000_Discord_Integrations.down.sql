@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS discord_announcements CASCADE;
+DROP TABLE IF EXISTS discord_integrations CASCADE;
@@ -0,0 +1,27 @@
+CREATE TABLE discord_integrations (
+    channel_id uuid PRIMARY KEY, -- foreign key to users(id)
+    webhook_url_encrypted bytea DEFAULT NULL, -- NULL = announcements are posted by a bot
+    bot_token_encrypted bytea DEFAULT NULL, -- NULL = announcements are posted through the webhook
+    bot_channel_id varchar(32) DEFAULT NULL, -- the discord channel the bot posts in
+    last_error text NOT NULL DEFAULT '',
+    -- Timestamps
+    updated_at timestamptz NOT NULL DEFAULT NOW(),
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE discord_announcements (
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    kind int NOT NULL, -- 0 = go live, 1 = offline, 2 = vod published
+    enabled boolean NOT NULL DEFAULT TRUE,
+    template varchar(2000) NOT NULL,
+    PRIMARY KEY (channel_id, kind)
+);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS discord_integrations ADD CONSTRAINT discord_integrations_target_check CHECK ((webhook_url_encrypted IS NOT NULL) != (bot_token_encrypted IS NOT NULL AND bot_channel_id IS NOT NULL));
+
+-- Foreign keys
+
+ALTER TABLE discord_integrations ADD CONSTRAINT discord_integrations_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE discord_announcements ADD CONSTRAINT discord_announcements_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
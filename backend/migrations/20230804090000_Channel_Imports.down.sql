@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS channel_imports CASCADE;
+DROP TABLE IF EXISTS channel_schedule_segments CASCADE;
+DROP TABLE IF EXISTS channel_panels CASCADE;
+
+ALTER TABLE users DROP COLUMN IF EXISTS profile_image_url;
@@ -0,0 +1,54 @@
+ALTER TABLE users ADD COLUMN profile_image_url varchar(512) NOT NULL DEFAULT '';
+
+CREATE TABLE channel_panels (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    position int NOT NULL,
+    title varchar(64) NOT NULL,
+    description text NOT NULL,
+    image_url varchar(512) DEFAULT NULL,
+    link_url varchar(512) DEFAULT NULL,
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE channel_schedule_segments (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    title varchar(255) NOT NULL,
+    category varchar(64) NOT NULL DEFAULT '',
+    recurring boolean NOT NULL DEFAULT FALSE,
+    -- Timestamps
+    starts_at timestamptz NOT NULL,
+    ends_at timestamptz DEFAULT NULL, -- NULL = open ended
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE channel_imports (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    platform int NOT NULL, -- 0 = twitch, 1 = youtube
+    access_token_encrypted bytea DEFAULT NULL, -- removed once the import has finished
+    status int NOT NULL DEFAULT 0, -- 0 = queued, 1 = running, 2 = completed, 3 = failed
+    step int NOT NULL DEFAULT 0, -- 0 = fetching, 1 = profile image, 2 = panels, 3 = schedule, 4 = emotes
+    total_items int NOT NULL DEFAULT 0,
+    imported_items int NOT NULL DEFAULT 0,
+    skipped_items int NOT NULL DEFAULT 0,
+    error text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    updated_at timestamptz NOT NULL DEFAULT NOW(),
+    completed_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE INDEX channel_panels_channel_id_idx ON channel_panels (channel_id, position);
+CREATE INDEX channel_schedule_segments_channel_id_starts_at_idx ON channel_schedule_segments (channel_id, starts_at);
+CREATE INDEX channel_imports_channel_id_created_at_idx ON channel_imports (channel_id, created_at DESC);
+
+-- Foreign keys
+
+ALTER TABLE channel_panels ADD CONSTRAINT channel_panels_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE channel_schedule_segments ADD CONSTRAINT channel_schedule_segments_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE channel_imports ADD CONSTRAINT channel_imports_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
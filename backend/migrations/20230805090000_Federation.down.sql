@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS federation_activities CASCADE;
+DROP TABLE IF EXISTS federation_followers CASCADE;
+DROP TABLE IF EXISTS federation_actors CASCADE;
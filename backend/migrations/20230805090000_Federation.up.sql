@@ -0,0 +1,35 @@
+CREATE TABLE federation_actors (
+    channel_id uuid PRIMARY KEY, -- foreign key to users(id)
+    private_key_pem text NOT NULL, -- PKCS#8 RSA key used to sign deliveries
+    public_key_pem text NOT NULL,
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE federation_followers (
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    actor_url varchar(2048) NOT NULL, -- the id of the remote actor
+    inbox_url varchar(2048) NOT NULL,
+    shared_inbox_url varchar(2048) DEFAULT NULL, -- NULL = the remote server has no shared inbox
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (channel_id, actor_url)
+);
+
+CREATE TABLE federation_activities (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    kind int NOT NULL, -- 0 = go live, 1 = vod published
+    stream_id uuid NOT NULL, -- foreign key to streams(id)
+    title text NOT NULL, -- the stream title when the activity was published
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE INDEX federation_activities_channel_id_created_at_idx ON federation_activities (channel_id, created_at DESC);
+
+-- Foreign keys
+
+ALTER TABLE federation_actors ADD CONSTRAINT federation_actors_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE federation_followers ADD CONSTRAINT federation_followers_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE federation_activities ADD CONSTRAINT federation_activities_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE federation_activities ADD CONSTRAINT federation_activities_stream_id_fkey FOREIGN KEY (stream_id) REFERENCES streams(id) ON DELETE CASCADE;
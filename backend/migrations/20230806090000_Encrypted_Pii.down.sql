@@ -0,0 +1,3 @@
+-- Encrypted rows have to be decrypted before, they don't fit into the old columns.
+ALTER TABLE payout_methods ALTER COLUMN provider_account_id TYPE varchar(255);
+ALTER TABLE users ALTER COLUMN email TYPE varchar(255);
@@ -0,0 +1,3 @@
+-- Encrypted values (enc:<key id>:<base64 ciphertext>) are longer than the plaintext they replace.
+ALTER TABLE users ALTER COLUMN email TYPE text;
+ALTER TABLE payout_methods ALTER COLUMN provider_account_id TYPE text;
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS moderation_jobs CASCADE;
+DROP TABLE IF EXISTS chat_bans CASCADE;
+
+DROP INDEX IF EXISTS chat_messages_channel_id_created_at_idx;
@@ -0,0 +1,42 @@
+CREATE TABLE chat_bans (
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    banned_by uuid DEFAULT NULL, -- foreign key to users(id)
+    reason varchar(255) NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (channel_id, user_id)
+);
+
+CREATE TABLE moderation_jobs (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    created_by uuid NOT NULL, -- foreign key to users(id)
+    kind int NOT NULL, -- 0 = ban users, 1 = delete messages
+    status int NOT NULL DEFAULT 0, -- 0 = queued, 1 = running, 2 = completed, 3 = failed
+    user_ids uuid[] NOT NULL DEFAULT '{}', -- the users to ban
+    reason varchar(255) NOT NULL DEFAULT '',
+    pattern varchar(255) NOT NULL DEFAULT '', -- the text of the messages to delete
+    since timestamptz DEFAULT NULL, -- messages sent before are kept
+    total_items int NOT NULL DEFAULT 0,
+    processed_items int NOT NULL DEFAULT 0,
+    error text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    updated_at timestamptz NOT NULL DEFAULT NOW(),
+    completed_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE INDEX chat_bans_user_id_idx ON chat_bans (user_id);
+CREATE INDEX chat_messages_channel_id_created_at_idx ON chat_messages (channel_id, created_at DESC);
+CREATE INDEX moderation_jobs_channel_id_created_at_idx ON moderation_jobs (channel_id, created_at DESC);
+
+-- Foreign keys
+
+ALTER TABLE chat_bans ADD CONSTRAINT chat_bans_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE chat_bans ADD CONSTRAINT chat_bans_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE chat_bans ADD CONSTRAINT chat_bans_banned_by_fkey FOREIGN KEY (banned_by) REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE moderation_jobs ADD CONSTRAINT moderation_jobs_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE moderation_jobs ADD CONSTRAINT moderation_jobs_created_by_fkey FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE CASCADE;
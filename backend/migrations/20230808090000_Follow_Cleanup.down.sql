@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS channel_events_channel_id_kind_created_at_idx;
+
+ALTER TABLE moderation_jobs DROP COLUMN IF EXISTS only_silent;
+ALTER TABLE moderation_jobs DROP COLUMN IF EXISTS max_account_age;
+ALTER TABLE moderation_jobs DROP COLUMN IF EXISTS until;
@@ -0,0 +1,8 @@
+-- Follows are removed by moderation jobs of kind 2, in the window from since to until.
+ALTER TABLE moderation_jobs ADD COLUMN until timestamptz DEFAULT NULL;
+ALTER TABLE moderation_jobs ADD COLUMN max_account_age int DEFAULT NULL; -- in minutes, only follows of accounts this new when following are removed
+ALTER TABLE moderation_jobs ADD COLUMN only_silent boolean NOT NULL DEFAULT FALSE; -- only follows of users who never chatted in the channel are removed
+
+-- Indexes
+
+CREATE INDEX channel_events_channel_id_kind_created_at_idx ON channel_events (channel_id, kind, created_at);
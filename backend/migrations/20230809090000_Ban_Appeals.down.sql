@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS chat_ban_appeal_events CASCADE;
+DROP TABLE IF EXISTS chat_ban_appeals CASCADE;
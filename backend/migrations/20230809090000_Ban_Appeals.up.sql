@@ -0,0 +1,39 @@
+CREATE TABLE chat_ban_appeals (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    message text NOT NULL,
+    status int NOT NULL DEFAULT 0, -- 0 = pending, 1 = accepted, 2 = denied
+    reviewed_by uuid DEFAULT NULL, -- foreign key to users(id)
+    review_note text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    reviewed_at timestamptz DEFAULT NULL
+);
+
+CREATE TABLE chat_ban_appeal_events (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    appeal_id uuid NOT NULL, -- foreign key to chat_ban_appeals(id)
+    actor_id uuid DEFAULT NULL, -- foreign key to users(id), NULL = deleted account
+    action int NOT NULL, -- 0 = submitted, 1 = accepted, 2 = denied
+    note text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE INDEX chat_ban_appeals_channel_id_status_created_at_idx ON chat_ban_appeals (channel_id, status, created_at);
+CREATE INDEX chat_ban_appeal_events_appeal_id_created_at_idx ON chat_ban_appeal_events (appeal_id, created_at);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS chat_ban_appeals ADD CONSTRAINT chat_ban_appeals_channel_id_user_id_unique UNIQUE (channel_id, user_id);
+
+-- Foreign keys
+
+ALTER TABLE chat_ban_appeals ADD CONSTRAINT chat_ban_appeals_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE chat_ban_appeals ADD CONSTRAINT chat_ban_appeals_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE chat_ban_appeals ADD CONSTRAINT chat_ban_appeals_reviewed_by_fkey FOREIGN KEY (reviewed_by) REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE chat_ban_appeal_events ADD CONSTRAINT chat_ban_appeal_events_appeal_id_fkey FOREIGN KEY (appeal_id) REFERENCES chat_ban_appeals(id) ON DELETE CASCADE;
+ALTER TABLE chat_ban_appeal_events ADD CONSTRAINT chat_ban_appeal_events_actor_id_fkey FOREIGN KEY (actor_id) REFERENCES users(id) ON DELETE SET NULL;
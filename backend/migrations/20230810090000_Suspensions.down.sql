@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS user_suspension_appeals CASCADE;
+DROP TABLE IF EXISTS user_suspensions CASCADE;
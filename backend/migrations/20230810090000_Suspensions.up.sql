@@ -0,0 +1,42 @@
+CREATE TABLE user_suspensions (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    suspended_by uuid DEFAULT NULL, -- foreign key to users(id)
+    reason text NOT NULL,
+    lifted_by uuid DEFAULT NULL, -- foreign key to users(id), NULL = expired or lifted by a deleted account
+    lift_reason text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    expires_at timestamptz DEFAULT NULL, -- NULL = permanent
+    lifted_at timestamptz DEFAULT NULL
+);
+
+CREATE TABLE user_suspension_appeals (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    suspension_id uuid NOT NULL, -- foreign key to user_suspensions(id)
+    message text NOT NULL,
+    status int NOT NULL DEFAULT 0, -- 0 = pending, 1 = accepted, 2 = denied
+    reviewed_by uuid DEFAULT NULL, -- foreign key to users(id)
+    review_note text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    reviewed_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE INDEX user_suspensions_user_id_created_at_idx ON user_suspensions (user_id, created_at DESC);
+CREATE INDEX user_suspensions_expires_at_idx ON user_suspensions (expires_at) WHERE lifted_at IS NULL;
+CREATE INDEX user_suspension_appeals_status_created_at_idx ON user_suspension_appeals (status, created_at);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS user_suspension_appeals ADD CONSTRAINT user_suspension_appeals_suspension_id_unique UNIQUE (suspension_id);
+
+-- Foreign keys
+
+ALTER TABLE user_suspensions ADD CONSTRAINT user_suspensions_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE user_suspensions ADD CONSTRAINT user_suspensions_suspended_by_fkey FOREIGN KEY (suspended_by) REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE user_suspensions ADD CONSTRAINT user_suspensions_lifted_by_fkey FOREIGN KEY (lifted_by) REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE user_suspension_appeals ADD CONSTRAINT user_suspension_appeals_suspension_id_fkey FOREIGN KEY (suspension_id) REFERENCES user_suspensions(id) ON DELETE CASCADE;
+ALTER TABLE user_suspension_appeals ADD CONSTRAINT user_suspension_appeals_reviewed_by_fkey FOREIGN KEY (reviewed_by) REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS legal_hold_events CASCADE;
+DROP TABLE IF EXISTS legal_holds CASCADE;
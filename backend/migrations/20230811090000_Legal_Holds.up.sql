@@ -0,0 +1,35 @@
+CREATE TABLE legal_holds (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    subject_kind int NOT NULL, -- 0 = user, 1 = channel, 2 = vod
+    subject_id uuid NOT NULL, -- foreign key to users(id) for users and channels, streams(id) for vods
+    reason text NOT NULL,
+    placed_by uuid DEFAULT NULL, -- foreign key to users(id)
+    released_by uuid DEFAULT NULL, -- foreign key to users(id)
+    release_reason text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    released_at timestamptz DEFAULT NULL
+);
+
+CREATE TABLE legal_hold_events (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    hold_id uuid NOT NULL, -- foreign key to legal_holds(id)
+    actor_id uuid DEFAULT NULL, -- foreign key to users(id)
+    action int NOT NULL, -- 0 = placed, 1 = released
+    note text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE UNIQUE INDEX legal_holds_subject_active_idx ON legal_holds (subject_kind, subject_id) WHERE released_at IS NULL;
+CREATE INDEX legal_holds_created_at_idx ON legal_holds (created_at DESC);
+CREATE INDEX legal_hold_events_hold_id_created_at_idx ON legal_hold_events (hold_id, created_at);
+
+-- Foreign keys
+
+ALTER TABLE legal_holds ADD CONSTRAINT legal_holds_placed_by_fkey FOREIGN KEY (placed_by) REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE legal_holds ADD CONSTRAINT legal_holds_released_by_fkey FOREIGN KEY (released_by) REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE legal_hold_events ADD CONSTRAINT legal_hold_events_hold_id_fkey FOREIGN KEY (hold_id) REFERENCES legal_holds(id) ON DELETE CASCADE;
+ALTER TABLE legal_hold_events ADD CONSTRAINT legal_hold_events_actor_id_fkey FOREIGN KEY (actor_id) REFERENCES users(id) ON DELETE SET NULL;
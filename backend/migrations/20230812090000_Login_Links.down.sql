@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS login_links CASCADE;
+
+ALTER TABLE users DROP COLUMN IF EXISTS email_hash;
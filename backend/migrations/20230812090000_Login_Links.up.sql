@@ -0,0 +1,29 @@
+ALTER TABLE users ADD COLUMN email_hash text DEFAULT NULL; -- keyed hash of the email, so users can be found by email while it is encrypted
+
+CREATE TABLE login_links (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    token_hash text NOT NULL, -- sha256 of the token sent by email
+    device_hash text NOT NULL, -- sha256 of the token kept by the device which requested the link
+    requested_ip text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    expires_at timestamptz NOT NULL,
+    confirmed_at timestamptz DEFAULT NULL,
+    used_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE INDEX users_email_hash_idx ON users (email_hash);
+CREATE INDEX login_links_user_id_created_at_idx ON login_links (user_id, created_at);
+CREATE INDEX login_links_requested_ip_created_at_idx ON login_links (requested_ip, created_at);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS login_links ADD CONSTRAINT login_links_token_hash_unique UNIQUE (token_hash);
+ALTER TABLE IF EXISTS login_links ADD CONSTRAINT login_links_device_hash_unique UNIQUE (device_hash);
+
+-- Foreign keys
+
+ALTER TABLE login_links ADD CONSTRAINT login_links_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1 @@
+ALTER TABLE sessions ADD COLUMN elevated_until timestamptz DEFAULT NULL; -- the session can do sensitive actions until then
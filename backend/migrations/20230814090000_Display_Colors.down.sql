@@ -0,0 +1,2 @@
+ALTER TABLE users DROP COLUMN IF EXISTS display_color;
+ALTER TABLE users DROP COLUMN IF EXISTS display_gradient_end;
@@ -0,0 +1,2 @@
+ALTER TABLE users ADD COLUMN display_color varchar(32) NOT NULL DEFAULT ''; -- palette name, hex color or light/dark hex colors, e.g. #9146ff/#b197fc
+ALTER TABLE users ADD COLUMN display_gradient_end varchar(32) NOT NULL DEFAULT ''; -- same format, only for users allowed to use gradients
@@ -0,0 +1,14 @@
+CREATE TABLE channel_appearances (
+    channel_id uuid PRIMARY KEY, -- foreign key to users(id)
+    accent_color varchar(32) NOT NULL DEFAULT '', -- palette name, hex color or light/dark hex colors, empty = site default
+    banner_url text DEFAULT NULL, -- processed by the image processor, NULL = no banner
+    layout int NOT NULL DEFAULT 0, -- 0 = standard, 1 = theatre
+    show_panels boolean NOT NULL DEFAULT TRUE,
+    show_schedule boolean NOT NULL DEFAULT TRUE,
+    -- Timestamps
+    updated_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Foreign keys
+
+ALTER TABLE channel_appearances ADD CONSTRAINT channel_appearances_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,16 @@
+CREATE TABLE display_color_changes (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    display_color varchar(32) NOT NULL, -- the color the user changed to, empty = removed
+    display_gradient_end varchar(32) NOT NULL,
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE INDEX display_color_changes_user_id_created_at_idx ON display_color_changes (user_id, created_at);
+
+-- Foreign keys
+
+ALTER TABLE display_color_changes ADD CONSTRAINT display_color_changes_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
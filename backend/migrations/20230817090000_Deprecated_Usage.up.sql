@@ -0,0 +1,17 @@
+CREATE TABLE deprecated_usage_daily (
+    surface varchar(256) NOT NULL, -- the field as Type.field or the endpoint as METHOD /path
+    client varchar(64) NOT NULL, -- token:<id> for access tokens, session:<id> for logins, anonymous otherwise
+    user_id uuid DEFAULT NULL, -- foreign key to users(id), NULL = anonymous
+    day timestamptz NOT NULL, -- the start of the day (UTC)
+    uses bigint NOT NULL DEFAULT 0,
+    last_used_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (surface, client, day)
+);
+
+-- Indexes
+
+CREATE INDEX deprecated_usage_daily_surface_day_idx ON deprecated_usage_daily (surface, day);
+
+-- Foreign keys
+
+ALTER TABLE deprecated_usage_daily ADD CONSTRAINT deprecated_usage_daily_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
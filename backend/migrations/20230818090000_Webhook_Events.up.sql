@@ -0,0 +1,7 @@
+CREATE TABLE webhook_events (
+    source varchar(32) NOT NULL, -- the sender of the webhook, e.g. payments
+    event_id varchar(255) NOT NULL, -- the id the sender gave the event, the same for every delivery of it
+    -- Timestamps
+    processed_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (source, event_id)
+);
@@ -0,0 +1,16 @@
+CREATE TABLE dead_letters (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    consumer int NOT NULL, -- 0 = notifications, 1 = moderation, 2 = import
+    payload bytea NOT NULL, -- the message as it was delivered
+    error text NOT NULL DEFAULT '',
+    attempts int NOT NULL DEFAULT 1, -- a retried message which fails again is counted on the same row
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    failed_at timestamptz NOT NULL DEFAULT NOW(),
+    retried_at timestamptz DEFAULT NULL,
+    discarded_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE INDEX dead_letters_consumer_failed_at_idx ON dead_letters (consumer, failed_at DESC) WHERE retried_at IS NULL AND discarded_at IS NULL;
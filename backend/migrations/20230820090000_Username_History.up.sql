@@ -0,0 +1,16 @@
+CREATE TABLE username_history (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    username varchar(32) NOT NULL, -- the username the user had before the change
+    -- Timestamps
+    changed_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE INDEX username_history_user_id_changed_at_idx ON username_history (user_id, changed_at DESC);
+CREATE INDEX username_history_username_idx ON username_history (username);
+
+-- Foreign keys
+
+ALTER TABLE username_history ADD CONSTRAINT username_history_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
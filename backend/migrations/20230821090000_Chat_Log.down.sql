@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS chat_log CASCADE;
+DROP TABLE IF EXISTS chat_sequences CASCADE;
@@ -0,0 +1,22 @@
+CREATE TABLE chat_sequences (
+    channel_id uuid PRIMARY KEY, -- foreign key to users(id)
+    sequence bigint NOT NULL -- the sequence of the last chat event of the channel
+);
+CREATE TABLE chat_log (
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    sequence bigint NOT NULL,
+    message_id uuid NOT NULL, -- the message the event is about, not a foreign key as purchases and deletions have no row in chat_messages
+    payload bytea NOT NULL, -- the encoded scuffle.events.ChatMessage, without its sequence
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (channel_id, sequence)
+);
+
+-- Indexes
+
+CREATE INDEX chat_log_channel_id_message_id_idx ON chat_log (channel_id, message_id);
+
+-- Foreign keys
+
+ALTER TABLE chat_sequences ADD CONSTRAINT chat_sequences_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE chat_log ADD CONSTRAINT chat_log_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,23 @@
+CREATE TABLE password_resets (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    token_hash text NOT NULL, -- sha256 of the token sent by email
+    requested_ip text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    expires_at timestamptz NOT NULL,
+    used_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE INDEX password_resets_user_id_created_at_idx ON password_resets (user_id, created_at);
+CREATE INDEX password_resets_requested_ip_created_at_idx ON password_resets (requested_ip, created_at);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS password_resets ADD CONSTRAINT password_resets_token_hash_unique UNIQUE (token_hash);
+
+-- Foreign keys
+
+ALTER TABLE password_resets ADD CONSTRAINT password_resets_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS channel_events_user_id_kind_idx;
+
+ALTER TABLE users DROP COLUMN IF EXISTS presence_visibility;
+ALTER TABLE users DROP COLUMN IF EXISTS presence_share_watching;
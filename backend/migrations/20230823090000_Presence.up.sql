@@ -0,0 +1,6 @@
+ALTER TABLE users ADD COLUMN presence_visibility int NOT NULL DEFAULT 0; -- 0 = nobody, 1 = friends (users who follow each other), 2 = followers
+ALTER TABLE users ADD COLUMN presence_share_watching boolean NOT NULL DEFAULT FALSE; -- whether the channel the user watches is shown along with the online status
+
+-- Indexes
+
+CREATE INDEX channel_events_user_id_kind_idx ON channel_events (user_id, kind);
@@ -0,0 +1,28 @@
+CREATE TABLE friend_requests (
+    user_id uuid NOT NULL, -- foreign key to users(id), who asked
+    friend_id uuid NOT NULL, -- foreign key to users(id), who was asked
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, friend_id)
+);
+
+CREATE TABLE friends (
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    friend_id uuid NOT NULL, -- foreign key to users(id), stored for both users of a friendship
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, friend_id)
+);
+
+-- Indexes
+
+CREATE INDEX friend_requests_user_id_created_at_idx ON friend_requests (user_id, created_at DESC, friend_id DESC);
+CREATE INDEX friend_requests_friend_id_created_at_idx ON friend_requests (friend_id, created_at DESC, user_id DESC);
+CREATE INDEX friends_user_id_created_at_idx ON friends (user_id, created_at DESC, friend_id DESC);
+
+-- Foreign keys
+
+ALTER TABLE friend_requests ADD CONSTRAINT friend_requests_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE friend_requests ADD CONSTRAINT friend_requests_friend_id_fkey FOREIGN KEY (friend_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE friends ADD CONSTRAINT friends_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE friends ADD CONSTRAINT friends_friend_id_fkey FOREIGN KEY (friend_id) REFERENCES users(id) ON DELETE CASCADE;
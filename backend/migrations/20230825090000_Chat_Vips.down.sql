@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS chat_vips CASCADE;
+
+ALTER TABLE users DROP COLUMN IF EXISTS chat_slow_mode;
+ALTER TABLE users DROP COLUMN IF EXISTS chat_block_links;
@@ -0,0 +1,21 @@
+CREATE TABLE chat_vips (
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    granted_by uuid DEFAULT NULL, -- foreign key to users(id)
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (channel_id, user_id)
+);
+
+ALTER TABLE users ADD COLUMN chat_slow_mode int NOT NULL DEFAULT 0; -- seconds between two messages of a user in the chat, 0 = off
+ALTER TABLE users ADD COLUMN chat_block_links boolean NOT NULL DEFAULT FALSE; -- messages with links are rejected
+
+-- Indexes
+
+CREATE INDEX chat_vips_user_id_idx ON chat_vips (user_id);
+
+-- Foreign keys
+
+ALTER TABLE chat_vips ADD CONSTRAINT chat_vips_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE chat_vips ADD CONSTRAINT chat_vips_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE chat_vips ADD CONSTRAINT chat_vips_granted_by_fkey FOREIGN KEY (granted_by) REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1,11 @@
+CREATE TABLE user_social_links (
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    position int NOT NULL, -- the order on the profile, lowest first
+    label varchar(32) NOT NULL,
+    url varchar(512) NOT NULL,
+    PRIMARY KEY (user_id, position)
+);
+
+-- Foreign keys
+
+ALTER TABLE user_social_links ADD CONSTRAINT user_social_links_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS users_profile_image_job_id_idx;
+
+ALTER TABLE users DROP COLUMN IF EXISTS profile_image_job_id;
+ALTER TABLE users DROP COLUMN IF EXISTS pending_profile_image_url;
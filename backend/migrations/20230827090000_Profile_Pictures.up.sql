@@ -0,0 +1,8 @@
+ALTER TABLE users ADD COLUMN profile_image_job_id uuid DEFAULT NULL; -- the image processor job of a new profile picture, set while it is processed
+ALTER TABLE users ADD COLUMN pending_profile_image_url varchar(512) DEFAULT NULL; -- where the new profile picture is served from once processed
+
+-- dead_letters.consumer 3 = image processor results
+
+-- Indexes
+
+CREATE INDEX users_profile_image_job_id_idx ON users (profile_image_job_id) WHERE profile_image_job_id IS NOT NULL;
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS channel_audit_events CASCADE;
+
+ALTER TABLE users DROP COLUMN IF EXISTS chat_archive;
+ALTER TABLE streams DROP COLUMN IF EXISTS chat_archived;
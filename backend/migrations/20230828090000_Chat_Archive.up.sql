@@ -0,0 +1,20 @@
+ALTER TABLE users ADD COLUMN chat_archive boolean NOT NULL DEFAULT TRUE; -- whether chat messages are kept after they were sent
+ALTER TABLE streams ADD COLUMN chat_archived boolean NOT NULL DEFAULT TRUE; -- the chat setting when the stream started, turned off if chat stopped being kept during the stream
+
+CREATE TABLE channel_audit_events (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    actor_id uuid DEFAULT NULL, -- foreign key to users(id)
+    action int NOT NULL, -- 0 = chat archive turned on, 1 = chat archive turned off
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE INDEX channel_audit_events_channel_id_created_at_idx ON channel_audit_events (channel_id, created_at DESC);
+
+-- Foreign keys
+
+ALTER TABLE channel_audit_events ADD CONSTRAINT channel_audit_events_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE channel_audit_events ADD CONSTRAINT channel_audit_events_actor_id_fkey FOREIGN KEY (actor_id) REFERENCES users(id) ON DELETE SET NULL;
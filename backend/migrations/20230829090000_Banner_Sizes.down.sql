@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS channel_appearances_banner_job_id_idx;
+
+ALTER TABLE channel_appearances DROP COLUMN IF EXISTS banner_id;
+ALTER TABLE channel_appearances DROP COLUMN IF EXISTS banner_job_id;
+ALTER TABLE channel_appearances DROP COLUMN IF EXISTS pending_banner_id;
+ALTER TABLE channel_appearances ADD COLUMN banner_url text DEFAULT NULL;
@@ -0,0 +1,9 @@
+-- Banners are cropped to a desktop and a mobile size now, the ones uploaded before only have a single image and have to be uploaded again.
+ALTER TABLE channel_appearances DROP COLUMN IF EXISTS banner_url;
+ALTER TABLE channel_appearances ADD COLUMN banner_id uuid DEFAULT NULL; -- the sizes are served from banners/{channel_id}/{banner_id}/, NULL = no banner
+ALTER TABLE channel_appearances ADD COLUMN banner_job_id uuid DEFAULT NULL; -- the image processor job of a new banner, set while it is processed
+ALTER TABLE channel_appearances ADD COLUMN pending_banner_id uuid DEFAULT NULL; -- the id of the new banner once processed
+
+-- Indexes
+
+CREATE INDEX channel_appearances_banner_job_id_idx ON channel_appearances (banner_job_id) WHERE banner_job_id IS NOT NULL;
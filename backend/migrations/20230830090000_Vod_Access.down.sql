@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS channel_events_subscriptions_idx;
+
+ALTER TABLE streams DROP COLUMN IF EXISTS vod_access;
+ALTER TABLE streams DROP COLUMN IF EXISTS vod_public_at;
@@ -0,0 +1,6 @@
+ALTER TABLE streams ADD COLUMN vod_access int NOT NULL DEFAULT 0; -- 0 = public, 1 = subscribers only, 2 = early access for subscribers
+ALTER TABLE streams ADD COLUMN vod_public_at timestamptz DEFAULT NULL; -- when an early access recording becomes public
+
+-- Indexes
+
+CREATE INDEX channel_events_subscriptions_idx ON channel_events (channel_id, user_id, created_at) WHERE kind = 1;
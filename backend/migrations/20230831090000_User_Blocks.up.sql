@@ -0,0 +1,17 @@
+CREATE TABLE user_blocks (
+    user_id uuid NOT NULL, -- foreign key to users(id), who blocked
+    blocked_id uuid NOT NULL, -- foreign key to users(id)
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, blocked_id)
+);
+
+-- Indexes
+
+CREATE INDEX user_blocks_blocked_id_idx ON user_blocks (blocked_id);
+CREATE INDEX user_blocks_user_id_created_at_idx ON user_blocks (user_id, created_at);
+
+-- Foreign keys
+
+ALTER TABLE user_blocks ADD CONSTRAINT user_blocks_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE user_blocks ADD CONSTRAINT user_blocks_blocked_id_fkey FOREIGN KEY (blocked_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,6 @@
+ALTER TABLE sessions ADD COLUMN ip_address text NOT NULL DEFAULT ''; -- the address the session was created from
+ALTER TABLE sessions ADD COLUMN user_agent text NOT NULL DEFAULT ''; -- the user agent of the client which created the session
+
+-- Indexes
+
+CREATE INDEX sessions_user_id_idx ON sessions (user_id) WHERE invalidated_at IS NULL;
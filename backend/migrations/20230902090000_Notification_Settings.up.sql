@@ -0,0 +1,13 @@
+CREATE TABLE notification_settings (
+    user_id uuid PRIMARY KEY, -- foreign key to users(id)
+    go_live boolean NOT NULL DEFAULT TRUE, -- emails when a followed channel goes live
+    new_follower boolean NOT NULL DEFAULT TRUE,
+    mentions boolean NOT NULL DEFAULT TRUE,
+    marketing_emails boolean NOT NULL DEFAULT FALSE,
+    -- Timestamps
+    updated_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Foreign keys
+
+ALTER TABLE notification_settings ADD CONSTRAINT notification_settings_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS waitlist_entries CASCADE;
+DROP TABLE IF EXISTS invite_codes CASCADE;
@@ -0,0 +1,36 @@
+CREATE TABLE invite_codes (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    code varchar(16) NOT NULL,
+    created_by uuid DEFAULT NULL, -- foreign key to users(id), NULL for the codes of admitted waitlist entries
+    used_by uuid DEFAULT NULL, -- foreign key to users(id)
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    used_at timestamptz DEFAULT NULL -- a code can be used once, it stays used if the user is deleted
+);
+
+CREATE TABLE waitlist_entries (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    email text NOT NULL, -- encrypted
+    email_hash text NOT NULL, -- keyed hash of the email, so an email can only sign up once
+    invite_code_id uuid DEFAULT NULL, -- foreign key to invite_codes(id), the code sent once admitted
+    admitted_by uuid DEFAULT NULL, -- foreign key to users(id)
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    admitted_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE UNIQUE INDEX invite_codes_code_idx ON invite_codes (code);
+CREATE INDEX invite_codes_created_by_idx ON invite_codes (created_by);
+CREATE INDEX invite_codes_used_by_idx ON invite_codes (used_by);
+
+CREATE UNIQUE INDEX waitlist_entries_email_hash_idx ON waitlist_entries (email_hash);
+CREATE INDEX waitlist_entries_pending_idx ON waitlist_entries (created_at) WHERE admitted_at IS NULL;
+
+-- Foreign keys
+
+ALTER TABLE invite_codes ADD CONSTRAINT invite_codes_created_by_fkey FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE invite_codes ADD CONSTRAINT invite_codes_used_by_fkey FOREIGN KEY (used_by) REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE waitlist_entries ADD CONSTRAINT waitlist_entries_invite_code_id_fkey FOREIGN KEY (invite_code_id) REFERENCES invite_codes(id) ON DELETE SET NULL;
+ALTER TABLE waitlist_entries ADD CONSTRAINT waitlist_entries_admitted_by_fkey FOREIGN KEY (admitted_by) REFERENCES users(id) ON DELETE SET NULL;
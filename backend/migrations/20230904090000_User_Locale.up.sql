@@ -0,0 +1,2 @@
+ALTER TABLE users ADD COLUMN locale varchar(35) NOT NULL DEFAULT 'en-US'; -- BCP-47 language tag emails are written in
+ALTER TABLE users ADD COLUMN timezone varchar(64) NOT NULL DEFAULT 'UTC'; -- IANA time zone times are shown in
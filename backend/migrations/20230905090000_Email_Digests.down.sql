@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS email_digests;
+
+ALTER TABLE notification_settings DROP COLUMN IF EXISTS weekly_digest;
@@ -0,0 +1,15 @@
+ALTER TABLE notification_settings ADD COLUMN weekly_digest boolean NOT NULL DEFAULT TRUE; -- weekly email of followed channel activity
+
+CREATE TABLE email_digests (
+    user_id uuid PRIMARY KEY, -- foreign key to users(id)
+    -- Timestamps
+    sent_at timestamptz NOT NULL DEFAULT NOW() -- last digest sent, or skipped because nothing happened
+);
+
+-- Indexes
+
+CREATE INDEX email_digests_sent_at_idx ON email_digests (sent_at);
+
+-- Foreign keys
+
+ALTER TABLE email_digests ADD CONSTRAINT email_digests_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,21 @@
+CREATE TABLE data_exports (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    status int NOT NULL DEFAULT 0, -- 0 = queued, 1 = running, 2 = completed, 3 = failed
+    object_key varchar(255) NOT NULL DEFAULT '', -- key of the archive in the object storage, set once completed
+    size_bytes bigint NOT NULL DEFAULT 0,
+    error text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    updated_at timestamptz NOT NULL DEFAULT NOW(),
+    completed_at timestamptz DEFAULT NULL,
+    expires_at timestamptz DEFAULT NULL -- the archive can't be downloaded anymore afterwards
+);
+
+-- Indexes
+
+CREATE INDEX data_exports_user_id_created_at_idx ON data_exports (user_id, created_at DESC);
+
+-- Foreign keys
+
+ALTER TABLE data_exports ADD CONSTRAINT data_exports_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
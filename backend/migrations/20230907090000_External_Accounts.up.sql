@@ -0,0 +1,19 @@
+CREATE TABLE external_accounts (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    provider int NOT NULL, -- 0 = google, 1 = discord, 2 = github
+    provider_user_id varchar(255) NOT NULL, -- the id of the account at the provider, which never changes
+    provider_username varchar(255) NOT NULL DEFAULT '', -- the name of the account at the provider when it was linked
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    last_login_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE UNIQUE INDEX external_accounts_provider_provider_user_id_idx ON external_accounts (provider, provider_user_id);
+CREATE UNIQUE INDEX external_accounts_user_id_provider_idx ON external_accounts (user_id, provider);
+
+-- Foreign keys
+
+ALTER TABLE external_accounts ADD CONSTRAINT external_accounts_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS admin_approval_events CASCADE;
+DROP TABLE IF EXISTS admin_approvals CASCADE;
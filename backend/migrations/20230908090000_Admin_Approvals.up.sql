@@ -0,0 +1,36 @@
+CREATE TABLE admin_approvals (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind int NOT NULL, -- 0 = permanent suspension, 1 = payout method verification
+    target_id uuid NOT NULL, -- foreign key to users(id) for suspensions, payout_methods(id) for verifications
+    reason text NOT NULL, -- the reason shown to the suspended user, or the review note of the payout method
+    status int NOT NULL DEFAULT 0, -- 0 = pending, 1 = approved, 2 = rejected, 3 = expired
+    proposed_by uuid DEFAULT NULL, -- foreign key to users(id)
+    decided_by uuid DEFAULT NULL, -- foreign key to users(id)
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    expires_at timestamptz NOT NULL, -- pending approvals can't be approved anymore afterwards
+    decided_at timestamptz DEFAULT NULL
+);
+
+CREATE TABLE admin_approval_events (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    approval_id uuid NOT NULL, -- foreign key to admin_approvals(id)
+    actor_id uuid DEFAULT NULL, -- foreign key to users(id)
+    action int NOT NULL, -- 0 = proposed, 1 = approved, 2 = rejected
+    note text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE UNIQUE INDEX admin_approvals_kind_target_pending_idx ON admin_approvals (kind, target_id) WHERE status = 0;
+CREATE INDEX admin_approvals_created_at_idx ON admin_approvals (created_at DESC);
+CREATE INDEX admin_approval_events_approval_id_created_at_idx ON admin_approval_events (approval_id, created_at);
+
+-- Foreign keys
+
+ALTER TABLE admin_approvals ADD CONSTRAINT admin_approvals_proposed_by_fkey FOREIGN KEY (proposed_by) REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE admin_approvals ADD CONSTRAINT admin_approvals_decided_by_fkey FOREIGN KEY (decided_by) REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE admin_approval_events ADD CONSTRAINT admin_approval_events_approval_id_fkey FOREIGN KEY (approval_id) REFERENCES admin_approvals(id) ON DELETE CASCADE;
+ALTER TABLE admin_approval_events ADD CONSTRAINT admin_approval_events_actor_id_fkey FOREIGN KEY (actor_id) REFERENCES users(id) ON DELETE SET NULL;
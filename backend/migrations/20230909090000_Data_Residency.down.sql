@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS users_data_region_idx;
+DROP INDEX IF EXISTS streams_region_idx;
+
+ALTER TABLE data_exports DROP COLUMN IF EXISTS region;
+ALTER TABLE streams DROP COLUMN IF EXISTS region;
+ALTER TABLE users DROP COLUMN IF EXISTS data_region;
@@ -0,0 +1,8 @@
+ALTER TABLE users ADD COLUMN data_region varchar(32) NOT NULL DEFAULT ''; -- region tag the user's data is kept in, empty for the default region
+ALTER TABLE streams ADD COLUMN region varchar(32) NOT NULL DEFAULT ''; -- region the stream was recorded in, VODs stay there
+ALTER TABLE data_exports ADD COLUMN region varchar(32) NOT NULL DEFAULT ''; -- region the archive was uploaded to
+
+-- Indexes
+
+CREATE INDEX streams_region_idx ON streams (region);
+CREATE INDEX users_data_region_idx ON users (data_region);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS passkey_challenges;
+DROP TABLE IF EXISTS passkeys;
+
+ALTER TABLE users DROP COLUMN IF EXISTS passkey_required;
@@ -0,0 +1,35 @@
+ALTER TABLE users ADD COLUMN passkey_required boolean NOT NULL DEFAULT FALSE; -- logging in with a password also needs a passkey
+
+CREATE TABLE passkeys (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    credential_id varchar(1024) NOT NULL, -- base64url id the authenticator gave the credential
+    public_key bytea NOT NULL, -- uncompressed P-256 point, 65 bytes
+    sign_count bigint NOT NULL DEFAULT 0, -- the signature counter of the authenticator, 0 if it does not count
+    name varchar(64) NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    last_used_at timestamptz DEFAULT NULL
+);
+
+CREATE TABLE passkey_challenges (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid DEFAULT NULL, -- foreign key to users(id), null for passwordless logins which don't know the user yet
+    kind int NOT NULL, -- 0 = registration, 1 = login
+    challenge varchar(64) NOT NULL, -- base64url random bytes the authenticator signs
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    expires_at timestamptz NOT NULL,
+    used_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE UNIQUE INDEX passkeys_credential_id_idx ON passkeys (credential_id);
+CREATE INDEX passkeys_user_id_idx ON passkeys (user_id);
+CREATE INDEX passkey_challenges_expires_at_idx ON passkey_challenges (expires_at);
+
+-- Foreign keys
+
+ALTER TABLE passkeys ADD CONSTRAINT passkeys_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE passkey_challenges ADD CONSTRAINT passkey_challenges_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS recovery_code_events;
+DROP TABLE IF EXISTS recovery_codes;
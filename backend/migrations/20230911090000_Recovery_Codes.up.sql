@@ -0,0 +1,28 @@
+CREATE TABLE recovery_codes (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    code_hash varchar(64) NOT NULL, -- sha256 of the normalized code as hex
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    used_at timestamptz DEFAULT NULL -- each code logs in once
+);
+
+CREATE TABLE recovery_code_events (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    action int NOT NULL, -- 0 = regenerated, 1 = used
+    ip_address varchar(45) NOT NULL DEFAULT '',
+    user_agent varchar(512) NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE UNIQUE INDEX recovery_codes_user_id_code_hash_idx ON recovery_codes (user_id, code_hash);
+CREATE INDEX recovery_code_events_user_id_created_at_idx ON recovery_code_events (user_id, created_at DESC);
+
+-- Foreign keys
+
+ALTER TABLE recovery_codes ADD CONSTRAINT recovery_codes_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE recovery_code_events ADD CONSTRAINT recovery_code_events_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
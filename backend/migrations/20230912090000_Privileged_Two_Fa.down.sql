@@ -0,0 +1 @@
+ALTER TABLE users DROP COLUMN moderation_two_fa_required;
@@ -0,0 +1,3 @@
+ALTER TABLE users ADD COLUMN moderation_two_fa_required boolean NOT NULL DEFAULT FALSE; -- moderating the channel needs a passkey as second factor
+
+-- channel_audit_events.action: 2 = moderation 2FA turned on, 3 = moderation 2FA turned off
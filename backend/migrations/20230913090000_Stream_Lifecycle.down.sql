@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS stream_lifecycle_transitions;
+
+ALTER TABLE streams DROP COLUMN lifecycle;
@@ -0,0 +1,26 @@
+ALTER TABLE streams ADD COLUMN lifecycle int NOT NULL DEFAULT 0; -- 0 = requested, 1 = ready, 2 = live, 3 = reconnecting, 4 = ended, 5 = processing vod
+
+CREATE TABLE stream_lifecycle_transitions (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    stream_id uuid NOT NULL, -- foreign key to streams(id)
+    from_state int NOT NULL, -- same values as streams.lifecycle
+    to_state int NOT NULL,
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Existing streams start from what their ready state and end time say, without transitions.
+UPDATE streams SET lifecycle = CASE
+    WHEN ready_state IN (2, 4) OR ended_at <= NOW() THEN CASE WHEN recorded THEN 5 ELSE 4 END
+    WHEN ready_state = 3 THEN 3
+    WHEN ready_state IN (1, 5) THEN 2
+    ELSE 0
+END;
+
+-- Indexes
+
+CREATE INDEX stream_lifecycle_transitions_stream_id_created_at_idx ON stream_lifecycle_transitions (stream_id, created_at);
+
+-- Foreign keys
+
+ALTER TABLE stream_lifecycle_transitions ADD CONSTRAINT stream_lifecycle_transitions_stream_id_fkey FOREIGN KEY (stream_id) REFERENCES streams(id) ON DELETE CASCADE;
@@ -0,0 +1,29 @@
+CREATE TABLE email_changes (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    old_email text NOT NULL, -- encrypted, restored if the change is reverted
+    new_email text NOT NULL, -- encrypted
+    new_email_hash text NOT NULL, -- keyed hash of the new email
+    confirm_token_hash text NOT NULL, -- sha256 of the token sent to the new email
+    revert_token_hash text NOT NULL, -- sha256 of the token sent to the old email
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    expires_at timestamptz NOT NULL, -- the confirmation link stops working
+    revert_expires_at timestamptz NOT NULL, -- the revert link stops working
+    confirmed_at timestamptz DEFAULT NULL, -- users.email is only updated once the new address is confirmed
+    reverted_at timestamptz DEFAULT NULL,
+    cancelled_at timestamptz DEFAULT NULL -- another change was requested before this one was confirmed
+);
+
+-- Indexes
+
+CREATE INDEX email_changes_user_id_created_at_idx ON email_changes (user_id, created_at);
+
+-- CONSTRAINTS
+
+ALTER TABLE IF EXISTS email_changes ADD CONSTRAINT email_changes_confirm_token_hash_unique UNIQUE (confirm_token_hash);
+ALTER TABLE IF EXISTS email_changes ADD CONSTRAINT email_changes_revert_token_hash_unique UNIQUE (revert_token_hash);
+
+-- Foreign keys
+
+ALTER TABLE email_changes ADD CONSTRAINT email_changes_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,17 @@
+CREATE TABLE channel_users (
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    user_id uuid NOT NULL, -- foreign key to users(id), the viewer
+    muted_at timestamptz, -- null if the channel is not muted
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (channel_id, user_id)
+);
+
+-- Indexes
+
+CREATE INDEX channel_users_user_id_muted_at_idx ON channel_users (user_id, muted_at) WHERE muted_at IS NOT NULL;
+
+-- Foreign keys
+
+ALTER TABLE channel_users ADD CONSTRAINT channel_users_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE channel_users ADD CONSTRAINT channel_users_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
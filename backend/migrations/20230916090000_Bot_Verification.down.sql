@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS bot_applications CASCADE;
+
+DROP INDEX IF EXISTS channel_users_channel_id_bot_active_at_idx;
+ALTER TABLE channel_users DROP COLUMN bot_active_at;
+
+ALTER TABLE users DROP COLUMN bot_verified_at;
@@ -0,0 +1,31 @@
+ALTER TABLE users ADD COLUMN bot_verified_at timestamptz DEFAULT NULL; -- null if the user is not a verified bot
+
+ALTER TABLE channel_users ADD COLUMN bot_active_at timestamptz DEFAULT NULL; -- when a verified bot last chatted in the channel
+
+CREATE TABLE bot_applications (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id uuid NOT NULL, -- foreign key to users(id), the bot account
+    description text NOT NULL,
+    website_url text NOT NULL DEFAULT '',
+    status int NOT NULL DEFAULT 0, -- 0 = pending, 1 = approved, 2 = rejected, 3 = revoked
+    reviewed_by uuid DEFAULT NULL, -- foreign key to users(id)
+    review_note text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    reviewed_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE INDEX bot_applications_status_created_at_idx ON bot_applications (status, created_at);
+CREATE INDEX channel_users_channel_id_bot_active_at_idx ON channel_users (channel_id, bot_active_at) WHERE bot_active_at IS NOT NULL;
+
+-- CONSTRAINTS
+
+-- An account has at most one application which is pending or approved, rejected and revoked ones can apply again.
+CREATE UNIQUE INDEX bot_applications_user_id_open_idx ON bot_applications (user_id) WHERE status IN (0, 1);
+
+-- Foreign keys
+
+ALTER TABLE bot_applications ADD CONSTRAINT bot_applications_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE bot_applications ADD CONSTRAINT bot_applications_reviewed_by_fkey FOREIGN KEY (reviewed_by) REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1 @@
+ALTER TABLE channel_users ADD COLUMN live_notifications boolean NOT NULL DEFAULT TRUE; -- false if the user turned off go-live notifications for the channel
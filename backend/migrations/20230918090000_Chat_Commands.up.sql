@@ -0,0 +1,21 @@
+CREATE TABLE chat_commands (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    name varchar(25) NOT NULL, -- lowercase, without the leading !
+    response text NOT NULL,
+    cooldown int NOT NULL DEFAULT 0, -- seconds between two uses of the command in the chat, 0 = off
+    permission int NOT NULL DEFAULT 0, -- 0 = everyone, 1 = VIPs, 2 = broadcaster
+    uses bigint NOT NULL DEFAULT 0,
+    -- Timestamps
+    last_used_at timestamptz DEFAULT NULL,
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    updated_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- CONSTRAINTS
+
+CREATE UNIQUE INDEX chat_commands_channel_id_name_idx ON chat_commands (channel_id, name);
+
+-- Foreign keys
+
+ALTER TABLE chat_commands ADD CONSTRAINT chat_commands_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS channel_events_follows_user_id_created_at_idx;
+DROP INDEX IF EXISTS streams_live_idx;
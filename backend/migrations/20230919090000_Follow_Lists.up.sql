@@ -0,0 +1,5 @@
+-- Indexes
+
+-- The channels a user follows, the followers of a channel use channel_events_channel_id_kind_created_at_idx.
+CREATE INDEX channel_events_follows_user_id_created_at_idx ON channel_events (user_id, created_at) WHERE kind = 0;
+CREATE INDEX streams_live_idx ON streams (channel_id, ended_at) WHERE deleted = FALSE;
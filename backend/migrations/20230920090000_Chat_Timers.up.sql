@@ -0,0 +1,26 @@
+CREATE TABLE chat_timers (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    name varchar(25) NOT NULL, -- lowercase
+    message text NOT NULL,
+    interval int NOT NULL, -- seconds between two posts of the timer
+    min_messages int NOT NULL DEFAULT 0, -- chat events needed since the last post, 0 = post in a quiet chat too
+    enabled boolean NOT NULL DEFAULT TRUE,
+    last_sequence bigint NOT NULL DEFAULT 0, -- the chat sequence of the last post, or of the creation
+    -- Timestamps
+    last_posted_at timestamptz DEFAULT NULL,
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    updated_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- CONSTRAINTS
+
+CREATE UNIQUE INDEX chat_timers_channel_id_name_idx ON chat_timers (channel_id, name);
+
+-- Indexes
+
+CREATE INDEX chat_timers_enabled_idx ON chat_timers (channel_id, last_posted_at) WHERE enabled = TRUE;
+
+-- Foreign keys
+
+ALTER TABLE chat_timers ADD CONSTRAINT chat_timers_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
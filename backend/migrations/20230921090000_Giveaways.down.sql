@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS giveaway_entries CASCADE;
+DROP TABLE IF EXISTS giveaways CASCADE;
@@ -0,0 +1,41 @@
+CREATE TABLE giveaways (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    title varchar(140) NOT NULL,
+    keyword varchar(25) DEFAULT NULL, -- lowercase, NULL = entered with the button only
+    followers_only boolean NOT NULL DEFAULT FALSE,
+    follower_weight int NOT NULL DEFAULT 1, -- tickets of a follower
+    subscriber_weight int NOT NULL DEFAULT 1, -- tickets of a subscriber
+    status int NOT NULL DEFAULT 0, -- 0 = open, 1 = drawn, 2 = cancelled
+    seed varchar(64) NOT NULL, -- kept secret until the draw
+    commitment varchar(64) NOT NULL, -- sha256 of the seed, public from the start
+    winner_id uuid DEFAULT NULL, -- foreign key to users(id)
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    closed_at timestamptz DEFAULT NULL
+);
+
+CREATE TABLE giveaway_entries (
+    giveaway_id uuid NOT NULL, -- foreign key to giveaways(id)
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    tickets int NOT NULL,
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (giveaway_id, user_id)
+);
+
+-- CONSTRAINTS
+
+-- A channel runs one giveaway at a time.
+CREATE UNIQUE INDEX giveaways_channel_id_open_idx ON giveaways (channel_id) WHERE status = 0;
+
+-- Indexes
+
+CREATE INDEX giveaways_channel_id_created_at_idx ON giveaways (channel_id, created_at);
+
+-- Foreign keys
+
+ALTER TABLE giveaways ADD CONSTRAINT giveaways_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE giveaways ADD CONSTRAINT giveaways_winner_id_fkey FOREIGN KEY (winner_id) REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE giveaway_entries ADD CONSTRAINT giveaway_entries_giveaway_id_fkey FOREIGN KEY (giveaway_id) REFERENCES giveaways(id) ON DELETE CASCADE;
+ALTER TABLE giveaway_entries ADD CONSTRAINT giveaway_entries_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
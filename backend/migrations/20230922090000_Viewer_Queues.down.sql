@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS viewer_queue_entries CASCADE;
+DROP TABLE IF EXISTS viewer_queues CASCADE;
@@ -0,0 +1,28 @@
+CREATE TABLE viewer_queues (
+    channel_id uuid PRIMARY KEY, -- foreign key to users(id)
+    title varchar(140) NOT NULL, -- what the viewers queue for
+    open boolean NOT NULL DEFAULT TRUE, -- whether viewers can join
+    max_size int DEFAULT NULL, -- NULL = unlimited
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    updated_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE viewer_queue_entries (
+    channel_id uuid NOT NULL, -- foreign key to viewer_queues(channel_id)
+    user_id uuid NOT NULL, -- foreign key to users(id)
+    position bigint NOT NULL, -- lower goes first, ties in user id order
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (channel_id, user_id)
+);
+
+-- Indexes
+
+CREATE INDEX viewer_queue_entries_channel_id_position_idx ON viewer_queue_entries (channel_id, position, user_id);
+
+-- Foreign keys
+
+ALTER TABLE viewer_queues ADD CONSTRAINT viewer_queues_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE viewer_queue_entries ADD CONSTRAINT viewer_queue_entries_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES viewer_queues(channel_id) ON DELETE CASCADE;
+ALTER TABLE viewer_queue_entries ADD CONSTRAINT viewer_queue_entries_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1 @@
+ALTER TABLE users ADD COLUMN stream_language varchar(35) NOT NULL DEFAULT ''; -- BCP-47 language tag, empty = not set
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS media_requests CASCADE;
+ALTER TABLE users DROP COLUMN IF EXISTS media_requests_enabled;
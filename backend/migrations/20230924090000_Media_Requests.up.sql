@@ -0,0 +1,25 @@
+ALTER TABLE users ADD COLUMN media_requests_enabled boolean NOT NULL DEFAULT FALSE; -- whether viewers can request media in the channel
+
+CREATE TABLE media_requests (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    user_id uuid NOT NULL, -- foreign key to users(id), the viewer who requested it
+    url varchar(512) NOT NULL,
+    status int NOT NULL DEFAULT 0, -- 0 = pending, 1 = approved, 2 = rejected, 3 = played
+    reviewed_by uuid DEFAULT NULL, -- foreign key to users(id), NULL = pending or the reviewer was deleted
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    reviewed_at timestamptz DEFAULT NULL,
+    played_at timestamptz DEFAULT NULL
+);
+
+-- Indexes
+
+CREATE INDEX media_requests_channel_id_status_created_at_idx ON media_requests (channel_id, status, created_at);
+CREATE INDEX media_requests_user_id_idx ON media_requests (user_id);
+
+-- Foreign keys
+
+ALTER TABLE media_requests ADD CONSTRAINT media_requests_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE media_requests ADD CONSTRAINT media_requests_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE media_requests ADD CONSTRAINT media_requests_reviewed_by_fkey FOREIGN KEY (reviewed_by) REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS retired_stream_keys CASCADE;
+DROP TABLE IF EXISTS stream_keys CASCADE;
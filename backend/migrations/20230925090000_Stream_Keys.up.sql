@@ -0,0 +1,26 @@
+CREATE TABLE stream_keys (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    name varchar(64) NOT NULL, -- to tell keys apart, like the encoder or the device it is used on
+    key varchar(64) NOT NULL, -- the secret part, after live_{channel id}_
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    last_used_at timestamptz DEFAULT NULL
+);
+
+CREATE TABLE retired_stream_keys (
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    key varchar(64) NOT NULL, -- a default or named key which was regenerated
+    expires_at timestamptz NOT NULL, -- the key is accepted until then, so an encoder can reconnect after a rotation
+    PRIMARY KEY (channel_id, key)
+);
+
+-- CONSTRAINTS
+
+CREATE UNIQUE INDEX stream_keys_channel_id_name_idx ON stream_keys (channel_id, lower(name));
+CREATE UNIQUE INDEX stream_keys_channel_id_key_idx ON stream_keys (channel_id, key);
+
+-- Foreign keys
+
+ALTER TABLE stream_keys ADD CONSTRAINT stream_keys_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE retired_stream_keys ADD CONSTRAINT retired_stream_keys_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS channel_tags CASCADE;
+DROP TABLE IF EXISTS tags CASCADE;
@@ -0,0 +1,27 @@
+CREATE TABLE tags (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    name varchar(25) NOT NULL, -- shown as given, matched without case
+    description text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE channel_tags (
+    channel_id uuid NOT NULL, -- foreign key to users(id)
+    tag_id uuid NOT NULL, -- foreign key to tags(id)
+    position int NOT NULL, -- the order the broadcaster put the tags in, lowest first
+    PRIMARY KEY (channel_id, tag_id)
+);
+
+-- Indexes
+
+CREATE INDEX channel_tags_tag_id_idx ON channel_tags (tag_id);
+
+-- CONSTRAINTS
+
+CREATE UNIQUE INDEX tags_name_idx ON tags (lower(name));
+
+-- Foreign keys
+
+ALTER TABLE channel_tags ADD CONSTRAINT channel_tags_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE channel_tags ADD CONSTRAINT channel_tags_tag_id_fkey FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE;
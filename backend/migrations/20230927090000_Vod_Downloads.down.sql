@@ -0,0 +1 @@
+DROP TABLE IF EXISTS vod_downloads CASCADE;
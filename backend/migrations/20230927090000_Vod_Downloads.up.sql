@@ -0,0 +1,27 @@
+CREATE TABLE vod_downloads (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    stream_id uuid NOT NULL, -- foreign key to streams(id)
+    requested_by uuid NOT NULL, -- foreign key to users(id)
+    variant varchar(64) NOT NULL, -- name of the variant of the recording
+    remux boolean NOT NULL, -- false = the variant is transcoded, its codecs can't go into an MP4 as they are
+    status int NOT NULL DEFAULT 0, -- 0 = queued, 1 = running, 2 = completed, 3 = failed
+    progress int NOT NULL DEFAULT 0, -- 0 to 100
+    object_key varchar(255) NOT NULL, -- key of the MP4 in the object storage of the region
+    region varchar(32) NOT NULL DEFAULT '', -- region the MP4 is uploaded to, the same as the recording
+    size_bytes bigint NOT NULL DEFAULT 0,
+    error text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    updated_at timestamptz NOT NULL DEFAULT NOW(),
+    completed_at timestamptz DEFAULT NULL,
+    expires_at timestamptz DEFAULT NULL -- the MP4 can't be downloaded anymore afterwards
+);
+
+-- Indexes
+
+CREATE INDEX vod_downloads_stream_id_variant_idx ON vod_downloads (stream_id, variant, created_at DESC);
+
+-- Foreign keys
+
+ALTER TABLE vod_downloads ADD CONSTRAINT vod_downloads_stream_id_fkey FOREIGN KEY (stream_id) REFERENCES streams(id) ON DELETE CASCADE;
+ALTER TABLE vod_downloads ADD CONSTRAINT vod_downloads_requested_by_fkey FOREIGN KEY (requested_by) REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,17 @@
+CREATE TABLE stream_chapters (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    stream_id uuid NOT NULL, -- foreign key to streams(id)
+    title varchar(255) NOT NULL, -- title of the stream during the chapter
+    category varchar(64) NOT NULL, -- category of the stream during the chapter
+    position_seconds bigint NOT NULL, -- seconds since the stream started
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+-- Indexes
+
+CREATE INDEX stream_chapters_stream_id_idx ON stream_chapters (stream_id, position_seconds);
+
+-- Foreign keys
+
+ALTER TABLE stream_chapters ADD CONSTRAINT stream_chapters_stream_id_fkey FOREIGN KEY (stream_id) REFERENCES streams(id) ON DELETE CASCADE;
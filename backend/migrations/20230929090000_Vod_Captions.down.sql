@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS vod_caption_cues CASCADE;
+DROP TABLE IF EXISTS vod_captions CASCADE;
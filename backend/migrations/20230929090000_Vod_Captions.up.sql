@@ -0,0 +1,30 @@
+CREATE TABLE vod_captions (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    stream_id uuid NOT NULL, -- foreign key to streams(id)
+    status int NOT NULL DEFAULT 0, -- 0 = queued, 1 = completed, 2 = failed
+    language varchar(16) NOT NULL DEFAULT '', -- BCP-47 tag of the language the transcription backend heard
+    object_key varchar(255) NOT NULL, -- key of the WebVTT file in the object storage of the region
+    region varchar(32) NOT NULL DEFAULT '', -- region the WebVTT file is uploaded to, the same as the recording
+    error text NOT NULL DEFAULT '',
+    -- Timestamps
+    created_at timestamptz NOT NULL DEFAULT NOW(),
+    updated_at timestamptz NOT NULL DEFAULT NOW(),
+    completed_at timestamptz DEFAULT NULL
+);
+
+CREATE TABLE vod_caption_cues (
+    stream_id uuid NOT NULL, -- foreign key to streams(id)
+    start_ms bigint NOT NULL, -- milliseconds since the start of the VOD
+    end_ms bigint NOT NULL,
+    text text NOT NULL
+);
+
+-- Indexes
+
+CREATE UNIQUE INDEX vod_captions_stream_id_idx ON vod_captions (stream_id);
+CREATE INDEX vod_caption_cues_stream_id_idx ON vod_caption_cues (stream_id, start_ms);
+
+-- Foreign keys
+
+ALTER TABLE vod_captions ADD CONSTRAINT vod_captions_stream_id_fkey FOREIGN KEY (stream_id) REFERENCES streams(id) ON DELETE CASCADE;
+ALTER TABLE vod_caption_cues ADD CONSTRAINT vod_caption_cues_stream_id_fkey FOREIGN KEY (stream_id) REFERENCES streams(id) ON DELETE CASCADE;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS chat_bans_channel_id_created_at_idx;
+ALTER TABLE chat_bans DROP COLUMN IF EXISTS expires_at;
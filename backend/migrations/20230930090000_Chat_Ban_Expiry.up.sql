@@ -0,0 +1,5 @@
+ALTER TABLE chat_bans ADD COLUMN expires_at timestamptz DEFAULT NULL; -- NULL = permanent
+
+-- Indexes
+
+CREATE INDEX chat_bans_channel_id_created_at_idx ON chat_bans (channel_id, created_at DESC, user_id DESC);
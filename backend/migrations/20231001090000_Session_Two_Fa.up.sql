@@ -0,0 +1 @@
+ALTER TABLE sessions ADD COLUMN two_fa_at timestamptz DEFAULT NULL; -- when a passkey or recovery code was last checked for the session
@@ -0,0 +1,2 @@
+ALTER TABLE stream_sessions ADD COLUMN peak_viewers bigint NOT NULL DEFAULT 0;
+ALTER TABLE stream_sessions ADD COLUMN average_viewers bigint NOT NULL DEFAULT 0;
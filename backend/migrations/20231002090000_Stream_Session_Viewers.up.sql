@@ -0,0 +1,3 @@
+-- The edge does not report viewer counts, so these were never filled in.
+ALTER TABLE stream_sessions DROP COLUMN IF EXISTS peak_viewers;
+ALTER TABLE stream_sessions DROP COLUMN IF EXISTS average_viewers;
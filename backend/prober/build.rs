@@ -0,0 +1,17 @@
+const PROTO_DIR: &str = "../../proto";
+
+fn main() {
+    let mut config = prost_build::Config::new();
+
+    config.protoc_arg("--experimental_allow_proto3_optional");
+    config.bytes(["."]);
+
+    tonic_build::configure()
+        .build_server(false)
+        .compile_with_config(
+            config,
+            &[format!("{}/scuffle/backend/admin.proto", PROTO_DIR)],
+            &[PROTO_DIR],
+        )
+        .unwrap();
+}
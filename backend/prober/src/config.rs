@@ -0,0 +1,145 @@
+use std::net::SocketAddr;
+
+use anyhow::Result;
+use common::config::{LoggingConfig, TlsConfig};
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+/// The prober runs the path of a streamer and a viewer end to end and reports how it went
+pub struct AppConfig {
+    /// The path to the config file
+    pub config_file: Option<String>,
+
+    /// Name of this instance
+    pub name: String,
+
+    ///  The logging config
+    pub logging: LoggingConfig,
+
+    /// Metrics Config
+    pub metrics: MetricsConfig,
+
+    /// Admin gRPC Config
+    pub admin: AdminConfig,
+
+    /// Probe Config
+    pub probe: ProbeConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Bind address for the Prometheus `/metrics` endpoint
+    pub bind_address: SocketAddr,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: "[::]:9100".parse().expect("failed to parse bind address"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct AdminConfig {
+    /// The addresses of the admin gRPC server of the API, used to find the stream the probe published
+    pub addresses: Vec<String>,
+
+    /// Resolve interval in seconds (0 to disable)
+    pub resolve_interval: u64,
+
+    /// If the admin gRPC server uses TLS
+    pub tls: Option<TlsConfig>,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            addresses: vec!["localhost:50051".to_string()],
+            resolve_interval: 30,
+            tls: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct ProbeConfig {
+    /// How long to wait between the start of two probes in seconds
+    pub interval: u64,
+
+    /// How long a single step may take before it counts as failed in seconds
+    pub step_timeout: u64,
+
+    /// The GraphQL endpoint of the API
+    pub api_url: String,
+
+    /// The RTMP url of the ingest without the stream key
+    pub rtmp_url: String,
+
+    /// The public url of the edge
+    pub edge_url: String,
+
+    /// The captcha token sent when registering, the API has to use a turnstile test key which accepts it
+    pub captcha_token: String,
+
+    /// The invite code used to register, needed while registration is invite only
+    pub invite_code: Option<String>,
+
+    /// The prefix of the usernames of probe accounts, so they can be told apart from real users.
+    /// The rest of the 20 characters a username can have is random
+    pub username_prefix: String,
+
+    /// The domain of the emails of probe accounts
+    pub email_domain: String,
+
+    /// The ffmpeg binary used to publish the synthetic stream
+    pub ffmpeg: String,
+
+    /// How long the synthetic stream is in seconds
+    pub stream_duration: u64,
+}
+
+impl Default for ProbeConfig {
+    fn default() -> Self {
+        Self {
+            interval: 300,
+            step_timeout: 60,
+            api_url: "http://localhost:4000/v1/gql".to_string(),
+            rtmp_url: "rtmp://localhost:1935/live".to_string(),
+            edge_url: "http://localhost:9080".to_string(),
+            captcha_token: "XXXX.DUMMY.TOKEN.XXXX".to_string(),
+            invite_code: None,
+            username_prefix: "probe_".to_string(),
+            email_domain: "probe.scuffle.tv".to_string(),
+            ffmpeg: "ffmpeg".to_string(),
+            stream_duration: 60,
+        }
+    }
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            config_file: Some("config".to_string()),
+            name: "scuffle-prober".to_string(),
+            logging: LoggingConfig::default(),
+            metrics: MetricsConfig::default(),
+            admin: AdminConfig::default(),
+            probe: ProbeConfig::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn parse() -> Result<Self> {
+        let (mut config, config_file) =
+            common::config::parse::<Self>(!cfg!(test), Self::default().config_file)?;
+
+        config.config_file = config_file;
+
+        Ok(config)
+    }
+}
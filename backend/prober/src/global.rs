@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use common::{
+    context::Context,
+    grpc::{make_channel, TlsSettings},
+};
+use tonic::transport::{Certificate, Channel, Identity};
+
+use crate::{config::AppConfig, metrics::Metrics, pb::scuffle::backend::admin_client::AdminClient};
+
+pub struct GlobalState {
+    pub config: AppConfig,
+    pub ctx: Context,
+    pub metrics: Metrics,
+    /// Talks to the API and the edge like a browser would
+    pub http: reqwest::Client,
+    pub admin: AdminClient<Channel>,
+}
+
+impl GlobalState {
+    pub fn new(config: AppConfig, ctx: Context) -> Self {
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("scuffle-prober/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(config.probe.step_timeout))
+            .build()
+            .expect("failed to build http client");
+
+        let admin_channel = make_channel(
+            config.admin.addresses.clone(),
+            Duration::from_secs(config.admin.resolve_interval),
+            if let Some(tls) = &config.admin.tls {
+                let cert = std::fs::read(&tls.cert).expect("failed to read admin cert");
+                let key = std::fs::read(&tls.key).expect("failed to read admin key");
+                let ca = std::fs::read(&tls.ca_cert).expect("failed to read admin ca");
+
+                Some(TlsSettings {
+                    ca_cert: Certificate::from_pem(ca),
+                    identity: Identity::from_pem(cert, key),
+                    domain: tls.domain.clone().unwrap_or_default(),
+                })
+            } else {
+                None
+            },
+        )
+        .expect("failed to create admin channel");
+
+        Self {
+            config,
+            ctx,
+            metrics: Metrics::default(),
+            http,
+            admin: AdminClient::new(admin_channel),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use common::{context::Context, logging, signal};
+use tokio::{select, signal::unix::SignalKind, time};
+
+mod config;
+mod global;
+mod metrics;
+mod pb;
+mod probe;
+mod server;
+
+#[cfg(test)]
+mod tests;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let config = config::AppConfig::parse()?;
+    logging::init(&config.logging.level, config.logging.mode)?;
+
+    if let Some(file) = &config.config_file {
+        tracing::info!(file = file, "loaded config from file");
+    }
+
+    tracing::debug!("config: {:#?}", config);
+
+    let (ctx, handler) = Context::new();
+
+    let global = Arc::new(global::GlobalState::new(config, ctx));
+
+    let server_future = tokio::spawn(server::run(global.clone()));
+    let probe_future = tokio::spawn(probe::run(global.clone()));
+
+    // Listen on both sigint and sigterm and cancel the context when either is received
+    let mut signal_handler = signal::SignalHandler::new()
+        .with_signal(SignalKind::interrupt())
+        .with_signal(SignalKind::terminate());
+
+    select! {
+        r = server_future => tracing::error!("metrics server stopped unexpectedly: {:?}", r),
+        r = probe_future => tracing::error!("prober stopped unexpectedly: {:?}", r),
+        _ = signal_handler.recv() => tracing::info!("shutting down"),
+    }
+
+    // We cannot have a context in scope when we cancel the handler, otherwise it will deadlock.
+    drop(global);
+
+    // Cancel the context
+    tracing::info!("waiting for tasks to finish");
+
+    select! {
+        _ = time::sleep(Duration::from_secs(60)) => tracing::warn!("force shutting down"),
+        _ = signal_handler.recv() => tracing::warn!("force shutting down"),
+        _ = handler.cancel() => tracing::info!("shutting down"),
+    }
+
+    Ok(())
+}
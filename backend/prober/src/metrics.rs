@@ -0,0 +1,215 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+/// A step of a probe, each needs the ones before it to have succeeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Step {
+    /// Register a new account and fetch its stream key.
+    Register,
+    /// Publish the synthetic stream until the transcoder reports its variants.
+    Publish,
+    /// Fetch the playlists and a segment of the stream from the edge.
+    Playback,
+    /// Send a chat message in the channel of the account.
+    Chat,
+}
+
+impl Step {
+    pub const ALL: [Step; 4] = [Step::Register, Step::Publish, Step::Playback, Step::Chat];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Step::Register => "register",
+            Step::Publish => "publish",
+            Step::Playback => "playback",
+            Step::Chat => "chat",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Outcome {
+    successes: u64,
+    failures: u64,
+    /// Whether the last try succeeded and how long it took.
+    last: Option<(bool, Duration)>,
+}
+
+impl Outcome {
+    fn record(&mut self, success: bool, duration: Duration) {
+        if success {
+            self.successes += 1;
+        } else {
+            self.failures += 1;
+        }
+
+        self.last = Some((success, duration));
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    steps: BTreeMap<Step, Outcome>,
+    probes: Outcome,
+    /// Unix timestamp in seconds of the last probe which got through every step.
+    last_success_at: Option<i64>,
+}
+
+/// The results of the probes, rendered in the Prometheus text format for the `/metrics` endpoint.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    inner: Mutex<Inner>,
+}
+
+impl Metrics {
+    pub fn record_step(&self, step: Step, success: bool, duration: Duration) {
+        let mut inner = self.inner.lock().expect("metrics lock poisoned");
+        inner
+            .steps
+            .entry(step)
+            .or_default()
+            .record(success, duration);
+    }
+
+    pub fn record_probe(&self, success: bool, duration: Duration, finished_at: i64) {
+        let mut inner = self.inner.lock().expect("metrics lock poisoned");
+        inner.probes.record(success, duration);
+        if success {
+            inner.last_success_at = Some(finished_at);
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let inner = self.inner.lock().expect("metrics lock poisoned");
+        let mut out = String::new();
+
+        let steps = Step::ALL
+            .iter()
+            .map(|step| {
+                (
+                    step.name(),
+                    inner.steps.get(step).copied().unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        header(
+            &mut out,
+            "scuffle_probe_step_total",
+            "counter",
+            "Tries of each probe step by result.",
+        );
+        for (name, outcome) in &steps {
+            writeln!(
+                out,
+                "scuffle_probe_step_total{{step=\"{}\",result=\"success\"}} {}",
+                name, outcome.successes
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "scuffle_probe_step_total{{step=\"{}\",result=\"failure\"}} {}",
+                name, outcome.failures
+            )
+            .unwrap();
+        }
+
+        header(
+            &mut out,
+            "scuffle_probe_step_up",
+            "gauge",
+            "Whether the last try of each probe step succeeded.",
+        );
+        for (name, outcome) in &steps {
+            if let Some((success, _)) = outcome.last {
+                writeln!(
+                    out,
+                    "scuffle_probe_step_up{{step=\"{}\"}} {}",
+                    name, success as u8
+                )
+                .unwrap();
+            }
+        }
+
+        header(
+            &mut out,
+            "scuffle_probe_step_duration_seconds",
+            "gauge",
+            "How long the last try of each probe step took.",
+        );
+        for (name, outcome) in &steps {
+            if let Some((_, duration)) = outcome.last {
+                writeln!(
+                    out,
+                    "scuffle_probe_step_duration_seconds{{step=\"{}\"}} {}",
+                    name,
+                    duration.as_secs_f64()
+                )
+                .unwrap();
+            }
+        }
+
+        header(
+            &mut out,
+            "scuffle_probe_total",
+            "counter",
+            "Probes by result, a probe succeeds when every step does.",
+        );
+        writeln!(
+            out,
+            "scuffle_probe_total{{result=\"success\"}} {}",
+            inner.probes.successes
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "scuffle_probe_total{{result=\"failure\"}} {}",
+            inner.probes.failures
+        )
+        .unwrap();
+
+        if let Some((success, duration)) = inner.probes.last {
+            header(
+                &mut out,
+                "scuffle_probe_up",
+                "gauge",
+                "Whether the last probe succeeded.",
+            );
+            writeln!(out, "scuffle_probe_up {}", success as u8).unwrap();
+
+            header(
+                &mut out,
+                "scuffle_probe_duration_seconds",
+                "gauge",
+                "How long the last probe took end to end.",
+            );
+            writeln!(
+                out,
+                "scuffle_probe_duration_seconds {}",
+                duration.as_secs_f64()
+            )
+            .unwrap();
+        }
+
+        if let Some(last_success_at) = inner.last_success_at {
+            header(
+                &mut out,
+                "scuffle_probe_last_success_timestamp_seconds",
+                "gauge",
+                "When the last probe which succeeded finished.",
+            );
+            writeln!(
+                out,
+                "scuffle_probe_last_success_timestamp_seconds {}",
+                last_success_at
+            )
+            .unwrap();
+        }
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+    writeln!(out, "# TYPE {} {}", name, kind).unwrap();
+}
@@ -0,0 +1,9 @@
+pub mod scuffle {
+    pub mod backend {
+        tonic::include_proto!("scuffle.backend");
+    }
+
+    pub mod types {
+        tonic::include_proto!("scuffle.types");
+    }
+}
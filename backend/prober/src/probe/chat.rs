@@ -0,0 +1,36 @@
+use anyhow::{ensure, Result};
+use rand::Rng;
+use serde_json::json;
+
+use super::{gql, register::Account};
+use crate::global::GlobalState;
+
+const SEND_MESSAGE: &str = r#"
+    mutation SendMessage($channelId: UUID!, $content: String!) {
+        chat {
+            sendMessage(channelId: $channelId, content: $content) {
+                content
+            }
+        }
+    }
+"#;
+
+/// Sends a chat message in the channel of the probe account.
+pub async fn chat(global: &GlobalState, account: &Account) -> Result<()> {
+    let content = format!("probe {:016x}", rand::thread_rng().gen::<u64>());
+
+    let data = gql::request(
+        global,
+        Some(&account.token),
+        SEND_MESSAGE,
+        json!({ "channelId": account.user_id.to_string(), "content": content }),
+    )
+    .await?;
+
+    ensure!(
+        data["chat"]["sendMessage"]["content"] == content.as_str(),
+        "sent message came back with different content"
+    );
+
+    Ok(())
+}
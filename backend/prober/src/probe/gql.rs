@@ -0,0 +1,40 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+use crate::global::GlobalState;
+
+#[derive(serde::Deserialize)]
+struct Response {
+    data: Option<Value>,
+    #[serde(default)]
+    errors: Vec<Error>,
+}
+
+#[derive(serde::Deserialize)]
+struct Error {
+    message: String,
+}
+
+/// Sends a GraphQL request to the API, logged in with the session token if one is given.
+pub async fn request(
+    global: &GlobalState,
+    token: Option<&str>,
+    query: &str,
+    variables: Value,
+) -> Result<Value> {
+    let mut request = global
+        .http
+        .post(&global.config.probe.api_url)
+        .json(&json!({ "query": query, "variables": variables }));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+
+    let response: Response = request.send().await?.error_for_status()?.json().await?;
+
+    if let Some(error) = response.errors.first() {
+        return Err(anyhow!("api returned an error: {}", error.message));
+    }
+
+    response.data.ok_or_else(|| anyhow!("api returned no data"))
+}
@@ -0,0 +1,97 @@
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context, Result};
+use common::prelude::FutureTimeout;
+use tokio::{select, time::MissedTickBehavior};
+
+use crate::{global::GlobalState, metrics::Step};
+
+pub mod chat;
+pub mod gql;
+pub mod playback;
+pub mod publish;
+pub mod register;
+
+/// Probes on the configured interval. Probes run one after another, a slow one delays the next instead of overlapping it.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    let mut interval = tokio::time::interval(Duration::from_secs(global.config.probe.interval));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        select! {
+            _ = interval.tick() => {},
+            _ = global.ctx.done() => return Ok(()),
+        }
+
+        select! {
+            _ = probe(&global) => {},
+            _ = global.ctx.done() => return Ok(()),
+        }
+    }
+}
+
+/// Times a step and records how it went. Every step has to finish within the step timeout.
+async fn step<T>(
+    global: &GlobalState,
+    step: Step,
+    fut: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let start = Instant::now();
+
+    let result = fut
+        .timeout(Duration::from_secs(global.config.probe.step_timeout))
+        .await
+        .map_err(|_| anyhow!("timed out"))
+        .and_then(|r| r);
+
+    global
+        .metrics
+        .record_step(step, result.is_ok(), start.elapsed());
+
+    result.with_context(|| format!("{} step failed", step.name()))
+}
+
+/// Runs the path of a new streamer and their viewers once: register, go live, watch and chat.
+async fn probe(global: &GlobalState) {
+    let start = Instant::now();
+
+    let result = async {
+        let account = step(global, Step::Register, register::register(global)).await?;
+
+        // The stream stays up until the probe is done, so playback and chat happen while the channel is live.
+        let publisher = step(global, Step::Publish, publish::publish(global, &account)).await?;
+        step(
+            global,
+            Step::Playback,
+            playback::playback(global, &publisher.stream_id),
+        )
+        .await?;
+        step(global, Step::Chat, chat::chat(global, &account)).await?;
+
+        tracing::debug!(
+            username = %account.username,
+            stream_id = %publisher.stream_id,
+            "probe succeeded"
+        );
+
+        Ok::<_, anyhow::Error>(())
+    }
+    .await;
+
+    let finished_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+
+    global
+        .metrics
+        .record_probe(result.is_ok(), start.elapsed(), finished_at);
+
+    if let Err(e) = result {
+        tracing::warn!("probe failed: {:#}", e);
+    }
+}
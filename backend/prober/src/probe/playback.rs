@@ -0,0 +1,58 @@
+use anyhow::{ensure, Context, Result};
+use reqwest::Url;
+
+use crate::global::GlobalState;
+
+/// The URIs in an HLS playlist, the lines which are not tags or comments.
+pub fn uris(playlist: &str) -> impl Iterator<Item = &str> {
+    playlist
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+async fn fetch(global: &GlobalState, url: Url) -> Result<String> {
+    Ok(global
+        .http
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?)
+}
+
+/// Plays the stream back like a viewer, from the master playlist down to the newest segment of the first variant.
+pub async fn playback(global: &GlobalState, stream_id: &str) -> Result<()> {
+    let master_url = Url::parse(&format!(
+        "{}/{}/master.m3u8",
+        global.config.probe.edge_url.trim_end_matches('/'),
+        stream_id
+    ))?;
+    let master = fetch(global, master_url.clone()).await?;
+
+    let variant_url = master_url.join(
+        uris(&master)
+            .next()
+            .context("master playlist has no variants")?,
+    )?;
+    let variant = fetch(global, variant_url.clone()).await?;
+
+    let segment_url = variant_url.join(
+        uris(&variant)
+            .last()
+            .context("variant playlist has no segments")?,
+    )?;
+    let segment = global
+        .http
+        .get(segment_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    ensure!(!segment.is_empty(), "segment is empty");
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use std::{process::Stdio, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use tokio::process::{Child, Command};
+
+use super::register::Account;
+use crate::{global::GlobalState, pb::scuffle::backend::ListLiveStreamsRequest};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The synthetic stream, ffmpeg is killed when this is dropped which ends the stream.
+pub struct Publisher {
+    _ffmpeg: Child,
+    pub stream_id: String,
+}
+
+/// The arguments for ffmpeg to publish a test pattern with a tone for `duration` seconds, in real time.
+pub fn ffmpeg_args(url: &str, duration: u64) -> Vec<String> {
+    [
+        "-hide_banner",
+        "-loglevel",
+        "error",
+        "-re",
+        "-f",
+        "lavfi",
+        "-i",
+        "testsrc2=size=1280x720:rate=30",
+        "-f",
+        "lavfi",
+        "-i",
+        "sine=frequency=440:sample_rate=48000",
+        "-t",
+        &duration.to_string(),
+        "-c:v",
+        "libx264",
+        "-preset",
+        "veryfast",
+        "-tune",
+        "zerolatency",
+        "-pix_fmt",
+        "yuv420p",
+        "-g",
+        "60",
+        "-c:a",
+        "aac",
+        "-b:a",
+        "128k",
+        "-f",
+        "flv",
+        url,
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+/// Publishes the synthetic stream to the ingest and waits until the transcoder published its variants.
+pub async fn publish(global: &GlobalState, account: &Account) -> Result<Publisher> {
+    let config = &global.config.probe;
+    let url = format!(
+        "{}/{}",
+        config.rtmp_url.trim_end_matches('/'),
+        account.stream_key
+    );
+
+    let mut ffmpeg = Command::new(&config.ffmpeg)
+        .args(ffmpeg_args(&url, config.stream_duration))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .context("failed to start ffmpeg")?;
+
+    loop {
+        if let Some(status) = ffmpeg.try_wait()? {
+            bail!("ffmpeg exited before the stream was ready: {}", status);
+        }
+
+        let streams = global
+            .admin
+            .clone()
+            .list_live_streams(ListLiveStreamsRequest {
+                username: Some(account.username.clone()),
+            })
+            .await?
+            .into_inner()
+            .streams;
+
+        let ready = streams.into_iter().find(|stream| {
+            stream
+                .state
+                .as_ref()
+                .map_or(false, |state| !state.variants.is_empty())
+        });
+
+        if let Some(stream) = ready {
+            return Ok(Publisher {
+                _ffmpeg: ffmpeg,
+                stream_id: stream.stream_id,
+            });
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+use rand::{distributions::Alphanumeric, Rng};
+use serde_json::json;
+use uuid::Uuid;
+
+use super::gql;
+use crate::global::GlobalState;
+
+const REGISTER: &str = r#"
+    mutation Register($username: String!, $password: String!, $email: String!, $captchaToken: String!, $inviteCode: String) {
+        auth {
+            register(username: $username, password: $password, email: $email, captchaToken: $captchaToken, inviteCode: $inviteCode, updateContext: false) {
+                token
+                user {
+                    id
+                    streamKey
+                }
+            }
+        }
+    }
+"#;
+
+/// Usernames are at most 20 characters.
+const MAX_USERNAME_LENGTH: usize = 20;
+
+/// The account the probe registered.
+pub struct Account {
+    pub user_id: Uuid,
+    pub username: String,
+    /// The session token from registering.
+    pub token: String,
+    /// The full stream key, as it is entered in the streaming software.
+    pub stream_key: String,
+}
+
+/// A username with the prefix, filled up with random characters to the length limit so it is not taken.
+pub fn username(prefix: &str) -> String {
+    let random = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(MAX_USERNAME_LENGTH.saturating_sub(prefix.len()))
+        .map(|c| (c as char).to_ascii_lowercase())
+        .collect::<String>();
+
+    format!("{}{}", prefix, random)
+}
+
+/// A password which passes the password rules of the API.
+pub fn password() -> String {
+    let random = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(24)
+        .map(char::from)
+        .collect::<String>();
+
+    format!("Pr0be!{}", random)
+}
+
+pub async fn register(global: &GlobalState) -> Result<Account> {
+    let config = &global.config.probe;
+    let username = username(&config.username_prefix);
+
+    let data = gql::request(
+        global,
+        None,
+        REGISTER,
+        json!({
+            "username": username,
+            "password": password(),
+            "email": format!("{}@{}", username, config.email_domain),
+            "captchaToken": config.captcha_token,
+            "inviteCode": config.invite_code,
+        }),
+    )
+    .await?;
+
+    let session = &data["auth"]["register"];
+
+    Ok(Account {
+        user_id: session["user"]["id"]
+            .as_str()
+            .context("registered user has no id")?
+            .parse()?,
+        username,
+        token: session["token"]
+            .as_str()
+            .context("registered session has no token")?
+            .to_string(),
+        stream_key: session["user"]["streamKey"]
+            .as_str()
+            .context("registered user has no stream key")?
+            .to_string(),
+    })
+}
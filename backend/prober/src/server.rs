@@ -0,0 +1,69 @@
+use std::{convert::Infallible, sync::Arc};
+
+use anyhow::Result;
+use hyper::{
+    header, server::conn::Http, service::service_fn, Body, Method, Request, Response, StatusCode,
+};
+use tokio::{net::TcpSocket, select};
+
+use crate::global::GlobalState;
+
+fn respond(global: &GlobalState, req: &Request<Body>) -> Response<Body> {
+    let response = Response::builder();
+
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, "/metrics") => response
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(Body::from(global.metrics.render())),
+        (&Method::GET, "/health") => response.status(StatusCode::OK).body(Body::from("ok")),
+        _ => response.status(StatusCode::NOT_FOUND).body(Body::empty()),
+    }
+    .expect("failed to build response")
+}
+
+/// Serves the results of the probes for Prometheus to scrape.
+pub async fn run(global: Arc<GlobalState>) -> Result<()> {
+    tracing::info!("Listening on {}", global.config.metrics.bind_address);
+    let socket = if global.config.metrics.bind_address.is_ipv6() {
+        TcpSocket::new_v6()?
+    } else {
+        TcpSocket::new_v4()?
+    };
+
+    socket.set_reuseaddr(true)?;
+    socket.set_reuseport(true)?;
+    socket.bind(global.config.metrics.bind_address)?;
+    let listener = socket.listen(1024)?;
+
+    // Connections only hold a Weak reference to the global state, so open keep-alive connections don't block the shutdown.
+    let weak = Arc::downgrade(&global);
+
+    loop {
+        select! {
+            _ = global.ctx.done() => {
+                return Ok(());
+            },
+            r = listener.accept() => {
+                let (socket, addr) = r?;
+                tracing::debug!("Accepted connection from {}", addr);
+
+                let weak = weak.clone();
+                tokio::spawn(Http::new().serve_connection(
+                    socket,
+                    service_fn(move |req| {
+                        let response = match weak.upgrade() {
+                            Some(global) => respond(&global, &req),
+                            None => Response::builder()
+                                .status(StatusCode::SERVICE_UNAVAILABLE)
+                                .body(Body::empty())
+                                .expect("failed to build response"),
+                        };
+
+                        async move { Ok::<_, Infallible>(response) }
+                    }),
+                ));
+            },
+        }
+    }
+}
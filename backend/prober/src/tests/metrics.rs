@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use crate::metrics::{Metrics, Step};
+
+#[test]
+fn test_render_empty() {
+    let out = Metrics::default().render();
+
+    assert!(out.contains("scuffle_probe_step_total{step=\"register\",result=\"success\"} 0\n"));
+    assert!(out.contains("scuffle_probe_step_total{step=\"chat\",result=\"failure\"} 0\n"));
+    assert!(out.contains("scuffle_probe_total{result=\"success\"} 0\n"));
+    // Nothing ran yet, so there is no last result to report.
+    assert!(!out.contains("scuffle_probe_step_up{"));
+    assert!(!out.contains("scuffle_probe_up "));
+    assert!(!out.contains("scuffle_probe_last_success_timestamp_seconds "));
+}
+
+#[test]
+fn test_render() {
+    let metrics = Metrics::default();
+
+    metrics.record_step(Step::Register, true, Duration::from_millis(250));
+    metrics.record_step(Step::Publish, true, Duration::from_secs(8));
+    metrics.record_step(Step::Playback, true, Duration::from_millis(500));
+    metrics.record_step(Step::Chat, true, Duration::from_millis(100));
+    metrics.record_probe(true, Duration::from_secs(9), 1_694_000_000);
+
+    metrics.record_step(Step::Register, true, Duration::from_millis(200));
+    metrics.record_step(Step::Publish, false, Duration::from_secs(60));
+    metrics.record_probe(false, Duration::from_secs(61), 1_694_000_300);
+
+    let out = metrics.render();
+
+    assert!(out.contains("# TYPE scuffle_probe_step_total counter\n"));
+    assert!(out.contains("scuffle_probe_step_total{step=\"register\",result=\"success\"} 2\n"));
+    assert!(out.contains("scuffle_probe_step_total{step=\"publish\",result=\"success\"} 1\n"));
+    assert!(out.contains("scuffle_probe_step_total{step=\"publish\",result=\"failure\"} 1\n"));
+    assert!(out.contains("scuffle_probe_step_total{step=\"chat\",result=\"success\"} 1\n"));
+
+    assert!(out.contains("scuffle_probe_step_up{step=\"register\"} 1\n"));
+    assert!(out.contains("scuffle_probe_step_up{step=\"publish\"} 0\n"));
+    assert!(out.contains("scuffle_probe_step_duration_seconds{step=\"register\"} 0.2\n"));
+    assert!(out.contains("scuffle_probe_step_duration_seconds{step=\"publish\"} 60\n"));
+
+    assert!(out.contains("scuffle_probe_total{result=\"success\"} 1\n"));
+    assert!(out.contains("scuffle_probe_total{result=\"failure\"} 1\n"));
+    assert!(out.contains("scuffle_probe_up 0\n"));
+    assert!(out.contains("scuffle_probe_duration_seconds 61\n"));
+    // A failed probe does not move the time of the last success.
+    assert!(out.contains("scuffle_probe_last_success_timestamp_seconds 1694000000\n"));
+}
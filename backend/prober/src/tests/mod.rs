@@ -0,0 +1,3 @@
+mod config;
+mod metrics;
+mod probe;
@@ -0,0 +1,50 @@
+use crate::probe::{playback, publish, register};
+
+#[test]
+fn test_username() {
+    let username = register::username("probe_");
+
+    assert!(username.starts_with("probe_"));
+    assert_eq!(username.len(), 20);
+    assert!(username
+        .chars()
+        .all(|c| c == '_' || c.is_ascii_lowercase() || c.is_ascii_digit()));
+    assert_ne!(username, register::username("probe_"));
+}
+
+#[test]
+fn test_password() {
+    let password = register::password();
+
+    assert!(password.chars().any(|c| c.is_ascii_lowercase()));
+    assert!(password.chars().any(|c| c.is_ascii_uppercase()));
+    assert!(password.chars().any(|c| c.is_ascii_digit()));
+    assert!(password.chars().any(|c| !c.is_ascii_alphanumeric()));
+    assert!(password.len() >= 8 && password.len() <= 100);
+}
+
+#[test]
+fn test_ffmpeg_args() {
+    let args = publish::ffmpeg_args("rtmp://localhost:1935/live/live_1_abc", 30);
+
+    let duration = args.iter().position(|a| a == "-t").unwrap();
+    assert_eq!(args[duration + 1], "30");
+    assert_eq!(
+        args[args.len() - 3..],
+        ["-f", "flv", "rtmp://localhost:1935/live/live_1_abc"]
+    );
+}
+
+#[test]
+fn test_uris() {
+    let master = "#EXTM3U\n#EXT-X-INDEPENDENT-SEGMENTS\n#EXT-X-STREAM-INF:BANDWIDTH=6000000,RESOLUTION=1280x720\n720p/index.m3u8\n\n#EXT-X-STREAM-INF:BANDWIDTH=128000\naudio/index.m3u8\n";
+    assert_eq!(
+        playback::uris(master).collect::<Vec<_>>(),
+        vec!["720p/index.m3u8", "audio/index.m3u8"]
+    );
+
+    let variant = "#EXTM3U\r\n#EXT-X-MAP:URI=\"init.mp4\"\r\n#EXTINF:2.000,\r\n0.mp4\r\n#EXTINF:2.000,\r\n1.mp4\r\n";
+    assert_eq!(playback::uris(variant).last(), Some("1.mp4"));
+
+    assert_eq!(playback::uris("#EXTM3U\n").next(), None);
+}
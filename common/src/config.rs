@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use anyhow::Result;
 
@@ -115,6 +115,158 @@ impl Default for RedisConfig {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// The database URL to use
+    pub uri: String,
+
+    /// The maximum number of connections in the pool
+    pub max_connections: u32,
+
+    /// The number of connections the pool keeps open even when they are idle
+    pub min_connections: u32,
+
+    /// How long a query waits for a connection in seconds before it fails
+    pub acquire_timeout: u32,
+
+    /// Waiting longer than this for a connection in milliseconds is logged as a warning
+    pub slow_acquire_threshold: u32,
+
+    /// Statements which take longer than this in milliseconds are logged as a warning, with their query
+    pub slow_statement_threshold: u32,
+
+    /// How often the pool statistics are logged in seconds, 0 disables them
+    pub stats_interval: u32,
+
+    /// How often a transaction which lost a conflict with a concurrent one is run again before the error is returned
+    pub max_retries: u32,
+
+    /// How long to wait before the first retry in milliseconds, doubled for every retry after it
+    pub retry_backoff: u32,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            uri: "postgres://root@localhost:5432/scuffle_dev".to_string(),
+            max_connections: 10,
+            min_connections: 0,
+            acquire_timeout: 30,
+            slow_acquire_threshold: 100,
+            slow_statement_threshold: 1000,
+            stats_interval: 60,
+            max_retries: 3,
+            retry_backoff: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
+#[serde(default)]
+pub struct SecurityHeadersConfig {
+    /// If the security headers are added to responses
+    pub enabled: bool,
+
+    /// The Content-Security-Policy of routes without a policy of their own
+    pub content_security_policy: String,
+
+    /// Content-Security-Policies of routes by path prefix, the longest matching prefix wins
+    pub route_policies: HashMap<String, String>,
+
+    /// The origins allowed to embed responses in a frame, like the websites embedding the player
+    pub frame_ancestors: Vec<String>,
+
+    /// The max-age of the Strict-Transport-Security header in seconds, 0 disables HSTS
+    pub hsts_max_age: u32,
+
+    /// If HSTS also applies to subdomains
+    pub hsts_include_subdomains: bool,
+
+    /// Where browsers report Content-Security-Policy violations to
+    pub report_uri: Option<String>,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            content_security_policy: "default-src 'none'".to_string(),
+            route_policies: HashMap::new(),
+            frame_ancestors: Vec::new(),
+            hsts_max_age: 31536000,
+            hsts_include_subdomains: false,
+            report_uri: None,
+        }
+    }
+}
+
+impl SecurityHeadersConfig {
+    /// The Content-Security-Policy of a path, with the frame ancestors and the report uri added.
+    pub fn content_security_policy(&self, path: &str) -> String {
+        let mut policy = self
+            .route_policies
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, policy)| policy.as_str())
+            .unwrap_or(&self.content_security_policy)
+            .trim()
+            .trim_end_matches(';')
+            .to_string();
+
+        if !policy.contains("frame-ancestors") {
+            let ancestors = match self.frame_ancestors.is_empty() {
+                true => "'none'".to_string(),
+                false => self.frame_ancestors.join(" "),
+            };
+
+            policy.push_str(&format!("; frame-ancestors {}", ancestors));
+        }
+
+        if let Some(report_uri) = &self.report_uri {
+            policy.push_str(&format!("; report-uri {}", report_uri));
+        }
+
+        policy.trim_start_matches("; ").to_string()
+    }
+
+    /// The headers added to a response for a path.
+    pub fn headers(&self, path: &str) -> Vec<(&'static str, String)> {
+        if !self.enabled {
+            return Vec::new();
+        }
+
+        let mut headers = vec![
+            (
+                "Content-Security-Policy",
+                self.content_security_policy(path),
+            ),
+            ("X-Content-Type-Options", "nosniff".to_string()),
+            (
+                "Referrer-Policy",
+                "strict-origin-when-cross-origin".to_string(),
+            ),
+        ];
+
+        // Browsers without frame-ancestors support only understand a single origin, so we can only deny.
+        if self.frame_ancestors.is_empty() {
+            headers.push(("X-Frame-Options", "DENY".to_string()));
+        }
+
+        if self.hsts_max_age > 0 {
+            let mut hsts = format!("max-age={}", self.hsts_max_age);
+            if self.hsts_include_subdomains {
+                hsts.push_str("; includeSubDomains");
+            }
+
+            headers.push(("Strict-Transport-Security", hsts));
+        }
+
+        headers
+    }
+}
+
 pub fn parse<C: config::Config + 'static>(
     enable_cli: bool,
     config_file: Option<String>,
@@ -0,0 +1,195 @@
+use std::{future::Future, str::FromStr, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use sqlx::{
+    postgres::{PgConnectOptions, PgPoolOptions},
+    ConnectOptions, PgPool,
+};
+use tokio::time::Instant;
+
+use crate::{config::DatabaseConfig, context::Context};
+
+pub async fn connect(config: &DatabaseConfig) -> Result<PgPool> {
+    let options = PgConnectOptions::from_str(&config.uri)?
+        .disable_statement_logging()
+        .log_slow_statements(
+            log::LevelFilter::Warn,
+            Duration::from_millis(config.slow_statement_threshold as u64),
+        )
+        .to_owned();
+
+    Ok(PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(Duration::from_secs(config.acquire_timeout as u64))
+        .connect_with(options)
+        .await?)
+}
+
+/// A snapshot of how busy a pool is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    /// The number of open connections.
+    pub size: u32,
+    /// The number of open connections nobody is using.
+    pub idle: u32,
+    /// The maximum number of connections.
+    pub max: u32,
+    /// How long it took to get a connection. The pool does not tell how many tasks are waiting for one,
+    /// but this only grows once there are waiters.
+    pub acquire: Duration,
+}
+
+impl PoolStats {
+    pub fn in_use(&self) -> u32 {
+        self.size.saturating_sub(self.idle)
+    }
+
+    /// If every connection is in use, so new queries have to wait for one.
+    pub fn is_saturated(&self) -> bool {
+        self.in_use() >= self.max
+    }
+
+    pub fn is_slow(&self, config: &DatabaseConfig) -> bool {
+        self.acquire >= Duration::from_millis(config.slow_acquire_threshold as u64)
+    }
+}
+
+/// Measures the pool by taking a connection out of it, just like a query would.
+pub async fn stats(pool: &PgPool) -> Result<PoolStats> {
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+
+    let start = Instant::now();
+    let conn = pool.acquire().await?;
+    let acquire = start.elapsed();
+    drop(conn);
+
+    Ok(PoolStats {
+        size,
+        idle,
+        max: pool.options().get_max_connections(),
+        acquire,
+    })
+}
+
+/// Logs the statistics of a pool until the context is done, and warns when queries have to wait for connections.
+pub async fn monitor(ctx: Context, pool: Arc<PgPool>, config: DatabaseConfig) {
+    if config.stats_interval == 0 {
+        return;
+    }
+
+    let mut interval = tokio::time::interval(Duration::from_secs(config.stats_interval as u64));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = ctx.done() => return,
+        }
+
+        let stats = match stats(&pool).await {
+            Ok(stats) => stats,
+            Err(e) => {
+                tracing::warn!("failed to acquire database connection: {}", e);
+                continue;
+            }
+        };
+
+        tracing::info!(
+            size = stats.size,
+            in_use = stats.in_use(),
+            idle = stats.idle,
+            max = stats.max,
+            acquire_ms = stats.acquire.as_millis() as u64,
+            "database pool stats"
+        );
+
+        if stats.is_saturated() || stats.is_slow(&config) {
+            tracing::warn!(
+                in_use = stats.in_use(),
+                max = stats.max,
+                acquire_ms = stats.acquire.as_millis() as u64,
+                "database pool is exhausted, consider raising max_connections"
+            );
+        }
+    }
+}
+
+/// What a failed query means for the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The transaction lost a conflict with a concurrent one and was rolled back (40001), running it again can succeed.
+    Retryable,
+    /// The connection was lost while committing, so the transaction may or may not have been applied (40003).
+    /// CockroachDB reports this as an ambiguous result.
+    Ambiguous,
+    /// A unique constraint was violated (23505).
+    UniqueViolation,
+    Other,
+}
+
+impl ErrorKind {
+    pub fn of(error: &sqlx::Error) -> Self {
+        let sqlx::Error::Database(e) = error else {
+            return Self::Other;
+        };
+
+        match e.code().as_deref() {
+            Some("40001") => Self::Retryable,
+            Some("40003") => Self::Ambiguous,
+            Some("23505") => Self::UniqueViolation,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// An error which may have been caused by a query, so [`retry`] can tell if it is worth running again.
+pub trait DatabaseError {
+    fn database_error(&self) -> Option<&sqlx::Error>;
+}
+
+impl DatabaseError for sqlx::Error {
+    fn database_error(&self) -> Option<&sqlx::Error> {
+        Some(self)
+    }
+}
+
+impl DatabaseError for anyhow::Error {
+    fn database_error(&self) -> Option<&sqlx::Error> {
+        self.downcast_ref()
+    }
+}
+
+/// The time to wait before the given retry, starting at 1.
+pub fn retry_delay(config: &DatabaseConfig, retry: u32) -> Duration {
+    Duration::from_millis(config.retry_backoff as u64)
+        * 2u32.saturating_pow(retry.saturating_sub(1))
+}
+
+/// Runs a transaction again when it lost a conflict or its commit was ambiguous, waiting longer before every retry.
+/// Only use this for idempotent transactions, an ambiguous commit may have been applied already.
+/// Every call of `f` has to begin a new transaction, the failed one was rolled back.
+pub async fn retry<T, E, F, Fut>(config: &DatabaseConfig, mut f: F) -> Result<T, E>
+where
+    E: DatabaseError,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut retries = 0;
+    loop {
+        let error = match f().await {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+
+        let kind = error.database_error().map(ErrorKind::of);
+        if retries >= config.max_retries
+            || !matches!(kind, Some(ErrorKind::Retryable | ErrorKind::Ambiguous))
+        {
+            return Err(error);
+        }
+
+        retries += 1;
+        tracing::debug!(retries, ?kind, "retrying transaction");
+        tokio::time::sleep(retry_delay(config, retries)).await;
+    }
+}
@@ -4,10 +4,14 @@
 pub mod config;
 #[cfg(feature = "context")]
 pub mod context;
+#[cfg(feature = "database")]
+pub mod database;
 #[cfg(feature = "grpc")]
 pub mod grpc;
 #[cfg(feature = "logging")]
 pub mod logging;
+#[cfg(feature = "net")]
+pub mod net;
 #[cfg(feature = "prelude")]
 pub mod prelude;
 #[cfg(feature = "rmq")]
@@ -0,0 +1,56 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// If an address is reachable on the public internet. Services which connect to addresses given by users
+/// check this for every address a host resolves to, so they can't be pointed into our own network.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        // 0.0.0.0/8, "this network"
+        || a == 0
+        // 100.64.0.0/10, carrier-grade NAT
+        || (a == 100 && b & 0xc0 == 64))
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    if ip.is_unspecified() || ip.is_loopback() || ip.is_multicast() {
+        return false;
+    }
+
+    let segments = ip.segments();
+
+    // fc00::/7, unique local
+    if segments[0] & 0xfe00 == 0xfc00 {
+        return false;
+    }
+
+    // fe80::/10, link-local
+    if segments[0] & 0xffc0 == 0xfe80 {
+        return false;
+    }
+
+    // 64:ff9b::/96, NAT64 reaches the embedded IPv4 address.
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        return is_public_v4(Ipv4Addr::from(
+            u32::from(segments[6]) << 16 | u32::from(segments[7]),
+        ));
+    }
+
+    // IPv4-mapped and IPv4-compatible addresses reach the IPv4 address.
+    match ip.to_ipv4() {
+        Some(v4) => is_public_v4(v4),
+        None => true,
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use crate::config::SecurityHeadersConfig;
+
+#[test]
+fn test_content_security_policy() {
+    let config = SecurityHeadersConfig {
+        route_policies: HashMap::from([
+            (
+                "/v1/gql/playground".to_string(),
+                "default-src 'self'; frame-ancestors 'self';".to_string(),
+            ),
+            (
+                "/v1".to_string(),
+                "default-src 'none'; img-src *".to_string(),
+            ),
+        ]),
+        ..Default::default()
+    };
+
+    let tests = vec![
+        ("/", "default-src 'none'; frame-ancestors 'none'"),
+        (
+            "/v1/gql",
+            "default-src 'none'; img-src *; frame-ancestors 'none'",
+        ),
+        (
+            "/v1/gql/playground",
+            "default-src 'self'; frame-ancestors 'self'",
+        ),
+    ];
+
+    for (path, expected) in tests {
+        assert_eq!(config.content_security_policy(path), expected, "{}", path);
+    }
+
+    let config = SecurityHeadersConfig {
+        content_security_policy: String::new(),
+        frame_ancestors: vec![
+            "https://scuffle.tv".to_string(),
+            "https://*.example".to_string(),
+        ],
+        report_uri: Some("https://api.scuffle.tv/v1/csp-report".to_string()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        config.content_security_policy("/"),
+        "frame-ancestors https://scuffle.tv https://*.example; report-uri https://api.scuffle.tv/v1/csp-report"
+    );
+}
+
+#[test]
+fn test_security_headers() {
+    let config = SecurityHeadersConfig::default();
+    let headers = config.headers("/");
+
+    assert_eq!(
+        headers,
+        vec![
+            (
+                "Content-Security-Policy",
+                "default-src 'none'; frame-ancestors 'none'".to_string()
+            ),
+            ("X-Content-Type-Options", "nosniff".to_string()),
+            (
+                "Referrer-Policy",
+                "strict-origin-when-cross-origin".to_string()
+            ),
+            ("X-Frame-Options", "DENY".to_string()),
+            ("Strict-Transport-Security", "max-age=31536000".to_string()),
+        ]
+    );
+
+    let config = SecurityHeadersConfig {
+        frame_ancestors: vec!["*".to_string()],
+        hsts_include_subdomains: true,
+        ..Default::default()
+    };
+    let headers = config.headers("/");
+
+    assert!(!headers.iter().any(|(name, _)| *name == "X-Frame-Options"));
+    assert!(headers.contains(&(
+        "Strict-Transport-Security",
+        "max-age=31536000; includeSubDomains".to_string()
+    )));
+
+    let config = SecurityHeadersConfig {
+        hsts_max_age: 0,
+        ..Default::default()
+    };
+    assert!(!config
+        .headers("/")
+        .iter()
+        .any(|(name, _)| *name == "Strict-Transport-Security"));
+
+    let config = SecurityHeadersConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    assert!(config.headers("/").is_empty());
+}
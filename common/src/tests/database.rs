@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use crate::{
+    config::DatabaseConfig,
+    database::{retry, retry_delay, ErrorKind, PoolStats},
+};
+
+#[test]
+fn test_pool_stats() {
+    let config = DatabaseConfig {
+        slow_acquire_threshold: 100,
+        ..Default::default()
+    };
+
+    let tests = vec![
+        ((4, 4, 10, 0), (0, false, false)),
+        ((10, 2, 10, 5), (8, false, false)),
+        ((10, 0, 10, 50), (10, true, false)),
+        ((10, 0, 10, 250), (10, true, true)),
+        // A connection can be closed between reading the size and the idle count.
+        ((3, 4, 10, 0), (0, false, false)),
+    ];
+
+    for ((size, idle, max, acquire), (in_use, saturated, slow)) in tests {
+        let stats = PoolStats {
+            size,
+            idle,
+            max,
+            acquire: Duration::from_millis(acquire),
+        };
+
+        assert_eq!(stats.in_use(), in_use, "{:?}", stats);
+        assert_eq!(stats.is_saturated(), saturated, "{:?}", stats);
+        assert_eq!(stats.is_slow(&config), slow, "{:?}", stats);
+    }
+}
+
+#[test]
+fn test_retry_delay() {
+    let config = DatabaseConfig {
+        retry_backoff: 20,
+        ..Default::default()
+    };
+
+    assert_eq!(retry_delay(&config, 1), Duration::from_millis(20));
+    assert_eq!(retry_delay(&config, 2), Duration::from_millis(40));
+    assert_eq!(retry_delay(&config, 3), Duration::from_millis(80));
+}
+
+#[tokio::test]
+async fn test_retry_other_errors() {
+    let config = DatabaseConfig::default();
+
+    assert_eq!(ErrorKind::of(&sqlx::Error::RowNotFound), ErrorKind::Other);
+
+    // Only conflicts are worth running again, anything else is returned right away.
+    let mut calls = 0;
+    let result: Result<(), sqlx::Error> = retry(&config, || {
+        calls += 1;
+        async { Err(sqlx::Error::RowNotFound) }
+    })
+    .await;
+
+    assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    assert_eq!(calls, 1);
+}
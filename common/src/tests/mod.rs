@@ -1,8 +1,14 @@
+#[cfg(feature = "config")]
+mod config;
 #[cfg(feature = "context")]
 mod context;
+#[cfg(feature = "database")]
+mod database;
 #[cfg(feature = "grpc")]
 mod grpc;
 #[cfg(feature = "logging")]
 mod logging;
+#[cfg(feature = "net")]
+mod net;
 #[cfg(feature = "signal")]
 mod signal;
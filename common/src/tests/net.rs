@@ -0,0 +1,38 @@
+use std::net::IpAddr;
+
+use crate::net::is_public;
+
+#[test]
+fn test_is_public() {
+    let tests = vec![
+        ("203.0.113.7", true),
+        ("8.8.8.8", true),
+        ("2001:db8::1", true),
+        ("2606:4700::1111", true),
+        ("::ffff:203.0.113.7", true),
+        ("0.0.0.0", false),
+        ("0.1.2.3", false),
+        ("127.0.0.1", false),
+        ("10.0.0.5", false),
+        ("172.16.0.1", false),
+        ("192.168.1.20", false),
+        ("169.254.169.254", false),
+        ("100.64.0.1", false),
+        ("255.255.255.255", false),
+        ("224.0.0.1", false),
+        ("::", false),
+        ("::1", false),
+        ("fc00::1", false),
+        ("fd12:3456::1", false),
+        ("fe80::1", false),
+        ("ff02::1", false),
+        ("::ffff:127.0.0.1", false),
+        ("::ffff:10.0.0.1", false),
+        ("::ffff:169.254.169.254", false),
+        ("64:ff9b::a9fe:a9fe", false),
+    ];
+
+    for (ip, expected) in tests {
+        assert_eq!(is_public(ip.parse::<IpAddr>().unwrap()), expected, "{}", ip);
+    }
+}
@@ -1,7 +1,7 @@
 use std::net::SocketAddr;
 
 use anyhow::Result;
-use common::config::{LoggingConfig, RedisConfig, TlsConfig};
+use common::config::{LoggingConfig, RedisConfig, SecurityHeadersConfig, TlsConfig};
 
 #[derive(Debug, Clone, PartialEq, config::Config, serde::Deserialize)]
 #[serde(default)]
@@ -61,6 +61,9 @@ pub struct AppConfig {
 
     /// Redis configuration
     pub redis: RedisConfig,
+
+    /// Security Headers Config
+    pub security_headers: SecurityHeadersConfig,
 }
 
 impl Default for AppConfig {
@@ -72,6 +75,7 @@ impl Default for AppConfig {
             grpc: GrpcConfig::default(),
             logging: LoggingConfig::default(),
             redis: RedisConfig::default(),
+            security_headers: SecurityHeadersConfig::default(),
         }
     }
 }
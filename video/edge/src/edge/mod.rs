@@ -75,6 +75,26 @@ pub fn cors_middleware(_: &Arc<GlobalState>) -> Middleware<Body, RouteError> {
     })
 }
 
+pub fn security_headers_middleware(global: &Arc<GlobalState>) -> Middleware<Body, RouteError> {
+    let config = global.config.security_headers.clone();
+
+    Middleware::post_with_info(move |mut resp, info| {
+        let headers = config.headers(info.uri().path());
+        async move {
+            for (name, value) in headers {
+                match value.parse() {
+                    Ok(value) => {
+                        resp.headers_mut().insert(name, value);
+                    }
+                    Err(_) => tracing::warn!(header = name, "invalid security header value"),
+                }
+            }
+
+            Ok(resp)
+        }
+    })
+}
+
 pub fn routes(global: &Arc<GlobalState>) -> Router<Body, RouteError> {
     let weak = Arc::downgrade(global);
     Router::builder()
@@ -82,6 +102,7 @@ pub fn routes(global: &Arc<GlobalState>) -> Router<Body, RouteError> {
         // Our error handler
         .err_handler_with_info(error_handler)
         .middleware(cors_middleware(global))
+        .middleware(security_headers_middleware(global))
         .scope("/", stream::routes(global))
         .build()
         .expect("failed to build router")
@@ -350,6 +350,7 @@ impl TestState {
             record,
             transcode,
             state: None,
+            region: String::new(),
         }))
         .await;
         stream_id
@@ -1702,6 +1703,7 @@ async fn test_ingest_stream_try_resume_success() {
             record: false,
             transcode: false,
             state: Some(stream_state.clone()),
+            region: String::new(),
         }))
         .await;
 
@@ -1911,6 +1913,7 @@ async fn test_ingest_stream_try_resume_failed() {
                     priority: 1,
                 }],
             }),
+            region: String::new(),
         }))
         .await;
 
@@ -2091,6 +2094,7 @@ async fn test_ingest_stream_transcoder_full_tls(tls_dir: PathBuf) {
                 record: false,
                 transcode: true,
                 state: None,
+                region: String::new(),
             }))
             .unwrap();
         }